@@ -357,6 +357,8 @@ mod tests {
             }),
             EventMsg::TurnAborted(TurnAbortedEvent {
                 reason: TurnAbortReason::Replaced,
+                message_count: 0,
+                tool_call_count: 0,
             }),
             EventMsg::UserMessage(UserMessageEvent {
                 message: "Let's try again".into(),
@@ -6,6 +6,7 @@ use codex_protocol::config_types::ForcedLoginMethod;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
 use codex_protocol::config_types::Verbosity;
+use codex_protocol::event_filter::EventFilter;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::parse_command::ParsedCommand;
@@ -412,6 +413,10 @@ pub struct AddConversationListenerParams {
     pub conversation_id: ConversationId,
     #[serde(default)]
     pub experimental_raw_events: bool,
+    /// Only notify the events this filter accepts; others are never
+    /// serialized. All events when omitted.
+    #[serde(default)]
+    pub event_filter: Option<EventFilter>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
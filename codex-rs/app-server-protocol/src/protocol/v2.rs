@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use crate::protocol::common::AuthMode;
 use codex_protocol::account::PlanType;
 use codex_protocol::approvals::ExecPolicyAmendment as CoreExecPolicyAmendment;
+use codex_protocol::approvals::ProjectApprovalRule as CoreProjectApprovalRule;
 use codex_protocol::config_types::ForcedLoginMethod;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode as CoreSandboxMode;
@@ -102,6 +103,9 @@ pub enum CodexErrorInfo {
         #[ts(rename = "httpStatusCode")]
         http_status_code: Option<u16>,
     },
+    /// The final answer did not match the turn's output schema after every
+    /// repair attempt.
+    StructuredOutputInvalid,
     Other,
 }
 
@@ -126,6 +130,7 @@ impl From<CoreCodexErrorInfo> for CodexErrorInfo {
             CoreCodexErrorInfo::ResponseTooManyFailedAttempts { http_status_code } => {
                 CodexErrorInfo::ResponseTooManyFailedAttempts { http_status_code }
             }
+            CoreCodexErrorInfo::StructuredOutputInvalid => CodexErrorInfo::StructuredOutputInvalid,
             CoreCodexErrorInfo::Other => CodexErrorInfo::Other,
         }
     }
@@ -482,6 +487,11 @@ pub enum ApprovalDecision {
     AcceptWithExecpolicyAmendment {
         execpolicy_amendment: ExecPolicyAmendment,
     },
+    /// Approve and keep approving commands matching `rule` in this project,
+    /// across sessions.
+    AcceptForProject {
+        rule: ProjectApprovalRule,
+    },
     Decline,
     Cancel,
 }
@@ -603,6 +613,36 @@ impl From<CoreExecPolicyAmendment> for ExecPolicyAmendment {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(tag = "type")]
+#[ts(export_to = "v2/")]
+pub enum ProjectApprovalRule {
+    Exact { command: Vec<String> },
+    Prefix { prefix: Vec<String> },
+    Glob { pattern: String },
+}
+
+impl ProjectApprovalRule {
+    pub fn into_core(self) -> CoreProjectApprovalRule {
+        match self {
+            ProjectApprovalRule::Exact { command } => CoreProjectApprovalRule::Exact { command },
+            ProjectApprovalRule::Prefix { prefix } => CoreProjectApprovalRule::Prefix { prefix },
+            ProjectApprovalRule::Glob { pattern } => CoreProjectApprovalRule::Glob { pattern },
+        }
+    }
+}
+
+impl From<CoreProjectApprovalRule> for ProjectApprovalRule {
+    fn from(value: CoreProjectApprovalRule) -> Self {
+        match value {
+            CoreProjectApprovalRule::Exact { command } => ProjectApprovalRule::Exact { command },
+            CoreProjectApprovalRule::Prefix { prefix } => ProjectApprovalRule::Prefix { prefix },
+            CoreProjectApprovalRule::Glob { pattern } => ProjectApprovalRule::Glob { pattern },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "camelCase")]
 #[ts(tag = "type")]
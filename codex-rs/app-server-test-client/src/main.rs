@@ -371,6 +371,7 @@ impl CodexClient {
             params: AddConversationListenerParams {
                 conversation_id: *conversation_id,
                 experimental_raw_events: false,
+                event_filter: None,
             },
         };
 
@@ -90,6 +90,7 @@ pub(crate) async fn apply_bespoke_event_handling(
     let Event {
         id: event_turn_id,
         msg,
+        ..
     } = event;
     match msg {
         EventMsg::TaskComplete(_ev) => {
@@ -1108,9 +1109,8 @@ async fn on_file_change_request_approval_response(
             let (decision, completion_status) = match response.decision {
                 ApprovalDecision::Accept
                 | ApprovalDecision::AcceptForSession
-                | ApprovalDecision::AcceptWithExecpolicyAmendment { .. } => {
-                    (ReviewDecision::Approved, None)
-                }
+                | ApprovalDecision::AcceptWithExecpolicyAmendment { .. }
+                | ApprovalDecision::AcceptForProject { .. } => (ReviewDecision::Approved, None),
                 ApprovalDecision::Decline => {
                     (ReviewDecision::Denied, Some(PatchApplyStatus::Declined))
                 }
@@ -1188,6 +1188,12 @@ async fn on_command_execution_request_approval_response(
                     },
                     None,
                 ),
+                ApprovalDecision::AcceptForProject { rule } => (
+                    ReviewDecision::ApprovedForProject {
+                        rule: rule.into_core(),
+                    },
+                    None,
+                ),
                 ApprovalDecision::Decline => (
                     ReviewDecision::Denied,
                     Some(CommandExecutionStatus::Declined),
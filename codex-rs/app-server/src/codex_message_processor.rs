@@ -113,6 +113,7 @@ use codex_core::Cursor as RolloutCursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::InitialHistory;
 use codex_core::NewConversation;
+use codex_core::RemovedConversation;
 use codex_core::RolloutRecorder;
 use codex_core::SessionMeta;
 use codex_core::auth::CLIENT_ID;
@@ -131,6 +132,7 @@ use codex_core::git_info::git_diff_to_remote;
 use codex_core::mcp::collect_mcp_snapshot;
 use codex_core::mcp::group_tools_by_server;
 use codex_core::parse_cursor;
+use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDelivery as CoreReviewDelivery;
@@ -145,6 +147,7 @@ use codex_login::ShutdownHandle;
 use codex_login::run_login_server;
 use codex_protocol::ConversationId;
 use codex_protocol::config_types::ForcedLoginMethod;
+use codex_protocol::event_filter::EventFilter;
 use codex_protocol::items::TurnItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::GitInfo as CoreGitInfo;
@@ -1397,6 +1400,7 @@ impl CodexMessageProcessor {
                     .attach_conversation_listener(
                         conversation_id,
                         params.experimental_raw_events,
+                        None,
                         ApiVersion::V2,
                     )
                     .await
@@ -1682,7 +1686,7 @@ impl CodexMessageProcessor {
                 } = session_configured;
                 // Auto-attach a conversation listener when resuming a thread.
                 if let Err(err) = self
-                    .attach_conversation_listener(conversation_id, false, ApiVersion::V2)
+                    .attach_conversation_listener(conversation_id, false, None, ApiVersion::V2)
                     .await
                 {
                     tracing::warn!(
@@ -2473,7 +2477,7 @@ impl CodexMessageProcessor {
         }
 
         // If the conversation is active, request shutdown and wait briefly.
-        if let Some(conversation) = self
+        if let Some(RemovedConversation { conversation, .. }) = self
             .conversation_manager
             .remove_conversation(&conversation_id)
             .await
@@ -2528,13 +2532,19 @@ impl CodexMessageProcessor {
             }
         }
 
-        // Move the rollout file to archived.
+        // Move the rollout file, and any parts it continues in, to archived.
         let result: std::io::Result<()> = async {
             let archive_folder = self
                 .config
                 .codex_home
                 .join(codex_core::ARCHIVED_SESSIONS_SUBDIR);
             tokio::fs::create_dir_all(&archive_folder).await?;
+            let parts = codex_core::rollout_parts(&canonical_rollout_path).await?;
+            for part in parts.iter().skip(1) {
+                if let Some(part_name) = part.file_name() {
+                    tokio::fs::rename(part, archive_folder.join(part_name)).await?;
+                }
+            }
             tokio::fs::rename(&canonical_rollout_path, &archive_folder.join(&file_name)).await?;
             Ok(())
         }
@@ -2579,6 +2589,7 @@ impl CodexMessageProcessor {
         let _ = conversation
             .submit(Op::UserInput {
                 items: mapped_items,
+                allow_sensitive: false,
             })
             .await;
 
@@ -2633,6 +2644,9 @@ impl CodexMessageProcessor {
                 effort,
                 summary,
                 final_output_json_schema: None,
+                allow_sensitive: false,
+                bypass_read_cache: false,
+                deadline: None,
             })
             .await;
 
@@ -2733,6 +2747,9 @@ impl CodexMessageProcessor {
                     model: params.model,
                     effort: params.effort.map(Some),
                     summary: params.summary,
+                    execution_mode: None,
+                    sampling: None,
+                    offline: None,
                 })
                 .await;
         }
@@ -2741,6 +2758,7 @@ impl CodexMessageProcessor {
         let turn_id = conversation
             .submit(Op::UserInput {
                 items: mapped_items,
+                allow_sensitive: false,
             })
             .await;
 
@@ -2859,7 +2877,9 @@ impl CodexMessageProcessor {
         review_request: ReviewRequest,
         display_text: &str,
     ) -> std::result::Result<(), JSONRPCErrorError> {
-        let rollout_path = find_conversation_path_by_id_str(
+        // The review runs in a conversation of its own with a blank history;
+        // the parent only has to exist.
+        find_conversation_path_by_id_str(
             &self.config.codex_home,
             &parent_conversation_id.to_string(),
         )
@@ -2885,7 +2905,7 @@ impl CodexMessageProcessor {
             ..
         } = self
             .conversation_manager
-            .fork_conversation(usize::MAX, config, rollout_path)
+            .new_conversation(config)
             .await
             .map_err(|err| JSONRPCErrorError {
                 code: INTERNAL_ERROR_CODE,
@@ -2894,7 +2914,7 @@ impl CodexMessageProcessor {
             })?;
 
         if let Err(err) = self
-            .attach_conversation_listener(conversation_id, false, ApiVersion::V2)
+            .attach_conversation_listener(conversation_id, false, None, ApiVersion::V2)
             .await
         {
             tracing::warn!(
@@ -3027,9 +3047,15 @@ impl CodexMessageProcessor {
         let AddConversationListenerParams {
             conversation_id,
             experimental_raw_events,
+            event_filter,
         } = params;
         match self
-            .attach_conversation_listener(conversation_id, experimental_raw_events, ApiVersion::V1)
+            .attach_conversation_listener(
+                conversation_id,
+                experimental_raw_events,
+                event_filter,
+                ApiVersion::V1,
+            )
             .await
         {
             Ok(subscription_id) => {
@@ -3070,6 +3096,7 @@ impl CodexMessageProcessor {
         &mut self,
         conversation_id: ConversationId,
         experimental_raw_events: bool,
+        event_filter: Option<EventFilter>,
         api_version: ApiVersion,
     ) -> Result<Uuid, JSONRPCErrorError> {
         let conversation = match self
@@ -3117,33 +3144,15 @@ impl CodexMessageProcessor {
                                 continue;
                             }
 
-                        // For now, we send a notification for every event,
-                        // JSON-serializing the `Event` as-is, but these should
-                        // be migrated to be variants of `ServerNotification`
-                        // instead.
-                        let method = format!("codex/event/{}", event.msg);
-                        let mut params = match serde_json::to_value(event.clone()) {
-                            Ok(serde_json::Value::Object(map)) => map,
-                            Ok(_) => {
-                                error!("event did not serialize to an object");
-                                continue;
-                            }
-                            Err(err) => {
-                                error!("failed to serialize event: {err}");
-                                continue;
-                            }
-                        };
-                        params.insert(
-                            "conversationId".to_string(),
-                            conversation_id.to_string().into(),
-                        );
-
-                        outgoing_for_task
-                            .send_notification(OutgoingNotification {
-                                method,
-                                params: Some(params.into()),
-                            })
-                            .await;
+                        // Filtered out events skip serialization but still
+                        // get their bespoke handling, e.g. approvals.
+                        if event_filter
+                            .as_ref()
+                            .is_none_or(|filter| filter.accepts(&event.msg))
+                        {
+                            send_event_notification(&outgoing_for_task, conversation_id, &event)
+                                .await;
+                        }
 
                         apply_bespoke_event_handling(
                             event.clone(),
@@ -3313,6 +3322,39 @@ impl CodexMessageProcessor {
     }
 }
 
+/// Notify the client of `event`. For now, we send a notification for every
+/// event, JSON-serializing the `Event` as-is, but these should be migrated to
+/// be variants of `ServerNotification` instead.
+async fn send_event_notification(
+    outgoing: &OutgoingMessageSender,
+    conversation_id: ConversationId,
+    event: &Event,
+) {
+    let method = format!("codex/event/{}", event.msg);
+    let mut params = match serde_json::to_value(event) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => {
+            error!("event did not serialize to an object");
+            return;
+        }
+        Err(err) => {
+            error!("failed to serialize event: {err}");
+            return;
+        }
+    };
+    params.insert(
+        "conversationId".to_string(),
+        conversation_id.to_string().into(),
+    );
+
+    outgoing
+        .send_notification(OutgoingNotification {
+            method,
+            params: Some(params.into()),
+        })
+        .await;
+}
+
 fn skills_to_info(
     skills: &[codex_core::skills::SkillMetadata],
 ) -> Vec<codex_app_server_protocol::SkillMetadata> {
@@ -3598,6 +3640,7 @@ mod tests {
                 meta: session_meta.clone(),
                 git: None,
             }),
+            correlation_id: None,
         };
 
         fs::write(&path, format!("{}\n", serde_json::to_string(&line)?))?;
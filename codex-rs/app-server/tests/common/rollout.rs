@@ -49,6 +49,12 @@ pub fn create_fake_rollout(
         instructions: None,
         source: SessionSource::Cli,
         model_provider: model_provider.map(str::to_string),
+        session_profile: None,
+        skills: None,
+        model_list_source: None,
+        tool_capabilities: None,
+        project_notes: None,
+        parent: None,
     };
     let payload = serde_json::to_value(SessionMetaLine {
         meta,
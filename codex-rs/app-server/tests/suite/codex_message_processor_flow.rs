@@ -98,6 +98,7 @@ async fn test_codex_jsonrpc_conversation_flow() -> Result<()> {
         .send_add_conversation_listener_request(AddConversationListenerParams {
             conversation_id,
             experimental_raw_events: false,
+            event_filter: None,
         })
         .await?;
     let add_listener_resp: JSONRPCResponse = timeout(
@@ -227,6 +228,7 @@ async fn test_send_user_turn_changes_approval_policy_behavior() -> Result<()> {
         .send_add_conversation_listener_request(AddConversationListenerParams {
             conversation_id,
             experimental_raw_events: false,
+            event_filter: None,
         })
         .await?;
     let _: AddConversationSubscriptionResponse = to_response::<AddConversationSubscriptionResponse>(
@@ -393,6 +395,7 @@ async fn test_send_user_turn_updates_sandbox_and_cwd_between_turns() -> Result<(
         .send_add_conversation_listener_request(AddConversationListenerParams {
             conversation_id,
             experimental_raw_events: false,
+            event_filter: None,
         })
         .await?;
     timeout(
@@ -91,6 +91,7 @@ async fn shell_command_interruption() -> anyhow::Result<()> {
         .send_add_conversation_listener_request(AddConversationListenerParams {
             conversation_id,
             experimental_raw_events: false,
+            event_filter: None,
         })
         .await?;
     let _add_listener_resp: JSONRPCResponse = timeout(
@@ -393,6 +393,8 @@ async fn thread_list_includes_git_info() -> Result<()> {
         commit_hash: Some("abc123".to_string()),
         branch: Some("main".to_string()),
         repository_url: Some("https://example.com/repo.git".to_string()),
+        root: None,
+        dirty: None,
     };
     let conversation_id = create_fake_rollout(
         codex_home.path(),
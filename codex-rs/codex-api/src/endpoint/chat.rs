@@ -62,6 +62,7 @@ impl<T: HttpTransport, A: AuthProvider> ChatClient<T, A> {
             ChatRequestBuilder::new(model, &prompt.instructions, &prompt.input, &prompt.tools)
                 .conversation_id(conversation_id)
                 .session_source(session_source)
+                .sampling(prompt.sampling)
                 .build(self.streaming.provider())?;
 
         self.stream_request(request).await
@@ -87,6 +87,7 @@ impl<T: HttpTransport, A: AuthProvider> ResponsesClient<T, A> {
             .conversation(conversation_id)
             .session_source(session_source)
             .store_override(store_override)
+            .sampling(prompt.sampling)
             .extra_headers(extra_headers)
             .build(self.streaming.provider())?;
 
@@ -3,6 +3,7 @@ use crate::provider::Provider;
 use crate::requests::headers::build_conversation_headers;
 use crate::requests::headers::insert_header;
 use crate::requests::headers::subagent_header;
+use codex_protocol::config_types::SamplingParams;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::ReasoningItemContent;
@@ -26,6 +27,7 @@ pub struct ChatRequestBuilder<'a> {
     tools: &'a [Value],
     conversation_id: Option<String>,
     session_source: Option<SessionSource>,
+    sampling: SamplingParams,
 }
 
 impl<'a> ChatRequestBuilder<'a> {
@@ -42,6 +44,7 @@ impl<'a> ChatRequestBuilder<'a> {
             tools,
             conversation_id: None,
             session_source: None,
+            sampling: SamplingParams::default(),
         }
     }
 
@@ -55,6 +58,11 @@ impl<'a> ChatRequestBuilder<'a> {
         self
     }
 
+    pub fn sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     pub fn build(self, _provider: &Provider) -> Result<ChatRequest, ApiError> {
         let mut messages = Vec::<Value>::new();
         messages.push(json!({"role": "system", "content": self.instructions}));
@@ -309,12 +317,28 @@ impl<'a> ChatRequestBuilder<'a> {
             }
         }
 
-        let payload = json!({
+        let mut payload = json!({
             "model": self.model,
             "messages": messages,
             "stream": true,
             "tools": self.tools,
         });
+        if let Some(obj) = payload.as_object_mut() {
+            let SamplingParams {
+                temperature,
+                top_p,
+                seed,
+            } = self.sampling;
+            if let Some(temperature) = temperature {
+                obj.insert("temperature".to_string(), json!(temperature));
+            }
+            if let Some(top_p) = top_p {
+                obj.insert("top_p".to_string(), json!(top_p));
+            }
+            if let Some(seed) = seed {
+                obj.insert("seed".to_string(), json!(seed));
+            }
+        }
 
         let mut headers = build_conversation_headers(self.conversation_id);
         if let Some(subagent) = subagent_header(&self.session_source) {
@@ -385,4 +409,20 @@ mod tests {
             Some(&HeaderValue::from_static("review"))
         );
     }
+
+    #[test]
+    fn sends_only_the_sampling_parameters_that_are_set() {
+        let req = ChatRequestBuilder::new("gpt-test", "inst", &[], &[])
+            .sampling(SamplingParams {
+                temperature: Some(0.5),
+                top_p: None,
+                seed: Some(7),
+            })
+            .build(&provider())
+            .expect("request");
+
+        assert_eq!(req.body.get("temperature"), Some(&json!(0.5)));
+        assert_eq!(req.body.get("top_p"), None);
+        assert_eq!(req.body.get("seed"), Some(&json!(7)));
+    }
 }
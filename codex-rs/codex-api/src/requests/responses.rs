@@ -6,6 +6,7 @@ use crate::provider::Provider;
 use crate::requests::headers::build_conversation_headers;
 use crate::requests::headers::insert_header;
 use crate::requests::headers::subagent_header;
+use codex_protocol::config_types::SamplingParams;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::SessionSource;
 use http::HeaderMap;
@@ -31,6 +32,7 @@ pub struct ResponsesRequestBuilder<'a> {
     conversation_id: Option<String>,
     session_source: Option<SessionSource>,
     store_override: Option<bool>,
+    sampling: SamplingParams,
     headers: HeaderMap,
 }
 
@@ -89,6 +91,12 @@ impl<'a> ResponsesRequestBuilder<'a> {
         self
     }
 
+    /// The Responses API has no `seed`; it is not sent.
+    pub fn sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     pub fn extra_headers(mut self, headers: HeaderMap) -> Self {
         self.headers = headers;
         self
@@ -123,6 +131,8 @@ impl<'a> ResponsesRequestBuilder<'a> {
             include: self.include,
             prompt_cache_key: self.prompt_cache_key,
             text: self.text,
+            temperature: self.sampling.temperature,
+            top_p: self.sampling.top_p,
         };
 
         let mut body = serde_json::to_value(&req)
@@ -0,0 +1,524 @@
+//! Raise the reasoning effort when the model flounders, and lower it again
+//! once it doesn't.
+//!
+//! With an `[adaptive_effort]` table in `config.toml`, every user turn that
+//! asked the model is checked for signs of floundering by an
+//! [`EffortSignalDetector`]; [`HeuristicDetector`] unless an embedder set
+//! [`AdaptiveEffortPolicy::detector`]. Each signal scores one point, and a
+//! turn without signals clears the score. Once the score reaches
+//! `threshold`, the turns that follow run one effort step higher, up to
+//! `max_effort`, and [`EventMsg::EffortEscalated`] is sent and recorded.
+//! After `deescalate_after` clean turns in a row the effort goes back down
+//! one step, reported as [`EventMsg::EffortDeescalated`], until it is the
+//! effort the session is configured with.
+//!
+//! Changing the effort, such as with `Op::OverrideTurnContext`, drops the
+//! adaptation. A resumed session starts at its configured effort.
+//!
+//! [`EventMsg::EffortEscalated`]: crate::protocol::EventMsg::EffortEscalated
+//! [`EventMsg::EffortDeescalated`]: crate::protocol::EventMsg::EffortDeescalated
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::EffortDeescalatedEvent;
+use codex_protocol::protocol::EffortEscalatedEvent;
+use codex_protocol::protocol::EventMsg;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::classify::ItemClass;
+use crate::classify::classify_item;
+use crate::stream_events_utils::last_assistant_message_from_item;
+
+/// Identical tool calls within a turn that count as a retry loop.
+const RETRY_LOOP_CALLS: usize = 3;
+
+/// Shortest user message whose restating counts; shorter prompts are
+/// repeated for other reasons.
+const MIN_RESTATED_CHARS: usize = 24;
+
+/// Phrases of an assistant message that admit it is stuck, lowercase.
+const UNCERTAINTY_MARKERS: &[&str] = &[
+    "i'm not sure",
+    "i am not sure",
+    "i'm not certain",
+    "i am not certain",
+    "i'm stuck",
+    "i am stuck",
+    "i don't know why",
+    "unclear why",
+];
+
+/// When and how far the reasoning effort adapts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AdaptiveEffortPolicy {
+    /// Signals, over consecutive turns, that raise the effort one step.
+    pub threshold: u32,
+
+    /// The highest effort to raise it to.
+    pub max_effort: ReasoningEffort,
+
+    /// Clean turns in a row that lower a raised effort one step.
+    pub deescalate_after: u32,
+
+    /// Detector used instead of [`HeuristicDetector`]. Only settable
+    /// programmatically.
+    #[serde(skip)]
+    pub detector: Option<CustomDetector>,
+}
+
+impl AdaptiveEffortPolicy {
+    /// The signals of `turn`, from the configured detector.
+    pub(crate) fn signals(&self, turn: &TurnObservation<'_>) -> Vec<EffortSignal> {
+        match &self.detector {
+            Some(CustomDetector(detector)) => detector.detect(turn),
+            None => HeuristicDetector.detect(turn),
+        }
+    }
+}
+
+impl Default for AdaptiveEffortPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: 2,
+            max_effort: ReasoningEffort::High,
+            deescalate_after: 3,
+            detector: None,
+        }
+    }
+}
+
+/// A sign that the model is floundering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EffortSignal {
+    /// The turn made the same call to `tool` again and again.
+    ToolRetryLoop { tool: String },
+    /// An assistant message repeated the user's message.
+    RestatedProblem,
+    /// An assistant message said the model is unsure or stuck.
+    Uncertainty,
+    /// The turn ended with an error instead of an answer.
+    TurnFailed,
+    /// A signal of a custom detector, reported as given.
+    Other(String),
+}
+
+impl fmt::Display for EffortSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffortSignal::ToolRetryLoop { tool } => write!(f, "repeated `{tool}` calls"),
+            EffortSignal::RestatedProblem => f.write_str("restated the problem"),
+            EffortSignal::Uncertainty => f.write_str("said it is unsure"),
+            EffortSignal::TurnFailed => f.write_str("turn failed"),
+            EffortSignal::Other(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// What a finished turn recorded, as a detector sees it.
+#[derive(Debug, Clone, Copy)]
+pub struct TurnObservation<'a> {
+    /// The items the turn recorded, its user message first.
+    pub items: &'a [ResponseItem],
+    /// Why the turn ended without an answer, if it did.
+    pub error: Option<&'a str>,
+}
+
+/// Finds the signs of floundering in a turn.
+pub trait EffortSignalDetector: Send + Sync {
+    fn detect(&self, turn: &TurnObservation<'_>) -> Vec<EffortSignal>;
+}
+
+/// A detector given through [`AdaptiveEffortPolicy::detector`].
+#[derive(Clone)]
+pub struct CustomDetector(pub Arc<dyn EffortSignalDetector>);
+
+impl fmt::Debug for CustomDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomDetector(..)")
+    }
+}
+
+impl PartialEq for CustomDetector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The default detector: a tool called [`RETRY_LOOP_CALLS`] times with the
+/// same arguments, an assistant message containing the user's message, an
+/// assistant message admitting it is unsure, and a failed turn.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicDetector;
+
+impl EffortSignalDetector for HeuristicDetector {
+    fn detect(&self, turn: &TurnObservation<'_>) -> Vec<EffortSignal> {
+        let mut signals = Vec::new();
+        let mut calls: HashMap<(&str, &str), usize> = HashMap::new();
+        let mut prompts = Vec::new();
+        let mut restated = false;
+        let mut uncertain = false;
+        for item in turn.items {
+            let call = match item {
+                ResponseItem::FunctionCall {
+                    name, arguments, ..
+                } => Some((name.as_str(), arguments.as_str())),
+                ResponseItem::CustomToolCall { name, input, .. } => {
+                    Some((name.as_str(), input.as_str()))
+                }
+                _ => None,
+            };
+            if let Some(call) = call {
+                let count = calls.entry(call).or_default();
+                *count += 1;
+                if *count == RETRY_LOOP_CALLS {
+                    signals.push(EffortSignal::ToolRetryLoop {
+                        tool: call.0.to_string(),
+                    });
+                }
+                continue;
+            }
+            if classify_item(item) == ItemClass::UserMessage {
+                prompts.extend(user_text(item).filter(|text| text.len() >= MIN_RESTATED_CHARS));
+            } else if let Some(text) = last_assistant_message_from_item(item) {
+                let text = normalize(&text);
+                restated |= prompts.iter().any(|prompt| text.contains(prompt.as_str()));
+                uncertain |= UNCERTAINTY_MARKERS
+                    .iter()
+                    .any(|marker| text.contains(marker));
+            }
+        }
+        if restated {
+            signals.push(EffortSignal::RestatedProblem);
+        }
+        if uncertain {
+            signals.push(EffortSignal::Uncertainty);
+        }
+        if turn.error.is_some() {
+            signals.push(EffortSignal::TurnFailed);
+        }
+        signals
+    }
+}
+
+fn user_text(item: &ResponseItem) -> impl Iterator<Item = String> + '_ {
+    let content: &[ContentItem] = match item {
+        ResponseItem::Message { content, .. } => content.as_slice(),
+        _ => &[],
+    };
+    content.iter().filter_map(|content| match content {
+        ContentItem::InputText { text } => Some(normalize(text)),
+        _ => None,
+    })
+}
+
+/// Lowercase with runs of whitespace collapsed and typographic apostrophes
+/// made plain, so wording compares regardless of layout.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .replace('\u{2019}', "'")
+}
+
+/// Every effort, lowest first.
+const EFFORTS: [ReasoningEffort; 6] = [
+    ReasoningEffort::None,
+    ReasoningEffort::Minimal,
+    ReasoningEffort::Low,
+    ReasoningEffort::Medium,
+    ReasoningEffort::High,
+    ReasoningEffort::XHigh,
+];
+
+fn rank(effort: ReasoningEffort) -> usize {
+    EFFORTS
+        .iter()
+        .position(|step| *step == effort)
+        .unwrap_or_default()
+}
+
+/// The effort one step above `effort`, if it is below `max`.
+fn step_up(effort: ReasoningEffort, max: ReasoningEffort) -> Option<ReasoningEffort> {
+    if rank(effort) >= rank(max) {
+        return None;
+    }
+    EFFORTS.get(rank(effort) + 1).copied()
+}
+
+fn step_down(effort: ReasoningEffort) -> Option<ReasoningEffort> {
+    rank(effort)
+        .checked_sub(1)
+        .and_then(|rank| EFFORTS.get(rank).copied())
+}
+
+/// A change of the effort of the turns that follow.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum EffortChange {
+    Escalated(EffortEscalatedEvent),
+    Deescalated(EffortDeescalatedEvent),
+}
+
+impl From<EffortChange> for EventMsg {
+    fn from(change: EffortChange) -> Self {
+        match change {
+            EffortChange::Escalated(event) => EventMsg::EffortEscalated(event),
+            EffortChange::Deescalated(event) => EventMsg::EffortDeescalated(event),
+        }
+    }
+}
+
+/// Where the adaptation of a session stands.
+#[derive(Debug, Default)]
+pub(crate) struct EffortAdapter {
+    /// The effort the session is configured with, while it is overridden.
+    base: Option<ReasoningEffort>,
+    /// The effort turns run with instead of the configured one.
+    current: Option<ReasoningEffort>,
+    score: u32,
+    clean_turns: u32,
+}
+
+impl EffortAdapter {
+    /// The effort the next turn should run with, when it is adapted.
+    pub(crate) fn effort_override(&self) -> Option<ReasoningEffort> {
+        self.current
+    }
+
+    /// Forget the adaptation, as when the effort is set explicitly.
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Account for a finished turn that ran with `effort` and showed
+    /// `signals`, returning how the effort of the next turn changes, if it
+    /// does.
+    pub(crate) fn observe(
+        &mut self,
+        policy: &AdaptiveEffortPolicy,
+        effort: ReasoningEffort,
+        signals: &[EffortSignal],
+    ) -> Option<EffortChange> {
+        if signals.is_empty() {
+            self.score = 0;
+            self.clean_turns = self.clean_turns.saturating_add(1);
+            return self.deescalate(policy, effort);
+        }
+        self.clean_turns = 0;
+        self.score = self
+            .score
+            .saturating_add(u32::try_from(signals.len()).unwrap_or(u32::MAX));
+        if self.score < policy.threshold {
+            return None;
+        }
+        let to = step_up(effort, policy.max_effort)?;
+        self.score = 0;
+        self.base.get_or_insert(effort);
+        self.current = Some(to);
+        let reason = signals
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(EffortChange::Escalated(EffortEscalatedEvent {
+            from: effort,
+            to,
+            reason,
+        }))
+    }
+
+    fn deescalate(
+        &mut self,
+        policy: &AdaptiveEffortPolicy,
+        effort: ReasoningEffort,
+    ) -> Option<EffortChange> {
+        let base = self.base?;
+        if self.clean_turns < policy.deescalate_after.max(1) {
+            return None;
+        }
+        self.clean_turns = 0;
+        let to = step_down(effort).filter(|to| rank(*to) > rank(base));
+        self.current = to;
+        if to.is_none() {
+            self.base = None;
+        }
+        let to = to.unwrap_or(base);
+        if to == effort {
+            return None;
+        }
+        Some(EffortChange::Deescalated(EffortDeescalatedEvent {
+            from: effort,
+            to,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        let text = text.to_string();
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![if role == "assistant" {
+                ContentItem::OutputText { text }
+            } else {
+                ContentItem::InputText { text }
+            }],
+        }
+    }
+
+    fn call(call_id: &str, command: &str) -> [ResponseItem; 2] {
+        [
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: format!("{{\"command\":\"{command}\"}}"),
+                call_id: call_id.to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload::default(),
+            },
+        ]
+    }
+
+    fn detect(items: &[ResponseItem], error: Option<&str>) -> Vec<EffortSignal> {
+        HeuristicDetector.detect(&TurnObservation { items, error })
+    }
+
+    #[test]
+    fn heuristics_find_each_signal() {
+        let prompt = "Why does the parser reject nested tables?";
+        let mut items = vec![message("user", prompt)];
+        for n in 0..RETRY_LOOP_CALLS {
+            items.extend(call(&format!("call-{n}"), "cargo test"));
+        }
+        items.extend(call("call-other", "ls"));
+        items.push(message(
+            "assistant",
+            "So: why does the parser  reject nested tables? I\u{2019}m not sure yet.",
+        ));
+
+        assert_eq!(
+            detect(&items, Some("stream disconnected")),
+            vec![
+                EffortSignal::ToolRetryLoop {
+                    tool: "shell".to_string()
+                },
+                EffortSignal::RestatedProblem,
+                EffortSignal::Uncertainty,
+                EffortSignal::TurnFailed,
+            ]
+        );
+        assert_eq!(
+            detect(
+                &[message("user", "hi"), message("assistant", "hi, what now?")],
+                None
+            ),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn steps_stay_within_the_cap() {
+        assert_eq!(
+            step_up(ReasoningEffort::Low, ReasoningEffort::High),
+            Some(ReasoningEffort::Medium)
+        );
+        assert_eq!(step_up(ReasoningEffort::High, ReasoningEffort::High), None);
+        assert_eq!(step_up(ReasoningEffort::XHigh, ReasoningEffort::High), None);
+        assert_eq!(
+            step_down(ReasoningEffort::Medium),
+            Some(ReasoningEffort::Low)
+        );
+        assert_eq!(step_down(ReasoningEffort::None), None);
+    }
+
+    #[test]
+    fn escalates_at_the_threshold_and_settles_after_clean_turns() {
+        let policy = AdaptiveEffortPolicy {
+            threshold: 2,
+            max_effort: ReasoningEffort::High,
+            deescalate_after: 2,
+            detector: None,
+        };
+        let unsure = [EffortSignal::Uncertainty];
+        let clean = [];
+        let mut adapter = EffortAdapter::default();
+
+        assert_eq!(
+            adapter.observe(&policy, ReasoningEffort::Low, &unsure),
+            None
+        );
+        assert_eq!(
+            adapter.observe(&policy, ReasoningEffort::Low, &unsure),
+            Some(EffortChange::Escalated(EffortEscalatedEvent {
+                from: ReasoningEffort::Low,
+                to: ReasoningEffort::Medium,
+                reason: "said it is unsure".to_string(),
+            }))
+        );
+        assert_eq!(adapter.effort_override(), Some(ReasoningEffort::Medium));
+
+        assert_eq!(
+            adapter.observe(&policy, ReasoningEffort::Medium, &clean),
+            None
+        );
+        assert_eq!(
+            adapter.observe(&policy, ReasoningEffort::Medium, &clean),
+            Some(EffortChange::Deescalated(EffortDeescalatedEvent {
+                from: ReasoningEffort::Medium,
+                to: ReasoningEffort::Low,
+            }))
+        );
+        assert_eq!(adapter.effort_override(), None);
+    }
+
+    #[test]
+    fn custom_detectors_replace_the_heuristics() {
+        struct Always;
+        impl EffortSignalDetector for Always {
+            fn detect(&self, _turn: &TurnObservation<'_>) -> Vec<EffortSignal> {
+                vec![EffortSignal::Other("tests still fail".to_string())]
+            }
+        }
+        let policy = AdaptiveEffortPolicy {
+            threshold: 1,
+            detector: Some(CustomDetector(Arc::new(Always))),
+            ..AdaptiveEffortPolicy::default()
+        };
+        let turn = TurnObservation {
+            items: &[],
+            error: None,
+        };
+        let signals = policy.signals(&turn);
+        assert_eq!(
+            signals,
+            vec![EffortSignal::Other("tests still fail".to_string())]
+        );
+
+        let mut adapter = EffortAdapter::default();
+        let Some(EffortChange::Escalated(event)) =
+            adapter.observe(&policy, ReasoningEffort::Medium, &signals)
+        else {
+            panic!("expected an escalation to the cap");
+        };
+        assert_eq!(event.to, ReasoningEffort::High);
+        assert_eq!(
+            adapter.observe(&policy, ReasoningEffort::High, &signals),
+            None
+        );
+    }
+}
@@ -72,7 +72,8 @@ pub(crate) async fn apply_patch(
             match rx_approve.await.unwrap_or_default() {
                 ReviewDecision::Approved
                 | ReviewDecision::ApprovedExecpolicyAmendment { .. }
-                | ReviewDecision::ApprovedForSession => {
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedForProject { .. } => {
                     InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                         action,
                         user_explicitly_approved_this_action: true,
@@ -1,5 +1,6 @@
 mod storage;
 
+use chrono::DateTime;
 use chrono::Utc;
 use reqwest::StatusCode;
 use serde::Deserialize;
@@ -13,22 +14,37 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use std::time::Instant;
 
 use codex_app_server_protocol::AuthMode;
 use codex_protocol::config_types::ForcedLoginMethod;
+use codex_protocol::protocol::AuthRefreshFailedEvent;
+use codex_protocol::protocol::RetrySubsystem;
+use codex_protocol::protocol::RetryingEvent;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 
 pub use crate::auth::storage::AuthCredentialsStoreMode;
 pub use crate::auth::storage::AuthDotJson;
 use crate::auth::storage::AuthStorageBackend;
+pub use crate::auth::storage::CredentialMigrationError;
 use crate::auth::storage::create_auth_storage;
+use crate::auth::storage::migrate_credentials;
+use crate::codex_home::CodexHome;
 use crate::config::Config;
 use crate::error::RefreshTokenFailedError;
 use crate::error::RefreshTokenFailedReason;
+use crate::retry::RetryPolicy;
+use crate::retry::retry;
 use crate::token_data::KnownPlan as InternalKnownPlan;
 use crate::token_data::PlanType as InternalPlanType;
 use crate::token_data::TokenData;
 use crate::token_data::parse_id_token;
+use crate::token_data::parse_jwt_expiration;
 use crate::util::try_parse_error_message;
 use codex_client::CodexHttpClient;
 use codex_protocol::account::PlanType as AccountPlanType;
@@ -1052,6 +1068,56 @@ mod tests {
     }
 }
 
+/// Expiry of the access token an [`AuthManager`] currently hands out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStatus {
+    /// When the access token expires, if it says so. API keys do not expire.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the manager can refresh the token itself, which only ChatGPT
+    /// tokens allow.
+    pub refreshable: bool,
+}
+
+/// Sent to the subscribers of an [`AuthManager`].
+#[derive(Debug, Clone)]
+pub enum AuthNotification {
+    /// The manager hands out different auth, such as refreshed tokens.
+    Changed,
+    /// A refresh started by [`AuthManager::enable_auto_refresh`] failed.
+    RefreshFailed(AuthRefreshFailedEvent),
+}
+
+/// When [`AuthManager::enable_auto_refresh`] refreshes the ChatGPT tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoRefreshPolicy {
+    /// How long before the access token expires it is refreshed.
+    pub margin: Duration,
+    /// Delay before retrying a failed refresh, doubled for each further
+    /// failure.
+    pub retry_delay: Duration,
+    /// Longest delay between retries. Tokens that are still due after a
+    /// successful refresh are not refreshed again sooner than this either.
+    pub max_retry_delay: Duration,
+}
+
+impl Default for AutoRefreshPolicy {
+    fn default() -> Self {
+        Self {
+            margin: Duration::from_secs(5 * 60),
+            retry_delay: Duration::from_secs(5),
+            max_retry_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl AutoRefreshPolicy {
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_retry_delay)
+    }
+}
+
 /// Central manager providing a single source of truth for auth.json derived
 /// authentication data. It loads once (or on preference change) and then
 /// hands out cloned `CodexAuth` values so the rest of the program has a
@@ -1062,10 +1128,12 @@ mod tests {
 /// different parts of the program seeing inconsistent auth data mid‑run.
 #[derive(Debug)]
 pub struct AuthManager {
-    codex_home: PathBuf,
+    codex_home: CodexHome,
     inner: RwLock<CachedAuth>,
     enable_codex_api_key_env: bool,
-    auth_credentials_store_mode: AuthCredentialsStoreMode,
+    auth_credentials_store_mode: RwLock<AuthCredentialsStoreMode>,
+    notifications: broadcast::Sender<AuthNotification>,
+    auto_refresh_enabled: AtomicBool,
 }
 
 impl AuthManager {
@@ -1074,12 +1142,13 @@ impl AuthManager {
     /// simply return `None` in that case so callers can treat it as an
     /// unauthenticated state.
     pub fn new(
-        codex_home: PathBuf,
+        codex_home: impl Into<CodexHome>,
         enable_codex_api_key_env: bool,
         auth_credentials_store_mode: AuthCredentialsStoreMode,
     ) -> Self {
+        let codex_home = codex_home.into();
         let auth = load_auth(
-            &codex_home,
+            codex_home.root(),
             enable_codex_api_key_env,
             auth_credentials_store_mode,
         )
@@ -1089,7 +1158,9 @@ impl AuthManager {
             codex_home,
             inner: RwLock::new(CachedAuth { auth }),
             enable_codex_api_key_env,
-            auth_credentials_store_mode,
+            auth_credentials_store_mode: RwLock::new(auth_credentials_store_mode),
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            auto_refresh_enabled: AtomicBool::new(false),
         }
     }
 
@@ -1099,7 +1170,7 @@ impl AuthManager {
     pub fn from_auth_for_testing(auth: CodexAuth) -> Arc<Self> {
         let cached = CachedAuth { auth: Some(auth) };
         let temp_dir = tempfile::tempdir().expect("temp codex home");
-        let codex_home = temp_dir.path().to_path_buf();
+        let codex_home = CodexHome::new(temp_dir.path());
         TEST_AUTH_TEMP_DIRS
             .lock()
             .expect("lock test codex homes")
@@ -1108,7 +1179,9 @@ impl AuthManager {
             codex_home,
             inner: RwLock::new(cached),
             enable_codex_api_key_env: false,
-            auth_credentials_store_mode: AuthCredentialsStoreMode::File,
+            auth_credentials_store_mode: RwLock::new(AuthCredentialsStoreMode::File),
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            auto_refresh_enabled: AtomicBool::new(false),
         })
     }
 
@@ -1117,10 +1190,12 @@ impl AuthManager {
     pub fn from_auth_for_testing_with_home(auth: CodexAuth, codex_home: PathBuf) -> Arc<Self> {
         let cached = CachedAuth { auth: Some(auth) };
         Arc::new(Self {
-            codex_home,
+            codex_home: CodexHome::new(codex_home),
             inner: RwLock::new(cached),
             enable_codex_api_key_env: false,
-            auth_credentials_store_mode: AuthCredentialsStoreMode::File,
+            auth_credentials_store_mode: RwLock::new(AuthCredentialsStoreMode::File),
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            auto_refresh_enabled: AtomicBool::new(false),
         })
     }
 
@@ -1130,6 +1205,11 @@ impl AuthManager {
     }
 
     pub fn codex_home(&self) -> &Path {
+        self.codex_home.root()
+    }
+
+    /// The layout of the codex home this manager loads auth from.
+    pub fn home(&self) -> &CodexHome {
         &self.codex_home
     }
 
@@ -1137,19 +1217,23 @@ impl AuthManager {
     /// whether the auth value changed.
     pub fn reload(&self) -> bool {
         let new_auth = load_auth(
-            &self.codex_home,
+            self.codex_home.root(),
             self.enable_codex_api_key_env,
-            self.auth_credentials_store_mode,
+            self.auth_credentials_store_mode(),
         )
         .ok()
         .flatten();
-        if let Ok(mut guard) = self.inner.write() {
+        let changed = if let Ok(mut guard) = self.inner.write() {
             let changed = !AuthManager::auths_equal(&guard.auth, &new_auth);
             guard.auth = new_auth;
             changed
         } else {
             false
+        };
+        if changed {
+            self.notify(AuthNotification::Changed);
         }
+        changed
     }
 
     fn auths_equal(a: &Option<CodexAuth>, b: &Option<CodexAuth>) -> bool {
@@ -1160,9 +1244,19 @@ impl AuthManager {
         }
     }
 
+    /// A manager for the auth of `codex_home`, loaded the way this one loads
+    /// its own.
+    pub(crate) fn for_home(&self, codex_home: PathBuf) -> Arc<Self> {
+        Self::shared(
+            codex_home,
+            self.enable_codex_api_key_env,
+            self.auth_credentials_store_mode(),
+        )
+    }
+
     /// Convenience constructor returning an `Arc` wrapper.
     pub fn shared(
-        codex_home: PathBuf,
+        codex_home: impl Into<CodexHome>,
         enable_codex_api_key_env: bool,
         auth_credentials_store_mode: AuthCredentialsStoreMode,
     ) -> Arc<Self> {
@@ -1184,8 +1278,11 @@ impl AuthManager {
         };
         match auth.refresh_token().await {
             Ok(token) => {
-                // Reload to pick up persisted changes.
-                self.reload();
+                // Reload to pick up persisted changes. Refreshed tokens compare
+                // equal to the old ones, so announce the change here.
+                if !self.reload() {
+                    self.notify(AuthNotification::Changed);
+                }
                 Ok(Some(token))
             }
             Err(e) => {
@@ -1195,18 +1292,181 @@ impl AuthManager {
         }
     }
 
+    /// Like [`Self::refresh_token`], retrying transient failures under
+    /// `policy`. `on_retry` is told about each retry before its delay.
+    pub async fn refresh_token_with_retry(
+        &self,
+        policy: &RetryPolicy,
+        on_retry: impl FnMut(&RetryingEvent),
+    ) -> Result<Option<String>, RefreshTokenError> {
+        retry(policy, RetrySubsystem::AuthRefresh, on_retry, || {
+            self.refresh_token()
+        })
+        .await
+    }
+
     /// Log out by deleting the on‑disk auth.json (if present). Returns Ok(true)
     /// if a file was removed, Ok(false) if no auth file existed. On success,
     /// reloads the in‑memory auth cache so callers immediately observe the
     /// unauthenticated state.
     pub fn logout(&self) -> std::io::Result<bool> {
-        let removed = super::auth::logout(&self.codex_home, self.auth_credentials_store_mode)?;
+        let removed =
+            super::auth::logout(self.codex_home.root(), self.auth_credentials_store_mode())?;
         // Always reload to clear any cached auth (even if file absent).
         self.reload();
         Ok(removed)
     }
 
+    /// Where the credentials are stored.
+    pub fn auth_credentials_store_mode(&self) -> AuthCredentialsStoreMode {
+        self.auth_credentials_store_mode
+            .read()
+            .map(|mode| *mode)
+            .unwrap_or_default()
+    }
+
+    /// Move the stored credentials to the store used by `to`, scrubbing them
+    /// from the current one, and use that store from now on. Returns whether
+    /// there were credentials to move. On error the current store stays in
+    /// use; the credentials are never written to `auth.json` unless `to` is
+    /// [`AuthCredentialsStoreMode::File`].
+    pub fn migrate_credentials(
+        &self,
+        to: AuthCredentialsStoreMode,
+    ) -> Result<bool, CredentialMigrationError> {
+        let moved = migrate_credentials(
+            self.codex_home.root(),
+            self.auth_credentials_store_mode(),
+            to,
+        )?;
+        if let Ok(mut mode) = self.auth_credentials_store_mode.write() {
+            *mode = to;
+        }
+        self.reload();
+        Ok(moved)
+    }
+
     pub fn get_auth_mode(&self) -> Option<AuthMode> {
         self.auth().map(|a| a.mode)
     }
+
+    /// Expiry of the current access token.
+    pub fn token_status(&self) -> TokenStatus {
+        let tokens = self
+            .auth()
+            .filter(|auth| auth.mode == AuthMode::ChatGPT)
+            .and_then(|auth| auth.get_current_token_data());
+        TokenStatus {
+            expires_at: tokens
+                .as_ref()
+                .and_then(|tokens| parse_jwt_expiration(&tokens.access_token)),
+            refreshable: tokens.is_some_and(|tokens| !tokens.refresh_token.is_empty()),
+        }
+    }
+
+    /// Receive [`AuthNotification`]s sent from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuthNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Refresh the ChatGPT tokens in the background `policy.margin` before
+    /// the access token expires, instead of only once a request was rejected.
+    /// Failed refreshes are retried with backoff and reported as
+    /// [`AuthNotification::RefreshFailed`]. Calling this again has no effect;
+    /// the refreshing stops when the manager is dropped.
+    pub fn enable_auto_refresh(self: &Arc<Self>, policy: AutoRefreshPolicy) {
+        if self.auto_refresh_enabled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tokio::spawn(run_auto_refresh(
+            Arc::downgrade(self),
+            self.subscribe(),
+            policy,
+        ));
+    }
+
+    fn notify(&self, notification: AuthNotification) {
+        // Sending only fails when nobody is subscribed.
+        let _ = self.notifications.send(notification);
+    }
+}
+
+const NOTIFICATION_CAPACITY: usize = 16;
+
+async fn run_auto_refresh(
+    manager: Weak<AuthManager>,
+    mut notifications: broadcast::Receiver<AuthNotification>,
+    policy: AutoRefreshPolicy,
+) {
+    // Consecutive failed refreshes, and whether the last one was permanent.
+    let mut failures: u32 = 0;
+    let mut gave_up = false;
+    let mut last_refresh: Option<Instant> = None;
+    loop {
+        let delay = {
+            let Some(manager) = manager.upgrade() else {
+                return;
+            };
+            let status = manager.token_status();
+            if !status.refreshable || gave_up {
+                None
+            } else if failures > 0 {
+                Some(policy.retry_delay(failures))
+            } else {
+                status.expires_at.map(|expires_at| {
+                    let due = (expires_at - Utc::now())
+                        .to_std()
+                        .unwrap_or_default()
+                        .saturating_sub(policy.margin);
+                    let earliest = last_refresh
+                        .map(|at| policy.max_retry_delay.saturating_sub(at.elapsed()))
+                        .unwrap_or_default();
+                    due.max(earliest)
+                })
+            }
+        };
+
+        // Wait until the refresh is due, starting over when the auth changes.
+        let notification = match delay {
+            Some(delay) => tokio::select! {
+                () = tokio::time::sleep(delay) => None,
+                notification = notifications.recv() => Some(notification),
+            },
+            None => Some(notifications.recv().await),
+        };
+        match notification {
+            None => {}
+            Some(Ok(AuthNotification::Changed)) => {
+                failures = 0;
+                gave_up = false;
+                continue;
+            }
+            Some(Ok(AuthNotification::RefreshFailed(_)) | Err(RecvError::Lagged(_))) => continue,
+            Some(Err(RecvError::Closed)) => return,
+        }
+
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        let expires_at = manager.token_status().expires_at;
+        match manager.refresh_token().await {
+            Ok(_) => {
+                failures = 0;
+                last_refresh = Some(Instant::now());
+            }
+            Err(err) => {
+                failures += 1;
+                gave_up = err.failed_reason().is_some();
+                let retry_in = (!gave_up).then(|| policy.retry_delay(failures));
+                tracing::warn!("proactive token refresh failed (attempt {failures}): {err}");
+                manager.notify(AuthNotification::RefreshFailed(AuthRefreshFailedEvent {
+                    message: err.to_string(),
+                    attempt: failures,
+                    retry_in_ms: retry_in
+                        .map(|delay| u64::try_from(delay.as_millis()).unwrap_or(u64::MAX)),
+                    expires_at: expires_at.map(|expires_at| expires_at.timestamp()),
+                }));
+            }
+        }
+    }
 }
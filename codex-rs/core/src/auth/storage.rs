@@ -14,8 +14,10 @@ use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use thiserror::Error;
 use tracing::warn;
 
+use crate::codex_home::CodexHome;
 use crate::token_data::TokenData;
 use codex_keyring_store::DefaultKeyringStore;
 use codex_keyring_store::KeyringStore;
@@ -47,7 +49,7 @@ pub struct AuthDotJson {
 }
 
 pub(super) fn get_auth_file(codex_home: &Path) -> PathBuf {
-    codex_home.join("auth.json")
+    CodexHome::new(codex_home).auth_file()
 }
 
 pub(super) fn delete_file_if_exists(codex_home: &Path) -> std::io::Result<bool> {
@@ -277,6 +279,76 @@ fn create_auth_storage_with_keyring_store(
     }
 }
 
+/// Why credentials could not be moved to another store.
+#[derive(Debug, Error)]
+pub enum CredentialMigrationError {
+    /// The OS keyring could not be used, e.g. because it is locked or no
+    /// secret service is running. The credentials were not removed from
+    /// the store they were in.
+    #[error("the OS keyring is unavailable: {0}")]
+    KeyringUnavailable(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub(super) fn migrate_credentials(
+    codex_home: &Path,
+    from: AuthCredentialsStoreMode,
+    to: AuthCredentialsStoreMode,
+) -> Result<bool, CredentialMigrationError> {
+    let keyring_store: Arc<dyn KeyringStore> = Arc::new(DefaultKeyringStore);
+    migrate_credentials_with_keyring_store(codex_home, from, to, keyring_store)
+}
+
+/// Moves the credentials found by `from` to the store that `to` saves to,
+/// then removes them from the other one. `auto` moves them to the keyring;
+/// unlike saving in `auto` mode, a migration never falls back to the file.
+/// Returns whether there were credentials to move.
+fn migrate_credentials_with_keyring_store(
+    codex_home: &Path,
+    from: AuthCredentialsStoreMode,
+    to: AuthCredentialsStoreMode,
+    keyring_store: Arc<dyn KeyringStore>,
+) -> Result<bool, CredentialMigrationError> {
+    if from == to {
+        return Ok(false);
+    }
+    let source = create_auth_storage_with_keyring_store(
+        codex_home.to_path_buf(),
+        from,
+        keyring_store.clone(),
+    );
+    let loaded = match from {
+        AuthCredentialsStoreMode::Keyring => source
+            .load()
+            .map_err(|err| CredentialMigrationError::KeyringUnavailable(err.to_string()))?,
+        AuthCredentialsStoreMode::File | AuthCredentialsStoreMode::Auto => source.load()?,
+    };
+    let Some(auth) = loaded else {
+        return Ok(false);
+    };
+    let key = compute_store_key(codex_home)?;
+    let keyring_unavailable = |error: codex_keyring_store::CredentialStoreError| {
+        CredentialMigrationError::KeyringUnavailable(error.message())
+    };
+    match to {
+        AuthCredentialsStoreMode::File => {
+            FileAuthStorage::new(codex_home.to_path_buf()).save(&auth)?;
+            keyring_store
+                .delete(KEYRING_SERVICE, &key)
+                .map_err(keyring_unavailable)?;
+        }
+        AuthCredentialsStoreMode::Keyring | AuthCredentialsStoreMode::Auto => {
+            let serialized = serde_json::to_string(&auth).map_err(std::io::Error::other)?;
+            keyring_store
+                .save(KEYRING_SERVICE, &key, &serialized)
+                .map_err(keyring_unavailable)?;
+            delete_file_if_exists(codex_home)?;
+        }
+    }
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -669,4 +741,102 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn migration_to_the_keyring_scrubs_the_auth_file() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let mock_keyring = MockKeyringStore::default();
+        let auth = auth_with_prefix("migrated");
+        FileAuthStorage::new(codex_home.path().to_path_buf()).save(&auth)?;
+
+        let moved = migrate_credentials_with_keyring_store(
+            codex_home.path(),
+            AuthCredentialsStoreMode::File,
+            AuthCredentialsStoreMode::Keyring,
+            Arc::new(mock_keyring.clone()),
+        )?;
+
+        assert!(moved);
+        let key = compute_store_key(codex_home.path())?;
+        assert_keyring_saved_auth_and_removed_fallback(
+            &mock_keyring,
+            &key,
+            codex_home.path(),
+            &auth,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migration_to_the_file_removes_the_keyring_entry() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let mock_keyring = MockKeyringStore::default();
+        let auth = auth_with_prefix("migrated");
+        seed_keyring_with_auth(
+            &mock_keyring,
+            || compute_store_key(codex_home.path()),
+            &auth,
+        )?;
+
+        let moved = migrate_credentials_with_keyring_store(
+            codex_home.path(),
+            AuthCredentialsStoreMode::Keyring,
+            AuthCredentialsStoreMode::File,
+            Arc::new(mock_keyring.clone()),
+        )?;
+
+        assert!(moved);
+        let saved = FileAuthStorage::new(codex_home.path().to_path_buf()).load()?;
+        assert_eq!(saved, Some(auth));
+        let key = compute_store_key(codex_home.path())?;
+        assert!(
+            !mock_keyring.contains(&key),
+            "keyring entry should be removed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migration_to_an_unavailable_keyring_keeps_the_auth_file() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let mock_keyring = MockKeyringStore::default();
+        let key = compute_store_key(codex_home.path())?;
+        mock_keyring.set_error(&key, KeyringError::Invalid("error".into(), "locked".into()));
+        let auth = auth_with_prefix("stays");
+        FileAuthStorage::new(codex_home.path().to_path_buf()).save(&auth)?;
+
+        let err = migrate_credentials_with_keyring_store(
+            codex_home.path(),
+            AuthCredentialsStoreMode::File,
+            AuthCredentialsStoreMode::Auto,
+            Arc::new(mock_keyring),
+        )
+        .expect_err("the keyring is unavailable");
+
+        assert!(
+            matches!(err, CredentialMigrationError::KeyringUnavailable(_)),
+            "{err:?}"
+        );
+        let kept = FileAuthStorage::new(codex_home.path().to_path_buf()).load()?;
+        assert_eq!(kept, Some(auth));
+        Ok(())
+    }
+
+    #[test]
+    fn migration_without_credentials_moves_nothing() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let mock_keyring = MockKeyringStore::default();
+
+        let moved = migrate_credentials_with_keyring_store(
+            codex_home.path(),
+            AuthCredentialsStoreMode::File,
+            AuthCredentialsStoreMode::Keyring,
+            Arc::new(mock_keyring.clone()),
+        )?;
+
+        assert!(!moved);
+        let key = compute_store_key(codex_home.path())?;
+        assert!(!mock_keyring.contains(&key));
+        Ok(())
+    }
 }
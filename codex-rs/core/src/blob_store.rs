@@ -0,0 +1,114 @@
+//! Content-addressed storage for outputs too large to keep inline.
+//!
+//! Blobs live in `$CODEX_HOME/blobs/sha256/`, one file per content named by
+//! the lowercase hex SHA-256 of its bytes, so storing the same content twice
+//! writes it once. Events and rollouts refer to a blob with a [`BlobRef`].
+
+use std::io;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::BlobRef;
+use sha2::Digest;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::codex_home::CodexHome;
+
+pub(crate) const BLOBS_SUBDIR: &str = "blobs";
+
+const SHA256_SUBDIR: &str = "sha256";
+
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(codex_home: &Path) -> Self {
+        Self {
+            root: CodexHome::new(codex_home).blobs_dir().join(SHA256_SUBDIR),
+        }
+    }
+
+    /// Store `content` and return the reference to it.
+    pub async fn put(&self, content: &[u8]) -> io::Result<BlobRef> {
+        let blob = BlobRef {
+            sha256: format!("{:x}", Sha256::digest(content)),
+            size_bytes: content.len() as u64,
+        };
+        let path = self.path(&blob);
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(blob);
+        }
+        tokio::fs::create_dir_all(&self.root).await?;
+        // Readers never see a partly written blob.
+        let partial = self
+            .root
+            .join(format!(".{}.{}", blob.sha256, Uuid::new_v4()));
+        tokio::fs::write(&partial, content).await?;
+        if let Err(err) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(err);
+        }
+        Ok(blob)
+    }
+
+    /// The content `blob` refers to.
+    pub async fn get(&self, blob: &BlobRef) -> io::Result<Vec<u8>> {
+        if !is_sha256_hex(&blob.sha256) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("not a sha256 blob reference: {}", blob.sha256),
+            ));
+        }
+        tokio::fs::read(self.path(blob)).await
+    }
+
+    /// Where the content of `blob` is stored.
+    pub fn path(&self, blob: &BlobRef) -> PathBuf {
+        self.root.join(&blob.sha256)
+    }
+}
+
+fn is_sha256_hex(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn identical_content_is_stored_once() {
+        let home = tempfile::tempdir().expect("tempdir");
+        let store = BlobStore::new(home.path());
+
+        let first = store.put(b"hello").await.expect("put");
+        let second = store.put(b"hello").await.expect("put again");
+
+        assert_eq!(first, second);
+        assert_eq!(first.size_bytes, 5);
+        assert_eq!(store.get(&first).await.expect("get"), b"hello");
+        let stored = std::fs::read_dir(home.path().join(BLOBS_SUBDIR))
+            .expect("read blobs")
+            .count();
+        assert_eq!(stored, 1);
+    }
+
+    #[tokio::test]
+    async fn references_outside_the_store_are_rejected() {
+        let home = tempfile::tempdir().expect("tempdir");
+        let store = BlobStore::new(home.path());
+        let blob = BlobRef {
+            sha256: "../../config.toml".to_string(),
+            size_bytes: 0,
+        };
+
+        let err = store.get(&blob).await.expect_err("not a hash");
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,76 @@
+//! Child conversations that a conversation delegates a sub-task to, e.g.
+//! "research X while I keep editing".
+//!
+//! A child is spawned through the same [`ConversationManager`] as its parent,
+//! counts against the manager's conversation limit and, unless it is asked
+//! to outlive the parent, is removed together with it. Both rollouts record
+//! the link. Once the child has answered, the parent ingests the final
+//! assistant message as a tagged context item rather than as user input.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+
+use crate::config::Config;
+
+pub(crate) const CHILD_RESULT_OPEN_TAG: &str = "<child_conversation_result";
+const CHILD_RESULT_CLOSE_TAG: &str = "</child_conversation_result>";
+
+/// How a child conversation differs from the parent it is spawned from.
+/// Unset fields inherit the parent's configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildConversationOverrides {
+    pub model: Option<String>,
+    pub cwd: Option<PathBuf>,
+    /// Replaces the parent's developer instructions, e.g. to describe the
+    /// sub-task's scope.
+    pub developer_instructions: Option<String>,
+    /// Keep the child running when the parent is removed from the manager.
+    pub outlive_parent: bool,
+}
+
+impl ChildConversationOverrides {
+    pub(crate) fn apply(&self, mut config: Config) -> Config {
+        if let Some(model) = &self.model {
+            config.model = Some(model.clone());
+        }
+        if let Some(cwd) = &self.cwd {
+            config.cwd = cwd.clone();
+        }
+        if let Some(developer_instructions) = &self.developer_instructions {
+            config.developer_instructions = Some(developer_instructions.clone());
+        }
+        config
+    }
+}
+
+/// Parent and children of a conversation tracked by the manager.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversationLineage {
+    pub parent: Option<ConversationId>,
+    /// In spawn order.
+    pub children: Vec<ConversationId>,
+}
+
+/// Text the parent's transcript receives for the child's final answer. It is
+/// recorded with the user role, like the environment context, but is not a
+/// user message.
+pub(crate) fn child_result_text(child_id: ConversationId, message: &str) -> String {
+    format!(
+        "{CHILD_RESULT_OPEN_TAG} child_id=\"{child_id}\">\nFinal answer of the child conversation you delegated to:\n{message}\n{CHILD_RESULT_CLOSE_TAG}"
+    )
+}
+
+pub(crate) fn child_result_item(child_id: ConversationId, message: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: child_result_text(child_id, message),
+        }],
+    }
+}
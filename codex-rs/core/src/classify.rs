@@ -0,0 +1,288 @@
+//! The canonical classification of history items.
+//!
+//! This is the classification Codex itself uses: forks cut at the items
+//! classified as [`ItemClass::UserMessage`], so their count is the number of
+//! fork ordinals a conversation has, and tooling that counts turns in a
+//! transcript should classify its items with [`classify_item`] to agree with
+//! Codex.
+
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ResponseItem;
+
+use crate::event_mapping::parse_turn_item;
+
+/// What a history item is, as far as turns are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemClass {
+    /// A message the user submitted. Each one starts a turn.
+    UserMessage,
+    AssistantMessage,
+    Reasoning,
+    /// A call of a tool, including local shell calls and web searches.
+    ToolCall,
+    ToolOutput,
+    /// A message Codex adds to the context on its own: instructions, the
+    /// environment context, user context, workspace change notices, imported
+    /// entries, child conversation results, output repair requests and
+    /// records of user shell commands.
+    SessionPrefix,
+    /// Ghost snapshots, compaction summaries and items this build does not
+    /// know.
+    Other,
+}
+
+/// Classify `item` the way Codex does.
+pub fn classify_item(item: &ResponseItem) -> ItemClass {
+    match parse_turn_item(item) {
+        Some(TurnItem::UserMessage(_)) => return ItemClass::UserMessage,
+        Some(TurnItem::AgentMessage(_)) => return ItemClass::AssistantMessage,
+        Some(TurnItem::Reasoning(_)) => return ItemClass::Reasoning,
+        Some(TurnItem::WebSearch(_)) => return ItemClass::ToolCall,
+        None => {}
+    }
+    match item {
+        // Messages of any other role, and user messages that are not user
+        // input.
+        ResponseItem::Message { .. } => ItemClass::SessionPrefix,
+        ResponseItem::Reasoning { .. } => ItemClass::Reasoning,
+        ResponseItem::LocalShellCall { .. }
+        | ResponseItem::FunctionCall { .. }
+        | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::WebSearchCall { .. } => ItemClass::ToolCall,
+        ResponseItem::FunctionCallOutput { .. } | ResponseItem::CustomToolCallOutput { .. } => {
+            ItemClass::ToolOutput
+        }
+        ResponseItem::GhostSnapshot { .. }
+        | ResponseItem::Compaction { .. }
+        | ResponseItem::Other => ItemClass::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::child_conversations::CHILD_RESULT_OPEN_TAG;
+    use crate::codex::make_session_and_context;
+    use crate::rollout::import::IMPORTED_ENTRY_OPEN_TAG;
+    use crate::structured_output::STRUCTURED_OUTPUT_REPAIR_OPEN_TAG;
+    use crate::user_context::USER_CONTEXT_OPEN_TAG;
+    use crate::user_instructions::SkillInstructions;
+    use crate::user_shell_command::USER_SHELL_COMMAND_OPEN;
+    use crate::workspace_watch::WORKSPACE_CHANGED_OPEN_TAG;
+    use codex_git::GhostCommit;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::models::LocalShellAction;
+    use codex_protocol::models::LocalShellExecAction;
+    use codex_protocol::models::LocalShellStatus;
+    use codex_protocol::models::ReasoningItemReasoningSummary;
+    use codex_protocol::models::WebSearchAction;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, content: ContentItem) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        }
+    }
+
+    fn input_text(role: &str, text: &str) -> ResponseItem {
+        message(
+            role,
+            ContentItem::InputText {
+                text: text.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn classifies_every_response_item_variant() {
+        let cases = [
+            (input_text("user", "fix the bug"), ItemClass::UserMessage),
+            (
+                message(
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string(),
+                    },
+                ),
+                ItemClass::UserMessage,
+            ),
+            (
+                message(
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: "done".to_string(),
+                    },
+                ),
+                ItemClass::AssistantMessage,
+            ),
+            (
+                input_text("developer", "be terse"),
+                ItemClass::SessionPrefix,
+            ),
+            (input_text("system", "you are"), ItemClass::SessionPrefix),
+            (
+                ResponseItem::Reasoning {
+                    id: "r1".to_string(),
+                    summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                        text: "thinking".to_string(),
+                    }],
+                    content: None,
+                    encrypted_content: Some("opaque".to_string()),
+                },
+                ItemClass::Reasoning,
+            ),
+            (
+                ResponseItem::LocalShellCall {
+                    id: None,
+                    call_id: Some("call-1".to_string()),
+                    status: LocalShellStatus::Completed,
+                    action: LocalShellAction::Exec(LocalShellExecAction {
+                        command: vec!["ls".to_string()],
+                        timeout_ms: None,
+                        working_directory: None,
+                        env: None,
+                        user: None,
+                    }),
+                },
+                ItemClass::ToolCall,
+            ),
+            (
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: "{}".to_string(),
+                    call_id: "call-2".to_string(),
+                },
+                ItemClass::ToolCall,
+            ),
+            (
+                ResponseItem::FunctionCallOutput {
+                    call_id: "call-2".to_string(),
+                    output: FunctionCallOutputPayload {
+                        content: "ok".to_string(),
+                        ..Default::default()
+                    },
+                },
+                ItemClass::ToolOutput,
+            ),
+            (
+                ResponseItem::CustomToolCall {
+                    id: None,
+                    status: None,
+                    call_id: "call-3".to_string(),
+                    name: "apply_patch".to_string(),
+                    input: "*** Begin Patch".to_string(),
+                },
+                ItemClass::ToolCall,
+            ),
+            (
+                ResponseItem::CustomToolCallOutput {
+                    call_id: "call-3".to_string(),
+                    output: "ok".to_string(),
+                },
+                ItemClass::ToolOutput,
+            ),
+            (
+                ResponseItem::WebSearchCall {
+                    id: Some("ws-1".to_string()),
+                    status: None,
+                    action: WebSearchAction::Search {
+                        query: Some("weather".to_string()),
+                    },
+                },
+                ItemClass::ToolCall,
+            ),
+            (
+                ResponseItem::WebSearchCall {
+                    id: Some("ws-2".to_string()),
+                    status: None,
+                    action: WebSearchAction::OpenPage {
+                        url: Some("https://example.com".to_string()),
+                    },
+                },
+                ItemClass::ToolCall,
+            ),
+            (
+                ResponseItem::GhostSnapshot {
+                    ghost_commit: GhostCommit::new(
+                        "ghost-1".to_string(),
+                        None,
+                        Vec::new(),
+                        Vec::new(),
+                    ),
+                },
+                ItemClass::Other,
+            ),
+            (
+                ResponseItem::Compaction {
+                    encrypted_content: "opaque".to_string(),
+                },
+                ItemClass::Other,
+            ),
+            (ResponseItem::Other, ItemClass::Other),
+        ];
+
+        for (item, expected) in cases {
+            assert_eq!(classify_item(&item), expected, "{item:?}");
+        }
+    }
+
+    #[test]
+    fn user_role_context_messages_are_session_prefix() {
+        let skill: ResponseItem = SkillInstructions {
+            name: "review".to_string(),
+            path: "/skills/review/SKILL.md".to_string(),
+            contents: "check everything".to_string(),
+        }
+        .into();
+        let items = [
+            input_text("user", "<user_instructions>be nice</user_instructions>"),
+            skill,
+            input_text(
+                "user",
+                &format!("{WORKSPACE_CHANGED_OPEN_TAG}main</workspace_changed>"),
+            ),
+            input_text(
+                "user",
+                &format!("{IMPORTED_ENTRY_OPEN_TAG}hi</imported_entry>"),
+            ),
+            input_text("user", &format!("{CHILD_RESULT_OPEN_TAG} id=\"c\">done")),
+            input_text(
+                "user",
+                &format!("{STRUCTURED_OUTPUT_REPAIR_OPEN_TAG}fix it</structured_output_repair>"),
+            ),
+            input_text("user", &format!("{USER_SHELL_COMMAND_OPEN}\nls\n")),
+            input_text(
+                "user",
+                &format!("{USER_CONTEXT_OPEN_TAG}\n  <timezone>UTC</timezone>\n</user_context>"),
+            ),
+            message(
+                "user",
+                ContentItem::OutputText {
+                    text: "<environment_context>".to_string(),
+                },
+            ),
+        ];
+
+        for item in items {
+            assert_eq!(classify_item(&item), ItemClass::SessionPrefix, "{item:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn initial_context_is_session_prefix() {
+        let (session, mut turn_context) = make_session_and_context().await;
+        turn_context.developer_instructions = Some("be terse".to_string());
+        turn_context.user_instructions = Some("run the tests".to_string());
+
+        let items = session.build_initial_context(&turn_context);
+
+        assert_eq!(items.len(), 3);
+        for item in items {
+            assert_eq!(classify_item(&item), ItemClass::SessionPrefix, "{item:?}");
+        }
+    }
+}
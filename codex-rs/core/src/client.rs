@@ -11,7 +11,7 @@ use codex_api::RequestTelemetry;
 use codex_api::ReqwestTransport;
 use codex_api::ResponseStream as ApiResponseStream;
 use codex_api::ResponsesClient as ApiResponsesClient;
-use codex_api::ResponsesOptions as ApiResponsesOptions;
+use codex_api::ResponsesRequestBuilder as ApiResponsesRequestBuilder;
 use codex_api::SseTelemetry;
 use codex_api::TransportError;
 use codex_api::common::Reasoning;
@@ -21,8 +21,12 @@ use codex_app_server_protocol::AuthMode;
 use codex_otel::otel_manager::OtelManager;
 use codex_protocol::ConversationId;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_protocol::config_types::SamplingParams;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::protocol::ProviderEndpointSwitchedEvent;
+use codex_protocol::protocol::RequestFingerprint;
+use codex_protocol::protocol::RetryingEvent;
 use codex_protocol::protocol::SessionSource;
 use eventsource_stream::Event;
 use eventsource_stream::EventStreamError;
@@ -32,6 +36,7 @@ use http::HeaderValue;
 use http::StatusCode as HttpStatusCode;
 use reqwest::StatusCode;
 use serde_json::Value;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::warn;
@@ -43,13 +48,23 @@ use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::config::Config;
 use crate::default_client::build_reqwest_client;
+use crate::endpoint_failover::EndpointRouting;
+use crate::endpoint_failover::EndpointSwitches;
+use crate::endpoint_failover::FailoverTransport;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::features::FEATURES;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
+use crate::models_manager::endpoint_health::EndpointHealth;
 use crate::models_manager::model_family::ModelFamily;
+use crate::request_trace::TracingTransport;
+use crate::request_trace::TurnRequestTracer;
+use crate::retry::RetryPolicy;
+use crate::rollout::reconstruct;
+use crate::sampling;
+use crate::structured_output;
 use crate::tools::spec::create_tools_json_for_chat_completions_api;
 use crate::tools::spec::create_tools_json_for_responses_api;
 
@@ -64,6 +79,13 @@ pub struct ModelClient {
     effort: Option<ReasoningEffortConfig>,
     summary: ReasoningSummaryConfig,
     session_source: SessionSource,
+    request_tracer: Option<TurnRequestTracer>,
+    endpoint_health: Option<Arc<EndpointHealth>>,
+    endpoint_switches: EndpointSwitches,
+    /// Fingerprint of the latest Responses API request, shared by clones.
+    request_fingerprint: Arc<Mutex<Option<RequestFingerprint>>>,
+    /// Retries of token refreshes not taken yet, shared by clones.
+    auth_refresh_retries: Arc<Mutex<Vec<RetryingEvent>>>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -89,9 +111,51 @@ impl ModelClient {
             effort,
             summary,
             session_source,
+            request_tracer: None,
+            endpoint_health: None,
+            endpoint_switches: EndpointSwitches::default(),
+            request_fingerprint: Arc::default(),
+            auth_refresh_retries: Arc::default(),
         }
     }
 
+    /// Report every request made by this client to `tracer`.
+    pub(crate) fn set_request_tracer(&mut self, tracer: TurnRequestTracer) {
+        self.request_tracer = Some(tracer);
+    }
+
+    /// Fail over between the provider's base URLs using the shared `health`.
+    pub(crate) fn set_endpoint_health(&mut self, health: Arc<EndpointHealth>) {
+        self.endpoint_health = Some(health);
+    }
+
+    /// Drains the endpoint switches observed since the last call.
+    pub(crate) fn take_endpoint_switches(&self) -> Vec<ProviderEndpointSwitchedEvent> {
+        self.endpoint_switches.take()
+    }
+
+    /// Drains the token refresh retries made since the last call.
+    pub(crate) fn take_auth_refresh_retries(&self) -> Vec<RetryingEvent> {
+        self.auth_refresh_retries
+            .lock()
+            .map(|mut retries| std::mem::take(&mut *retries))
+            .unwrap_or_default()
+    }
+
+    fn record_auth_refresh_retry(&self, retry: &RetryingEvent) {
+        if let Ok(mut retries) = self.auth_refresh_retries.lock() {
+            retries.push(retry.clone());
+        }
+    }
+
+    /// Takes the fingerprint of the latest request, if it was not taken yet.
+    pub(crate) fn take_request_fingerprint(&self) -> Option<RequestFingerprint> {
+        self.request_fingerprint
+            .lock()
+            .ok()
+            .and_then(|mut fingerprint| fingerprint.take())
+    }
+
     pub fn get_model_context_window(&self) -> Option<i64> {
         let model_family = self.get_model_family();
         let effective_context_window_percent = model_family.effective_context_window_percent;
@@ -108,12 +172,24 @@ impl ModelClient {
         &self.provider
     }
 
+    /// Every provider call starts here, so a client built from an offline
+    /// config cannot reach the provider, whoever holds it.
+    fn ensure_online(&self) -> Result<()> {
+        if self.config.offline {
+            return Err(CodexErr::Offline);
+        }
+        Ok(())
+    }
+
     /// Streams a single model turn using either the Responses or Chat
     /// Completions wire API, depending on the configured provider.
     ///
     /// For Chat providers, the underlying stream is optionally aggregated
     /// based on the `show_raw_agent_reasoning` flag in the config.
+    ///
+    /// Fails without a request when the client's config is offline.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        self.ensure_online()?;
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses_api(prompt).await,
             WireApi::Chat => {
@@ -137,19 +213,19 @@ impl ModelClient {
     /// Streams a turn via the OpenAI Chat Completions API.
     ///
     /// This path is only used when the provider is configured with
-    /// `WireApi::Chat`; it does not support `output_schema` today.
+    /// `WireApi::Chat`. Chat Completions has no structured outputs, so an
+    /// `output_schema` is described in the instructions instead and enforced
+    /// by validating the final answer.
     async fn stream_chat_completions(&self, prompt: &Prompt) -> Result<ApiResponseStream> {
-        if prompt.output_schema.is_some() {
-            return Err(CodexErr::UnsupportedOperation(
-                "output_schema is not supported for Chat Completions API".to_string(),
-            ));
-        }
-
         let auth_manager = self.auth_manager.clone();
         let model_family = self.get_model_family();
-        let instructions = prompt.get_full_instructions(&model_family).into_owned();
+        let mut instructions = prompt.get_full_instructions(&model_family).into_owned();
+        if let Some(schema) = &prompt.output_schema {
+            instructions.push_str("\n\n");
+            instructions.push_str(&structured_output::schema_instructions(schema));
+        }
         let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json, self.get_sampling().0);
         let conversation_id = self.conversation_id.to_string();
         let session_source = self.session_source.clone();
 
@@ -160,7 +236,7 @@ impl ModelClient {
                 .provider
                 .to_api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
+            let transport = self.build_transport(&api_provider.base_url);
             let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
             let client = ApiChatClient::new(transport, api_provider, api_auth)
                 .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
@@ -179,7 +255,15 @@ impl ModelClient {
                 Err(ApiError::Transport(TransportError::Http { status, .. }))
                     if status == StatusCode::UNAUTHORIZED =>
                 {
-                    handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.config.retry.auth_refresh,
+                        |retry| self.record_auth_refresh_retry(retry),
+                    )
+                    .await?;
                     continue;
                 }
                 Err(err) => return Err(map_api_error(err)),
@@ -238,7 +322,12 @@ impl ModelClient {
         };
 
         let text = create_text_param_for_request(verbosity, &prompt.output_schema);
-        let api_prompt = build_api_prompt(prompt, instructions.clone(), tools_json);
+        let api_prompt = build_api_prompt(
+            prompt,
+            instructions.clone(),
+            tools_json,
+            self.get_sampling().0,
+        );
         let conversation_id = self.conversation_id.to_string();
         let session_source = self.session_source.clone();
 
@@ -249,25 +338,33 @@ impl ModelClient {
                 .provider
                 .to_api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
+            let transport = self.build_transport(&api_provider.base_url);
             let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let request = ApiResponsesRequestBuilder::new(
+                &self.get_model(),
+                &api_prompt.instructions,
+                &api_prompt.input,
+            )
+            .tools(&api_prompt.tools)
+            .parallel_tool_calls(api_prompt.parallel_tool_calls)
+            .reasoning(reasoning.clone())
+            .include(include.clone())
+            .prompt_cache_key(Some(conversation_id.clone()))
+            .text(text.clone())
+            .conversation(Some(conversation_id.clone()))
+            .session_source(Some(session_source.clone()))
+            .sampling(api_prompt.sampling)
+            .extra_headers(beta_feature_headers(&self.config))
+            .build(&api_provider)
+            .map_err(map_api_error)?;
+            // Fingerprinted as built, so that it matches what is sent.
+            if let Ok(mut fingerprint) = self.request_fingerprint.lock() {
+                *fingerprint = reconstruct::fingerprint(&request.body);
+            }
             let client = ApiResponsesClient::new(transport, api_provider, api_auth)
                 .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
 
-            let options = ApiResponsesOptions {
-                reasoning: reasoning.clone(),
-                include: include.clone(),
-                prompt_cache_key: Some(conversation_id.clone()),
-                text: text.clone(),
-                store_override: None,
-                conversation_id: Some(conversation_id.clone()),
-                session_source: Some(session_source.clone()),
-                extra_headers: beta_feature_headers(&self.config),
-            };
-
-            let stream_result = client
-                .stream_prompt(&self.get_model(), &api_prompt, options)
-                .await;
+            let stream_result = client.stream_request(request).await;
 
             match stream_result {
                 Ok(stream) => {
@@ -276,7 +373,15 @@ impl ModelClient {
                 Err(ApiError::Transport(TransportError::Http { status, .. }))
                     if status == StatusCode::UNAUTHORIZED =>
                 {
-                    handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.config.retry.auth_refresh,
+                        |retry| self.record_auth_refresh_retry(retry),
+                    )
+                    .await?;
                     continue;
                 }
                 Err(err) => return Err(map_api_error(err)),
@@ -316,6 +421,16 @@ impl ModelClient {
         self.summary
     }
 
+    /// Sampling parameters sent with this client's requests, and the names of
+    /// the configured ones left out because the model or provider ignores them.
+    pub(crate) fn get_sampling(&self) -> (SamplingParams, Vec<&'static str>) {
+        sampling::effective(
+            &self.config.sampling,
+            &self.model_family,
+            self.provider.wire_api,
+        )
+    }
+
     pub fn get_auth_manager(&self) -> Option<Arc<AuthManager>> {
         self.auth_manager.clone()
     }
@@ -325,6 +440,7 @@ impl ModelClient {
     /// This is a unary call (no streaming) that returns a new list of
     /// `ResponseItem`s representing the compacted transcript.
     pub async fn compact_conversation_history(&self, prompt: &Prompt) -> Result<Vec<ResponseItem>> {
+        self.ensure_online()?;
         if prompt.input.is_empty() {
             return Ok(Vec::new());
         }
@@ -334,7 +450,7 @@ impl ModelClient {
             .provider
             .to_api_provider(auth.as_ref().map(|a| a.mode))?;
         let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-        let transport = ReqwestTransport::new(build_reqwest_client());
+        let transport = self.build_transport(&api_provider.base_url);
         let request_telemetry = self.build_request_telemetry();
         let client = ApiCompactClient::new(transport, api_provider, api_auth)
             .with_telemetry(Some(request_telemetry));
@@ -371,6 +487,34 @@ impl ModelClient {
 }
 
 impl ModelClient {
+    /// Builds the HTTP transport for requests against `base_url`, wrapped so
+    /// they fail over to the provider's other endpoints and reach the turn's
+    /// tracer.
+    fn build_transport(
+        &self,
+        base_url: &str,
+    ) -> FailoverTransport<TracingTransport<ReqwestTransport>> {
+        let routing = self.endpoint_health.as_ref().map(|health| EndpointRouting {
+            provider: self.provider.name.clone(),
+            endpoints: self
+                .provider
+                .endpoint_base_urls(base_url)
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            cooldown: self.provider.endpoint_cooldown(),
+            health: Arc::clone(health),
+            switches: self.endpoint_switches.clone(),
+        });
+        FailoverTransport::new(
+            TracingTransport::new(
+                ReqwestTransport::new(build_reqwest_client()),
+                self.request_tracer.clone(),
+            ),
+            routing,
+        )
+    }
+
     /// Builds request and SSE telemetry for streaming API calls (Chat/Responses).
     fn build_streaming_telemetry(&self) -> (Arc<dyn RequestTelemetry>, Arc<dyn SseTelemetry>) {
         let telemetry = Arc::new(ApiTelemetry::new(self.otel_manager.clone()));
@@ -388,13 +532,19 @@ impl ModelClient {
 }
 
 /// Adapts the core `Prompt` type into the `codex-api` payload shape.
-fn build_api_prompt(prompt: &Prompt, instructions: String, tools_json: Vec<Value>) -> ApiPrompt {
+fn build_api_prompt(
+    prompt: &Prompt,
+    instructions: String,
+    tools_json: Vec<Value>,
+    sampling: SamplingParams,
+) -> ApiPrompt {
     ApiPrompt {
         instructions,
         input: prompt.get_formatted_input(),
         tools: tools_json,
         parallel_tool_calls: prompt.parallel_tool_calls,
         output_schema: prompt.output_schema.clone(),
+        sampling,
     }
 }
 
@@ -479,7 +629,8 @@ where
     ResponseStream { rx_event }
 }
 
-/// Handles a 401 response by optionally refreshing ChatGPT tokens once.
+/// Handles a 401 response by optionally refreshing ChatGPT tokens once,
+/// retrying the refresh under `policy`.
 ///
 /// When refresh succeeds, the caller should retry the API call; otherwise
 /// the mapped `CodexErr` is returned to the caller.
@@ -488,6 +639,8 @@ async fn handle_unauthorized(
     refreshed: &mut bool,
     auth_manager: &Option<Arc<AuthManager>>,
     auth: &Option<crate::auth::CodexAuth>,
+    policy: &RetryPolicy,
+    on_retry: impl FnMut(&RetryingEvent),
 ) -> Result<()> {
     if *refreshed {
         return Err(map_unauthorized_status(status));
@@ -497,7 +650,7 @@ async fn handle_unauthorized(
         && let Some(auth) = auth.as_ref()
         && auth.mode == AuthMode::ChatGPT
     {
-        match manager.refresh_token().await {
+        match manager.refresh_token_with_retry(policy, on_retry).await {
             Ok(_) => {
                 *refreshed = true;
                 Ok(())
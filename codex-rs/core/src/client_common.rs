@@ -87,7 +87,7 @@ impl Prompt {
     }
 }
 
-fn reserialize_shell_outputs(items: &mut [ResponseItem]) {
+pub(crate) fn reserialize_shell_outputs(items: &mut [ResponseItem]) {
     let mut shell_call_ids: HashSet<String> = HashSet::new();
 
     items.iter_mut().for_each(|item| match item {
@@ -349,6 +349,8 @@ mod tests {
                 verbosity: Some(OpenAiVerbosity::Low),
                 format: None,
             }),
+            temperature: None,
+            top_p: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -387,6 +389,8 @@ mod tests {
             include: vec![],
             prompt_cache_key: None,
             text: Some(text_controls),
+            temperature: None,
+            top_p: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -423,6 +427,8 @@ mod tests {
             include: vec![],
             prompt_cache_key: None,
             text: None,
+            temperature: None,
+            top_p: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
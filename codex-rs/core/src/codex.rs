@@ -1,13 +1,20 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Weak;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use crate::AuthManager;
 use crate::SandboxState;
+use crate::adaptive_effort::TurnObservation;
+use crate::auth::AuthNotification;
+use crate::blob_store::BlobStore;
 use crate::client_common::REVIEW_PROMPT;
 use crate::compact;
 use crate::compact::run_inline_auto_compact_task;
@@ -16,10 +23,29 @@ use crate::compact_remote::run_inline_remote_auto_compact_task;
 use crate::exec_policy::ExecPolicyManager;
 use crate::features::Feature;
 use crate::features::Features;
+use crate::git_context::GitContextTracker;
+use crate::git_context::recorded_git_context;
+use crate::git_info::collect_git_info;
+use crate::handoff::HandoffContext;
+use crate::heartbeat;
+use crate::history_truncation::fit_to_context_window;
+use crate::history_truncation::last_turn_id;
+use crate::history_truncation::user_message_positions;
+use crate::manager_load::ConversationLoad;
 use crate::models_manager::manager::ModelsManager;
 use crate::models_manager::model_family::ModelFamily;
 use crate::parse_command::parse_command;
+use crate::parse_command::shlex_join;
 use crate::parse_turn_item;
+use crate::partial_messages::PartialMessageRecorder;
+use crate::pause::PauseState;
+use crate::pause::PauseStatus;
+use crate::post_process::PostProcessOutcome;
+use crate::post_process::TurnOutput;
+use crate::post_process::TurnPostProcessor;
+use crate::post_process::run_post_processors;
+use crate::pre_process::TurnPreProcessor;
+use crate::sampling;
 use crate::stream_events_utils::HandleOutputCtx;
 use crate::stream_events_utils::handle_non_tool_response_item;
 use crate::stream_events_utils::handle_output_item_done;
@@ -32,17 +58,58 @@ use async_channel::Sender;
 use codex_protocol::ConversationId;
 use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::items::TurnItem;
+use codex_protocol::openai_models::ModelPreset;
+use codex_protocol::parse_command::ParsedCommand;
+use codex_protocol::protocol::CheckpointCreatedEvent;
+use codex_protocol::protocol::CheckpointId;
+use codex_protocol::protocol::CheckpointItem;
+use codex_protocol::protocol::CheckpointRollbackItem;
+use codex_protocol::protocol::CheckpointRolledBackEvent;
+use codex_protocol::protocol::ContextRecoveredEvent;
+use codex_protocol::protocol::ContextUsage;
+use codex_protocol::protocol::ContextUsageUpdatedEvent;
+use codex_protocol::protocol::ExcludedSkill;
 use codex_protocol::protocol::FileChange;
+use codex_protocol::protocol::ForkedEvent;
 use codex_protocol::protocol::HasLegacyEvent;
+use codex_protocol::protocol::HeartbeatPhase;
+use codex_protocol::protocol::InstructionsReloadedEvent;
+use codex_protocol::protocol::InstructionsReplacedItem;
 use codex_protocol::protocol::ItemCompletedEvent;
+use codex_protocol::protocol::ItemPinnedEvent;
+use codex_protocol::protocol::ItemRef;
 use codex_protocol::protocol::ItemStartedEvent;
+use codex_protocol::protocol::ItemUnpinnedEvent;
+use codex_protocol::protocol::ModelDeprecatedEvent;
+use codex_protocol::protocol::ModelListRefreshedEvent;
+use codex_protocol::protocol::ModelListSource;
+use codex_protocol::protocol::ParentRef;
+use codex_protocol::protocol::PauseMode;
+use codex_protocol::protocol::PendingInput;
+use codex_protocol::protocol::PendingInputItem;
+use codex_protocol::protocol::PendingInputsRestoredEvent;
+use codex_protocol::protocol::PinnedItem;
+use codex_protocol::protocol::QueuedOfflineEvent;
 use codex_protocol::protocol::RawResponseItemEvent;
+use codex_protocol::protocol::RequestFingerprint;
+use codex_protocol::protocol::ResumeTrimReport;
+use codex_protocol::protocol::RetrySubsystem;
 use codex_protocol::protocol::ReviewRequest;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SafeModeChangedEvent;
+use codex_protocol::protocol::SafeModePolicies;
+use codex_protocol::protocol::SessionEndReason;
+use codex_protocol::protocol::SessionEndedEvent;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::SkillSelection;
+use codex_protocol::protocol::SkillUsageEvent;
 use codex_protocol::protocol::TaskStartedEvent;
+use codex_protocol::protocol::ToolDowngradeReport;
+use codex_protocol::protocol::TranscriptMutationKind;
 use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnContextItem;
+use codex_protocol::protocol::TurnId;
+use codex_protocol::protocol::TurnStartedItem;
 use codex_rmcp_client::ElicitationResponse;
 use futures::future::BoxFuture;
 use futures::prelude::*;
@@ -57,9 +124,14 @@ use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde_json;
 use serde_json::Value;
+use serde_json::json;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::task::AbortHandle;
+use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 use tracing::debug;
@@ -81,28 +153,49 @@ use crate::config::Constrained;
 use crate::config::ConstraintResult;
 use crate::config::GhostSnapshotConfig;
 use crate::config::types::ShellEnvironmentPolicy;
+use crate::config::types::ToolTimeouts;
 use crate::context_manager::ContextManager;
+use crate::context_manager::estimate_base_tokens;
+use crate::context_manager::estimate_item_tokens;
+use crate::context_usage::ContextUsageTracker;
+use crate::context_usage::TokenCounter;
+use crate::correlation::CorrelationIds;
+use crate::delta_coalescing::DeltaCoalescer;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
+use crate::event_subscriptions::EventSubscribers;
+use crate::event_subscriptions::spawn_event_fanout;
 #[cfg(test)]
 use crate::exec::StreamOutput;
+use crate::exec_env::create_env;
 use crate::exec_policy::ExecPolicyUpdateError;
+use crate::instructions_refresh::InstructionsWatcher;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::model_provider_info::CHAT_WIRE_API_DEPRECATION_SUMMARY;
+use crate::project_approvals::ProjectApprovalStore;
+use crate::project_approvals::ProjectScope;
+use crate::project_approvals::rule_covers;
 use crate::project_doc::get_user_instructions;
+use crate::project_doc::project_docs_over_budget;
+use crate::project_notes::ADD_PROJECT_NOTE_TOOL_NAME;
+use crate::project_notes::MAX_PREFIX_NOTES_BYTES;
+use crate::project_notes::ProjectNotes;
+use crate::project_notes::select_for_prefix;
 use crate::protocol::AgentMessageContentDeltaEvent;
 use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::CompactedItem;
 use crate::protocol::DeprecationNoticeEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::Op;
+use crate::protocol::PROTOCOL_VERSION;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReasoningContentDeltaEvent;
 use crate::protocol::ReasoningRawContentDeltaEvent;
@@ -116,11 +209,32 @@ use crate::protocol::Submission;
 use crate::protocol::TokenCountEvent;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::protocol::TurnDeadlineExceededEvent;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnRejectedEvent;
+use crate::protocol::WarningCode;
 use crate::protocol::WarningEvent;
+use crate::reasoning_log::ReasoningLog;
+use crate::reasoning_log::strip_for_rollout;
+use crate::request_trace::RequestTraceSink;
+use crate::retry::RetryBackoff;
+use crate::rollout::RolloutLogFile;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
+use crate::rollout::anonymize::is_anonymized_cwd;
+use crate::rollout::event_log::spawn_event_log;
+use crate::rollout::event_log_path;
+use crate::rollout::live::LiveRollout;
 use crate::rollout::map_session_init_error;
+use crate::rollout::reconstruct;
+use crate::rollout::resumed_summary::resumed_summary;
+use crate::rollout::sanitize::sanitize_for_model;
+use crate::runtime_env::RuntimeEnv;
+use crate::safe_mode;
+use crate::safe_mode::last_recorded_safe_mode;
+use crate::sensitive_input::SensitiveInputGuard;
+use crate::session_stats::SessionEndReceiver;
+use crate::session_stats::SessionStatsTracker;
 use crate::shell;
 use crate::shell_snapshot::ShellSnapshot;
 use crate::skills::SkillError;
@@ -128,28 +242,55 @@ use crate::skills::SkillInjections;
 use crate::skills::SkillMetadata;
 use crate::skills::SkillsManager;
 use crate::skills::build_skill_injections;
+use crate::skills::tools::partition_tool_skills;
+use crate::spawn_observer::SpawnMilestone;
+use crate::spawn_observer::SpawnProgress;
 use crate::state::ActiveTurn;
+use crate::state::Checkpoints;
+use crate::state::Pins;
 use crate::state::SessionServices;
 use crate::state::SessionState;
+use crate::state::TaskKind;
+use crate::state::resolve_item;
+use crate::structured_output;
 use crate::tasks::GhostSnapshotTask;
+use crate::tasks::RegularTask;
 use crate::tasks::ReviewTask;
 use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
 use crate::tools::ToolRouter;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::output_cap::ToolOutputCap;
 use crate::tools::parallel::ToolCallRuntime;
+use crate::tools::read_cache::ReadCache;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::ToolsConfigParams;
+use crate::transcript_journal::JournalReplay;
+use crate::transcript_journal::TranscriptJournal;
+use crate::turn_deadline::TurnDeadline;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::turn_results::TurnResults;
+use crate::turn_results::TurnTicket;
+use crate::turn_tally::TurnTally;
+use crate::turn_tmpdir::TurnTmpdir;
 use crate::unified_exec::UnifiedExecSessionManager;
+use crate::user_context::UserContextClock;
+use crate::user_context::recorded_user_context;
 use crate::user_instructions::DeveloperInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
+use crate::workspace_watch::WorkspaceSnapshot;
+use crate::workspace_watch::workspace_changed_notice;
 use codex_async_utils::OrCancelExt;
 use codex_otel::otel_manager::OtelManager;
+use codex_protocol::config_types::ContextRecoveryStrategy;
+use codex_protocol::config_types::ExecutionMode;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_protocol::config_types::SamplingParams;
+use codex_protocol::config_types::SkillsMode;
+use codex_protocol::config_types::ToolPolicy;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
@@ -166,6 +307,31 @@ pub struct Codex {
     pub(crate) next_id: AtomicU64,
     pub(crate) tx_sub: Sender<Submission>,
     pub(crate) rx_event: Receiver<Event>,
+    pub(crate) live_rollout: Option<LiveRollout>,
+    /// Submission loop of the session, aborted if it does not shut down when
+    /// discarding a session that failed to start.
+    pub(crate) session_task: Option<AbortHandle>,
+    /// Estimated context usage, kept current by the session after each turn.
+    pub(crate) context_usage: Option<Arc<ContextUsageTracker>>,
+    /// Reasoning summaries of the session, by turn.
+    pub(crate) reasoning_log: Option<Arc<ReasoningLog>>,
+    /// Journal of the changes to the history.
+    pub(crate) transcript_journal: Option<Arc<TranscriptJournal>>,
+    /// Git context of the workspace, as last recorded.
+    pub(crate) git_context: Option<Arc<GitContextTracker>>,
+    /// Set once the session has sent [`EventMsg::SessionEnded`].
+    pub(crate) session_end: Option<SessionEndReceiver>,
+    /// Tickets of submissions awaiting their turn's result.
+    pub(crate) turn_results: Option<Arc<TurnResults>>,
+    /// Pause status of the session, see [`crate::pause`].
+    pub(crate) pause: Option<Arc<PauseState>>,
+    /// What the session adds to its manager's load, see
+    /// [`crate::manager_load`].
+    pub(crate) load: Option<Arc<ConversationLoad>>,
+    /// Filtered copies of the events, see [`crate::event_subscriptions`].
+    pub(crate) event_subscribers: Option<Arc<EventSubscribers>>,
+    /// What [`crate::CodexConversation::generate_handoff`] needs.
+    pub(crate) handoff: Option<HandoffContext>,
 }
 
 /// Wrapper returned by [`Codex::spawn`] containing the spawned [`Codex`],
@@ -178,6 +344,9 @@ pub struct CodexSpawnOk {
 
 pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 pub(crate) const SUBMISSION_CHANNEL_CAPACITY: usize = 64;
+/// Fetches of the model list attempted in the background after a session
+/// started from a fallback list.
+const MODEL_LIST_REFRESH_ATTEMPTS: u64 = 8;
 static CHAT_WIRE_API_DEPRECATION_EMITTED: AtomicBool = AtomicBool::new(false);
 
 fn maybe_push_chat_wire_api_deprecation(
@@ -201,11 +370,87 @@ fn maybe_push_chat_wire_api_deprecation(
             summary: CHAT_WIRE_API_DEPRECATION_SUMMARY.to_string(),
             details: None,
         }),
+        correlation_id: None,
+    });
+}
+
+/// Fetch the model list again, with backoff, for a session that started from
+/// a cached or built-in list, and report it once it lands. Gives up after
+/// [`MODEL_LIST_REFRESH_ATTEMPTS`] or when the session is gone.
+fn spawn_model_list_refresh(
+    models_manager: Arc<ModelsManager>,
+    config: Arc<Config>,
+    tx_event: Sender<Event>,
+) {
+    tokio::spawn(async move {
+        for attempt in 1..=MODEL_LIST_REFRESH_ATTEMPTS {
+            tokio::time::sleep(backoff(attempt)).await;
+            if tx_event.is_closed() {
+                return;
+            }
+            if let Err(err) = models_manager.fetch_remote_models().await {
+                warn!("model list refresh attempt {attempt} failed: {err}");
+                continue;
+            }
+            let models = models_manager
+                .list_models(&config)
+                .await
+                .into_iter()
+                .map(|preset| preset.model)
+                .collect();
+            let event = Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::ModelListRefreshed(ModelListRefreshedEvent { models }),
+                correlation_id: None,
+            };
+            if let Err(err) = tx_event.send(event).await {
+                debug!("session ended before the model list was refreshed: {err}");
+            }
+            return;
+        }
+    });
+}
+
+/// Warn the session when the model list loses the model it uses, see
+/// [`ModelsManager::subscribe`]. Stops once the session is gone.
+fn spawn_model_removal_watch(models_manager: &ModelsManager, session: Weak<Session>) {
+    let mut presets = models_manager.subscribe();
+    tokio::spawn(async move {
+        let mut previous = presets.borrow_and_update().clone();
+        while presets.changed().await.is_ok() {
+            let current = presets.borrow_and_update().clone();
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            let model = session
+                .state
+                .lock()
+                .await
+                .session_configuration
+                .model
+                .clone();
+            let listed = |presets: &[ModelPreset]| presets.iter().any(|p| p.model == model);
+            if listed(&previous) && !listed(&current) {
+                let suggested = current
+                    .iter()
+                    .find(|preset| preset.is_default)
+                    .map(|preset| preset.model.clone());
+                session
+                    .send_event_raw(Event {
+                        id: INITIAL_SUBMIT_ID.to_owned(),
+                        msg: EventMsg::ModelDeprecated(ModelDeprecatedEvent { model, suggested }),
+                        correlation_id: None,
+                    })
+                    .await;
+            }
+            previous = current;
+        }
     });
 }
 
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
+    #[allow(clippy::too_many_arguments)]
     pub async fn spawn(
         config: Config,
         auth_manager: Arc<AuthManager>,
@@ -213,14 +458,159 @@ impl Codex {
         skills_manager: Arc<SkillsManager>,
         conversation_history: InitialHistory,
         session_source: SessionSource,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        token_counter: Arc<dyn TokenCounter>,
+        runtime_env: RuntimeEnv,
+        parent: Option<ParentRef>,
+        progress: SpawnProgress,
+    ) -> CodexResult<CodexSpawnOk> {
+        let conversation_id = match &conversation_history {
+            InitialHistory::New | InitialHistory::Forked(_) => runtime_env.conversation_id(),
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                resumed.conversation_id
+            }
+        };
+        progress.report(conversation_id, SpawnMilestone::IdAllocated);
+        // Created up front so a spawn that hangs or fails later can still
+        // report where its rollout is.
+        let log_file = match &conversation_history {
+            InitialHistory::New | InitialHistory::Forked(_) => {
+                let log_file = RolloutLogFile::create(&config, conversation_id, &runtime_env)
+                    .map_err(|e| {
+                        error!("failed to create rollout file: {e:#}");
+                        map_session_init_error(&anyhow::Error::from(e), &config.codex_home)
+                    });
+                let log_file = match log_file {
+                    Ok(log_file) => log_file,
+                    Err(err) => {
+                        progress.failed(conversation_id, &err);
+                        return Err(err);
+                    }
+                };
+                progress.report(
+                    conversation_id,
+                    SpawnMilestone::RolloutCreated {
+                        path: log_file.path().to_path_buf(),
+                    },
+                );
+                Some(log_file)
+            }
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                progress.report(
+                    conversation_id,
+                    SpawnMilestone::RolloutCreated {
+                        path: resumed.rollout_path.clone(),
+                    },
+                );
+                None
+            }
+        };
+        let created_path = log_file
+            .as_ref()
+            .map(|log_file| log_file.path().to_path_buf());
+
+        let result = Self::spawn_session(
+            config,
+            auth_manager,
+            models_manager,
+            skills_manager,
+            conversation_history,
+            session_source,
+            pre_processors,
+            post_processors,
+            token_counter,
+            runtime_env,
+            parent,
+            conversation_id,
+            log_file,
+            &progress,
+        )
+        .await;
+        if let Err(err) = &result {
+            if let Some(path) = created_path
+                && let Err(remove_err) = tokio::fs::remove_file(&path).await
+                && remove_err.kind() != std::io::ErrorKind::NotFound
+            {
+                warn!(
+                    "failed to remove rollout {} of session {conversation_id} that failed to start: {remove_err}",
+                    path.display()
+                );
+            }
+            progress.failed(conversation_id, err);
+        }
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn spawn_session(
+        config: Config,
+        auth_manager: Arc<AuthManager>,
+        models_manager: Arc<ModelsManager>,
+        skills_manager: Arc<SkillsManager>,
+        conversation_history: InitialHistory,
+        session_source: SessionSource,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        token_counter: Arc<dyn TokenCounter>,
+        runtime_env: RuntimeEnv,
+        parent: Option<ParentRef>,
+        conversation_id: ConversationId,
+        log_file: Option<RolloutLogFile>,
+        progress: &SpawnProgress,
     ) -> CodexResult<CodexSpawnOk> {
         let (tx_sub, rx_sub) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
         let (tx_event, rx_event) = async_channel::unbounded();
 
+        if config.features.enabled(Feature::Skills) {
+            skills_manager.set_skill_packs(config.skill_packs.clone());
+            for err in skills_manager.sync().await.errors {
+                warn!("{err}");
+            }
+        }
         let loaded_skills = config
             .features
             .enabled(Feature::Skills)
-            .then(|| skills_manager.skills_for_cwd(&config.cwd));
+            .then(|| {
+                if config.include_all_skills {
+                    skills_manager.skills_for_cwd(&config.cwd)
+                } else {
+                    skills_manager.skills_for_workspace(&config.cwd)
+                }
+            })
+            .map(|mut outcome| {
+                if let Some(enabled) = &config.enabled_skills {
+                    let (kept, dropped): (Vec<_>, Vec<_>) = std::mem::take(&mut outcome.skills)
+                        .into_iter()
+                        .partition(|skill| enabled.contains(&skill.name));
+                    outcome.skills = kept;
+                    outcome
+                        .excluded
+                        .extend(dropped.into_iter().map(|skill| ExcludedSkill {
+                            name: skill.name,
+                            reason: "not listed in `enabled_skills`".to_string(),
+                        }));
+                }
+                outcome
+            });
+        let skills_selection = loaded_skills.as_ref().map(|outcome| SkillSelection {
+            included: outcome
+                .skills
+                .iter()
+                .map(|skill| skill.name.clone())
+                .collect(),
+            excluded: outcome.excluded.clone(),
+            mode: config.skills_mode,
+        });
+        // Skills offered as tools are left out of the instructions.
+        let (skill_tools, loaded_skills) = match (config.skills_mode, loaded_skills) {
+            (SkillsMode::Tools, Some(mut outcome)) => {
+                let (tools, prompt) = partition_tool_skills(std::mem::take(&mut outcome.skills));
+                outcome.skills = prompt;
+                (tools, Some(outcome))
+            }
+            (_, loaded_skills) => (Vec::new(), loaded_skills),
+        };
 
         if let Some(outcome) = &loaded_skills {
             for err in &outcome.errors {
@@ -239,18 +629,57 @@ impl Codex {
                 .map(|outcome| outcome.skills.as_slice()),
         )
         .await;
+        let instructions_watcher = InstructionsWatcher::new(
+            &config,
+            loaded_skills.as_ref().map(|outcome| outcome.skills.clone()),
+        )
+        .await;
 
         let exec_policy = ExecPolicyManager::load(&config.features, &config.config_layer_stack)
             .await
             .map_err(|err| CodexErr::Fatal(format!("failed to load execpolicy: {err}")))?;
 
+        let replayed_inputs = if config.replay_pending_inputs {
+            conversation_history.pending_inputs()
+        } else {
+            Vec::new()
+        };
+
+        let mut config = config;
+        // Resumed and forked sessions keep reporting the profile they were
+        // started with.
+        if config.session_profile.is_none() {
+            config.session_profile = conversation_history.session_profile();
+        }
         let config = Arc::new(config);
-        if config.features.enabled(Feature::RemoteModels)
-            && let Err(err) = models_manager.refresh_available_models(&config).await
+        // Retries of the fetch are reported once the session is configured.
+        let mut model_list_retries = Vec::new();
+        let model_list = models_manager
+            .resolve_available_models_with(
+                &config,
+                || {
+                    progress.report(conversation_id, SpawnMilestone::ProviderRequestIssued);
+                },
+                |retry| model_list_retries.push(retry.clone()),
+            )
+            .await;
+        models_manager.start_background_refresh(&config);
+        if model_list.degraded
+            && let Some(model) = config.model.as_deref()
+            && !models_manager.is_known_model(model, &config).await
         {
-            error!("failed to refresh available models: {err:?}");
+            return Err(CodexErr::InvalidRequest(format!(
+                "model `{model}` is not in the {} model list and the model list could not be fetched",
+                model_list.source
+            )));
         }
-        let model = models_manager.get_model(&config.model, &config).await;
+        let model = match &config.model {
+            Some(model) => model.clone(),
+            None => models_manager.default_model(&config).await,
+        };
+        let model_family = models_manager.construct_model_family(&model, &config).await;
+        sampling::validate_for_model(&config.sampling, &model_family)
+            .map_err(CodexErr::InvalidRequest)?;
         let session_configuration = SessionConfiguration {
             provider: config.model_provider.clone(),
             model: model.clone(),
@@ -262,9 +691,16 @@ impl Codex {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            execution_mode: config.execution_mode,
+            tool_policy: config.tool_policy.clone(),
+            sampling: config.sampling,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             session_source,
+            skills_selection,
+            skill_tools,
+            model_list_source: Some(model_list.source),
+            offline: config.offline,
         };
 
         // Generate a unique ID for the lifetime of this Codex session.
@@ -280,20 +716,120 @@ impl Codex {
             conversation_history,
             session_source_clone,
             skills_manager,
+            pre_processors,
+            post_processors,
+            token_counter,
+            runtime_env,
+            instructions_watcher,
+            parent,
+            conversation_id,
+            log_file,
         )
         .await
         .map_err(|e| {
             error!("Failed to create session: {e:#}");
             map_session_init_error(&e, &config.codex_home)
         })?;
-        let conversation_id = session.conversation_id;
+        let live_rollout = session.services.live_rollout.clone();
+        let context_usage = Arc::clone(&session.services.context_usage);
+        let reasoning_log = Arc::clone(&session.services.reasoning_log);
+        let transcript_journal = Arc::clone(&session.services.transcript_journal);
+        let git_context = Arc::clone(&session.services.git_context);
+        let turn_results = Arc::clone(&session.services.turn_results);
+        let pause = Arc::clone(&session.services.pause);
+        let load = Arc::clone(&session.services.load);
+        let session_end = session.services.session_stats.subscribe();
+        spawn_model_removal_watch(&models_manager, Arc::downgrade(&session));
+        for retry in model_list_retries {
+            session
+                .send_event_raw(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Retrying(retry),
+                    correlation_id: None,
+                })
+                .await;
+        }
+        if let Some(total_bytes) = project_docs_over_budget(&config) {
+            let max_bytes = config.project_doc_max_bytes;
+            let message = format!(
+                "The project docs (AGENTS.md) total {total_bytes} bytes; only the first {max_bytes} are sent to the model. Raise `project_doc_max_bytes` or shorten them."
+            );
+            session
+                .send_event_raw(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Warning(
+                        WarningEvent::new(WarningCode::InstructionsTruncated, message)
+                            .with_details(
+                                json!({ "total_bytes": total_bytes, "max_bytes": max_bytes }),
+                            )
+                            .for_conversation(),
+                    ),
+                    correlation_id: None,
+                })
+                .await;
+        }
+        if model_list.degraded {
+            let source = model_list.source.to_string();
+            session
+                .send_event_raw(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Warning(
+                        WarningEvent::new(
+                            WarningCode::ModelListFallback,
+                            format!(
+                                "The model list could not be fetched; using the {source} list until it can."
+                            ),
+                        )
+                        .with_details(json!({ "source": source }))
+                        .for_conversation(),
+                    ),
+                    correlation_id: None,
+                })
+                .await;
+            spawn_model_list_refresh(
+                Arc::clone(&models_manager),
+                Arc::clone(&config),
+                session.tx_event.clone(),
+            );
+        }
 
+        let rx_event = match (&config.event_log, session.rollout_path().await) {
+            (Some(policy), Some(rollout_path)) => spawn_event_log(
+                event_log_path(&rollout_path),
+                config.codex_home.clone(),
+                policy.clone(),
+                Arc::clone(&session.services.sensitive_input),
+                session.services.runtime_env.clone(),
+                rx_event,
+            ),
+            _ => rx_event,
+        };
+        let (rx_event, event_subscribers) = spawn_event_fanout(rx_event);
+
+        let handoff = HandoffContext {
+            config: Arc::clone(&config),
+            auth_manager,
+            model,
+        };
         // This task will run until Op::Shutdown is received.
-        tokio::spawn(submission_loop(session, config, rx_sub));
+        let session_task =
+            tokio::spawn(submission_loop(session, config, rx_sub, replayed_inputs)).abort_handle();
         let codex = Codex {
             next_id: AtomicU64::new(0),
             tx_sub,
             rx_event,
+            live_rollout,
+            session_task: Some(session_task),
+            context_usage: Some(context_usage),
+            reasoning_log: Some(reasoning_log),
+            transcript_journal: Some(transcript_journal),
+            git_context: Some(git_context),
+            session_end: Some(session_end),
+            turn_results: Some(turn_results),
+            pause: Some(pause),
+            load: Some(load),
+            event_subscribers: Some(event_subscribers),
+            handoff: Some(handoff),
         };
 
         Ok(CodexSpawnOk {
@@ -304,22 +840,70 @@ impl Codex {
 
     /// Submit the `op` wrapped in a `Submission` with a unique ID.
     pub async fn submit(&self, op: Op) -> CodexResult<String> {
+        self.submit_with_correlation(op, None).await
+    }
+
+    /// Like [`Self::submit`], echoing `correlation_id` on the events of the
+    /// submission and the turn it starts.
+    pub async fn submit_with_correlation(
+        &self,
+        op: Op,
+        correlation_id: Option<String>,
+    ) -> CodexResult<String> {
         let id = self
             .next_id
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
             .to_string();
-        let sub = Submission { id: id.clone(), op };
+        let sub = Submission {
+            id: id.clone(),
+            op,
+            correlation_id,
+        };
         self.submit_with_id(sub).await?;
         Ok(id)
     }
 
+    /// Like [`Self::submit`], returning a ticket for the result of the turn
+    /// the submission starts or joins.
+    pub async fn submit_with_ticket(&self, op: Op) -> CodexResult<TurnTicket> {
+        let Some(turn_results) = &self.turn_results else {
+            return Err(CodexErr::UnsupportedOperation(
+                "turn results are not tracked for this conversation".to_string(),
+            ));
+        };
+        let id = self
+            .next_id
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            .to_string();
+        // Tracked before sending, so a turn that ends at once still resolves it.
+        let ticket = turn_results.track(id.clone());
+        self.submit_with_id(Submission {
+            id,
+            op,
+            correlation_id: None,
+        })
+        .await?;
+        Ok(ticket)
+    }
+
     /// Use sparingly: prefer `submit()` so Codex is responsible for generating
     /// unique IDs for each submission.
     pub async fn submit_with_id(&self, sub: Submission) -> CodexResult<()> {
-        self.tx_sub
-            .send(sub)
-            .await
-            .map_err(|_| CodexErr::InternalAgentDied)?;
+        if let Some(pause) = &self.pause
+            && !pause.accepts(&sub.op)
+        {
+            return Err(CodexErr::ConversationPaused);
+        }
+        // Counted before it is sent, so the session cannot take it first.
+        if let Some(load) = &self.load {
+            load.submission_queued();
+        }
+        if self.tx_sub.send(sub).await.is_err() {
+            if let Some(load) = &self.load {
+                load.submission_taken();
+            }
+            return Err(CodexErr::InternalAgentDied);
+        }
         Ok(())
     }
 
@@ -331,6 +915,86 @@ impl Codex {
             .map_err(|_| CodexErr::InternalAgentDied)?;
         Ok(event)
     }
+
+    /// See [`crate::CodexConversation::pause`].
+    pub(crate) async fn pause(&self, mode: PauseMode) -> CodexResult<()> {
+        let pause = self.pause_state()?;
+        if pause.request()
+            && let Err(err) = self.submit(Op::Pause { mode }).await
+        {
+            pause.resume();
+            return Err(err);
+        }
+        self.wait_for_pause_status(pause, |status| {
+            matches!(status, PauseStatus::Paused | PauseStatus::Running)
+        })
+        .await
+    }
+
+    /// See [`crate::CodexConversation::resume`].
+    pub(crate) async fn resume(&self) -> CodexResult<()> {
+        let pause = self.pause_state()?;
+        if pause.status() == PauseStatus::Running {
+            return Ok(());
+        }
+        self.submit(Op::Resume).await?;
+        self.wait_for_pause_status(pause, |status| *status == PauseStatus::Running)
+            .await
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.pause
+            .as_ref()
+            .is_some_and(|pause| pause.status() == PauseStatus::Paused)
+    }
+
+    fn pause_state(&self) -> CodexResult<&PauseState> {
+        self.pause.as_deref().ok_or_else(|| {
+            CodexErr::UnsupportedOperation("this conversation cannot be paused".to_string())
+        })
+    }
+
+    /// Wait for the session to reach a pause status satisfying `done`.
+    /// Fails with [`CodexErr::InternalAgentDied`] when the session ends first.
+    async fn wait_for_pause_status(
+        &self,
+        pause: &PauseState,
+        done: impl FnMut(&PauseStatus) -> bool,
+    ) -> CodexResult<()> {
+        let ended = async {
+            match self.session_end.clone() {
+                Some(mut session_end) => {
+                    let _ = session_end.wait_for(Option::is_some).await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            () = pause.wait_for(done) => Ok(()),
+            () = ended => Err(CodexErr::InternalAgentDied),
+        }
+    }
+
+    /// Shut the session down with `reason` and wait until it has sent its
+    /// [`EventMsg::SessionEnded`]. Fails with [`CodexErr::InternalAgentDied`]
+    /// when the session stopped without ending cleanly.
+    pub(crate) async fn end_session(
+        &self,
+        reason: SessionEndReason,
+    ) -> CodexResult<SessionEndedEvent> {
+        let Some(mut session_end) = self.session_end.clone() else {
+            return Err(CodexErr::InternalAgentDied);
+        };
+        // A session that already ended refuses the submission but still
+        // reports how it ended.
+        let _ = self.submit(Op::EndSession { reason }).await;
+        session_end
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|ended| (*ended).clone())
+            .ok_or(CodexErr::InternalAgentDied)
+    }
 }
 
 /// Context for an initialized model agent
@@ -340,6 +1004,10 @@ pub(crate) struct Session {
     conversation_id: ConversationId,
     tx_event: Sender<Event>,
     state: Mutex<SessionState>,
+    /// Held while the history is changed and the change is persisted, so the
+    /// rollout records transcript mutations in the order they were applied
+    /// and replaying it rebuilds the same history. Taken before `state`.
+    transcript: Mutex<()>,
     /// The set of enabled features should be invariant for the lifetime of the
     /// session.
     features: Features,
@@ -363,6 +1031,8 @@ pub(crate) struct TurnContext {
     pub(crate) user_instructions: Option<String>,
     pub(crate) approval_policy: AskForApproval,
     pub(crate) sandbox_policy: SandboxPolicy,
+    pub(crate) execution_mode: ExecutionMode,
+    pub(crate) tool_policy: ToolPolicy,
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
     pub(crate) tools_config: ToolsConfig,
     pub(crate) ghost_snapshot: GhostSnapshotConfig,
@@ -370,9 +1040,32 @@ pub(crate) struct TurnContext {
     pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
     pub(crate) tool_call_gate: Arc<ReadinessFlag>,
     pub(crate) truncation_policy: TruncationPolicy,
+    pub(crate) turn_tmpdir: TurnTmpdir,
+    /// Smallest `read_file` output replaced by a reference when it repeats an
+    /// earlier one; `None` when the read cache is off for the turn.
+    pub(crate) read_cache_min_bytes: Option<usize>,
+    pub(crate) tool_output_cap: Option<ToolOutputCap>,
+    pub(crate) tool_timeouts: ToolTimeouts,
+    pub(crate) deadline: TurnDeadline,
 }
 
 impl TurnContext {
+    /// Environment of the commands run during the turn: the shell environment
+    /// policy plus the turn's scratch directory.
+    pub(crate) fn tool_env(&self) -> HashMap<String, String> {
+        let mut env = create_env(&self.shell_environment_policy);
+        self.turn_tmpdir.export(&mut env);
+        env
+    }
+
+    /// [`Self::tool_env`] for the commands of tool call `call_id`, which get
+    /// the call's scratch directory, see [`TurnTmpdir::for_call`].
+    pub(crate) fn call_tool_env(&self, call_id: &str) -> HashMap<String, String> {
+        let mut env = create_env(&self.shell_environment_policy);
+        self.turn_tmpdir.for_call(call_id).export(&mut env);
+        env
+    }
+
     pub(crate) fn resolve_path(&self, path: Option<String>) -> PathBuf {
         path.as_ref()
             .map(PathBuf::from)
@@ -413,6 +1106,12 @@ pub(crate) struct SessionConfiguration {
     approval_policy: Constrained<AskForApproval>,
     /// How to sandbox commands executed in the system
     sandbox_policy: Constrained<SandboxPolicy>,
+    /// Whether tool calls run or are only reported as planned.
+    execution_mode: ExecutionMode,
+    /// Tools advertised to the model.
+    tool_policy: ToolPolicy,
+    /// Sampling parameters sent with every request.
+    sampling: SamplingParams,
 
     /// Working directory that should be treated as the *root* of the
     /// session. All relative paths supplied by the model as well as the
@@ -427,6 +1126,15 @@ pub(crate) struct SessionConfiguration {
     original_config_do_not_use: Arc<Config>,
     /// Source of the session (cli, vscode, exec, mcp, ...)
     session_source: SessionSource,
+    /// Skills offered to the model at spawn and the ones left out, `None`
+    /// when skills are disabled.
+    skills_selection: Option<SkillSelection>,
+    /// Skills offered to the model as tools, see [`crate::skills::tools`].
+    skill_tools: Vec<SkillMetadata>,
+    /// Where the model list came from at spawn, `None` in tests.
+    model_list_source: Option<ModelListSource>,
+    /// Queue turns instead of calling the provider, see `Config::offline`.
+    offline: bool,
 }
 
 impl SessionConfiguration {
@@ -450,6 +1158,19 @@ impl SessionConfiguration {
         if let Some(cwd) = updates.cwd.clone() {
             next_configuration.cwd = cwd;
         }
+        if let Some(execution_mode) = updates.execution_mode {
+            next_configuration.execution_mode = execution_mode;
+        }
+        if let Some(tool_policy) = updates.tool_policy.clone() {
+            next_configuration.tool_policy = tool_policy;
+        }
+        if let Some(params) = updates.sampling {
+            sampling::check_ranges(&params)?;
+            next_configuration.sampling = params;
+        }
+        if let Some(offline) = updates.offline {
+            next_configuration.offline = offline;
+        }
         Ok(next_configuration)
     }
 }
@@ -463,6 +1184,46 @@ pub(crate) struct SessionSettingsUpdate {
     pub(crate) reasoning_effort: Option<Option<ReasoningEffortConfig>>,
     pub(crate) reasoning_summary: Option<ReasoningSummaryConfig>,
     pub(crate) final_output_json_schema: Option<Option<Value>>,
+    pub(crate) execution_mode: Option<ExecutionMode>,
+    pub(crate) tool_policy: Option<ToolPolicy>,
+    pub(crate) sampling: Option<SamplingParams>,
+    pub(crate) offline: Option<bool>,
+    /// Return every `read_file` output of the turn in full.
+    pub(crate) bypass_read_cache: bool,
+    /// Wall-clock budget of the turn, instead of `turn_deadline`.
+    pub(crate) deadline: Option<Duration>,
+}
+
+impl SessionSettingsUpdate {
+    /// Set the policies of `policies`.
+    fn from_policies(policies: &SafeModePolicies) -> Self {
+        Self {
+            approval_policy: Some(policies.approval_policy),
+            sandbox_policy: Some(policies.sandbox_policy.clone()),
+            tool_policy: Some(policies.tool_policy.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// `self` without the policy changes safe mode holds fixed.
+    fn held_by_safe_mode(&self) -> Self {
+        Self {
+            approval_policy: None,
+            sandbox_policy: None,
+            tool_policy: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl SessionConfiguration {
+    fn safe_mode_policies(&self) -> SafeModePolicies {
+        SafeModePolicies {
+            approval_policy: self.approval_policy.value(),
+            sandbox_policy: self.sandbox_policy.get().clone(),
+            tool_policy: self.tool_policy.clone(),
+        }
+    }
 }
 
 impl Session {
@@ -473,6 +1234,8 @@ impl Session {
         let mut per_turn_config = (*config).clone();
         per_turn_config.model_reasoning_effort = session_configuration.model_reasoning_effort;
         per_turn_config.model_reasoning_summary = session_configuration.model_reasoning_summary;
+        per_turn_config.sampling = session_configuration.sampling;
+        per_turn_config.offline = session_configuration.offline;
         per_turn_config.features = config.features.clone();
         per_turn_config
     }
@@ -494,6 +1257,7 @@ impl Session {
         );
 
         let per_turn_config = Arc::new(per_turn_config);
+        let tool_capabilities = provider.tool_capabilities.clone();
         let client = ModelClient::new(
             per_turn_config.clone(),
             auth_manager,
@@ -509,7 +1273,9 @@ impl Session {
         let tools_config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             features: &per_turn_config.features,
-        });
+        })
+        .with_skill_tools(session_configuration.skill_tools.clone())
+        .with_tool_capabilities(tool_capabilities);
 
         TurnContext {
             sub_id,
@@ -521,6 +1287,8 @@ impl Session {
             user_instructions: session_configuration.user_instructions.clone(),
             approval_policy: session_configuration.approval_policy.value(),
             sandbox_policy: session_configuration.sandbox_policy.get().clone(),
+            execution_mode: session_configuration.execution_mode,
+            tool_policy: session_configuration.tool_policy.clone(),
             shell_environment_policy: per_turn_config.shell_environment_policy.clone(),
             tools_config,
             ghost_snapshot: per_turn_config.ghost_snapshot.clone(),
@@ -531,12 +1299,17 @@ impl Session {
                 per_turn_config.as_ref(),
                 model_family.truncation_policy,
             ),
+            turn_tmpdir: TurnTmpdir::new(per_turn_config.as_ref()),
+            read_cache_min_bytes: per_turn_config.read_cache_min_bytes,
+            tool_output_cap: ToolOutputCap::from_config(per_turn_config.as_ref()),
+            tool_timeouts: per_turn_config.tool_timeouts.clone(),
+            deadline: TurnDeadline::new(per_turn_config.turn_deadline),
         }
     }
 
     #[allow(clippy::too_many_arguments)]
     async fn new(
-        session_configuration: SessionConfiguration,
+        mut session_configuration: SessionConfiguration,
         config: Arc<Config>,
         auth_manager: Arc<AuthManager>,
         models_manager: Arc<ModelsManager>,
@@ -545,6 +1318,14 @@ impl Session {
         initial_history: InitialHistory,
         session_source: SessionSource,
         skills_manager: Arc<SkillsManager>,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        token_counter: Arc<dyn TokenCounter>,
+        runtime_env: RuntimeEnv,
+        instructions_watcher: InstructionsWatcher,
+        parent: Option<ParentRef>,
+        conversation_id: ConversationId,
+        log_file: Option<RolloutLogFile>,
     ) -> anyhow::Result<Arc<Self>> {
         debug!(
             "Configuring session: model={}; provider={:?}",
@@ -557,22 +1338,40 @@ impl Session {
             ));
         }
 
-        let (conversation_id, rollout_params) = match &initial_history {
-            InitialHistory::New | InitialHistory::Forked(_) => {
-                let conversation_id = ConversationId::default();
-                (
-                    conversation_id,
-                    RolloutRecorderParams::new(
-                        conversation_id,
-                        session_configuration.user_instructions.clone(),
-                        session_source,
-                    ),
-                )
+        // Read before the rollout is created so its meta can list the notes
+        // the prefix carries.
+        let project_notes = ProjectNotes::new(&config.codex_home, &session_configuration.cwd);
+        let prefix_notes = if config.features.enabled(Feature::ProjectNotes) {
+            match project_notes.list().await {
+                Ok(notes) => Some(select_for_prefix(notes, MAX_PREFIX_NOTES_BYTES)),
+                Err(err) => {
+                    warn!("failed to read project notes: {err}");
+                    Some(Vec::new())
+                }
+            }
+        } else {
+            None
+        };
+
+        let rollout_params = match &initial_history {
+            InitialHistory::New | InitialHistory::Forked(_) => RolloutRecorderParams::new(
+                conversation_id,
+                session_configuration.user_instructions.clone(),
+                session_source,
+            )
+            .with_skills(session_configuration.skills_selection.clone())
+            .with_model_list_source(session_configuration.model_list_source)
+            .with_project_notes(
+                prefix_notes
+                    .as_ref()
+                    .map(|notes| notes.iter().map(|note| note.id.clone()).collect()),
+            )
+            .with_parent(parent.clone())
+            .with_log_file(log_file),
+            InitialHistory::Resumed(resumed_history)
+            | InitialHistory::ResumedEmpty(resumed_history) => {
+                RolloutRecorderParams::resume(resumed_history.rollout_path.clone())
             }
-            InitialHistory::Resumed(resumed_history) => (
-                resumed_history.conversation_id,
-                RolloutRecorderParams::resume(resumed_history.rollout_path.clone()),
-            ),
         };
 
         // Kick off independent async setup tasks in parallel to reduce startup latency.
@@ -580,7 +1379,21 @@ impl Session {
         // - initialize RolloutRecorder with new or resumed session info
         // - perform default shell discovery
         // - load history metadata
-        let rollout_fut = RolloutRecorder::new(&config, rollout_params);
+        // - collect the git context of the workspace
+        let rollout_fut = RolloutRecorder::new_with_env(&config, rollout_params, &runtime_env);
+        // A resumed session continues from the git context its rollout
+        // recorded, so a HEAD that moved while it was closed is recorded on
+        // the first turn.
+        let git_context_fut = async {
+            match &initial_history {
+                InitialHistory::New | InitialHistory::Forked(_) => {
+                    collect_git_info(&session_configuration.cwd).await
+                }
+                InitialHistory::Resumed(_) | InitialHistory::ResumedEmpty(_) => {
+                    recorded_git_context(&initial_history.get_rollout_items())
+                }
+            }
+        };
 
         let history_meta_fut = crate::message_history::history_metadata(&config);
         let auth_statuses_fut = compute_auth_statuses(
@@ -589,14 +1402,20 @@ impl Session {
         );
 
         // Join all independent futures.
-        let (rollout_recorder, (history_log_id, history_entry_count), auth_statuses) =
-            tokio::join!(rollout_fut, history_meta_fut, auth_statuses_fut);
+        let (rollout_recorder, (history_log_id, history_entry_count), auth_statuses, git_context) = tokio::join!(
+            rollout_fut,
+            history_meta_fut,
+            auth_statuses_fut,
+            git_context_fut
+        );
 
         let rollout_recorder = rollout_recorder.map_err(|e| {
             error!("failed to initialize rollout recorder: {e:#}");
             anyhow::Error::from(e)
         })?;
         let rollout_path = rollout_recorder.rollout_path.clone();
+        let rollout_failure = rollout_recorder.writer_failure();
+        let persistence_events = rollout_recorder.take_persistence_events();
 
         let mut post_session_configured_events = Vec::<Event>::new();
 
@@ -613,9 +1432,74 @@ impl Session {
             post_session_configured_events.push(Event {
                 id: INITIAL_SUBMIT_ID.to_owned(),
                 msg: EventMsg::DeprecationNotice(DeprecationNoticeEvent { summary, details }),
+                correlation_id: None,
             });
         }
         maybe_push_chat_wire_api_deprecation(&config, &mut post_session_configured_events);
+        if let Some(selection) = &session_configuration.skills_selection {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::SkillsSelected(selection.clone()),
+                correlation_id: None,
+            });
+        }
+        if let Some(parent) = &parent {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Forked(ForkedEvent {
+                    parent_id: parent.conversation_id,
+                    child_id: conversation_id,
+                    cut_ordinal: parent.cut_ordinal,
+                }),
+                correlation_id: None,
+            });
+            if let Some(report) = &parent.stale_context {
+                post_session_configured_events.push(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::ForkedContextStale(report.clone()),
+                    correlation_id: None,
+                });
+            }
+        }
+        let skipped_lines: Vec<usize> = initial_history
+            .parse_errors()
+            .iter()
+            .map(|error| error.line)
+            .collect();
+        if !skipped_lines.is_empty() {
+            let message = format!(
+                "{} lines of the recorded session could not be read and were skipped, so its history may be incomplete.",
+                skipped_lines.len()
+            );
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Warning(
+                    WarningEvent::new(WarningCode::RolloutLinesSkipped, message)
+                        .with_details(json!({ "lines": skipped_lines }))
+                        .for_conversation(),
+                ),
+                correlation_id: None,
+            });
+        }
+        let pending_inputs = initial_history.pending_inputs();
+        if !pending_inputs.is_empty() {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::PendingInputsRestored(PendingInputsRestoredEvent {
+                    inputs: pending_inputs,
+                    replayed: config.replay_pending_inputs,
+                }),
+                correlation_id: None,
+            });
+        }
+
+        if let Some(recovered) = initial_history.interrupted_turn() {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::InterruptedTurnRecovered(recovered),
+                correlation_id: None,
+            });
+        }
 
         // todo(aibrahim): why are we passing model here while it can change?
         let otel_manager = OtelManager::new(
@@ -650,28 +1534,86 @@ impl Session {
                     .await
                     .map(Arc::new);
         }
-        let state = SessionState::new(session_configuration.clone());
+        if let InitialHistory::Resumed(resumed_history) = &initial_history
+            && let Some(tool_policy) = last_recorded_tool_policy(&resumed_history.history)
+        {
+            session_configuration.tool_policy = tool_policy;
+        }
+        let mut safe_mode = None;
+        if let InitialHistory::Resumed(resumed_history) = &initial_history
+            && let Some(switch) = last_recorded_safe_mode(&resumed_history.history)
+        {
+            match session_configuration.apply(&SessionSettingsUpdate::from_policies(&switch.after))
+            {
+                Ok(next) => {
+                    session_configuration = next;
+                    safe_mode = Some(switch.before);
+                }
+                Err(err) => warn!("could not restore safe mode: {err}"),
+            }
+        }
+        let mut state = SessionState::new(session_configuration.clone());
+        state.safe_mode = safe_mode;
 
         let services = SessionServices {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: UserNotifier::new(config.notify.clone()),
-            rollout: Mutex::new(Some(rollout_recorder)),
+            rollout: Mutex::new(Some(rollout_recorder.clone())),
+            live_rollout: Some(LiveRollout::new(rollout_recorder)),
             user_shell: Arc::new(default_shell),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            min_warning_severity: config.min_warning_severity,
+            adaptive_effort: config.adaptive_effort.clone(),
             exec_policy,
             auth_manager: Arc::clone(&auth_manager),
             otel_manager,
             models_manager: Arc::clone(&models_manager),
             tool_approvals: Mutex::new(ApprovalStore::default()),
+            project_approvals: ProjectApprovalStore::new(&config.codex_home),
+            project_notes,
+            project_notes_prefix: prefix_notes
+                .as_deref()
+                .and_then(crate::project_notes::prefix_item),
+            pre_processors,
+            post_processors,
             skills_manager,
+            request_trace: config
+                .request_trace
+                .clone()
+                .map(|target| RequestTraceSink::new(target, conversation_id)),
+            token_counter,
+            context_usage: Arc::new(ContextUsageTracker::new(
+                config.context_usage_thresholds.clone(),
+            )),
+            sensitive_input: Arc::new(SensitiveInputGuard::new(config.sensitive_input.as_ref())),
+            reasoning_log: Arc::new(ReasoningLog::default()),
+            transcript_journal: Arc::new(TranscriptJournal::default()),
+            git_context: Arc::new(GitContextTracker::new(git_context)),
+            persist_reasoning_summaries: config.persist_reasoning_summaries,
+            record_input_rejections: config.record_input_rejections,
+            user_context: config
+                .user_context
+                .clone()
+                .or_else(|| recorded_user_context(&initial_history.get_rollout_items()))
+                .map(|context| UserContextClock::new(context, &runtime_env)),
+            instructions_watcher,
+            session_stats: SessionStatsTracker::new(runtime_env.clone()),
+            correlation_ids: CorrelationIds::default(),
+            turn_results: Arc::new(TurnResults::default()),
+            pause: Arc::default(),
+            load: Arc::default(),
+            read_cache: ReadCache::default(),
+            blobs: BlobStore::new(&config.codex_home),
+            runtime_env,
         };
 
         let sess = Arc::new(Session {
             conversation_id,
             tx_event: tx_event.clone(),
             state: Mutex::new(state),
+            transcript: Mutex::new(()),
             features: config.features.clone(),
             active_turn: Mutex::new(None),
             services,
@@ -681,10 +1623,32 @@ impl Session {
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
         let initial_messages = initial_history.get_event_msgs();
+        let unknown_rollout_items = initial_history.unknown_item_count();
+        let (initial_history, sanitized) = sanitize_for_model(
+            initial_history,
+            &models_manager
+                .construct_model_family(&session_configuration.model, &config)
+                .await,
+        );
+        let (dropped, trim) = sess.fit_resumed_history(&initial_history).await.unzip();
+        let resumed = resumed_summary(
+            &initial_history,
+            sanitized.warnings(&session_configuration.model),
+            trim.clone(),
+        )
+        .await;
+        let trimmed_event = trim
+            .filter(|report| report.turns_dropped > 0)
+            .map(|report| Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::HistoryTrimmedOnResume(report),
+                correlation_id: None,
+            });
         let events = std::iter::once(Event {
             id: INITIAL_SUBMIT_ID.to_owned(),
             msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
                 session_id: conversation_id,
+                protocol_version: PROTOCOL_VERSION,
                 model: session_configuration.model.clone(),
                 model_provider_id: config.model_provider_id.clone(),
                 approval_policy: session_configuration.approval_policy.value(),
@@ -695,12 +1659,22 @@ impl Session {
                 history_entry_count,
                 initial_messages,
                 rollout_path,
+                unknown_rollout_items,
+                session_profile: config.session_profile.clone(),
+                resumed,
             }),
+            correlation_id: None,
         })
+        .chain(trimmed_event)
         .chain(post_session_configured_events.into_iter());
         for event in events {
             sess.send_event_raw(event).await;
         }
+        sess.relay_auth_notifications();
+        sess.relay_rollout_failure(rollout_failure);
+        if let Some(persistence_events) = persistence_events {
+            sess.relay_persistence_events(persistence_events);
+        }
 
         // Construct sandbox_state before initialize() so it can be sent to each
         // MCP server immediately after it becomes ready (avoiding blocking).
@@ -724,28 +1698,149 @@ impl Session {
             .await;
 
         // record_initial_history can emit events. We record only after the SessionConfiguredEvent is emitted.
-        sess.record_initial_history(initial_history).await;
+        let has_history = !matches!(initial_history, InitialHistory::New);
+        // The client still gets every turn in `initial_messages`; only the
+        // model's history leaves the dropped ones out.
+        sess.record_initial_history(initial_history, dropped.flatten())
+            .await;
+        if has_history {
+            let turn_context = sess.new_default_turn().await;
+            sess.update_context_usage(turn_context.as_ref()).await;
+        }
 
         Ok(sess)
     }
 
-    pub(crate) fn get_tx_event(&self) -> Sender<Event> {
-        self.tx_event.clone()
-    }
-
-    /// Ensure all rollout writes are durably flushed.
+    /// Pass failed proactive token refreshes on to the client, so the user
+    /// hears about them before requests start failing.
+    fn relay_auth_notifications(self: &Arc<Self>) {
+        let mut notifications = self.services.auth_manager.subscribe();
+        let session = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                match notifications.recv().await {
+                    Ok(AuthNotification::RefreshFailed(failed)) => {
+                        let Some(session) = session.upgrade() else {
+                            return;
+                        };
+                        session
+                            .send_event_raw(Event {
+                                id: INITIAL_SUBMIT_ID.to_owned(),
+                                msg: EventMsg::AuthRefreshFailed(failed),
+                                correlation_id: None,
+                            })
+                            .await;
+                    }
+                    Ok(AuthNotification::Changed) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Warn the client once the rollout writer fails, since nothing after
+    /// that is recorded and the session cannot be resumed past it.
+    fn relay_rollout_failure(
+        self: &Arc<Self>,
+        failure: impl Future<Output = Option<String>> + Send + 'static,
+    ) {
+        let session = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let Some(error) = failure.await else {
+                return;
+            };
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            session
+                .send_event_raw(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Warning(
+                        WarningEvent::new(
+                            WarningCode::RecordingStopped,
+                            format!(
+                                "The session is no longer being recorded: {error}. It cannot be resumed past this point."
+                            ),
+                        )
+                        .for_conversation(),
+                    ),
+                    correlation_id: None,
+                })
+                .await;
+        });
+    }
+
+    /// Send the events the rollout writer reports while the disk is full.
+    fn relay_persistence_events(self: &Arc<Self>, mut events: mpsc::UnboundedReceiver<EventMsg>) {
+        let session = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(msg) = events.recv().await {
+                let Some(session) = session.upgrade() else {
+                    return;
+                };
+                session
+                    .send_event_raw(Event {
+                        id: INITIAL_SUBMIT_ID.to_owned(),
+                        msg,
+                        correlation_id: None,
+                    })
+                    .await;
+            }
+        });
+    }
+
+    pub(crate) fn get_tx_event(&self) -> Sender<Event> {
+        self.tx_event.clone()
+    }
+
+    /// The `correlation_id` of the submission `sub_id`, for events sent
+    /// without [`Self::send_event_raw`].
+    pub(crate) fn correlation_id(&self, sub_id: &str) -> Option<String> {
+        self.services.correlation_ids.get(sub_id)
+    }
+
+    async fn rollout_path(&self) -> Option<PathBuf> {
+        let guard = self.services.rollout.lock().await;
+        guard.as_ref().map(|rec| rec.rollout_path.clone())
+    }
+
+    /// Ensure all rollout writes reached the file, so readers see them.
+    /// Syncing to disk is left to the recorder's fsync policy.
     pub(crate) async fn flush_rollout(&self) {
         let recorder = {
             let guard = self.services.rollout.lock().await;
             guard.clone()
         };
         if let Some(rec) = recorder
-            && let Err(e) = rec.flush().await
+            && let Err(e) = rec.flush_written().await
         {
             warn!("failed to flush rollout recorder: {e}");
         }
     }
 
+    /// Complete a pause that waits for the running turn, once no turn is
+    /// running. See [`crate::pause`].
+    pub(crate) async fn finish_pending_pause(&self) {
+        if self.active_turn.lock().await.is_some() {
+            return;
+        }
+        let Some(event) = self.services.pause.take_pending() else {
+            return;
+        };
+        self.send_event_raw(event).await;
+        let recorder = self.services.rollout.lock().await.clone();
+        if let Some(recorder) = recorder
+            && let Err(e) = recorder.flush().await
+        {
+            warn!("failed to flush rollout before pausing: {e}");
+        }
+        // Counted before the conversation sees the pause complete.
+        self.services.load.set_paused(true);
+        if !self.services.pause.complete() {
+            self.services.load.set_paused(false);
+        }
+    }
+
     fn next_internal_sub_id(&self) -> String {
         let id = self
             .next_internal_sub_id
@@ -758,10 +1853,17 @@ impl Session {
         state.get_total_token_usage()
     }
 
-    async fn record_initial_history(&self, conversation_history: InitialHistory) {
+    /// Seed the session from `conversation_history`, leaving the items of
+    /// `dropped` out of the rebuilt history, see [`Self::fit_resumed_history`].
+    async fn record_initial_history(
+        &self,
+        conversation_history: InitialHistory,
+        dropped: Option<Range<usize>>,
+    ) {
         let turn_context = self.new_default_turn().await;
         match conversation_history {
-            InitialHistory::New => {
+            // A rollout without turns continues as a new session in its file.
+            InitialHistory::New | InitialHistory::ResumedEmpty(_) => {
                 // Build and record initial items (user instructions + environment context)
                 let items = self.build_initial_context(&turn_context);
                 self.record_conversation_items(&turn_context, &items).await;
@@ -771,6 +1873,7 @@ impl Session {
             InitialHistory::Resumed(_) | InitialHistory::Forked(_) => {
                 let rollout_items = conversation_history.get_rollout_items();
                 let persist = matches!(conversation_history, InitialHistory::Forked(_));
+                self.services.reasoning_log.seed(&rollout_items);
 
                 // If resuming, warn when the last recorded model differs from the current one.
                 if let InitialHistory::Resumed(_) = conversation_history
@@ -789,31 +1892,81 @@ impl Session {
                         );
                         self.send_event(
                             &turn_context,
-                            EventMsg::Warning(WarningEvent {
-                                message: format!(
-                                    "This session was recorded with model `{prev}` but is resuming with `{curr}`. \
+                            EventMsg::Warning(
+                                WarningEvent::new(
+                                    WarningCode::ModelChangedOnResume,
+                                    format!(
+                                        "This session was recorded with model `{prev}` but is resuming with `{curr}`. \
                          Consider switching back to `{prev}` as it may affect Codex performance."
-                                ),
-                            }),
+                                    ),
+                                )
+                                .with_details(json!({ "previous": prev, "current": curr }))
+                                .for_conversation(),
+                            ),
                         )
                             .await;
                     }
                 }
 
+                if let InitialHistory::Resumed(_) = conversation_history
+                    && rollout_items.iter().any(|item| {
+                        matches!(item, RolloutItem::SessionMeta(meta) if is_anonymized_cwd(&meta.meta.cwd))
+                    })
+                {
+                    self.send_event(
+                        &turn_context,
+                        EventMsg::Warning(
+                            WarningEvent::new(
+                                WarningCode::AnonymizedSession,
+                                "This session was anonymized: paths, names and email addresses \
+                                 were replaced with placeholders such as `<REPO>`, so the files \
+                                 it refers to may not exist here.",
+                            )
+                            .for_conversation(),
+                        ),
+                    )
+                    .await;
+                }
+
                 // Always add response items to conversation history
-                let reconstructed_history =
+                let (mut reconstructed_history, mut checkpoints, mut pins, journal) =
                     self.reconstruct_history_from_rollout(&turn_context, &rollout_items);
-                if !reconstructed_history.is_empty() {
-                    self.record_into_history(&reconstructed_history, &turn_context)
-                        .await;
+                // Dropped turns stay in the rollout, so later rollbacks and
+                // mutations were replayed against them already.
+                if let Some(dropped) = dropped {
+                    reconstructed_history.drain(dropped.clone());
+                    checkpoints.drop_items(dropped);
+                    pins.retain_present(&reconstructed_history);
                 }
+                let mutation = {
+                    let _transcript = self.transcript.lock().await;
+                    if !reconstructed_history.is_empty() {
+                        self.record_into_history(&reconstructed_history, &turn_context)
+                            .await;
+                    }
+                    let mut state = self.state.lock().await;
+                    state.checkpoints = checkpoints;
+                    state.pins = pins;
+                    state.last_turn_id = last_turn_id(&rollout_items);
+                    state.recorded_segments = reconstruct::recorded_segments(&rollout_items);
+                    self.services.transcript_journal.seed(journal);
+                    self.services
+                        .transcript_journal
+                        .record(TranscriptMutationKind::Resumed, state.history.items())
+                        .map(RolloutItem::TranscriptMutation)
+                };
 
                 // If persisting, persist all rollout items as-is (recorder filters)
                 if persist && !rollout_items.is_empty() {
                     self.persist_rollout_items(&rollout_items).await;
                 }
+                if let Some(mutation) = mutation {
+                    self.persist_rollout_items(&[mutation]).await;
+                }
                 // Flush after seeding history and any persisted rollout copy.
                 self.flush_rollout().await;
+                let _transcript = self.transcript.lock().await;
+                self.spill_transcript().await;
             }
         }
     }
@@ -823,9 +1976,19 @@ impl Session {
         updates: SessionSettingsUpdate,
     ) -> ConstraintResult<()> {
         let mut state = self.state.lock().await;
+        let updates = if state.safe_mode.is_some() {
+            updates.held_by_safe_mode()
+        } else {
+            updates
+        };
 
         match state.session_configuration.apply(&updates) {
             Ok(updated) => {
+                if state.session_configuration.model_reasoning_effort
+                    != updated.model_reasoning_effort
+                {
+                    state.effort_adapter.reset();
+                }
                 state.session_configuration = updated;
                 Ok(())
             }
@@ -836,6 +1999,104 @@ impl Session {
         }
     }
 
+    pub(crate) async fn in_safe_mode(&self) -> bool {
+        self.state.lock().await.safe_mode.is_some()
+    }
+
+    /// Switch to the policies of safe mode, denying `mcp_tools` as well.
+    /// `None` when safe mode is already on.
+    pub(crate) async fn enter_safe_mode(
+        &self,
+        mcp_tools: Vec<String>,
+    ) -> ConstraintResult<Option<SafeModeChangedEvent>> {
+        let mut state = self.state.lock().await;
+        if state.safe_mode.is_some() {
+            return Ok(None);
+        }
+        let before = state.session_configuration.safe_mode_policies();
+        let after = safe_mode::safe_policies(&before, mcp_tools);
+        state.session_configuration = state
+            .session_configuration
+            .apply(&SessionSettingsUpdate::from_policies(&after))?;
+        state.safe_mode = Some(before.clone());
+        Ok(Some(SafeModeChangedEvent {
+            enabled: true,
+            before,
+            after,
+        }))
+    }
+
+    /// Restore the policies safe mode replaced. `None` when safe mode is
+    /// off.
+    pub(crate) async fn leave_safe_mode(&self) -> ConstraintResult<Option<SafeModeChangedEvent>> {
+        let mut state = self.state.lock().await;
+        let Some(restored) = state.safe_mode.clone() else {
+            return Ok(None);
+        };
+        let before = state.session_configuration.safe_mode_policies();
+        state.session_configuration = state
+            .session_configuration
+            .apply(&SessionSettingsUpdate::from_policies(&restored))?;
+        state.safe_mode = None;
+        Ok(Some(SafeModeChangedEvent {
+            enabled: false,
+            before,
+            after: restored,
+        }))
+    }
+
+    /// Ask the user to approve leaving safe mode, as a command approval so
+    /// that every client can answer it. `None` when safe mode is off.
+    pub(crate) async fn request_safe_mode_exit(
+        &self,
+        sub_id: &str,
+    ) -> Option<oneshot::Receiver<ReviewDecision>> {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let request = {
+            let mut state = self.state.lock().await;
+            let restored = state.safe_mode.as_ref()?;
+            let request = safe_mode::exit_approval_request(
+                sub_id,
+                state.session_configuration.cwd.clone(),
+                restored,
+            );
+            if state
+                .safe_mode_exit
+                .replace((sub_id.to_string(), tx_approve))
+                .is_some()
+            {
+                warn!("Overwriting the pending approval to leave safe mode");
+            }
+            request
+        };
+        self.send_event_raw(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::ExecApprovalRequest(request),
+            correlation_id: None,
+        })
+        .await;
+        Some(rx_approve)
+    }
+
+    /// Deliver `decision` when `sub_id` is the pending approval to leave
+    /// safe mode. Returns whether it was.
+    async fn resolve_safe_mode_exit(&self, sub_id: &str, decision: &ReviewDecision) -> bool {
+        let pending = {
+            let mut state = self.state.lock().await;
+            match &state.safe_mode_exit {
+                Some((pending_id, _)) if pending_id == sub_id => state.safe_mode_exit.take(),
+                _ => None,
+            }
+        };
+        match pending {
+            Some((_, tx_approve)) => {
+                tx_approve.send(decision.clone()).ok();
+                true
+            }
+            None => false,
+        }
+    }
+
     pub(crate) async fn new_turn_with_sub_id(
         &self,
         sub_id: String,
@@ -843,10 +2104,22 @@ impl Session {
     ) -> ConstraintResult<Arc<TurnContext>> {
         let (session_configuration, sandbox_policy_changed) = {
             let mut state = self.state.lock().await;
-            match state.session_configuration.clone().apply(&updates) {
+            let applied = if state.safe_mode.is_some() {
+                state
+                    .session_configuration
+                    .apply(&updates.held_by_safe_mode())
+            } else {
+                state.session_configuration.apply(&updates)
+            };
+            match applied {
                 Ok(next) => {
                     let sandbox_policy_changed =
                         state.session_configuration.sandbox_policy != next.sandbox_policy;
+                    if state.session_configuration.model_reasoning_effort
+                        != next.model_reasoning_effort
+                    {
+                        state.effort_adapter.reset();
+                    }
                     state.session_configuration = next.clone();
                     (next, sandbox_policy_changed)
                 }
@@ -858,6 +2131,7 @@ impl Session {
                             message: err.to_string(),
                             codex_error_info: Some(CodexErrorInfo::BadRequest),
                         }),
+                        correlation_id: None,
                     })
                     .await;
                     return Err(err);
@@ -871,6 +2145,8 @@ impl Session {
                 session_configuration,
                 updates.final_output_json_schema,
                 sandbox_policy_changed,
+                updates.bypass_read_cache,
+                updates.deadline,
             )
             .await)
     }
@@ -878,10 +2154,15 @@ impl Session {
     async fn new_turn_from_configuration(
         &self,
         sub_id: String,
-        session_configuration: SessionConfiguration,
+        mut session_configuration: SessionConfiguration,
         final_output_json_schema: Option<Option<Value>>,
         sandbox_policy_changed: bool,
+        bypass_read_cache: bool,
+        deadline: Option<Duration>,
     ) -> Arc<TurnContext> {
+        if let Some(effort) = self.state.lock().await.effort_adapter.effort_override() {
+            session_configuration.model_reasoning_effort = Some(effort);
+        }
         let per_turn_config = Self::build_per_turn_config(&session_configuration);
 
         if sandbox_policy_changed {
@@ -907,9 +2188,13 @@ impl Session {
             .models_manager
             .construct_model_family(session_configuration.model.as_str(), &per_turn_config)
             .await;
+        let otel_manager = self
+            .services
+            .otel_manager
+            .with_correlation_id(self.services.correlation_ids.get(&sub_id));
         let mut turn_context: TurnContext = Self::make_turn_context(
             Some(Arc::clone(&self.services.auth_manager)),
-            &self.services.otel_manager,
+            &otel_manager,
             session_configuration.provider.clone(),
             &session_configuration,
             per_turn_config,
@@ -920,9 +2205,94 @@ impl Session {
         if let Some(final_schema) = final_output_json_schema {
             turn_context.final_output_json_schema = final_schema;
         }
+        if bypass_read_cache {
+            turn_context.read_cache_min_bytes = None;
+        }
+        if deadline.is_some() {
+            turn_context.deadline = TurnDeadline::new(deadline);
+        }
+        turn_context
+            .client
+            .set_endpoint_health(self.services.models_manager.endpoint_health());
+        if let Some(request_trace) = &self.services.request_trace {
+            turn_context
+                .client
+                .set_request_tracer(request_trace.next_turn());
+        }
         Arc::new(turn_context)
     }
 
+    /// Read the instruction files again before a turn starts, as
+    /// `instructions_refresh` asks. New instructions are swapped into the
+    /// history and the session configuration, the swap is recorded in the
+    /// rollout, and the turn context is rebuilt from them.
+    async fn refresh_instructions(&self, turn_context: Arc<TurnContext>) -> Arc<TurnContext> {
+        let session_configuration = {
+            let state = self.state.lock().await;
+            state.session_configuration.clone()
+        };
+        // The instructions depend on the cwd alone of what a turn overrides.
+        let config = &session_configuration.original_config_do_not_use;
+        let config = if config.cwd == session_configuration.cwd {
+            Cow::Borrowed(config.as_ref())
+        } else {
+            Cow::Owned(Config {
+                cwd: session_configuration.cwd.clone(),
+                ..Config::clone(config)
+            })
+        };
+        let Some(reloaded) = self.services.instructions_watcher.check(&config).await else {
+            return turn_context;
+        };
+
+        let instructions = reloaded.user_instructions.clone().map(|text| {
+            ResponseItem::from(UserInstructions {
+                text,
+                directory: session_configuration.cwd.to_string_lossy().into_owned(),
+            })
+        });
+        let session_configuration = {
+            let _transcript = self.transcript.lock().await;
+            let session_configuration = {
+                let mut state = self.state.lock().await;
+                state.session_configuration.user_instructions = reloaded.user_instructions;
+                state.replace_user_instructions(instructions.clone());
+                state.session_configuration.clone()
+            };
+            let mutation = self
+                .journal_mutation(TranscriptMutationKind::InstructionsReplaced)
+                .await;
+            let rollout_items: Vec<RolloutItem> = std::iter::once(
+                RolloutItem::InstructionsReplaced(InstructionsReplacedItem {
+                    sources: reloaded.sources.clone(),
+                    instructions,
+                }),
+            )
+            .chain(mutation)
+            .collect();
+            self.persist_turn_rollout_items(&turn_context.sub_id, &rollout_items)
+                .await;
+            session_configuration
+        };
+        self.send_event(
+            &turn_context,
+            EventMsg::InstructionsReloaded(InstructionsReloadedEvent {
+                sources: reloaded.sources,
+            }),
+        )
+        .await;
+
+        self.new_turn_from_configuration(
+            turn_context.sub_id.clone(),
+            session_configuration,
+            Some(turn_context.final_output_json_schema.clone()),
+            false,
+            turn_context.read_cache_min_bytes.is_none(),
+            turn_context.deadline.budget(),
+        )
+        .await
+    }
+
     pub(crate) async fn new_default_turn(&self) -> Arc<TurnContext> {
         self.new_default_turn_with_sub_id(self.next_internal_sub_id())
             .await
@@ -933,7 +2303,7 @@ impl Session {
             let state = self.state.lock().await;
             state.session_configuration.clone()
         };
-        self.new_turn_from_configuration(sub_id, session_configuration, None, false)
+        self.new_turn_from_configuration(sub_id, session_configuration, None, false, false, None)
             .await
     }
 
@@ -960,9 +2330,12 @@ impl Session {
     /// Persist the event to rollout and send it to clients.
     pub(crate) async fn send_event(&self, turn_context: &TurnContext, msg: EventMsg) {
         let legacy_source = msg.clone();
+        // Looked up once: sending the event can end the submission's id.
+        let correlation_id = self.services.correlation_ids.get(&turn_context.sub_id);
         let event = Event {
             id: turn_context.sub_id.clone(),
             msg,
+            correlation_id: correlation_id.clone(),
         };
         self.send_event_raw(event).await;
 
@@ -971,15 +2344,32 @@ impl Session {
             let legacy_event = Event {
                 id: turn_context.sub_id.clone(),
                 msg: legacy,
+                correlation_id: correlation_id.clone(),
             };
             self.send_event_raw(legacy_event).await;
         }
     }
 
-    pub(crate) async fn send_event_raw(&self, event: Event) {
+    /// Send `event`, tagged with the `correlation_id` of its submission unless
+    /// it already has one.
+    /// Warnings below `Config::min_warning_severity` are dropped.
+    pub(crate) async fn send_event_raw(&self, mut event: Event) {
+        if let EventMsg::Warning(warning) = &event.msg
+            && warning.severity < self.services.min_warning_severity
+        {
+            debug!("suppressed {:?} warning: {}", warning.code, warning.message);
+            return;
+        }
+        self.services.session_stats.observe(&event.msg);
+        if event.correlation_id.is_none() {
+            event.correlation_id = self.services.correlation_ids.get(&event.id);
+        }
+        self.services.correlation_ids.observe(&event.id, &event.msg);
+        self.services.turn_results.observe(&event.id, &event.msg);
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
-        self.persist_rollout_items(&rollout_items).await;
+        self.persist_correlated_rollout_items(&rollout_items, event.correlation_id.clone())
+            .await;
         if let Err(e) = self.tx_event.send(event).await {
             error!("failed to send tool call event: {e}");
         }
@@ -1046,7 +2436,9 @@ impl Session {
     ///
     /// The request is keyed by `sub_id`/`call_id` so matching responses are delivered
     /// to the correct in-flight turn. If the task is aborted, this returns the
-    /// default `ReviewDecision` (`Denied`).
+    /// default `ReviewDecision` (`Denied`). Commands covered by a rule granted
+    /// for the project are approved without emitting a request, except in
+    /// safe mode.
     #[allow(clippy::too_many_arguments)]
     pub async fn request_command_approval(
         &self,
@@ -1056,6 +2448,100 @@ impl Session {
         cwd: PathBuf,
         reason: Option<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+    ) -> ReviewDecision {
+        let scope = ProjectScope::resolve(&cwd).await;
+        // Safe mode asks for every command and saves no rule that would
+        // outlast it.
+        let safe_mode = self.in_safe_mode().await;
+        if !safe_mode
+            && self
+                .services
+                .project_approvals
+                .is_allowed(&scope, &command)
+                .await
+        {
+            return ReviewDecision::Approved;
+        }
+        let proposed_execpolicy_amendment = proposed_execpolicy_amendment.filter(|_| !safe_mode);
+
+        let parsed_cmd = parse_command(&command);
+        let decision = self
+            .await_exec_approval(
+                turn_context,
+                ExecApprovalRequestEvent {
+                    call_id,
+                    turn_id: turn_context.sub_id.clone(),
+                    command: command.clone(),
+                    cwd,
+                    reason,
+                    proposed_execpolicy_amendment,
+                    parsed_cmd,
+                },
+            )
+            .await;
+        // The rule comes from the client; one that does not cover the
+        // command it answers is not saved, and the command runs once.
+        if let ReviewDecision::ApprovedForProject { rule } = &decision
+            && !rule_covers(rule, &command)
+        {
+            let message = format!(
+                "Not saving project approval rule {rule:?}: it does not cover `{}`",
+                shlex_join(&command)
+            );
+            warn!("{message}");
+            let warning = WarningEvent::new(WarningCode::ProjectApprovalNotSaved, message);
+            self.send_event(turn_context, EventMsg::Warning(warning))
+                .await;
+            return ReviewDecision::Approved;
+        }
+        if let ReviewDecision::ApprovedForProject { rule } = &decision
+            && !safe_mode
+            && let Err(err) = self
+                .services
+                .project_approvals
+                .grant(&scope, rule.clone())
+                .await
+        {
+            let message = format!("Failed to save project approval rule: {err}");
+            warn!("{message}");
+            let warning = WarningEvent::new(WarningCode::ProjectApprovalNotSaved, message);
+            self.send_event(turn_context, EventMsg::Warning(warning))
+                .await;
+        }
+        decision
+    }
+
+    /// Ask the user to approve saving `note` for the project, as a command
+    /// approval so that every client can answer it.
+    pub(crate) async fn request_note_approval(
+        &self,
+        turn_context: &TurnContext,
+        call_id: String,
+        note: &str,
+    ) -> ReviewDecision {
+        let command = vec![ADD_PROJECT_NOTE_TOOL_NAME.to_string(), note.to_string()];
+        let cmd = shlex_join(&command);
+        self.await_exec_approval(
+            turn_context,
+            ExecApprovalRequestEvent {
+                call_id,
+                turn_id: turn_context.sub_id.clone(),
+                command,
+                cwd: turn_context.cwd.clone(),
+                reason: Some(format!(
+                    "Save this note for future sessions in this project: {note}"
+                )),
+                proposed_execpolicy_amendment: None,
+                parsed_cmd: vec![ParsedCommand::Unknown { cmd }],
+            },
+        )
+        .await
+    }
+
+    async fn await_exec_approval(
+        &self,
+        turn_context: &TurnContext,
+        request: ExecApprovalRequestEvent,
     ) -> ReviewDecision {
         let sub_id = turn_context.sub_id.clone();
         // Add the tx_approve callback to the map before sending the request.
@@ -1075,17 +2561,8 @@ impl Session {
             warn!("Overwriting existing pending approval for sub_id: {event_id}");
         }
 
-        let parsed_cmd = parse_command(&command);
-        let event = EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
-            call_id,
-            turn_id: turn_context.sub_id.clone(),
-            command,
-            cwd,
-            reason,
-            proposed_execpolicy_amendment,
-            parsed_cmd,
-        });
-        self.send_event(turn_context, event).await;
+        self.send_event(turn_context, EventMsg::ExecApprovalRequest(request))
+            .await;
         rx_approve.await.unwrap_or_default()
     }
 
@@ -1168,17 +2645,101 @@ impl Session {
         turn_context: &TurnContext,
         items: &[ResponseItem],
     ) {
-        self.record_into_history(items, turn_context).await;
-        self.persist_rollout_response_items(items).await;
+        {
+            let _transcript = self.transcript.lock().await;
+            self.record_into_history(items, turn_context).await;
+            let mutation = self.journal_mutation(TranscriptMutationKind::Append).await;
+            self.persist_rollout_response_items(&turn_context.sub_id, items, mutation)
+                .await;
+        }
+        self.services.reasoning_log.record(items);
         self.send_raw_response_items(turn_context, items).await;
     }
 
+    /// Journal the change just made to the history, see
+    /// [`TranscriptJournal`]. Returns the entry to persist after the items of
+    /// the change. Callers hold `self.transcript`.
+    async fn journal_mutation(&self, kind: TranscriptMutationKind) -> Option<RolloutItem> {
+        let state = self.state.lock().await;
+        self.services
+            .transcript_journal
+            .record(kind, state.history.items())
+            .map(RolloutItem::TranscriptMutation)
+    }
+
+    /// Fit the history a rollout resumes with into the model's context
+    /// window, see [`fit_to_context_window`]. The range to drop is of the
+    /// history rebuilt from the rollout, so turns rolled back or otherwise
+    /// rewritten by the rollout are not counted. `None` for histories that
+    /// are not resumed.
+    async fn fit_resumed_history(
+        &self,
+        history: &InitialHistory,
+    ) -> Option<(Option<Range<usize>>, ResumeTrimReport)> {
+        let InitialHistory::Resumed(resumed) = history else {
+            return None;
+        };
+        let turn_context = self.new_default_turn().await;
+        let (items, _, _, _) =
+            self.reconstruct_history_from_rollout(&turn_context, &resumed.history);
+        let counter = self.services.token_counter.as_ref();
+        Some(fit_to_context_window(
+            &items,
+            turn_context.client.get_model_context_window(),
+            estimate_base_tokens(&turn_context, counter),
+            |item| estimate_item_tokens(item, counter),
+        ))
+    }
+
+    /// Drop the oldest turns of the history, keeping the current one, so the
+    /// prompt fits the model context window. At least one turn goes, since
+    /// the provider rejected the prompt whatever the estimate says. Returns
+    /// how many turns were dropped, `None` when only the current turn is left.
+    async fn drop_oldest_turns(&self, turn_context: &TurnContext) -> Option<usize> {
+        let mut history = self.clone_history().await.get_history();
+        let positions = user_message_positions(&history);
+        if positions.len() < 2 {
+            return None;
+        }
+        let counter = self.services.token_counter.as_ref();
+        let (range, report) = fit_to_context_window(
+            &history,
+            turn_context.client.get_model_context_window(),
+            estimate_base_tokens(turn_context, counter),
+            |item| estimate_item_tokens(item, counter),
+        );
+        let (range, dropped_turns) = match range {
+            Some(range) => (range, report.turns_dropped),
+            None => (positions[0]..positions[1], 1),
+        };
+        history.drain(range);
+
+        // The replacement is recorded in full so a resume starts from it.
+        self.replace_compacted_history(
+            history.clone(),
+            CompactedItem {
+                message: String::new(),
+                replacement_history: Some(history),
+            },
+            TranscriptMutationKind::ContextTruncation { dropped_turns },
+        )
+        .await;
+        self.recompute_token_usage(turn_context).await;
+        Some(dropped_turns)
+    }
+
+    /// Rebuild the in-memory history from rollout items, together with the
+    /// checkpoints that are still reachable and the pins of items still in the
+    /// history at the end of the rollout.
     fn reconstruct_history_from_rollout(
         &self,
         turn_context: &TurnContext,
         rollout_items: &[RolloutItem],
-    ) -> Vec<ResponseItem> {
+    ) -> (Vec<ResponseItem>, Checkpoints, Pins, JournalReplay) {
         let mut history = ContextManager::new();
+        let mut checkpoints = Checkpoints::default();
+        let mut pins = Pins::default();
+        let mut journal = JournalReplay::default();
         for item in rollout_items {
             match item {
                 RolloutItem::ResponseItem(response_item) => {
@@ -1201,11 +2762,53 @@ impl Session {
                         );
                         history.replace(rebuilt);
                     }
+                    checkpoints.clear();
+                }
+                RolloutItem::Checkpoint(checkpoint) => {
+                    checkpoints.record(checkpoint.id.clone(), history.item_count());
+                }
+                RolloutItem::CheckpointRollback(rollback) => {
+                    if let Some((history_len, _)) = checkpoints.rollback(&rollback.id) {
+                        history.truncate(history_len);
+                    }
+                }
+                RolloutItem::InstructionsReplaced(replaced) => {
+                    let before = history.item_count();
+                    history.replace_user_instructions(replaced.instructions.clone());
+                    if history.item_count() != before {
+                        checkpoints.clear();
+                    }
+                }
+                RolloutItem::ItemPinned(pinned) => {
+                    pins.pin(pinned.item.clone());
+                }
+                RolloutItem::ItemUnpinned(unpinned) => {
+                    pins.unpin(&unpinned.item);
+                }
+                RolloutItem::TranscriptMutation(mutation) => {
+                    // Undo persists nothing but its journal entry.
+                    if let TranscriptMutationKind::Undo { commit_id } = &mutation.kind {
+                        let mut items = history.items().to_vec();
+                        if let Some(index) = items.iter().rposition(|item| {
+                            matches!(
+                                item,
+                                ResponseItem::GhostSnapshot { ghost_commit }
+                                    if ghost_commit.id() == commit_id.as_str()
+                            )
+                        }) {
+                            items.remove(index);
+                            history.replace(items);
+                            checkpoints.clear();
+                        }
+                    }
+                    journal.observe(mutation, history.items());
                 }
                 _ => {}
             }
         }
-        history.get_history()
+        let history = history.get_history();
+        pins.retain_present(&history);
+        (history, checkpoints, pins, journal)
     }
 
     /// Append ResponseItems to the in-memory conversation history only.
@@ -1216,42 +2819,489 @@ impl Session {
     ) {
         let mut state = self.state.lock().await;
         state.record_items(items.iter(), turn_context.truncation_policy);
-    }
-
-    pub(crate) async fn record_model_warning(&self, message: impl Into<String>, ctx: &TurnContext) {
-        if !self.enabled(Feature::ModelWarnings) {
-            return;
+        if let Some(tally) = state.turn_tallies.get_mut(&turn_context.sub_id) {
+            tally.observe(items);
         }
+    }
 
-        let item = ResponseItem::Message {
-            id: None,
-            role: "user".to_string(),
-            content: vec![ContentItem::InputText {
-                text: format!("Warning: {}", message.into()),
-            }],
+    /// Start counting what the task `sub_id` records, keeping the items of
+    /// a user turn when the reasoning effort adapts to them.
+    pub(crate) async fn start_turn_tally(&self, sub_id: &str, kind: TaskKind) {
+        let tally = TurnTally {
+            items: (kind == TaskKind::Regular && self.services.adaptive_effort.is_some())
+                .then(Vec::new),
+            ..TurnTally::default()
         };
+        self.state
+            .lock()
+            .await
+            .turn_tallies
+            .insert(sub_id.to_string(), tally);
+    }
 
-        self.record_conversation_items(ctx, &[item]).await;
+    /// What the task `sub_id` recorded, once it ends.
+    pub(crate) async fn take_turn_tally(&self, sub_id: &str) -> TurnTally {
+        self.state
+            .lock()
+            .await
+            .turn_tallies
+            .remove(sub_id)
+            .unwrap_or_default()
     }
 
-    pub(crate) async fn replace_history(&self, items: Vec<ResponseItem>) {
+    /// Tally the skills of the user turn `turn_id`, starting with those the
+    /// session offered the model.
+    pub(crate) async fn track_skill_usage(&self, turn_context: &TurnContext, turn_id: TurnId) {
         let mut state = self.state.lock().await;
-        state.replace_history(items);
+        let Some(selection) = state.session_configuration.skills_selection.clone() else {
+            return;
+        };
+        if let Some(tally) = state.turn_tallies.get_mut(&turn_context.sub_id) {
+            tally.skill_usage = Some(SkillUsageEvent {
+                turn_id,
+                available: selection.included,
+                invoked: Vec::new(),
+            });
+        }
     }
 
-    async fn persist_rollout_response_items(&self, items: &[ResponseItem]) {
-        let rollout_items: Vec<RolloutItem> = items
-            .iter()
-            .cloned()
-            .map(RolloutItem::ResponseItem)
+    /// Account for the turn of `turn_context` invoking the skill `name`.
+    pub(crate) async fn record_skill_invoked(&self, turn_context: &TurnContext, name: &str) {
+        let mut state = self.state.lock().await;
+        if let Some(tally) = state.turn_tallies.get_mut(&turn_context.sub_id) {
+            tally.skill_invoked(name);
+        }
+    }
+
+    /// Report the turn of `turn_context` as ended by `error`, without a
+    /// final message, when it completes.
+    pub(crate) async fn record_turn_error(&self, turn_context: &TurnContext, error: String) {
+        let mut state = self.state.lock().await;
+        if let Some(tally) = state.turn_tallies.get_mut(&turn_context.sub_id) {
+            tally.error = Some(error);
+        }
+    }
+
+    /// Check the finished turn of `turn_context` for signs of floundering,
+    /// and report the reasoning effort of the next turn when that changes
+    /// it. Turns that never asked the model, such as user shell commands,
+    /// are left out.
+    pub(crate) async fn adapt_reasoning_effort(
+        &self,
+        turn_context: &TurnContext,
+        tally: &TurnTally,
+    ) {
+        let (Some(policy), Some(items)) = (&self.services.adaptive_effort, &tally.items) else {
+            return;
+        };
+        if tally.message_count == 0 && tally.tool_call_count == 0 && tally.error.is_none() {
+            return;
+        }
+        // Models without reasoning are not sent an effort to adapt.
+        let model_family = turn_context.client.get_model_family();
+        if !model_family.supports_reasoning_summaries {
+            return;
+        }
+        let Some(effort) = turn_context
+            .client
+            .get_reasoning_effort()
+            .or(model_family.default_reasoning_effort)
+        else {
+            return;
+        };
+        let signals = policy.signals(&TurnObservation {
+            items,
+            error: tally.error.as_deref(),
+        });
+        let change = self
+            .state
+            .lock()
+            .await
+            .effort_adapter
+            .observe(policy, effort, &signals);
+        if let Some(change) = change {
+            self.send_event(turn_context, change.into()).await;
+        }
+    }
+
+    /// Record the fingerprint of a request the turn of `turn_context` sent,
+    /// keeping only the segments the rollout does not keep yet.
+    async fn record_request_fingerprint(
+        &self,
+        turn_context: &TurnContext,
+        mut fingerprint: RequestFingerprint,
+    ) {
+        {
+            let mut state = self.state.lock().await;
+            fingerprint
+                .segments
+                .retain(|hash, _| state.recorded_segments.insert(hash.clone()));
+        }
+        self.persist_turn_rollout_items(
+            &turn_context.sub_id,
+            &[RolloutItem::RequestFingerprint(fingerprint)],
+        )
+        .await;
+    }
+
+    /// Assign the next [`TurnId`] to the user turn of `turn_context` and mark
+    /// in the rollout where the turn starts.
+    pub(crate) async fn start_user_turn(&self, turn_context: &TurnContext) -> TurnId {
+        let turn_id = {
+            let mut state = self.state.lock().await;
+            let turn_id = state.last_turn_id.map_or(TurnId::new(1), TurnId::next);
+            state.last_turn_id = Some(turn_id);
+            state.partial_message_seq = 0;
+            turn_id
+        };
+        self.persist_turn_rollout_items(
+            &turn_context.sub_id,
+            &[RolloutItem::TurnStarted(TurnStartedItem { turn_id })],
+        )
+        .await;
+        turn_id
+    }
+
+    /// Record `text`, the streaming assistant text of the turn of
+    /// `turn_context` so far, as a provisional partial message of the latest
+    /// user turn.
+    async fn record_partial_message(&self, turn_context: &TurnContext, text: String) {
+        let (turn_id, seq) = {
+            let mut state = self.state.lock().await;
+            let Some(turn_id) = state.last_turn_id else {
+                return;
+            };
+            state.partial_message_seq += 1;
+            (turn_id, state.partial_message_seq)
+        };
+        self.persist_turn_rollout_items(
+            &turn_context.sub_id,
+            &[RolloutItem::PartialMessage { turn_id, seq, text }],
+        )
+        .await;
+    }
+
+    /// Record a named checkpoint at the current end of the history and persist a
+    /// marker so the checkpoint can be rebuilt on resume.
+    pub(crate) async fn create_checkpoint(&self, sub_id: String, id: CheckpointId, label: String) {
+        {
+            let _transcript = self.transcript.lock().await;
+            self.state.lock().await.record_checkpoint(id.clone());
+            self.persist_turn_rollout_items(
+                &sub_id,
+                &[RolloutItem::Checkpoint(CheckpointItem {
+                    id: id.clone(),
+                    label: label.clone(),
+                })],
+            )
+            .await;
+        }
+        self.flush_rollout().await;
+        self.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::CheckpointCreated(CheckpointCreatedEvent { id, label }),
+            correlation_id: None,
+        })
+        .await;
+    }
+
+    /// Truncate the in-memory history to the given checkpoint and persist a
+    /// rollback marker. Checkpoints recorded after `id` are invalidated.
+    ///
+    /// `id` is resolved when the rollback runs, against the history as left by
+    /// every mutation before it, so a compaction in between makes it unknown
+    /// rather than cutting the compacted history at a stale length.
+    pub(crate) async fn rollback_to_checkpoint(&self, sub_id: String, id: CheckpointId) {
+        let message = if self.active_turn.lock().await.is_some() {
+            Some("Cannot roll back to a checkpoint while a task is running.".to_string())
+        } else {
+            let rolled_back = {
+                let _transcript = self.transcript.lock().await;
+                let rolled_back = self.state.lock().await.rollback_to_checkpoint(&id);
+                if rolled_back.is_some() {
+                    let mutation = self
+                        .journal_mutation(TranscriptMutationKind::CheckpointRollback {
+                            checkpoint: id.clone(),
+                        })
+                        .await;
+                    let rollout_items: Vec<RolloutItem> =
+                        std::iter::once(RolloutItem::CheckpointRollback(CheckpointRollbackItem {
+                            id: id.clone(),
+                        }))
+                        .chain(mutation)
+                        .collect();
+                    self.persist_turn_rollout_items(&sub_id, &rollout_items)
+                        .await;
+                }
+                rolled_back
+            };
+            match rolled_back {
+                Some(invalidated) => {
+                    self.flush_rollout().await;
+                    self.send_event_raw(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::CheckpointRolledBack(CheckpointRolledBackEvent {
+                            id,
+                            invalidated,
+                        }),
+                        correlation_id: None,
+                    })
+                    .await;
+                    None
+                }
+                None => Some(format!("Unknown checkpoint: {id}")),
+            }
+        };
+
+        if let Some(message) = message {
+            self.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message,
+                    codex_error_info: Some(CodexErrorInfo::BadRequest),
+                }),
+                correlation_id: None,
+            })
+            .await;
+        }
+    }
+
+    /// Pin the history item `item` names, or unpin it, and persist a marker
+    /// so the pin is restored on resume.
+    pub(crate) async fn set_item_pinned(&self, sub_id: String, item: ItemRef, pinned: bool) {
+        let changed = {
+            let _transcript = self.transcript.lock().await;
+            let mut state = self.state.lock().await;
+            let resolved = resolve_item(state.history.items(), &item).cloned();
+            match resolved {
+                Some(resolved) if pinned || state.pins.contains(&resolved) => {
+                    let marker = if pinned {
+                        state
+                            .pins
+                            .pin(resolved.clone())
+                            .then_some(RolloutItem::ItemPinned(PinnedItem { item: resolved }))
+                    } else {
+                        state.pins.unpin(&resolved);
+                        Some(RolloutItem::ItemUnpinned(PinnedItem { item: resolved }))
+                    };
+                    drop(state);
+                    if let Some(marker) = marker {
+                        self.persist_turn_rollout_items(&sub_id, &[marker]).await;
+                    }
+                    true
+                }
+                _ => false,
+            }
+        };
+
+        let msg = match (changed, pinned) {
+            (true, true) => {
+                self.flush_rollout().await;
+                EventMsg::ItemPinned(ItemPinnedEvent { item })
+            }
+            (true, false) => {
+                self.flush_rollout().await;
+                EventMsg::ItemUnpinned(ItemUnpinnedEvent { item })
+            }
+            (false, true) => EventMsg::Error(ErrorEvent {
+                message: format!("Unknown history item: {item}"),
+                codex_error_info: Some(CodexErrorInfo::BadRequest),
+            }),
+            (false, false) => EventMsg::Error(ErrorEvent {
+                message: format!("History item is not pinned: {item}"),
+                codex_error_info: Some(CodexErrorInfo::BadRequest),
+            }),
+        };
+        self.send_event_raw(Event {
+            id: sub_id,
+            msg,
+            correlation_id: None,
+        })
+        .await;
+    }
+
+    /// The items currently pinned.
+    pub(crate) async fn pins(&self) -> Pins {
+        self.state.lock().await.pins.clone()
+    }
+
+    pub(crate) async fn record_model_warning(&self, message: impl Into<String>, ctx: &TurnContext) {
+        if !self.enabled(Feature::ModelWarnings) {
+            return;
+        }
+
+        let item = ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!("Warning: {}", message.into()),
+            }],
+        };
+
+        self.record_conversation_items(ctx, &[item]).await;
+    }
+
+    /// Replace the history after an undo removed the ghost snapshot of
+    /// `commit_id`. Only the journal entry is persisted: replaying it removes
+    /// the snapshot again.
+    pub(crate) async fn replace_history_after_undo(
+        &self,
+        sub_id: &str,
+        items: Vec<ResponseItem>,
+        commit_id: String,
+    ) {
+        let _transcript = self.transcript.lock().await;
+        self.state.lock().await.replace_history(items);
+        if let Some(mutation) = self
+            .journal_mutation(TranscriptMutationKind::Undo { commit_id })
+            .await
+        {
+            self.persist_turn_rollout_items(sub_id, &[mutation]).await;
+        }
+    }
+
+    /// Replace the history with the result of a compaction and persist
+    /// `compacted` as one transcript mutation, journaled as `kind`.
+    ///
+    /// The `read_file` calls whose output the read cache referenced are kept
+    /// after `items`, so the references stay meaningful; `compacted` then
+    /// records the history it leaves, keeping replays faithful.
+    pub(crate) async fn replace_compacted_history(
+        &self,
+        mut items: Vec<ResponseItem>,
+        mut compacted: CompactedItem,
+        kind: TranscriptMutationKind,
+    ) {
+        let _transcript = self.transcript.lock().await;
+        let mut state = self.state.lock().await;
+        let retained = state
+            .history
+            .function_call_items(&self.services.read_cache.referenced_call_ids());
+        if !retained.is_empty() {
+            items.extend(retained);
+            compacted.replacement_history = Some(items.clone());
+        }
+        // Pinned items are kept verbatim. The replacement is recorded in full
+        // since rebuilding the history on resume would not know where they go.
+        if !state.pins.is_empty() {
+            state.pins.restore(state.history.items(), &mut items);
+            compacted.replacement_history = Some(items.clone());
+        }
+        state.replace_history(items);
+        drop(state);
+        let mutation = self.journal_mutation(kind).await;
+        let rollout_items: Vec<RolloutItem> = std::iter::once(RolloutItem::Compacted(compacted))
+            .chain(mutation)
             .collect();
         self.persist_rollout_items(&rollout_items).await;
+        self.spill_transcript().await;
+    }
+
+    /// Spill the journaled items the rollout holds, now that the history was
+    /// compacted, see [`TranscriptJournal::spill`]. Callers hold
+    /// `self.transcript`.
+    async fn spill_transcript(&self) {
+        let Some(live_rollout) = &self.services.live_rollout else {
+            return;
+        };
+        self.flush_rollout().await;
+        if let Err(e) = self
+            .services
+            .transcript_journal
+            .spill(live_rollout.rollout_path())
+            .await
+        {
+            warn!("failed to spill the transcript journal: {e}");
+        }
+    }
+
+    /// Whether the output of the function call `call_id` is in the history.
+    pub(crate) async fn has_function_call_output(&self, call_id: &str) -> bool {
+        self.state
+            .lock()
+            .await
+            .history
+            .has_function_call_output(call_id)
+    }
+
+    async fn persist_rollout_response_items(
+        &self,
+        sub_id: &str,
+        items: &[ResponseItem],
+        mutation: Option<RolloutItem>,
+    ) {
+        let rollout_items: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .chain(mutation)
+            .collect();
+        self.persist_turn_rollout_items(sub_id, &rollout_items)
+            .await;
     }
 
     pub fn enabled(&self, feature: Feature) -> bool {
         self.features.enabled(feature)
     }
 
+    /// Compare the workspace with its state at the end of the previous turn
+    /// and, if it changed underneath us, tell both the model and the user.
+    async fn notify_workspace_changes(&self, turn_context: &TurnContext) {
+        if !self.enabled(Feature::WorkspaceWatch) {
+            return;
+        }
+        let current = WorkspaceSnapshot::capture(&turn_context.cwd).await;
+        let previous = {
+            let mut state = self.state.lock().await;
+            state.workspace_snapshot.replace(current.clone())
+        };
+        let Some(previous) = previous else {
+            return;
+        };
+        let Some(event) = previous.changes_since(&current).await else {
+            return;
+        };
+        self.record_conversation_items(turn_context, &[workspace_changed_notice(&event)])
+            .await;
+        self.send_event(turn_context, EventMsg::WorkspaceChanged(event))
+            .await;
+    }
+
+    /// Record the workspace's git context when HEAD moved since it was last
+    /// recorded. Unlike the workspace watch this only feeds the rollout; the
+    /// model is not told.
+    async fn record_git_context(&self, turn_context: &TurnContext) {
+        let Some(context) = self.services.git_context.refresh(&turn_context.cwd).await else {
+            return;
+        };
+        self.persist_turn_rollout_items(&turn_context.sub_id, &[RolloutItem::GitContext(context)])
+            .await;
+    }
+
+    /// Tell the model the current time when it moved on since it was last
+    /// reported.
+    async fn refresh_user_context(&self, turn_context: &TurnContext) {
+        let Some(item) = self
+            .services
+            .user_context
+            .as_ref()
+            .and_then(UserContextClock::refresh_item)
+        else {
+            return;
+        };
+        self.record_conversation_items(turn_context, &[item]).await;
+    }
+
+    /// Remember the workspace as the turn left it, so the model's own edits
+    /// are not reported as external changes.
+    pub(crate) async fn refresh_workspace_snapshot(&self, turn_context: &TurnContext) {
+        if !self.enabled(Feature::WorkspaceWatch) {
+            return;
+        }
+        let snapshot = WorkspaceSnapshot::capture(&turn_context.cwd).await;
+        self.state.lock().await.workspace_snapshot = Some(snapshot);
+    }
+
     pub(crate) fn features(&self) -> Features {
         self.features.clone()
     }
@@ -1287,16 +3337,43 @@ impl Session {
             Some(turn_context.sandbox_policy.clone()),
             shell.as_ref().clone(),
         )));
+        if let Some(user_context) = &self.services.user_context {
+            items.push(user_context.prefix_item());
+        }
+        if let Some(notes) = &self.services.project_notes_prefix {
+            items.push(notes.clone());
+        }
         items
     }
 
     pub(crate) async fn persist_rollout_items(&self, items: &[RolloutItem]) {
+        self.persist_correlated_rollout_items(items, None).await;
+    }
+
+    /// Persist `items` tagged with the `correlation_id` of the submission
+    /// `sub_id`.
+    pub(crate) async fn persist_turn_rollout_items(&self, sub_id: &str, items: &[RolloutItem]) {
+        let correlation_id = self.services.correlation_ids.get(sub_id);
+        self.persist_correlated_rollout_items(items, correlation_id)
+            .await;
+    }
+
+    async fn persist_correlated_rollout_items(
+        &self,
+        items: &[RolloutItem],
+        correlation_id: Option<String>,
+    ) {
+        let stripped =
+            (!self.services.persist_reasoning_summaries).then(|| strip_for_rollout(items));
+        let items = stripped.as_deref().unwrap_or(items);
+        let redacted = self.services.sensitive_input.redact_rollout_items(items);
+        let items = redacted.as_deref().unwrap_or(items);
         let recorder = {
             let guard = self.services.rollout.lock().await;
             guard.clone()
         };
         if let Some(rec) = recorder
-            && let Err(e) = rec.record_items(items).await
+            && let Err(e) = rec.record_correlated_items(items, correlation_id).await
         {
             error!("failed to record rollout items: {e:#}");
         }
@@ -1328,7 +3405,7 @@ impl Session {
         let Some(estimated_total_tokens) = self
             .clone_history()
             .await
-            .estimate_token_count(turn_context)
+            .estimate_token_count(turn_context, self.services.token_counter.as_ref())
         else {
             return;
         };
@@ -1357,6 +3434,86 @@ impl Session {
         self.send_token_count_event(turn_context).await;
     }
 
+    /// Re-estimate the context usage of the next request and report any
+    /// `context_usage_thresholds` it newly crossed.
+    pub(crate) async fn update_context_usage(&self, turn_context: &TurnContext) {
+        let used_tokens_estimate = self
+            .clone_history()
+            .await
+            .estimate_token_count(turn_context, self.services.token_counter.as_ref())
+            .unwrap_or_default();
+        let usage = ContextUsage::new(
+            used_tokens_estimate,
+            turn_context.client.get_model_context_window(),
+        );
+        for threshold in self.services.context_usage.update(usage) {
+            let event =
+                EventMsg::ContextUsageUpdated(ContextUsageUpdatedEvent { usage, threshold });
+            self.send_event(turn_context, event).await;
+        }
+    }
+
+    /// Tell clients how the turn's tools were fitted to the provider when that
+    /// differs from what was last reported. Pruned tools are also surfaced
+    /// as a warning.
+    async fn report_tool_downgrades(
+        &self,
+        turn_context: &TurnContext,
+        report: &ToolDowngradeReport,
+    ) {
+        {
+            let mut state = self.state.lock().await;
+            if state.reported_tool_downgrades == *report {
+                return;
+            }
+            state.reported_tool_downgrades = report.clone();
+        }
+        if report.is_empty() {
+            return;
+        }
+        if !report.pruned.is_empty() {
+            let message = format!(
+                "Provider `{}` accepts fewer tools than are available; left out: {}.",
+                turn_context.client.get_provider().name,
+                report.pruned.join(", ")
+            );
+            warn!("{message}");
+            let warning = WarningEvent::new(WarningCode::ToolsLeftOut, message)
+                .with_details(json!({ "tools": report.pruned }));
+            self.send_event(turn_context, EventMsg::Warning(warning))
+                .await;
+        }
+        self.send_event(turn_context, EventMsg::ToolsDowngraded(report.clone()))
+            .await;
+    }
+
+    /// Sampling parameters the turn's requests carry, for its rollout record.
+    /// Configured parameters the model or provider ignores are reported once
+    /// per session.
+    pub(crate) async fn turn_sampling(&self, turn_context: &TurnContext) -> Option<SamplingParams> {
+        let (sent, ignored) = turn_context.client.get_sampling();
+        let newly_ignored: Vec<&'static str> = {
+            let mut state = self.state.lock().await;
+            ignored
+                .into_iter()
+                .filter(|name| state.ignored_sampling_warned.insert(name))
+                .collect()
+        };
+        for name in newly_ignored {
+            let message = format!(
+                "`{name}` is ignored by model `{}` on provider `{}` and is not sent.",
+                turn_context.client.get_model(),
+                turn_context.client.get_provider().name
+            );
+            warn!("{message}");
+            let warning = WarningEvent::new(WarningCode::SamplingParameterIgnored, message)
+                .with_details(json!({ "parameter": name }));
+            self.send_event(turn_context, EventMsg::Warning(warning))
+                .await;
+        }
+        (!sent.is_unset()).then_some(sent)
+    }
+
     pub(crate) async fn update_rate_limits(
         &self,
         turn_context: &TurnContext,
@@ -1462,20 +3619,45 @@ impl Session {
             .await;
     }
 
-    /// Returns the input if there was no task running to inject into
-    pub async fn inject_input(&self, input: Vec<UserInput>) -> Result<(), Vec<UserInput>> {
+    /// Returns the input if there was no task running to inject into, and the
+    /// id it is queued under otherwise.
+    ///
+    /// Injected input is recorded in the rollout as a queued
+    /// [`PendingInputItem`] before this returns, so it can be replayed if the
+    /// process stops before the turn picks it up.
+    pub async fn inject_input(&self, input: Vec<UserInput>) -> Result<String, Vec<UserInput>> {
+        let id = self.services.runtime_env.next_id();
+        let queued = PendingInput {
+            id: id.clone(),
+            items: input.clone(),
+        };
+        self.requeue_pending_input(id.clone(), input).await?;
+        self.persist_rollout_items(&[RolloutItem::PendingInput(PendingInputItem::Queued(queued))])
+            .await;
+        self.flush_rollout().await;
+        Ok(id)
+    }
+
+    /// Queue input that is already recorded in the rollout under `id`.
+    async fn requeue_pending_input(
+        &self,
+        id: String,
+        input: Vec<UserInput>,
+    ) -> Result<(), Vec<UserInput>> {
         let mut active = self.active_turn.lock().await;
         match active.as_mut() {
             Some(at) => {
                 let mut ts = at.turn_state.lock().await;
-                ts.push_pending_input(input.into());
+                ts.push_pending_input(id, input.into());
                 Ok(())
             }
             None => Err(input),
         }
     }
 
-    pub async fn get_pending_input(&self) -> Vec<ResponseInputItem> {
+    /// Take the input queued behind the running turn, with the ids to mark
+    /// consumed once it is in the history.
+    pub async fn get_pending_input(&self) -> Vec<(String, ResponseInputItem)> {
         let mut active = self.active_turn.lock().await;
         match active.as_mut() {
             Some(at) => {
@@ -1486,6 +3668,103 @@ impl Session {
         }
     }
 
+    async fn is_running_task(&self, sub_id: &str) -> bool {
+        let active = self.active_turn.lock().await;
+        active
+            .as_ref()
+            .is_some_and(|at| at.tasks.contains_key(sub_id))
+    }
+
+    async fn has_pending_input(&self) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at.turn_state.lock().await.has_pending_input(),
+            None => false,
+        }
+    }
+
+    /// Record that the pending inputs with `ids` no longer need replaying.
+    pub(crate) async fn mark_pending_inputs_consumed(&self, ids: Vec<String>) {
+        if ids.is_empty() {
+            return;
+        }
+        let items: Vec<RolloutItem> = ids
+            .into_iter()
+            .map(|id| RolloutItem::PendingInput(PendingInputItem::Consumed { id }))
+            .collect();
+        self.persist_rollout_items(&items).await;
+    }
+
+    /// Queue the input of the submission `sub_id` instead of starting a
+    /// turn, because the conversation is offline. Like injected input, it is
+    /// recorded in the rollout as a queued [`PendingInputItem`] first.
+    async fn queue_offline_input(&self, sub_id: &str, items: Vec<UserInput>) {
+        let input = PendingInput {
+            id: self.services.runtime_env.next_id(),
+            items,
+        };
+        self.persist_rollout_items(&[RolloutItem::PendingInput(PendingInputItem::Queued(
+            input.clone(),
+        ))])
+        .await;
+        self.flush_rollout().await;
+        self.park_offline_input(sub_id, input).await;
+    }
+
+    /// Add `input`, already recorded in the rollout, to the offline queue.
+    async fn park_offline_input(&self, sub_id: &str, input: PendingInput) {
+        let id = input.id.clone();
+        let queued = {
+            let mut state = self.state.lock().await;
+            state.offline_queue.push_back(input);
+            state.offline_queue.len()
+        };
+        self.send_event_raw(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::QueuedOffline(QueuedOfflineEvent { id, queued }),
+            correlation_id: None,
+        })
+        .await;
+    }
+
+    /// Start the next queued turn of a running [`Op::FlushQueued`], unless a
+    /// turn is running or the conversation is pausing. The flush ends once
+    /// the queue is empty or the conversation went offline again.
+    ///
+    /// Boxed, since the turn it starts calls back here once it ends: the
+    /// future would otherwise contain itself.
+    pub(crate) fn start_next_queued_turn(self: &Arc<Self>) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            if self.services.pause.status() != PauseStatus::Running
+                || self.active_turn.lock().await.is_some()
+            {
+                return;
+            }
+            let (sub_id, PendingInput { id, items }) = {
+                let mut state = self.state.lock().await;
+                let Some(sub_id) = state.offline_flush.clone() else {
+                    return;
+                };
+                let next = if state.session_configuration.offline {
+                    None
+                } else {
+                    state.offline_queue.pop_front()
+                };
+                match next {
+                    Some(input) => (sub_id, input),
+                    None => {
+                        state.offline_flush = None;
+                        return;
+                    }
+                }
+            };
+            // As for replayed input, the turn records it as it starts.
+            self.mark_pending_inputs_consumed(vec![id]).await;
+            let turn_context = self.new_default_turn_with_sub_id(sub_id).await;
+            self.spawn_task(turn_context, items, RegularTask).await;
+        })
+    }
+
     pub async fn list_resources(
         &self,
         server: &str,
@@ -1575,13 +3854,23 @@ impl Session {
     }
 }
 
-async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiver<Submission>) {
+async fn submission_loop(
+    sess: Arc<Session>,
+    config: Arc<Config>,
+    rx_sub: Receiver<Submission>,
+    replayed_inputs: Vec<PendingInput>,
+) {
     // Seed with context in case there is an OverrideTurnContext first.
     let mut previous_context: Option<Arc<TurnContext>> = Some(sess.new_default_turn().await);
+    handlers::replay_pending_inputs(&sess, replayed_inputs, &mut previous_context).await;
 
     // To break out of this loop, send Op::Shutdown.
     while let Ok(sub) = rx_sub.recv().await {
         debug!(?sub, "Submission");
+        sess.services.load.submission_taken();
+        sess.services
+            .correlation_ids
+            .begin(&sub.id, sub.correlation_id.clone());
         match sub.op.clone() {
             Op::Interrupt => {
                 handlers::interrupt(&sess).await;
@@ -1593,6 +3882,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                 model,
                 effort,
                 summary,
+                execution_mode,
+                sampling,
+                offline,
             } => {
                 handlers::override_turn_context(
                     &sess,
@@ -1604,6 +3896,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                         model,
                         reasoning_effort: effort,
                         reasoning_summary: summary,
+                        execution_mode,
+                        sampling,
+                        offline,
                         ..Default::default()
                     },
                 )
@@ -1658,59 +3953,130 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                 handlers::resolve_elicitation(&sess, server_name, request_id, decision).await;
             }
             Op::Shutdown => {
-                if handlers::shutdown(&sess, sub.id.clone()).await {
+                if handlers::shutdown(&sess, sub.id.clone(), SessionEndReason::Shutdown).await {
                     break;
                 }
             }
-            Op::Review { review_request } => {
-                handlers::review(&sess, &config, sub.id.clone(), review_request).await;
+            Op::EndSession { reason } => {
+                if handlers::shutdown(&sess, sub.id.clone(), reason).await {
+                    break;
+                }
+            }
+            Op::Review { review_request } => {
+                handlers::review(&sess, &config, sub.id.clone(), review_request).await;
+            }
+            Op::Checkpoint { id, label } => {
+                handlers::checkpoint(&sess, sub.id.clone(), id, label).await;
+            }
+            Op::RollbackToCheckpoint { id } => {
+                handlers::rollback_to_checkpoint(&sess, sub.id.clone(), id).await;
+            }
+            Op::PinItem { item } => {
+                handlers::set_item_pinned(&sess, sub.id.clone(), item, true).await;
+            }
+            Op::UnpinItem { item } => {
+                handlers::set_item_pinned(&sess, sub.id.clone(), item, false).await;
+            }
+            Op::LinkConversation { link } => {
+                handlers::link_conversation(&sess, sub.id.clone(), link).await;
+            }
+            Op::IngestChildResult { child_id, message } => {
+                handlers::ingest_child_result(&sess, sub.id.clone(), child_id, message).await;
+            }
+            Op::UpdateToolPolicy { tool_policy } => {
+                handlers::update_tool_policy(&sess, sub.id.clone(), tool_policy).await;
+            }
+            Op::SetSafeMode { enabled } => {
+                handlers::set_safe_mode(&sess, sub.id.clone(), enabled).await;
+            }
+            Op::Pause { mode } => {
+                handlers::pause(&sess, sub.id.clone(), mode).await;
+            }
+            Op::Resume => {
+                handlers::resume(&sess, sub.id.clone()).await;
+            }
+            Op::FlushQueued => {
+                handlers::flush_queued(&sess, sub.id.clone()).await;
             }
             _ => {} // Ignore unknown ops; enum is non_exhaustive to allow extensions.
         }
+        // A submission that started a turn keeps its id until the turn ends.
+        if !sess.is_running_task(&sub.id).await {
+            sess.services.correlation_ids.end(&sub.id);
+        }
+        sess.services.turn_results.handled(&sub.id);
     }
     debug!("Agent loop exited");
 }
 
 /// Operation handlers
 mod handlers {
+    use crate::codex::INITIAL_SUBMIT_ID;
     use crate::codex::Session;
     use crate::codex::SessionSettingsUpdate;
     use crate::codex::TurnContext;
 
+    use crate::child_conversations::child_result_item;
+    use crate::child_conversations::child_result_text;
     use crate::codex::spawn_review_thread;
     use crate::config::Config;
+    use crate::config::ConstraintResult;
+    use crate::error::CodexErr;
     use crate::features::Feature;
     use crate::mcp::auth::compute_auth_statuses;
     use crate::mcp::collect_mcp_snapshot_from_manager;
+    use crate::pre_process::PreflightDecision;
+    use crate::pre_process::UserTurnInput;
+    use crate::pre_process::run_pre_processors;
     use crate::review_prompts::resolve_review_request;
+    use crate::safe_mode;
     use crate::tasks::CompactTask;
     use crate::tasks::RegularTask;
     use crate::tasks::UndoTask;
     use crate::tasks::UserShellCommandTask;
+    use codex_protocol::ConversationId;
+    use codex_protocol::config_types::ToolPolicy;
     use codex_protocol::custom_prompts::CustomPrompt;
+    use codex_protocol::protocol::CheckpointId;
     use codex_protocol::protocol::CodexErrorInfo;
+    use codex_protocol::protocol::ConversationLink;
+    use codex_protocol::protocol::ConversationPausedEvent;
     use codex_protocol::protocol::ErrorEvent;
     use codex_protocol::protocol::Event;
     use codex_protocol::protocol::EventMsg;
+    use codex_protocol::protocol::InputRejectedEvent;
+    use codex_protocol::protocol::InputRejectedItem;
+    use codex_protocol::protocol::ItemRef;
     use codex_protocol::protocol::ListCustomPromptsResponseEvent;
     use codex_protocol::protocol::ListSkillsResponseEvent;
     use codex_protocol::protocol::Op;
+    use codex_protocol::protocol::PauseMode;
+    use codex_protocol::protocol::PendingInput;
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
+    use codex_protocol::protocol::RolloutItem;
+    use codex_protocol::protocol::SafeModeChangedEvent;
+    use codex_protocol::protocol::SessionEndReason;
+    use codex_protocol::protocol::SessionEndedEvent;
     use codex_protocol::protocol::SkillsListEntry;
     use codex_protocol::protocol::TurnAbortReason;
+    use codex_protocol::protocol::WarningCode;
     use codex_protocol::protocol::WarningEvent;
 
     use codex_protocol::user_input::UserInput;
     use codex_rmcp_client::ElicitationAction;
     use codex_rmcp_client::ElicitationResponse;
     use mcp_types::RequestId;
+    use serde_json::json;
+    use std::collections::BTreeSet;
     use std::path::PathBuf;
     use std::sync::Arc;
     use tracing::info;
     use tracing::warn;
 
     pub async fn interrupt(sess: &Arc<Session>) {
+        // The queued turns not started yet stay queued.
+        sess.state.lock().await.offline_flush = None;
         sess.interrupt_task().await;
     }
 
@@ -1726,6 +4092,7 @@ mod handlers {
                     message: err.to_string(),
                     codex_error_info: Some(CodexErrorInfo::BadRequest),
                 }),
+                correlation_id: None,
             })
             .await;
         }
@@ -1737,7 +4104,7 @@ mod handlers {
         op: Op,
         previous_context: &mut Option<Arc<TurnContext>>,
     ) {
-        let (items, updates) = match op {
+        let (items, updates, allow_sensitive) = match op {
             Op::UserTurn {
                 cwd,
                 approval_policy,
@@ -1747,6 +4114,9 @@ mod handlers {
                 summary,
                 final_output_json_schema,
                 items,
+                allow_sensitive,
+                bypass_read_cache,
+                deadline,
             } => (
                 items,
                 SessionSettingsUpdate {
@@ -1757,12 +4127,81 @@ mod handlers {
                     reasoning_effort: Some(effort),
                     reasoning_summary: Some(summary),
                     final_output_json_schema: Some(final_output_json_schema),
+                    execution_mode: None,
+                    tool_policy: None,
+                    sampling: None,
+                    offline: None,
+                    bypass_read_cache,
+                    deadline,
                 },
+                allow_sensitive,
             ),
-            Op::UserInput { items } => (items, SessionSettingsUpdate::default()),
+            Op::UserInput {
+                items,
+                allow_sensitive,
+            } => (items, SessionSettingsUpdate::default(), allow_sensitive),
             _ => unreachable!(),
         };
 
+        let mut input = UserTurnInput {
+            submission_id: sub_id.clone(),
+            items,
+        };
+        if let PreflightDecision::Reject { reason } =
+            run_pre_processors(&sess.services.pre_processors, &mut input)
+        {
+            if sess.services.record_input_rejections {
+                let marker = RolloutItem::InputRejected(InputRejectedItem {
+                    reason: reason.clone(),
+                });
+                sess.persist_turn_rollout_items(&sub_id, &[marker]).await;
+            }
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::InputRejected(InputRejectedEvent {
+                    reason,
+                    rule: None,
+                    span: None,
+                }),
+                correlation_id: None,
+            })
+            .await;
+            return;
+        }
+        let UserTurnInput { items, .. } = input;
+
+        let sensitive = sess.services.sensitive_input.scan(&items);
+        if let Some(first) = sensitive.first() {
+            if !allow_sensitive {
+                sess.send_event_raw(Event {
+                    id: sub_id,
+                    msg: EventMsg::InputRejected(InputRejectedEvent {
+                        reason: format!("the prompt matches sensitive input rule `{}`", first.rule),
+                        rule: Some(first.rule.clone()),
+                        span: Some(first.span),
+                    }),
+                    correlation_id: None,
+                })
+                .await;
+                return;
+            }
+            sess.services.sensitive_input.remember(&sensitive);
+            let rules: BTreeSet<&str> = sensitive.iter().map(|found| found.rule.as_str()).collect();
+            let message = format!(
+                "The prompt matches sensitive input rules ({}). It is sent as is; the matches are redacted in the rollout and event log.",
+                rules.iter().copied().collect::<Vec<_>>().join(", ")
+            );
+            sess.send_event_raw(Event {
+                id: sub_id.clone(),
+                msg: EventMsg::Warning(
+                    WarningEvent::new(WarningCode::SensitiveInputRedacted, message)
+                        .with_details(json!({ "rules": rules })),
+                ),
+                correlation_id: None,
+            })
+            .await;
+        }
+
         let Ok(current_context) = sess.new_turn_with_sub_id(sub_id, updates).await else {
             // new_turn_with_sub_id already emits the error event.
             return;
@@ -1771,19 +4210,64 @@ mod handlers {
             .client
             .get_otel_manager()
             .user_prompt(&items);
+        if current_context.client.config().offline {
+            sess.queue_offline_input(&current_context.sub_id, items)
+                .await;
+            return;
+        }
 
         // Attempt to inject input into current task
-        if let Err(items) = sess.inject_input(items).await {
-            if let Some(env_item) =
-                sess.build_environment_update_item(previous_context.as_ref(), &current_context)
-            {
-                sess.record_conversation_items(&current_context, std::slice::from_ref(&env_item))
-                    .await;
+        let items = match sess.inject_input(items).await {
+            Ok(pending_id) => {
+                sess.services
+                    .turn_results
+                    .queue(&current_context.sub_id, pending_id);
+                return;
             }
+            Err(items) => items,
+        };
+        let current_context = sess.refresh_instructions(current_context).await;
+        sess.notify_workspace_changes(&current_context).await;
+        sess.record_git_context(&current_context).await;
+        sess.refresh_user_context(&current_context).await;
+        if let Some(env_item) =
+            sess.build_environment_update_item(previous_context.as_ref(), &current_context)
+        {
+            sess.record_conversation_items(&current_context, std::slice::from_ref(&env_item))
+                .await;
+        }
+
+        sess.spawn_task(Arc::clone(&current_context), items, RegularTask)
+            .await;
+        *previous_context = Some(current_context);
+    }
 
-            sess.spawn_task(Arc::clone(&current_context), items, RegularTask)
+    /// Submit inputs left queued by a previous run, in order: the first one
+    /// starts a turn and the others are queued behind it, as they were.
+    pub async fn replay_pending_inputs(
+        sess: &Arc<Session>,
+        inputs: Vec<PendingInput>,
+        previous_context: &mut Option<Arc<TurnContext>>,
+    ) {
+        if sess.state.lock().await.session_configuration.offline {
+            for input in inputs {
+                sess.park_offline_input(INITIAL_SUBMIT_ID, input).await;
+            }
+            return;
+        }
+        for PendingInput { id, items } in inputs {
+            let Err(items) = sess.requeue_pending_input(id.clone(), items).await else {
+                continue;
+            };
+            // The turn records its input as it starts; from here on a crash
+            // must not replay it again.
+            sess.mark_pending_inputs_consumed(vec![id]).await;
+            let turn_context = sess
+                .new_default_turn_with_sub_id(INITIAL_SUBMIT_ID.to_owned())
                 .await;
-            *previous_context = Some(current_context);
+            sess.spawn_task(Arc::clone(&turn_context), items, RegularTask)
+                .await;
+            *previous_context = Some(turn_context);
         }
     }
 
@@ -1832,19 +4316,27 @@ mod handlers {
     /// Propagate a user's exec approval decision to the session.
     /// Also optionally applies an execpolicy amendment.
     pub async fn exec_approval(sess: &Arc<Session>, id: String, decision: ReviewDecision) {
+        if sess.resolve_safe_mode_exit(&id, &decision).await {
+            return;
+        }
         if let ReviewDecision::ApprovedExecpolicyAmendment {
             proposed_execpolicy_amendment,
         } = &decision
+            && !sess.in_safe_mode().await
             && let Err(err) = sess
                 .persist_execpolicy_amendment(proposed_execpolicy_amendment)
                 .await
         {
             let message = format!("Failed to apply execpolicy amendment: {err}");
             tracing::warn!("{message}");
-            let warning = EventMsg::Warning(WarningEvent { message });
+            let warning = EventMsg::Warning(WarningEvent::new(
+                WarningCode::ExecpolicyAmendmentNotSaved,
+                message,
+            ));
             sess.send_event_raw(Event {
                 id: id.clone(),
                 msg: warning,
+                correlation_id: None,
             })
             .await;
         }
@@ -1866,6 +4358,7 @@ mod handlers {
     }
 
     pub async fn add_to_history(sess: &Arc<Session>, config: &Arc<Config>, text: String) {
+        let text = sess.services.sensitive_input.redact_text(&text);
         let id = sess.conversation_id;
         let config = Arc::clone(config);
         tokio::spawn(async move {
@@ -1906,6 +4399,7 @@ mod handlers {
                         }),
                     },
                 ),
+                correlation_id: None,
             };
 
             sess_clone.send_event_raw(event).await;
@@ -1926,6 +4420,7 @@ mod handlers {
         let event = Event {
             id: sub_id,
             msg: EventMsg::McpListToolsResponse(snapshot),
+            correlation_id: None,
         };
         sess.send_event_raw(event).await;
     }
@@ -1943,6 +4438,7 @@ mod handlers {
             msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
                 custom_prompts,
             }),
+            correlation_id: None,
         };
         sess.send_event_raw(event).await;
     }
@@ -1985,6 +4481,7 @@ mod handlers {
         let event = Event {
             id: sub_id,
             msg: EventMsg::ListSkillsResponse(ListSkillsResponseEvent { skills }),
+            correlation_id: None,
         };
         sess.send_event_raw(event).await;
     }
@@ -1995,6 +4492,189 @@ mod handlers {
             .await;
     }
 
+    pub async fn checkpoint(sess: &Arc<Session>, sub_id: String, id: CheckpointId, label: String) {
+        sess.create_checkpoint(sub_id, id, label).await;
+    }
+
+    pub async fn rollback_to_checkpoint(sess: &Arc<Session>, sub_id: String, id: CheckpointId) {
+        sess.rollback_to_checkpoint(sub_id, id).await;
+    }
+
+    pub async fn set_item_pinned(sess: &Arc<Session>, sub_id: String, item: ItemRef, pinned: bool) {
+        sess.set_item_pinned(sub_id, item, pinned).await;
+    }
+
+    pub async fn link_conversation(sess: &Arc<Session>, sub_id: String, link: ConversationLink) {
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::ConversationLinked(link),
+            correlation_id: None,
+        })
+        .await;
+        sess.flush_rollout().await;
+    }
+
+    pub async fn update_tool_policy(sess: &Arc<Session>, sub_id: String, tool_policy: ToolPolicy) {
+        let updates = SessionSettingsUpdate {
+            tool_policy: Some(tool_policy.clone()),
+            ..Default::default()
+        };
+        if sess.update_settings(updates).await.is_err() {
+            return;
+        }
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::ToolPolicyUpdated(tool_policy),
+            correlation_id: None,
+        })
+        .await;
+        sess.flush_rollout().await;
+    }
+
+    /// Turn safe mode on at once, or ask for approval to turn it off and
+    /// turn it off once approved, without holding up other submissions.
+    pub async fn set_safe_mode(sess: &Arc<Session>, sub_id: String, enabled: bool) {
+        if enabled {
+            let mut mcp_tools: Vec<String> = sess
+                .services
+                .mcp_connection_manager
+                .read()
+                .await
+                .list_all_tools()
+                .await
+                .into_keys()
+                .collect();
+            mcp_tools.sort();
+            let result = sess.enter_safe_mode(mcp_tools).await;
+            report_safe_mode_change(sess, sub_id, result, "Safe mode is already on.").await;
+            return;
+        }
+        let Some(rx_approve) = sess.request_safe_mode_exit(&sub_id).await else {
+            report_safe_mode_change(sess, sub_id, Ok(None), "Safe mode is already off.").await;
+            return;
+        };
+        let sess = Arc::clone(sess);
+        tokio::spawn(async move {
+            let decision = rx_approve.await.unwrap_or_default();
+            if safe_mode::is_approved(&decision) {
+                let result = sess.leave_safe_mode().await;
+                report_safe_mode_change(&sess, sub_id, result, "Safe mode is already off.").await;
+            } else {
+                report_safe_mode_change(
+                    &sess,
+                    sub_id,
+                    Ok(None),
+                    "Safe mode stays on: leaving it was not approved.",
+                )
+                .await;
+            }
+        });
+    }
+
+    /// Send `result` of a safe mode switch: the change, recorded in the
+    /// rollout, or a warning saying `unchanged` when nothing changed.
+    async fn report_safe_mode_change(
+        sess: &Session,
+        sub_id: String,
+        result: ConstraintResult<Option<SafeModeChangedEvent>>,
+        unchanged: &str,
+    ) {
+        let msg = match result {
+            Ok(Some(change)) => EventMsg::SafeModeChanged(change),
+            Ok(None) => {
+                EventMsg::Warning(WarningEvent::new(WarningCode::SafeModeUnchanged, unchanged))
+            }
+            Err(err) => EventMsg::Error(ErrorEvent {
+                message: format!("Safe mode was not changed: {err}"),
+                codex_error_info: Some(CodexErrorInfo::BadRequest),
+            }),
+        };
+        let changed = matches!(msg, EventMsg::SafeModeChanged(_));
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg,
+            correlation_id: None,
+        })
+        .await;
+        if changed {
+            sess.flush_rollout().await;
+        }
+    }
+
+    pub async fn pause(sess: &Arc<Session>, sub_id: String, mode: PauseMode) {
+        let had_running_turn = sess.active_turn.lock().await.is_some();
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::ConversationPaused(ConversationPausedEvent {
+                mode,
+                had_running_turn,
+            }),
+            correlation_id: None,
+        };
+        if !sess.services.pause.drain(event) {
+            return;
+        }
+        if had_running_turn && mode == PauseMode::AbortTurn {
+            sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
+        }
+        sess.finish_pending_pause().await;
+    }
+
+    pub async fn resume(sess: &Arc<Session>, sub_id: String) {
+        sess.services.load.set_paused(false);
+        if !sess.services.pause.resume() {
+            return;
+        }
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::ConversationResumed,
+            correlation_id: None,
+        })
+        .await;
+        sess.flush_rollout().await;
+        sess.start_next_queued_turn().await;
+    }
+
+    /// Run the offline queue, one turn after the other, see
+    /// [`Session::start_next_queued_turn`].
+    pub async fn flush_queued(sess: &Arc<Session>, sub_id: String) {
+        let offline = {
+            let mut state = sess.state.lock().await;
+            let offline = state.session_configuration.offline;
+            if !offline && state.offline_flush.is_none() {
+                state.offline_flush = Some(sub_id.clone());
+            }
+            offline
+        };
+        if offline {
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Error(CodexErr::Offline.to_error_event(None)),
+                correlation_id: None,
+            })
+            .await;
+            return;
+        }
+        sess.start_next_queued_turn().await;
+    }
+
+    pub async fn ingest_child_result(
+        sess: &Arc<Session>,
+        sub_id: String,
+        child_id: ConversationId,
+        message: String,
+    ) {
+        let input = vec![UserInput::Text {
+            text: child_result_text(child_id, &message),
+        }];
+        if sess.inject_input(input).await.is_err() {
+            let turn_context = sess.new_default_turn_with_sub_id(sub_id).await;
+            sess.record_conversation_items(&turn_context, &[child_result_item(child_id, &message)])
+                .await;
+            sess.flush_rollout().await;
+        }
+    }
+
     pub async fn compact(sess: &Arc<Session>, sub_id: String) {
         let turn_context = sess.new_default_turn_with_sub_id(sub_id).await;
 
@@ -2008,7 +4688,7 @@ mod handlers {
         .await;
     }
 
-    pub async fn shutdown(sess: &Arc<Session>, sub_id: String) -> bool {
+    pub async fn shutdown(sess: &Arc<Session>, sub_id: String, reason: SessionEndReason) -> bool {
         sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
         sess.services
             .unified_exec_manager
@@ -2016,6 +4696,15 @@ mod handlers {
             .await;
         info!("Shutting down Codex instance");
 
+        // The end record goes to the rollout before the recorder stops; a
+        // rollout without it belongs to a session that did not end cleanly.
+        let ended = SessionEndedEvent {
+            reason,
+            stats: sess.services.session_stats.stats(sess.rollout_path().await),
+        };
+        sess.persist_rollout_items(&[RolloutItem::EventMsg(EventMsg::SessionEnded(ended.clone()))])
+            .await;
+
         // Gracefully flush and shutdown rollout recorder on session end so tests
         // that inspect the rollout file do not race with the background writer.
         let recorder_opt = {
@@ -2032,15 +4721,24 @@ mod handlers {
                     message: "Failed to shutdown rollout recorder".to_string(),
                     codex_error_info: Some(CodexErrorInfo::Other),
                 }),
+                correlation_id: None,
             };
             sess.send_event_raw(event).await;
         }
 
         let event = Event {
-            id: sub_id,
+            id: sub_id.clone(),
             msg: EventMsg::ShutdownComplete,
+            correlation_id: None,
+        };
+        sess.send_event_raw(event).await;
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::SessionEnded(ended.clone()),
+            correlation_id: None,
         };
         sess.send_event_raw(event).await;
+        sess.services.session_stats.mark_ended(ended);
         true
     }
 
@@ -2069,6 +4767,7 @@ mod handlers {
                         message: err.to_string(),
                         codex_error_info: Some(CodexErrorInfo::Other),
                     }),
+                    correlation_id: None,
                 };
                 sess.send_event(&turn_context, event.msg).await;
             }
@@ -2095,14 +4794,15 @@ async fn spawn_review_thread(
     review_features
         .disable(crate::features::Feature::WebSearchRequest)
         .disable(crate::features::Feature::ViewImageTool);
+    let provider = parent_turn_context.client.get_provider();
     let tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
         features: &review_features,
-    });
+    })
+    .with_tool_capabilities(provider.tool_capabilities.clone());
 
     let base_instructions = REVIEW_PROMPT.to_string();
     let review_prompt = resolved.prompt.clone();
-    let provider = parent_turn_context.client.get_provider();
     let auth_manager = parent_turn_context.client.get_auth_manager();
     let model_family = review_model_family.clone();
 
@@ -2111,6 +4811,7 @@ async fn spawn_review_thread(
     per_turn_config.model_reasoning_effort = Some(ReasoningEffortConfig::Low);
     per_turn_config.model_reasoning_summary = ReasoningSummaryConfig::Detailed;
     per_turn_config.features = review_features.clone();
+    per_turn_config.offline = parent_turn_context.client.config().offline;
 
     let otel_manager = parent_turn_context.client.get_otel_manager().with_model(
         config.review_model.as_str(),
@@ -2141,12 +4842,20 @@ async fn spawn_review_thread(
         compact_prompt: parent_turn_context.compact_prompt.clone(),
         approval_policy: parent_turn_context.approval_policy,
         sandbox_policy: parent_turn_context.sandbox_policy.clone(),
+        execution_mode: parent_turn_context.execution_mode,
+        tool_policy: parent_turn_context.tool_policy.clone(),
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
         cwd: parent_turn_context.cwd.clone(),
         final_output_json_schema: None,
         codex_linux_sandbox_exe: parent_turn_context.codex_linux_sandbox_exe.clone(),
         tool_call_gate: Arc::new(ReadinessFlag::new()),
         truncation_policy: TruncationPolicy::new(&per_turn_config, model_family.truncation_policy),
+        turn_tmpdir: TurnTmpdir::new(&per_turn_config),
+        // Reviews keep their own history, which earlier reads are not in.
+        read_cache_min_bytes: None,
+        tool_output_cap: parent_turn_context.tool_output_cap,
+        tool_timeouts: parent_turn_context.tool_timeouts.clone(),
+        deadline: TurnDeadline::new(parent_turn_context.deadline.budget()),
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -2211,6 +4920,11 @@ pub(crate) async fn run_task(
     if input.is_empty() {
         return None;
     }
+    // Cancelled when the turn runs out of time as well as when it is
+    // interrupted.
+    let turn_token = cancellation_token.child_token();
+    let started = Instant::now();
+    let _deadline_timer = turn_context.deadline.start(turn_token.clone());
 
     let auto_compact_limit = turn_context
         .client
@@ -2221,8 +4935,11 @@ pub(crate) async fn run_task(
     if total_usage_tokens >= auto_compact_limit {
         run_auto_compact(&sess, &turn_context).await;
     }
+    let turn_id = sess.start_user_turn(&turn_context).await;
+    sess.track_skill_usage(&turn_context, turn_id).await;
     let event = EventMsg::TaskStarted(TaskStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        turn_id: Some(turn_id),
     });
     sess.send_event(&turn_context, event).await;
 
@@ -2234,11 +4951,16 @@ pub(crate) async fn run_task(
 
     let SkillInjections {
         items: skill_items,
+        skills: injected_skills,
         warnings: skill_warnings,
     } = build_skill_injections(&input, skills_outcome.as_ref()).await;
+    for name in &injected_skills {
+        sess.record_skill_invoked(&turn_context, name).await;
+    }
 
     for message in skill_warnings {
-        sess.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+        let warning = WarningEvent::new(WarningCode::SkillNotInjected, message);
+        sess.send_event(&turn_context, EventMsg::Warning(warning))
             .await;
     }
 
@@ -2255,6 +4977,8 @@ pub(crate) async fn run_task(
     sess.maybe_start_ghost_snapshot(Arc::clone(&turn_context), cancellation_token.child_token())
         .await;
     let mut last_agent_message: Option<String> = None;
+    let mut structured_output_repairs = 0;
+    let mut context_recovered = false;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
@@ -2263,17 +4987,19 @@ pub(crate) async fn run_task(
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
-        let pending_input = sess
+        let (pending_ids, pending_input): (Vec<String>, Vec<ResponseItem>) = sess
             .get_pending_input()
             .await
             .into_iter()
-            .map(ResponseItem::from)
-            .collect::<Vec<ResponseItem>>();
+            .map(|(id, item)| (id, ResponseItem::from(item)))
+            .unzip();
 
         // Construct the input that we will send to the model.
         let turn_input: Vec<ResponseItem> = {
             sess.record_conversation_items(&turn_context, &pending_input)
                 .await;
+            sess.services.turn_results.consumed(&pending_ids);
+            sess.mark_pending_inputs_consumed(pending_ids).await;
             sess.clone_history().await.get_history_for_prompt()
         };
 
@@ -2285,15 +5011,29 @@ pub(crate) async fn run_task(
             })
             .map(|user_message| user_message.message())
             .collect::<Vec<String>>();
-        match run_turn(
+        let result = run_turn(
             Arc::clone(&sess),
             Arc::clone(&turn_context),
             Arc::clone(&turn_diff_tracker),
             turn_input,
-            cancellation_token.child_token(),
+            turn_token.child_token(),
         )
-        .await
+        .await;
+        // Whatever the turn was doing when the deadline passed, it ends here
+        // without a final message and without asking the model again.
+        if let Some(budget) = turn_context.deadline.budget()
+            && turn_context.deadline.is_exceeded()
         {
+            let event = EventMsg::TurnDeadlineExceeded(TurnDeadlineExceededEvent {
+                budget,
+                elapsed: started.elapsed(),
+            });
+            sess.send_event(&turn_context, event).await;
+            sess.record_turn_error(&turn_context, "the turn exceeded its deadline".to_string())
+                .await;
+            break;
+        }
+        match result {
             Ok(turn_output) => {
                 let TurnRunResult {
                     needs_follow_up,
@@ -2308,7 +5048,40 @@ pub(crate) async fn run_task(
                     continue;
                 }
 
+                // Input queued while the model was answering is picked up
+                // before the turn ends.
+                if !needs_follow_up && sess.has_pending_input().await {
+                    sess.services
+                        .turn_results
+                        .answered(&turn_context.sub_id, turn_last_agent_message);
+                    continue;
+                }
+
                 if !needs_follow_up {
+                    if let Some(schema) = turn_context.final_output_json_schema.as_ref()
+                        && let Err(errors) = structured_output::parse_and_validate(
+                            schema,
+                            turn_last_agent_message.as_deref().unwrap_or_default(),
+                        )
+                    {
+                        if structured_output_repairs
+                            < turn_context
+                                .client
+                                .config()
+                                .structured_output_repair_attempts
+                        {
+                            structured_output_repairs += 1;
+                            let repair = structured_output::repair_request(schema, &errors);
+                            sess.record_conversation_items(&turn_context, &[repair])
+                                .await;
+                            continue;
+                        }
+                        let err = CodexErr::StructuredOutputInvalid { errors };
+                        info!("Turn error: {err:#}");
+                        sess.record_turn_error(&turn_context, err.to_string()).await;
+                        let event = EventMsg::Error(err.to_error_event(None));
+                        sess.send_event(&turn_context, event).await;
+                    }
                     last_agent_message = turn_last_agent_message;
                     sess.notifier()
                         .notify(&UserNotification::AgentTurnComplete {
@@ -2326,6 +5099,30 @@ pub(crate) async fn run_task(
                 // Aborted turn is reported via a different event.
                 break;
             }
+            // The request is retried once; a second rejection ends the turn
+            // like any other error.
+            Err(CodexErr::ContextWindowExceeded) if !context_recovered => {
+                context_recovered = true;
+                let strategy = turn_context.client.config().context_recovery;
+                if let Some(recovered) =
+                    recover_context_window(&sess, &turn_context, strategy).await
+                {
+                    sess.send_event(&turn_context, EventMsg::ContextRecovered(recovered))
+                        .await;
+                    continue;
+                }
+                let err = CodexErr::ContextWindowExceeded;
+                info!("Turn error: {err:#}");
+                sess.record_turn_error(&turn_context, err.to_string()).await;
+                let event = EventMsg::Error(err.to_error_event(None));
+                sess.send_event(&turn_context, event).await;
+                break;
+            }
+            Err(CodexErr::TurnRejected(reason)) => {
+                // Reported via `EventMsg::TurnRejected` when it happened.
+                sess.record_turn_error(&turn_context, reason).await;
+                break;
+            }
             Err(CodexErr::InvalidImageRequest()) => {
                 let mut state = sess.state.lock().await;
                 error_or_panic(
@@ -2335,6 +5132,7 @@ pub(crate) async fn run_task(
             }
             Err(e) => {
                 info!("Turn error: {e:#}");
+                sess.record_turn_error(&turn_context, e.to_string()).await;
                 let event = EventMsg::Error(e.to_error_event(None));
                 sess.send_event(&turn_context, event).await;
                 // let the user continue the conversation
@@ -2343,17 +5141,51 @@ pub(crate) async fn run_task(
         }
     }
 
+    let summary = turn_diff_tracker
+        .lock()
+        .await
+        .get_summary(&turn_context.cwd);
+    if let Some(summary) = summary {
+        sess.send_event(&turn_context, EventMsg::TurnDiffSummary(summary))
+            .await;
+    }
+
     last_agent_message
 }
 
-async fn run_auto_compact(sess: &Arc<Session>, turn_context: &Arc<TurnContext>) {
+/// Returns whether the history was compacted.
+async fn run_auto_compact(sess: &Arc<Session>, turn_context: &Arc<TurnContext>) -> bool {
     if should_use_remote_compact_task(sess.as_ref(), &turn_context.client.get_provider()) {
-        run_inline_remote_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await;
+        run_inline_remote_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await
     } else {
-        run_inline_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await;
+        run_inline_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await
     }
 }
 
+/// Shorten the history with `strategy` after the provider rejected the
+/// prompt as longer than the context window. Returns the event reporting it
+/// when the request can be retried.
+async fn recover_context_window(
+    sess: &Arc<Session>,
+    turn_context: &Arc<TurnContext>,
+    strategy: ContextRecoveryStrategy,
+) -> Option<ContextRecoveredEvent> {
+    let dropped_turns = match strategy {
+        ContextRecoveryStrategy::FailFast => return None,
+        ContextRecoveryStrategy::AutoCompact => {
+            if !run_auto_compact(sess, turn_context).await {
+                return None;
+            }
+            0
+        }
+        ContextRecoveryStrategy::AutoTruncate => sess.drop_oldest_turns(turn_context).await?,
+    };
+    Some(ContextRecoveredEvent {
+        strategy,
+        dropped_turns,
+    })
+}
+
 #[instrument(level = "trace",
     skip_all,
     fields(
@@ -2379,6 +5211,7 @@ async fn run_turn(
         .await?;
     let router = Arc::new(ToolRouter::from_config(
         &turn_context.tools_config,
+        &turn_context.tool_policy,
         Some(
             mcp_tools
                 .into_iter()
@@ -2387,10 +5220,17 @@ async fn run_turn(
         ),
     ));
 
+    sess.report_tool_downgrades(&turn_context, router.downgrades())
+        .await;
+
     let model_supports_parallel = turn_context
         .client
         .get_model_family()
-        .supports_parallel_tool_calls;
+        .supports_parallel_tool_calls
+        && turn_context
+            .tools_config
+            .tool_capabilities
+            .supports_parallel_tool_calls;
 
     let prompt = Prompt {
         input,
@@ -2400,7 +5240,11 @@ async fn run_turn(
         output_schema: turn_context.final_output_json_schema.clone(),
     };
 
-    let mut retries = 0;
+    let mut backoff = RetryBackoff::new(
+        turn_context.client.config().retry.model_requests.clone(),
+        RetrySubsystem::ModelRequests,
+    )
+    .with_default_max_retries(turn_context.client.get_provider().stream_max_retries());
     loop {
         match try_run_turn(
             Arc::clone(&router),
@@ -2418,6 +5262,7 @@ async fn run_turn(
                 return Err(CodexErr::TurnAborted);
             }
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
+            Err(e @ CodexErr::TurnRejected(_)) => return Err(e),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
             Err(e @ CodexErr::Fatal(_)) => return Err(e),
             Err(e @ CodexErr::ContextWindowExceeded) => {
@@ -2436,32 +5281,38 @@ async fn run_turn(
             Err(e @ CodexErr::InvalidImageRequest()) => return Err(e),
             Err(e @ CodexErr::InvalidRequest(_)) => return Err(e),
             Err(e @ CodexErr::RefreshTokenFailed(_)) => return Err(e),
+            Err(e @ CodexErr::Offline) => return Err(e),
             Err(e) => {
-                // Use the configured provider-specific stream retry budget.
-                let max_retries = turn_context.client.get_provider().stream_max_retries();
-                if retries < max_retries {
-                    retries += 1;
-                    let delay = match e {
-                        CodexErr::Stream(_, Some(delay)) => delay,
-                        _ => backoff(retries),
-                    };
-                    warn!(
-                        "stream disconnected - retrying turn ({retries}/{max_retries} in {delay:?})...",
-                    );
+                // Retry under the `model_requests` profile, whose limit
+                // defaults to the provider-specific stream retry budget.
+                let Some(retry) = backoff.next_retry(&e) else {
+                    return Err(e);
+                };
+                let retries = retry.attempt;
+                let max_retries = backoff.max_retries();
+                let delay = Duration::from_millis(retry.delay_ms);
+                warn!(
+                    "stream disconnected - retrying turn ({retries}/{max_retries} in {delay:?})...",
+                );
 
-                    // Surface retry information to any UI/front‑end so the
-                    // user understands what is happening instead of staring
-                    // at a seemingly frozen screen.
-                    sess.notify_stream_error(
-                        &turn_context,
-                        format!("Reconnecting... {retries}/{max_retries}"),
-                        e,
-                    )
+                // Surface retry information to any UI/front‑end so the
+                // user understands what is happening instead of staring
+                // at a seemingly frozen screen.
+                sess.notify_stream_error(
+                    &turn_context,
+                    format!("Reconnecting... {retries}/{max_retries}"),
+                    e,
+                )
+                .await;
+                sess.send_event(&turn_context, EventMsg::Retrying(retry))
                     .await;
 
-                    tokio::time::sleep(delay).await;
-                } else {
-                    return Err(e);
+                if tokio::time::sleep(delay)
+                    .or_cancel(&cancellation_token)
+                    .await
+                    .is_err()
+                {
+                    return Err(CodexErr::TurnAborted);
                 }
             }
         }
@@ -2521,16 +5372,38 @@ async fn try_run_turn(
         developer_instructions: turn_context.developer_instructions.clone(),
         final_output_json_schema: turn_context.final_output_json_schema.clone(),
         truncation_policy: Some(turn_context.truncation_policy.into()),
+        sampling: sess.turn_sampling(&turn_context).await,
+        turn_tmpdir: Some(turn_context.turn_tmpdir.path().to_path_buf()),
     });
 
-    sess.persist_rollout_items(&[rollout_item]).await;
-    let mut stream = turn_context
-        .client
-        .clone()
-        .stream(prompt)
-        .instrument(trace_span!("stream_request"))
-        .or_cancel(&cancellation_token)
-        .await??;
+    sess.persist_turn_rollout_items(&turn_context.sub_id, &[rollout_item])
+        .await;
+    let stream = heartbeat::while_pending(
+        &sess,
+        &turn_context,
+        HeartbeatPhase::WaitingForModel,
+        turn_context
+            .client
+            .clone()
+            .stream(prompt)
+            .instrument(trace_span!("stream_request")),
+    )
+    .or_cancel(&cancellation_token)
+    .await?;
+    // Token refreshes are retried whether or not the request then succeeds.
+    for retry in turn_context.client.take_auth_refresh_retries() {
+        sess.send_event(&turn_context, EventMsg::Retrying(retry))
+            .await;
+    }
+    let mut stream = stream?;
+    for switch in turn_context.client.take_endpoint_switches() {
+        sess.send_event(&turn_context, EventMsg::ProviderEndpointSwitched(switch))
+            .await;
+    }
+    if let Some(fingerprint) = turn_context.client.take_request_fingerprint() {
+        sess.record_request_fingerprint(&turn_context, fingerprint)
+            .await;
+    }
 
     let tool_runtime = ToolCallRuntime::new(
         Arc::clone(&router),
@@ -2543,7 +5416,14 @@ async fn try_run_turn(
     let mut needs_follow_up = false;
     let mut last_agent_message: Option<String> = None;
     let mut active_item: Option<TurnItem> = None;
+    // With post-processors registered, completed items are held back until
+    // the stream completes, along with the item that was active for each.
+    let mut deferred_items: Option<Vec<(ResponseItem, Option<TurnItem>)>> =
+        (!sess.services.post_processors.is_empty()).then(Vec::new);
     let mut should_emit_turn_diff = false;
+    let mut coalescer = DeltaCoalescer::new(turn_context.client.config().delta_coalescing.clone());
+    let mut partial_messages =
+        PartialMessageRecorder::new(turn_context.client.config().partial_messages.clone());
     let receiving_span = trace_span!("receiving_stream");
     let outcome: CodexResult<TurnRunResult> = loop {
         let handle_responses = trace_span!(
@@ -2554,18 +5434,37 @@ async fn try_run_turn(
             from = field::Empty,
         );
 
-        let event = match stream
-            .next()
-            .instrument(trace_span!(parent: &handle_responses, "receiving"))
-            .or_cancel(&cancellation_token)
-            .await
+        // Waiting stops early when held deltas are due.
+        let flush_deadline = coalescer.deadline();
+        let next_event = async {
+            match flush_deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, stream.next()).await.ok(),
+                None => Some(stream.next().await),
+            }
+        };
+        let event = match heartbeat::while_pending(
+            &sess,
+            &turn_context,
+            HeartbeatPhase::WaitingForModel,
+            next_event.instrument(trace_span!(parent: &handle_responses, "receiving")),
+        )
+        .or_cancel(&cancellation_token)
+        .await
         {
             Ok(event) => event,
             Err(codex_async_utils::CancelErr::Cancelled) => break Err(CodexErr::TurnAborted),
         };
 
+        let Some(event) = event else {
+            send_agent_message_deltas(&sess, &turn_context, coalescer.take()).await;
+            continue;
+        };
         let event = match event {
-            Some(res) => res?,
+            Some(Ok(event)) => event,
+            Some(Err(err)) => {
+                send_agent_message_deltas(&sess, &turn_context, coalescer.take()).await;
+                return Err(err);
+            }
             None => {
                 break Err(CodexErr::Stream(
                     "stream closed before response.completed".into(),
@@ -2578,10 +5477,19 @@ async fn try_run_turn(
             .otel_manager
             .record_responses(&handle_responses, &event);
 
+        // Held deltas go out before anything else the turn reports.
+        if !matches!(event, ResponseEvent::OutputTextDelta(_)) {
+            send_agent_message_deltas(&sess, &turn_context, coalescer.take()).await;
+        }
+
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
                 let previously_active_item = active_item.take();
+                if let Some(deferred_items) = deferred_items.as_mut() {
+                    deferred_items.push((item, previously_active_item));
+                    continue;
+                }
                 let mut ctx = HandleOutputCtx {
                     sess: sess.clone(),
                     turn_context: turn_context.clone(),
@@ -2621,6 +5529,43 @@ async fn try_run_turn(
                     .await;
                 should_emit_turn_diff = true;
 
+                if let Some(deferred_items) = deferred_items.take() {
+                    let (items, mut started): (Vec<_>, Vec<_>) = deferred_items.into_iter().unzip();
+                    let mut turn = TurnOutput {
+                        turn_id: turn_context.sub_id.clone(),
+                        items,
+                    };
+                    if let PostProcessOutcome::RejectTurn { reason } =
+                        run_post_processors(&sess.services.post_processors, &mut turn)
+                    {
+                        let event = EventMsg::TurnRejected(TurnRejectedEvent {
+                            reason: reason.clone(),
+                        });
+                        sess.send_event(&turn_context, event).await;
+                        break Err(CodexErr::TurnRejected(reason));
+                    }
+                    // Processors may add or remove items; the started item of
+                    // an original position carries over to its replacement.
+                    started.resize(turn.items.len(), None);
+                    for (item, previously_active_item) in turn.items.into_iter().zip(started) {
+                        let mut ctx = HandleOutputCtx {
+                            sess: sess.clone(),
+                            turn_context: turn_context.clone(),
+                            tool_runtime: tool_runtime.clone(),
+                            cancellation_token: cancellation_token.child_token(),
+                        };
+                        let output_result =
+                            handle_output_item_done(&mut ctx, item, previously_active_item).await?;
+                        if let Some(tool_future) = output_result.tool_future {
+                            in_flight.push_back(tool_future);
+                        }
+                        if let Some(agent_message) = output_result.last_agent_message {
+                            last_agent_message = Some(agent_message);
+                        }
+                        needs_follow_up |= output_result.needs_follow_up;
+                    }
+                }
+
                 break Ok(TurnRunResult {
                     needs_follow_up,
                     last_agent_message,
@@ -2630,14 +5575,17 @@ async fn try_run_turn(
                 // In review child threads, suppress assistant text deltas; the
                 // UI will show a selection popup from the final ReviewOutput.
                 if let Some(active) = active_item.as_ref() {
+                    let item_id = active.id();
+                    if let Some(text) = partial_messages.push(&item_id, &delta) {
+                        sess.record_partial_message(&turn_context, text).await;
+                    }
                     let event = AgentMessageContentDeltaEvent {
                         thread_id: sess.conversation_id.to_string(),
                         turn_id: turn_context.sub_id.clone(),
-                        item_id: active.id(),
-                        delta: delta.clone(),
+                        item_id,
+                        delta,
                     };
-                    sess.send_event(&turn_context, EventMsg::AgentMessageContentDelta(event))
-                        .await;
+                    send_agent_message_deltas(&sess, &turn_context, coalescer.push(event)).await;
                 } else {
                     error_or_panic("OutputTextDelta without active item".to_string());
                 }
@@ -2692,6 +5640,7 @@ async fn try_run_turn(
             }
         }
     };
+    send_agent_message_deltas(&sess, &turn_context, coalescer.take()).await;
 
     drain_in_flight(&mut in_flight, sess.clone(), turn_context.clone()).await?;
 
@@ -2709,6 +5658,27 @@ async fn try_run_turn(
     outcome
 }
 
+async fn send_agent_message_deltas(
+    sess: &Session,
+    turn_context: &TurnContext,
+    deltas: impl IntoIterator<Item = AgentMessageContentDeltaEvent>,
+) {
+    for delta in deltas {
+        sess.send_event(turn_context, EventMsg::AgentMessageContentDelta(delta))
+            .await;
+    }
+}
+
+/// Tool policy a resumed conversation last switched to, if it changed it.
+fn last_recorded_tool_policy(items: &[RolloutItem]) -> Option<ToolPolicy> {
+    items.iter().rev().find_map(|item| match item {
+        RolloutItem::EventMsg(EventMsg::ToolPolicyUpdated(tool_policy)) => {
+            Some(tool_policy.clone())
+        }
+        _ => None,
+    })
+}
+
 pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
     responses.iter().rev().find_map(|item| {
         if let ResponseItem::Message { role, content, .. } = item {
@@ -2740,6 +5710,7 @@ mod tests {
     use super::*;
     use crate::CodexAuth;
     use crate::config::ConfigBuilder;
+    use crate::context_usage::ApproxTokenCounter;
     use crate::exec::ExecToolCallOutput;
     use crate::function_tool::FunctionCallError;
     use crate::shell::default_user_shell;
@@ -2747,7 +5718,6 @@ mod tests {
 
     use codex_protocol::models::FunctionCallOutputPayload;
 
-    use crate::protocol::CompactedItem;
     use crate::protocol::CreditsSnapshot;
     use crate::protocol::InitialHistory;
     use crate::protocol::RateLimitSnapshot;
@@ -2785,7 +5755,8 @@ mod tests {
         let (session, turn_context) = make_session_and_context().await;
         let (rollout_items, expected) = sample_rollout(&session, &turn_context);
 
-        let reconstructed = session.reconstruct_history_from_rollout(&turn_context, &rollout_items);
+        let (reconstructed, _, _, _) =
+            session.reconstruct_history_from_rollout(&turn_context, &rollout_items);
 
         assert_eq!(expected, reconstructed);
     }
@@ -2796,11 +5767,15 @@ mod tests {
         let (rollout_items, expected) = sample_rollout(&session, &turn_context);
 
         session
-            .record_initial_history(InitialHistory::Resumed(ResumedHistory {
-                conversation_id: ConversationId::default(),
-                history: rollout_items,
-                rollout_path: PathBuf::from("/tmp/resume.jsonl"),
-            }))
+            .record_initial_history(
+                InitialHistory::Resumed(ResumedHistory {
+                    conversation_id: ConversationId::default(),
+                    history: rollout_items,
+                    rollout_path: PathBuf::from("/tmp/resume.jsonl"),
+                    parse_errors: Vec::new(),
+                }),
+                None,
+            )
             .await;
 
         let actual = session.state.lock().await.clone_history().get_history();
@@ -2813,7 +5788,7 @@ mod tests {
         let (rollout_items, expected) = sample_rollout(&session, &turn_context);
 
         session
-            .record_initial_history(InitialHistory::Forked(rollout_items))
+            .record_initial_history(InitialHistory::Forked(rollout_items), None)
             .await;
 
         let actual = session.state.lock().await.clone_history().get_history();
@@ -2837,9 +5812,16 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            execution_mode: config.execution_mode,
+            tool_policy: config.tool_policy.clone(),
+            sampling: config.sampling,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
+            skills_selection: None,
+            skill_tools: Vec::new(),
+            model_list_source: None,
+            offline: false,
         };
 
         let mut state = SessionState::new(session_configuration);
@@ -2903,9 +5885,16 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            execution_mode: config.execution_mode,
+            tool_policy: config.tool_policy.clone(),
+            sampling: config.sampling,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
+            skills_selection: None,
+            skill_tools: Vec::new(),
+            model_list_source: None,
+            offline: false,
         };
 
         let mut state = SessionState::new(session_configuration);
@@ -3110,9 +6099,16 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            execution_mode: config.execution_mode,
+            tool_policy: config.tool_policy.clone(),
+            sampling: config.sampling,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
+            skills_selection: None,
+            skill_tools: Vec::new(),
+            model_list_source: None,
+            offline: false,
         };
         let per_turn_config = Session::build_per_turn_config(&session_configuration);
         let model_family = ModelsManager::construct_model_family_offline(
@@ -3135,14 +6131,46 @@ mod tests {
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: UserNotifier::new(None),
             rollout: Mutex::new(None),
+            live_rollout: None,
             user_shell: Arc::new(default_user_shell()),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            min_warning_severity: config.min_warning_severity,
+            adaptive_effort: config.adaptive_effort.clone(),
             exec_policy,
             auth_manager: auth_manager.clone(),
             otel_manager: otel_manager.clone(),
             models_manager,
             tool_approvals: Mutex::new(ApprovalStore::default()),
+            project_approvals: ProjectApprovalStore::new(&config.codex_home),
+            project_notes: ProjectNotes::new(&config.codex_home, &config.cwd),
+            project_notes_prefix: None,
+            pre_processors: Vec::new(),
+            post_processors: Vec::new(),
             skills_manager,
+            request_trace: None,
+            token_counter: Arc::new(ApproxTokenCounter),
+            context_usage: Arc::new(ContextUsageTracker::new(
+                config.context_usage_thresholds.clone(),
+            )),
+            sensitive_input: Arc::new(SensitiveInputGuard::new(config.sensitive_input.as_ref())),
+            reasoning_log: Arc::new(ReasoningLog::default()),
+            transcript_journal: Arc::new(TranscriptJournal::default()),
+            git_context: Arc::new(GitContextTracker::default()),
+            persist_reasoning_summaries: config.persist_reasoning_summaries,
+            record_input_rejections: config.record_input_rejections,
+            user_context: config
+                .user_context
+                .clone()
+                .map(|context| UserContextClock::new(context, &RuntimeEnv::default())),
+            instructions_watcher: InstructionsWatcher::new(&config, None).await,
+            session_stats: SessionStatsTracker::new(RuntimeEnv::default()),
+            correlation_ids: CorrelationIds::default(),
+            turn_results: Arc::new(TurnResults::default()),
+            pause: Arc::default(),
+            load: Arc::default(),
+            read_cache: ReadCache::default(),
+            blobs: BlobStore::new(&config.codex_home),
+            runtime_env: RuntimeEnv::default(),
         };
 
         let turn_context = Session::make_turn_context(
@@ -3160,6 +6188,7 @@ mod tests {
             conversation_id,
             tx_event,
             state: Mutex::new(state),
+            transcript: Mutex::new(()),
             features: config.features.clone(),
             active_turn: Mutex::new(None),
             services,
@@ -3197,9 +6226,16 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            execution_mode: config.execution_mode,
+            tool_policy: config.tool_policy.clone(),
+            sampling: config.sampling,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
+            skills_selection: None,
+            skill_tools: Vec::new(),
+            model_list_source: None,
+            offline: false,
         };
         let per_turn_config = Session::build_per_turn_config(&session_configuration);
         let model_family = ModelsManager::construct_model_family_offline(
@@ -3222,14 +6258,46 @@ mod tests {
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: UserNotifier::new(None),
             rollout: Mutex::new(None),
+            live_rollout: None,
             user_shell: Arc::new(default_user_shell()),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            min_warning_severity: config.min_warning_severity,
+            adaptive_effort: config.adaptive_effort.clone(),
             exec_policy,
             auth_manager: Arc::clone(&auth_manager),
             otel_manager: otel_manager.clone(),
             models_manager,
             tool_approvals: Mutex::new(ApprovalStore::default()),
+            project_approvals: ProjectApprovalStore::new(&config.codex_home),
+            project_notes: ProjectNotes::new(&config.codex_home, &config.cwd),
+            project_notes_prefix: None,
+            pre_processors: Vec::new(),
+            post_processors: Vec::new(),
             skills_manager,
+            request_trace: None,
+            token_counter: Arc::new(ApproxTokenCounter),
+            context_usage: Arc::new(ContextUsageTracker::new(
+                config.context_usage_thresholds.clone(),
+            )),
+            sensitive_input: Arc::new(SensitiveInputGuard::new(config.sensitive_input.as_ref())),
+            reasoning_log: Arc::new(ReasoningLog::default()),
+            transcript_journal: Arc::new(TranscriptJournal::default()),
+            git_context: Arc::new(GitContextTracker::default()),
+            persist_reasoning_summaries: config.persist_reasoning_summaries,
+            record_input_rejections: config.record_input_rejections,
+            user_context: config
+                .user_context
+                .clone()
+                .map(|context| UserContextClock::new(context, &RuntimeEnv::default())),
+            instructions_watcher: InstructionsWatcher::new(&config, None).await,
+            session_stats: SessionStatsTracker::new(RuntimeEnv::default()),
+            correlation_ids: CorrelationIds::default(),
+            turn_results: Arc::new(TurnResults::default()),
+            pause: Arc::default(),
+            load: Arc::default(),
+            read_cache: ReadCache::default(),
+            blobs: BlobStore::new(&config.codex_home),
+            runtime_env: RuntimeEnv::default(),
         };
 
         let turn_context = Arc::new(Session::make_turn_context(
@@ -3247,6 +6315,7 @@ mod tests {
             conversation_id,
             tx_event,
             state: Mutex::new(state),
+            transcript: Mutex::new(()),
             features: config.features.clone(),
             active_turn: Mutex::new(None),
             services,
@@ -3432,6 +6501,7 @@ mod tests {
         };
         let router = ToolRouter::from_config(
             &turn_context.tools_config,
+            &turn_context.tool_policy,
             Some(
                 tools
                     .into_iter()
@@ -1,39 +1,409 @@
+use crate::child_conversations::ChildConversationOverrides;
 use crate::codex::Codex;
+use crate::conversation_manager::ChildSpawner;
+use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
+use crate::event_replay::EventReplayBuffer;
+use crate::event_replay::ReplayGap;
+use crate::event_replay::SequencedEvent;
+use crate::event_subscriptions::EventSubscription;
+use crate::handoff::HandoffOptions;
+use crate::handoff::generate_handoff;
+use crate::handoff::handoff_item;
+use crate::handoff::handoff_model;
+use crate::handoff::record_handoff;
+use crate::manager_load::ConversationLoad;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ConversationMetrics;
+use crate::protocol::CheckpointId;
+use crate::protocol::ContextUsage;
 use crate::protocol::Event;
+use crate::protocol::ItemRef;
 use crate::protocol::Op;
 use crate::protocol::Submission;
+use crate::rollout::TranscriptTurn;
+use crate::rollout::live::LiveRollout;
+use crate::rollout::transcript::read_transcript;
+use crate::rollout::transcript::read_transcript_turns;
+use crate::transcript_journal::TranscriptFootprint;
+use crate::turn_results::TurnResult;
+use crate::turn_results::TurnTicket;
+use codex_protocol::ConversationId;
+use codex_protocol::config_types::ToolPolicy;
+use codex_protocol::event_filter::EventFilter;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::GitInfo;
+use codex_protocol::protocol::HandoffDocument;
+use codex_protocol::protocol::PauseMode;
+use codex_protocol::protocol::SessionEndReason;
+use codex_protocol::protocol::SessionEndedEvent;
+use codex_protocol::protocol::TranscriptMutation;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub struct CodexConversation {
     codex: Codex,
+    conversation_id: ConversationId,
     rollout_path: PathBuf,
+    children: ChildSpawner,
+    replay: EventReplayBuffer,
+    #[cfg(feature = "metrics")]
+    metrics: Option<ConversationMetrics>,
 }
 
 /// Conduit for the bidirectional stream of messages that compose a conversation
 /// in Codex.
 impl CodexConversation {
-    pub(crate) fn new(codex: Codex, rollout_path: PathBuf) -> Self {
+    pub(crate) fn new(
+        codex: Codex,
+        conversation_id: ConversationId,
+        rollout_path: PathBuf,
+        children: ChildSpawner,
+        replay: EventReplayBuffer,
+    ) -> Self {
         Self {
             codex,
+            conversation_id,
             rollout_path,
+            children,
+            replay,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
+    /// Report this conversation's turns, tokens and queue depth to the
+    /// manager's metrics.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(self, metrics: ConversationMetrics) -> Self {
+        Self {
+            metrics: Some(metrics),
+            ..self
+        }
+    }
+
+    pub fn conversation_id(&self) -> ConversationId {
+        self.conversation_id
+    }
+
     pub async fn submit(&self, op: Op) -> CodexResult<String> {
         self.codex.submit(op).await
     }
 
+    /// Submit `op` with a client-chosen `correlation_id`, echoed on every
+    /// event of the submission and of the turn it starts.
+    pub async fn submit_with_correlation(
+        &self,
+        op: Op,
+        correlation_id: impl Into<String>,
+    ) -> CodexResult<String> {
+        self.codex
+            .submit_with_correlation(op, Some(correlation_id.into()))
+            .await
+    }
+
+    /// Submit `op` and return a ticket for the result of the turn it starts,
+    /// or of the running turn its input is added to. Pass the ticket to
+    /// [`Self::wait_for_turn`]; results are delivered whether or not anyone
+    /// reads [`Self::next_event`].
+    pub async fn submit_with_ticket(&self, op: Op) -> CodexResult<TurnTicket> {
+        self.codex.submit_with_ticket(op).await
+    }
+
+    /// Wait for the result of the submission `ticket` was returned for.
+    /// Interrupted and replaced turns resolve with
+    /// [`crate::turn_results::TurnError::Aborted`], submissions that start no
+    /// turn with an error of their own.
+    pub async fn wait_for_turn(&self, ticket: TurnTicket) -> CodexResult<TurnResult> {
+        ticket.wait().await.ok_or(CodexErr::InternalAgentDied)
+    }
+
     /// Use sparingly: this is intended to be removed soon.
     pub async fn submit_with_id(&self, sub: Submission) -> CodexResult<()> {
         self.codex.submit_with_id(sub).await
     }
 
+    /// Record a named checkpoint at the current end of the transcript. The
+    /// returned id can later be passed to [`Self::rollback_to_checkpoint`].
+    pub async fn checkpoint(&self, label: impl Into<String>) -> CodexResult<CheckpointId> {
+        let id = CheckpointId::new();
+        self.codex
+            .submit(Op::Checkpoint {
+                id: id.clone(),
+                label: label.into(),
+            })
+            .await?;
+        Ok(id)
+    }
+
+    /// Truncate the transcript back to the given checkpoint. Checkpoints
+    /// recorded after it are invalidated.
+    pub async fn rollback_to_checkpoint(&self, id: CheckpointId) -> CodexResult<String> {
+        self.codex.submit(Op::RollbackToCheckpoint { id }).await
+    }
+
+    /// Pin the history item `item` names, so compaction and token-budget
+    /// truncation keep it verbatim. An item whose place is compacted away is
+    /// moved ahead of the compaction summary. Pins are recorded in the
+    /// rollout and restored on resume; forks keep them only for the items
+    /// before the fork point, see
+    /// [`crate::ConversationManager::fork_conversation`].
+    pub async fn pin_item(&self, item: ItemRef) -> CodexResult<String> {
+        self.codex.submit(Op::PinItem { item }).await
+    }
+
+    /// Remove the pin added by [`Self::pin_item`].
+    pub async fn unpin_item(&self, item: ItemRef) -> CodexResult<String> {
+        self.codex.submit(Op::UnpinItem { item }).await
+    }
+
+    /// Spawn a child conversation through the manager that spawned this one
+    /// and start it on `seed_prompt`. The child starts from this
+    /// conversation's configuration with `overrides` applied and its own empty
+    /// transcript; both rollouts record the link. Its events are read from the
+    /// child's own [`CodexConversation`], which the manager tracks under the
+    /// returned id.
+    pub async fn spawn_child(
+        &self,
+        overrides: ChildConversationOverrides,
+        seed_prompt: impl Into<String>,
+    ) -> CodexResult<ConversationId> {
+        self.children
+            .spawn_child(self, overrides, seed_prompt.into())
+            .await
+    }
+
+    /// Add the final answer of child `child_id` to this conversation's
+    /// transcript. Waits for the child's running turn, if any, to finish;
+    /// fails if the child has not answered yet or is not a child of this
+    /// conversation.
+    pub async fn ingest_child_result(&self, child_id: ConversationId) -> CodexResult<String> {
+        self.children.ingest_child_result(self, child_id).await
+    }
+
+    /// Replace the tools this conversation may use. Takes effect from the
+    /// next turn and is kept when the conversation is resumed.
+    pub async fn update_tool_policy(&self, tool_policy: ToolPolicy) -> CodexResult<String> {
+        self.codex
+            .submit(Op::UpdateToolPolicy { tool_policy })
+            .await
+    }
+
+    /// Turn safe mode on or off, see [`crate::safe_mode`]. Turning it on
+    /// takes effect from the next turn. Turning it off is first sent for
+    /// approval as an [`crate::protocol::EventMsg::ExecApprovalRequest`],
+    /// answered with [`Op::ExecApproval`] like a command approval. The
+    /// session reports each change with
+    /// [`crate::protocol::EventMsg::SafeModeChanged`].
+    pub async fn set_safe_mode(&self, enabled: bool) -> CodexResult<String> {
+        self.codex.submit(Op::SetSafeMode { enabled }).await
+    }
+
+    /// Pause the conversation without ending its session: its history and
+    /// tools stay in memory, so [`Self::resume`] is immediate. Submissions that would start a turn fail with
+    /// [`CodexErr::ConversationPaused`] from now on. Waits until the running
+    /// turn, if any, finished or was aborted according to `mode` and the
+    /// rollout was flushed; the session reports
+    /// [`crate::protocol::EventMsg::ConversationPaused`] then. Returns at once
+    /// when the conversation is already paused.
+    pub async fn pause(&self, mode: PauseMode) -> CodexResult<()> {
+        self.codex.pause(mode).await
+    }
+
+    /// Accept submissions again after [`Self::pause`]. Also cancels a pause
+    /// still waiting for its turn to end. The session reports
+    /// [`crate::protocol::EventMsg::ConversationResumed`].
+    pub async fn resume(&self) -> CodexResult<()> {
+        self.codex.resume().await
+    }
+
+    /// Whether [`Self::pause`] completed and the conversation was not
+    /// resumed since.
+    pub fn is_paused(&self) -> bool {
+        self.codex.is_paused()
+    }
+
+    /// Run the turns submitted while the conversation was offline, one after
+    /// the other in the order they were submitted. Turn offline mode off
+    /// with [`Op::OverrideTurnContext`] first: while still offline the
+    /// session answers with an error event and keeps the queue.
+    pub async fn flush_queued(&self) -> CodexResult<String> {
+        self.codex.submit(Op::FlushQueued).await
+    }
+
+    /// Shut the conversation down and wait for its final
+    /// [`crate::protocol::EventMsg::SessionEnded`], whose totals are returned.
+    /// The events leading up to it are still delivered through
+    /// [`Self::next_event`].
+    pub async fn end_session(&self, reason: SessionEndReason) -> CodexResult<SessionEndedEvent> {
+        self.codex.end_session(reason).await
+    }
+
     pub async fn next_event(&self) -> CodexResult<Event> {
-        self.codex.next_event().await
+        Ok(self.next_sequenced_event().await?.event)
+    }
+
+    /// A separate stream of the events `filter` accepts, starting with the
+    /// next event the session emits. It does not take events from
+    /// [`Self::next_event`], so several subscriptions can run next to the
+    /// main stream, each with its own filter.
+    pub fn subscribe_filtered(&self, filter: EventFilter) -> EventSubscription {
+        match &self.codex.event_subscribers {
+            Some(subscribers) => subscribers.subscribe(filter),
+            None => EventSubscription::closed(),
+        }
+    }
+
+    /// Like [`Self::next_event`], together with the event's sequence number
+    /// for [`Self::events_since`]. Numbers follow delivery order, so events
+    /// should be read by a single task.
+    pub async fn next_sequenced_event(&self) -> CodexResult<SequencedEvent> {
+        let event = self.codex.next_event().await?;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe(&event, self.codex.rx_event.len() + 1);
+        }
+        Ok(self.replay.push(event))
+    }
+
+    /// Delivered events with a sequence number greater than `seq`, and the
+    /// number of the latest delivered event. Pass `0` for every retained
+    /// event. Fails with [`ReplayGap`] when some of the requested events
+    /// are no longer retained.
+    pub fn events_since(&self, seq: u64) -> Result<(Vec<SequencedEvent>, u64), ReplayGap> {
+        self.replay.since(seq)
     }
 
     pub fn rollout_path(&self) -> PathBuf {
         self.rollout_path.clone()
     }
+
+    /// Every item of the conversation, including those compaction removed
+    /// from what the model sees, read back from the rollout. Items discarded
+    /// by a rollback are left out.
+    pub async fn transcript(&self) -> CodexResult<Vec<ResponseItem>> {
+        if let Some(live_rollout) = self.live_rollout() {
+            live_rollout.flush().await?;
+        }
+        match &self.codex.transcript_journal {
+            Some(journal) => Ok(journal.transcript(&self.rollout_path).await?),
+            None => Ok(read_transcript(&self.rollout_path).await?),
+        }
+    }
+
+    /// [`Self::transcript`], grouped by the user turn that recorded each
+    /// item. Turn ids can be passed to
+    /// [`crate::ConversationManager::fork_conversation_at_turn`].
+    pub async fn transcript_turns(&self) -> CodexResult<Vec<TranscriptTurn>> {
+        if let Some(live_rollout) = self.live_rollout() {
+            live_rollout.flush().await?;
+        }
+        Ok(read_transcript_turns(&self.rollout_path).await?)
+    }
+
+    /// Summarize the conversation for a human taking it over, with the
+    /// credentials of the session, and record the summary in the rollout.
+    /// See [`crate::handoff`].
+    pub async fn generate_handoff(&self, options: HandoffOptions) -> CodexResult<HandoffDocument> {
+        let Some(context) = self.codex.handoff.as_ref() else {
+            return Err(CodexErr::UnsupportedOperation(
+                "this conversation cannot write a handoff".to_string(),
+            ));
+        };
+        let model = handoff_model(&options, &context.config, Some(context.model.as_str()))?;
+        if let Some(live_rollout) = self.live_rollout() {
+            live_rollout.flush().await?;
+        }
+        let document = generate_handoff(
+            &context.config,
+            Arc::clone(&context.auth_manager),
+            self.conversation_id,
+            &self.rollout_path,
+            model,
+        )
+        .await?;
+        match self.live_rollout() {
+            Some(live_rollout) => live_rollout.record(&[handoff_item(&document)]).await?,
+            None => record_handoff(&context.config, &self.rollout_path, &document).await?,
+        }
+        Ok(document)
+    }
+
+    /// Estimated context usage as of the last completed turn.
+    pub fn context_usage(&self) -> ContextUsage {
+        self.codex
+            .context_usage
+            .as_ref()
+            .map(|tracker| tracker.usage())
+            .unwrap_or_default()
+    }
+
+    /// Reasoning summaries of turn `turn_index`, counted from 0 by user
+    /// message like [`crate::ConversationManager::fork_conversation`] counts
+    /// them. `None` when the conversation has fewer turns.
+    pub fn reasoning_for_turn(&self, turn_index: usize) -> Option<Vec<String>> {
+        self.codex
+            .reasoning_log
+            .as_ref()
+            .and_then(|log| log.for_turn(turn_index))
+    }
+
+    /// Every change made to the history, oldest first, including those made
+    /// before the conversation was resumed or forked.
+    pub fn mutation_log(&self) -> Vec<TranscriptMutation> {
+        self.codex
+            .transcript_journal
+            .as_ref()
+            .map(|journal| journal.mutations())
+            .unwrap_or_default()
+    }
+
+    /// The history as it would be sent to the model right after the change
+    /// `mutation_seq` of [`Self::mutation_log`], or `None` when there is no
+    /// such change. Changes made before a resume are replayed from the items
+    /// the rollout kept, and items older than the last compaction are read
+    /// back from the rollout.
+    pub async fn transcript_as_of(
+        &self,
+        mutation_seq: u64,
+    ) -> CodexResult<Option<Vec<ResponseItem>>> {
+        let Some(journal) = &self.codex.transcript_journal else {
+            return Ok(None);
+        };
+        if let Some(live_rollout) = self.live_rollout() {
+            live_rollout.flush().await?;
+        }
+        Ok(journal
+            .transcript_as_of(mutation_seq, &self.rollout_path)
+            .await?)
+    }
+
+    /// How many of the items behind [`Self::mutation_log`] are held in
+    /// memory. Compaction leaves the older ones in the rollout.
+    pub fn transcript_footprint(&self) -> TranscriptFootprint {
+        self.codex
+            .transcript_journal
+            .as_ref()
+            .map(|journal| journal.footprint())
+            .unwrap_or_default()
+    }
+
+    /// Git context of the workspace as last recorded in the rollout: at the
+    /// start of the session, then whenever a turn started on a new HEAD.
+    /// `None` outside a git repository.
+    pub fn git_context(&self) -> Option<GitInfo> {
+        self.codex
+            .git_context
+            .as_ref()
+            .and_then(|tracker| tracker.current())
+    }
+
+    /// What this conversation adds to its manager's load.
+    pub(crate) fn load(&self) -> Option<&ConversationLoad> {
+        self.codex.load.as_deref()
+    }
+
+    pub(crate) fn live_rollout(&self) -> Option<&LiveRollout> {
+        self.codex.live_rollout.as_ref()
+    }
 }
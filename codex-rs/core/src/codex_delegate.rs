@@ -26,6 +26,7 @@ use crate::codex::TurnContext;
 use crate::config::Config;
 use crate::error::CodexErr;
 use crate::models_manager::manager::ModelsManager;
+use crate::spawn_observer::SpawnProgress;
 use codex_protocol::protocol::InitialHistory;
 
 /// Start an interactive sub-Codex conversation and return IO channels.
@@ -52,6 +53,12 @@ pub(crate) async fn run_codex_conversation_interactive(
         Arc::clone(&parent_session.services.skills_manager),
         initial_history.unwrap_or(InitialHistory::New),
         SessionSource::SubAgent(SubAgentSource::Review),
+        Vec::new(),
+        Vec::new(),
+        Arc::clone(&parent_session.services.token_counter),
+        parent_session.services.runtime_env.clone(),
+        None,
+        SpawnProgress::default(),
     )
     .await?;
     let codex = Arc::new(codex);
@@ -86,6 +93,18 @@ pub(crate) async fn run_codex_conversation_interactive(
         next_id: AtomicU64::new(0),
         tx_sub: tx_ops,
         rx_event: rx_sub,
+        live_rollout: None,
+        session_task: None,
+        context_usage: None,
+        reasoning_log: None,
+        transcript_journal: None,
+        git_context: None,
+        session_end: None,
+        turn_results: None,
+        pause: None,
+        load: None,
+        event_subscribers: None,
+        handoff: None,
     })
 }
 
@@ -118,7 +137,11 @@ pub(crate) async fn run_codex_conversation_one_shot(
     .await?;
 
     // Send the initial input to kick off the one-shot turn.
-    io.submit(Op::UserInput { items: input }).await?;
+    io.submit(Op::UserInput {
+        items: input,
+        allow_sensitive: false,
+    })
+    .await?;
 
     // Bridge events so we can observe completion and shut down automatically.
     let (tx_bridge, rx_bridge) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
@@ -136,6 +159,7 @@ pub(crate) async fn run_codex_conversation_one_shot(
                     .send(Submission {
                         id: "shutdown".to_string(),
                         op: Op::Shutdown {},
+                        correlation_id: None,
                     })
                     .await;
                 child_cancel.cancel();
@@ -154,6 +178,18 @@ pub(crate) async fn run_codex_conversation_one_shot(
         next_id: AtomicU64::new(0),
         rx_event: rx_bridge,
         tx_sub: tx_closed,
+        live_rollout: None,
+        session_task: None,
+        context_usage: None,
+        reasoning_log: None,
+        transcript_journal: None,
+        git_context: None,
+        session_end: None,
+        turn_results: None,
+        pause: None,
+        load: None,
+        event_subscribers: None,
+        handoff: None,
     })
 }
 
@@ -183,14 +219,17 @@ async fn forward_events(
                     Event {
                         id: _,
                         msg: EventMsg::AgentMessageDelta(_) | EventMsg::AgentReasoningDelta(_),
+                        ..
                     } => {}
                     Event {
                         id: _,
                         msg: EventMsg::SessionConfigured(_),
+                        ..
                     } => {}
                     Event {
                         id,
                         msg: EventMsg::ExecApprovalRequest(event),
+                        ..
                     } => {
                         // Initiate approval via parent session; do not surface to consumer.
                         handle_exec_approval(
@@ -206,6 +245,7 @@ async fn forward_events(
                     Event {
                         id,
                         msg: EventMsg::ApplyPatchApprovalRequest(event),
+                        ..
                     } => {
                         handle_patch_approval(
                             &codex,
@@ -258,7 +298,7 @@ async fn forward_ops(
 ) {
     loop {
         let op: Op = match rx_ops.recv().or_cancel(&cancel_token_ops).await {
-            Ok(Ok(Submission { id: _, op })) => op,
+            Ok(Ok(Submission { id: _, op, .. })) => op,
             Ok(Err(_)) | Err(_) => break,
         };
         let _ = codex.submit(op).await;
@@ -364,6 +404,18 @@ mod tests {
             next_id: AtomicU64::new(0),
             tx_sub,
             rx_event: rx_events,
+            live_rollout: None,
+            session_task: None,
+            context_usage: None,
+            reasoning_log: None,
+            transcript_journal: None,
+            git_context: None,
+            session_end: None,
+            turn_results: None,
+            pause: None,
+            load: None,
+            event_subscribers: None,
+            handoff: None,
         });
 
         let (session, ctx, _rx_evt) = crate::codex::make_session_and_context_with_rx().await;
@@ -374,7 +426,10 @@ mod tests {
                 id: "full".to_string(),
                 msg: EventMsg::TurnAborted(TurnAbortedEvent {
                     reason: TurnAbortReason::Interrupted,
+                    message_count: 0,
+                    tool_call_count: 0,
                 }),
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -400,6 +455,7 @@ mod tests {
                         input: "{}".to_string(),
                     },
                 }),
+                correlation_id: None,
             })
             .await
             .unwrap();
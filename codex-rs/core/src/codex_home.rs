@@ -0,0 +1,297 @@
+//! The layout of the Codex home directory, `~/.codex` by default.
+//!
+//! Every path under the home is resolved through [`CodexHome`], so the
+//! managers built on the same home agree on where sessions, skills, blobs
+//! and profiles live. The layout is the one Codex has always used;
+//! directories are still created on first use, and [`CodexHome::create`]
+//! creates all of them up front, private to the user on unix.
+//! [`CodexHome::verify`] reports the pieces that are missing or readable by
+//! others.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::blob_store::BLOBS_SUBDIR;
+use crate::config::CONFIG_TOML_FILE;
+use crate::rollout::ARCHIVED_SESSIONS_SUBDIR;
+use crate::rollout::SESSIONS_SUBDIR;
+use crate::session_profiles::SESSION_PROFILES_SUBDIR;
+
+/// Where user and system skills live, under the home and under a repo's
+/// `.codex` directory alike.
+pub(crate) const SKILLS_SUBDIR: &str = "skills";
+
+const AUTH_FILE: &str = "auth.json";
+
+/// Permission bits of the directories [`CodexHome::create`] makes.
+#[cfg(unix)]
+const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// A Codex home directory and the paths under it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodexHome {
+    root: PathBuf,
+}
+
+/// Something about the home that [`CodexHome::verify`] found wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutProblem {
+    /// The directory does not exist yet.
+    Missing { path: PathBuf },
+    /// Something other than a directory is in the way.
+    NotADirectory { path: PathBuf },
+    /// Others than the user can access the directory; `mode` holds its
+    /// permission bits.
+    Permissions { path: PathBuf, mode: u32 },
+    /// The directory could not be inspected.
+    Unreadable { path: PathBuf, error: String },
+}
+
+impl fmt::Display for LayoutProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutProblem::Missing { path } => write!(f, "{} is missing", path.display()),
+            LayoutProblem::NotADirectory { path } => {
+                write!(f, "{} is not a directory", path.display())
+            }
+            LayoutProblem::Permissions { path, mode } => write!(
+                f,
+                "{} is accessible by others (mode {mode:o}, expected 700)",
+                path.display()
+            ),
+            LayoutProblem::Unreadable { path, error } => {
+                write!(f, "cannot inspect {}: {error}", path.display())
+            }
+        }
+    }
+}
+
+impl CodexHome {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `config.toml`.
+    pub fn config_file(&self) -> PathBuf {
+        self.root.join(CONFIG_TOML_FILE)
+    }
+
+    /// `auth.json`, where credentials stored as a file are kept.
+    pub fn auth_file(&self) -> PathBuf {
+        self.root.join(AUTH_FILE)
+    }
+
+    /// `sessions/`, the rollouts by date.
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.root.join(SESSIONS_SUBDIR)
+    }
+
+    /// `archived_sessions/`.
+    pub fn archived_sessions_dir(&self) -> PathBuf {
+        self.root.join(ARCHIVED_SESSIONS_SUBDIR)
+    }
+
+    /// `skills/`, user skills with the system skills and skill packs in
+    /// hidden directories.
+    pub fn skills_dir(&self) -> PathBuf {
+        self.root.join(SKILLS_SUBDIR)
+    }
+
+    /// `blobs/`, see [`crate::blob_store`].
+    pub fn blobs_dir(&self) -> PathBuf {
+        self.root.join(BLOBS_SUBDIR)
+    }
+
+    /// `profiles/`, see [`crate::session_profiles`].
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.root.join(SESSION_PROFILES_SUBDIR)
+    }
+
+    /// The home and the directories under it, in creation order.
+    fn dirs(&self) -> [PathBuf; 6] {
+        [
+            self.root.clone(),
+            self.sessions_dir(),
+            self.archived_sessions_dir(),
+            self.skills_dir(),
+            self.blobs_dir(),
+            self.profiles_dir(),
+        ]
+    }
+
+    /// Create the home and its directories. Those that exist are left as
+    /// they are.
+    pub fn create(&self) -> io::Result<()> {
+        for dir in self.dirs() {
+            let mut builder = std::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::DirBuilderExt;
+                builder.mode(PRIVATE_DIR_MODE);
+            }
+            builder.create(&dir)?;
+        }
+        Ok(())
+    }
+
+    /// What is missing or accessible by others, empty when the home is as
+    /// [`Self::create`] leaves it.
+    pub fn verify(&self) -> Vec<LayoutProblem> {
+        let mut problems = Vec::new();
+        for path in self.dirs() {
+            let metadata = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    problems.push(LayoutProblem::Missing { path });
+                    continue;
+                }
+                Err(err) => {
+                    problems.push(LayoutProblem::Unreadable {
+                        path,
+                        error: err.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if !metadata.is_dir() {
+                problems.push(LayoutProblem::NotADirectory { path });
+                continue;
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = metadata.permissions().mode() & 0o777;
+                if mode & 0o077 != 0 {
+                    problems.push(LayoutProblem::Permissions { path, mode });
+                }
+            }
+        }
+        problems
+    }
+}
+
+impl From<PathBuf> for CodexHome {
+    fn from(root: PathBuf) -> Self {
+        Self::new(root)
+    }
+}
+
+impl From<&Path> for CodexHome {
+    fn from(root: &Path) -> Self {
+        Self::new(root)
+    }
+}
+
+impl AsRef<Path> for CodexHome {
+    fn as_ref(&self) -> &Path {
+        &self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn paths_keep_the_existing_layout() {
+        let home = CodexHome::new("/home/user/.codex");
+        assert_eq!(
+            home.sessions_dir(),
+            PathBuf::from("/home/user/.codex/sessions")
+        );
+        assert_eq!(
+            home.archived_sessions_dir(),
+            PathBuf::from("/home/user/.codex/archived_sessions")
+        );
+        assert_eq!(home.skills_dir(), PathBuf::from("/home/user/.codex/skills"));
+        assert_eq!(home.blobs_dir(), PathBuf::from("/home/user/.codex/blobs"));
+        assert_eq!(
+            home.profiles_dir(),
+            PathBuf::from("/home/user/.codex/profiles")
+        );
+        assert_eq!(
+            home.config_file(),
+            PathBuf::from("/home/user/.codex/config.toml")
+        );
+        assert_eq!(
+            home.auth_file(),
+            PathBuf::from("/home/user/.codex/auth.json")
+        );
+    }
+
+    #[test]
+    fn create_makes_a_fresh_home_that_verifies() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let home = CodexHome::new(dir.path().join("nested").join(".codex"));
+        assert_eq!(
+            home.verify().first(),
+            Some(&LayoutProblem::Missing {
+                path: home.root().to_path_buf()
+            })
+        );
+
+        home.create()?;
+        assert_eq!(home.verify(), Vec::new());
+        assert!(home.sessions_dir().is_dir());
+        assert!(home.profiles_dir().is_dir());
+        // Creating it again changes nothing.
+        home.create()?;
+        assert_eq!(home.verify(), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn verify_reports_missing_and_misplaced_pieces() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let home = CodexHome::new(dir.path());
+        home.create()?;
+        std::fs::remove_dir(home.blobs_dir())?;
+        std::fs::remove_dir(home.skills_dir())?;
+        std::fs::write(home.skills_dir(), "not a directory")?;
+
+        let problems = home.verify();
+        assert!(problems.contains(&LayoutProblem::Missing {
+            path: home.blobs_dir()
+        }));
+        assert!(problems.contains(&LayoutProblem::NotADirectory {
+            path: home.skills_dir()
+        }));
+        assert_eq!(
+            LayoutProblem::Missing {
+                path: home.blobs_dir()
+            }
+            .to_string(),
+            format!("{} is missing", home.blobs_dir().display())
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn verify_reports_directories_others_can_access() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new()?;
+        let home = CodexHome::new(dir.path().join(".codex"));
+        home.create()?;
+        std::fs::set_permissions(home.sessions_dir(), std::fs::Permissions::from_mode(0o755))?;
+
+        assert_eq!(
+            home.verify(),
+            vec![LayoutProblem::Permissions {
+                path: home.sessions_dir(),
+                mode: 0o755,
+            }]
+        );
+        Ok(())
+    }
+}
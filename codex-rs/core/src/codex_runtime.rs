@@ -0,0 +1,111 @@
+//! Process-wide state shared by the conversation managers of several codex
+//! homes.
+//!
+//! An app with one workspace per codex home creates one [`CodexRuntime`] and
+//! derives a [`ConversationManager`] for each home with
+//! [`CodexRuntime::manager_for_home`]. The model list is fetched, cached and
+//! refreshed once for the whole runtime, with the runtime's own auth, and
+//! metrics count the conversations of every home. Everything else is scoped
+//! to the home: its auth, skills, sessions and rollouts, and a manager refuses
+//! to start a conversation whose config names another codex home.
+//!
+//! Managers hold the shared state through an `Arc`, so dropping one leaves the
+//! runtime and the managers of the other homes running.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_protocol::protocol::SessionSource;
+
+use crate::AuthManager;
+#[cfg(any(test, feature = "test-support"))]
+use crate::CodexAuth;
+use crate::ConversationManager;
+#[cfg(any(test, feature = "test-support"))]
+use crate::ModelProviderInfo;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ManagerMetrics;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ManagerMetricsCounters;
+use crate::models_manager::manager::ModelsManager;
+
+/// Handle to the state shared across codex homes. Clones are cheap and share
+/// the same state.
+#[derive(Clone)]
+pub struct CodexRuntime {
+    shared: Arc<RuntimeShared>,
+}
+
+struct RuntimeShared {
+    auth_manager: Arc<AuthManager>,
+    models_manager: Arc<ModelsManager>,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<ManagerMetricsCounters>,
+}
+
+impl CodexRuntime {
+    /// A runtime fetching the model list with `auth_manager` and caching it
+    /// in that manager's codex home. Homes load their auth the way
+    /// `auth_manager` does.
+    pub fn new(auth_manager: Arc<AuthManager>) -> Self {
+        let models_manager = Arc::new(ModelsManager::new(Arc::clone(&auth_manager)));
+        Self::from_parts(auth_manager, models_manager)
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    /// Construct with a dummy AuthManager containing the provided CodexAuth,
+    /// fetching models from `provider`. Used for integration tests.
+    pub fn with_models_provider(
+        auth: CodexAuth,
+        provider: impl Into<ModelProviderInfo>,
+        codex_home: PathBuf,
+    ) -> Self {
+        let auth_manager = AuthManager::from_auth_for_testing_with_home(auth, codex_home);
+        let models_manager = Arc::new(ModelsManager::with_provider(
+            Arc::clone(&auth_manager),
+            provider.into(),
+        ));
+        Self::from_parts(auth_manager, models_manager)
+    }
+
+    fn from_parts(auth_manager: Arc<AuthManager>, models_manager: Arc<ModelsManager>) -> Self {
+        Self {
+            shared: Arc::new(RuntimeShared {
+                auth_manager,
+                models_manager,
+                #[cfg(feature = "metrics")]
+                metrics: Arc::default(),
+            }),
+        }
+    }
+
+    /// A manager for the conversations of `codex_home`, with the auth and
+    /// skills found there. Each call returns a new manager, tracking its own
+    /// conversations.
+    pub fn manager_for_home(
+        &self,
+        codex_home: impl Into<PathBuf>,
+        session_source: SessionSource,
+    ) -> ConversationManager {
+        let auth_manager = self.shared.auth_manager.for_home(codex_home.into());
+        ConversationManager::on_runtime(self, auth_manager, session_source)
+    }
+
+    /// The model list shared by every home, see
+    /// [`ModelsManager::subscribe`].
+    pub fn models_manager(&self) -> Arc<ModelsManager> {
+        Arc::clone(&self.shared.models_manager)
+    }
+
+    /// Metrics of the conversations of every home, see
+    /// [`crate::manager_metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics_snapshot(&self) -> ManagerMetrics {
+        self.shared.metrics.snapshot()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics(&self) -> Arc<ManagerMetricsCounters> {
+        Arc::clone(&self.shared.metrics)
+    }
+}
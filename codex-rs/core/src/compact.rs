@@ -14,6 +14,7 @@ use crate::protocol::ContextCompactedEvent;
 use crate::protocol::EventMsg;
 use crate::protocol::TaskStartedEvent;
 use crate::protocol::TurnContextItem;
+use crate::protocol::WarningCode;
 use crate::protocol::WarningEvent;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
@@ -24,6 +25,7 @@ use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::TranscriptMutationKind;
 use codex_protocol::user_input::UserInput;
 use futures::prelude::*;
 use tracing::error;
@@ -39,14 +41,16 @@ pub(crate) fn should_use_remote_compact_task(
     provider.is_openai() && session.enabled(Feature::RemoteCompaction)
 }
 
+/// Compact the history within the current turn. Returns whether the history
+/// was replaced.
 pub(crate) async fn run_inline_auto_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
-) {
+) -> bool {
     let prompt = turn_context.compact_prompt().to_string();
     let input = vec![UserInput::Text { text: prompt }];
 
-    run_compact_task_inner(sess, turn_context, input).await;
+    run_compact_task_inner(sess, turn_context, input).await
 }
 
 pub(crate) async fn run_compact_task(
@@ -56,6 +60,7 @@ pub(crate) async fn run_compact_task(
 ) {
     let start_event = EventMsg::TaskStarted(TaskStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        turn_id: None,
     });
     sess.send_event(&turn_context, start_event).await;
     run_compact_task_inner(sess.clone(), turn_context, input).await;
@@ -65,10 +70,11 @@ async fn run_compact_task_inner(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
     input: Vec<UserInput>,
-) {
+) -> bool {
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
 
     let mut history = sess.clone_history().await;
+    let pins = sess.pins().await;
     history.record_items(
         &[initial_input_for_turn.into()],
         turn_context.truncation_policy,
@@ -91,6 +97,8 @@ async fn run_compact_task_inner(
         developer_instructions: turn_context.developer_instructions.clone(),
         final_output_json_schema: turn_context.final_output_json_schema.clone(),
         truncation_policy: Some(turn_context.truncation_policy.into()),
+        sampling: sess.turn_sampling(&turn_context).await,
+        turn_tmpdir: None,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -116,15 +124,17 @@ async fn run_compact_task_inner(
                 break;
             }
             Err(CodexErr::Interrupted) => {
-                return;
+                return false;
             }
             Err(e @ CodexErr::ContextWindowExceeded) => {
-                if turn_input.len() > 1 {
-                    // Trim from the beginning to preserve cache (prefix-based) and keep recent messages intact.
+                // Trim from the beginning to preserve cache (prefix-based) and keep recent messages
+                // intact. Pinned items are never trimmed.
+                if turn_input.len() > 1
+                    && history.remove_first_item_except(|item| pins.contains(item))
+                {
                     error!(
-                        "Context window exceeded while compacting; removing oldest history item. Error: {e}"
+                        "Context window exceeded while compacting; removed oldest history item. Error: {e}"
                     );
-                    history.remove_first_item();
                     truncated_count += 1;
                     retries = 0;
                     continue;
@@ -132,7 +142,7 @@ async fn run_compact_task_inner(
                 sess.set_total_tokens_full(turn_context.as_ref()).await;
                 let event = EventMsg::Error(e.to_error_event(None));
                 sess.send_event(&turn_context, event).await;
-                return;
+                return false;
             }
             Err(e) => {
                 if retries < max_retries {
@@ -149,7 +159,7 @@ async fn run_compact_task_inner(
                 } else {
                     let event = EventMsg::Error(e.to_error_event(None));
                     sess.send_event(&turn_context, event).await;
-                    return;
+                    return false;
                 }
             }
         }
@@ -159,7 +169,14 @@ async fn run_compact_task_inner(
     let summary_suffix =
         get_last_assistant_message_from_turn(&history_snapshot).unwrap_or_default();
     let summary_text = format!("{SUMMARY_PREFIX}\n{summary_suffix}");
-    let user_messages = collect_user_messages(&history_snapshot);
+    // Pinned user messages are put back verbatim ahead of the summary by
+    // `replace_compacted_history` instead of competing for the message budget.
+    let unpinned: Vec<ResponseItem> = history_snapshot
+        .iter()
+        .filter(|item| !pins.contains(item))
+        .cloned()
+        .collect();
+    let user_messages = collect_user_messages(&unpinned);
 
     let initial_context = sess.build_initial_context(turn_context.as_ref());
     let mut new_history = build_compacted_history(initial_context, &user_messages, &summary_text);
@@ -169,22 +186,29 @@ async fn run_compact_task_inner(
         .cloned()
         .collect();
     new_history.extend(ghost_snapshots);
-    sess.replace_history(new_history).await;
+    sess.replace_compacted_history(
+        new_history,
+        CompactedItem {
+            message: summary_text.clone(),
+            replacement_history: None,
+        },
+        TranscriptMutationKind::Compaction,
+    )
+    .await;
     sess.recompute_token_usage(&turn_context).await;
 
-    let rollout_item = RolloutItem::Compacted(CompactedItem {
-        message: summary_text.clone(),
-        replacement_history: None,
-    });
-    sess.persist_rollout_items(&[rollout_item]).await;
-
     let event = EventMsg::ContextCompacted(ContextCompactedEvent {});
     sess.send_event(&turn_context, event).await;
 
-    let warning = EventMsg::Warning(WarningEvent {
-        message: "Heads up: Long conversations and multiple compactions can cause the model to be less accurate. Start a new conversation when possible to keep conversations small and targeted.".to_string(),
-    });
+    let warning = EventMsg::Warning(
+        WarningEvent::new(
+            WarningCode::LongConversation,
+            "Heads up: Long conversations and multiple compactions can cause the model to be less accurate. Start a new conversation when possible to keep conversations small and targeted.",
+        )
+        .for_conversation(),
+    );
     sess.send_event(&turn_context, warning).await;
+    true
 }
 
 pub fn content_items_to_text(content: &[ContentItem]) -> Option<String> {
@@ -226,6 +250,18 @@ pub(crate) fn is_summary_message(message: &str) -> bool {
     message.starts_with(format!("{SUMMARY_PREFIX}\n").as_str())
 }
 
+/// Whether `item` summarizes compacted turns: the local summary message or
+/// the remote compaction item.
+pub(crate) fn is_compaction_summary(item: &ResponseItem) -> bool {
+    match item {
+        ResponseItem::Compaction { .. } => true,
+        ResponseItem::Message { role, content, .. } if role == "user" => {
+            content_items_to_text(content).is_some_and(|text| is_summary_message(&text))
+        }
+        _ => false,
+    }
+}
+
 pub(crate) fn build_compacted_history(
     initial_context: Vec<ResponseItem>,
     user_messages: &[String],
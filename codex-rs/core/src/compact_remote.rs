@@ -7,33 +7,42 @@ use crate::error::Result as CodexResult;
 use crate::protocol::CompactedItem;
 use crate::protocol::ContextCompactedEvent;
 use crate::protocol::EventMsg;
-use crate::protocol::RolloutItem;
 use crate::protocol::TaskStartedEvent;
+use crate::protocol::TranscriptMutationKind;
+use crate::reasoning_log::without_raw_content;
 use codex_protocol::models::ResponseItem;
 
+/// Compact the history within the current turn. Returns whether the history
+/// was replaced.
 pub(crate) async fn run_inline_remote_auto_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
-) {
-    run_remote_compact_task_inner(&sess, &turn_context).await;
+) -> bool {
+    run_remote_compact_task_inner(&sess, &turn_context).await
 }
 
 pub(crate) async fn run_remote_compact_task(sess: Arc<Session>, turn_context: Arc<TurnContext>) {
     let start_event = EventMsg::TaskStarted(TaskStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        turn_id: None,
     });
     sess.send_event(&turn_context, start_event).await;
 
     run_remote_compact_task_inner(&sess, &turn_context).await;
 }
 
-async fn run_remote_compact_task_inner(sess: &Arc<Session>, turn_context: &Arc<TurnContext>) {
+async fn run_remote_compact_task_inner(
+    sess: &Arc<Session>,
+    turn_context: &Arc<TurnContext>,
+) -> bool {
     if let Err(err) = run_remote_compact_task_inner_impl(sess, turn_context).await {
         let event = EventMsg::Error(
             err.to_error_event(Some("Error running remote compact task".to_string())),
         );
         sess.send_event(turn_context, event).await;
+        return false;
     }
+    true
 }
 
 async fn run_remote_compact_task_inner_impl(
@@ -49,10 +58,15 @@ async fn run_remote_compact_task_inner_impl(
         output_schema: None,
     };
 
-    let mut new_history = turn_context
+    // Raw reasoning must not be fed back to the model as part of the new
+    // history.
+    let mut new_history: Vec<ResponseItem> = turn_context
         .client
         .compact_conversation_history(&prompt)
-        .await?;
+        .await?
+        .into_iter()
+        .map(without_raw_content)
+        .collect();
     // Required to keep `/undo` available after compaction
     let ghost_snapshots: Vec<ResponseItem> = history
         .get_history()
@@ -64,15 +78,17 @@ async fn run_remote_compact_task_inner_impl(
     if !ghost_snapshots.is_empty() {
         new_history.extend(ghost_snapshots);
     }
-    sess.replace_history(new_history.clone()).await;
-    sess.recompute_token_usage(turn_context).await;
-
     let compacted_item = CompactedItem {
         message: String::new(),
-        replacement_history: Some(new_history),
+        replacement_history: Some(new_history.clone()),
     };
-    sess.persist_rollout_items(&[RolloutItem::Compacted(compacted_item)])
-        .await;
+    sess.replace_compacted_history(
+        new_history,
+        compacted_item,
+        TranscriptMutationKind::Compaction,
+    )
+    .await;
+    sess.recompute_token_usage(turn_context).await;
 
     let event = EventMsg::ContextCompacted(ContextCompactedEvent {});
     sess.send_event(turn_context, event).await;
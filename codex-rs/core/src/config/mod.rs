@@ -1,3 +1,4 @@
+use crate::adaptive_effort::AdaptiveEffortPolicy;
 use crate::auth::AuthCredentialsStoreMode;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config::types::History;
@@ -7,16 +8,24 @@ use crate::config::types::Notifications;
 use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
+use crate::config::types::ParallelToolCalls;
+use crate::config::types::RefreshPolicy;
 use crate::config::types::SandboxWorkspaceWrite;
 use crate::config::types::ScrollInputMode;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
+use crate::config::types::ToolTimeouts;
+use crate::config::types::ToolTimeoutsToml;
 use crate::config::types::Tui;
+use crate::config::types::TurnTmpdirLocation;
 use crate::config::types::UriBasedFileOpener;
+use crate::config::types::UserContext;
 use crate::config_loader::ConfigLayerStack;
 use crate::config_loader::ConfigRequirements;
 use crate::config_loader::LoaderOverrides;
 use crate::config_loader::load_config_layers_state;
+use crate::delta_coalescing::DeltaCoalescingPolicy;
+use crate::event_replay::EventReplayPolicy;
 use crate::features::Feature;
 use crate::features::FeatureOverrides;
 use crate::features::Features;
@@ -26,18 +35,32 @@ use crate::model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::OLLAMA_OSS_PROVIDER_ID;
 use crate::model_provider_info::built_in_model_providers;
+use crate::partial_messages::PartialMessagePolicy;
 use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::request_trace::TraceTarget;
+use crate::retry::RetryProfiles;
+use crate::retry::RetryProfilesToml;
+use crate::rollout::EventLogPolicy;
+use crate::rollout::RolloutFsyncPolicy;
+use crate::sensitive_input::SensitiveInputPolicy;
+use crate::skills::SkillPackSource;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
+use codex_protocol::config_types::ContextRecoveryStrategy;
+use codex_protocol::config_types::ExecutionMode;
 use codex_protocol::config_types::ForcedLoginMethod;
 use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::config_types::SamplingParams;
 use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::SkillsMode;
+use codex_protocol::config_types::ToolPolicy;
 use codex_protocol::config_types::TrustLevel;
 use codex_protocol::config_types::Verbosity;
 use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::WarningSeverity;
 use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use codex_utils_absolute_path::AbsolutePathBufGuard;
@@ -50,6 +73,7 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 #[cfg(test)]
 use tempfile::tempdir;
 
@@ -71,6 +95,15 @@ pub use service::ConfigServiceError;
 
 const OPENAI_DEFAULT_REVIEW_MODEL: &str = "gpt-5.1-codex-max";
 
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_ROLLOUT_MAX_LINE_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_TOOL_OUTPUT_BYTES: usize = 1024 * 1024;
+const DEFAULT_TOOL_OUTPUT_HEAD_RATIO: f64 = 0.5;
+const DEFAULT_MODEL_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS: u32 = 2;
+
+const DEFAULT_CONTEXT_USAGE_THRESHOLDS: [u8; 3] = [50, 80, 95];
+
 pub use codex_git::GhostSnapshotConfig;
 
 /// Maximum number of bytes of the documentation that will be embedded. Larger
@@ -355,6 +388,184 @@ pub struct Config {
 
     /// OTEL configuration (exporter type, endpoint, headers, etc.).
     pub otel: crate::config::types::OtelConfig,
+
+    /// Debug tracing of raw provider requests and responses. Only settable
+    /// programmatically; `None` (the default) disables tracing. Traces are
+    /// never recorded in the rollout.
+    pub request_trace: Option<TraceTarget>,
+
+    /// When set, every emitted `Event` is also written to
+    /// `<rollout>.events.jsonl`, independent of the rollout itself.
+    pub event_log: Option<EventLogPolicy>,
+
+    /// Rollout lines longer than this are written as `RolloutItem::Chunked`
+    /// parts. `None` never splits lines.
+    pub rollout_max_line_bytes: Option<usize>,
+
+    /// Rollouts that would grow past this many bytes continue in a new part
+    /// file next to the first. `None` keeps a single file.
+    pub max_rollout_bytes: Option<u64>,
+
+    /// Recording stops once all parts of a rollout together would grow past
+    /// this many bytes. `None` never stops.
+    pub rollout_hard_cap_bytes: Option<u64>,
+
+    /// How many delivered events each conversation keeps in memory for
+    /// `CodexConversation::events_since`.
+    pub event_replay: EventReplayPolicy,
+
+    /// How assistant text deltas are joined before they are sent. The
+    /// default sends each delta as it arrives.
+    pub delta_coalescing: DeltaCoalescingPolicy,
+
+    /// Rules that reject user input that looks like a secret. `None`
+    /// accepts all input.
+    pub sensitive_input: Option<SensitiveInputPolicy>,
+
+    /// Raise the reasoning effort after turns that flounder, see
+    /// [`crate::adaptive_effort`]. `None` keeps the configured effort.
+    pub adaptive_effort: Option<AdaptiveEffortPolicy>,
+
+    /// Record assistant text in the rollout while it streams, see
+    /// [`crate::partial_messages`]. `None` records items once complete.
+    pub partial_messages: Option<PartialMessagePolicy>,
+
+    /// How model requests, model list fetches and token refreshes are
+    /// retried, see [`crate::retry`].
+    pub retry: RetryProfiles,
+
+    /// Model that writes handoff summaries, see [`crate::handoff`]. `None`
+    /// uses the model of the conversation.
+    pub handoff_model: Option<String>,
+
+    /// How often a turn that is waiting on the model or a tool without
+    /// producing output emits `EventMsg::Heartbeat`. `None` disables
+    /// heartbeats.
+    pub heartbeat_interval: Option<Duration>,
+
+    /// Extra model requests allowed when a turn's final answer does not match
+    /// its `final_output_json_schema`.
+    pub structured_output_repair_attempts: u32,
+
+    /// Whether tool calls run or are only reported as planned. Can be
+    /// switched mid-session with `Op::OverrideTurnContext`.
+    pub execution_mode: ExecutionMode,
+
+    /// Tools advertised to the model. Can be changed mid-session with
+    /// `CodexConversation::update_tool_policy`.
+    pub tool_policy: ToolPolicy,
+
+    /// Percentages of the model's context window at which
+    /// `EventMsg::ContextUsageUpdated` is emitted.
+    pub context_usage_thresholds: Vec<u8>,
+
+    /// Sampling parameters sent with every request. Can be replaced
+    /// mid-session with `Op::OverrideTurnContext`.
+    pub sampling: SamplingParams,
+
+    /// Names of the skills offered to the model. `None` offers every skill
+    /// found for the working directory.
+    pub enabled_skills: Option<Vec<String>>,
+
+    /// Offer every skill even when its `applies-to` rules do not match the
+    /// workspace.
+    pub include_all_skills: bool,
+
+    /// How skills are offered to the model: listed in the instructions, or
+    /// as tools when they declare parameters.
+    pub skills_mode: SkillsMode,
+
+    /// Submit the inputs still queued when a resumed session last stopped,
+    /// see [`codex_protocol::protocol::PendingInputItem`].
+    pub replay_pending_inputs: bool,
+
+    /// Never call the model provider: turns are queued until offline mode is
+    /// turned off and they are flushed, and the model list comes from the
+    /// cache or the built-in presets.
+    pub offline: bool,
+
+    /// What a turn does when the provider rejects its prompt as longer than
+    /// the model context window.
+    pub context_recovery: ContextRecoveryStrategy,
+
+    /// Write reasoning summaries to the rollout. When off, reasoning items
+    /// are recorded with their encrypted content only.
+    pub persist_reasoning_summaries: bool,
+
+    /// Where each turn's scratch directory, exported to commands as
+    /// `CODEX_TURN_TMPDIR`, is created.
+    pub turn_tmpdir_location: TurnTmpdirLocation,
+
+    /// Keep turn scratch directories after their turn ends, for debugging.
+    pub keep_turn_tmpdirs: bool,
+
+    /// When `AGENTS.md` and the other instruction files are read again so
+    /// that edits made during the session reach the model.
+    pub instructions_refresh: RefreshPolicy,
+
+    /// Create `cwd` when a conversation is spawned in a directory that does
+    /// not exist yet, instead of failing.
+    pub create_cwd_if_missing: bool,
+
+    /// `read_file` outputs at least this large that are identical to one
+    /// still in the history are replaced by a reference to it. `None` always
+    /// returns reads in full.
+    pub read_cache_min_bytes: Option<usize>,
+
+    /// Exec tool outputs longer than this are cut to their head and tail for
+    /// the model and the event stream, and kept in full in the blob store.
+    /// `None` never cuts outputs.
+    pub max_tool_output_bytes: Option<usize>,
+
+    /// Share of `max_tool_output_bytes` kept from the head of a cut output,
+    /// between 0 and 1; the rest is kept from its tail.
+    pub tool_output_head_ratio: f64,
+
+    /// Interval at which the model list is fetched again while someone
+    /// subscribes to it, see [`ModelsManager::subscribe`]. `None` refreshes
+    /// it only when asked to.
+    ///
+    /// [`ModelsManager::subscribe`]: crate::models_manager::manager::ModelsManager::subscribe
+    pub model_list_refresh_interval: Option<Duration>,
+
+    /// Check, when forking, that the files the forked history read still
+    /// read the same in the fork's workspace.
+    pub fork_consistency_check: bool,
+
+    /// Tell the model of a fork which files it read have changed since.
+    pub fork_stale_notice: bool,
+
+    /// How long each tool call may run before it is stopped.
+    pub tool_timeouts: ToolTimeouts,
+
+    /// Concurrency and isolation of the tool calls a model response asks
+    /// for at once.
+    pub parallel_tool_calls: ParallelToolCalls,
+
+    /// Warnings below this severity are not sent to clients.
+    pub min_warning_severity: WarningSeverity,
+
+    /// How long a turn may run in total, model time and tool calls included,
+    /// unless the turn sets its own deadline. `None` leaves turns unbounded.
+    pub turn_deadline: Option<Duration>,
+
+    /// Record an audit marker in the rollout when a turn pre-processor
+    /// rejects an input, see [`crate::pre_process`].
+    pub record_input_rejections: bool,
+
+    /// When the rollout file is synced to disk.
+    pub rollout_fsync: RolloutFsyncPolicy,
+
+    /// Time zone and locale told to the model in the session prefix, along
+    /// with the current time, which is refreshed as turns start.
+    pub user_context: Option<UserContext>,
+
+    /// Remote skill packs, keyed by name, see [`crate::skills::remote`].
+    pub skill_packs: BTreeMap<String, SkillPackSource>,
+
+    /// Session profile this `Config` was materialized from, see
+    /// [`crate::session_profiles`]. Recorded in the rollout's session meta.
+    pub session_profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -816,6 +1027,164 @@ pub struct ConfigToml {
     /// OTEL configuration.
     pub otel: Option<crate::config::types::OtelConfigToml>,
 
+    /// Persist the full event stream next to each rollout for auditing.
+    pub event_log: Option<EventLogPolicy>,
+
+    /// Split rollout lines longer than this many bytes into chunks.
+    /// Defaults to 1 MiB; `0` disables splitting.
+    pub rollout_max_line_bytes: Option<usize>,
+
+    /// Continue the rollout in a new part file once it would grow past this
+    /// many bytes. Unset or `0` keeps a single file.
+    pub max_rollout_bytes: Option<u64>,
+
+    /// Stop recording once all parts of the rollout together would grow past
+    /// this many bytes. Unset or `0` never stops.
+    pub rollout_hard_cap_bytes: Option<u64>,
+
+    /// Events kept in memory so reconnecting clients can catch up.
+    pub event_replay: Option<EventReplayPolicy>,
+
+    /// Join assistant text deltas into fewer events.
+    pub delta_coalescing: Option<DeltaCoalescingPolicy>,
+
+    /// Reject user input that matches these rules instead of sending it.
+    pub sensitive_input: Option<SensitiveInputPolicy>,
+
+    /// Raise the reasoning effort automatically after turns that flounder.
+    pub adaptive_effort: Option<AdaptiveEffortPolicy>,
+
+    /// Record streaming assistant text so a crash mid-answer keeps it.
+    pub partial_messages: Option<PartialMessagePolicy>,
+
+    /// Retry profiles for model requests, model list fetches and token
+    /// refreshes.
+    pub retry: Option<RetryProfilesToml>,
+
+    /// Model that writes handoff summaries for escalation to a human.
+    pub handoff_model: Option<String>,
+
+    /// Interval between heartbeat events while a turn produces no output.
+    /// Defaults to 10000; `0` disables heartbeats.
+    pub heartbeat_interval_ms: Option<u64>,
+
+    /// Retries with the validation errors when a final answer does not match
+    /// the turn's output schema. Defaults to 2.
+    pub structured_output_repair_attempts: Option<u32>,
+
+    /// `dry-run` reports tool calls as planned without running them.
+    pub execution_mode: Option<ExecutionMode>,
+
+    /// Allow or deny individual tools by name.
+    pub tool_policy: Option<ToolPolicy>,
+
+    /// Context window percentages that notify clients when crossed.
+    /// Defaults to `[50, 80, 95]`.
+    pub context_usage_thresholds: Option<Vec<u8>>,
+
+    /// Sampling temperature in `[0, 2]`. Not supported by reasoning models.
+    pub temperature: Option<f64>,
+
+    /// Nucleus sampling probability mass in `(0, 1]`. Not supported by
+    /// reasoning models.
+    pub top_p: Option<f64>,
+
+    /// Seed for providers that support deterministic sampling.
+    pub seed: Option<u64>,
+
+    /// Only offer the skills with these names to the model.
+    pub enabled_skills: Option<Vec<String>>,
+
+    /// Ignore the `applies-to` rules of skills and offer all of them.
+    pub include_all_skills: Option<bool>,
+
+    /// `prompt` (the default) or `tools`, see [`SkillsMode`].
+    pub skills_mode: Option<SkillsMode>,
+
+    /// Replay inputs that were queued but never processed when a resumed
+    /// session last stopped.
+    pub replay_pending_inputs: Option<bool>,
+
+    /// Start sessions without calling the model provider.
+    pub offline: Option<bool>,
+
+    /// `fail-fast` (the default), `auto-compact` or `auto-truncate`, see
+    /// [`ContextRecoveryStrategy`].
+    pub context_recovery: Option<ContextRecoveryStrategy>,
+
+    /// Keep reasoning summaries out of the rollout when `false`.
+    pub persist_reasoning_summaries: Option<bool>,
+
+    /// Create turn scratch directories under the system temporary directory
+    /// (`system`, the default) or under `$CODEX_HOME/tmp` (`codex-home`).
+    pub turn_tmpdir_location: Option<TurnTmpdirLocation>,
+
+    /// Do not remove turn scratch directories when their turn ends.
+    pub keep_turn_tmpdirs: Option<bool>,
+
+    /// Re-read instruction files before each turn: `never` (the default),
+    /// `on-change` or `every-turn`.
+    pub instructions_refresh: Option<RefreshPolicy>,
+
+    /// Create the working directory when it does not exist.
+    pub create_cwd_if_missing: Option<bool>,
+
+    /// Replace repeated `read_file` outputs of at least this many bytes with
+    /// a reference to the earlier identical output. Unset disables it.
+    pub read_cache_min_bytes: Option<usize>,
+
+    /// Cut exec tool outputs longer than this many bytes to their head and
+    /// tail and store them in full. Defaults to 1 MiB; `0` never cuts.
+    pub max_tool_output_bytes: Option<usize>,
+
+    /// Share of a cut output kept from its head, from 0 to 1. Defaults to
+    /// 0.5.
+    pub tool_output_head_ratio: Option<f64>,
+
+    /// Seconds between background refreshes of the model list while it has
+    /// subscribers. Defaults to 900; `0` disables them.
+    pub model_list_refresh_interval_sec: Option<u64>,
+
+    /// Re-read, when forking, the files the forked history read and report
+    /// those that changed. Defaults to true.
+    pub fork_consistency_check: Option<bool>,
+
+    /// Add a message listing the changed files to the fork's history, so the
+    /// model reads them again. Defaults to true.
+    pub fork_stale_notice: Option<bool>,
+
+    /// Time limits for tool calls: `default_sec` (30 minutes when unset) and
+    /// `per_tool` limits keyed by tool name.
+    pub tool_timeouts: Option<ToolTimeoutsToml>,
+
+    /// Parallel tool calls: `max_concurrency` (unlimited when unset) and
+    /// `isolation`, `shared-cwd` (the default) or `per-call-tmp` to give each
+    /// call its own scratch directory.
+    pub parallel_tool_calls: Option<ParallelToolCalls>,
+
+    /// Lowest severity of the warnings sent to clients: `info` (the default)
+    /// or `warning`.
+    pub min_warning_severity: Option<WarningSeverity>,
+
+    /// Seconds a turn may run in total, model time and tool calls included,
+    /// before it is stopped and completed with what it has. Unset leaves
+    /// turns unbounded.
+    pub turn_deadline_sec: Option<u64>,
+
+    /// Record the reason in the rollout when a turn pre-processor rejects an
+    /// input. The input itself is never recorded.
+    pub record_input_rejections: Option<bool>,
+
+    /// When the rollout file is synced to disk: after every item, at most
+    /// every `interval_ms`, or when a turn ends (the default).
+    pub rollout_fsync: Option<RolloutFsyncPolicy>,
+
+    /// Tell the model the user's time zone, locale and the current time.
+    pub user_context: Option<UserContext>,
+
+    /// Skill packs fetched from a URL and pinned by version.
+    pub skill_packs: Option<BTreeMap<String, SkillPackSource>>,
+
     /// Tracks whether the Windows onboarding screen has been acknowledged.
     pub windows_wsl_setup_acknowledged: Option<bool>,
 
@@ -1312,6 +1681,27 @@ impl Config {
             .set(sandbox_policy)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
 
+        if let Some(policy) = &cfg.sensitive_input {
+            policy.validate().map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid sensitive_input pattern: {e}"),
+                )
+            })?;
+        }
+
+        let tool_output_head_ratio = cfg
+            .tool_output_head_ratio
+            .unwrap_or(DEFAULT_TOOL_OUTPUT_HEAD_RATIO);
+        if !(0.0..=1.0).contains(&tool_output_head_ratio) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "tool_output_head_ratio must be between 0 and 1, got {tool_output_head_ratio}"
+                ),
+            ));
+        }
+
         let config = Self {
             model,
             review_model,
@@ -1433,11 +1823,79 @@ impl Config {
                     trace_exporter,
                 }
             },
+            request_trace: None,
+            event_log: cfg.event_log,
+            rollout_max_line_bytes: match cfg.rollout_max_line_bytes {
+                Some(0) => None,
+                Some(bytes) => Some(bytes),
+                None => Some(DEFAULT_ROLLOUT_MAX_LINE_BYTES),
+            },
+            max_rollout_bytes: cfg.max_rollout_bytes.filter(|bytes| *bytes > 0),
+            rollout_hard_cap_bytes: cfg.rollout_hard_cap_bytes.filter(|bytes| *bytes > 0),
+            event_replay: cfg.event_replay.unwrap_or_default(),
+            delta_coalescing: cfg.delta_coalescing.unwrap_or_default(),
+            sensitive_input: cfg.sensitive_input,
+            adaptive_effort: cfg.adaptive_effort,
+            partial_messages: cfg.partial_messages,
+            retry: cfg.retry.map(Into::into).unwrap_or_default(),
+            handoff_model: cfg.handoff_model,
+            heartbeat_interval: match cfg.heartbeat_interval_ms {
+                Some(0) => None,
+                Some(ms) => Some(Duration::from_millis(ms)),
+                None => Some(DEFAULT_HEARTBEAT_INTERVAL),
+            },
+            structured_output_repair_attempts: cfg
+                .structured_output_repair_attempts
+                .unwrap_or(DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS),
+            execution_mode: cfg.execution_mode.unwrap_or_default(),
+            tool_policy: cfg.tool_policy.unwrap_or_default(),
+            context_usage_thresholds: cfg
+                .context_usage_thresholds
+                .unwrap_or_else(|| DEFAULT_CONTEXT_USAGE_THRESHOLDS.to_vec()),
+            sampling: SamplingParams {
+                temperature: cfg.temperature,
+                top_p: cfg.top_p,
+                seed: cfg.seed,
+            },
+            enabled_skills: cfg.enabled_skills,
+            include_all_skills: cfg.include_all_skills.unwrap_or(false),
+            skills_mode: cfg.skills_mode.unwrap_or_default(),
+            replay_pending_inputs: cfg.replay_pending_inputs.unwrap_or(false),
+            offline: cfg.offline.unwrap_or(false),
+            context_recovery: cfg.context_recovery.unwrap_or_default(),
+            persist_reasoning_summaries: cfg.persist_reasoning_summaries.unwrap_or(true),
+            turn_tmpdir_location: cfg.turn_tmpdir_location.unwrap_or_default(),
+            keep_turn_tmpdirs: cfg.keep_turn_tmpdirs.unwrap_or(false),
+            instructions_refresh: cfg.instructions_refresh.unwrap_or_default(),
+            create_cwd_if_missing: cfg.create_cwd_if_missing.unwrap_or(false),
+            read_cache_min_bytes: cfg.read_cache_min_bytes,
+            max_tool_output_bytes: match cfg.max_tool_output_bytes {
+                Some(0) => None,
+                Some(bytes) => Some(bytes),
+                None => Some(DEFAULT_MAX_TOOL_OUTPUT_BYTES),
+            },
+            tool_output_head_ratio,
+            model_list_refresh_interval: match cfg.model_list_refresh_interval_sec {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => Some(DEFAULT_MODEL_LIST_REFRESH_INTERVAL),
+            },
+            fork_consistency_check: cfg.fork_consistency_check.unwrap_or(true),
+            fork_stale_notice: cfg.fork_stale_notice.unwrap_or(true),
+            tool_timeouts: cfg.tool_timeouts.map(Into::into).unwrap_or_default(),
+            parallel_tool_calls: cfg.parallel_tool_calls.unwrap_or_default(),
+            min_warning_severity: cfg.min_warning_severity.unwrap_or(WarningSeverity::Info),
+            turn_deadline: cfg.turn_deadline_sec.map(Duration::from_secs),
+            record_input_rejections: cfg.record_input_rejections.unwrap_or(false),
+            rollout_fsync: cfg.rollout_fsync.unwrap_or_default(),
+            user_context: cfg.user_context,
+            skill_packs: cfg.skill_packs.unwrap_or_default(),
+            session_profile: None,
         };
         Ok(config)
     }
 
-    fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
+    pub(crate) fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let base = codex_dir?;
         for candidate in [LOCAL_PROJECT_DOC_FILENAME, DEFAULT_PROJECT_DOC_FILENAME] {
             let mut path = base.to_path_buf();
@@ -1609,6 +2067,29 @@ persistence = "none"
         );
     }
 
+    #[test]
+    fn rollout_fsync_parses_each_mode() {
+        let parse = |cfg: &str| {
+            toml::from_str::<ConfigToml>(cfg)
+                .expect("rollout_fsync should parse")
+                .rollout_fsync
+        };
+
+        assert_eq!(parse(""), None);
+        assert_eq!(
+            parse("rollout_fsync = { mode = \"every-item\" }"),
+            Some(RolloutFsyncPolicy::EveryItem)
+        );
+        assert_eq!(
+            parse("[rollout_fsync]\nmode = \"interval\"\ninterval_ms = 250\n"),
+            Some(RolloutFsyncPolicy::Interval { interval_ms: 250 })
+        );
+        assert_eq!(
+            parse("rollout_fsync = { mode = \"turn-end\" }"),
+            Some(RolloutFsyncPolicy::TurnEnd)
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -3096,6 +3577,9 @@ model_verbosity = "high"
             request_max_retries: Some(4),
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         };
         let model_provider_map = {
@@ -3212,6 +3696,50 @@ model_verbosity = "high"
                 tui_scroll_wheel_like_max_duration_ms: None,
                 tui_scroll_invert: false,
                 otel: OtelConfig::default(),
+                request_trace: None,
+                event_log: None,
+                rollout_max_line_bytes: Some(DEFAULT_ROLLOUT_MAX_LINE_BYTES),
+                max_rollout_bytes: None,
+                rollout_hard_cap_bytes: None,
+                event_replay: EventReplayPolicy::default(),
+                delta_coalescing: DeltaCoalescingPolicy::default(),
+                sensitive_input: None,
+                adaptive_effort: None,
+                partial_messages: None,
+                retry: RetryProfiles::default(),
+                handoff_model: None,
+                heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+                structured_output_repair_attempts: DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS,
+                execution_mode: ExecutionMode::Normal,
+                tool_policy: ToolPolicy::default(),
+                context_usage_thresholds: DEFAULT_CONTEXT_USAGE_THRESHOLDS.to_vec(),
+                sampling: SamplingParams::default(),
+                enabled_skills: None,
+                include_all_skills: false,
+                skills_mode: SkillsMode::default(),
+                replay_pending_inputs: false,
+                offline: false,
+                context_recovery: ContextRecoveryStrategy::default(),
+                persist_reasoning_summaries: true,
+                turn_tmpdir_location: TurnTmpdirLocation::System,
+                keep_turn_tmpdirs: false,
+                instructions_refresh: RefreshPolicy::Never,
+                create_cwd_if_missing: false,
+                read_cache_min_bytes: None,
+                max_tool_output_bytes: Some(DEFAULT_MAX_TOOL_OUTPUT_BYTES),
+                tool_output_head_ratio: DEFAULT_TOOL_OUTPUT_HEAD_RATIO,
+                model_list_refresh_interval: Some(DEFAULT_MODEL_LIST_REFRESH_INTERVAL),
+                fork_consistency_check: true,
+                fork_stale_notice: true,
+                tool_timeouts: ToolTimeouts::default(),
+                parallel_tool_calls: ParallelToolCalls::default(),
+                min_warning_severity: WarningSeverity::Info,
+                turn_deadline: None,
+                record_input_rejections: false,
+                rollout_fsync: RolloutFsyncPolicy::default(),
+                user_context: None,
+                skill_packs: BTreeMap::new(),
+                session_profile: None,
             },
             o3_profile_config
         );
@@ -3295,6 +3823,50 @@ model_verbosity = "high"
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
             otel: OtelConfig::default(),
+            request_trace: None,
+            event_log: None,
+            rollout_max_line_bytes: Some(DEFAULT_ROLLOUT_MAX_LINE_BYTES),
+            max_rollout_bytes: None,
+            rollout_hard_cap_bytes: None,
+            event_replay: EventReplayPolicy::default(),
+            delta_coalescing: DeltaCoalescingPolicy::default(),
+            sensitive_input: None,
+            adaptive_effort: None,
+            partial_messages: None,
+            retry: RetryProfiles::default(),
+            handoff_model: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            structured_output_repair_attempts: DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS,
+            execution_mode: ExecutionMode::Normal,
+            tool_policy: ToolPolicy::default(),
+            context_usage_thresholds: DEFAULT_CONTEXT_USAGE_THRESHOLDS.to_vec(),
+            sampling: SamplingParams::default(),
+            enabled_skills: None,
+            include_all_skills: false,
+            skills_mode: SkillsMode::default(),
+            replay_pending_inputs: false,
+            offline: false,
+            context_recovery: ContextRecoveryStrategy::default(),
+            persist_reasoning_summaries: true,
+            turn_tmpdir_location: TurnTmpdirLocation::System,
+            keep_turn_tmpdirs: false,
+            instructions_refresh: RefreshPolicy::Never,
+            create_cwd_if_missing: false,
+            read_cache_min_bytes: None,
+            max_tool_output_bytes: Some(DEFAULT_MAX_TOOL_OUTPUT_BYTES),
+            tool_output_head_ratio: DEFAULT_TOOL_OUTPUT_HEAD_RATIO,
+            model_list_refresh_interval: Some(DEFAULT_MODEL_LIST_REFRESH_INTERVAL),
+            fork_consistency_check: true,
+            fork_stale_notice: true,
+            tool_timeouts: ToolTimeouts::default(),
+            parallel_tool_calls: ParallelToolCalls::default(),
+            min_warning_severity: WarningSeverity::Info,
+            turn_deadline: None,
+            record_input_rejections: false,
+            rollout_fsync: RolloutFsyncPolicy::default(),
+            user_context: None,
+            skill_packs: BTreeMap::new(),
+            session_profile: None,
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -3393,6 +3965,50 @@ model_verbosity = "high"
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
             otel: OtelConfig::default(),
+            request_trace: None,
+            event_log: None,
+            rollout_max_line_bytes: Some(DEFAULT_ROLLOUT_MAX_LINE_BYTES),
+            max_rollout_bytes: None,
+            rollout_hard_cap_bytes: None,
+            event_replay: EventReplayPolicy::default(),
+            delta_coalescing: DeltaCoalescingPolicy::default(),
+            sensitive_input: None,
+            adaptive_effort: None,
+            partial_messages: None,
+            retry: RetryProfiles::default(),
+            handoff_model: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            structured_output_repair_attempts: DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS,
+            execution_mode: ExecutionMode::Normal,
+            tool_policy: ToolPolicy::default(),
+            context_usage_thresholds: DEFAULT_CONTEXT_USAGE_THRESHOLDS.to_vec(),
+            sampling: SamplingParams::default(),
+            enabled_skills: None,
+            include_all_skills: false,
+            skills_mode: SkillsMode::default(),
+            replay_pending_inputs: false,
+            offline: false,
+            context_recovery: ContextRecoveryStrategy::default(),
+            persist_reasoning_summaries: true,
+            turn_tmpdir_location: TurnTmpdirLocation::System,
+            keep_turn_tmpdirs: false,
+            instructions_refresh: RefreshPolicy::Never,
+            create_cwd_if_missing: false,
+            read_cache_min_bytes: None,
+            max_tool_output_bytes: Some(DEFAULT_MAX_TOOL_OUTPUT_BYTES),
+            tool_output_head_ratio: DEFAULT_TOOL_OUTPUT_HEAD_RATIO,
+            model_list_refresh_interval: Some(DEFAULT_MODEL_LIST_REFRESH_INTERVAL),
+            fork_consistency_check: true,
+            fork_stale_notice: true,
+            tool_timeouts: ToolTimeouts::default(),
+            parallel_tool_calls: ParallelToolCalls::default(),
+            min_warning_severity: WarningSeverity::Info,
+            turn_deadline: None,
+            record_input_rejections: false,
+            rollout_fsync: RolloutFsyncPolicy::default(),
+            user_context: None,
+            skill_packs: BTreeMap::new(),
+            session_profile: None,
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -3477,6 +4093,50 @@ model_verbosity = "high"
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
             otel: OtelConfig::default(),
+            request_trace: None,
+            event_log: None,
+            rollout_max_line_bytes: Some(DEFAULT_ROLLOUT_MAX_LINE_BYTES),
+            max_rollout_bytes: None,
+            rollout_hard_cap_bytes: None,
+            event_replay: EventReplayPolicy::default(),
+            delta_coalescing: DeltaCoalescingPolicy::default(),
+            sensitive_input: None,
+            adaptive_effort: None,
+            partial_messages: None,
+            retry: RetryProfiles::default(),
+            handoff_model: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            structured_output_repair_attempts: DEFAULT_STRUCTURED_OUTPUT_REPAIR_ATTEMPTS,
+            execution_mode: ExecutionMode::Normal,
+            tool_policy: ToolPolicy::default(),
+            context_usage_thresholds: DEFAULT_CONTEXT_USAGE_THRESHOLDS.to_vec(),
+            sampling: SamplingParams::default(),
+            enabled_skills: None,
+            include_all_skills: false,
+            skills_mode: SkillsMode::default(),
+            replay_pending_inputs: false,
+            offline: false,
+            context_recovery: ContextRecoveryStrategy::default(),
+            persist_reasoning_summaries: true,
+            turn_tmpdir_location: TurnTmpdirLocation::System,
+            keep_turn_tmpdirs: false,
+            instructions_refresh: RefreshPolicy::Never,
+            create_cwd_if_missing: false,
+            read_cache_min_bytes: None,
+            max_tool_output_bytes: Some(DEFAULT_MAX_TOOL_OUTPUT_BYTES),
+            tool_output_head_ratio: DEFAULT_TOOL_OUTPUT_HEAD_RATIO,
+            model_list_refresh_interval: Some(DEFAULT_MODEL_LIST_REFRESH_INTERVAL),
+            fork_consistency_check: true,
+            fork_stale_notice: true,
+            tool_timeouts: ToolTimeouts::default(),
+            parallel_tool_calls: ParallelToolCalls::default(),
+            min_warning_severity: WarningSeverity::Info,
+            turn_deadline: None,
+            record_input_rejections: false,
+            rollout_fsync: RolloutFsyncPolicy::default(),
+            user_context: None,
+            skill_packs: BTreeMap::new(),
+            session_profile: None,
         };
 
         assert_eq!(expected_gpt5_profile_config, gpt5_profile_config);
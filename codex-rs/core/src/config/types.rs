@@ -3,9 +3,12 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use chrono::DateTime;
+use chrono::Utc;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::time::Duration;
 use wildmatch::WildMatchPattern;
@@ -221,6 +224,39 @@ mod option_duration_secs {
     }
 }
 
+mod duration_secs_map {
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serializer;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    pub fn serialize<S>(value: &HashMap<String, Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(
+            value
+                .iter()
+                .map(|(name, duration)| (name, duration.as_secs_f64())),
+        )
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HashMap::<String, f64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, secs)| {
+                Duration::try_from_secs_f64(secs)
+                    .map(|duration| (name, duration))
+                    .map_err(serde::de::Error::custom)
+            })
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub enum UriBasedFileOpener {
     #[serde(rename = "vscode")]
@@ -273,6 +309,126 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Where the scratch directory of each turn is created.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TurnTmpdirLocation {
+    /// Under the system temporary directory.
+    #[default]
+    System,
+    /// Under `$CODEX_HOME/tmp`.
+    CodexHome,
+}
+
+/// How the tool calls of one model response run when the model asks for
+/// several at once.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+pub struct ParallelToolCalls {
+    /// Calls that may run at once; unlimited when unset. Tools that cannot
+    /// run in parallel, such as shell commands, always run alone.
+    #[serde(default)]
+    pub max_concurrency: Option<NonZeroUsize>,
+
+    /// What the calls share.
+    #[serde(default)]
+    pub isolation: ToolCallIsolation,
+}
+
+/// What concurrent tool calls of a turn share.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolCallIsolation {
+    /// The cwd and the turn's scratch directory.
+    #[default]
+    SharedCwd,
+    /// The cwd only: each call gets its own scratch directory under the
+    /// turn's, exported as `CODEX_TURN_TMPDIR` to the commands it runs.
+    PerCallTmp,
+}
+
+/// When the instruction files are read again during a session.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RefreshPolicy {
+    /// Only when the session starts.
+    #[default]
+    Never,
+    /// Before each turn, replacing the instructions when the files changed.
+    OnChange,
+    /// Before each turn, always replacing the instructions.
+    EveryTurn,
+}
+
+/// Time limits for tool calls.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ToolTimeoutsToml {
+    /// Limit in seconds for tools not listed in `per_tool`.
+    #[serde(default, with = "option_duration_secs")]
+    pub default_sec: Option<Duration>,
+
+    /// Limits in seconds keyed by tool name.
+    #[serde(default, with = "duration_secs_map")]
+    pub per_tool: HashMap<String, Duration>,
+}
+
+/// How long a tool call may run before it is stopped and the model is told
+/// it timed out. Shell commands are killed along with their process group
+/// and whatever they printed is kept; other tools are abandoned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolTimeouts {
+    /// Limit for tools not listed in `per_tool`.
+    pub default: Duration,
+
+    /// Limits keyed by tool name.
+    pub per_tool: HashMap<String, Duration>,
+}
+
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+impl ToolTimeouts {
+    /// The limit for the tool named `tool_name`.
+    pub fn for_tool(&self, tool_name: &str) -> Duration {
+        self.per_tool
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for ToolTimeouts {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_TOOL_TIMEOUT,
+            per_tool: HashMap::new(),
+        }
+    }
+}
+
+impl From<ToolTimeoutsToml> for ToolTimeouts {
+    fn from(toml: ToolTimeoutsToml) -> Self {
+        Self {
+            default: toml.default_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT),
+            per_tool: toml.per_tool,
+        }
+    }
+}
+
+/// Where and how the user lives, told to the model so it gets dates and
+/// times right.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct UserContext {
+    /// Time zone of the user, such as `Europe/Berlin`.
+    pub timezone: Option<String>,
+
+    /// Locale of the user, such as `de-DE`.
+    pub locale: Option<String>,
+
+    /// Report this as the time the session started instead of reading the
+    /// clock, for deterministic tests and replays. The reported time still
+    /// advances with the runtime's clock.
+    pub now_override: Option<DateTime<Utc>>,
+}
+
 // ===== OTEL configuration =====
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -670,6 +826,27 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn deserialize_tool_timeouts() {
+        let toml: ToolTimeoutsToml = toml::from_str(
+            r#"
+            default_sec = 120
+            per_tool = { shell = 1.5, read_file = 10 }
+        "#,
+        )
+        .expect("should deserialize tool timeouts");
+
+        let timeouts = ToolTimeouts::from(toml);
+        assert_eq!(timeouts.default, Duration::from_secs(120));
+        assert_eq!(timeouts.for_tool("shell"), Duration::from_millis(1500));
+        assert_eq!(timeouts.for_tool("read_file"), Duration::from_secs(10));
+        assert_eq!(timeouts.for_tool("list_dir"), Duration::from_secs(120));
+        assert_eq!(
+            ToolTimeouts::from(ToolTimeoutsToml::default()),
+            ToolTimeouts::default()
+        );
+    }
+
     #[test]
     fn deserialize_stdio_command_server_config() {
         let cfg: McpServerConfig = toml::from_str(
@@ -1,16 +1,19 @@
 use crate::codex::TurnContext;
 use crate::context_manager::normalize;
+use crate::context_usage::TokenCounter;
+use crate::history_truncation::truncate_at_index;
 use crate::truncate::TruncationPolicy;
-use crate::truncate::approx_token_count;
 use crate::truncate::approx_tokens_from_byte_count;
 use crate::truncate::truncate_function_output_items_with_policy;
 use crate::truncate::truncate_text;
+use crate::user_instructions::UserInstructions;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::TokenUsage;
 use codex_protocol::protocol::TokenUsageInfo;
+use std::collections::HashSet;
 use std::ops::Deref;
 
 /// Transcript of conversation history
@@ -53,15 +56,23 @@ impl ContextManager {
         I::Item: std::ops::Deref<Target = ResponseItem>,
     {
         for item in items {
-            let item_ref = item.deref();
-            let is_ghost_snapshot = matches!(item_ref, ResponseItem::GhostSnapshot { .. });
-            if !is_api_message(item_ref) && !is_ghost_snapshot {
-                continue;
+            if let Some(processed) = Self::prepare_item(item.deref(), policy) {
+                self.items.push(processed);
             }
+        }
+    }
 
-            let processed = self.process_item(item_ref, policy);
-            self.items.push(processed);
+    /// `item` as [`Self::record_items`] records it with `policy`, `None`
+    /// when it is not recorded at all.
+    pub(crate) fn prepare_item(
+        item: &ResponseItem,
+        policy: TruncationPolicy,
+    ) -> Option<ResponseItem> {
+        let is_ghost_snapshot = matches!(item, ResponseItem::GhostSnapshot { .. });
+        if !is_api_message(item) && !is_ghost_snapshot {
+            return None;
         }
+        Some(Self::process_item(item, policy))
     }
 
     pub(crate) fn get_history(&mut self) -> Vec<ResponseItem> {
@@ -77,34 +88,23 @@ impl ContextManager {
         history
     }
 
-    // Estimate token usage using byte-based heuristics from the truncation helpers.
-    // This is a coarse lower bound, not a tokenizer-accurate count.
-    pub(crate) fn estimate_token_count(&self, turn_context: &TurnContext) -> Option<i64> {
-        let model_family = turn_context.client.get_model_family();
-        let base_tokens =
-            i64::try_from(approx_token_count(model_family.base_instructions.as_str()))
-                .unwrap_or(i64::MAX);
-
-        let items_tokens = self.items.iter().fold(0i64, |acc, item| {
-            acc + match item {
-                ResponseItem::GhostSnapshot { .. } => 0,
-                ResponseItem::Reasoning {
-                    encrypted_content: Some(content),
-                    ..
-                }
-                | ResponseItem::Compaction {
-                    encrypted_content: content,
-                } => estimate_reasoning_length(content.len()) as i64,
-                item => {
-                    let serialized = serde_json::to_string(item).unwrap_or_default();
-                    i64::try_from(approx_token_count(&serialized)).unwrap_or(i64::MAX)
-                }
-            }
-        });
+    // Estimate token usage of the next request with `counter`. Encrypted
+    // reasoning is sized from its length since it cannot be tokenized.
+    pub(crate) fn estimate_token_count(
+        &self,
+        turn_context: &TurnContext,
+        counter: &dyn TokenCounter,
+    ) -> Option<i64> {
+        let base_tokens = estimate_base_tokens(turn_context, counter);
+        let items_tokens = self
+            .items
+            .iter()
+            .fold(0i64, |acc, item| acc + estimate_item_tokens(item, counter));
 
         Some(base_tokens.saturating_add(items_tokens))
     }
 
+    #[cfg(test)]
     pub(crate) fn remove_first_item(&mut self) {
         if !self.items.is_empty() {
             // Remove the oldest item (front of the list). Items are ordered from
@@ -117,10 +117,93 @@ impl ContextManager {
         }
     }
 
+    /// Like [`Self::remove_first_item`], removing the oldest item other than
+    /// the newest one that `keep` does not hold on to. Returns `false` when
+    /// there is no such item.
+    pub(crate) fn remove_first_item_except(
+        &mut self,
+        keep: impl Fn(&ResponseItem) -> bool,
+    ) -> bool {
+        let removable = self.items.len().saturating_sub(1);
+        let Some(index) = self.items[..removable].iter().position(|item| !keep(item)) else {
+            return false;
+        };
+        let removed = self.items.remove(index);
+        normalize::remove_corresponding_for(&mut self.items, &removed);
+        true
+    }
+
     pub(crate) fn replace(&mut self, items: Vec<ResponseItem>) {
         self.items = items;
     }
 
+    /// Swap the user instructions message for `instructions`, or drop it when
+    /// `instructions` is `None`. Without a message to swap, `instructions` is
+    /// inserted after the leading developer messages, where the initial
+    /// context puts it.
+    pub(crate) fn replace_user_instructions(&mut self, instructions: Option<ResponseItem>) {
+        let existing = self.items.iter().position(|item| {
+            matches!(
+                item,
+                ResponseItem::Message { role, content, .. }
+                    if role == "user" && UserInstructions::is_user_instructions(content)
+            )
+        });
+        match (existing, instructions) {
+            (Some(index), Some(instructions)) => self.items[index] = instructions,
+            (Some(index), None) => {
+                self.items.remove(index);
+            }
+            (None, Some(instructions)) => {
+                let index = self
+                    .items
+                    .iter()
+                    .take_while(
+                        |item| matches!(item, ResponseItem::Message { role, .. } if role == "developer"),
+                    )
+                    .count();
+                self.items.insert(index, instructions);
+            }
+            (None, None) => {}
+        }
+    }
+
+    /// Whether the output of the function call `call_id` is recorded.
+    pub(crate) fn has_function_call_output(&self, call_id: &str) -> bool {
+        self.items.iter().any(|item| {
+            matches!(item, ResponseItem::FunctionCallOutput { call_id: id, .. } if id == call_id)
+        })
+    }
+
+    /// The function calls among `call_ids` with their outputs, in history
+    /// order.
+    pub(crate) fn function_call_items(&self, call_ids: &HashSet<String>) -> Vec<ResponseItem> {
+        self.items
+            .iter()
+            .filter(|item| match item {
+                ResponseItem::FunctionCall { call_id, .. }
+                | ResponseItem::FunctionCallOutput { call_id, .. } => call_ids.contains(call_id),
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Number of items currently recorded, without normalization.
+    pub(crate) fn item_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Items currently recorded, without normalization.
+    pub(crate) fn items(&self) -> &[ResponseItem] {
+        &self.items
+    }
+
+    /// Drop every item recorded at or after `index`.
+    pub(crate) fn truncate(&mut self, index: usize) {
+        self.items = truncate_at_index(std::mem::take(&mut self.items), index);
+    }
+
     pub(crate) fn replace_last_turn_images(&mut self, placeholder: &str) {
         let Some(last_item) = self.items.last_mut() else {
             return;
@@ -224,7 +307,7 @@ impl ContextManager {
         items.retain(|item| !matches!(item, ResponseItem::GhostSnapshot { .. }));
     }
 
-    fn process_item(&self, item: &ResponseItem, policy: TruncationPolicy) -> ResponseItem {
+    fn process_item(item: &ResponseItem, policy: TruncationPolicy) -> ResponseItem {
         let policy_with_serialization_budget = policy.mul(1.2);
         match item {
             ResponseItem::FunctionCallOutput { call_id, output } => {
@@ -283,6 +366,31 @@ fn is_api_message(message: &ResponseItem) -> bool {
     }
 }
 
+/// Estimated tokens the base instructions add to every request.
+pub(crate) fn estimate_base_tokens(turn_context: &TurnContext, counter: &dyn TokenCounter) -> i64 {
+    let model_family = turn_context.client.get_model_family();
+    i64::try_from(counter.count_tokens(model_family.base_instructions.as_str())).unwrap_or(i64::MAX)
+}
+
+/// Estimated tokens `item` adds to a request, see
+/// [`ContextManager::estimate_token_count`].
+pub(crate) fn estimate_item_tokens(item: &ResponseItem, counter: &dyn TokenCounter) -> i64 {
+    match item {
+        ResponseItem::GhostSnapshot { .. } => 0,
+        ResponseItem::Reasoning {
+            encrypted_content: Some(content),
+            ..
+        }
+        | ResponseItem::Compaction {
+            encrypted_content: content,
+        } => estimate_reasoning_length(content.len()) as i64,
+        item => {
+            let serialized = serde_json::to_string(item).unwrap_or_default();
+            i64::try_from(counter.count_tokens(&serialized)).unwrap_or(i64::MAX)
+        }
+    }
+}
+
 fn estimate_reasoning_length(encoded_len: usize) -> usize {
     encoded_len
         .saturating_mul(3)
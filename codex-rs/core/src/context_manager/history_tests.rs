@@ -1,6 +1,7 @@
 use super::*;
 use crate::truncate;
 use crate::truncate::TruncationPolicy;
+use crate::user_instructions::DeveloperInstructions;
 use codex_git::GhostCommit;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -177,6 +178,24 @@ fn remove_first_item_removes_matching_output_for_function_call() {
     assert_eq!(h.contents(), vec![]);
 }
 
+#[test]
+fn remove_first_item_except_skips_kept_items_and_the_newest() {
+    let items = vec![
+        user_msg("pinned"),
+        assistant_msg("old answer"),
+        user_msg("compact now"),
+    ];
+    let mut h = create_history_with_items(items);
+    let keep = |item: &ResponseItem| *item == user_msg("pinned");
+
+    assert!(h.remove_first_item_except(keep));
+    assert_eq!(
+        h.contents(),
+        vec![user_msg("pinned"), user_msg("compact now")]
+    );
+    assert!(!h.remove_first_item_except(keep));
+}
+
 #[test]
 fn remove_first_item_removes_matching_call_for_output() {
     let items = vec![
@@ -832,3 +851,32 @@ fn normalize_mixed_inserts_and_removals_panics_in_debug() {
     let mut h = create_history_with_items(items);
     h.normalize_history();
 }
+
+#[test]
+fn replace_user_instructions_swaps_the_instructions_message() {
+    let instructions = |text: &str| -> ResponseItem {
+        UserInstructions {
+            directory: "/repo".to_string(),
+            text: text.to_string(),
+        }
+        .into()
+    };
+    let developer: ResponseItem = DeveloperInstructions::new("be terse".to_string()).into();
+
+    let mut h =
+        create_history_with_items(vec![developer.clone(), instructions("old"), user_msg("hi")]);
+    h.replace_user_instructions(Some(instructions("new")));
+    assert_eq!(
+        h.contents(),
+        vec![developer.clone(), instructions("new"), user_msg("hi")]
+    );
+
+    h.replace_user_instructions(None);
+    assert_eq!(h.contents(), vec![developer.clone(), user_msg("hi")]);
+
+    h.replace_user_instructions(Some(instructions("back")));
+    assert_eq!(
+        h.contents(),
+        vec![developer, instructions("back"), user_msg("hi")]
+    );
+}
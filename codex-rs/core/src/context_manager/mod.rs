@@ -2,3 +2,5 @@ mod history;
 mod normalize;
 
 pub(crate) use history::ContextManager;
+pub(crate) use history::estimate_base_tokens;
+pub(crate) use history::estimate_item_tokens;
@@ -0,0 +1,137 @@
+//! Estimated context window usage of a conversation.
+//!
+//! After every turn the session estimates the size of its next request with
+//! the [`TokenCounter`] registered on the [`ConversationManager`] and reports
+//! it through [`CodexConversation::context_usage`]. Crossing one of the
+//! configured `context_usage_thresholds` emits
+//! [`EventMsg::ContextUsageUpdated`] once; dropping back below a threshold,
+//! e.g. after compaction, re-arms it.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+//! [`CodexConversation::context_usage`]: crate::CodexConversation::context_usage
+//! [`EventMsg::ContextUsageUpdated`]: crate::protocol::EventMsg::ContextUsageUpdated
+
+use std::sync::Mutex;
+
+use codex_protocol::protocol::ContextUsage;
+
+use crate::truncate::approx_token_count;
+
+/// Counts the tokens `text` takes up in a request.
+pub trait TokenCounter: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Byte-based estimate shared with output truncation. A coarse lower bound,
+/// not a tokenizer-accurate count.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproxTokenCounter;
+
+impl TokenCounter for ApproxTokenCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        approx_token_count(text)
+    }
+}
+
+/// Latest usage of one session and the thresholds it is above.
+pub(crate) struct ContextUsageTracker {
+    /// Ascending and deduplicated.
+    thresholds: Vec<u8>,
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    usage: ContextUsage,
+    /// Number of leading `thresholds` the latest usage is at or above.
+    crossed: usize,
+}
+
+impl ContextUsageTracker {
+    pub(crate) fn new(mut thresholds: Vec<u8>) -> Self {
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        Self {
+            thresholds,
+            state: Mutex::new(TrackerState::default()),
+        }
+    }
+
+    pub(crate) fn usage(&self) -> ContextUsage {
+        self.lock_state().usage
+    }
+
+    /// Record `usage` and return the thresholds it newly crossed, ascending.
+    pub(crate) fn update(&self, usage: ContextUsage) -> Vec<u8> {
+        let crossed = usage.percent.map_or(0, |percent| {
+            self.thresholds
+                .iter()
+                .take_while(|threshold| **threshold <= percent)
+                .count()
+        });
+        let mut state = self.lock_state();
+        let newly_crossed = self
+            .thresholds
+            .get(state.crossed..crossed)
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default();
+        state.usage = usage;
+        state.crossed = crossed;
+        newly_crossed
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reports_each_crossing_once() {
+        let tracker = ContextUsageTracker::new(vec![95, 50, 80, 50]);
+        let window = Some(1_000);
+
+        assert_eq!(
+            tracker.update(ContextUsage::new(400, window)),
+            Vec::<u8>::new()
+        );
+        assert_eq!(tracker.update(ContextUsage::new(850, window)), vec![50, 80]);
+        assert_eq!(
+            tracker.update(ContextUsage::new(900, window)),
+            Vec::<u8>::new()
+        );
+        // Compaction brings usage back down, so 80 can be crossed again.
+        assert_eq!(
+            tracker.update(ContextUsage::new(600, window)),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            tracker.update(ContextUsage::new(2_000, window)),
+            vec![80, 95]
+        );
+        assert_eq!(
+            tracker.usage(),
+            ContextUsage {
+                used_tokens_estimate: 2_000,
+                window,
+                percent: Some(100),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_window_crosses_nothing() {
+        let tracker = ContextUsageTracker::new(vec![50]);
+        assert_eq!(
+            tracker.update(ContextUsage::new(10, None)),
+            Vec::<u8>::new()
+        );
+        assert_eq!(tracker.usage().percent, None);
+    }
+}
@@ -1,34 +1,108 @@
 use crate::AuthManager;
 #[cfg(any(test, feature = "test-support"))]
 use crate::CodexAuth;
+use crate::CodexHome;
+use crate::CodexRuntime;
 #[cfg(any(test, feature = "test-support"))]
 use crate::ModelProviderInfo;
+use crate::child_conversations::ChildConversationOverrides;
+use crate::child_conversations::ConversationLineage;
 use crate::codex::Codex;
 use crate::codex::CodexSpawnOk;
 use crate::codex::INITIAL_SUBMIT_ID;
+use crate::codex::get_last_assistant_message_from_turn;
 use crate::codex_conversation::CodexConversation;
 use crate::config::Config;
+use crate::config::ConfigBuilder;
+use crate::config::ConfigOverrides;
+use crate::context_usage::ApproxTokenCounter;
+use crate::context_usage::TokenCounter;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
+use crate::event_replay::EventReplayBuffer;
+#[cfg(feature = "test-support")]
+use crate::fault_injection::FaultPlan;
+#[cfg(feature = "test-support")]
+use crate::fault_injection::RolloutFaultsRegistration;
+use crate::fork_consistency;
+use crate::history_truncation::truncate_rollout_before_turn_id;
+use crate::history_truncation::try_truncate_before_nth_user_message;
+use crate::history_truncation::user_message_positions;
+use crate::manager_load::ConversationLoad;
+use crate::manager_load::LoadCounters;
+use crate::manager_load::ManagerLoad;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ConversationMetrics;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ManagerMetrics;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::ManagerMetricsCounters;
+#[cfg(feature = "metrics")]
+use crate::manager_metrics::SpawnKind;
 use crate::models_manager::manager::ModelsManager;
+use crate::post_process::TurnPostProcessor;
+use crate::pre_process::TurnPreProcessor;
+use crate::project_approvals::ProjectApprovalStore;
+use crate::project_approvals::ProjectScope;
+use crate::project_notes::ProjectNotes;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
+use crate::protocol::Op;
 use crate::protocol::SessionConfiguredEvent;
+use crate::rollout::DeletionReport;
+use crate::rollout::PromptScript;
+use crate::rollout::ReadOnlyTranscript;
 use crate::rollout::RolloutRecorder;
+use crate::rollout::delete::delete_rollout;
+use crate::rollout::forks::ForkedChild;
+use crate::rollout::forks::forks_of;
+use crate::rollout::latest::SessionSummary;
+use crate::rollout::latest::latest_session_for_cwd;
+use crate::runtime_env::RuntimeEnv;
+use crate::script_run::ScriptRunOptions;
+use crate::script_run::ScriptRunReport;
+use crate::script_run::run_prompts;
+use crate::session_profiles;
+use crate::session_profiles::SessionProfiles;
+use crate::skills::SkillUsageStats;
 use crate::skills::SkillsManager;
+use crate::spawn_observer::SpawnMilestone;
+use crate::spawn_observer::SpawnObserver;
+use crate::spawn_observer::SpawnProgress;
 use codex_protocol::ConversationId;
-use codex_protocol::items::TurnItem;
+use codex_protocol::approvals::ProjectApprovalRule;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::openai_models::ModelPreset;
+use codex_protocol::protocol::ConversationLink;
+use codex_protocol::protocol::ForkConsistencyReport;
 use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::ParentRef;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionEndReason;
+use codex_protocol::protocol::SessionEndedEvent;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::TurnId;
+use codex_protocol::user_input::UserInput;
+use futures::future::join_all;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Weak;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 #[cfg(any(test, feature = "test-support"))]
 use tempfile::TempDir;
 use tokio::sync::RwLock;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// How long a session that failed to start gets to shut down before it is
+/// aborted.
+const FAILED_SPAWN_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Represents a newly created Codex conversation, including the first event
 /// (which is [`EventMsg::SessionConfigured`]).
@@ -36,43 +110,213 @@ pub struct NewConversation {
     pub conversation_id: ConversationId,
     pub conversation: Arc<CodexConversation>,
     pub session_configured: SessionConfiguredEvent,
+    /// For a fork, the files its history read that changed in its workspace
+    /// since. `None` for other conversations and when
+    /// `fork_consistency_check` is off.
+    pub fork_consistency: Option<ForkConsistencyReport>,
+}
+
+/// A conversation [`ConversationManager::remove_conversation`] or
+/// [`ConversationManager::remove_conversation_gracefully`] stopped tracking,
+/// and its state at that point.
+pub struct RemovedConversation {
+    pub conversation: Arc<CodexConversation>,
+    /// Whether a turn was running. [`ConversationManager::remove_conversation`]
+    /// does not stop it, so its rollout may still grow.
+    pub was_turn_in_flight: bool,
+    /// Whether everything recorded so far was written to the rollout and
+    /// synced to disk.
+    pub rollout_flushed: bool,
+    /// References to the conversation held outside the manager, not counting
+    /// `conversation`. The rollout is still in use while this is not zero.
+    pub remaining_refs: usize,
+    /// How the session ended, when it was removed with
+    /// [`ConversationManager::remove_conversation_gracefully`] and ended in
+    /// time.
+    pub session_ended: Option<SessionEndedEvent>,
+}
+
+fn removed_conversation(
+    conversation: Arc<CodexConversation>,
+    was_turn_in_flight: bool,
+    rollout_flushed: bool,
+    session_ended: Option<SessionEndedEvent>,
+) -> RemovedConversation {
+    // The manager no longer holds the conversation; the one reference it has
+    // left is `conversation`.
+    let remaining_refs = Arc::strong_count(&conversation) - 1;
+    RemovedConversation {
+        conversation,
+        was_turn_in_flight,
+        rollout_flushed,
+        remaining_refs,
+        session_ended,
+    }
 }
 
 /// [`ConversationManager`] is responsible for creating conversations and
 /// maintaining them in memory.
+///
+/// The manager is a handle: clones are cheap and share the same
+/// conversations, limits, and registered post-processors.
+#[derive(Clone)]
 pub struct ConversationManager {
-    conversations: Arc<RwLock<HashMap<ConversationId, Arc<CodexConversation>>>>,
+    shared: Arc<ManagerShared>,
+}
+
+/// State shared between the manager, its clones, and the conversations it
+/// spawns, so a conversation can spawn children of its own.
+struct ManagerShared {
+    conversations: RwLock<HashMap<ConversationId, Arc<CodexConversation>>>,
+    /// Parent of every child conversation that is still tracked.
+    parents: RwLock<HashMap<ConversationId, ChildLink>>,
+    /// Every fork that is still tracked, by its own id.
+    forks: RwLock<HashMap<ConversationId, ForkedChild>>,
+    /// Ids of resumed conversations that are starting and not yet tracked.
+    resuming: std::sync::Mutex<HashSet<ConversationId>>,
+    /// `usize::MAX` when unlimited.
+    max_conversations: AtomicUsize,
     auth_manager: Arc<AuthManager>,
     models_manager: Arc<ModelsManager>,
     skills_manager: Arc<SkillsManager>,
     session_source: SessionSource,
+    failed_spawns: FailedSpawnCounters,
+    /// See [`ConversationManager::load`].
+    load: Arc<LoadCounters>,
+    token_counter: std::sync::RwLock<Arc<dyn TokenCounter>>,
+    runtime_env: std::sync::RwLock<RuntimeEnv>,
+    /// Base of [`ConversationManager::new_conversation_default`] and
+    /// [`ConversationManager::new_conversation_with`].
+    default_config: std::sync::RwLock<Option<Config>>,
+    pre_processors: std::sync::RwLock<Vec<Arc<dyn TurnPreProcessor>>>,
+    post_processors: std::sync::RwLock<Vec<Arc<dyn TurnPostProcessor>>>,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<ManagerMetricsCounters>,
+    /// The only codex home conversations may be configured for, on a manager
+    /// derived from a [`CodexRuntime`].
+    codex_home: Option<PathBuf>,
+    /// Temporary codex home of a test manager, removed once the last handle
+    /// and conversation are gone.
     #[cfg(any(test, feature = "test-support"))]
     _test_codex_home_guard: Option<TempDir>,
+    /// Plan injected with [`ConversationManager::inject_rollout_faults`].
+    #[cfg(feature = "test-support")]
+    rollout_faults: std::sync::Mutex<Option<RolloutFaultsRegistration>>,
+}
+
+/// A resumed conversation id claimed by [`ManagerShared::reserve_resumed_id`],
+/// released on drop.
+struct ResumeReservation<'a> {
+    resuming: &'a std::sync::Mutex<HashSet<ConversationId>>,
+    conversation_id: ConversationId,
+}
+
+impl Drop for ResumeReservation<'_> {
+    fn drop(&mut self) {
+        self.resuming
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&self.conversation_id);
+    }
+}
+
+#[derive(Default)]
+struct FailedSpawnCounters {
+    discarded: AtomicUsize,
+    aborted: AtomicUsize,
+    leaked_rollouts: AtomicUsize,
+}
+
+/// Sessions that failed to start and were cleaned up by the manager.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FailedSpawnStats {
+    /// Sessions discarded because they failed before `SessionConfigured`.
+    pub discarded: usize,
+    /// Of those, sessions that did not shut down in time and were aborted.
+    pub aborted: usize,
+    /// Rollout files of discarded sessions that could not be removed.
+    pub leaked_rollouts: usize,
+}
+
+/// Where a fork cuts the history it starts from.
+#[derive(Debug, Clone, Copy)]
+enum ForkCut {
+    /// Before the nth user message of the rollout.
+    UserMessage(usize),
+    /// Before the start of the turn with this id.
+    Turn(TurnId),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ChildLink {
+    parent_id: ConversationId,
+    remove_with_parent: bool,
 }
 
 impl ConversationManager {
     pub fn new(auth_manager: Arc<AuthManager>, session_source: SessionSource) -> Self {
-        let skills_manager = Arc::new(SkillsManager::new(auth_manager.codex_home().to_path_buf()));
+        let skills_manager = Arc::new(SkillsManager::new(auth_manager.home().clone()));
+        let models_manager = Arc::new(ModelsManager::new(auth_manager.clone()));
         Self {
-            conversations: Arc::new(RwLock::new(HashMap::new())),
-            auth_manager: auth_manager.clone(),
-            session_source,
-            models_manager: Arc::new(ModelsManager::new(auth_manager)),
+            shared: Arc::new(ManagerShared::new(
+                auth_manager,
+                models_manager,
+                skills_manager,
+                session_source,
+            )),
+        }
+    }
+
+    /// A manager for the codex home of `auth_manager` sharing the model list
+    /// and metrics of `runtime`, see [`CodexRuntime::manager_for_home`].
+    pub(crate) fn on_runtime(
+        runtime: &CodexRuntime,
+        auth_manager: Arc<AuthManager>,
+        session_source: SessionSource,
+    ) -> Self {
+        let codex_home = auth_manager.codex_home().to_path_buf();
+        let skills_manager = Arc::new(SkillsManager::new(auth_manager.home().clone()));
+        let mut shared = ManagerShared::new(
+            auth_manager,
+            runtime.models_manager(),
             skills_manager,
-            #[cfg(any(test, feature = "test-support"))]
-            _test_codex_home_guard: None,
+            session_source,
+        );
+        shared.codex_home = Some(codex_home);
+        #[cfg(feature = "metrics")]
+        {
+            shared.metrics = runtime.metrics();
+        }
+        Self {
+            shared: Arc::new(shared),
         }
     }
 
+    /// Like [`Self::new`], starting conversations from `base` unless told
+    /// otherwise, see [`Self::new_conversation_with`].
+    pub fn with_default_config(
+        auth_manager: Arc<AuthManager>,
+        session_source: SessionSource,
+        base: Config,
+    ) -> Self {
+        let manager = Self::new(auth_manager, session_source);
+        manager.set_default_config(base);
+        manager
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     /// Construct with a dummy AuthManager containing the provided CodexAuth.
     /// Used for integration tests: should not be used by ordinary business logic.
-    pub fn with_models_provider(auth: CodexAuth, provider: ModelProviderInfo) -> Self {
+    /// `provider` may also be a recording or playback provider from
+    /// `provider_cassette`, or a faulty provider from `fault_injection`.
+    pub fn with_models_provider(auth: CodexAuth, provider: impl Into<ModelProviderInfo>) -> Self {
         let temp_dir = tempfile::tempdir().unwrap_or_else(|err| panic!("temp codex home: {err}"));
         let codex_home = temp_dir.path().to_path_buf();
-        let mut manager = Self::with_models_provider_and_home(auth, provider, codex_home);
-        manager._test_codex_home_guard = Some(temp_dir);
-        manager
+        let mut shared = ManagerShared::for_testing(auth, provider.into(), codex_home);
+        shared._test_codex_home_guard = Some(temp_dir);
+        Self {
+            shared: Arc::new(shared),
+        }
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -80,105 +324,402 @@ impl ConversationManager {
     /// Used for integration tests: should not be used by ordinary business logic.
     pub fn with_models_provider_and_home(
         auth: CodexAuth,
-        provider: ModelProviderInfo,
+        provider: impl Into<ModelProviderInfo>,
         codex_home: PathBuf,
     ) -> Self {
-        let auth_manager = crate::AuthManager::from_auth_for_testing_with_home(auth, codex_home);
-        let skills_manager = Arc::new(SkillsManager::new(auth_manager.codex_home().to_path_buf()));
         Self {
-            conversations: Arc::new(RwLock::new(HashMap::new())),
-            auth_manager: auth_manager.clone(),
-            session_source: SessionSource::Exec,
-            models_manager: Arc::new(ModelsManager::with_provider(auth_manager, provider)),
-            skills_manager,
-            _test_codex_home_guard: None,
+            shared: Arc::new(ManagerShared::for_testing(
+                auth,
+                provider.into(),
+                codex_home,
+            )),
         }
     }
 
+    #[cfg(feature = "test-support")]
+    /// Inject the rollout faults of `plan` into the conversations this manager
+    /// starts from now on, replacing any plan injected before. Provider faults
+    /// are injected by passing a `FaultyProvider` as the provider instead.
+    pub fn inject_rollout_faults(&self, plan: &FaultPlan) {
+        let mut rollout_faults = self
+            .shared
+            .rollout_faults
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // Unregister the previous plan before registering the new one for
+        // the same codex home.
+        drop(rollout_faults.take());
+        *rollout_faults = Some(RolloutFaultsRegistration::new(
+            self.shared.auth_manager.codex_home().to_path_buf(),
+            plan.clone(),
+        ));
+    }
+
     pub fn session_source(&self) -> SessionSource {
-        self.session_source.clone()
+        self.shared.session_source.clone()
+    }
+
+    /// The codex home the conversations of this manager are recorded under.
+    pub fn codex_home(&self) -> &CodexHome {
+        self.shared.auth_manager.home()
     }
 
     pub fn skills_manager(&self) -> Arc<SkillsManager> {
-        self.skills_manager.clone()
+        self.shared.skills_manager.clone()
+    }
+
+    /// How often each skill was available to, and invoked by, user turns
+    /// across the conversations of this manager and any other sharing its
+    /// codex home, see [`crate::skills::usage`].
+    pub async fn skill_usage_report(&self) -> std::io::Result<BTreeMap<String, SkillUsageStats>> {
+        self.shared.skills_manager.usage_stats().await
+    }
+
+    pub fn auth_manager(&self) -> Arc<AuthManager> {
+        self.shared.auth_manager.clone()
+    }
+
+    /// Cap the number of conversations, children included, that this manager
+    /// tracks at once. `None` removes the cap. Conversations that are already
+    /// tracked are kept even if they exceed the new cap.
+    pub fn set_max_conversations(&self, max_conversations: Option<usize>) {
+        self.shared
+            .max_conversations
+            .store(max_conversations.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    /// Counts of sessions that failed to start, so leaks are observable.
+    pub fn failed_spawn_stats(&self) -> FailedSpawnStats {
+        self.shared.failed_spawn_stats()
+    }
+
+    /// The conversations the manager tracks, by what they are doing. Cheap:
+    /// it reads counters the sessions keep current, see
+    /// [`crate::manager_load`].
+    pub fn load(&self) -> ManagerLoad {
+        self.shared.load.snapshot()
+    }
+
+    /// Resolve once fewer than `max_in_flight` tracked conversations run a
+    /// turn. Never resolves for `0`.
+    pub async fn wait_for_capacity(&self, max_in_flight: usize) {
+        self.shared.load.wait_for_capacity(max_in_flight).await;
+    }
+
+    /// Current values of the manager's operational metrics. See
+    /// [`crate::manager_metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics_snapshot(&self) -> ManagerMetrics {
+        self.shared.metrics.snapshot()
+    }
+
+    /// Parent and children of `conversation_id`, as recorded when the children
+    /// were spawned with [`CodexConversation::spawn_child`].
+    pub async fn lineage(&self, conversation_id: ConversationId) -> ConversationLineage {
+        self.shared.lineage(conversation_id).await
+    }
+
+    /// Conversations forked from `conversation_id`, in fork order: the forks
+    /// this manager tracks and those recorded under codex home. See
+    /// [`crate::rollout::forks`] for which recorded forks are found.
+    pub async fn children_of(&self, conversation_id: ConversationId) -> Vec<ForkedChild> {
+        let codex_home = self.shared.auth_manager.codex_home();
+        let mut children: HashMap<ConversationId, ForkedChild> =
+            match forks_of(codex_home, conversation_id).await {
+                Ok(recorded) => recorded
+                    .into_iter()
+                    .map(|child| (child.conversation_id, child))
+                    .collect(),
+                Err(err) => {
+                    warn!("failed to scan sessions for forks of {conversation_id}: {err}");
+                    HashMap::new()
+                }
+            };
+        for child in self.shared.forks.read().await.values() {
+            if child.parent.conversation_id == conversation_id {
+                children.insert(child.conversation_id, child.clone());
+            }
+        }
+        let mut children: Vec<ForkedChild> = children.into_values().collect();
+        // Conversation ids are time-ordered, so this is fork order.
+        children.sort_by_key(|child| child.conversation_id.to_string());
+        children
+    }
+
+    /// Estimate context usage of conversations this manager spawns from now
+    /// on with `counter` instead of the byte-based default.
+    pub fn set_token_counter(&self, counter: Arc<dyn TokenCounter>) {
+        *self
+            .shared
+            .token_counter
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = counter;
+    }
+
+    /// Take the clock and ids of conversations this manager spawns from now
+    /// on, including their delegates, from `env` instead of the system.
+    pub fn set_runtime_env(&self, env: RuntimeEnv) {
+        *self
+            .shared
+            .runtime_env
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = env;
+    }
+
+    /// Start conversations spawned with [`Self::new_conversation_default`]
+    /// and [`Self::new_conversation_with`] from now on from `base`.
+    pub fn set_default_config(&self, base: Config) {
+        *self
+            .shared
+            .default_config
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(base);
+    }
+
+    /// Run `processor` on every user input submitted to conversations this
+    /// manager spawns from now on, after the processors registered before it.
+    pub fn register_turn_pre_processor(&self, processor: Arc<dyn TurnPreProcessor>) {
+        self.shared
+            .pre_processors
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(processor);
+    }
+
+    /// Run `processor` on the output of every turn in conversations this
+    /// manager spawns from now on, after the processors registered before it.
+    pub fn register_turn_post_processor(&self, processor: Arc<dyn TurnPostProcessor>) {
+        self.shared
+            .post_processors
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(processor);
+    }
+
+    /// Commands approved with [`ReviewDecision::ApprovedForProject`] for the
+    /// project containing `cwd`.
+    ///
+    /// [`ReviewDecision::ApprovedForProject`]: crate::protocol::ReviewDecision::ApprovedForProject
+    pub async fn list_project_approval_rules(
+        &self,
+        cwd: &Path,
+    ) -> std::io::Result<Vec<ProjectApprovalRule>> {
+        self.project_approvals()
+            .list_rules(&ProjectScope::resolve(cwd).await)
+            .await
+    }
+
+    /// Stop auto-approving commands matching `rule` in the project containing
+    /// `cwd`. Returns whether the rule was granted.
+    pub async fn revoke_project_approval_rule(
+        &self,
+        cwd: &Path,
+        rule: &ProjectApprovalRule,
+    ) -> std::io::Result<bool> {
+        self.project_approvals()
+            .revoke(&ProjectScope::resolve(cwd).await, rule)
+            .await
+    }
+
+    /// The notes saved for the project containing `cwd`, which sessions
+    /// working in it are told about when [`Feature::ProjectNotes`] is
+    /// enabled.
+    ///
+    /// [`Feature::ProjectNotes`]: crate::features::Feature::ProjectNotes
+    pub fn notes(&self, cwd: &Path) -> ProjectNotes {
+        ProjectNotes::new(self.shared.auth_manager.codex_home(), cwd)
+    }
+
+    fn project_approvals(&self) -> ProjectApprovalStore {
+        ProjectApprovalStore::new(self.shared.auth_manager.codex_home())
+    }
+
+    /// Session profiles defined in the `profiles` directory of codex home.
+    pub async fn list_profiles(&self) -> SessionProfiles {
+        session_profiles::list_profiles(self.shared.auth_manager.codex_home()).await
+    }
+
+    /// The `Config` for a conversation using the session profile `name`:
+    /// the user's configuration, then the profile, then `overrides`.
+    pub async fn config_for_profile(
+        &self,
+        name: &str,
+        overrides: ConfigOverrides,
+    ) -> CodexResult<Config> {
+        let codex_home = self.shared.auth_manager.codex_home();
+        let profile = session_profiles::load_profile(codex_home, name).await?;
+        let mut config = ConfigBuilder::default()
+            .codex_home(codex_home.to_path_buf())
+            .cli_overrides(profile.config_overrides())
+            .harness_overrides(overrides)
+            .build()
+            .await?;
+        config.session_profile = Some(profile.name);
+        Ok(config)
+    }
+
+    /// Start a conversation configured by the session profile `name`, see
+    /// [`Self::config_for_profile`]. The profile name is recorded in the
+    /// rollout, so resumed and forked conversations report it too.
+    pub async fn new_conversation_with_profile(
+        &self,
+        name: &str,
+        overrides: ConfigOverrides,
+    ) -> CodexResult<NewConversation> {
+        let config = self.config_for_profile(name, overrides).await?;
+        self.new_conversation(config).await
     }
 
     pub async fn new_conversation(&self, config: Config) -> CodexResult<NewConversation> {
-        self.spawn_conversation(
-            config,
-            self.auth_manager.clone(),
-            self.models_manager.clone(),
-        )
-        .await
+        self.shared
+            .spawn(
+                config,
+                self.shared.auth_manager.clone(),
+                InitialHistory::New,
+                self.shared.pre_processors(),
+                self.shared.post_processors(),
+                None,
+            )
+            .await
     }
 
-    async fn spawn_conversation(
+    /// Like [`Self::new_conversation`], telling `observer` about each step of
+    /// the spawn as it happens, see [`crate::spawn_observer`].
+    pub async fn new_conversation_with_observer(
         &self,
         config: Config,
-        auth_manager: Arc<AuthManager>,
-        models_manager: Arc<ModelsManager>,
+        observer: Arc<dyn SpawnObserver>,
     ) -> CodexResult<NewConversation> {
-        let CodexSpawnOk {
-            codex,
+        self.shared
+            .spawn_observed(
+                config,
+                self.shared.auth_manager.clone(),
+                InitialHistory::New,
+                self.shared.pre_processors(),
+                self.shared.post_processors(),
+                None,
+                SpawnProgress::new(observer),
+            )
+            .await
+    }
+
+    /// Start a conversation from `config` and submit the prompts of
+    /// `script` to it one turn at a time, see [`crate::script_run`]. The
+    /// conversation is shut down once the script has run, so its rollout is
+    /// complete when the report is returned. Only a failed spawn fails the
+    /// run; a step that fails is reported in its outcome.
+    pub async fn run_script(
+        &self,
+        script: &PromptScript,
+        config: Config,
+        options: ScriptRunOptions,
+    ) -> CodexResult<ScriptRunReport> {
+        let NewConversation {
             conversation_id,
-        } = Codex::spawn(
-            config,
-            auth_manager,
-            models_manager,
-            self.skills_manager.clone(),
-            InitialHistory::New,
-            self.session_source.clone(),
-        )
-        .await?;
-        self.finalize_spawn(codex, conversation_id).await
+            conversation,
+            ..
+        } = self.new_conversation(config).await?;
+        let steps = run_prompts(&conversation, script, &options).await;
+        if let Err(err) = conversation.end_session(SessionEndReason::Shutdown).await {
+            warn!("conversation {conversation_id} did not end cleanly: {err}");
+        }
+        self.shared.remove_conversation(conversation_id).await;
+        Ok(ScriptRunReport {
+            conversation_id,
+            rollout_path: conversation.rollout_path(),
+            steps,
+        })
     }
 
-    async fn finalize_spawn(
+    /// Start a conversation from the manager's default config, see
+    /// [`Self::with_default_config`].
+    pub async fn new_conversation_default(&self) -> CodexResult<NewConversation> {
+        self.new_conversation_with(|_| {}).await
+    }
+
+    /// Start a conversation from a copy of the manager's default config
+    /// changed by `overrides`. The result goes through the same checks as a
+    /// config passed to [`Self::new_conversation`].
+    pub async fn new_conversation_with(
         &self,
-        codex: Codex,
-        conversation_id: ConversationId,
+        overrides: impl FnOnce(&mut Config),
     ) -> CodexResult<NewConversation> {
-        // The first event must be `SessionInitialized`. Validate and forward it
-        // to the caller so that they can display it in the conversation
-        // history.
-        let event = codex.next_event().await?;
-        let session_configured = match event {
-            Event {
-                id,
-                msg: EventMsg::SessionConfigured(session_configured),
-            } if id == INITIAL_SUBMIT_ID => session_configured,
-            _ => {
-                return Err(CodexErr::SessionConfiguredNotFirstEvent);
-            }
-        };
+        let mut config = self
+            .shared
+            .default_config
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| {
+                CodexErr::UnsupportedOperation(
+                    "the conversation manager has no default config".to_string(),
+                )
+            })?;
+        overrides(&mut config);
+        self.new_conversation(config).await
+    }
 
-        let conversation = Arc::new(CodexConversation::new(
-            codex,
-            session_configured.rollout_path.clone(),
-        ));
-        self.conversations
-            .write()
+    /// Like [`Self::new_conversation`], additionally running `post_processors`
+    /// in order after the manager's own on every turn of this conversation.
+    pub async fn new_conversation_with_post_processors(
+        &self,
+        config: Config,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+    ) -> CodexResult<NewConversation> {
+        let post_processors = self
+            .shared
+            .post_processors()
+            .into_iter()
+            .chain(post_processors)
+            .collect();
+        self.shared
+            .spawn(
+                config,
+                self.shared.auth_manager.clone(),
+                InitialHistory::New,
+                self.shared.pre_processors(),
+                post_processors,
+                None,
+            )
             .await
-            .insert(conversation_id, conversation.clone());
+    }
 
-        Ok(NewConversation {
-            conversation_id,
-            conversation,
-            session_configured,
-        })
+    /// Like [`Self::new_conversation`], additionally running `pre_processors`
+    /// in order after the manager's own on every input of this conversation.
+    pub async fn new_conversation_with_pre_processors(
+        &self,
+        config: Config,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+    ) -> CodexResult<NewConversation> {
+        let pre_processors = self
+            .shared
+            .pre_processors()
+            .into_iter()
+            .chain(pre_processors)
+            .collect();
+        self.shared
+            .spawn(
+                config,
+                self.shared.auth_manager.clone(),
+                InitialHistory::New,
+                pre_processors,
+                self.shared.post_processors(),
+                None,
+            )
+            .await
     }
 
     pub async fn get_conversation(
         &self,
         conversation_id: ConversationId,
     ) -> CodexResult<Arc<CodexConversation>> {
-        let conversations = self.conversations.read().await;
-        conversations
-            .get(&conversation_id)
-            .cloned()
-            .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id))
+        self.shared.get_conversation(conversation_id).await
     }
 
+    /// Resume the conversation recorded at `rollout_path`. It keeps the id
+    /// recorded in the rollout and appends to the same file; while that id is
+    /// running in this manager, resuming fails with
+    /// [`CodexErr::ConversationAlreadyExists`].
     pub async fn resume_conversation_from_rollout(
         &self,
         config: Config,
@@ -190,54 +731,570 @@ impl ConversationManager {
             .await
     }
 
-    pub async fn resume_conversation_with_history(
+    /// Load the rollout at `rollout_path` for inspection. Nothing is spawned
+    /// and the manager's auth is not used, so this works without credentials.
+    pub async fn open_readonly(&self, rollout_path: &Path) -> CodexResult<ReadOnlyTranscript> {
+        Ok(ReadOnlyTranscript::open(rollout_path).await?)
+    }
+
+    /// Resume the session `transcript` was opened from, starting from the
+    /// history it loaded.
+    pub async fn resume_read_only(
         &self,
+        transcript: ReadOnlyTranscript,
         config: Config,
-        initial_history: InitialHistory,
         auth_manager: Arc<AuthManager>,
     ) -> CodexResult<NewConversation> {
-        let CodexSpawnOk {
-            codex,
-            conversation_id,
-        } = Codex::spawn(
+        self.resume_conversation_with_history(config, transcript.history().clone(), auth_manager)
+            .await
+    }
+
+    /// The session whose rollout was written last among those started in the
+    /// project containing `cwd`: the same git repository on any branch or
+    /// worktree, or the same directory outside git.
+    pub async fn latest_session_for_cwd(&self, cwd: &Path) -> Option<SessionSummary> {
+        match latest_session_for_cwd(self.shared.auth_manager.codex_home(), cwd).await {
+            Ok(summary) => summary,
+            Err(err) => {
+                warn!("failed to scan sessions for {}: {err}", cwd.display());
+                None
+            }
+        }
+    }
+
+    /// Resume [`Self::latest_session_for_cwd`], failing with
+    /// [`CodexErr::NoSessionForCwd`] when there is none.
+    pub async fn resume_latest_for_cwd(
+        &self,
+        config: Config,
+        cwd: &Path,
+    ) -> CodexResult<NewConversation> {
+        let Some(summary) = self.latest_session_for_cwd(cwd).await else {
+            return Err(CodexErr::NoSessionForCwd(cwd.to_path_buf()));
+        };
+        self.resume_conversation_from_rollout(
             config,
-            auth_manager,
-            self.models_manager.clone(),
-            self.skills_manager.clone(),
-            initial_history,
-            self.session_source.clone(),
+            summary.path,
+            self.shared.auth_manager.clone(),
         )
-        .await?;
-        self.finalize_spawn(codex, conversation_id).await
+        .await
+    }
+
+    pub async fn resume_conversation_with_history(
+        &self,
+        config: Config,
+        initial_history: InitialHistory,
+        auth_manager: Arc<AuthManager>,
+    ) -> CodexResult<NewConversation> {
+        let links = recorded_links(&initial_history);
+        let conversation = self
+            .shared
+            .spawn(
+                config,
+                auth_manager,
+                initial_history,
+                self.shared.pre_processors(),
+                self.shared.post_processors(),
+                None,
+            )
+            .await?;
+        self.shared.restore_links(links).await;
+        Ok(conversation)
     }
 
     /// Removes the conversation from the manager's internal map, though the
     /// conversation is stored as `Arc<CodexConversation>`, it is possible that
-    /// other references to it exist elsewhere. Returns the conversation if the
-    /// conversation was found and removed.
+    /// other references to it exist elsewhere. Returns the conversation, with
+    /// whether a turn was running and how many references remain, if the
+    /// conversation was found and removed. Its rollout is flushed first, on a
+    /// best-effort basis.
+    ///
+    /// Children spawned by the conversation are removed with it, recursively,
+    /// unless they were spawned to outlive it.
     pub async fn remove_conversation(
         &self,
         conversation_id: &ConversationId,
-    ) -> Option<Arc<CodexConversation>> {
-        self.conversations.write().await.remove(conversation_id)
+    ) -> Option<RemovedConversation> {
+        let conversation = self.shared.remove_conversation(*conversation_id).await?;
+        let was_turn_in_flight = conversation
+            .load()
+            .is_some_and(ConversationLoad::turn_in_flight);
+        let rollout_flushed = match conversation.live_rollout() {
+            Some(live_rollout) => match live_rollout.flush().await {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!("failed to flush the rollout of conversation {conversation_id}: {err}");
+                    false
+                }
+            },
+            None => false,
+        };
+        Some(removed_conversation(
+            conversation,
+            was_turn_in_flight,
+            rollout_flushed,
+            None,
+        ))
+    }
+
+    /// Like [`Self::remove_conversation`], but shut the conversation down
+    /// first and wait up to `timeout` for its final
+    /// [`EventMsg::SessionEnded`], interrupting a running turn. Once the
+    /// session ended its rollout is complete: nothing is written to it after
+    /// the end record. A session that does not end in time is removed all the
+    /// same, without [`RemovedConversation::session_ended`].
+    pub async fn remove_conversation_gracefully(
+        &self,
+        conversation_id: &ConversationId,
+        timeout: Duration,
+    ) -> Option<RemovedConversation> {
+        let conversation = self.get_conversation(*conversation_id).await.ok()?;
+        let was_turn_in_flight = conversation
+            .load()
+            .is_some_and(ConversationLoad::turn_in_flight);
+        let session_ended = match tokio::time::timeout(
+            timeout,
+            conversation.end_session(SessionEndReason::Shutdown),
+        )
+        .await
+        {
+            Ok(Ok(ended)) => Some(ended),
+            Ok(Err(err)) => {
+                warn!("conversation {conversation_id} did not end cleanly: {err}");
+                None
+            }
+            Err(_) => {
+                warn!("conversation {conversation_id} did not end within {timeout:?}");
+                None
+            }
+        };
+        self.shared.remove_conversation(*conversation_id).await;
+        // The session flushed its rollout before it reported the end.
+        let rollout_flushed = session_ended.is_some();
+        Some(removed_conversation(
+            conversation,
+            was_turn_in_flight,
+            rollout_flushed,
+            session_ended,
+        ))
+    }
+
+    /// Shut the conversation down, stop tracking it like
+    /// [`Self::remove_conversation`], and delete its rollout from disk: every
+    /// part, the event log and other files named after it, and the blobs no
+    /// other rollout refers to. See [`crate::rollout::delete`].
+    ///
+    /// Fails with [`CodexErr::ForkBaseInUse`], before shutting anything
+    /// down, while [`Self::children_of`] lists forks of the conversation,
+    /// unless `force` is set. Rollouts of children removed with it are kept.
+    pub async fn remove_conversation_and_delete(
+        &self,
+        conversation_id: ConversationId,
+        force: bool,
+    ) -> CodexResult<DeletionReport> {
+        let conversation = self.get_conversation(conversation_id).await?;
+        if !force {
+            let forks = self.children_of(conversation_id).await;
+            if !forks.is_empty() {
+                return Err(CodexErr::ForkBaseInUse {
+                    conversation_id,
+                    forks: forks.iter().map(|fork| fork.conversation_id).collect(),
+                });
+            }
+        }
+        if let Err(err) = conversation.end_session(SessionEndReason::Shutdown).await {
+            warn!("conversation {conversation_id} did not end cleanly: {err}");
+        }
+        self.shared.remove_conversation(conversation_id).await;
+        let codex_home = self.shared.auth_manager.codex_home();
+        Ok(delete_rollout(codex_home, &conversation.rollout_path()).await?)
+    }
+
+    /// End every conversation this manager tracks and stop tracking them.
+    /// Each conversation is asked to shut down with
+    /// [`SessionEndReason::ManagerShutdown`] and sends its final
+    /// [`EventMsg::SessionEnded`] to its readers before it is removed. Returns
+    /// the session-ended events of the conversations that ended cleanly.
+    pub async fn shutdown_all(&self) -> HashMap<ConversationId, SessionEndedEvent> {
+        let conversations: Vec<(ConversationId, Arc<CodexConversation>)> = self
+            .shared
+            .conversations
+            .read()
+            .await
+            .iter()
+            .map(|(id, conversation)| (*id, Arc::clone(conversation)))
+            .collect();
+        let ended = join_all(conversations.iter().map(|(id, conversation)| async move {
+            (
+                *id,
+                conversation
+                    .end_session(SessionEndReason::ManagerShutdown)
+                    .await,
+            )
+        }))
+        .await;
+
+        let mut events = HashMap::new();
+        for (id, result) in ended {
+            match result {
+                Ok(event) => {
+                    events.insert(id, event);
+                }
+                Err(err) => warn!("conversation {id} did not end cleanly: {err}"),
+            }
+            self.shared.remove_conversation(id).await;
+        }
+        events
     }
 
     /// Fork an existing conversation by taking messages up to the given position
     /// (not including the message at the given position) and starting a new
     /// conversation with identical configuration (unless overridden by the
-    /// caller's `config`). The new conversation will have a fresh id.
+    /// caller's `config`). The new conversation will have a fresh id. Fails
+    /// with [`CodexErr::ForkPointOutOfRange`] if the conversation has no user
+    /// message at that position.
+    ///
+    /// If `path` belongs to a conversation that is still running, the fork
+    /// ends at its last completed turn; see [`Self::fork_conversation_by_id`].
+    ///
+    /// Pins do not protect items from the cut: pinned items after the fork
+    /// point are left out like any other, and only the pins of items the fork
+    /// keeps carry over.
+    ///
+    /// The fork's session meta names the conversation it was cut from and
+    /// where, its first event after `SessionConfigured` is
+    /// [`EventMsg::Forked`], and [`Self::children_of`] lists it.
     pub async fn fork_conversation(
         &self,
         nth_user_message: usize,
         config: Config,
         path: PathBuf,
     ) -> CodexResult<NewConversation> {
+        let history = self.history_for_fork(&path).await?;
+        self.fork_from_history(history, ForkCut::UserMessage(nth_user_message), config)
+            .await
+    }
+
+    /// Like [`Self::fork_conversation`], but cuts before the start of turn
+    /// `turn_id`, as reported by [`EventMsg::TaskStarted`]. The id names the
+    /// same turn however the history was compacted or rolled back since.
+    /// Fails with [`CodexErr::UnknownTurn`] if the rollout does not record
+    /// that turn, rather than guessing a cut.
+    pub async fn fork_conversation_at_turn(
+        &self,
+        turn_id: TurnId,
+        config: Config,
+        path: PathBuf,
+    ) -> CodexResult<NewConversation> {
+        let history = self.history_for_fork(&path).await?;
+        self.fork_from_history(history, ForkCut::Turn(turn_id), config)
+            .await
+    }
+
+    /// The history of the rollout at `path`, up to the last completed turn if
+    /// it belongs to a conversation that is still running.
+    async fn history_for_fork(&self, path: &Path) -> CodexResult<InitialHistory> {
+        let live_rollout = {
+            let conversations = self.shared.conversations.read().await;
+            conversations
+                .values()
+                .filter_map(|conversation| conversation.live_rollout())
+                .find(|live_rollout| live_rollout.rollout_path() == path)
+                .cloned()
+        };
+        Ok(match live_rollout {
+            Some(live_rollout) => live_rollout.snapshot(false).await?,
+            None => RolloutRecorder::get_rollout_history(path).await?,
+        })
+    }
+
+    /// Fork a conversation tracked by this manager. The source may be
+    /// streaming a turn: the fork never includes that turn's partial items.
+    /// With `wait_for_turn` the fork waits for the turn to finish and
+    /// includes it; otherwise it ends at the last completed turn.
+    pub async fn fork_conversation_by_id(
+        &self,
+        conversation_id: ConversationId,
+        nth_user_message: usize,
+        config: Config,
+        wait_for_turn: bool,
+    ) -> CodexResult<NewConversation> {
+        let conversation = self.get_conversation(conversation_id).await?;
+        let history = match conversation.live_rollout() {
+            Some(live_rollout) => live_rollout.snapshot(wait_for_turn).await?,
+            None => RolloutRecorder::get_rollout_history(&conversation.rollout_path()).await?,
+        };
+        self.fork_from_history(history, ForkCut::UserMessage(nth_user_message), config)
+            .await
+    }
+
+    async fn fork_from_history(
+        &self,
+        history: InitialHistory,
+        cut: ForkCut,
+        config: Config,
+    ) -> CodexResult<NewConversation> {
+        let source = match &history {
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                Some((resumed.conversation_id, resumed.rollout_path.clone()))
+            }
+            InitialHistory::New | InitialHistory::Forked(_) => None,
+        };
+
         // Compute the prefix up to the cut point.
-        let history = RolloutRecorder::get_rollout_history(&path).await?;
-        let history = truncate_before_nth_user_message(history, nth_user_message);
+        let (mut history, cut_ordinal) = match cut {
+            ForkCut::UserMessage(n) => (try_truncate_before_nth_user_message(history, n)?, n),
+            ForkCut::Turn(turn_id) => {
+                let history =
+                    truncate_rollout_before_turn_id(history.get_rollout_items(), turn_id)?;
+                // The user messages the fork keeps, so that forking at the
+                // ordinal again keeps the same history.
+                let ordinal = user_message_positions(&history.get_rollout_items()).len();
+                (history, ordinal)
+            }
+        };
+        let fork_consistency = match &mut history {
+            InitialHistory::Forked(items) if config.fork_consistency_check => {
+                let report = fork_consistency::check(items, &config.cwd).await;
+                if report.has_diverged() && config.fork_stale_notice {
+                    items.push(RolloutItem::ResponseItem(fork_consistency::stale_notice(
+                        &report,
+                    )));
+                }
+                Some(report)
+            }
+            _ => None,
+        };
+        let parent = source.map(|(conversation_id, rollout_path)| ParentRef {
+            conversation_id,
+            rollout_path,
+            cut_ordinal,
+            stale_context: fork_consistency
+                .clone()
+                .filter(ForkConsistencyReport::has_diverged),
+        });
 
         // Spawn a new conversation with the computed initial history.
-        let auth_manager = self.auth_manager.clone();
+        let mut forked = self
+            .shared
+            .spawn(
+                config,
+                self.shared.auth_manager.clone(),
+                history,
+                self.shared.pre_processors(),
+                self.shared.post_processors(),
+                parent.clone(),
+            )
+            .await?;
+        if let Some(parent) = parent {
+            self.shared.forks.write().await.insert(
+                forked.conversation_id,
+                ForkedChild {
+                    conversation_id: forked.conversation_id,
+                    path: forked.session_configured.rollout_path.clone(),
+                    parent,
+                },
+            );
+        }
+        forked.fork_consistency = fork_consistency;
+        Ok(forked)
+    }
+
+    pub async fn list_models(&self, config: &Config) -> Vec<ModelPreset> {
+        self.shared.models_manager.list_models(config).await
+    }
+
+    /// Fetch the model list again, see [`ModelsManager::refresh_models`].
+    pub async fn refresh_models(&self, config: &Config) -> Vec<ModelPreset> {
+        self.shared.models_manager.refresh_models(config).await
+    }
+
+    /// The model list, kept current for pickers, see
+    /// [`ModelsManager::subscribe`]. It is fetched again every
+    /// `model_list_refresh_interval` while it has subscribers, and a
+    /// conversation whose model is removed from it reports
+    /// [`crate::protocol::EventMsg::ModelDeprecated`].
+    pub fn subscribe_models(&self) -> watch::Receiver<Arc<Vec<ModelPreset>>> {
+        self.shared.models_manager.subscribe()
+    }
+
+    pub fn get_models_manager(&self) -> Arc<ModelsManager> {
+        self.shared.models_manager.clone()
+    }
+}
+
+impl ManagerShared {
+    fn new(
+        auth_manager: Arc<AuthManager>,
+        models_manager: Arc<ModelsManager>,
+        skills_manager: Arc<SkillsManager>,
+        session_source: SessionSource,
+    ) -> Self {
+        Self {
+            conversations: RwLock::new(HashMap::new()),
+            parents: RwLock::new(HashMap::new()),
+            forks: RwLock::new(HashMap::new()),
+            resuming: std::sync::Mutex::new(HashSet::new()),
+            max_conversations: AtomicUsize::new(usize::MAX),
+            auth_manager,
+            models_manager,
+            skills_manager,
+            session_source,
+            failed_spawns: FailedSpawnCounters::default(),
+            load: Arc::default(),
+            token_counter: std::sync::RwLock::new(Arc::new(ApproxTokenCounter)),
+            runtime_env: std::sync::RwLock::new(RuntimeEnv::default()),
+            default_config: std::sync::RwLock::new(None),
+            pre_processors: std::sync::RwLock::new(Vec::new()),
+            post_processors: std::sync::RwLock::new(Vec::new()),
+            #[cfg(feature = "metrics")]
+            metrics: Arc::default(),
+            codex_home: None,
+            #[cfg(any(test, feature = "test-support"))]
+            _test_codex_home_guard: None,
+            #[cfg(feature = "test-support")]
+            rollout_faults: std::sync::Mutex::new(None),
+        }
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    fn for_testing(auth: CodexAuth, provider: ModelProviderInfo, codex_home: PathBuf) -> Self {
+        let auth_manager = crate::AuthManager::from_auth_for_testing_with_home(auth, codex_home);
+        let skills_manager = Arc::new(SkillsManager::new(auth_manager.home().clone()));
+        let models_manager = Arc::new(ModelsManager::with_provider(auth_manager.clone(), provider));
+        Self::new(
+            auth_manager,
+            models_manager,
+            skills_manager,
+            SessionSource::Exec,
+        )
+    }
+
+    fn token_counter(&self) -> Arc<dyn TokenCounter> {
+        self.token_counter
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    fn runtime_env(&self) -> RuntimeEnv {
+        self.runtime_env
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    fn pre_processors(&self) -> Vec<Arc<dyn TurnPreProcessor>> {
+        self.pre_processors
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    fn post_processors(&self) -> Vec<Arc<dyn TurnPostProcessor>> {
+        self.post_processors
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    async fn spawn(
+        self: &Arc<Self>,
+        config: Config,
+        auth_manager: Arc<AuthManager>,
+        initial_history: InitialHistory,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        parent: Option<ParentRef>,
+    ) -> CodexResult<NewConversation> {
+        self.spawn_observed(
+            config,
+            auth_manager,
+            initial_history,
+            pre_processors,
+            post_processors,
+            parent,
+            SpawnProgress::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn spawn_observed(
+        self: &Arc<Self>,
+        config: Config,
+        auth_manager: Arc<AuthManager>,
+        initial_history: InitialHistory,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        parent: Option<ParentRef>,
+        progress: SpawnProgress,
+    ) -> CodexResult<NewConversation> {
+        #[cfg(feature = "metrics")]
+        let kind = SpawnKind::of(&initial_history);
+        let result = self
+            .start_conversation(
+                config,
+                auth_manager,
+                initial_history,
+                pre_processors,
+                post_processors,
+                parent,
+                progress,
+            )
+            .await;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_spawn(kind, &result);
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn start_conversation(
+        self: &Arc<Self>,
+        mut config: Config,
+        auth_manager: Arc<AuthManager>,
+        initial_history: InitialHistory,
+        pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+        post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+        parent: Option<ParentRef>,
+        progress: SpawnProgress,
+    ) -> CodexResult<NewConversation> {
+        let max_conversations = self.max_conversations.load(Ordering::Relaxed);
+        if self.conversations.read().await.len() >= max_conversations {
+            return Err(CodexErr::ConversationLimitReached(max_conversations));
+        }
+        if let Some(codex_home) = &self.codex_home
+            && config.codex_home != *codex_home
+        {
+            return Err(CodexErr::InvalidRequest(format!(
+                "config is for codex home {}, this manager serves {}",
+                config.codex_home.display(),
+                codex_home.display()
+            )));
+        }
+        // The session meta and approval scopes record this canonical path.
+        config.cwd = resolve_working_directory(&config.cwd, config.create_cwd_if_missing)?;
+        // Held until the resumed conversation is tracked, so a second resume
+        // of the same rollout fails instead of writing to it too.
+        let _resuming = match &initial_history {
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                Some(self.reserve_resumed_id(resumed.conversation_id).await?)
+            }
+            InitialHistory::New | InitialHistory::Forked(_) => None,
+        };
+
+        let children = ChildSpawner {
+            manager: Arc::downgrade(self),
+            config: config.clone(),
+            pre_processors: pre_processors.clone(),
+            post_processors: post_processors.clone(),
+        };
+        // A resumed rollout belongs to an earlier session and must survive a
+        // failed resume.
+        let owns_rollout = !matches!(
+            initial_history,
+            InitialHistory::Resumed(_) | InitialHistory::ResumedEmpty(_)
+        );
         let CodexSpawnOk {
             codex,
             conversation_id,
@@ -246,166 +1303,536 @@ impl ConversationManager {
             auth_manager,
             self.models_manager.clone(),
             self.skills_manager.clone(),
-            history,
+            initial_history,
             self.session_source.clone(),
+            pre_processors,
+            post_processors,
+            self.token_counter(),
+            self.runtime_env(),
+            parent,
+            progress.clone(),
         )
         .await?;
-
-        self.finalize_spawn(codex, conversation_id).await
+        self.finalize_spawn(codex, conversation_id, children, owns_rollout, progress)
+            .await
     }
 
-    pub async fn list_models(&self, config: &Config) -> Vec<ModelPreset> {
-        self.models_manager.list_models(config).await
-    }
+    async fn finalize_spawn(
+        &self,
+        codex: Codex,
+        conversation_id: ConversationId,
+        children: ChildSpawner,
+        owns_rollout: bool,
+        progress: SpawnProgress,
+    ) -> CodexResult<NewConversation> {
+        // The first event must be `SessionInitialized`. Validate and forward it
+        // to the caller so that they can display it in the conversation
+        // history.
+        let event = match codex.next_event().await {
+            Ok(event) => event,
+            Err(err) => {
+                self.discard_failed_spawn(codex, conversation_id, owns_rollout)
+                    .await;
+                progress.failed(conversation_id, &err);
+                return Err(err);
+            }
+        };
+        let session_configured = match &event {
+            Event {
+                id,
+                msg: EventMsg::SessionConfigured(session_configured),
+                ..
+            } if id == INITIAL_SUBMIT_ID => session_configured.clone(),
+            Event { msg, .. } => {
+                let got = Box::new(msg.clone());
+                self.discard_failed_spawn(codex, conversation_id, owns_rollout)
+                    .await;
+                let err = CodexErr::SessionConfiguredNotFirstEvent { got };
+                progress.failed(conversation_id, &err);
+                return Err(err);
+            }
+        };
 
-    pub fn get_models_manager(&self) -> Arc<ModelsManager> {
-        self.models_manager.clone()
+        // Replay starts with `SessionConfigured`, which the caller receives
+        // here rather than from `next_event`.
+        let replay = EventReplayBuffer::new(children.config.event_replay.clone());
+        replay.push(event);
+        let conversation = CodexConversation::new(
+            codex,
+            conversation_id,
+            session_configured.rollout_path.clone(),
+            children,
+            replay,
+        );
+        #[cfg(feature = "metrics")]
+        let conversation =
+            conversation.with_metrics(ConversationMetrics::new(Arc::clone(&self.metrics)));
+        let conversation = Arc::new(conversation);
+        let mut conversations = self.conversations.write().await;
+        conversations.insert(conversation_id, conversation.clone());
+        if let Some(load) = conversation.load() {
+            load.attach(Arc::clone(&self.load));
+        }
+        drop(conversations);
+        #[cfg(feature = "metrics")]
+        self.metrics.conversation_added();
+        progress.report(conversation_id, SpawnMilestone::SessionConfigured);
+
+        Ok(NewConversation {
+            conversation_id,
+            conversation,
+            session_configured,
+            fork_consistency: None,
+        })
     }
-}
 
-/// Return a prefix of `items` obtained by cutting strictly before the nth user message
-/// (0-based) and all items that follow it.
-fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> InitialHistory {
-    // Work directly on rollout items, and cut the vector at the nth user message input.
-    let items: Vec<RolloutItem> = history.get_rollout_items();
-
-    // Find indices of user message inputs in rollout order.
-    let mut user_positions: Vec<usize> = Vec::new();
-    for (idx, item) in items.iter().enumerate() {
-        if let RolloutItem::ResponseItem(item @ ResponseItem::Message { .. }) = item
-            && matches!(
-                crate::event_mapping::parse_turn_item(item),
-                Some(TurnItem::UserMessage(_))
-            )
+    /// Shut down a session that never reached the caller, aborting it if it
+    /// does not stop in time, and remove the rollout it created so nothing
+    /// keeps running or lingers on disk without a handle to it.
+    async fn discard_failed_spawn(
+        &self,
+        codex: Codex,
+        conversation_id: ConversationId,
+        owns_rollout: bool,
+    ) {
+        self.failed_spawns.discarded.fetch_add(1, Ordering::Relaxed);
+        let shutdown = async {
+            codex.submit(Op::Shutdown).await?;
+            while !matches!(codex.next_event().await?.msg, EventMsg::ShutdownComplete) {}
+            CodexResult::Ok(())
+        };
+        // An error means the session already stopped.
+        if tokio::time::timeout(FAILED_SPAWN_SHUTDOWN_TIMEOUT, shutdown)
+            .await
+            .is_err()
         {
-            user_positions.push(idx);
+            warn!("session {conversation_id} failed to start and did not shut down; aborting it");
+            self.failed_spawns.aborted.fetch_add(1, Ordering::Relaxed);
+            if let Some(session_task) = &codex.session_task {
+                session_task.abort();
+            }
+        }
+
+        let rollout_path = codex
+            .live_rollout
+            .as_ref()
+            .map(|live_rollout| live_rollout.rollout_path().to_path_buf());
+        drop(codex);
+        if let Some(rollout_path) = rollout_path.filter(|_| owns_rollout) {
+            match tokio::fs::remove_file(&rollout_path).await {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    warn!(
+                        "failed to remove rollout {} of session {conversation_id} that failed to start: {err}",
+                        rollout_path.display()
+                    );
+                    self.failed_spawns
+                        .leaked_rollouts
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+            }
         }
+        warn!(
+            "discarded session {conversation_id} that failed to start ({:?})",
+            self.failed_spawn_stats()
+        );
     }
 
-    // If fewer than or equal to n user messages exist, treat as empty (out of range).
-    if user_positions.len() <= n {
-        return InitialHistory::New;
+    /// Claim `conversation_id` for a resume, failing with
+    /// [`CodexErr::ConversationAlreadyExists`] while another conversation
+    /// holds it.
+    async fn reserve_resumed_id(
+        &self,
+        conversation_id: ConversationId,
+    ) -> CodexResult<ResumeReservation<'_>> {
+        // Checked under the read lock so a resume finishing meanwhile is
+        // seen either as tracked or as still reserved.
+        let conversations = self.conversations.read().await;
+        let mut resuming = self
+            .resuming
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if conversations.contains_key(&conversation_id) || !resuming.insert(conversation_id) {
+            return Err(CodexErr::ConversationAlreadyExists(conversation_id));
+        }
+        Ok(ResumeReservation {
+            resuming: &self.resuming,
+            conversation_id,
+        })
     }
 
-    // Cut strictly before the nth user message (do not keep the nth itself).
-    let cut_idx = user_positions[n];
-    let rolled: Vec<RolloutItem> = items.into_iter().take(cut_idx).collect();
+    fn failed_spawn_stats(&self) -> FailedSpawnStats {
+        FailedSpawnStats {
+            discarded: self.failed_spawns.discarded.load(Ordering::Relaxed),
+            aborted: self.failed_spawns.aborted.load(Ordering::Relaxed),
+            leaked_rollouts: self.failed_spawns.leaked_rollouts.load(Ordering::Relaxed),
+        }
+    }
 
-    if rolled.is_empty() {
-        InitialHistory::New
-    } else {
-        InitialHistory::Forked(rolled)
+    async fn get_conversation(
+        &self,
+        conversation_id: ConversationId,
+    ) -> CodexResult<Arc<CodexConversation>> {
+        let conversations = self.conversations.read().await;
+        conversations
+            .get(&conversation_id)
+            .cloned()
+            .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::codex::make_session_and_context;
-    use assert_matches::assert_matches;
-    use codex_protocol::models::ContentItem;
-    use codex_protocol::models::ReasoningItemReasoningSummary;
-    use codex_protocol::models::ResponseItem;
-    use pretty_assertions::assert_eq;
+    async fn lineage(&self, conversation_id: ConversationId) -> ConversationLineage {
+        let parents = self.parents.read().await;
+        let mut children: Vec<ConversationId> = parents
+            .iter()
+            .filter(|(_, link)| link.parent_id == conversation_id)
+            .map(|(child_id, _)| *child_id)
+            .collect();
+        // Conversation ids are time-ordered, so this is spawn order.
+        children.sort_by_key(ToString::to_string);
+        ConversationLineage {
+            parent: parents.get(&conversation_id).map(|link| link.parent_id),
+            children,
+        }
+    }
 
-    fn user_msg(text: &str) -> ResponseItem {
-        ResponseItem::Message {
-            id: None,
-            role: "user".to_string(),
-            content: vec![ContentItem::OutputText {
-                text: text.to_string(),
-            }],
+    /// Track links recorded in a resumed rollout. Whether a child was spawned
+    /// to outlive its parent is not recorded, so restored children are never
+    /// removed implicitly.
+    async fn restore_links(&self, links: Vec<ConversationLink>) {
+        let mut parents = self.parents.write().await;
+        for link in links {
+            parents.entry(link.child_id).or_insert(ChildLink {
+                parent_id: link.parent_id,
+                remove_with_parent: false,
+            });
         }
     }
-    fn assistant_msg(text: &str) -> ResponseItem {
-        ResponseItem::Message {
-            id: None,
-            role: "assistant".to_string(),
-            content: vec![ContentItem::OutputText {
-                text: text.to_string(),
-            }],
+
+    /// Stop tracking `conversation_id`, and counting it in the load.
+    async fn untrack(&self, conversation_id: ConversationId) -> Option<Arc<CodexConversation>> {
+        let mut conversations = self.conversations.write().await;
+        let removed = conversations.remove(&conversation_id);
+        if let Some(conversation) = &removed
+            && let Some(load) = conversation.load()
+        {
+            load.detach();
         }
+        removed
     }
 
-    #[test]
-    fn drops_from_last_user_only() {
-        let items = [
-            user_msg("u1"),
-            assistant_msg("a1"),
-            assistant_msg("a2"),
-            user_msg("u2"),
-            assistant_msg("a3"),
-            ResponseItem::Reasoning {
-                id: "r1".to_string(),
-                summary: vec![ReasoningItemReasoningSummary::SummaryText {
-                    text: "s".to_string(),
-                }],
-                content: None,
-                encrypted_content: None,
-            },
-            ResponseItem::FunctionCall {
-                id: None,
-                name: "tool".to_string(),
-                arguments: "{}".to_string(),
-                call_id: "c1".to_string(),
-            },
-            assistant_msg("a4"),
-        ];
+    async fn remove_conversation(
+        &self,
+        conversation_id: ConversationId,
+    ) -> Option<Arc<CodexConversation>> {
+        let removed = self.untrack(conversation_id).await;
+        self.forks.write().await.remove(&conversation_id);
+        #[cfg(feature = "metrics")]
+        if removed.is_some() {
+            self.metrics.conversation_removed();
+        }
 
-        // Wrap as InitialHistory::Forked with response items only.
-        let initial: Vec<RolloutItem> = items
+        let mut pending = vec![conversation_id];
+        while let Some(parent_id) = pending.pop() {
+            let mut parents = self.parents.write().await;
+            parents.remove(&parent_id);
+            let children: Vec<(ConversationId, ChildLink)> = parents
+                .iter()
+                .filter(|(_, link)| link.parent_id == parent_id)
+                .map(|(child_id, link)| (*child_id, *link))
+                .collect();
+            for (child_id, link) in children {
+                if link.remove_with_parent {
+                    pending.push(child_id);
+                } else {
+                    parents.remove(&child_id);
+                }
+            }
+            drop(parents);
+            if parent_id != conversation_id {
+                let _removed = self.untrack(parent_id).await;
+                #[cfg(feature = "metrics")]
+                if _removed.is_some() {
+                    self.metrics.conversation_removed();
+                }
+            }
+        }
+        removed
+    }
+}
+
+/// Parent/child links recorded in a resumed rollout. Forks start a new
+/// conversation, so they do not inherit the links of their source.
+fn recorded_links(history: &InitialHistory) -> Vec<ConversationLink> {
+    match history {
+        InitialHistory::Resumed(resumed) => resumed
+            .history
             .iter()
-            .cloned()
-            .map(RolloutItem::ResponseItem)
-            .collect();
-        let truncated = truncate_before_nth_user_message(InitialHistory::Forked(initial), 1);
-        let got_items = truncated.get_rollout_items();
-        let expected_items = vec![
-            RolloutItem::ResponseItem(items[0].clone()),
-            RolloutItem::ResponseItem(items[1].clone()),
-            RolloutItem::ResponseItem(items[2].clone()),
-        ];
-        assert_eq!(
-            serde_json::to_value(&got_items).unwrap(),
-            serde_json::to_value(&expected_items).unwrap()
+            .filter_map(|item| match item {
+                RolloutItem::EventMsg(EventMsg::ConversationLinked(link)) => Some(*link),
+                _ => None,
+            })
+            .collect(),
+        InitialHistory::New | InitialHistory::ResumedEmpty(_) | InitialHistory::Forked(_) => {
+            Vec::new()
+        }
+    }
+}
+
+/// Lets a conversation spawn children through the manager that spawned it.
+/// The manager is held weakly because it owns the conversation.
+pub(crate) struct ChildSpawner {
+    manager: Weak<ManagerShared>,
+    /// The parent's configuration, which children start from.
+    config: Config,
+    pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+    post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+}
+
+impl ChildSpawner {
+    fn manager(&self) -> CodexResult<Arc<ManagerShared>> {
+        self.manager.upgrade().ok_or_else(|| {
+            CodexErr::UnsupportedOperation(
+                "the conversation manager that spawned this conversation was dropped".to_string(),
+            )
+        })
+    }
+
+    pub(crate) async fn spawn_child(
+        &self,
+        parent: &CodexConversation,
+        overrides: ChildConversationOverrides,
+        seed_prompt: String,
+    ) -> CodexResult<ConversationId> {
+        let manager = self.manager()?;
+        let parent_id = parent.conversation_id();
+        let child = manager
+            .spawn(
+                overrides.apply(self.config.clone()),
+                manager.auth_manager.clone(),
+                InitialHistory::New,
+                self.pre_processors.clone(),
+                self.post_processors.clone(),
+                None,
+            )
+            .await?;
+        let child_id = child.conversation_id;
+        manager.parents.write().await.insert(
+            child_id,
+            ChildLink {
+                parent_id,
+                remove_with_parent: !overrides.outlive_parent,
+            },
         );
 
-        let initial2: Vec<RolloutItem> = items
-            .iter()
-            .cloned()
-            .map(RolloutItem::ResponseItem)
+        let link = ConversationLink {
+            parent_id,
+            child_id,
+        };
+        parent.submit(Op::LinkConversation { link }).await?;
+        child
+            .conversation
+            .submit(Op::LinkConversation { link })
+            .await?;
+        child
+            .conversation
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text { text: seed_prompt }],
+                allow_sensitive: false,
+            })
+            .await?;
+        Ok(child_id)
+    }
+
+    pub(crate) async fn ingest_child_result(
+        &self,
+        parent: &CodexConversation,
+        child_id: ConversationId,
+    ) -> CodexResult<String> {
+        let manager = self.manager()?;
+        let is_child = manager
+            .parents
+            .read()
+            .await
+            .get(&child_id)
+            .is_some_and(|link| link.parent_id == parent.conversation_id());
+        if !is_child {
+            return Err(CodexErr::ConversationNotFound(child_id));
+        }
+
+        let child = manager.get_conversation(child_id).await?;
+        let history = match child.live_rollout() {
+            Some(live_rollout) => live_rollout.snapshot(true).await?,
+            None => RolloutRecorder::get_rollout_history(&child.rollout_path()).await?,
+        };
+        let items: Vec<ResponseItem> = history
+            .get_rollout_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                RolloutItem::ResponseItem(item) => Some(item),
+                _ => None,
+            })
             .collect();
-        let truncated2 = truncate_before_nth_user_message(InitialHistory::Forked(initial2), 2);
-        assert_matches!(truncated2, InitialHistory::New);
+        let message = get_last_assistant_message_from_turn(&items).ok_or_else(|| {
+            CodexErr::UnsupportedOperation(format!(
+                "child conversation {child_id} has not produced an answer yet"
+            ))
+        })?;
+        parent
+            .submit(Op::IngestChildResult { child_id, message })
+            .await
     }
+}
+
+/// `cwd` canonicalized, once it is known to be a readable directory. A
+/// missing directory is created first when `create_if_missing` is set.
+fn resolve_working_directory(cwd: &Path, create_if_missing: bool) -> CodexResult<PathBuf> {
+    let invalid = |reason: String| CodexErr::InvalidWorkingDirectory {
+        path: cwd.to_path_buf(),
+        reason,
+    };
+    if create_if_missing && !cwd.exists() {
+        std::fs::create_dir_all(cwd)
+            .map_err(|err| invalid(format!("could not be created: {err}")))?;
+    }
+    let path = std::fs::canonicalize(cwd).map_err(|err| {
+        invalid(match err.kind() {
+            std::io::ErrorKind::NotFound => "does not exist".to_string(),
+            _ => err.to_string(),
+        })
+    })?;
+    if !path.is_dir() {
+        return Err(invalid("is not a directory".to_string()));
+    }
+    std::fs::read_dir(&path).map_err(|err| invalid(format!("cannot be read: {err}")))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigBuilder;
+    use crate::protocol::BackgroundEventEvent;
+    use crate::rollout::RolloutRecorderParams;
+    use crate::rollout::live::LiveRollout;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::AtomicU64;
 
     #[tokio::test]
-    async fn ignores_session_prefix_messages_when_truncating() {
-        let (session, turn_context) = make_session_and_context().await;
-        let mut items = session.build_initial_context(&turn_context);
-        items.push(user_msg("feature request"));
-        items.push(assistant_msg("ack"));
-        items.push(user_msg("second question"));
-        items.push(assistant_msg("answer"));
-
-        let rollout_items: Vec<RolloutItem> = items
-            .iter()
-            .cloned()
-            .map(RolloutItem::ResponseItem)
-            .collect();
+    async fn session_that_skips_session_configured_is_cleaned_up() {
+        let codex_home = TempDir::new().expect("create temp dir");
+        let config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .build()
+            .await
+            .expect("load default test config");
+        let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"));
+        let shared = Arc::new(ManagerShared::new(
+            auth_manager.clone(),
+            Arc::new(ModelsManager::new(auth_manager)),
+            Arc::new(SkillsManager::new(codex_home.path().to_path_buf())),
+            SessionSource::Exec,
+        ));
+
+        let conversation_id = ConversationId::default();
+        let recorder = RolloutRecorder::new(
+            &config,
+            RolloutRecorderParams::new(conversation_id, None, SessionSource::Exec),
+        )
+        .await
+        .expect("create rollout");
+        let rollout_path = recorder.rollout_path.clone();
+        assert!(rollout_path.exists());
 
-        let truncated = truncate_before_nth_user_message(InitialHistory::Forked(rollout_items), 1);
-        let got_items = truncated.get_rollout_items();
+        // Stands in for a session whose first event is not `SessionConfigured`.
+        let (tx_sub, rx_sub) = async_channel::unbounded::<crate::protocol::Submission>();
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let session_recorder = recorder.clone();
+        let session_task = tokio::spawn(async move {
+            let event = |msg| Event {
+                id: INITIAL_SUBMIT_ID.to_string(),
+                msg,
+                correlation_id: None,
+            };
+            let background = EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: "hello".to_string(),
+            });
+            tx_event.send(event(background)).await.ok();
+            while let Ok(sub) = rx_sub.recv().await {
+                if matches!(sub.op, Op::Shutdown) {
+                    session_recorder.shutdown().await.ok();
+                    tx_event.send(event(EventMsg::ShutdownComplete)).await.ok();
+                    break;
+                }
+            }
+        });
+        let codex = Codex {
+            next_id: AtomicU64::new(0),
+            tx_sub,
+            rx_event,
+            live_rollout: Some(LiveRollout::new(recorder)),
+            session_task: Some(session_task.abort_handle()),
+            context_usage: None,
+            reasoning_log: None,
+            transcript_journal: None,
+            git_context: None,
+            session_end: None,
+            turn_results: None,
+            pause: None,
+            load: None,
+            event_subscribers: None,
+            handoff: None,
+        };
+        let children = ChildSpawner {
+            manager: Arc::downgrade(&shared),
+            config,
+            pre_processors: Vec::new(),
+            post_processors: Vec::new(),
+        };
 
-        let expected: Vec<RolloutItem> = vec![
-            RolloutItem::ResponseItem(items[0].clone()),
-            RolloutItem::ResponseItem(items[1].clone()),
-            RolloutItem::ResponseItem(items[2].clone()),
-        ];
+        let result = shared
+            .finalize_spawn(
+                codex,
+                conversation_id,
+                children,
+                true,
+                SpawnProgress::default(),
+            )
+            .await;
+        let Err(CodexErr::SessionConfiguredNotFirstEvent { got }) = result else {
+            panic!("expected SessionConfiguredNotFirstEvent");
+        };
+        assert!(matches!(*got, EventMsg::BackgroundEvent(_)));
 
+        tokio::time::timeout(Duration::from_secs(1), session_task)
+            .await
+            .expect("session task should have stopped")
+            .expect("session task should not panic");
+        assert!(!rollout_path.exists());
+        assert!(shared.conversations.read().await.is_empty());
         assert_eq!(
-            serde_json::to_value(&got_items).unwrap(),
-            serde_json::to_value(&expected).unwrap()
+            shared.failed_spawn_stats(),
+            FailedSpawnStats {
+                discarded: 1,
+                aborted: 0,
+                leaked_rollouts: 0,
+            }
         );
     }
+
+    #[tokio::test]
+    async fn test_codex_home_lives_as_long_as_any_clone() {
+        let provider = crate::built_in_model_providers()["openai"].clone();
+        let manager =
+            ConversationManager::with_models_provider(CodexAuth::from_api_key("dummy"), provider);
+        let clone = manager.clone();
+        let codex_home = clone.shared.auth_manager.codex_home().to_path_buf();
+        assert!(codex_home.exists());
+
+        drop(manager);
+        assert!(codex_home.exists());
+
+        drop(clone);
+        assert!(!codex_home.exists());
+    }
 }
@@ -0,0 +1,47 @@
+//! The `correlation_id`s clients attach to submissions, looked up by
+//! submission id so every event of a submission, and of the turn it starts,
+//! carries the same id.
+//!
+//! An id is tracked from the moment its submission is handled until the turn
+//! it started completes or is aborted. Submissions that start no turn drop
+//! their id once handled.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use codex_protocol::protocol::EventMsg;
+
+#[derive(Default)]
+pub(crate) struct CorrelationIds {
+    by_sub_id: Mutex<HashMap<String, String>>,
+}
+
+impl CorrelationIds {
+    /// Track `correlation_id` for the submission `sub_id`.
+    pub(crate) fn begin(&self, sub_id: &str, correlation_id: Option<String>) {
+        if let Some(correlation_id) = correlation_id {
+            self.lock().insert(sub_id.to_string(), correlation_id);
+        }
+    }
+
+    pub(crate) fn get(&self, sub_id: &str) -> Option<String> {
+        self.lock().get(sub_id).cloned()
+    }
+
+    /// Stop tracking `sub_id` once `msg`, the last event of a turn, was sent.
+    pub(crate) fn observe(&self, sub_id: &str, msg: &EventMsg) {
+        if matches!(msg, EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_)) {
+            self.end(sub_id);
+        }
+    }
+
+    pub(crate) fn end(&self, sub_id: &str) {
+        self.lock().remove(sub_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.by_sub_id
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
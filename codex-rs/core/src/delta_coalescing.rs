@@ -0,0 +1,192 @@
+//! Coalescing of assistant text deltas into fewer, larger events.
+//!
+//! Providers stream answers a token at a time. With a
+//! [`DeltaCoalescingPolicy`] that holds deltas back, consecutive
+//! `AgentMessageContentDelta`s of the same item are joined and sent once the
+//! oldest held text is `max_latency_ms` old, the held text reaches
+//! `max_bytes`, or a delta completes a line. Any other event of the turn sends
+//! the held text first, so deltas are never reordered with tool calls or
+//! item boundaries, and the joined deltas carry exactly the streamed text.
+
+use std::time::Duration;
+
+use codex_protocol::protocol::AgentMessageContentDeltaEvent;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::time::Instant;
+
+/// How assistant text deltas are coalesced. The default sends every delta
+/// as it arrives.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeltaCoalescingPolicy {
+    /// Longest a delta is held back. `0` sends every delta as it arrives.
+    pub max_latency_ms: u64,
+
+    /// Send the held text once it reaches this many bytes. `0` sets no
+    /// limit.
+    pub max_bytes: usize,
+
+    /// Send the held text up to the end of the last complete line as soon
+    /// as a delta ends one.
+    pub flush_on_newline: bool,
+}
+
+impl DeltaCoalescingPolicy {
+    fn is_passthrough(&self) -> bool {
+        self.max_latency_ms == 0
+    }
+}
+
+/// Held assistant text of a turn, see the [module docs](self).
+pub(crate) struct DeltaCoalescer {
+    policy: DeltaCoalescingPolicy,
+    pending: Option<PendingDelta>,
+}
+
+struct PendingDelta {
+    event: AgentMessageContentDeltaEvent,
+    /// When the oldest held text is due.
+    deadline: Instant,
+}
+
+impl DeltaCoalescer {
+    pub(crate) fn new(policy: DeltaCoalescingPolicy) -> Self {
+        Self {
+            policy,
+            pending: None,
+        }
+    }
+
+    /// Hold `delta`, returning the deltas due now, oldest first.
+    pub(crate) fn push(
+        &mut self,
+        delta: AgentMessageContentDeltaEvent,
+    ) -> Vec<AgentMessageContentDeltaEvent> {
+        if self.policy.is_passthrough() {
+            return vec![delta];
+        }
+        let mut due = Vec::new();
+        let now = Instant::now();
+        match &mut self.pending {
+            Some(pending)
+                if pending.event.item_id == delta.item_id
+                    && pending.event.turn_id == delta.turn_id =>
+            {
+                pending.event.delta.push_str(&delta.delta);
+            }
+            pending => {
+                due.extend(pending.take().map(|pending| pending.event));
+                *pending = Some(PendingDelta {
+                    event: delta,
+                    deadline: now + Duration::from_millis(self.policy.max_latency_ms),
+                });
+            }
+        }
+
+        if self.policy.flush_on_newline
+            && let Some(pending) = &mut self.pending
+            && let Some(newline) = pending.event.delta.rfind('\n')
+        {
+            let rest = pending.event.delta.split_off(newline + 1);
+            due.push(pending.event.clone());
+            if rest.is_empty() {
+                self.pending = None;
+            } else {
+                pending.event.delta = rest;
+                // What is left arrived with the delta that ended the line.
+                pending.deadline = now + Duration::from_millis(self.policy.max_latency_ms);
+            }
+        }
+        if self.policy.max_bytes > 0
+            && self
+                .pending
+                .as_ref()
+                .is_some_and(|pending| pending.event.delta.len() >= self.policy.max_bytes)
+        {
+            due.extend(self.take());
+        }
+        due
+    }
+
+    /// When the held text is due, `None` when nothing is held.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|pending| pending.deadline)
+    }
+
+    /// The held text, which is then no longer held.
+    pub(crate) fn take(&mut self) -> Option<AgentMessageContentDeltaEvent> {
+        self.pending.take().map(|pending| pending.event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn delta(item_id: &str, text: &str) -> AgentMessageContentDeltaEvent {
+        AgentMessageContentDeltaEvent {
+            thread_id: "thread".to_string(),
+            turn_id: "turn".to_string(),
+            item_id: item_id.to_string(),
+            delta: text.to_string(),
+        }
+    }
+
+    fn texts(deltas: Vec<AgentMessageContentDeltaEvent>) -> Vec<String> {
+        deltas.into_iter().map(|delta| delta.delta).collect()
+    }
+
+    #[test]
+    fn the_default_policy_passes_deltas_through() {
+        let mut coalescer = DeltaCoalescer::new(DeltaCoalescingPolicy::default());
+        assert_eq!(texts(coalescer.push(delta("msg", "a"))), vec!["a"]);
+        assert_eq!(coalescer.deadline(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn held_text_is_due_after_the_latency_of_its_oldest_delta() {
+        let mut coalescer = DeltaCoalescer::new(DeltaCoalescingPolicy {
+            max_latency_ms: 30,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        assert_eq!(
+            texts(coalescer.push(delta("msg", "a"))),
+            Vec::<String>::new()
+        );
+        tokio::time::advance(Duration::from_millis(10)).await;
+        assert_eq!(
+            texts(coalescer.push(delta("msg", "b"))),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            coalescer.deadline(),
+            Some(start + Duration::from_millis(30))
+        );
+        assert_eq!(texts(coalescer.take().into_iter().collect()), vec!["ab"]);
+        assert_eq!(coalescer.deadline(), None);
+    }
+
+    #[test]
+    fn lines_bytes_and_items_end_the_held_text() {
+        let mut coalescer = DeltaCoalescer::new(DeltaCoalescingPolicy {
+            max_latency_ms: 1_000,
+            max_bytes: 4,
+            flush_on_newline: true,
+        });
+        assert_eq!(
+            texts(coalescer.push(delta("msg", "ab"))),
+            Vec::<String>::new()
+        );
+        assert_eq!(texts(coalescer.push(delta("msg", "c\nd"))), vec!["abc\n"]);
+        assert_eq!(texts(coalescer.push(delta("msg", "efg"))), vec!["defg"]);
+        assert_eq!(
+            texts(coalescer.push(delta("msg", "h"))),
+            Vec::<String>::new()
+        );
+        assert_eq!(texts(coalescer.push(delta("other", "i"))), vec!["h"]);
+        assert_eq!(texts(coalescer.take().into_iter().collect()), vec!["i"]);
+    }
+}
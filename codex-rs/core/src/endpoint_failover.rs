@@ -0,0 +1,151 @@
+//! Failover between the base URLs of a provider configured with
+//! `fallback_base_urls`.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use codex_client::HttpTransport;
+use codex_client::Request;
+use codex_client::Response;
+use codex_client::StreamResponse;
+use codex_client::TransportError;
+use codex_protocol::protocol::ProviderEndpointSwitchedEvent;
+
+use crate::models_manager::endpoint_health::EndpointHealth;
+
+/// Endpoint switches observed by a client, waiting to be surfaced as events.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EndpointSwitches(Arc<Mutex<Vec<ProviderEndpointSwitchedEvent>>>);
+
+impl EndpointSwitches {
+    fn push(&self, switch: ProviderEndpointSwitchedEvent) {
+        if let Ok(mut switches) = self.0.lock() {
+            switches.push(switch);
+        }
+    }
+
+    pub(crate) fn take(&self) -> Vec<ProviderEndpointSwitchedEvent> {
+        self.0
+            .lock()
+            .map(|mut switches| std::mem::take(&mut *switches))
+            .unwrap_or_default()
+    }
+}
+
+/// Everything a [`FailoverTransport`] needs to route one client's requests.
+pub(crate) struct EndpointRouting {
+    pub(crate) provider: String,
+    /// Base URLs in preference order; the first is the one requests are
+    /// built against.
+    pub(crate) endpoints: Vec<String>,
+    pub(crate) cooldown: Duration,
+    pub(crate) health: Arc<EndpointHealth>,
+    pub(crate) switches: EndpointSwitches,
+}
+
+/// [`HttpTransport`] wrapper that retries a request against the next healthy
+/// base URL when the current one is unreachable or returns a 5xx.
+pub(crate) struct FailoverTransport<T> {
+    inner: T,
+    routing: Option<EndpointRouting>,
+}
+
+impl<T> FailoverTransport<T> {
+    pub(crate) fn new(inner: T, routing: Option<EndpointRouting>) -> Self {
+        let routing = routing.filter(|routing| routing.endpoints.len() > 1);
+        Self { inner, routing }
+    }
+}
+
+impl<T: HttpTransport> FailoverTransport<T> {
+    async fn route<R, F, Fut>(&self, req: Request, send: F) -> Result<R, TransportError>
+    where
+        F: Fn(Request) -> Fut,
+        Fut: Future<Output = Result<R, TransportError>>,
+    {
+        let Some(routing) = &self.routing else {
+            return send(req).await;
+        };
+        let Some(suffix) = routing
+            .endpoints
+            .first()
+            .and_then(|primary| req.url.strip_prefix(primary.as_str()))
+            .map(str::to_string)
+        else {
+            return send(req).await;
+        };
+
+        let mut last_err = None;
+        for endpoint in routing.health.ordered(&routing.endpoints) {
+            let mut attempt = req.clone();
+            attempt.url = format!("{endpoint}{suffix}");
+            match send(attempt).await {
+                Err(err) if is_endpoint_failure(&err) => {
+                    routing.health.mark_unhealthy(&endpoint, routing.cooldown);
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+                Ok(response) => {
+                    if let Some(previous) = routing.health.mark_served(&routing.provider, &endpoint)
+                    {
+                        routing.switches.push(ProviderEndpointSwitchedEvent {
+                            provider: routing.provider.clone(),
+                            from: previous,
+                            to: endpoint,
+                        });
+                    }
+                    return Ok(response);
+                }
+            }
+        }
+        Err(last_err.unwrap_or(TransportError::RetryLimit))
+    }
+}
+
+#[async_trait]
+impl<T: HttpTransport> HttpTransport for FailoverTransport<T> {
+    async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+        self.route(req, |req| self.inner.execute(req)).await
+    }
+
+    async fn stream(&self, req: Request) -> Result<StreamResponse, TransportError> {
+        self.route(req, |req| self.inner.stream(req)).await
+    }
+}
+
+/// Connection failures and server errors mean the endpoint itself is
+/// unhealthy; anything else (4xx, build errors) would fail everywhere.
+fn is_endpoint_failure(err: &TransportError) -> bool {
+    match err {
+        TransportError::Http { status, .. } => status.is_server_error(),
+        TransportError::Network(_) | TransportError::Timeout => true,
+        TransportError::RetryLimit | TransportError::Build(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+
+    #[test]
+    fn only_connect_errors_and_server_errors_trigger_failover() {
+        assert!(is_endpoint_failure(&TransportError::Network(
+            "connection refused".to_string()
+        )));
+        assert!(is_endpoint_failure(&TransportError::Timeout));
+        assert!(is_endpoint_failure(&TransportError::Http {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            headers: None,
+            body: None,
+        }));
+        assert!(!is_endpoint_failure(&TransportError::Http {
+            status: StatusCode::BAD_REQUEST,
+            headers: None,
+            body: None,
+        }));
+    }
+}
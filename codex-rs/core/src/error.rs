@@ -1,4 +1,6 @@
 use crate::exec::ExecToolCallOutput;
+use crate::history_truncation::TruncationError;
+use crate::session_profiles::SessionProfileError;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use crate::truncate::TruncationPolicy;
@@ -11,10 +13,13 @@ use codex_async_utils::CancelErr;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::CodexErrorInfo;
 use codex_protocol::protocol::ErrorEvent;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::TurnId;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -61,6 +66,11 @@ pub enum CodexErr {
     #[error("turn aborted. Something went wrong? Hit `/feedback` to report the issue.")]
     TurnAborted,
 
+    /// A turn post-processor rejected the model's output. Reported to
+    /// clients through `EventMsg::TurnRejected`.
+    #[error("turn rejected: {0}")]
+    TurnRejected(String),
+
     /// Returned by ResponsesClient when the SSE stream disconnects or errors out **after** the HTTP
     /// handshake has succeeded but **before** it finished emitting `response.completed`.
     ///
@@ -78,8 +88,63 @@ pub enum CodexErr {
     #[error("no conversation with id: {0}")]
     ConversationNotFound(ConversationId),
 
-    #[error("session configured event was not the first event in the stream")]
-    SessionConfiguredNotFirstEvent,
+    #[error("conversation limit reached: at most {0} conversations can run at once")]
+    ConversationLimitReached(usize),
+
+    /// The rollout being resumed belongs to a conversation that is already
+    /// running, or being resumed, in this manager.
+    #[error("conversation {0} is already running")]
+    ConversationAlreadyExists(ConversationId),
+
+    /// The conversation is paused and refuses submissions until it is
+    /// resumed, see [`crate::CodexConversation::pause`].
+    #[error("the conversation is paused")]
+    ConversationPaused,
+
+    /// The conversation is offline and does not call the model provider,
+    /// see `Config::offline`.
+    #[error("the conversation is offline")]
+    Offline,
+
+    /// The configured `cwd` cannot be used as a conversation's working
+    /// directory.
+    #[error("invalid working directory {}: {reason}", path.display())]
+    InvalidWorkingDirectory { path: PathBuf, reason: String },
+
+    /// The final answer did not match the turn's `final_output_json_schema`
+    /// even after the configured repair attempts.
+    #[error("final answer does not match the output schema: {}", errors.join("; "))]
+    StructuredOutputInvalid { errors: Vec<String> },
+
+    /// A fork was requested before a user message the history does not have.
+    /// `available` is the number of user messages, so callers can clamp.
+    #[error("cannot fork before user message {requested}: the conversation has {available}")]
+    ForkPointOutOfRange { requested: usize, available: usize },
+
+    /// A fork was requested at a turn the conversation does not record.
+    #[error("cannot fork at turn {0}: the conversation has no such turn")]
+    UnknownTurn(TurnId),
+
+    /// The rollout to delete is the fork base of other conversations, see
+    /// [`crate::ConversationManager::remove_conversation_and_delete`].
+    #[error("conversation {conversation_id} is the fork base of {} other conversations", forks.len())]
+    ForkBaseInUse {
+        conversation_id: ConversationId,
+        forks: Vec<ConversationId>,
+    },
+
+    /// No recorded session belongs to the project containing this directory.
+    #[error("no recorded session for {}", .0.display())]
+    NoSessionForCwd(PathBuf),
+
+    /// A newly spawned session emitted `got` before `SessionConfigured`. The
+    /// session is shut down and a rollout it created is removed.
+    #[error("session configured event was not the first event in the stream (got {got:?})")]
+    SessionConfiguredNotFirstEvent { got: Box<EventMsg> },
+
+    /// A session profile does not exist or its file is invalid.
+    #[error(transparent)]
+    SessionProfile(#[from] SessionProfileError),
 
     /// Returned by run_command_stream when the spawned child process timed out (10s).
     #[error("timeout waiting for child process to exit")]
@@ -175,6 +240,21 @@ pub enum CodexErr {
     EnvVar(EnvVarError),
 }
 
+impl From<TruncationError> for CodexErr {
+    fn from(err: TruncationError) -> Self {
+        match err {
+            TruncationError::OutOfRange {
+                requested,
+                available,
+            } => CodexErr::ForkPointOutOfRange {
+                requested,
+                available,
+            },
+            TruncationError::UnknownTurn(turn_id) => CodexErr::UnknownTurn(turn_id),
+        }
+    }
+}
+
 impl From<CancelErr> for CodexErr {
     fn from(_: CancelErr) -> Self {
         CodexErr::TurnAborted
@@ -452,13 +532,23 @@ impl CodexErr {
                 http_status_code: self.http_status_code_value(),
             },
             CodexErr::RefreshTokenFailed(_) => CodexErrorInfo::Unauthorized,
-            CodexErr::SessionConfiguredNotFirstEvent
+            CodexErr::SessionConfiguredNotFirstEvent { .. }
             | CodexErr::InternalServerError
             | CodexErr::InternalAgentDied => CodexErrorInfo::InternalServerError,
-            CodexErr::UnsupportedOperation(_) | CodexErr::ConversationNotFound(_) => {
-                CodexErrorInfo::BadRequest
-            }
+            CodexErr::UnsupportedOperation(_)
+            | CodexErr::ConversationNotFound(_)
+            | CodexErr::ConversationLimitReached(_)
+            | CodexErr::ConversationAlreadyExists(_)
+            | CodexErr::ConversationPaused
+            | CodexErr::Offline
+            | CodexErr::InvalidWorkingDirectory { .. }
+            | CodexErr::ForkPointOutOfRange { .. }
+            | CodexErr::UnknownTurn(_)
+            | CodexErr::ForkBaseInUse { .. }
+            | CodexErr::NoSessionForCwd(_)
+            | CodexErr::SessionProfile(_) => CodexErrorInfo::BadRequest,
             CodexErr::Sandbox(_) => CodexErrorInfo::SandboxError,
+            CodexErr::StructuredOutputInvalid { .. } => CodexErrorInfo::StructuredOutputInvalid,
             _ => CodexErrorInfo::Other,
         }
     }
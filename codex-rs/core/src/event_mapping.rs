@@ -13,14 +13,26 @@ use codex_protocol::user_input::UserInput;
 use tracing::warn;
 use uuid::Uuid;
 
+use crate::child_conversations::CHILD_RESULT_OPEN_TAG;
+use crate::project_notes::PROJECT_NOTES_OPEN_TAG;
+use crate::rollout::import::IMPORTED_ENTRY_OPEN_TAG;
+use crate::structured_output::STRUCTURED_OUTPUT_REPAIR_OPEN_TAG;
+use crate::user_context::USER_CONTEXT_OPEN_TAG;
 use crate::user_instructions::SkillInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_shell_command::is_user_shell_command_text;
+use crate::workspace_watch::WORKSPACE_CHANGED_OPEN_TAG;
 
 fn is_session_prefix(text: &str) -> bool {
     let trimmed = text.trim_start();
     let lowered = trimmed.to_ascii_lowercase();
     lowered.starts_with("<environment_context>")
+        || lowered.starts_with(WORKSPACE_CHANGED_OPEN_TAG)
+        || lowered.starts_with(IMPORTED_ENTRY_OPEN_TAG)
+        || lowered.starts_with(CHILD_RESULT_OPEN_TAG)
+        || lowered.starts_with(STRUCTURED_OUTPUT_REPAIR_OPEN_TAG)
+        || lowered.starts_with(USER_CONTEXT_OPEN_TAG)
+        || lowered.starts_with(PROJECT_NOTES_OPEN_TAG)
 }
 
 fn parse_user_message(message: &[ContentItem]) -> Option<UserMessageItem> {
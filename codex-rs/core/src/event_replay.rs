@@ -0,0 +1,235 @@
+//! Bounded in-memory replay of the events a conversation delivered.
+//!
+//! Every event returned by [`CodexConversation::next_event`], starting with
+//! `SessionConfigured`, gets a sequence number, beginning at 1 and increasing
+//! by one. A client that reconnects asks
+//! [`CodexConversation::events_since`] for everything after the last number
+//! it saw. The most recent events are kept up to the configured
+//! [`EventReplayPolicy`]; once the requested events have been evicted the
+//! client gets a [`ReplayGap`] and has to rebuild from the transcript.
+//!
+//! [`CodexConversation::next_event`]: crate::CodexConversation::next_event
+//! [`CodexConversation::events_since`]: crate::CodexConversation::events_since
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use codex_protocol::protocol::Event;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+const DEFAULT_EVENT_REPLAY_MAX_EVENTS: usize = 1_000;
+const DEFAULT_EVENT_REPLAY_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// How many delivered events a conversation keeps for replay.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventReplayPolicy {
+    /// Most events kept. `0` disables replay.
+    pub max_events: usize,
+
+    /// Most bytes kept, measured as serialized JSON.
+    pub max_bytes: usize,
+}
+
+impl Default for EventReplayPolicy {
+    fn default() -> Self {
+        Self {
+            max_events: DEFAULT_EVENT_REPLAY_MAX_EVENTS,
+            max_bytes: DEFAULT_EVENT_REPLAY_MAX_BYTES,
+        }
+    }
+}
+
+/// An event together with its position in the conversation's event stream.
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: Event,
+}
+
+/// The events after the requested sequence number are no longer retained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "events were evicted from the replay buffer; the earliest available is {earliest_available}"
+)]
+pub struct ReplayGap {
+    /// Sequence number of the oldest retained event, or the next one to be
+    /// delivered if none is retained.
+    pub earliest_available: u64,
+}
+
+pub(crate) struct EventReplayBuffer {
+    policy: EventReplayPolicy,
+    state: Mutex<ReplayState>,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    /// Retained events, oldest first, with their serialized sizes.
+    events: VecDeque<(SequencedEvent, usize)>,
+    bytes: usize,
+    last_seq: u64,
+}
+
+impl EventReplayBuffer {
+    pub(crate) fn new(policy: EventReplayPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(ReplayState::default()),
+        }
+    }
+
+    /// Assign the next sequence number to `event` and retain it.
+    pub(crate) fn push(&self, event: Event) -> SequencedEvent {
+        let size = serde_json::to_vec(&event).map_or(0, |json| json.len());
+        let mut state = self.lock_state();
+        state.last_seq += 1;
+        let sequenced = SequencedEvent {
+            seq: state.last_seq,
+            event,
+        };
+        state.events.push_back((sequenced.clone(), size));
+        state.bytes += size;
+        while state.events.len() > self.policy.max_events || state.bytes > self.policy.max_bytes {
+            let Some((_, evicted)) = state.events.pop_front() else {
+                break;
+            };
+            state.bytes -= evicted;
+        }
+        sequenced
+    }
+
+    /// Events after `seq`, oldest first, and the sequence number of the latest
+    /// delivered event.
+    pub(crate) fn since(&self, seq: u64) -> Result<(Vec<SequencedEvent>, u64), ReplayGap> {
+        let state = self.lock_state();
+        let earliest_available = state
+            .events
+            .front()
+            .map_or(state.last_seq + 1, |(event, _)| event.seq);
+        if seq < state.last_seq && seq + 1 < earliest_available {
+            return Err(ReplayGap { earliest_available });
+        }
+        let events = state
+            .events
+            .iter()
+            .filter(|(event, _)| event.seq > seq)
+            .map(|(event, _)| event.clone())
+            .collect();
+        Ok((events, state.last_seq))
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::AgentMessageDeltaEvent;
+    use codex_protocol::protocol::EventMsg;
+    use pretty_assertions::assert_eq;
+
+    fn delta(index: usize) -> Event {
+        Event {
+            id: "turn".to_string(),
+            msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: format!("chunk {index}"),
+            }),
+            correlation_id: None,
+        }
+    }
+
+    fn seqs(events: &[SequencedEvent]) -> Vec<u64> {
+        events.iter().map(|event| event.seq).collect()
+    }
+
+    #[test]
+    fn replays_contiguous_events_and_reports_gaps() {
+        let buffer = EventReplayBuffer::new(EventReplayPolicy {
+            max_events: 50,
+            ..EventReplayPolicy::default()
+        });
+        let pushed: Vec<u64> = (0..100)
+            .map(|index| buffer.push(delta(index)).seq)
+            .collect();
+        assert_eq!(pushed, (1..=100).collect::<Vec<_>>());
+
+        // Events 1..=50 were evicted.
+        assert_eq!(
+            buffer.since(10).unwrap_err(),
+            ReplayGap {
+                earliest_available: 51
+            }
+        );
+        assert_eq!(
+            buffer.since(0).unwrap_err(),
+            ReplayGap {
+                earliest_available: 51
+            }
+        );
+
+        // Resuming right before the oldest retained event is contiguous.
+        let (events, current) = buffer.since(50).unwrap();
+        assert_eq!(seqs(&events), (51..=100).collect::<Vec<_>>());
+        assert_eq!(current, 100);
+
+        let (events, current) = buffer.since(90).unwrap();
+        assert_eq!(seqs(&events), (91..=100).collect::<Vec<_>>());
+        assert_eq!(current, 100);
+        let EventMsg::AgentMessageDelta(last) = &events[9].event.msg else {
+            panic!("expected a delta");
+        };
+        assert_eq!(last.delta, "chunk 99");
+
+        // A caller that has seen everything gets nothing new.
+        let (events, current) = buffer.since(100).unwrap();
+        assert!(events.is_empty());
+        assert_eq!(current, 100);
+    }
+
+    #[test]
+    fn byte_limit_evicts_oldest_events() {
+        let size = serde_json::to_vec(&delta(0)).unwrap().len();
+        let buffer = EventReplayBuffer::new(EventReplayPolicy {
+            max_events: 100,
+            max_bytes: size * 3,
+        });
+        for index in 0..5 {
+            buffer.push(delta(index));
+        }
+
+        let (events, current) = buffer.since(2).unwrap();
+        assert_eq!(seqs(&events), vec![3, 4, 5]);
+        assert_eq!(current, 5);
+        assert_eq!(
+            buffer.since(1).unwrap_err(),
+            ReplayGap {
+                earliest_available: 3
+            }
+        );
+    }
+
+    #[test]
+    fn disabled_replay_still_numbers_events() {
+        let buffer = EventReplayBuffer::new(EventReplayPolicy {
+            max_events: 0,
+            ..EventReplayPolicy::default()
+        });
+        assert_eq!(buffer.push(delta(0)).seq, 1);
+        assert_eq!(buffer.push(delta(1)).seq, 2);
+
+        assert_eq!(
+            buffer.since(0).unwrap_err(),
+            ReplayGap {
+                earliest_available: 3
+            }
+        );
+        assert_eq!(buffer.since(2).unwrap().1, 2);
+    }
+}
@@ -0,0 +1,144 @@
+//! Filtered subscriptions to a conversation's events, see
+//! [`crate::CodexConversation::subscribe_filtered`].
+//!
+//! Events pass through a fan-out task on their way from the session to
+//! [`crate::CodexConversation::next_event`]. Each subscriber gets its own copy
+//! of the events its [`EventFilter`] accepts, whether or not anyone reads the
+//! main stream; events it filters out are never copied for it. A subscription
+//! ends when the session does or when it is dropped.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use async_channel::Receiver;
+use async_channel::Sender;
+use codex_protocol::event_filter::EventFilter;
+use codex_protocol::protocol::Event;
+
+#[derive(Default)]
+pub(crate) struct EventSubscribers {
+    subscribers: Mutex<Vec<(EventFilter, Sender<Event>)>>,
+}
+
+impl EventSubscribers {
+    pub(crate) fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        let (tx, rx) = async_channel::unbounded();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((filter, tx));
+        EventSubscription { rx }
+    }
+
+    fn publish(&self, event: &Event) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(filter, tx)| {
+                if !filter.accepts(&event.msg) {
+                    return !tx.is_closed();
+                }
+                tx.try_send(event.clone()).is_ok()
+            });
+    }
+
+    /// End every subscription.
+    fn close(&self) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Events of one subscription, see
+/// [`crate::CodexConversation::subscribe_filtered`].
+pub struct EventSubscription {
+    rx: Receiver<Event>,
+}
+
+impl EventSubscription {
+    /// A subscription that never delivers an event.
+    pub(crate) fn closed() -> Self {
+        let (_, rx) = async_channel::unbounded();
+        Self { rx }
+    }
+
+    /// The next accepted event, or `None` once the session has ended.
+    pub async fn next_event(&self) -> Option<Event> {
+        self.rx.recv().await.ok()
+    }
+}
+
+/// Forward the events of `rx` to the returned receiver, and a copy of each to
+/// the subscribers that accept it.
+pub(crate) fn spawn_event_fanout(rx: Receiver<Event>) -> (Receiver<Event>, Arc<EventSubscribers>) {
+    let (tx, forwarded) = async_channel::unbounded();
+    let subscribers = Arc::new(EventSubscribers::default());
+    let fanout = Arc::clone(&subscribers);
+    tokio::spawn(async move {
+        while let Ok(event) = rx.recv().await {
+            fanout.publish(&event);
+            // Subscribers keep receiving events when nobody reads the main
+            // stream any more.
+            let _ = tx.send(event).await;
+        }
+        fanout.close();
+    });
+    (forwarded, subscribers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::event_filter::EventCategory;
+    use codex_protocol::protocol::AgentReasoningEvent;
+    use codex_protocol::protocol::EventMsg;
+    use pretty_assertions::assert_eq;
+
+    fn event(msg: EventMsg) -> Event {
+        Event {
+            id: "1".to_string(),
+            msg,
+            correlation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_get_their_events_and_end_with_the_session() {
+        let (tx, rx) = async_channel::unbounded();
+        let (forwarded, subscribers) = spawn_event_fanout(rx);
+        let everything = subscribers.subscribe(EventFilter::all());
+        let content = subscribers.subscribe(EventFilter::only([EventCategory::Content]));
+
+        tx.send(event(EventMsg::AgentReasoning(AgentReasoningEvent {
+            text: "thinking".to_string(),
+        })))
+        .await
+        .expect("send");
+        tx.send(event(EventMsg::ShutdownComplete))
+            .await
+            .expect("send");
+        drop(tx);
+
+        let names = |events: Vec<Event>| -> Vec<String> {
+            events.into_iter().map(|e| e.msg.to_string()).collect()
+        };
+        let mut main = Vec::new();
+        while let Ok(event) = forwarded.recv().await {
+            main.push(event);
+        }
+        let mut all = Vec::new();
+        while let Some(event) = everything.next_event().await {
+            all.push(event);
+        }
+        let mut filtered = Vec::new();
+        while let Some(event) = content.next_event().await {
+            filtered.push(event);
+        }
+        assert_eq!(names(main), vec!["agent_reasoning", "shutdown_complete"]);
+        assert_eq!(names(all), vec!["agent_reasoning", "shutdown_complete"]);
+        assert_eq!(names(filtered), vec!["shutdown_complete"]);
+    }
+}
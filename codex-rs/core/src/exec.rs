@@ -125,6 +125,8 @@ pub struct StdoutStream {
     pub sub_id: String,
     pub call_id: String,
     pub tx_event: Sender<Event>,
+    /// `correlation_id` of the submission the output belongs to.
+    pub correlation_id: Option<String>,
 }
 
 pub async fn process_exec_tool_call(
@@ -716,6 +718,7 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
             let event = Event {
                 id: stream.sub_id.clone(),
                 msg,
+                correlation_id: stream.correlation_id.clone(),
             };
             #[allow(clippy::let_unit_value)]
             let _ = stream.tx_event.send(event).await;
@@ -0,0 +1,485 @@
+//! Scripted failures, so crates embedding Codex can exercise their error
+//! paths without a misbehaving provider or a failing disk.
+//!
+//! A [`FaultPlan`] lists the failures to inject. Provider faults are served
+//! by a [`FaultyProvider`], a local HTTP server forwarding to the provider it
+//! wraps. It converts into the [`ModelProviderInfo`] that points at it, so it
+//! can be passed to [`crate::ConversationManager::with_models_provider`].
+//! Rollout faults apply to the conversations a manager starts after
+//! [`crate::ConversationManager::inject_rollout_faults`].
+//!
+//! Model requests (every other `POST` sent to the provider), model list
+//! fetches (`GET` requests for `/models`), token refreshes (`POST` requests
+//! for `/oauth/token`, sent to the provider when the refresh URL is pointed
+//! at it) and rollout appends are each counted from 1 across everything the
+//! plan is attached to. A fault fires once, on the request or append it
+//! names, and is consumed; the faults that never fired are left in
+//! [`FaultPlan::remaining_faults`].
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use axum::Router;
+use axum::body::Body;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::Method;
+use axum::http::StatusCode;
+use axum::http::Uri;
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::header::HOST;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use futures::StreamExt;
+use futures::stream;
+use serde_json::Value;
+
+use crate::ModelProviderInfo;
+use crate::provider_cassette::DEFAULT_BASE_URL;
+use crate::provider_cassette::LocalServer;
+use crate::provider_cassette::lock;
+use crate::provider_cassette::request_target;
+use crate::provider_cassette::upstream_failure;
+use crate::rollout::writer::RolloutSink;
+
+/// Plans attached to the rollouts of a codex home, see
+/// [`RolloutFaultsRegistration`].
+static ROLLOUT_FAULTS: LazyLock<Mutex<HashMap<PathBuf, FaultPlan>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A failure to inject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Answer the `request`th model request with `status` instead of
+    /// forwarding it.
+    ProviderStatus { request: u64, status: u16 },
+    /// Forward the `request`th model request, but stop streaming the response
+    /// after its `after_tokens`th text delta, keeping the connection open.
+    StallStream { request: u64, after_tokens: usize },
+    /// Answer the `fetch`th model list fetch with `status`.
+    ModelsFetchStatus { fetch: u64, status: u16 },
+    /// Answer the `refresh`th token refresh with `status`.
+    TokenRefreshStatus { refresh: u64, status: u16 },
+    /// Fail the `append`th rollout append with `EIO`.
+    RolloutAppend { append: u64 },
+    /// Fail the `append`th rollout append with `ENOSPC`.
+    RolloutNoSpace { append: u64 },
+}
+
+/// Failures to inject, in the order they were added. Clones share the
+/// faults and the counts.
+#[derive(Debug, Clone, Default)]
+pub struct FaultPlan {
+    state: Arc<Mutex<PlanState>>,
+}
+
+#[derive(Debug, Default)]
+struct PlanState {
+    faults: Vec<Fault>,
+    requests: u64,
+    models_fetches: u64,
+    token_refreshes: u64,
+    appends: u64,
+}
+
+impl FaultPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the `request`th model request with `status`.
+    pub fn fail_provider_request(self, request: u64, status: u16) -> Self {
+        self.with(Fault::ProviderStatus { request, status })
+    }
+
+    /// Stall the response to the `request`th model request after
+    /// `after_tokens` text deltas.
+    pub fn stall_stream(self, request: u64, after_tokens: usize) -> Self {
+        self.with(Fault::StallStream {
+            request,
+            after_tokens,
+        })
+    }
+
+    /// Answer the `fetch`th model list fetch with `status`.
+    pub fn fail_models_fetch(self, fetch: u64, status: u16) -> Self {
+        self.with(Fault::ModelsFetchStatus { fetch, status })
+    }
+
+    /// Answer the `refresh`th token refresh with `status`.
+    pub fn fail_token_refresh(self, refresh: u64, status: u16) -> Self {
+        self.with(Fault::TokenRefreshStatus { refresh, status })
+    }
+
+    /// Fail the `append`th rollout append with `EIO`.
+    pub fn fail_rollout_append(self, append: u64) -> Self {
+        self.with(Fault::RolloutAppend { append })
+    }
+
+    /// Fail `appends` rollout appends from the `from`th on with `ENOSPC`, as
+    /// a disk that fills up and is cleared again would.
+    pub fn fill_rollout_disk(self, from: u64, appends: u64) -> Self {
+        (from..from + appends).fold(self, |plan, append| {
+            plan.with(Fault::RolloutNoSpace { append })
+        })
+    }
+
+    /// The faults that have not fired yet, in the order they were added.
+    pub fn remaining_faults(&self) -> Vec<Fault> {
+        lock(&self.state).faults.clone()
+    }
+
+    fn with(self, fault: Fault) -> Self {
+        lock(&self.state).faults.push(fault);
+        self
+    }
+
+    /// Count a model request and take the fault it triggers.
+    fn take_request_fault(&self) -> Option<Fault> {
+        let mut state = lock(&self.state);
+        state.requests += 1;
+        let request = state.requests;
+        let index = state.faults.iter().position(|fault| match fault {
+            Fault::ProviderStatus { request: nth, .. }
+            | Fault::StallStream { request: nth, .. } => *nth == request,
+            Fault::ModelsFetchStatus { .. }
+            | Fault::TokenRefreshStatus { .. }
+            | Fault::RolloutAppend { .. }
+            | Fault::RolloutNoSpace { .. } => false,
+        })?;
+        Some(state.faults.remove(index))
+    }
+
+    /// Count a model list fetch and take the status it is answered with.
+    fn take_models_fetch_fault(&self) -> Option<u16> {
+        let mut state = lock(&self.state);
+        state.models_fetches += 1;
+        let fetch = state.models_fetches;
+        let index = state.faults.iter().position(
+            |fault| matches!(fault, Fault::ModelsFetchStatus { fetch: nth, .. } if *nth == fetch),
+        )?;
+        match state.faults.remove(index) {
+            Fault::ModelsFetchStatus { status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Count a token refresh and take the status it is answered with.
+    fn take_token_refresh_fault(&self) -> Option<u16> {
+        let mut state = lock(&self.state);
+        state.token_refreshes += 1;
+        let refresh = state.token_refreshes;
+        let index = state.faults.iter().position(|fault| {
+            matches!(fault, Fault::TokenRefreshStatus { refresh: nth, .. } if *nth == refresh)
+        })?;
+        match state.faults.remove(index) {
+            Fault::TokenRefreshStatus { status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Count a rollout append and take the fault it triggers.
+    fn take_append_fault(&self) -> Option<Fault> {
+        let mut state = lock(&self.state);
+        state.appends += 1;
+        let append = state.appends;
+        let index = state.faults.iter().position(|fault| {
+            *fault == Fault::RolloutAppend { append } || *fault == Fault::RolloutNoSpace { append }
+        })?;
+        Some(state.faults.remove(index))
+    }
+}
+
+/// Forwards requests to a provider, injecting the provider faults of a plan.
+pub struct FaultyProvider {
+    provider: ModelProviderInfo,
+    _server: LocalServer,
+}
+
+struct ProxyState {
+    upstream_base_url: String,
+    client: reqwest::Client,
+    plan: FaultPlan,
+}
+
+impl FaultyProvider {
+    /// Start forwarding to `upstream`.
+    pub async fn start(upstream: ModelProviderInfo, plan: &FaultPlan) -> io::Result<Self> {
+        let state = Arc::new(ProxyState {
+            upstream_base_url: upstream
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            client: reqwest::Client::new(),
+            plan: plan.clone(),
+        });
+        let server = LocalServer::start(Router::new().fallback(forward).with_state(state)).await?;
+        let provider = ModelProviderInfo {
+            base_url: Some(server.base_url.clone()),
+            fallback_base_urls: None,
+            ..upstream
+        };
+        Ok(Self {
+            provider,
+            _server: server,
+        })
+    }
+}
+
+impl From<&FaultyProvider> for ModelProviderInfo {
+    fn from(faulty: &FaultyProvider) -> Self {
+        faulty.provider.clone()
+    }
+}
+
+async fn forward(
+    State(state): State<Arc<ProxyState>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri.path();
+    let (fault, injected_status) = if method == Method::GET && path.ends_with("/models") {
+        (None, state.plan.take_models_fetch_fault())
+    } else if method == Method::POST && path.ends_with("/oauth/token") {
+        (None, state.plan.take_token_refresh_fault())
+    } else if method == Method::POST {
+        let fault = state.plan.take_request_fault();
+        match fault {
+            Some(Fault::ProviderStatus { status, .. }) => (fault, Some(status)),
+            _ => (fault, None),
+        }
+    } else {
+        (None, None)
+    };
+    if let Some(status) = injected_status {
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::json!({
+            "error": { "message": format!("fault injected with status {status}") }
+        });
+        return (
+            status,
+            [(CONTENT_TYPE, "application/json")],
+            body.to_string(),
+        )
+            .into_response();
+    }
+
+    let url = format!("{}{}", state.upstream_base_url, request_target(&uri));
+    let mut forwarded = headers;
+    forwarded.remove(HOST);
+    forwarded.remove(CONTENT_LENGTH);
+    let upstream = match state
+        .client
+        .request(method, url)
+        .headers(forwarded)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(upstream) => upstream,
+        Err(err) => return upstream_failure(&err),
+    };
+    let status =
+        StatusCode::from_u16(upstream.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let content_type = upstream.headers().get(CONTENT_TYPE).cloned();
+    let bytes = match upstream.bytes().await {
+        Ok(bytes) => bytes,
+        Err(err) => return upstream_failure(&err),
+    };
+    let body = match fault {
+        Some(Fault::StallStream { after_tokens, .. }) => {
+            let prefix = Bytes::from(stalled_prefix(&bytes, after_tokens));
+            Body::from_stream(stream::iter([Ok::<_, io::Error>(prefix)]).chain(stream::pending()))
+        }
+        _ => Body::from(bytes),
+    };
+    let mut response = (status, body).into_response();
+    if let Some(content_type) = content_type {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+/// The events of the SSE `body` up to its `after_tokens`th Responses API
+/// text delta, or up to its first one when `after_tokens` is 0.
+fn stalled_prefix(body: &[u8], after_tokens: usize) -> String {
+    let body = String::from_utf8_lossy(body);
+    let mut prefix = String::new();
+    let mut tokens = 0;
+    for event in body.split_inclusive("\n\n") {
+        let delta = is_text_delta(event);
+        if delta && tokens == after_tokens {
+            break;
+        }
+        prefix.push_str(event);
+        if delta {
+            tokens += 1;
+            if tokens == after_tokens {
+                break;
+            }
+        }
+    }
+    prefix
+}
+
+fn is_text_delta(event: &str) -> bool {
+    event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str::<Value>(data.trim()).ok())
+        .any(|data| data["type"] == "response.output_text.delta")
+}
+
+/// Attaches a plan to the rollouts of the conversations started in a codex
+/// home until dropped.
+pub(crate) struct RolloutFaultsRegistration {
+    codex_home: PathBuf,
+}
+
+impl RolloutFaultsRegistration {
+    pub(crate) fn new(codex_home: PathBuf, plan: FaultPlan) -> Self {
+        lock(&ROLLOUT_FAULTS).insert(codex_home.clone(), plan);
+        Self { codex_home }
+    }
+}
+
+impl Drop for RolloutFaultsRegistration {
+    fn drop(&mut self) {
+        lock(&ROLLOUT_FAULTS).remove(&self.codex_home);
+    }
+}
+
+/// The plan attached to the rollouts of `codex_home`, if any.
+pub(crate) fn rollout_faults(codex_home: &Path) -> Option<FaultPlan> {
+    lock(&ROLLOUT_FAULTS).get(codex_home).cloned()
+}
+
+/// A rollout sink injecting the rollout faults of a plan.
+pub(crate) struct FaultySink<S> {
+    inner: S,
+    plan: FaultPlan,
+}
+
+impl<S> FaultySink<S> {
+    pub(crate) fn new(inner: S, plan: FaultPlan) -> Self {
+        Self { inner, plan }
+    }
+}
+
+impl<S: RolloutSink> RolloutSink for FaultySink<S> {
+    async fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self.plan.take_append_fault() {
+            Some(Fault::RolloutNoSpace { .. }) => {
+                return Err(io::Error::from_raw_os_error(libc::ENOSPC));
+            }
+            Some(_) => return Err(io::Error::from_raw_os_error(libc::EIO)),
+            None => {}
+        }
+        self.inner.append(bytes).await
+    }
+
+    async fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync().await
+    }
+
+    async fn continue_in(&mut self, path: &Path) -> io::Result<()> {
+        self.inner.continue_in(path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemorySink {
+        written: Vec<u8>,
+    }
+
+    impl RolloutSink for MemorySink {
+        async fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+            self.written.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        async fn sync(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn faults_fire_once_on_the_append_they_name() {
+        let plan = FaultPlan::new()
+            .fail_rollout_append(2)
+            .fail_provider_request(1, 500);
+        let mut sink = FaultySink::new(MemorySink::default(), plan.clone());
+
+        sink.append(b"one\n").await.expect("first append");
+        let err = sink.append(b"two\n").await.expect_err("second append");
+        sink.append(b"three\n").await.expect("third append");
+
+        assert_eq!(err.raw_os_error(), Some(libc::EIO));
+        assert_eq!(sink.inner.written, b"one\nthree\n");
+        assert_eq!(
+            plan.remaining_faults(),
+            vec![Fault::ProviderStatus {
+                request: 1,
+                status: 500
+            }]
+        );
+    }
+
+    #[test]
+    fn requests_fetches_and_refreshes_are_counted_apart() {
+        let plan = FaultPlan::new()
+            .fail_models_fetch(2, 503)
+            .fail_token_refresh(1, 500)
+            .fail_provider_request(2, 429);
+
+        assert_eq!(plan.take_models_fetch_fault(), None);
+        assert_eq!(plan.take_request_fault(), None);
+        assert_eq!(plan.take_token_refresh_fault(), Some(500));
+        assert_eq!(plan.take_models_fetch_fault(), Some(503));
+        assert_eq!(
+            plan.take_request_fault(),
+            Some(Fault::ProviderStatus {
+                request: 2,
+                status: 429
+            })
+        );
+        assert_eq!(plan.remaining_faults(), Vec::new());
+    }
+
+    #[test]
+    fn stalled_prefix_stops_after_the_given_deltas() {
+        let body = concat!(
+            "event: response.created\ndata: {\"type\":\"response.created\"}\n\n",
+            "event: response.output_text.delta\ndata: {\"type\":\"response.output_text.delta\",\"delta\":\"a\"}\n\n",
+            "event: response.output_text.delta\ndata: {\"type\":\"response.output_text.delta\",\"delta\":\"b\"}\n\n",
+            "event: response.completed\ndata: {\"type\":\"response.completed\"}\n\n",
+        );
+
+        let prefix = stalled_prefix(body.as_bytes(), 1);
+        assert!(prefix.ends_with("\"delta\":\"a\"}\n\n"), "{prefix}");
+        assert!(!prefix.contains("\"b\""), "{prefix}");
+
+        let prefix = stalled_prefix(body.as_bytes(), 0);
+        assert_eq!(
+            prefix,
+            "event: response.created\ndata: {\"type\":\"response.created\"}\n\n"
+        );
+    }
+}
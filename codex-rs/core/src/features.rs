@@ -94,6 +94,10 @@ pub enum Feature {
     Skills,
     /// Enforce UTF8 output in Powershell.
     PowershellUtf8,
+    /// Tell the model when the workspace changed outside codex between turns.
+    WorkspaceWatch,
+    /// Tell the model the notes saved for the project and let it save more.
+    ProjectNotes,
 }
 
 impl Feature {
@@ -395,10 +399,22 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Experimental,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::WorkspaceWatch,
+        key: "workspace_watch",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
     FeatureSpec {
         id: Feature::Tui2,
         key: "tui2",
         stage: Stage::Experimental,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::ProjectNotes,
+        key: "project_notes",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
 ];
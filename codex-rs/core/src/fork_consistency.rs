@@ -0,0 +1,220 @@
+//! Checking a fork's history against its workspace, as configured by
+//! `fork_consistency_check`.
+//!
+//! A forked history holds the outputs of the `read_file` calls it made. The
+//! last read of each file runs again, in the fork's cwd for files under the
+//! cwd of the session the fork was cut from, and its output is compared with
+//! the recorded one by hash. Files that read differently are stale; files
+//! that are gone are missing. The check is bounded: it looks at the
+//! [`MAX_CHECKED_FILES`] files read last, skips files larger than
+//! [`MAX_FILE_BYTES`] and stops after [`CHECK_TIMEOUT`]. Files it did not get
+//! to are not reported.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ForkConsistencyReport;
+use codex_protocol::protocol::RolloutItem;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::tools::handlers::read_file;
+use crate::user_instructions::DeveloperInstructions;
+
+const READ_FILE_TOOL: &str = "read_file";
+const MAX_CHECKED_FILES: usize = 64;
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The last recorded read of one file.
+struct RecordedRead {
+    /// Where the fork finds the file.
+    path: PathBuf,
+    /// The call's arguments, naming `path`.
+    arguments: String,
+    output_hash: Vec<u8>,
+}
+
+enum Divergence {
+    Stale,
+    Missing,
+}
+
+/// Re-read the files `items` read, as the fork cut to `items` sees them
+/// from `cwd`.
+pub(crate) async fn check(items: &[RolloutItem], cwd: &Path) -> ForkConsistencyReport {
+    let deadline = Instant::now() + CHECK_TIMEOUT;
+    let mut report = ForkConsistencyReport::default();
+    for read in recorded_reads(items, cwd) {
+        match tokio::time::timeout_at(deadline, recheck(&read)).await {
+            Ok(Some(Divergence::Stale)) => report.stale_files.push(read.path),
+            Ok(Some(Divergence::Missing)) => report.missing_files.push(read.path),
+            Ok(None) => {}
+            Err(_) => {
+                warn!("fork consistency check stopped after {CHECK_TIMEOUT:?}");
+                break;
+            }
+        }
+    }
+    report.stale_files.sort();
+    report.missing_files.sort();
+    report
+}
+
+/// A message asking the model to read the files of `report` again.
+pub(crate) fn stale_notice(report: &ForkConsistencyReport) -> ResponseItem {
+    let mut text = "This conversation was forked from an earlier one. These files changed \
+                    since they were read above; read them again before relying on them:"
+        .to_string();
+    for path in &report.stale_files {
+        text.push_str(&format!("\n- {} (modified)", path.display()));
+    }
+    for path in &report.missing_files {
+        text.push_str(&format!("\n- {} (deleted)", path.display()));
+    }
+    DeveloperInstructions::new(text).into()
+}
+
+/// The last successful read of each file, most recent first.
+fn recorded_reads(items: &[RolloutItem], cwd: &Path) -> Vec<RecordedRead> {
+    let source_cwd = items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.cwd.as_path()),
+        _ => None,
+    });
+    let mut arguments_by_call: HashMap<&str, &str> = HashMap::new();
+    let mut references: HashMap<&str, &str> = HashMap::new();
+    let mut outputs: HashMap<&str, &str> = HashMap::new();
+    let mut reads = Vec::new();
+    for item in items {
+        match item {
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            }) if name == READ_FILE_TOOL => {
+                arguments_by_call.insert(call_id, arguments);
+            }
+            RolloutItem::ReadReference(reference) => {
+                references.insert(&reference.call_id, &reference.original_call_id);
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { call_id, output }) => {
+                let Some(arguments) = arguments_by_call.get(call_id.as_str()) else {
+                    continue;
+                };
+                if output.success == Some(false) {
+                    continue;
+                }
+                // A reference stands for the output of the call it names.
+                let content = match references.get(call_id.as_str()) {
+                    Some(original) => match outputs.get(original) {
+                        Some(content) => *content,
+                        None => continue,
+                    },
+                    None => output.content.as_str(),
+                };
+                outputs.insert(call_id, content);
+                reads.push((*arguments, content));
+            }
+            _ => {}
+        }
+    }
+
+    let mut seen = HashSet::new();
+    reads
+        .into_iter()
+        .rev()
+        .filter_map(|(arguments, content)| {
+            let mut arguments: Value = serde_json::from_str(arguments).ok()?;
+            let recorded = PathBuf::from(arguments.get("file_path")?.as_str()?);
+            let path = match source_cwd.map(|source_cwd| recorded.strip_prefix(source_cwd)) {
+                Some(Ok(relative)) => cwd.join(relative),
+                _ => recorded,
+            };
+            if !seen.insert(path.clone()) {
+                return None;
+            }
+            arguments["file_path"] = Value::String(path.to_string_lossy().into_owned());
+            Some(RecordedRead {
+                path,
+                arguments: arguments.to_string(),
+                output_hash: Sha256::digest(content.as_bytes()).to_vec(),
+            })
+        })
+        .take(MAX_CHECKED_FILES)
+        .collect()
+}
+
+async fn recheck(read: &RecordedRead) -> Option<Divergence> {
+    let metadata = match tokio::fs::metadata(&read.path).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Some(Divergence::Missing),
+        Err(_) => return None,
+    };
+    if metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let Ok(lines) = read_file::read(&read.arguments).await else {
+        return Some(Divergence::Stale);
+    };
+    let hash = Sha256::digest(lines.join("\n").as_bytes());
+    (hash.as_slice() != read.output_hash).then_some(Divergence::Stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn read_call(call_id: &str, path: &Path, output: &str) -> [RolloutItem; 2] {
+        [
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: READ_FILE_TOOL.to_string(),
+                arguments: json!({ "file_path": path }).to_string(),
+                call_id: call_id.to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload {
+                    content: output.to_string(),
+                    ..Default::default()
+                },
+            }),
+        ]
+    }
+
+    #[tokio::test]
+    async fn only_the_last_read_of_each_file_is_checked() {
+        let cwd = tempfile::tempdir().expect("tempdir");
+        let notes = cwd.path().join("notes.txt");
+        std::fs::write(&notes, "second\n").expect("write notes");
+        let gone = cwd.path().join("gone.txt");
+        let items = [
+            read_call("call-1", &notes, "L1: first"),
+            read_call("call-2", &gone, "L1: gone"),
+            read_call("call-3", &notes, "L1: second"),
+        ]
+        .concat();
+
+        let report = check(&items, cwd.path()).await;
+
+        assert_eq!(
+            report,
+            ForkConsistencyReport {
+                stale_files: Vec::new(),
+                missing_files: vec![gone],
+            }
+        );
+    }
+}
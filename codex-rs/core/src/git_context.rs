@@ -0,0 +1,108 @@
+//! Git context of the workspace, kept so each turn of a rollout can be tied to
+//! the commit it ran on.
+//!
+//! The full context is collected when the session starts and written to the
+//! session meta. At the start of every turn only HEAD is read again; when it
+//! moved, the context is collected anew and recorded as a
+//! [`RolloutItem::GitContext`].
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use codex_protocol::protocol::GitInfo;
+use codex_protocol::protocol::RolloutItem;
+
+use crate::git_info::collect_git_info;
+use crate::git_info::head_commit;
+
+#[derive(Debug, Default)]
+pub(crate) struct GitContextTracker {
+    current: Mutex<Option<GitInfo>>,
+}
+
+impl GitContextTracker {
+    pub(crate) fn new(current: Option<GitInfo>) -> Self {
+        Self {
+            current: Mutex::new(current),
+        }
+    }
+
+    /// The context last recorded; `None` outside a git repository.
+    pub(crate) fn current(&self) -> Option<GitInfo> {
+        self.lock_current().clone()
+    }
+
+    /// Collect the context again when HEAD in `cwd` moved since it was last
+    /// recorded, returning it so it can be recorded. Leaving the repository
+    /// clears the context without anything to record.
+    pub(crate) async fn refresh(&self, cwd: &Path) -> Option<GitInfo> {
+        let head = head_commit(cwd).await;
+        let recorded = self.current().and_then(|current| current.commit_hash);
+        if head == recorded {
+            return None;
+        }
+        let collected = collect_git_info(cwd).await;
+        *self.lock_current() = collected.clone();
+        collected
+    }
+
+    fn lock_current(&self) -> std::sync::MutexGuard<'_, Option<GitInfo>> {
+        self.current
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// The git context a rollout last recorded: its latest
+/// [`RolloutItem::GitContext`], or the one in its session meta.
+pub(crate) fn recorded_git_context(items: &[RolloutItem]) -> Option<GitInfo> {
+    items.iter().rev().find_map(|item| match item {
+        RolloutItem::GitContext(context) => Some(context.clone()),
+        RolloutItem::SessionMeta(meta_line) => meta_line.git.clone(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use pretty_assertions::assert_eq;
+
+    fn context(commit: &str) -> GitInfo {
+        GitInfo {
+            commit_hash: Some(commit.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn the_latest_recorded_context_wins_over_the_session_meta() {
+        let meta = RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta::default(),
+            git: Some(context("aaa")),
+        });
+        assert_eq!(
+            recorded_git_context(std::slice::from_ref(&meta)),
+            Some(context("aaa"))
+        );
+        assert_eq!(
+            recorded_git_context(&[
+                meta,
+                RolloutItem::GitContext(context("bbb")),
+                RolloutItem::GitContext(context("ccc")),
+            ]),
+            Some(context("ccc"))
+        );
+        assert_eq!(recorded_git_context(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn non_git_directories_record_nothing() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let tracker = GitContextTracker::default();
+        assert_eq!(tracker.refresh(dir.path()).await, None);
+        assert_eq!(tracker.current(), None);
+    }
+}
@@ -0,0 +1,448 @@
+//! Handoff summaries, for escalating a conversation to a human.
+//!
+//! A handoff is written by a request of its own, outside any turn: the
+//! transcript, grouped by turn, and the diff summary of each turn are sent to
+//! the handoff model, which answers with the sections of a
+//! [`HandoffDocument`] as JSON. The files changed come from the diff
+//! summaries rather than from the model. The document is recorded in the
+//! rollout as a [`RolloutAnnotation::Handoff`], which a resumed session does
+//! not show to the model.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use codex_otel::otel_manager::OtelManager;
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::HandoffDocument;
+use codex_protocol::protocol::RolloutAnnotation;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::SubAgentSource;
+use codex_protocol::protocol::TurnFileChange;
+use codex_protocol::protocol::TurnFileChangeKind;
+use codex_protocol::protocol::TurnId;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::AuthManager;
+use crate::CodexAuth;
+use crate::ModelClient;
+use crate::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::codex::get_last_assistant_message_from_turn;
+use crate::compact::content_items_to_text;
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::models_manager::model_family::find_family_for_model;
+use crate::rollout::RolloutRecorder;
+use crate::rollout::RolloutRecorderParams;
+use crate::rollout::TranscriptTurn;
+use crate::rollout::transcript::read_transcript_turns;
+use crate::structured_output::parse_and_validate;
+use crate::terminal;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+const HANDOFF_PROMPT: &str = include_str!("../templates/handoff/prompt.md");
+
+/// Longest tool call input or output shown to the handoff model, in bytes.
+const TOOL_TEXT_MAX_BYTES: usize = 2_000;
+
+/// How a handoff is written.
+#[derive(Debug, Clone, Default)]
+pub struct HandoffOptions {
+    /// Model that writes the summary. Defaults to `handoff_model` from the
+    /// config, then to the model of the conversation.
+    pub model: Option<String>,
+}
+
+/// What a live conversation needs to write its handoff: the config and
+/// credentials of its session and the model it started with.
+#[derive(Clone)]
+pub(crate) struct HandoffContext {
+    pub(crate) config: Arc<Config>,
+    pub(crate) auth_manager: Arc<AuthManager>,
+    pub(crate) model: String,
+}
+
+/// The sections of a [`HandoffDocument`] the model writes.
+#[derive(Deserialize)]
+struct HandoffSections {
+    goal: String,
+    actions_taken: Vec<String>,
+    failures: Vec<String>,
+    suggested_next_steps: Vec<String>,
+}
+
+/// The model `options` name, else the configured handoff model, else
+/// `conversation_model`.
+pub(crate) fn handoff_model(
+    options: &HandoffOptions,
+    config: &Config,
+    conversation_model: Option<&str>,
+) -> CodexResult<String> {
+    options
+        .model
+        .clone()
+        .or_else(|| config.handoff_model.clone())
+        .or_else(|| conversation_model.map(str::to_string))
+        .ok_or_else(|| {
+            CodexErr::InvalidRequest(
+                "no model to write the handoff with: pass one in the options or set `handoff_model`"
+                    .to_string(),
+            )
+        })
+}
+
+/// Write the handoff of the conversation recorded at `rollout_path` with
+/// `model`. Everything the conversation recorded must be written first.
+pub(crate) async fn generate_handoff(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    conversation_id: ConversationId,
+    rollout_path: &Path,
+    model: String,
+) -> CodexResult<HandoffDocument> {
+    let turns = read_transcript_turns(rollout_path).await?;
+    let diffs = turn_diffs(
+        &RolloutRecorder::get_rollout_history(rollout_path)
+            .await?
+            .get_rollout_items(),
+    );
+    let files_changed = merge_file_changes(diffs.iter().flat_map(|(_, files)| files));
+
+    let schema = sections_schema();
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: render_transcript(&turns, &diffs),
+            }],
+        }],
+        base_instructions_override: Some(HANDOFF_PROMPT.to_string()),
+        output_schema: Some(schema.clone()),
+        ..Default::default()
+    };
+    let client = handoff_client(config, auth_manager, conversation_id, &model);
+    let answer = stream_answer(&client, &prompt).await?;
+    let sections: HandoffSections = serde_json::from_value(
+        parse_and_validate(&schema, &answer)
+            .map_err(|errors| CodexErr::StructuredOutputInvalid { errors })?,
+    )?;
+
+    let markdown = render_markdown(&sections, &files_changed);
+    Ok(HandoffDocument {
+        goal: sections.goal,
+        actions_taken: sections.actions_taken,
+        files_changed,
+        failures: sections.failures,
+        suggested_next_steps: sections.suggested_next_steps,
+        markdown,
+        model,
+    })
+}
+
+pub(crate) fn handoff_item(document: &HandoffDocument) -> RolloutItem {
+    RolloutItem::Annotation(RolloutAnnotation::Handoff(document.clone()))
+}
+
+/// Append `document` to the rollout at `rollout_path`, which no session is
+/// writing.
+pub(crate) async fn record_handoff(
+    config: &Config,
+    rollout_path: &Path,
+    document: &HandoffDocument,
+) -> std::io::Result<()> {
+    let recorder = RolloutRecorder::new(
+        config,
+        RolloutRecorderParams::resume(rollout_path.to_path_buf()),
+    )
+    .await?;
+    recorder.record_items(&[handoff_item(document)]).await?;
+    recorder.shutdown().await
+}
+
+fn handoff_client(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    conversation_id: ConversationId,
+    model: &str,
+) -> ModelClient {
+    let model_family = find_family_for_model(model).with_config_overrides(config);
+    let session_source = SessionSource::SubAgent(SubAgentSource::Other("handoff".to_string()));
+    let auth = auth_manager.auth();
+    let otel_manager = OtelManager::new(
+        conversation_id,
+        model,
+        model_family.slug.as_str(),
+        auth.as_ref().and_then(CodexAuth::get_account_id),
+        auth.as_ref().and_then(CodexAuth::get_account_email),
+        auth.as_ref().map(|auth| auth.mode),
+        config.otel.log_user_prompt,
+        terminal::user_agent(),
+        session_source.clone(),
+    );
+    ModelClient::new(
+        Arc::new(config.clone()),
+        Some(auth_manager),
+        model_family,
+        otel_manager,
+        config.model_provider.clone(),
+        config.model_reasoning_effort,
+        config.model_reasoning_summary,
+        conversation_id,
+        session_source,
+    )
+}
+
+/// Run `prompt` to completion and return the last assistant message.
+async fn stream_answer(client: &ModelClient, prompt: &Prompt) -> CodexResult<String> {
+    let mut stream = client.stream(prompt).await?;
+    let mut items = Vec::new();
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(CodexErr::Stream(
+                "stream closed before response.completed".into(),
+                None,
+            ));
+        };
+        match event? {
+            ResponseEvent::OutputItemDone(item) => items.push(item),
+            ResponseEvent::Completed { .. } => break,
+            _ => {}
+        }
+    }
+    get_last_assistant_message_from_turn(&items).ok_or_else(|| CodexErr::StructuredOutputInvalid {
+        errors: vec!["the model did not answer".to_string()],
+    })
+}
+
+fn sections_schema() -> Value {
+    let list = json!({ "type": "array", "items": { "type": "string" } });
+    json!({
+        "type": "object",
+        "properties": {
+            "goal": { "type": "string" },
+            "actions_taken": list,
+            "failures": list,
+            "suggested_next_steps": list,
+        },
+        "required": ["goal", "actions_taken", "failures", "suggested_next_steps"],
+        "additionalProperties": false,
+    })
+}
+
+/// The files changed by each turn of `items`, from its diff summary.
+fn turn_diffs(items: &[RolloutItem]) -> Vec<(Option<TurnId>, Vec<TurnFileChange>)> {
+    let mut turn_id = None;
+    let mut diffs = Vec::new();
+    for item in items {
+        match item {
+            RolloutItem::TurnStarted(started) => turn_id = Some(started.turn_id),
+            RolloutItem::EventMsg(EventMsg::TurnDiffSummary(summary)) => {
+                diffs.push((turn_id, summary.files.clone()));
+            }
+            _ => {}
+        }
+    }
+    diffs
+}
+
+/// One entry per path, with the line counts of every change added up. A
+/// file added by one turn and modified by a later one stays added.
+fn merge_file_changes<'a>(
+    changes: impl Iterator<Item = &'a TurnFileChange>,
+) -> Vec<TurnFileChange> {
+    let mut merged = BTreeMap::<_, TurnFileChange>::new();
+    for change in changes {
+        merged
+            .entry(change.path.clone())
+            .and_modify(|merged| {
+                merged.additions += change.additions;
+                merged.deletions += change.deletions;
+                merged.outside_cwd |= change.outside_cwd;
+                if !(merged.kind == TurnFileChangeKind::Added
+                    && change.kind == TurnFileChangeKind::Modified)
+                {
+                    merged.kind = change.kind;
+                }
+            })
+            .or_insert_with(|| change.clone());
+    }
+    merged.into_values().collect()
+}
+
+/// The transcript as the handoff model reads it: each turn with what was
+/// said, the tool calls and their outputs, and the files it changed.
+fn render_transcript(
+    turns: &[TranscriptTurn],
+    diffs: &[(Option<TurnId>, Vec<TurnFileChange>)],
+) -> String {
+    let turns: Vec<Value> = turns
+        .iter()
+        .map(|turn| {
+            let files_changed: Vec<&TurnFileChange> = diffs
+                .iter()
+                .filter(|(turn_id, _)| *turn_id == turn.turn_id)
+                .flat_map(|(_, files)| files)
+                .collect();
+            json!({
+                "turn_id": turn.turn_id,
+                "items": turn.items.iter().filter_map(render_item).collect::<Vec<_>>(),
+                "files_changed": files_changed,
+            })
+        })
+        .collect();
+    json!({ "turns": turns }).to_string()
+}
+
+fn render_item(item: &ResponseItem) -> Option<Value> {
+    let tool_text = |text: &str| truncate_text(text, TruncationPolicy::Bytes(TOOL_TEXT_MAX_BYTES));
+    match item {
+        ResponseItem::Message { role, content, .. } if role == "user" || role == "assistant" => {
+            Some(json!({ "role": role, "text": content_items_to_text(content)? }))
+        }
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => Some(json!({ "tool_call": name, "input": tool_text(arguments) })),
+        ResponseItem::CustomToolCall { name, input, .. } => {
+            Some(json!({ "tool_call": name, "input": tool_text(input) }))
+        }
+        ResponseItem::LocalShellCall { action, .. } => Some(json!({
+            "tool_call": "local_shell",
+            "input": tool_text(&serde_json::to_string(action).ok()?),
+        })),
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            Some(json!({ "tool_output": tool_text(&output.content) }))
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => {
+            Some(json!({ "tool_output": tool_text(output) }))
+        }
+        _ => None,
+    }
+}
+
+fn render_markdown(sections: &HandoffSections, files_changed: &[TurnFileChange]) -> String {
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "None.\n".to_string()
+        } else {
+            items.iter().map(|item| format!("- {item}\n")).collect()
+        }
+    };
+    let files = if files_changed.is_empty() {
+        "None.\n".to_string()
+    } else {
+        files_changed
+            .iter()
+            .map(|file| {
+                let kind = match file.kind {
+                    TurnFileChangeKind::Added => "added",
+                    TurnFileChangeKind::Deleted => "deleted",
+                    TurnFileChangeKind::Modified => "modified",
+                };
+                format!(
+                    "- `{}` ({kind}, +{} -{})\n",
+                    file.path.display(),
+                    file.additions,
+                    file.deletions
+                )
+            })
+            .collect()
+    };
+    format!(
+        "# Handoff\n\n## Goal\n\n{}\n\n## Actions taken\n\n{}\n## Files changed\n\n{files}\n## Failures\n\n{}\n## Suggested next steps\n\n{}",
+        sections.goal,
+        list(&sections.actions_taken),
+        list(&sections.failures),
+        list(&sections.suggested_next_steps),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn change(path: &str, kind: TurnFileChangeKind, additions: u64) -> TurnFileChange {
+        TurnFileChange {
+            path: PathBuf::from(path),
+            kind,
+            additions,
+            deletions: 1,
+            outside_cwd: false,
+        }
+    }
+
+    #[test]
+    fn file_changes_are_merged_by_path() {
+        let changes = [
+            change("b.rs", TurnFileChangeKind::Added, 3),
+            change("a.rs", TurnFileChangeKind::Modified, 1),
+            change("b.rs", TurnFileChangeKind::Modified, 2),
+            change("a.rs", TurnFileChangeKind::Deleted, 0),
+        ];
+        assert_eq!(
+            merge_file_changes(changes.iter()),
+            vec![
+                TurnFileChange {
+                    deletions: 2,
+                    ..change("a.rs", TurnFileChangeKind::Deleted, 1)
+                },
+                TurnFileChange {
+                    deletions: 2,
+                    ..change("b.rs", TurnFileChangeKind::Added, 5)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_has_every_section() {
+        let sections = HandoffSections {
+            goal: "Fix the build.".to_string(),
+            actions_taken: vec!["Read the logs.".to_string()],
+            failures: Vec::new(),
+            suggested_next_steps: vec!["Run the tests.".to_string()],
+        };
+        assert_eq!(
+            render_markdown(
+                &sections,
+                &[change("src/lib.rs", TurnFileChangeKind::Modified, 2)]
+            ),
+            "# Handoff\n\n## Goal\n\nFix the build.\n\n## Actions taken\n\n- Read the logs.\n\n## Files changed\n\n- `src/lib.rs` (modified, +2 -1)\n\n## Failures\n\nNone.\n\n## Suggested next steps\n\n- Run the tests.\n"
+        );
+    }
+
+    #[test]
+    fn model_comes_from_the_options_then_the_config() {
+        let mut config = crate::config::test_config();
+        config.handoff_model = Some("configured".to_string());
+        let options = HandoffOptions {
+            model: Some("requested".to_string()),
+        };
+        assert_eq!(
+            handoff_model(&options, &config, Some("session")).ok(),
+            Some("requested".to_string())
+        );
+        assert_eq!(
+            handoff_model(&HandoffOptions::default(), &config, Some("session")).ok(),
+            Some("configured".to_string())
+        );
+        config.handoff_model = None;
+        assert_eq!(
+            handoff_model(&HandoffOptions::default(), &config, Some("session")).ok(),
+            Some("session".to_string())
+        );
+        assert!(handoff_model(&HandoffOptions::default(), &config, None).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Keepalive events for turns that are busy but silent, e.g. while the model
+//! reasons with summaries hidden or a tool runs for a long time, so clients
+//! (and proxies in between) can tell a working turn from a hung one.
+
+use std::future::Future;
+use std::time::Duration;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::HeartbeatEvent;
+use codex_protocol::protocol::HeartbeatPhase;
+use tokio::time::Instant;
+use tokio::time::MissedTickBehavior;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+
+/// Await `fut`, emitting a [`HeartbeatEvent`] for `phase` every configured
+/// interval until it completes.
+pub(crate) async fn while_pending<F>(
+    sess: &Session,
+    turn_context: &TurnContext,
+    phase: HeartbeatPhase,
+    fut: F,
+) -> F::Output
+where
+    F: Future,
+{
+    let interval = turn_context.client.config().heartbeat_interval;
+    while_pending_every(sess, turn_context, phase, interval, fut).await
+}
+
+async fn while_pending_every<F>(
+    sess: &Session,
+    turn_context: &TurnContext,
+    phase: HeartbeatPhase,
+    interval: Option<Duration>,
+    fut: F,
+) -> F::Output
+where
+    F: Future,
+{
+    let Some(period) = interval.filter(|period| !period.is_zero()) else {
+        return fut.await;
+    };
+    let started = Instant::now();
+    let mut ticks = tokio::time::interval_at(started + period, period);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            biased;
+            output = &mut fut => return output,
+            _ = ticks.tick() => {
+                let elapsed_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+                let event = EventMsg::Heartbeat(HeartbeatEvent {
+                    phase: phase.clone(),
+                    elapsed_ms,
+                });
+                sess.send_event(turn_context, event).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::make_session_and_context_with_rx;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test(start_paused = true)]
+    async fn heartbeats_follow_cadence_and_stop_when_output_resumes() {
+        let (sess, turn_context, rx) = make_session_and_context_with_rx().await;
+        while rx.try_recv().is_ok() {}
+
+        // A provider that stalls for 25s before producing output.
+        let output = while_pending_every(
+            &sess,
+            &turn_context,
+            HeartbeatPhase::WaitingForModel,
+            Some(Duration::from_secs(10)),
+            async {
+                tokio::time::sleep(Duration::from_secs(25)).await;
+                "output"
+            },
+        )
+        .await;
+        assert_eq!(output, "output");
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        let mut heartbeats = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let EventMsg::Heartbeat(heartbeat) = event.msg {
+                heartbeats.push((heartbeat.phase, heartbeat.elapsed_ms));
+            }
+        }
+        assert_eq!(
+            heartbeats,
+            vec![
+                (HeartbeatPhase::WaitingForModel, 10_000),
+                (HeartbeatPhase::WaitingForModel, 20_000),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn disabled_interval_emits_nothing() {
+        let (sess, turn_context, rx) = make_session_and_context_with_rx().await;
+        while rx.try_recv().is_ok() {}
+
+        while_pending_every(
+            &sess,
+            &turn_context,
+            HeartbeatPhase::ExecutingTool {
+                call_id: "call-1".to_string(),
+            },
+            None,
+            tokio::time::sleep(Duration::from_secs(60)),
+        )
+        .await;
+
+        assert!(rx.try_recv().is_err());
+    }
+}
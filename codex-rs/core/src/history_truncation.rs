@@ -0,0 +1,528 @@
+//! Helpers for cutting a conversation history at a given position, shared by
+//! forking (cut at a turn id, or at the nth user message), checkpoint rollback
+//! (cut at a recorded item index), and resuming onto a smaller context window
+//! (drop the oldest turns, cut at the same user messages as forks).
+//!
+//! Turn ids are recorded in the rollout and never reused, so a cut at a turn
+//! id lands on the same turn whatever happened to the history since the id
+//! was handed out. Ordinals count user messages in the rollout, rolled back
+//! ones included, which is not what a client counting the turns it shows
+//! sees once the history was rolled back.
+
+use std::ops::Range;
+
+use crate::classify::ItemClass;
+use crate::classify::classify_item;
+use crate::compact::is_compaction_summary;
+use crate::event_mapping::parse_turn_item;
+use crate::reasoning_log::without_raw_content;
+use codex_protocol::items::TurnItem;
+use codex_protocol::items::UserMessageItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::ResumeTrimReport;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::TurnId;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub(crate) enum TruncationError {
+    /// There is no user message at `requested`; there are `available` of them.
+    #[error("no user message at position {requested}: the history has {available}")]
+    OutOfRange { requested: usize, available: usize },
+    /// The history records no turn with this id.
+    #[error("no turn {0} in the history")]
+    UnknownTurn(TurnId),
+}
+
+/// Return the items strictly before `index`. Indices past the end keep every
+/// item.
+pub(crate) fn truncate_at_index<T>(mut items: Vec<T>, index: usize) -> Vec<T> {
+    items.truncate(index);
+    items
+}
+
+/// An item of a history that cuts are found in. References to items left in
+/// the rollout say whether they are cut points, so a history holding them is
+/// cut without reading them back.
+pub(crate) trait CutPoint {
+    /// Whether forks can cut at this item, see [`ItemClass::UserMessage`].
+    fn is_user_message(&self) -> bool;
+}
+
+impl CutPoint for ResponseItem {
+    fn is_user_message(&self) -> bool {
+        classify_item(self) == ItemClass::UserMessage
+    }
+}
+
+impl CutPoint for RolloutItem {
+    fn is_user_message(&self) -> bool {
+        matches!(self, RolloutItem::ResponseItem(item) if item.is_user_message())
+    }
+}
+
+/// Indices of the user messages in `items`, in order. The nth entry is where
+/// forking at the nth user message cuts.
+pub(crate) fn user_message_positions<T: CutPoint>(items: &[T]) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.is_user_message())
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Position of the marker that starts turn `turn_id` in `items`.
+fn turn_position(items: &[RolloutItem], turn_id: TurnId) -> Option<usize> {
+    items.iter().position(
+        |item| matches!(item, RolloutItem::TurnStarted(started) if started.turn_id == turn_id),
+    )
+}
+
+/// The id of the latest turn recorded in `items`, which the next turn
+/// follows.
+pub(crate) fn last_turn_id(items: &[RolloutItem]) -> Option<TurnId> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::TurnStarted(started) => Some(started.turn_id),
+            _ => None,
+        })
+        .max()
+}
+
+/// The user message `item` holds, if it is one that forks can cut at.
+pub(crate) fn user_message_at(item: &RolloutItem) -> Option<UserMessageItem> {
+    match item {
+        RolloutItem::ResponseItem(response_item) if item.is_user_message() => {
+            match parse_turn_item(response_item) {
+                Some(TurnItem::UserMessage(message)) => Some(message),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return a prefix of `items` obtained by cutting strictly before the nth user message
+/// (0-based) and all items that follow it. Items this build cannot parse
+/// ([`RolloutItem::Unknown`]) are never cut points and are kept like any other
+/// item before the cut.
+///
+/// Lenient: when there is no nth user message the result is
+/// [`InitialHistory::New`]. The crate's forks use the strict
+/// `try_truncate_before_nth_user_message`, which treats that as an error.
+pub fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> InitialHistory {
+    try_truncate_before_nth_user_message(history, n).unwrap_or(InitialHistory::New)
+}
+
+/// Like [`truncate_before_nth_user_message`], but fails when the history has
+/// `n` or fewer user messages.
+pub(crate) fn try_truncate_before_nth_user_message(
+    history: InitialHistory,
+    n: usize,
+) -> Result<InitialHistory, TruncationError> {
+    // Work directly on rollout items, and cut the vector at the nth user message input.
+    let items: Vec<RolloutItem> = history.get_rollout_items();
+
+    let user_positions = user_message_positions(&items);
+
+    let Some(&position) = user_positions.get(n) else {
+        return Err(TruncationError::OutOfRange {
+            requested: n,
+            available: user_positions.len(),
+        });
+    };
+
+    // Cut strictly before the nth user message (do not keep the nth itself).
+    Ok(fork_prefix(items, position))
+}
+
+/// Return the items of `items` strictly before the start of turn `turn_id`,
+/// as a fork starts from them. Fails when `items` record no such turn, for
+/// instance when they were written before turns had ids.
+pub(crate) fn truncate_rollout_before_turn_id(
+    items: Vec<RolloutItem>,
+    turn_id: TurnId,
+) -> Result<InitialHistory, TruncationError> {
+    let Some(position) = turn_position(&items, turn_id) else {
+        return Err(TruncationError::UnknownTurn(turn_id));
+    };
+    Ok(fork_prefix(items, position))
+}
+
+/// The items before `position`, as the history of a fork. The fork sends the
+/// kept items back to the model, without raw reasoning.
+fn fork_prefix(items: Vec<RolloutItem>, position: usize) -> InitialHistory {
+    let rolled: Vec<RolloutItem> = truncate_at_index(items, position)
+        .into_iter()
+        .map(|item| match item {
+            RolloutItem::ResponseItem(item) => RolloutItem::ResponseItem(without_raw_content(item)),
+            item => item,
+        })
+        .collect();
+
+    if rolled.is_empty() {
+        InitialHistory::New
+    } else {
+        InitialHistory::Forked(rolled)
+    }
+}
+
+/// Fit `items` into a context window of `window_tokens` by dropping the
+/// oldest turns, where `item_tokens` sizes each item of the prompt and
+/// `base_tokens` what the prompt takes besides them. Turns start at the user
+/// messages forks cut at; what precedes the first one, such as the initial
+/// context, is always kept, and so is the last turn, even when it does not fit
+/// on its own.
+///
+/// Returns the range of `items` to drop, if any, with the report.
+pub(crate) fn fit_to_context_window(
+    items: &[ResponseItem],
+    window_tokens: Option<i64>,
+    base_tokens: i64,
+    item_tokens: impl Fn(&ResponseItem) -> i64,
+) -> (Option<Range<usize>>, ResumeTrimReport) {
+    let positions = user_message_positions(items);
+    let turns = positions.len();
+    // `prefix_tokens[index]` sizes `items[..index]`, so dropping turns is a
+    // subtraction rather than a new estimate.
+    let mut prefix_tokens = Vec::with_capacity(items.len() + 1);
+    prefix_tokens.push(0i64);
+    for item in items {
+        let total = prefix_tokens[prefix_tokens.len() - 1];
+        prefix_tokens.push(total.saturating_add(item_tokens(item)));
+    }
+    let total_tokens = base_tokens.saturating_add(prefix_tokens[items.len()]);
+    let dropped_range = |dropped: usize| match dropped {
+        0 => None,
+        dropped => Some(positions[0]..positions[dropped]),
+    };
+    let estimate_without = |dropped: usize| match dropped_range(dropped) {
+        None => total_tokens,
+        Some(range) => total_tokens - (prefix_tokens[range.end] - prefix_tokens[range.start]),
+    };
+    let fits = |tokens: i64| window_tokens.is_none_or(|window| tokens <= window);
+
+    let droppable = turns.saturating_sub(1);
+    let dropped = (0..droppable)
+        .find(|&dropped| fits(estimate_without(dropped)))
+        .unwrap_or(droppable);
+    let range = dropped_range(dropped);
+    let compacted = items.iter().enumerate().any(|(index, item)| {
+        is_compaction_summary(item) && !range.as_ref().is_some_and(|range| range.contains(&index))
+    });
+    let report = ResumeTrimReport {
+        turns_restored: turns - dropped,
+        turns_dropped: dropped,
+        compacted,
+        window_tokens,
+        estimated_prompt_tokens: estimate_without(dropped),
+    };
+    (range, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::make_session_and_context;
+    use assert_matches::assert_matches;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ReasoningItemReasoningSummary;
+    use codex_protocol::protocol::TurnStartedItem;
+    use pretty_assertions::assert_eq;
+
+    fn user_msg(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+    fn assistant_msg(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn drops_from_last_user_only() {
+        let items = [
+            user_msg("u1"),
+            assistant_msg("a1"),
+            assistant_msg("a2"),
+            user_msg("u2"),
+            assistant_msg("a3"),
+            ResponseItem::Reasoning {
+                id: "r1".to_string(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "s".to_string(),
+                }],
+                content: None,
+                encrypted_content: None,
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "tool".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "c1".to_string(),
+            },
+            assistant_msg("a4"),
+        ];
+
+        // Wrap as InitialHistory::Forked with response items only.
+        let initial: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+        let truncated = truncate_before_nth_user_message(InitialHistory::Forked(initial), 1);
+        let got_items = truncated.get_rollout_items();
+        let expected_items = vec![
+            RolloutItem::ResponseItem(items[0].clone()),
+            RolloutItem::ResponseItem(items[1].clone()),
+            RolloutItem::ResponseItem(items[2].clone()),
+        ];
+        assert_eq!(
+            serde_json::to_value(&got_items).unwrap(),
+            serde_json::to_value(&expected_items).unwrap()
+        );
+
+        let initial2: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+        let truncated2 = truncate_before_nth_user_message(InitialHistory::Forked(initial2), 2);
+        assert_matches!(truncated2, InitialHistory::New);
+    }
+
+    #[tokio::test]
+    async fn ignores_session_prefix_messages_when_truncating() {
+        let (session, turn_context) = make_session_and_context().await;
+        let mut items = session.build_initial_context(&turn_context);
+        items.push(user_msg("feature request"));
+        items.push(assistant_msg("ack"));
+        items.push(user_msg("second question"));
+        items.push(assistant_msg("answer"));
+
+        let rollout_items: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+
+        let truncated = truncate_before_nth_user_message(InitialHistory::Forked(rollout_items), 1);
+        let got_items = truncated.get_rollout_items();
+
+        let expected: Vec<RolloutItem> = vec![
+            RolloutItem::ResponseItem(items[0].clone()),
+            RolloutItem::ResponseItem(items[1].clone()),
+            RolloutItem::ResponseItem(items[2].clone()),
+        ];
+
+        assert_eq!(
+            serde_json::to_value(&got_items).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+    }
+
+    fn history(items: &[ResponseItem]) -> InitialHistory {
+        InitialHistory::Forked(
+            items
+                .iter()
+                .cloned()
+                .map(RolloutItem::ResponseItem)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn strict_truncation_reports_out_of_range_fork_points() {
+        let items = [
+            assistant_msg("a0"),
+            user_msg("u1"),
+            assistant_msg("a1"),
+            user_msg("u2"),
+            assistant_msg("a2"),
+        ];
+
+        // Exact boundary: the last user message is still a valid cut point.
+        let strict = try_truncate_before_nth_user_message(history(&items), 1)
+            .expect("the last user message is in range");
+        let lenient = truncate_before_nth_user_message(history(&items), 1);
+        let expected: Vec<RolloutItem> = items[..3]
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+        assert_eq!(
+            serde_json::to_value(strict.get_rollout_items()).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(lenient.get_rollout_items()).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        // One past the last user message.
+        assert_eq!(
+            try_truncate_before_nth_user_message(history(&items), 2).unwrap_err(),
+            TruncationError::OutOfRange {
+                requested: 2,
+                available: 2,
+            }
+        );
+        assert_matches!(
+            truncate_before_nth_user_message(history(&items), 2),
+            InitialHistory::New
+        );
+
+        // A rollout without user turns has no cut point at all.
+        let no_turns = [assistant_msg("a0")];
+        assert_eq!(
+            try_truncate_before_nth_user_message(history(&no_turns), 0).unwrap_err(),
+            TruncationError::OutOfRange {
+                requested: 0,
+                available: 0,
+            }
+        );
+        assert_matches!(
+            truncate_before_nth_user_message(history(&no_turns), 0),
+            InitialHistory::New
+        );
+    }
+
+    #[test]
+    fn turn_ids_cut_before_the_turn_start() {
+        let started = |turn_id: u64| {
+            RolloutItem::TurnStarted(TurnStartedItem {
+                turn_id: TurnId::new(turn_id),
+            })
+        };
+        let items = vec![
+            RolloutItem::ResponseItem(assistant_msg("initial context")),
+            started(1),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            RolloutItem::ResponseItem(assistant_msg("a1")),
+            started(2),
+            RolloutItem::ResponseItem(user_msg("u2")),
+        ];
+        assert_eq!(last_turn_id(&items), Some(TurnId::new(2)));
+
+        let truncated = truncate_rollout_before_turn_id(items.clone(), TurnId::new(2))
+            .expect("turn 2 is recorded");
+        assert_eq!(
+            serde_json::to_value(truncated.get_rollout_items()).unwrap(),
+            serde_json::to_value(&items[..4]).unwrap()
+        );
+
+        assert_eq!(
+            truncate_rollout_before_turn_id(items, TurnId::new(3)).unwrap_err(),
+            TruncationError::UnknownTurn(TurnId::new(3))
+        );
+        assert_eq!(last_turn_id(&[]), None);
+    }
+
+    #[test]
+    fn truncate_at_index_keeps_prefix() {
+        let items = vec![user_msg("u1"), assistant_msg("a1"), user_msg("u2")];
+
+        let truncated = truncate_at_index(items.clone(), 2);
+        assert_eq!(
+            serde_json::to_value(&truncated).unwrap(),
+            serde_json::to_value(&items[..2]).unwrap()
+        );
+
+        let untouched = truncate_at_index(items.clone(), 10);
+        assert_eq!(
+            serde_json::to_value(&untouched).unwrap(),
+            serde_json::to_value(&items).unwrap()
+        );
+    }
+
+    #[test]
+    fn oldest_turns_are_dropped_until_the_history_fits() {
+        let items = vec![
+            assistant_msg("initial context"),
+            user_msg("u1"),
+            assistant_msg("a1"),
+            user_msg("u2"),
+            assistant_msg("a2"),
+            user_msg("u3"),
+            assistant_msg("a3"),
+        ];
+        // Every item takes ten tokens.
+        let estimate = |_: &ResponseItem| 10;
+
+        let (dropped, report) = fit_to_context_window(&items, Some(45), 0, estimate);
+        assert_eq!(dropped, Some(1..5));
+        assert_eq!(
+            report,
+            ResumeTrimReport {
+                turns_restored: 1,
+                turns_dropped: 2,
+                compacted: false,
+                window_tokens: Some(45),
+                estimated_prompt_tokens: 30,
+            }
+        );
+
+        // The last turn is kept even when it does not fit.
+        let (dropped, report) = fit_to_context_window(&items, Some(5), 0, estimate);
+        assert_eq!(dropped, Some(1..5));
+        assert_eq!(report.estimated_prompt_tokens, 30);
+
+        for window_tokens in [None, Some(70)] {
+            let (dropped, report) = fit_to_context_window(&items, window_tokens, 0, estimate);
+            assert_eq!(dropped, None);
+            assert_eq!((report.turns_restored, report.turns_dropped), (3, 0));
+        }
+    }
+
+    #[test]
+    fn unknown_items_are_not_cut_points() {
+        let unknown = || RolloutItem::Unknown {
+            raw: serde_json::json!({"type": "hologram", "payload": {"frames": 3}}),
+        };
+        let items = vec![
+            unknown(),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            unknown(),
+            RolloutItem::ResponseItem(assistant_msg("a1")),
+            RolloutItem::ResponseItem(user_msg("u2")),
+        ];
+
+        let truncated =
+            try_truncate_before_nth_user_message(InitialHistory::Forked(items), 1).unwrap();
+        let kinds: Vec<&str> = truncated
+            .get_rollout_items()
+            .iter()
+            .map(|item| match item {
+                RolloutItem::Unknown { .. } => "unknown",
+                RolloutItem::ResponseItem(ResponseItem::Message { role, .. }) if role == "user" => {
+                    "user"
+                }
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["unknown", "user", "unknown", "other"]);
+
+        // Unknown items alone have no user message to cut at.
+        assert_eq!(
+            try_truncate_before_nth_user_message(InitialHistory::Forked(vec![unknown()]), 0)
+                .unwrap_err(),
+            TruncationError::OutOfRange {
+                requested: 0,
+                available: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Reading the instruction files again between turns, as configured by
+//! [`RefreshPolicy`], so edits made to `AGENTS.md` during a session reach the
+//! model.
+//!
+//! The instructions are read from the `AGENTS.md` in codex home and the
+//! project docs found from the session's working directory. Under
+//! [`RefreshPolicy::OnChange`] the paths and contents of those files are hashed
+//! before each turn and the instructions are rebuilt only when the hash moved.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing::warn;
+
+use crate::config::Config;
+use crate::config::types::RefreshPolicy;
+use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
+use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
+use crate::project_doc::discover_project_doc_paths;
+use crate::project_doc::get_user_instructions;
+use crate::skills::SkillMetadata;
+
+/// Instructions read again from their files.
+#[derive(Debug)]
+pub(crate) struct ReloadedInstructions {
+    pub(crate) sources: Vec<PathBuf>,
+    pub(crate) user_instructions: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct InstructionsWatcher {
+    policy: RefreshPolicy,
+    /// Skills rendered into the instructions when the session started.
+    skills: Option<Vec<SkillMetadata>>,
+    /// Hash of the instruction files the current instructions were read from.
+    fingerprint: Mutex<u64>,
+}
+
+impl InstructionsWatcher {
+    /// `config` must be the one the session's instructions were built from.
+    pub(crate) async fn new(config: &Config, skills: Option<Vec<SkillMetadata>>) -> Self {
+        let fingerprint = match config.instructions_refresh {
+            RefreshPolicy::OnChange => fingerprint(&instruction_sources(config).await).await,
+            RefreshPolicy::Never | RefreshPolicy::EveryTurn => 0,
+        };
+        Self {
+            policy: config.instructions_refresh,
+            skills,
+            fingerprint: Mutex::new(fingerprint),
+        }
+    }
+
+    /// Rebuild the instructions for a turn run with `config` when the policy
+    /// calls for it. `None` means the current instructions stay.
+    pub(crate) async fn check(&self, config: &Config) -> Option<ReloadedInstructions> {
+        if self.policy == RefreshPolicy::Never {
+            return None;
+        }
+        let sources = instruction_sources(config).await;
+        if self.policy == RefreshPolicy::OnChange {
+            let next = fingerprint(&sources).await;
+            let mut current = self
+                .fingerprint
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if *current == next {
+                return None;
+            }
+            *current = next;
+        }
+
+        let mut config = config.clone();
+        config.user_instructions = Config::load_instructions(Some(&config.codex_home));
+        let user_instructions = get_user_instructions(&config, self.skills.as_deref()).await;
+        Some(ReloadedInstructions {
+            sources,
+            user_instructions,
+        })
+    }
+}
+
+/// The files the instructions are read from, in the order they are read.
+async fn instruction_sources(config: &Config) -> Vec<PathBuf> {
+    let mut sources = Vec::new();
+    for name in [LOCAL_PROJECT_DOC_FILENAME, DEFAULT_PROJECT_DOC_FILENAME] {
+        let path = config.codex_home.join(name);
+        if tokio::fs::read_to_string(&path)
+            .await
+            .is_ok_and(|contents| !contents.trim().is_empty())
+        {
+            sources.push(path);
+            break;
+        }
+    }
+    match discover_project_doc_paths(config) {
+        Ok(paths) => sources.extend(paths),
+        Err(err) => warn!("failed to discover project docs: {err}"),
+    }
+    sources
+}
+
+async fn fingerprint(sources: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for path in sources {
+        path.hash(&mut hasher);
+        tokio::fs::read(path).await.ok().hash(&mut hasher);
+    }
+    hasher.finish()
+}
@@ -0,0 +1,127 @@
+//! A small JSON file under `$CODEX_HOME` that several sessions update.
+//!
+//! Updates are read-modify-write cycles serialized per file within this
+//! process, and written through a `.tmp` sibling that is renamed over the
+//! file so that readers never see a partial write.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex as StdMutex;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+/// The update lock of every store file opened in this process.
+static STORE_LOCKS: LazyLock<StdMutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(|| StdMutex::new(HashMap::new()));
+
+/// A JSON file holding a `T`, which reads as `T::default()` while missing.
+#[derive(Debug)]
+pub(crate) struct JsonStore<T> {
+    path: PathBuf,
+    _contents: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for JsonStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            _contents: PhantomData,
+        }
+    }
+}
+
+impl<T> JsonStore<T>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            _contents: PhantomData,
+        }
+    }
+
+    pub(crate) async fn load(&self) -> std::io::Result<T> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(text) => serde_json::from_str(&text).map_err(std::io::Error::other),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(T::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Apply `update` to the stored value and save it when `update` returns
+    /// `true`, which is also what this returns.
+    pub(crate) async fn update(
+        &self,
+        update: impl FnOnce(&mut T) -> bool,
+    ) -> std::io::Result<bool> {
+        let lock = self.lock();
+        let _guard = lock.lock().await;
+        let mut contents = self.load().await?;
+        if !update(&mut contents) {
+            return Ok(false);
+        }
+        self.save(&contents).await?;
+        Ok(true)
+    }
+
+    fn lock(&self) -> Arc<Mutex<()>> {
+        let mut locks = STORE_LOCKS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Arc::clone(locks.entry(self.path.clone()).or_default())
+    }
+
+    async fn save(&self, contents: &T) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_string_pretty(contents)?;
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, json).await?;
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn concurrent_updates_through_separate_stores_all_land() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let path = codex_home.path().join("counts.json");
+
+        let updates = (0..16).map(|_| {
+            let store = JsonStore::<BTreeMap<String, u64>>::new(path.clone());
+            async move {
+                store
+                    .update(|counts| {
+                        *counts.entry("turns".to_string()).or_default() += 1;
+                        true
+                    })
+                    .await
+            }
+        });
+        for result in futures::future::join_all(updates).await {
+            result?;
+        }
+
+        let store = JsonStore::<BTreeMap<String, u64>>::new(path);
+        assert_eq!(
+            store.load().await?,
+            BTreeMap::from([("turns".to_string(), 16)])
+        );
+        assert!(!store.update(|_| false).await?);
+        Ok(())
+    }
+}
@@ -5,32 +5,59 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+pub mod adaptive_effort;
 pub mod api_bridge;
 mod apply_patch;
 pub mod auth;
 pub mod bash;
+pub mod blob_store;
+pub mod child_conversations;
+pub mod classify;
 mod client;
 mod client_common;
 pub mod codex;
 mod codex_conversation;
 mod compact_remote;
 pub use codex_conversation::CodexConversation;
+pub mod codex_home;
+pub use codex_home::CodexHome;
+mod codex_runtime;
+pub use codex_runtime::CodexRuntime;
 mod codex_delegate;
 mod command_safety;
 pub mod config;
 pub mod config_loader;
 mod context_manager;
+pub mod context_usage;
+mod correlation;
 pub mod custom_prompts;
+pub mod delta_coalescing;
+mod endpoint_failover;
 pub mod env;
 mod environment_context;
 pub mod error;
+pub mod event_replay;
+mod event_subscriptions;
+pub use event_subscriptions::EventSubscription;
 pub mod exec;
 pub mod exec_env;
 mod exec_policy;
+#[cfg(feature = "test-support")]
+pub mod fault_injection;
 pub mod features;
 mod flags;
+mod fork_consistency;
+mod git_context;
 pub mod git_info;
+pub mod handoff;
+mod heartbeat;
+mod history_truncation;
+mod instructions_refresh;
+mod json_store;
 pub mod landlock;
+mod manager_load;
+#[cfg(feature = "metrics")]
+pub mod manager_metrics;
 pub mod mcp;
 mod mcp_connection_manager;
 pub mod models_manager;
@@ -41,14 +68,20 @@ mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
 pub mod parse_command;
+pub mod partial_messages;
 pub mod path_utils;
+mod pause;
+pub mod post_process;
 pub mod powershell;
+pub mod pre_process;
 pub mod sandboxing;
+mod sampling;
 mod stream_events_utils;
 mod text_encoding;
 pub mod token_data;
 mod truncate;
 mod unified_exec;
+mod user_context;
 mod user_instructions;
 pub use model_provider_info::CHAT_WIRE_API_DEPRECATION_SUMMARY;
 pub use model_provider_info::DEFAULT_LMSTUDIO_PORT;
@@ -64,40 +97,74 @@ mod event_mapping;
 pub mod review_format;
 pub mod review_prompts;
 pub use codex_protocol::protocol::InitialHistory;
+pub use history_truncation::truncate_before_nth_user_message;
 pub use conversation_manager::ConversationManager;
+pub use conversation_manager::FailedSpawnStats;
+pub use manager_load::ManagerLoad;
+#[cfg(feature = "metrics")]
+pub use manager_metrics::ManagerMetrics;
 pub use conversation_manager::NewConversation;
+pub use conversation_manager::RemovedConversation;
 // Re-export common auth types for workspace consumers
 pub use auth::AuthManager;
 pub use auth::CodexAuth;
 pub mod default_client;
+mod project_approvals;
+pub mod project_notes;
 pub mod project_doc;
-mod rollout;
+#[cfg(feature = "test-support")]
+pub mod provider_cassette;
+mod reasoning_log;
+pub mod request_trace;
+pub mod retry;
+pub mod rollout;
+pub mod runtime_env;
+pub(crate) mod safe_mode;
 pub(crate) mod safety;
+pub mod script_run;
 pub mod seatbelt;
+pub mod sensitive_input;
+pub mod session_profiles;
+mod session_stats;
 pub mod shell;
 pub mod shell_snapshot;
 pub mod skills;
 pub mod spawn;
+pub mod spawn_observer;
+mod structured_output;
 pub mod terminal;
 mod tools;
+mod transcript_journal;
+pub use transcript_journal::TranscriptFootprint;
+mod turn_deadline;
 pub mod turn_diff_tracker;
+mod turn_tmpdir;
+pub mod turn_results;
+mod turn_tally;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
+pub use rollout::DeletionReport;
 pub use rollout::INTERACTIVE_SESSION_SOURCES;
+pub use rollout::ReadOnlyTranscript;
 pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::TranscriptTurn;
 pub use rollout::find_conversation_path_by_id_str;
+pub use rollout::forks::ForkedChild;
+pub use rollout::latest::SessionSummary;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
 pub use rollout::list::parse_cursor;
 pub use rollout::list::read_head_for_summary;
+pub use rollout::rollout_parts;
 mod function_tool;
 mod state;
 mod tasks;
 mod user_notification;
 mod user_shell_command;
 pub mod util;
+mod workspace_watch;
 
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
 pub use command_safety::is_dangerous_command;
@@ -0,0 +1,256 @@
+//! Load of a [`ConversationManager`], for routing new conversations across
+//! processes, see [`ConversationManager::load`].
+//!
+//! Every session updates its [`ConversationLoad`] as turns start and end, as
+//! it pauses and resumes, and as submissions are queued and picked up. The
+//! manager attaches it to its [`LoadCounters`] under the lock of its
+//! conversations map, when it starts tracking the conversation, and detaches
+//! it under the same lock when it stops, so the counters always describe
+//! the conversations in the map. Reading them takes no lock.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+//! [`ConversationManager::load`]: crate::ConversationManager::load
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use tokio::sync::Notify;
+
+/// Conversations a manager tracks, by what they are doing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ManagerLoad {
+    /// Conversations tracked: `idle + turn_in_flight + paused`.
+    pub total: usize,
+    /// Conversations waiting for input.
+    pub idle: usize,
+    /// Conversations running a turn.
+    pub turn_in_flight: usize,
+    /// Conversations paused with `CodexConversation::pause`.
+    pub paused: usize,
+    /// Submissions sent to tracked conversations that their sessions have
+    /// not picked up yet.
+    pub queued_submissions: usize,
+}
+
+/// Counters of one manager, see the module docs.
+#[derive(Default)]
+pub(crate) struct LoadCounters {
+    idle: AtomicUsize,
+    turn_in_flight: AtomicUsize,
+    paused: AtomicUsize,
+    queued_submissions: AtomicUsize,
+    /// Notified whenever `turn_in_flight` drops.
+    turn_ended: Notify,
+}
+
+impl LoadCounters {
+    pub(crate) fn snapshot(&self) -> ManagerLoad {
+        let idle = self.idle.load(Ordering::Acquire);
+        let turn_in_flight = self.turn_in_flight.load(Ordering::Acquire);
+        let paused = self.paused.load(Ordering::Acquire);
+        ManagerLoad {
+            total: idle + turn_in_flight + paused,
+            idle,
+            turn_in_flight,
+            paused,
+            queued_submissions: self.queued_submissions.load(Ordering::Acquire),
+        }
+    }
+
+    /// Resolve once fewer than `max_in_flight` conversations run a turn.
+    pub(crate) async fn wait_for_capacity(&self, max_in_flight: usize) {
+        loop {
+            let turn_ended = self.turn_ended.notified();
+            tokio::pin!(turn_ended);
+            // Registered before the check, so a turn ending in between wakes
+            // this waiter.
+            turn_ended.as_mut().enable();
+            if self.turn_in_flight.load(Ordering::Acquire) < max_in_flight {
+                return;
+            }
+            turn_ended.await;
+        }
+    }
+
+    fn counter(&self, state: State) -> &AtomicUsize {
+        match state {
+            State::Idle => &self.idle,
+            State::TurnInFlight => &self.turn_in_flight,
+            State::Paused => &self.paused,
+        }
+    }
+
+    fn enter(&self, state: State) {
+        self.counter(state).fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn leave(&self, state: State) {
+        self.counter(state).fetch_sub(1, Ordering::AcqRel);
+        if state == State::TurnInFlight {
+            self.turn_ended.notify_waiters();
+        }
+    }
+
+    fn add_queued(&self, submissions: usize) {
+        self.queued_submissions
+            .fetch_add(submissions, Ordering::AcqRel);
+    }
+
+    fn remove_queued(&self, submissions: usize) {
+        self.queued_submissions
+            .fetch_sub(submissions, Ordering::AcqRel);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    TurnInFlight,
+    Paused,
+}
+
+/// What one conversation adds to the load of the manager tracking it.
+#[derive(Default)]
+pub(crate) struct ConversationLoad {
+    inner: Mutex<ConversationLoadInner>,
+}
+
+#[derive(Default)]
+struct ConversationLoadInner {
+    /// The counters of the manager tracking the conversation, if any.
+    counters: Option<Arc<LoadCounters>>,
+    turn_in_flight: bool,
+    paused: bool,
+    queued_submissions: usize,
+}
+
+impl ConversationLoadInner {
+    fn state(&self) -> State {
+        if self.paused {
+            State::Paused
+        } else if self.turn_in_flight {
+            State::TurnInFlight
+        } else {
+            State::Idle
+        }
+    }
+
+    fn detach(&mut self) {
+        if let Some(counters) = self.counters.take() {
+            counters.leave(self.state());
+            counters.remove_queued(self.queued_submissions);
+        }
+    }
+
+    /// Apply `change`, moving the conversation between counters as its
+    /// state changes.
+    fn update(&mut self, change: impl FnOnce(&mut Self)) {
+        let (state, queued) = (self.state(), self.queued_submissions);
+        change(self);
+        let Some(counters) = &self.counters else {
+            return;
+        };
+        if self.state() != state {
+            counters.enter(self.state());
+            counters.leave(state);
+        }
+        if self.queued_submissions > queued {
+            counters.add_queued(self.queued_submissions - queued);
+        } else if self.queued_submissions < queued {
+            counters.remove_queued(queued - self.queued_submissions);
+        }
+    }
+}
+
+impl ConversationLoad {
+    /// Count the conversation in `counters` from now on.
+    pub(crate) fn attach(&self, counters: Arc<LoadCounters>) {
+        let mut inner = self.lock();
+        inner.detach();
+        counters.enter(inner.state());
+        counters.add_queued(inner.queued_submissions);
+        inner.counters = Some(counters);
+    }
+
+    /// Stop counting the conversation.
+    pub(crate) fn detach(&self) {
+        self.lock().detach();
+    }
+
+    pub(crate) fn set_turn_in_flight(&self, turn_in_flight: bool) {
+        self.lock()
+            .update(|inner| inner.turn_in_flight = turn_in_flight);
+    }
+
+    /// Whether a turn is running, attached or not.
+    pub(crate) fn turn_in_flight(&self) -> bool {
+        self.lock().turn_in_flight
+    }
+
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.lock().update(|inner| inner.paused = paused);
+    }
+
+    /// A submission was sent to the session.
+    pub(crate) fn submission_queued(&self) {
+        self.lock().update(|inner| inner.queued_submissions += 1);
+    }
+
+    /// The session picked up a submission, or sending it failed.
+    pub(crate) fn submission_taken(&self) {
+        self.lock().update(|inner| {
+            inner.queued_submissions = inner.queued_submissions.saturating_sub(1);
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ConversationLoadInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for ConversationLoad {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn detached_conversations_leave_the_counters() {
+        let counters = Arc::new(LoadCounters::default());
+        let load = ConversationLoad::default();
+        load.submission_queued();
+        load.attach(Arc::clone(&counters));
+        load.set_turn_in_flight(true);
+        assert_eq!(
+            counters.snapshot(),
+            ManagerLoad {
+                total: 1,
+                turn_in_flight: 1,
+                queued_submissions: 1,
+                ..ManagerLoad::default()
+            }
+        );
+
+        let waiter = tokio::spawn({
+            let counters = Arc::clone(&counters);
+            async move { counters.wait_for_capacity(1).await }
+        });
+        load.submission_taken();
+        load.detach();
+        waiter.await.expect("waiter");
+        assert!(load.turn_in_flight());
+        // Changes after detaching are not counted.
+        load.set_turn_in_flight(false);
+        load.set_paused(true);
+        assert_eq!(counters.snapshot(), ManagerLoad::default());
+    }
+}
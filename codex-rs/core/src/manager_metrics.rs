@@ -0,0 +1,316 @@
+//! Operational metrics of a [`ConversationManager`], available with the
+//! `metrics` feature.
+//!
+//! Counters are atomics updated as conversations are spawned and as their
+//! events are delivered through [`CodexConversation::next_event`], so turns
+//! and tokens are counted once a client has read the corresponding events.
+//! [`ConversationManager::metrics_snapshot`] reads them all at once and
+//! [`ManagerMetrics::to_prometheus_text`] renders a snapshot for an embedder's
+//! own HTTP endpoint.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+//! [`ConversationManager::metrics_snapshot`]: crate::ConversationManager::metrics_snapshot
+//! [`CodexConversation::next_event`]: crate::CodexConversation::next_event
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use codex_protocol::protocol::CodexErrorInfo;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InitialHistory;
+
+use crate::error::CodexErr;
+
+/// Error codes that spawn failures are counted by, indexed by [`error_code`].
+const ERROR_CODES: [&str; 12] = [
+    "context_window_exceeded",
+    "usage_limit_exceeded",
+    "http_connection_failed",
+    "response_stream_connection_failed",
+    "internal_server_error",
+    "unauthorized",
+    "bad_request",
+    "sandbox_error",
+    "response_stream_disconnected",
+    "response_too_many_failed_attempts",
+    "structured_output_invalid",
+    "other",
+];
+
+/// Point-in-time copy of a manager's metrics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManagerMetrics {
+    /// Conversations the manager is tracking.
+    pub active_conversations: u64,
+    /// Conversations paused with `CodexConversation::pause`.
+    pub paused_conversations: u64,
+    /// New conversations started, children included.
+    pub spawns: u64,
+    /// Conversations resumed from a rollout.
+    pub resumes: u64,
+    /// Conversations forked from another one.
+    pub forks: u64,
+    /// Conversations that failed to start, by the `CodexErrorInfo` code of
+    /// the error. Codes without failures are omitted.
+    pub spawn_failures: BTreeMap<String, u64>,
+    /// Turns that reported `TaskComplete`.
+    pub turns_completed: u64,
+    /// Tokens reported by the provider across all conversations.
+    pub total_tokens: u64,
+    /// Most events any conversation had waiting to be read at once.
+    pub event_queue_high_water: u64,
+}
+
+impl ManagerMetrics {
+    /// Render the snapshot in the Prometheus text exposition format.
+    pub fn to_prometheus_text(&self) -> String {
+        let mut out = String::new();
+        header(
+            &mut out,
+            "codex_active_conversations",
+            "gauge",
+            "Conversations tracked by the manager.",
+        );
+        sample(
+            &mut out,
+            "codex_active_conversations",
+            "",
+            self.active_conversations,
+        );
+        header(
+            &mut out,
+            "codex_paused_conversations",
+            "gauge",
+            "Conversations that are paused.",
+        );
+        sample(
+            &mut out,
+            "codex_paused_conversations",
+            "",
+            self.paused_conversations,
+        );
+
+        let started = "codex_conversations_started_total";
+        header(
+            &mut out,
+            started,
+            "counter",
+            "Conversations started, by how they were started.",
+        );
+        sample(&mut out, started, "kind=\"new\"", self.spawns);
+        sample(&mut out, started, "kind=\"resume\"", self.resumes);
+        sample(&mut out, started, "kind=\"fork\"", self.forks);
+
+        let failures = "codex_conversation_spawn_failures_total";
+        header(
+            &mut out,
+            failures,
+            "counter",
+            "Conversations that failed to start, by error code.",
+        );
+        for (code, count) in &self.spawn_failures {
+            sample(&mut out, failures, &format!("code=\"{code}\""), *count);
+        }
+
+        for (name, kind, help, value) in [
+            (
+                "codex_turns_completed_total",
+                "counter",
+                "Turns completed across all conversations.",
+                self.turns_completed,
+            ),
+            (
+                "codex_tokens_total",
+                "counter",
+                "Tokens used across all conversations.",
+                self.total_tokens,
+            ),
+            (
+                "codex_event_queue_high_water",
+                "gauge",
+                "Most events a conversation had waiting to be read at once.",
+                self.event_queue_high_water,
+            ),
+        ] {
+            header(&mut out, name, kind, help);
+            sample(&mut out, name, "", value);
+        }
+        out
+    }
+}
+
+// Writing to a `String` cannot fail.
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: u64) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{name} {value}");
+    } else {
+        let _ = writeln!(out, "{name}{{{labels}}} {value}");
+    }
+}
+
+/// How a conversation is being started.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SpawnKind {
+    New,
+    Resume,
+    Fork,
+}
+
+impl SpawnKind {
+    pub(crate) fn of(history: &InitialHistory) -> Self {
+        match history {
+            InitialHistory::New => Self::New,
+            InitialHistory::Resumed(_) | InitialHistory::ResumedEmpty(_) => Self::Resume,
+            InitialHistory::Forked(_) => Self::Fork,
+        }
+    }
+}
+
+/// Counters shared by a manager and its conversations.
+#[derive(Default)]
+pub(crate) struct ManagerMetricsCounters {
+    active_conversations: AtomicU64,
+    paused_conversations: AtomicU64,
+    spawns: AtomicU64,
+    resumes: AtomicU64,
+    forks: AtomicU64,
+    spawn_failures: [AtomicU64; ERROR_CODES.len()],
+    turns_completed: AtomicU64,
+    total_tokens: AtomicU64,
+    event_queue_high_water: AtomicU64,
+}
+
+impl ManagerMetricsCounters {
+    pub(crate) fn snapshot(&self) -> ManagerMetrics {
+        ManagerMetrics {
+            active_conversations: self.active_conversations.load(Ordering::Relaxed),
+            paused_conversations: self.paused_conversations.load(Ordering::Relaxed),
+            spawns: self.spawns.load(Ordering::Relaxed),
+            resumes: self.resumes.load(Ordering::Relaxed),
+            forks: self.forks.load(Ordering::Relaxed),
+            spawn_failures: ERROR_CODES
+                .iter()
+                .zip(&self.spawn_failures)
+                .map(|(code, count)| (code.to_string(), count.load(Ordering::Relaxed)))
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+            turns_completed: self.turns_completed.load(Ordering::Relaxed),
+            total_tokens: self.total_tokens.load(Ordering::Relaxed),
+            event_queue_high_water: self.event_queue_high_water.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Record the outcome of starting a conversation of `kind`.
+    pub(crate) fn record_spawn<T>(&self, kind: SpawnKind, result: &Result<T, CodexErr>) {
+        let counter = match (result, kind) {
+            (Err(err), _) => &self.spawn_failures[error_code(&err.to_codex_protocol_error())],
+            (Ok(_), SpawnKind::New) => &self.spawns,
+            (Ok(_), SpawnKind::Resume) => &self.resumes,
+            (Ok(_), SpawnKind::Fork) => &self.forks,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn conversation_added(&self) {
+        self.active_conversations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn conversation_removed(&self) {
+        self.active_conversations.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Per-conversation view of the manager's counters.
+pub(crate) struct ConversationMetrics {
+    counters: Arc<ManagerMetricsCounters>,
+    /// Latest cumulative token total reported by this conversation.
+    last_total_tokens: AtomicI64,
+    /// Whether this conversation is counted as paused.
+    paused: AtomicBool,
+}
+
+impl ConversationMetrics {
+    pub(crate) fn new(counters: Arc<ManagerMetricsCounters>) -> Self {
+        Self {
+            counters,
+            last_total_tokens: AtomicI64::new(0),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    /// Account for `event` as it is delivered, with `queued` events (itself
+    /// included) waiting in the conversation's queue.
+    pub(crate) fn observe(&self, event: &Event, queued: usize) {
+        let counters = &self.counters;
+        counters
+            .event_queue_high_water
+            .fetch_max(u64::try_from(queued).unwrap_or(u64::MAX), Ordering::Relaxed);
+        match &event.msg {
+            EventMsg::TaskComplete(_) => {
+                counters.turns_completed.fetch_add(1, Ordering::Relaxed);
+            }
+            EventMsg::TokenCount(token_count) => {
+                let Some(info) = &token_count.info else {
+                    return;
+                };
+                // Token counts are cumulative and may repeat; add the growth.
+                let total = info.total_token_usage.total_tokens;
+                let previous = self.last_total_tokens.swap(total, Ordering::Relaxed);
+                if let Ok(added) = u64::try_from(total - previous)
+                    && added > 0
+                {
+                    counters.total_tokens.fetch_add(added, Ordering::Relaxed);
+                }
+            }
+            EventMsg::ConversationPaused(_) => self.set_paused(true),
+            EventMsg::ConversationResumed => self.set_paused(false),
+            _ => {}
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::Relaxed) == paused {
+            return;
+        }
+        let gauge = &self.counters.paused_conversations;
+        if paused {
+            gauge.fetch_add(1, Ordering::Relaxed);
+        } else {
+            gauge.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for ConversationMetrics {
+    fn drop(&mut self) {
+        self.set_paused(false);
+    }
+}
+
+fn error_code(info: &CodexErrorInfo) -> usize {
+    match info {
+        CodexErrorInfo::ContextWindowExceeded => 0,
+        CodexErrorInfo::UsageLimitExceeded => 1,
+        CodexErrorInfo::HttpConnectionFailed { .. } => 2,
+        CodexErrorInfo::ResponseStreamConnectionFailed { .. } => 3,
+        CodexErrorInfo::InternalServerError => 4,
+        CodexErrorInfo::Unauthorized => 5,
+        CodexErrorInfo::BadRequest => 6,
+        CodexErrorInfo::SandboxError => 7,
+        CodexErrorInfo::ResponseStreamDisconnected { .. } => 8,
+        CodexErrorInfo::ResponseTooManyFailedAttempts { .. } => 9,
+        CodexErrorInfo::StructuredOutputInvalid => 10,
+        CodexErrorInfo::Other => 11,
+    }
+}
@@ -9,6 +9,7 @@ use codex_api::Provider as ApiProvider;
 use codex_api::WireApi as ApiWireApi;
 use codex_api::provider::RetryConfig as ApiRetryConfig;
 use codex_app_server_protocol::AuthMode;
+use codex_protocol::config_types::ToolCapabilities;
 use http::HeaderMap;
 use http::header::HeaderName;
 use http::header::HeaderValue;
@@ -22,6 +23,7 @@ use crate::error::EnvVarError;
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
 const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
+const DEFAULT_ENDPOINT_COOLDOWN_MS: u64 = 30_000;
 /// Hard cap for user-configured `stream_max_retries`.
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
@@ -93,6 +95,19 @@ pub struct ModelProviderInfo {
     /// the connection as lost.
     pub stream_idle_timeout_ms: Option<u64>,
 
+    /// Additional base URLs tried, in order, when `base_url` is unreachable or
+    /// returns a server error. Health is tracked per URL and shared across
+    /// conversations.
+    pub fallback_base_urls: Option<Vec<String>>,
+
+    /// How long (in milliseconds) an endpoint that failed is skipped before it
+    /// is tried again.
+    pub endpoint_cooldown_ms: Option<u64>,
+
+    /// Limits on the tools this provider accepts. Tools are sent unchanged
+    /// when unset.
+    pub tool_capabilities: Option<ToolCapabilities>,
+
     /// Does this provider require an OpenAI API Key or ChatGPT login token? If true,
     /// user is presented with login screen on first run, and login preference and token/key
     /// are stored in auth.json. If false (which is the default), login screen is skipped,
@@ -210,6 +225,26 @@ impl ModelProviderInfo {
             .map(Duration::from_millis)
             .unwrap_or(Duration::from_millis(DEFAULT_STREAM_IDLE_TIMEOUT_MS))
     }
+
+    /// Effective cool-down before an unhealthy endpoint is retried.
+    pub fn endpoint_cooldown(&self) -> Duration {
+        self.endpoint_cooldown_ms
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_millis(DEFAULT_ENDPOINT_COOLDOWN_MS))
+    }
+
+    /// All base URLs for this provider in preference order, starting with
+    /// `primary` (the resolved `base_url`).
+    pub(crate) fn endpoint_base_urls(&self, primary: &str) -> Vec<String> {
+        let mut urls = vec![primary.to_string()];
+        for url in self.fallback_base_urls.iter().flatten() {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
     pub fn create_openai_provider() -> ModelProviderInfo {
         ModelProviderInfo {
             name: OPENAI_PROVIDER_NAME.into(),
@@ -246,6 +281,9 @@ impl ModelProviderInfo {
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: true,
         }
     }
@@ -319,6 +357,9 @@ pub fn create_oss_provider_with_base_url(base_url: &str, wire_api: WireApi) -> M
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     }
 }
@@ -347,6 +388,9 @@ base_url = "http://localhost:11434/v1"
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         };
 
@@ -377,6 +421,9 @@ query_params = { api-version = "2025-04-01-preview" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         };
 
@@ -410,6 +457,9 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         };
 
@@ -441,6 +491,9 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                fallback_base_urls: None,
+                endpoint_cooldown_ms: None,
+                tool_capabilities: None,
                 requires_openai_auth: false,
             };
             let api = provider.to_api_provider(None).expect("api provider");
@@ -463,6 +516,9 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         };
         let named_api = named_provider.to_api_provider(None).expect("api provider");
@@ -487,6 +543,9 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                fallback_base_urls: None,
+                endpoint_cooldown_ms: None,
+                tool_capabilities: None,
                 requires_openai_auth: false,
             };
             let api = provider.to_api_provider(None).expect("api provider");
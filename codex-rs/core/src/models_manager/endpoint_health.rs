@@ -0,0 +1,94 @@
+//! Health tracking for providers configured with several base URLs.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Per-endpoint health shared by every conversation under a `ModelsManager`.
+#[derive(Debug, Default)]
+pub(crate) struct EndpointHealth {
+    state: Mutex<EndpointHealthState>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealthState {
+    /// Endpoints that failed recently, keyed by base URL, with the instant at
+    /// which they may be tried again.
+    unhealthy_until: HashMap<String, Instant>,
+    /// The endpoint that last served a request, keyed by provider name.
+    active: HashMap<String, String>,
+}
+
+impl EndpointHealth {
+    /// Orders `endpoints` so healthy ones come first, preserving the
+    /// configured preference within each group. Endpoints whose cool-down
+    /// has expired count as healthy again.
+    pub(crate) fn ordered(&self, endpoints: &[String]) -> Vec<String> {
+        let now = Instant::now();
+        let Ok(mut state) = self.state.lock() else {
+            return endpoints.to_vec();
+        };
+        state.unhealthy_until.retain(|_, until| *until > now);
+        let (healthy, unhealthy): (Vec<String>, Vec<String>) = endpoints
+            .iter()
+            .cloned()
+            .partition(|url| !state.unhealthy_until.contains_key(url));
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    pub(crate) fn mark_unhealthy(&self, endpoint: &str, cooldown: Duration) {
+        if let Ok(mut state) = self.state.lock() {
+            state
+                .unhealthy_until
+                .insert(endpoint.to_string(), Instant::now() + cooldown);
+        }
+    }
+
+    /// Records that `endpoint` served a request for `provider`. Returns the
+    /// previously active endpoint when this is a switch.
+    pub(crate) fn mark_served(&self, provider: &str, endpoint: &str) -> Option<String> {
+        let Ok(mut state) = self.state.lock() else {
+            return None;
+        };
+        state.unhealthy_until.remove(endpoint);
+        let previous = state
+            .active
+            .insert(provider.to_string(), endpoint.to_string());
+        previous.filter(|previous| previous != endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn urls() -> Vec<String> {
+        vec!["https://a".to_string(), "https://b".to_string()]
+    }
+
+    #[test]
+    fn unhealthy_endpoints_move_to_the_back_until_cooldown_expires() {
+        let health = EndpointHealth::default();
+        health.mark_unhealthy("https://a", Duration::from_secs(60));
+        assert_eq!(
+            health.ordered(&urls()),
+            vec!["https://b".to_string(), "https://a".to_string()]
+        );
+
+        health.mark_unhealthy("https://a", Duration::ZERO);
+        assert_eq!(health.ordered(&urls()), urls());
+    }
+
+    #[test]
+    fn mark_served_reports_switches() {
+        let health = EndpointHealth::default();
+        assert_eq!(health.mark_served("proxy", "https://a"), None);
+        assert_eq!(health.mark_served("proxy", "https://a"), None);
+        assert_eq!(
+            health.mark_served("proxy", "https://b"),
+            Some("https://a".to_string())
+        );
+    }
+}
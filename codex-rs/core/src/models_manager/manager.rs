@@ -5,17 +5,26 @@ use codex_app_server_protocol::AuthMode;
 use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ModelPreset;
 use codex_protocol::openai_models::ModelsResponse;
+use codex_protocol::protocol::ModelListSource;
+use codex_protocol::protocol::RetrySubsystem;
+use codex_protocol::protocol::RetryingEvent;
 use http::HeaderMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio::sync::TryLockError;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
 use tracing::error;
+use tracing::warn;
 
 use super::cache;
 use super::cache::ModelsCache;
+use super::endpoint_health::EndpointHealth;
 use crate::api_bridge::auth_provider_from_auth;
 use crate::api_bridge::map_api_error;
 use crate::auth::AuthManager;
@@ -26,6 +35,7 @@ use crate::features::Feature;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::models_manager::model_family::ModelFamily;
 use crate::models_manager::model_presets::builtin_model_presets;
+use crate::retry::retry;
 
 const MODEL_CACHE_FILE: &str = "models_cache.json";
 const DEFAULT_MODEL_CACHE_TTL: Duration = Duration::from_secs(300);
@@ -33,6 +43,16 @@ const OPENAI_DEFAULT_API_MODEL: &str = "gpt-5.1-codex-max";
 const OPENAI_DEFAULT_CHATGPT_MODEL: &str = "gpt-5.2-codex";
 const CODEX_AUTO_BALANCED_MODEL: &str = "codex-auto-balanced";
 
+/// Model list a session starts with, see
+/// [`ModelsManager::resolve_available_models`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedModelList {
+    pub source: ModelListSource,
+    /// The fetch failed, so the list may be out of date until it is fetched
+    /// again.
+    pub degraded: bool,
+}
+
 /// Coordinates remote model discovery plus cached metadata on disk.
 #[derive(Debug)]
 pub struct ModelsManager {
@@ -44,13 +64,17 @@ pub struct ModelsManager {
     codex_home: PathBuf,
     cache_ttl: Duration,
     provider: ModelProviderInfo,
+    endpoint_health: Arc<EndpointHealth>,
+    /// The model list as last built, see [`Self::subscribe`].
+    presets_tx: watch::Sender<Arc<Vec<ModelPreset>>>,
+    background_refresh_started: AtomicBool,
 }
 
 impl ModelsManager {
     /// Construct a manager scoped to the provided `AuthManager`.
     pub fn new(auth_manager: Arc<AuthManager>) -> Self {
         let codex_home = auth_manager.codex_home().to_path_buf();
-        Self {
+        let manager = Self {
             local_models: builtin_model_presets(auth_manager.get_auth_mode()),
             remote_models: RwLock::new(Self::load_remote_models_from_file().unwrap_or_default()),
             auth_manager,
@@ -58,14 +82,19 @@ impl ModelsManager {
             codex_home,
             cache_ttl: DEFAULT_MODEL_CACHE_TTL,
             provider: ModelProviderInfo::create_openai_provider(),
-        }
+            endpoint_health: Arc::new(EndpointHealth::default()),
+            presets_tx: watch::Sender::new(Arc::default()),
+            background_refresh_started: AtomicBool::new(false),
+        };
+        manager.publish(manager.build_available_models(Vec::new()));
+        manager
     }
 
     #[cfg(any(test, feature = "test-support"))]
     /// Construct a manager scoped to the provided `AuthManager` with a specific provider. Used for integration tests.
     pub fn with_provider(auth_manager: Arc<AuthManager>, provider: ModelProviderInfo) -> Self {
         let codex_home = auth_manager.codex_home().to_path_buf();
-        Self {
+        let manager = Self {
             local_models: builtin_model_presets(auth_manager.get_auth_mode()),
             remote_models: RwLock::new(Self::load_remote_models_from_file().unwrap_or_default()),
             auth_manager,
@@ -73,20 +102,93 @@ impl ModelsManager {
             codex_home,
             cache_ttl: DEFAULT_MODEL_CACHE_TTL,
             provider,
-        }
+            endpoint_health: Arc::new(EndpointHealth::default()),
+            presets_tx: watch::Sender::new(Arc::default()),
+            background_refresh_started: AtomicBool::new(false),
+        };
+        manager.publish(manager.build_available_models(Vec::new()));
+        manager
     }
 
     /// Fetch the latest remote models, using the on-disk cache when still fresh.
+    /// Offline, only the cache is loaded, however old.
     pub async fn refresh_available_models(&self, config: &Config) -> CoreResult<()> {
-        if !config.features.enabled(Feature::RemoteModels)
-            || self.auth_manager.get_auth_mode() == Some(AuthMode::ApiKey)
-        {
+        if !self.remote_models_enabled(config) {
+            return Ok(());
+        }
+        if config.offline {
+            self.try_load_cache(false).await;
             return Ok(());
         }
-        if self.try_load_cache().await {
+        if self.try_load_cache(true).await {
             return Ok(());
         }
+        self.fetch_remote_models_with_retry(config, |_| {}).await
+    }
+
+    /// Like [`Self::refresh_available_models`], but a failed fetch falls back
+    /// to the last cached list, however old, and then to the built-in one.
+    /// Offline, those fallbacks are used without fetching.
+    pub async fn resolve_available_models(&self, config: &Config) -> ResolvedModelList {
+        self.resolve_available_models_with(config, || {}, |_| {})
+            .await
+    }
+
+    /// Like [`Self::resolve_available_models`], calling `on_fetch` before the
+    /// provider is asked for the list and `on_retry` before each retry.
+    pub(crate) async fn resolve_available_models_with(
+        &self,
+        config: &Config,
+        on_fetch: impl FnOnce(),
+        on_retry: impl FnMut(&RetryingEvent),
+    ) -> ResolvedModelList {
+        let resolved = |source, degraded| ResolvedModelList { source, degraded };
+        if !self.remote_models_enabled(config) {
+            return resolved(ModelListSource::Builtin, false);
+        }
+        if config.offline {
+            return if self.try_load_cache(false).await {
+                resolved(ModelListSource::Cache, false)
+            } else {
+                resolved(ModelListSource::Builtin, false)
+            };
+        }
+        if self.try_load_cache(true).await {
+            return resolved(ModelListSource::Cache, false);
+        }
+        on_fetch();
+        match self.fetch_remote_models_with_retry(config, on_retry).await {
+            Ok(()) => resolved(ModelListSource::Network, false),
+            Err(err) => {
+                warn!("failed to fetch models, using a fallback list: {err}");
+                if self.try_load_cache(false).await {
+                    resolved(ModelListSource::Cache, true)
+                } else {
+                    resolved(ModelListSource::Builtin, true)
+                }
+            }
+        }
+    }
+
+    /// [`Self::fetch_remote_models`], retried under the `preset_fetch`
+    /// profile of `config`.
+    async fn fetch_remote_models_with_retry(
+        &self,
+        config: &Config,
+        on_retry: impl FnMut(&RetryingEvent),
+    ) -> CoreResult<()> {
+        retry(
+            &config.retry.preset_fetch,
+            RetrySubsystem::PresetFetch,
+            on_retry,
+            || self.fetch_remote_models(),
+        )
+        .await
+    }
 
+    /// Fetch the remote models from the provider, bypassing the cache, and
+    /// cache them.
+    pub(crate) async fn fetch_remote_models(&self) -> CoreResult<()> {
         let auth = self.auth_manager.auth();
         let api_provider = self.provider.to_api_provider(Some(AuthMode::ChatGPT))?;
         let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
@@ -112,7 +214,9 @@ impl ModelsManager {
             error!("failed to refresh available models: {err}");
         }
         let remote_models = self.remote_models(config).await;
-        self.build_available_models(remote_models)
+        let presets = self.build_available_models(remote_models);
+        self.publish(presets.clone());
+        presets
     }
 
     pub fn try_list_models(&self, config: &Config) -> Result<Vec<ModelPreset>, TryLockError> {
@@ -120,6 +224,79 @@ impl ModelsManager {
         Ok(self.build_available_models(remote_models))
     }
 
+    /// Fetch the model list again, bypassing the cache, and notify the
+    /// subscribers when it changed. Returns the list, the one known before
+    /// when the fetch failed.
+    pub async fn refresh_models(&self, config: &Config) -> Vec<ModelPreset> {
+        if self.remote_models_enabled(config)
+            && !config.offline
+            && let Err(err) = self.fetch_remote_models_with_retry(config, |_| {}).await
+        {
+            warn!("failed to refresh the model list: {err}");
+        }
+        let remote_models = self.remote_models(config).await;
+        let presets = self.build_available_models(remote_models);
+        self.publish(presets.clone());
+        presets
+    }
+
+    /// The model list, updated whenever a refresh finds that it changed:
+    /// models added, removed, or with other metadata. A list that only
+    /// comes back in another order is not a change.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Vec<ModelPreset>>> {
+        self.presets_tx.subscribe()
+    }
+
+    /// Refresh the model list every `config.model_list_refresh_interval`
+    /// while it has subscribers, until the manager is dropped. Only the
+    /// first call with an interval starts the refresh.
+    pub(crate) fn start_background_refresh(self: &Arc<Self>, config: &Config) {
+        let Some(interval) = config.model_list_refresh_interval else {
+            return;
+        };
+        if self.background_refresh_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let manager = Arc::downgrade(self);
+        let config = config.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // The first tick completes at once.
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+                if manager.presets_tx.receiver_count() > 0 {
+                    manager.refresh_models(&config).await;
+                }
+            }
+        });
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    /// Replace the remote models as a fetch would and publish the new list.
+    /// Used for integration tests.
+    pub async fn set_remote_models_for_testing(&self, models: Vec<ModelInfo>, config: &Config) {
+        self.apply_remote_models(models).await;
+        let remote_models = self.remote_models(config).await;
+        self.publish(self.build_available_models(remote_models));
+    }
+
+    /// Make `presets` the current list, notifying the subscribers unless it
+    /// holds the same presets as before.
+    fn publish(&self, presets: Vec<ModelPreset>) {
+        self.presets_tx.send_if_modified(|current| {
+            if same_presets(current, &presets) {
+                return false;
+            }
+            *current = Arc::new(presets);
+            true
+        });
+    }
+
     fn find_family_for_model(slug: &str) -> ModelFamily {
         super::model_family::find_family_for_model(slug)
     }
@@ -138,6 +315,11 @@ impl ModelsManager {
         if let Err(err) = self.refresh_available_models(config).await {
             error!("failed to refresh available models: {err}");
         }
+        self.default_model(config).await
+    }
+
+    /// The model to use when none is configured, from the models known now.
+    pub(crate) async fn default_model(&self, config: &Config) -> String {
         // if codex-auto-balanced exists & signed in with chatgpt mode, return it, otherwise return the default model
         let auth_mode = self.auth_manager.get_auth_mode();
         let remote_models = self.remote_models(config).await;
@@ -154,6 +336,21 @@ impl ModelsManager {
         OPENAI_DEFAULT_API_MODEL.to_string()
     }
 
+    /// Whether `slug` is in the remote or built-in model list, hidden models
+    /// included.
+    pub async fn is_known_model(&self, slug: &str, config: &Config) -> bool {
+        self.remote_models(config)
+            .await
+            .iter()
+            .any(|model| model.slug == slug)
+            || self.local_models.iter().any(|preset| preset.model == slug)
+    }
+
+    /// Endpoint health shared by every conversation using this manager.
+    pub(crate) fn endpoint_health(&self) -> Arc<EndpointHealth> {
+        Arc::clone(&self.endpoint_health)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn get_model_offline(model: Option<&str>) -> String {
         model.unwrap_or(OPENAI_DEFAULT_CHATGPT_MODEL).to_string()
@@ -177,7 +374,8 @@ impl ModelsManager {
     }
 
     /// Attempt to satisfy the refresh from the cache when it matches the provider and TTL.
-    async fn try_load_cache(&self) -> bool {
+    /// With `require_fresh` unset the cache is used whatever its age.
+    async fn try_load_cache(&self, require_fresh: bool) -> bool {
         // todo(aibrahim): think if we should store fetched_at in ModelsManager so we don't always need to read the disk
         let cache_path = self.cache_path();
         let cache = match cache::load_cache(&cache_path).await {
@@ -191,7 +389,7 @@ impl ModelsManager {
             Some(cache) => cache,
             None => return false,
         };
-        if !cache.is_fresh(self.cache_ttl) {
+        if require_fresh && !cache.is_fresh(self.cache_ttl) {
             return false;
         }
         let models = cache.models.clone();
@@ -265,6 +463,11 @@ impl ModelsManager {
         merged_presets
     }
 
+    fn remote_models_enabled(&self, config: &Config) -> bool {
+        config.features.enabled(Feature::RemoteModels)
+            && self.auth_manager.get_auth_mode() != Some(AuthMode::ApiKey)
+    }
+
     async fn remote_models(&self, config: &Config) -> Vec<ModelInfo> {
         if config.features.enabled(Feature::RemoteModels) {
             self.remote_models.read().await.clone()
@@ -286,6 +489,16 @@ impl ModelsManager {
     }
 }
 
+/// Whether `a` and `b` hold the same presets, in whatever order.
+fn same_presets(a: &[ModelPreset], b: &[ModelPreset]) -> bool {
+    fn by_slug(presets: &[ModelPreset]) -> Vec<&ModelPreset> {
+        let mut sorted: Vec<&ModelPreset> = presets.iter().collect();
+        sorted.sort_by(|x, y| x.model.cmp(&y.model));
+        sorted
+    }
+    a.len() == b.len() && by_slug(a) == by_slug(b)
+}
+
 /// Convert a client version string to a whole version string (e.g. "1.2.3-alpha.4" -> "1.2.3")
 fn format_client_version_to_whole() -> String {
     format_client_version_from_parts(
@@ -373,6 +586,9 @@ mod tests {
             request_max_retries: Some(0),
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(5_000),
+            fallback_base_urls: None,
+            endpoint_cooldown_ms: None,
+            tool_capabilities: None,
             requires_openai_auth: false,
         }
     }
@@ -567,6 +783,70 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn offline_uses_the_stale_cache_without_fetching() {
+        let server = MockServer::start().await;
+        let cached_models = vec![remote_model("cached", "Cached", 1)];
+        mount_models_once(
+            &server,
+            ModelsResponse {
+                models: cached_models.clone(),
+                etag: String::new(),
+            },
+        )
+        .await;
+
+        let codex_home = tempdir().expect("temp dir");
+        let mut config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .build()
+            .await
+            .expect("load default test config");
+        config.features.enable(Feature::RemoteModels);
+        let auth_manager = Arc::new(AuthManager::new(
+            codex_home.path().to_path_buf(),
+            false,
+            AuthCredentialsStoreMode::File,
+        ));
+        ModelsManager::with_provider(Arc::clone(&auth_manager), provider_for(server.uri()))
+            .refresh_available_models(&config)
+            .await
+            .expect("initial refresh succeeds");
+        let cache_path = codex_home.path().join(MODEL_CACHE_FILE);
+        let contents =
+            std::fs::read_to_string(&cache_path).expect("cache file should exist after refresh");
+        let mut cache: ModelsCache =
+            serde_json::from_str(&contents).expect("cache should deserialize");
+        cache.fetched_at = Utc::now() - chrono::Duration::hours(1);
+        std::fs::write(&cache_path, serde_json::to_string_pretty(&cache).unwrap())
+            .expect("cache rewrite succeeds");
+
+        server.reset().await;
+        let models_mock = mount_models_once(
+            &server,
+            ModelsResponse {
+                models: vec![remote_model("fresh", "Fresh", 9)],
+                etag: String::new(),
+            },
+        )
+        .await;
+        config.offline = true;
+        let manager = ModelsManager::with_provider(auth_manager, provider_for(server.uri()));
+        assert_eq!(
+            manager.resolve_available_models(&config).await,
+            ResolvedModelList {
+                source: ModelListSource::Cache,
+                degraded: false,
+            }
+        );
+        manager
+            .refresh_available_models(&config)
+            .await
+            .expect("offline refresh succeeds");
+        assert_eq!(manager.remote_models(&config).await, cached_models);
+        assert_eq!(models_mock.requests().len(), 0, "offline never fetches");
+    }
+
     #[tokio::test]
     async fn refresh_available_models_drops_removed_remote_models() {
         let server = MockServer::start().await;
@@ -637,6 +917,60 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn refresh_models_notifies_subscribers_of_changes_only() {
+        let server = MockServer::start().await;
+        let codex_home = tempdir().expect("temp dir");
+        let mut config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .build()
+            .await
+            .expect("load default test config");
+        config.features.enable(Feature::RemoteModels);
+        let auth_manager =
+            AuthManager::from_auth_for_testing(CodexAuth::create_dummy_chatgpt_auth_for_testing());
+        let manager = ModelsManager::with_provider(auth_manager, provider_for(server.uri()));
+        let mut presets = manager.subscribe();
+        let refresh_with = |models: Vec<ModelInfo>| {
+            let server = &server;
+            let manager = &manager;
+            let config = &config;
+            async move {
+                server.reset().await;
+                mount_models_once(
+                    server,
+                    ModelsResponse {
+                        models,
+                        etag: String::new(),
+                    },
+                )
+                .await;
+                manager.refresh_models(config).await
+            }
+        };
+
+        refresh_with(vec![
+            remote_model("remote-a", "Remote A", 1),
+            remote_model("remote-b", "Remote B", 2),
+        ])
+        .await;
+        assert!(presets.has_changed().expect("manager alive"));
+        presets.borrow_and_update();
+
+        refresh_with(vec![
+            remote_model("remote-b", "Remote B", 2),
+            remote_model("remote-a", "Remote A", 1),
+        ])
+        .await;
+        assert!(!presets.has_changed().expect("manager alive"));
+
+        refresh_with(vec![remote_model("remote-b", "Remote B", 2)]).await;
+        assert!(presets.has_changed().expect("manager alive"));
+        let listed = presets.borrow_and_update().clone();
+        assert!(listed.iter().any(|preset| preset.model == "remote-b"));
+        assert!(!listed.iter().any(|preset| preset.model == "remote-a"));
+    }
+
     #[test]
     fn build_available_models_picks_default_after_hiding_hidden_models() {
         let auth_manager =
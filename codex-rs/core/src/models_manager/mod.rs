@@ -1,4 +1,5 @@
 pub mod cache;
+pub(crate) mod endpoint_health;
 pub mod manager;
 pub mod model_family;
 pub mod model_presets;
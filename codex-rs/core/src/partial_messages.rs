@@ -0,0 +1,216 @@
+//! Recording of assistant text while it streams.
+//!
+//! Items reach the rollout once they are complete, so a process that dies
+//! in the middle of an answer loses everything streamed in that turn. With a
+//! [`PartialMessagePolicy`], the text of the streaming item is recorded as a
+//! provisional [`RolloutItem::PartialMessage`] whenever it completes a
+//! sentence, at most once every `min_interval_ms`. Each record holds the
+//! whole text of its item up to that sentence, so only the newest one
+//! matters, and the completed item or the end of the turn supersedes it.
+//! Reading a rollout keeps only a partial message nothing superseded: the
+//! answer of a turn the process did not live to finish, which a resumed
+//! session reports with `EventMsg::InterruptedTurnRecovered`.
+
+use std::time::Duration;
+
+use codex_protocol::protocol::RolloutItem;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::time::Instant;
+
+/// When streaming assistant text is recorded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PartialMessagePolicy {
+    /// Shortest time between two records of the same turn. `0` records
+    /// every sentence.
+    pub min_interval_ms: u64,
+}
+
+impl Default for PartialMessagePolicy {
+    fn default() -> Self {
+        Self {
+            min_interval_ms: 2_000,
+        }
+    }
+}
+
+/// Text of the streaming item of a turn, see the [module docs](self).
+pub(crate) struct PartialMessageRecorder {
+    policy: Option<PartialMessagePolicy>,
+    item_id: Option<String>,
+    text: String,
+    /// Length of the text last recorded.
+    recorded_len: usize,
+    last_recorded: Option<Instant>,
+}
+
+impl PartialMessageRecorder {
+    pub(crate) fn new(policy: Option<PartialMessagePolicy>) -> Self {
+        Self {
+            policy,
+            item_id: None,
+            text: String::new(),
+            recorded_len: 0,
+            last_recorded: None,
+        }
+    }
+
+    /// Add `delta` to the text of item `item_id`, returning the text to
+    /// record when the delta completes a sentence and a record is due.
+    pub(crate) fn push(&mut self, item_id: &str, delta: &str) -> Option<String> {
+        let policy = self.policy.as_ref()?;
+        if self.item_id.as_deref() != Some(item_id) {
+            self.item_id = Some(item_id.to_string());
+            self.text.clear();
+            self.recorded_len = 0;
+        }
+        self.text.push_str(delta);
+
+        let end = last_sentence_end(&self.text)?;
+        if end <= self.recorded_len {
+            return None;
+        }
+        let now = Instant::now();
+        if let Some(last) = self.last_recorded
+            && now.duration_since(last) < Duration::from_millis(policy.min_interval_ms)
+        {
+            return None;
+        }
+        self.recorded_len = end;
+        self.last_recorded = Some(now);
+        Some(self.text[..end].to_string())
+    }
+}
+
+/// End of the last complete sentence of `text`: after a newline, or after
+/// `.`, `!`, `?` or `…` once whitespace follows.
+fn last_sentence_end(text: &str) -> Option<usize> {
+    let mut end = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        let after = index + ch.len_utf8();
+        let ends_sentence = match ch {
+            '\n' => true,
+            '.' | '!' | '?' | '…' => chars.peek().is_some_and(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if ends_sentence {
+            end = Some(after);
+        }
+    }
+    end
+}
+
+/// Drop the partial messages of `items` that a later item supersedes, which
+/// leaves at most the last one.
+pub(crate) fn collapse_partial_messages(items: &mut Vec<RolloutItem>) {
+    let live = items
+        .iter()
+        .rposition(RolloutItem::supersedes_partial_message)
+        .filter(|&index| matches!(items[index], RolloutItem::PartialMessage { .. }));
+    let mut index = 0;
+    items.retain(|item| {
+        let keep = !matches!(item, RolloutItem::PartialMessage { .. }) || Some(index) == live;
+        index += 1;
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::protocol::TurnId;
+    use codex_protocol::protocol::TurnStartedItem;
+    use pretty_assertions::assert_eq;
+
+    fn partial(seq: u64, text: &str) -> RolloutItem {
+        RolloutItem::PartialMessage {
+            turn_id: TurnId::new(1),
+            seq,
+            text: text.to_string(),
+        }
+    }
+
+    fn texts(items: &[RolloutItem]) -> Vec<&str> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                RolloutItem::PartialMessage { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sentences_end_at_punctuation_before_whitespace_and_at_newlines() {
+        assert_eq!(last_sentence_end("No end yet"), None);
+        assert_eq!(last_sentence_end("Pi is 3.14"), None);
+        assert_eq!(last_sentence_end("Done. And"), Some(5));
+        assert_eq!(last_sentence_end("Really?! Yes… ok"), Some(15));
+        assert_eq!(last_sentence_end("- item\n- other"), Some(7));
+    }
+
+    #[test]
+    fn completed_sentences_are_recorded_once() {
+        let mut recorder =
+            PartialMessageRecorder::new(Some(PartialMessagePolicy { min_interval_ms: 0 }));
+        assert_eq!(recorder.push("msg", "One"), None);
+        assert_eq!(recorder.push("msg", ". Tw"), Some("One.".to_string()));
+        assert_eq!(recorder.push("msg", "o"), None);
+        assert_eq!(
+            recorder.push("msg", ". Three"),
+            Some("One. Two.".to_string())
+        );
+        // A new item starts over.
+        assert_eq!(recorder.push("other", "Four. "), Some("Four.".to_string()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn records_are_at_least_the_interval_apart() {
+        let mut recorder = PartialMessageRecorder::new(Some(PartialMessagePolicy {
+            min_interval_ms: 100,
+        }));
+        assert_eq!(recorder.push("msg", "One. "), Some("One.".to_string()));
+        assert_eq!(recorder.push("msg", "Two. "), None);
+        tokio::time::advance(Duration::from_millis(100)).await;
+        assert_eq!(
+            recorder.push("msg", "Three. "),
+            Some("One. Two. Three.".to_string())
+        );
+    }
+
+    #[test]
+    fn without_a_policy_nothing_is_recorded() {
+        let mut recorder = PartialMessageRecorder::new(None);
+        assert_eq!(recorder.push("msg", "One. Two. "), None);
+    }
+
+    #[test]
+    fn only_a_partial_message_nothing_superseded_is_kept() {
+        let answer = RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: "One. Two.".to_string(),
+            }],
+        });
+        let mut completed = vec![partial(1, "One."), partial(2, "One. Two."), answer];
+        collapse_partial_messages(&mut completed);
+        assert_eq!(texts(&completed), Vec::<&str>::new());
+        assert_eq!(completed.len(), 1);
+
+        let mut interrupted = vec![
+            RolloutItem::TurnStarted(TurnStartedItem {
+                turn_id: TurnId::new(1),
+            }),
+            partial(1, "One."),
+            partial(2, "One. Two."),
+        ];
+        collapse_partial_messages(&mut interrupted);
+        assert_eq!(texts(&interrupted), vec!["One. Two."]);
+        assert_eq!(interrupted.len(), 2);
+    }
+}
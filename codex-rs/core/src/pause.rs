@@ -0,0 +1,205 @@
+//! Pausing a conversation without ending its session.
+//!
+//! A paused session keeps its history, tools and rollout in memory, so
+//! resuming it is immediate, unlike removing it and resuming it from the
+//! rollout. [`crate::CodexConversation::pause`] refuses new submissions at
+//! once and asks the session to pause with [`Op::Pause`]. The session lets
+//! the running turn finish, or aborts it, then flushes the rollout and
+//! reports `ConversationPaused`. Provider streams live only as long as a
+//! turn, so a paused session holds none.
+//!
+//! While paused, only the submissions that cannot start a turn are
+//! accepted: answers to approval requests of a turn still finishing,
+//! interrupts, [`Op::Resume`] and shutdown.
+
+use std::sync::Mutex;
+
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::Op;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PauseStatus {
+    Running,
+    /// The conversation refuses submissions; the session has not handled
+    /// [`Op::Pause`] yet.
+    Requested,
+    /// The session handled [`Op::Pause`] and waits for the running turn to
+    /// end.
+    Draining,
+    Paused,
+}
+
+/// Pause status shared by a session and its conversation.
+pub(crate) struct PauseState {
+    status: watch::Sender<PauseStatus>,
+    /// The `ConversationPaused` event to send once the pause completes.
+    pending: Mutex<Option<Event>>,
+}
+
+impl Default for PauseState {
+    fn default() -> Self {
+        let (status, _) = watch::channel(PauseStatus::Running);
+        Self {
+            status,
+            pending: Mutex::new(None),
+        }
+    }
+}
+
+impl PauseState {
+    pub(crate) fn status(&self) -> PauseStatus {
+        *self.status.borrow()
+    }
+
+    /// Whether `op` may be submitted in the current status.
+    pub(crate) fn accepts(&self, op: &Op) -> bool {
+        self.status() == PauseStatus::Running
+            || matches!(
+                op,
+                Op::Pause { .. }
+                    | Op::Resume
+                    | Op::Interrupt
+                    | Op::ExecApproval { .. }
+                    | Op::PatchApproval { .. }
+                    | Op::ResolveElicitation { .. }
+                    | Op::SetSafeMode { .. }
+                    | Op::Shutdown
+                    | Op::EndSession { .. }
+            )
+    }
+
+    /// Start refusing submissions. Returns `false` when the conversation is
+    /// already paused or pausing.
+    pub(crate) fn request(&self) -> bool {
+        self.status.send_if_modified(|status| {
+            if *status == PauseStatus::Running {
+                *status = PauseStatus::Requested;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// The session handled [`Op::Pause`], and reports the pause with
+    /// `event` once it completes. Returns `false` when the pause was resumed
+    /// before that.
+    pub(crate) fn drain(&self, event: Event) -> bool {
+        let draining = self.transition(PauseStatus::Requested, PauseStatus::Draining);
+        if draining {
+            *self.pending() = Some(event);
+        }
+        draining
+    }
+
+    /// The event reporting the pause, when one is waiting for the running
+    /// turn to end. Send it, then call [`Self::complete`].
+    pub(crate) fn take_pending(&self) -> Option<Event> {
+        if self.status() == PauseStatus::Draining {
+            self.pending().take()
+        } else {
+            None
+        }
+    }
+
+    /// Enter the paused status, unless the pause was resumed meanwhile.
+    /// Returns `false` in that case.
+    pub(crate) fn complete(&self) -> bool {
+        self.transition(PauseStatus::Draining, PauseStatus::Paused)
+    }
+
+    /// Accept submissions again. Returns `false` when the conversation was
+    /// not paused or pausing.
+    pub(crate) fn resume(&self) -> bool {
+        self.status.send_if_modified(|status| {
+            if *status == PauseStatus::Running {
+                false
+            } else {
+                *status = PauseStatus::Running;
+                true
+            }
+        })
+    }
+
+    /// Wait until the status satisfies `done`.
+    pub(crate) async fn wait_for(&self, done: impl FnMut(&PauseStatus) -> bool) {
+        let mut status = self.status.subscribe();
+        // The sender lives as long as `self`, so this cannot fail.
+        let _ = status.wait_for(done).await;
+    }
+
+    fn pending(&self) -> std::sync::MutexGuard<'_, Option<Event>> {
+        match self.pending.lock() {
+            Ok(pending) => pending,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    fn transition(&self, from: PauseStatus, to: PauseStatus) -> bool {
+        self.status.send_if_modified(|status| {
+            if *status == from {
+                *status = to;
+                true
+            } else {
+                false
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::ConversationPausedEvent;
+    use codex_protocol::protocol::EventMsg;
+    use codex_protocol::protocol::PauseMode;
+    use pretty_assertions::assert_eq;
+
+    fn paused_event() -> Event {
+        Event {
+            id: "pause".to_string(),
+            msg: EventMsg::ConversationPaused(ConversationPausedEvent {
+                mode: PauseMode::FinishTurn,
+                had_running_turn: false,
+            }),
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn a_pause_refuses_turns_until_resumed() {
+        let pause = PauseState::default();
+        let turn = Op::UserInput {
+            items: Vec::new(),
+            allow_sensitive: false,
+        };
+        assert!(pause.accepts(&turn));
+
+        assert!(pause.request());
+        assert!(!pause.request());
+        assert!(!pause.accepts(&turn));
+        assert!(pause.accepts(&Op::Interrupt));
+        assert!(pause.take_pending().is_none());
+        assert!(pause.drain(paused_event()));
+        assert!(pause.take_pending().is_some());
+        assert!(pause.take_pending().is_none());
+        pause.complete();
+        assert_eq!(pause.status(), PauseStatus::Paused);
+
+        assert!(pause.resume());
+        assert!(!pause.resume());
+        assert!(pause.accepts(&turn));
+    }
+
+    #[test]
+    fn resuming_while_pausing_cancels_the_pause() {
+        let pause = PauseState::default();
+        assert!(pause.request());
+        assert!(pause.resume());
+        assert!(!pause.drain(paused_event()));
+        assert!(pause.take_pending().is_none());
+        pause.complete();
+        assert_eq!(pause.status(), PauseStatus::Running);
+    }
+}
@@ -0,0 +1,135 @@
+//! Deterministic post-processing of model output before a turn is committed.
+//!
+//! Processors registered on the [`ConversationManager`] or passed when a
+//! conversation is spawned see every item the model produced in a turn after
+//! the stream completes, but before anything is recorded to the transcript
+//! or rollout, tool calls run, or item completion events are sent. Manager
+//! processors run first, then conversation processors, each in registration
+//! order; the first rejection stops the chain.
+//!
+//! While any processor is registered, tool calls start only once the stream
+//! has completed instead of as soon as the model emits them.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+
+use std::sync::Arc;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+
+/// Items the model produced in one turn, in stream order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnOutput {
+    pub turn_id: String,
+    pub items: Vec<ResponseItem>,
+}
+
+impl TurnOutput {
+    /// Text of every assistant message in the turn.
+    pub fn assistant_texts_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        self.items
+            .iter_mut()
+            .filter_map(|item| match item {
+                ResponseItem::Message { role, content, .. } if role == "assistant" => {
+                    Some(content.iter_mut())
+                }
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|content| match content {
+                ContentItem::OutputText { text } => Some(text),
+                ContentItem::InputText { .. } | ContentItem::InputImage { .. } => None,
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostProcessOutcome {
+    /// Commit the turn, including any edits made to it.
+    Continue,
+    /// Discard the turn and report it as failed.
+    RejectTurn { reason: String },
+}
+
+pub trait TurnPostProcessor: Send + Sync {
+    fn process(&self, turn: &mut TurnOutput) -> PostProcessOutcome;
+}
+
+/// Run `processors` over `turn` in order, stopping at the first rejection.
+pub(crate) fn run_post_processors(
+    processors: &[Arc<dyn TurnPostProcessor>],
+    turn: &mut TurnOutput,
+) -> PostProcessOutcome {
+    for processor in processors {
+        if let outcome @ PostProcessOutcome::RejectTurn { .. } = processor.process(turn) {
+            return outcome;
+        }
+    }
+    PostProcessOutcome::Continue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    struct Append(&'static str);
+
+    impl TurnPostProcessor for Append {
+        fn process(&self, turn: &mut TurnOutput) -> PostProcessOutcome {
+            for text in turn.assistant_texts_mut() {
+                text.push_str(self.0);
+            }
+            PostProcessOutcome::Continue
+        }
+    }
+
+    struct Reject;
+
+    impl TurnPostProcessor for Reject {
+        fn process(&self, _turn: &mut TurnOutput) -> PostProcessOutcome {
+            PostProcessOutcome::RejectTurn {
+                reason: "no".to_string(),
+            }
+        }
+    }
+
+    fn turn(text: &str) -> TurnOutput {
+        TurnOutput {
+            turn_id: "turn-1".to_string(),
+            items: vec![ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: text.to_string(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn processors_run_in_order_and_stop_at_rejection() {
+        let processors: Vec<Arc<dyn TurnPostProcessor>> = vec![
+            Arc::new(Append("a")),
+            Arc::new(Append("b")),
+            Arc::new(Reject),
+            Arc::new(Append("c")),
+        ];
+
+        let mut output = turn("x");
+        assert_eq!(
+            run_post_processors(&processors[..2], &mut output),
+            PostProcessOutcome::Continue
+        );
+        assert_eq!(output, turn("xab"));
+
+        let mut output = turn("x");
+        assert_eq!(
+            run_post_processors(&processors, &mut output),
+            PostProcessOutcome::RejectTurn {
+                reason: "no".to_string()
+            }
+        );
+        assert_eq!(output, turn("xab"));
+    }
+}
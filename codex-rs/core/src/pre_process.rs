@@ -0,0 +1,149 @@
+//! Embedder-side checks of user input before a turn starts.
+//!
+//! Processors registered on the [`ConversationManager`] or passed when a
+//! conversation is spawned see every submitted user input before the
+//! sensitive input scan, before a turn starts or the input joins the running
+//! one, and before anything is recorded. Manager processors run first, then
+//! conversation processors, each in registration order; the first rejection
+//! stops the chain. A rejected input is reported with
+//! [`EventMsg::InputRejected`] and only leaves an audit marker in the rollout
+//! when `record_input_rejections` is set.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+//! [`EventMsg::InputRejected`]: crate::protocol::EventMsg::InputRejected
+
+use std::sync::Arc;
+
+use codex_protocol::user_input::UserInput;
+
+/// One submitted user input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserTurnInput {
+    /// Id of the submission that carried the input.
+    pub submission_id: String,
+    pub items: Vec<UserInput>,
+}
+
+impl UserTurnInput {
+    /// Text of every text item of the input.
+    pub fn texts_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        self.items.iter_mut().filter_map(|item| match item {
+            UserInput::Text { text } => Some(text),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightDecision {
+    /// Accept the input as it was handed to the processor; edits are dropped.
+    Proceed,
+    /// Accept the input with the edits made to it.
+    Rewrite,
+    /// Discard the input.
+    Reject { reason: String },
+}
+
+pub trait TurnPreProcessor: Send + Sync {
+    fn process(&self, input: &mut UserTurnInput) -> PreflightDecision;
+}
+
+/// Run `processors` over `input` in order, stopping at the first rejection.
+/// Returns [`PreflightDecision::Rewrite`] when any processor rewrote it.
+pub(crate) fn run_pre_processors(
+    processors: &[Arc<dyn TurnPreProcessor>],
+    input: &mut UserTurnInput,
+) -> PreflightDecision {
+    let mut decision = PreflightDecision::Proceed;
+    for processor in processors {
+        let mut candidate = input.clone();
+        match processor.process(&mut candidate) {
+            PreflightDecision::Proceed => {}
+            PreflightDecision::Rewrite => {
+                *input = candidate;
+                decision = PreflightDecision::Rewrite;
+            }
+            rejection @ PreflightDecision::Reject { .. } => return rejection,
+        }
+    }
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    struct Append(&'static str);
+
+    impl TurnPreProcessor for Append {
+        fn process(&self, input: &mut UserTurnInput) -> PreflightDecision {
+            for text in input.texts_mut() {
+                text.push_str(self.0);
+            }
+            PreflightDecision::Rewrite
+        }
+    }
+
+    /// Edits the input but lets it through unchanged.
+    struct Meddle;
+
+    impl TurnPreProcessor for Meddle {
+        fn process(&self, input: &mut UserTurnInput) -> PreflightDecision {
+            input.items.clear();
+            PreflightDecision::Proceed
+        }
+    }
+
+    struct Reject;
+
+    impl TurnPreProcessor for Reject {
+        fn process(&self, _input: &mut UserTurnInput) -> PreflightDecision {
+            PreflightDecision::Reject {
+                reason: "no".to_string(),
+            }
+        }
+    }
+
+    fn input(text: &str) -> UserTurnInput {
+        UserTurnInput {
+            submission_id: "sub-1".to_string(),
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn processors_run_in_order_and_stop_at_rejection() {
+        let processors: Vec<Arc<dyn TurnPreProcessor>> = vec![
+            Arc::new(Append("a")),
+            Arc::new(Meddle),
+            Arc::new(Append("b")),
+            Arc::new(Reject),
+            Arc::new(Append("c")),
+        ];
+
+        let mut submitted = input("x");
+        assert_eq!(
+            run_pre_processors(&processors[1..2], &mut submitted),
+            PreflightDecision::Proceed
+        );
+        assert_eq!(submitted, input("x"));
+
+        assert_eq!(
+            run_pre_processors(&processors[..3], &mut submitted),
+            PreflightDecision::Rewrite
+        );
+        assert_eq!(submitted, input("xab"));
+
+        let mut submitted = input("x");
+        assert_eq!(
+            run_pre_processors(&processors, &mut submitted),
+            PreflightDecision::Reject {
+                reason: "no".to_string()
+            }
+        );
+        assert_eq!(submitted, input("xab"));
+    }
+}
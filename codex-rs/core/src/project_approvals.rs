@@ -0,0 +1,264 @@
+//! Command approvals that persist for a project across sessions.
+//!
+//! Rules granted with [`ReviewDecision::ApprovedForProject`] are stored in
+//! `$CODEX_HOME/project_approvals.json`, keyed by the project root: the root
+//! of the git repository containing the cwd, or the canonicalized cwd outside
+//! git. Every session working in that project consults them before asking
+//! the user to approve a command.
+//!
+//! [`ReviewDecision::ApprovedForProject`]: codex_protocol::protocol::ReviewDecision::ApprovedForProject
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::approvals::ProjectApprovalRule;
+use serde::Deserialize;
+use serde::Serialize;
+use wildmatch::WildMatch;
+
+use crate::bash::parse_shell_lc_plain_commands;
+use crate::git_info::resolve_root_git_project_for_trust;
+use crate::json_store::JsonStore;
+use crate::parse_command::extract_shell_command;
+use crate::parse_command::shlex_join;
+
+const PROJECT_APPROVALS_FILENAME: &str = "project_approvals.json";
+
+/// Project that a set of approval rules applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProjectScope(PathBuf);
+
+impl ProjectScope {
+    pub(crate) fn for_cwd(cwd: &Path) -> Self {
+        let root = resolve_root_git_project_for_trust(cwd)
+            .unwrap_or_else(|| std::fs::canonicalize(cwd).unwrap_or_else(|_| cwd.to_path_buf()));
+        Self(root)
+    }
+
+    /// [`ProjectScope::for_cwd`] off the async runtime; resolving the scope
+    /// touches the filesystem.
+    pub(crate) async fn resolve(cwd: &Path) -> Self {
+        let owned = cwd.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::for_cwd(&owned))
+            .await
+            .unwrap_or_else(|_| Self(cwd.to_path_buf()))
+    }
+
+    pub(crate) fn key(&self) -> String {
+        self.0.to_string_lossy().into_owned()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProjectApprovalsFile {
+    #[serde(default)]
+    projects: BTreeMap<String, Vec<ProjectApprovalRule>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ProjectApprovalStore {
+    store: JsonStore<ProjectApprovalsFile>,
+}
+
+impl ProjectApprovalStore {
+    pub(crate) fn new(codex_home: &Path) -> Self {
+        Self {
+            store: JsonStore::new(codex_home.join(PROJECT_APPROVALS_FILENAME)),
+        }
+    }
+
+    /// Persist `rule` for `scope`. Granting a rule that already exists is a
+    /// no-op.
+    pub(crate) async fn grant(
+        &self,
+        scope: &ProjectScope,
+        rule: ProjectApprovalRule,
+    ) -> std::io::Result<()> {
+        self.store
+            .update(|file| {
+                let rules = file.projects.entry(scope.key()).or_default();
+                if rules.contains(&rule) {
+                    return false;
+                }
+                rules.push(rule);
+                true
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn list_rules(
+        &self,
+        scope: &ProjectScope,
+    ) -> std::io::Result<Vec<ProjectApprovalRule>> {
+        let mut file = self.store.load().await?;
+        Ok(file.projects.remove(&scope.key()).unwrap_or_default())
+    }
+
+    /// Remove `rule` from `scope`, returning whether it was present.
+    pub(crate) async fn revoke(
+        &self,
+        scope: &ProjectScope,
+        rule: &ProjectApprovalRule,
+    ) -> std::io::Result<bool> {
+        self.store
+            .update(|file| {
+                let key = scope.key();
+                let Some(rules) = file.projects.get_mut(&key) else {
+                    return false;
+                };
+                let before = rules.len();
+                rules.retain(|existing| existing != rule);
+                if rules.len() == before {
+                    return false;
+                }
+                if rules.is_empty() {
+                    file.projects.remove(&key);
+                }
+                true
+            })
+            .await
+    }
+
+    /// Whether a rule granted for `scope` covers `command`. A store that
+    /// cannot be read allows nothing.
+    pub(crate) async fn is_allowed(&self, scope: &ProjectScope, command: &[String]) -> bool {
+        let Some(tokens) = plain_command(command) else {
+            return false;
+        };
+        match self.list_rules(scope).await {
+            Ok(rules) => rules.iter().any(|rule| rule_matches(rule, &tokens)),
+            Err(err) => {
+                tracing::warn!("failed to read project approvals: {err}");
+                false
+            }
+        }
+    }
+}
+
+/// The argv of `command` when it runs exactly one plain command, unwrapping
+/// `bash -lc "..."`. Scripts that chain commands or use anything beyond plain
+/// words return `None` so a rule can never approve more than it names.
+fn plain_command(command: &[String]) -> Option<Vec<String>> {
+    if extract_shell_command(command).is_none() {
+        return Some(command.to_vec());
+    }
+    match parse_shell_lc_plain_commands(command) {
+        Some(mut commands) if commands.len() == 1 => commands.pop(),
+        _ => None,
+    }
+}
+
+/// Whether `rule` may be saved while approving `command`: it has to match
+/// the command, and a glob has to name the command's program literally so
+/// that a rule like `*` cannot approve everything.
+pub(crate) fn rule_covers(rule: &ProjectApprovalRule, command: &[String]) -> bool {
+    let Some(tokens) = plain_command(command) else {
+        return false;
+    };
+    let names_program = match rule {
+        ProjectApprovalRule::Glob { pattern } => tokens.first().is_some_and(|program| {
+            pattern.split_whitespace().next()
+                == Some(shlex_join(std::slice::from_ref(program)).as_str())
+        }),
+        ProjectApprovalRule::Exact { .. } | ProjectApprovalRule::Prefix { .. } => true,
+    };
+    names_program && rule_matches(rule, &tokens)
+}
+
+fn rule_matches(rule: &ProjectApprovalRule, tokens: &[String]) -> bool {
+    match rule {
+        ProjectApprovalRule::Exact { command } => command == tokens,
+        ProjectApprovalRule::Prefix { prefix } => !prefix.is_empty() && tokens.starts_with(prefix),
+        ProjectApprovalRule::Glob { pattern } => {
+            WildMatch::new(pattern).matches(&shlex_join(tokens))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| (*arg).to_string()).collect()
+    }
+
+    #[test]
+    fn rules_match_single_plain_commands() {
+        let prefix = ProjectApprovalRule::Prefix {
+            prefix: argv(&["cargo", "test"]),
+        };
+        let glob = ProjectApprovalRule::Glob {
+            pattern: "npm run *".to_string(),
+        };
+        let exact = ProjectApprovalRule::Exact {
+            command: argv(&["make"]),
+        };
+
+        let matches = |rule: &ProjectApprovalRule, command: &[&str]| {
+            plain_command(&argv(command)).is_some_and(|tokens| rule_matches(rule, &tokens))
+        };
+        assert!(matches(&prefix, &["cargo", "test", "--all"]));
+        assert!(matches(&prefix, &["bash", "-lc", "cargo test -p core"]));
+        assert!(!matches(
+            &prefix,
+            &["bash", "-lc", "cargo test && rm -rf ."]
+        ));
+        assert!(!matches(&prefix, &["cargo", "build"]));
+        assert!(matches(&glob, &["npm", "run", "lint"]));
+        assert!(!matches(&glob, &["npm", "install"]));
+        assert!(matches(&exact, &["make"]));
+        assert!(!matches(&exact, &["make", "install"]));
+    }
+
+    #[test]
+    fn rules_cover_only_the_command_they_approve() {
+        let command = argv(&["bash", "-lc", "cargo test -p core"]);
+
+        let covers = |rule: ProjectApprovalRule| rule_covers(&rule, &command);
+        assert!(covers(ProjectApprovalRule::Prefix {
+            prefix: argv(&["cargo", "test"]),
+        }));
+        assert!(covers(ProjectApprovalRule::Glob {
+            pattern: "cargo test *".to_string(),
+        }));
+        assert!(!covers(ProjectApprovalRule::Glob {
+            pattern: "*".to_string(),
+        }));
+        assert!(!covers(ProjectApprovalRule::Glob {
+            pattern: "*cargo test*".to_string(),
+        }));
+        assert!(!covers(ProjectApprovalRule::Prefix {
+            prefix: argv(&["rm"]),
+        }));
+        assert!(!covers(ProjectApprovalRule::Exact {
+            command: argv(&["cargo", "build"]),
+        }));
+    }
+
+    #[tokio::test]
+    async fn grant_list_and_revoke_round_trip() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        let store = ProjectApprovalStore::new(codex_home.path());
+        let scope = ProjectScope::for_cwd(project.path());
+        let rule = ProjectApprovalRule::Prefix {
+            prefix: argv(&["cargo", "test"]),
+        };
+
+        store.grant(&scope, rule.clone()).await?;
+        store.grant(&scope, rule.clone()).await?;
+        assert_eq!(store.list_rules(&scope).await?, vec![rule.clone()]);
+        assert!(store.is_allowed(&scope, &argv(&["cargo", "test"])).await);
+
+        let reloaded = ProjectApprovalStore::new(codex_home.path());
+        assert!(reloaded.revoke(&scope, &rule).await?);
+        assert!(!reloaded.revoke(&scope, &rule).await?);
+        assert_eq!(reloaded.list_rules(&scope).await?, Vec::new());
+        Ok(())
+    }
+}
@@ -127,6 +127,21 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
     }
 }
 
+/// Total size of the project docs when it exceeds `project_doc_max_bytes`,
+/// so that [`read_project_docs`] truncates them.
+pub(crate) fn project_docs_over_budget(config: &Config) -> Option<u64> {
+    if config.project_doc_max_bytes == 0 {
+        return None;
+    }
+    let total: u64 = discover_project_doc_paths(config)
+        .ok()?
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    (total > config.project_doc_max_bytes as u64).then_some(total)
+}
+
 /// Discover the list of AGENTS.md files using the same search rules as
 /// `read_project_docs`, but return the file paths instead of concatenated
 /// contents. The list is ordered from repository root to the current working
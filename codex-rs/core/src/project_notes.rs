@@ -0,0 +1,198 @@
+//! Notes about a project that sessions working in it are told about.
+//!
+//! Notes are stored in `$CODEX_HOME/project_notes.json`, keyed by the project
+//! root as [project approvals](crate::project_approvals) are. With
+//! [`Feature::ProjectNotes`] enabled, a session's prefix carries a
+//! `<project_notes>` item with the most recent notes of its project that fit
+//! in [`MAX_PREFIX_NOTES_BYTES`], and the model can save new ones with the
+//! `add_project_note` tool once the user approves.
+//!
+//! [`Feature::ProjectNotes`]: crate::features::Feature::ProjectNotes
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::json_store::JsonStore;
+use crate::project_approvals::ProjectScope;
+
+const PROJECT_NOTES_FILENAME: &str = "project_notes.json";
+
+/// The tool the model saves notes with.
+pub(crate) const ADD_PROJECT_NOTE_TOOL_NAME: &str = "add_project_note";
+
+pub(crate) const PROJECT_NOTES_OPEN_TAG: &str = "<project_notes>";
+const PROJECT_NOTES_CLOSE_TAG: &str = "</project_notes>";
+
+/// Bytes of note text the session prefix carries at most.
+pub(crate) const MAX_PREFIX_NOTES_BYTES: usize = 4 * 1024;
+
+/// A note saved for a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectNote {
+    pub id: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProjectNotesFile {
+    #[serde(default)]
+    projects: BTreeMap<String, Vec<ProjectNote>>,
+}
+
+/// The notes of the project containing a directory.
+#[derive(Debug, Clone)]
+pub struct ProjectNotes {
+    store: JsonStore<ProjectNotesFile>,
+    scope: ProjectScope,
+}
+
+impl ProjectNotes {
+    pub(crate) fn new(codex_home: &Path, cwd: &Path) -> Self {
+        Self {
+            store: JsonStore::new(codex_home.join(PROJECT_NOTES_FILENAME)),
+            scope: ProjectScope::for_cwd(cwd),
+        }
+    }
+
+    /// Save `text` as a new note.
+    pub async fn add(&self, text: impl Into<String>) -> std::io::Result<ProjectNote> {
+        let note = ProjectNote {
+            id: Uuid::new_v4().to_string(),
+            text: text.into(),
+            created_at: Utc::now(),
+        };
+        self.store
+            .update(|file| {
+                file.projects
+                    .entry(self.scope.key())
+                    .or_default()
+                    .push(note.clone());
+                true
+            })
+            .await?;
+        Ok(note)
+    }
+
+    /// The notes of the project, most recent first.
+    pub async fn list(&self) -> std::io::Result<Vec<ProjectNote>> {
+        let mut file = self.store.load().await?;
+        let mut notes = file.projects.remove(&self.scope.key()).unwrap_or_default();
+        // Notes are stored in the order they were added.
+        notes.reverse();
+        Ok(notes)
+    }
+
+    /// Remove the note `id`, returning whether it was present.
+    pub async fn remove(&self, id: &str) -> std::io::Result<bool> {
+        self.store
+            .update(|file| {
+                let key = self.scope.key();
+                let Some(notes) = file.projects.get_mut(&key) else {
+                    return false;
+                };
+                let before = notes.len();
+                notes.retain(|note| note.id != id);
+                if notes.len() == before {
+                    return false;
+                }
+                if notes.is_empty() {
+                    file.projects.remove(&key);
+                }
+                true
+            })
+            .await
+    }
+}
+
+/// The most recent of `notes`, listed most recent first, whose text fits in
+/// `max_bytes` together.
+pub(crate) fn select_for_prefix(notes: Vec<ProjectNote>, max_bytes: usize) -> Vec<ProjectNote> {
+    let mut used = 0;
+    notes
+        .into_iter()
+        .take_while(|note| {
+            used += note.text.len();
+            used <= max_bytes
+        })
+        .collect()
+}
+
+/// The session prefix item telling the model about `notes`, `None` when
+/// there are none.
+pub(crate) fn prefix_item(notes: &[ProjectNote]) -> Option<ResponseItem> {
+    if notes.is_empty() {
+        return None;
+    }
+    let mut lines = vec![
+        PROJECT_NOTES_OPEN_TAG.to_string(),
+        "Notes saved in earlier sessions of this project, most recent first:".to_string(),
+    ];
+    lines.extend(notes.iter().map(|note| format!("- {}", note.text)));
+    lines.push(PROJECT_NOTES_CLOSE_TAG.to_string());
+    Some(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: lines.join("\n"),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn note(text: &str, minute: u32) -> ProjectNote {
+        ProjectNote {
+            id: text.to_string(),
+            text: text.to_string(),
+            created_at: DateTime::parse_from_rfc3339(&format!("2025-01-01T00:{minute:02}:00Z"))
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[tokio::test]
+    async fn add_list_and_remove_round_trip() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        let other = TempDir::new()?;
+        let notes = ProjectNotes::new(codex_home.path(), project.path());
+
+        let first = notes.add("never edit Cargo.lock by hand").await?;
+        let second = notes.add("run just fmt before committing").await?;
+        assert_eq!(notes.list().await?, vec![second.clone(), first.clone()]);
+        assert_eq!(
+            ProjectNotes::new(codex_home.path(), other.path())
+                .list()
+                .await?,
+            Vec::new()
+        );
+
+        assert!(notes.remove(&first.id).await?);
+        assert!(!notes.remove(&first.id).await?);
+        assert_eq!(notes.list().await?, vec![second]);
+        Ok(())
+    }
+
+    #[test]
+    fn the_prefix_keeps_the_most_recent_notes_that_fit() {
+        let notes = vec![note("newest", 3), note("newer", 2), note("oldest", 1)];
+        assert_eq!(
+            select_for_prefix(notes, "newest".len() + "newer".len()),
+            vec![note("newest", 3), note("newer", 2)]
+        );
+        assert_eq!(prefix_item(&[]), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Recording model provider traffic to cassette files and serving it back, so
+//! integration tests of crates embedding Codex run without the network and
+//! without a hand-rolled fake provider.
+//!
+//! Both providers are local HTTP servers standing in for a model provider.
+//! Each converts into the [`ModelProviderInfo`] that points at it, so either
+//! can be passed to [`crate::ConversationManager::with_models_provider`].
+//! [`RecordingProvider`] forwards every request to the provider it wraps and
+//! writes the exchanges to a cassette when finished. [`PlaybackProvider`]
+//! answers from a cassette. It matches each request to an unused recorded one
+//! with the same [`RequestFingerprint`], and answers requests nothing matches
+//! with `400 Bad Request` and a diff against the closest recorded fingerprint.
+//!
+//! Cassettes hold no credentials. Request headers are not recorded, and
+//! neither are the wrapped provider's tokens, keys or headers. Only the
+//! status, content type and body of responses are kept.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::Method;
+use axum::http::StatusCode;
+use axum::http::Uri;
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::header::HOST;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use regex_lite::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::ModelProviderInfo;
+
+/// Base URL of a provider that does not set one, as for API key auth.
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Request body fields that differ between otherwise identical sessions.
+const VOLATILE_FIELDS: &[&str] = &["prompt_cache_key"];
+
+#[expect(clippy::expect_used)]
+static UUID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
+        .expect("valid UUID pattern")
+});
+
+/// What a request is matched on: its method, its path and query relative to
+/// the provider's base URL, and its JSON body without
+/// [`VOLATILE_FIELDS`] and with UUIDs replaced by `<uuid>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestFingerprint {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+impl RequestFingerprint {
+    fn new(method: &Method, uri: &Uri, body: &[u8]) -> Self {
+        let path = request_target(uri);
+        let mut body = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(body)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+        };
+        if let Value::Object(fields) = &mut body {
+            for field in VOLATILE_FIELDS {
+                fields.remove(*field);
+            }
+        }
+        normalize_ids(&mut body);
+        Self {
+            method: method.to_string(),
+            path: UUID.replace_all(&path, "<uuid>").into_owned(),
+            body,
+        }
+    }
+
+    fn pretty(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+fn normalize_ids(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if UUID.is_match(text) {
+                *text = UUID.replace_all(text, "<uuid>").into_owned();
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize_ids),
+        Value::Object(fields) => fields.values_mut().for_each(normalize_ids),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Cassette {
+    /// The wrapped provider, without credentials, pointing nowhere.
+    provider: ModelProviderInfo,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RequestFingerprint,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    body: String,
+}
+
+impl IntoResponse for RecordedResponse {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::BAD_GATEWAY);
+        match self.content_type {
+            Some(content_type) => {
+                (status, [(CONTENT_TYPE, content_type)], self.body).into_response()
+            }
+            None => (status, self.body).into_response(),
+        }
+    }
+}
+
+/// `provider` with its credentials, headers and endpoints removed.
+fn sanitized(provider: &ModelProviderInfo) -> ModelProviderInfo {
+    ModelProviderInfo {
+        base_url: None,
+        env_key: None,
+        env_key_instructions: None,
+        experimental_bearer_token: None,
+        http_headers: None,
+        env_http_headers: None,
+        fallback_base_urls: None,
+        ..provider.clone()
+    }
+}
+
+/// The local server behind a provider, stopped when dropped.
+pub(crate) struct LocalServer {
+    pub(crate) base_url: String,
+    task: JoinHandle<()>,
+}
+
+impl LocalServer {
+    pub(crate) async fn start(router: Router) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let task = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, router).await {
+                warn!("local provider server stopped: {err}");
+            }
+        });
+        Ok(Self { base_url, task })
+    }
+}
+
+impl Drop for LocalServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Forwards requests to a real provider and records the exchanges.
+pub struct RecordingProvider {
+    provider: ModelProviderInfo,
+    cassette_path: PathBuf,
+    state: Arc<RecordingState>,
+    _server: LocalServer,
+}
+
+struct RecordingState {
+    upstream_base_url: String,
+    client: reqwest::Client,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl RecordingProvider {
+    /// Start recording the traffic sent to `upstream`. Nothing is written
+    /// to `cassette_path` before [`Self::finish`].
+    pub async fn start(
+        upstream: ModelProviderInfo,
+        cassette_path: impl Into<PathBuf>,
+    ) -> io::Result<Self> {
+        let state = Arc::new(RecordingState {
+            upstream_base_url: upstream
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            client: reqwest::Client::new(),
+            interactions: Mutex::new(Vec::new()),
+        });
+        let server = LocalServer::start(
+            Router::new()
+                .fallback(record)
+                .with_state(Arc::clone(&state)),
+        )
+        .await?;
+        let provider = ModelProviderInfo {
+            base_url: Some(server.base_url.clone()),
+            fallback_base_urls: None,
+            ..upstream
+        };
+        Ok(Self {
+            provider,
+            cassette_path: cassette_path.into(),
+            state,
+            _server: server,
+        })
+    }
+
+    /// Stop recording and write the cassette.
+    pub async fn finish(self) -> io::Result<()> {
+        let cassette = Cassette {
+            provider: sanitized(&self.provider),
+            interactions: std::mem::take(&mut *lock(&self.state.interactions)),
+        };
+        let json = serde_json::to_string_pretty(&cassette).map_err(io::Error::other)?;
+        tokio::fs::write(&self.cassette_path, json).await
+    }
+}
+
+impl From<&RecordingProvider> for ModelProviderInfo {
+    fn from(recording: &RecordingProvider) -> Self {
+        recording.provider.clone()
+    }
+}
+
+async fn record(
+    State(state): State<Arc<RecordingState>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = RequestFingerprint::new(&method, &uri, &body);
+    let url = format!("{}{}", state.upstream_base_url, request_target(&uri));
+    let mut forwarded = headers;
+    forwarded.remove(HOST);
+    forwarded.remove(CONTENT_LENGTH);
+    let upstream = state
+        .client
+        .request(method, url)
+        .headers(forwarded)
+        .body(body)
+        .send()
+        .await;
+    let response = match upstream {
+        Ok(upstream) => {
+            let status = upstream.status().as_u16();
+            let content_type = upstream
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            match upstream.bytes().await {
+                Ok(bytes) => RecordedResponse {
+                    status,
+                    content_type,
+                    body: String::from_utf8_lossy(&bytes).into_owned(),
+                },
+                Err(err) => return upstream_failure(&err),
+            }
+        }
+        Err(err) => return upstream_failure(&err),
+    };
+    lock(&state.interactions).push(Interaction {
+        request,
+        response: response.clone(),
+    });
+    response.into_response()
+}
+
+pub(crate) fn request_target(uri: &Uri) -> String {
+    uri.path_and_query()
+        .map_or_else(|| uri.path().to_string(), ToString::to_string)
+}
+
+pub(crate) fn upstream_failure(err: &reqwest::Error) -> Response {
+    (
+        StatusCode::BAD_GATEWAY,
+        format!("local provider could not reach the provider: {err}"),
+    )
+        .into_response()
+}
+
+/// Serves the exchanges of a cassette written by [`RecordingProvider`].
+pub struct PlaybackProvider {
+    provider: ModelProviderInfo,
+    state: Arc<PlaybackState>,
+    _server: LocalServer,
+}
+
+struct PlaybackState {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+    mismatches: Mutex<Vec<String>>,
+}
+
+impl PlaybackProvider {
+    /// Start serving the cassette at `cassette_path`.
+    pub async fn load(cassette_path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = tokio::fs::read_to_string(cassette_path).await?;
+        let cassette: Cassette = serde_json::from_str(&json)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let state = Arc::new(PlaybackState {
+            used: Mutex::new(vec![false; cassette.interactions.len()]),
+            interactions: cassette.interactions,
+            mismatches: Mutex::new(Vec::new()),
+        });
+        let server =
+            LocalServer::start(Router::new().fallback(play).with_state(Arc::clone(&state))).await?;
+        let provider = ModelProviderInfo {
+            base_url: Some(server.base_url.clone()),
+            ..cassette.provider
+        };
+        Ok(Self {
+            provider,
+            state,
+            _server: server,
+        })
+    }
+
+    /// Diffs of the requests no recorded request matched, in arrival order.
+    pub fn mismatches(&self) -> Vec<String> {
+        lock(&self.state.mismatches).clone()
+    }
+
+    /// Number of recorded exchanges not played back yet.
+    pub fn unplayed(&self) -> usize {
+        lock(&self.state.used).iter().filter(|used| !**used).count()
+    }
+}
+
+impl From<&PlaybackProvider> for ModelProviderInfo {
+    fn from(playback: &PlaybackProvider) -> Self {
+        playback.provider.clone()
+    }
+}
+
+async fn play(
+    State(state): State<Arc<PlaybackState>>,
+    method: Method,
+    uri: Uri,
+    body: Bytes,
+) -> Response {
+    let request = RequestFingerprint::new(&method, &uri, &body);
+    let unused: Vec<usize> = {
+        let mut used = lock(&state.used);
+        let unused: Vec<usize> = (0..used.len()).filter(|index| !used[*index]).collect();
+        if let Some(&index) = unused
+            .iter()
+            .find(|index| state.interactions[**index].request == request)
+        {
+            used[index] = true;
+            return state.interactions[index].response.clone().into_response();
+        }
+        unused
+    };
+
+    let closest = unused
+        .iter()
+        .find(|index| {
+            let recorded = &state.interactions[**index].request;
+            recorded.method == request.method && recorded.path == request.path
+        })
+        .or(unused.first());
+    let recorded = closest.map_or_else(String::new, |index| {
+        state.interactions[*index].request.pretty()
+    });
+    let diff = similar::TextDiff::from_lines(&recorded, &request.pretty())
+        .unified_diff()
+        .context_radius(3)
+        .header("recorded", "received")
+        .to_string();
+    let message = format!(
+        "no recorded request matches {} {}\n{diff}",
+        request.method, request.path
+    );
+    warn!("{message}");
+    lock(&state.mismatches).push(message.clone());
+    (StatusCode::BAD_REQUEST, message).into_response()
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
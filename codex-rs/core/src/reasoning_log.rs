@@ -0,0 +1,221 @@
+//! Reasoning summaries kept per turn, so a client can show why the model did
+//! something long after the reasoning events streamed by.
+//!
+//! Turns are counted like forks count them: the nth user message starts turn
+//! `n`, and the summaries of the reasoning items that follow it belong to that
+//! turn. Raw reasoning content is never kept here and is stripped from
+//! histories that are fed back to the model in a new form, see
+//! [`without_raw_content`].
+
+use std::sync::Mutex;
+
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::CompactedItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+
+use crate::event_mapping::parse_turn_item;
+
+/// Reasoning summaries of a conversation, by turn index.
+#[derive(Debug, Default)]
+pub(crate) struct ReasoningLog {
+    turns: Mutex<Vec<Vec<String>>>,
+}
+
+impl ReasoningLog {
+    /// Account for items as they are added to the history.
+    pub(crate) fn record(&self, items: &[ResponseItem]) {
+        let mut turns = self.lock_turns();
+        for item in items {
+            record_item(&mut turns, item);
+        }
+    }
+
+    /// Rebuild the log from a resumed or forked rollout.
+    pub(crate) fn seed(&self, items: &[RolloutItem]) {
+        let mut turns = self.lock_turns();
+        turns.clear();
+        for item in items {
+            if let RolloutItem::ResponseItem(item) = item {
+                record_item(&mut turns, item);
+            }
+        }
+    }
+
+    /// Summaries of turn `index`, or `None` when the conversation has fewer
+    /// turns.
+    pub(crate) fn for_turn(&self, index: usize) -> Option<Vec<String>> {
+        self.lock_turns().get(index).cloned()
+    }
+
+    fn lock_turns(&self) -> std::sync::MutexGuard<'_, Vec<Vec<String>>> {
+        self.turns
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn record_item(turns: &mut Vec<Vec<String>>, item: &ResponseItem) {
+    match item {
+        ResponseItem::Message { .. } => {
+            if matches!(parse_turn_item(item), Some(TurnItem::UserMessage(_))) {
+                turns.push(Vec::new());
+            }
+        }
+        ResponseItem::Reasoning { summary, .. } => {
+            // Reasoning before the first user message has no turn to go to.
+            if let Some(turn) = turns.last_mut() {
+                turn.extend(
+                    summary
+                        .iter()
+                        .map(|ReasoningItemReasoningSummary::SummaryText { text }| text.clone()),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `item` without the raw content it holds if it is a reasoning item.
+/// Summaries and encrypted content, which providers accept back, are kept.
+pub(crate) fn without_raw_content(item: ResponseItem) -> ResponseItem {
+    match item {
+        ResponseItem::Reasoning {
+            id,
+            summary,
+            encrypted_content,
+            ..
+        } => ResponseItem::Reasoning {
+            id,
+            summary,
+            content: None,
+            encrypted_content,
+        },
+        item => item,
+    }
+}
+
+/// `items` as they are written to the rollout when reasoning must not be
+/// persisted: reasoning items keep only their encrypted content, so a resumed
+/// session can still send it back, and reasoning events are dropped.
+pub(crate) fn strip_for_rollout(items: &[RolloutItem]) -> Vec<RolloutItem> {
+    items
+        .iter()
+        .filter(|item| {
+            !matches!(
+                item,
+                RolloutItem::EventMsg(
+                    EventMsg::AgentReasoning(_) | EventMsg::AgentReasoningRawContent(_)
+                )
+            )
+        })
+        .map(|item| match item {
+            RolloutItem::ResponseItem(item) => {
+                RolloutItem::ResponseItem(strip_reasoning(item.clone()))
+            }
+            RolloutItem::Compacted(CompactedItem {
+                message,
+                replacement_history,
+            }) => RolloutItem::Compacted(CompactedItem {
+                message: message.clone(),
+                replacement_history: replacement_history
+                    .clone()
+                    .map(|history| history.into_iter().map(strip_reasoning).collect()),
+            }),
+            item => item.clone(),
+        })
+        .collect()
+}
+
+fn strip_reasoning(item: ResponseItem) -> ResponseItem {
+    match item {
+        ResponseItem::Reasoning {
+            id,
+            encrypted_content,
+            ..
+        } => ResponseItem::Reasoning {
+            id,
+            summary: Vec::new(),
+            content: None,
+            encrypted_content,
+        },
+        item => item,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ReasoningItemContent;
+    use pretty_assertions::assert_eq;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn reasoning(summary: &str) -> ResponseItem {
+        ResponseItem::Reasoning {
+            id: "r".to_string(),
+            summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                text: summary.to_string(),
+            }],
+            content: Some(vec![ReasoningItemContent::ReasoningText {
+                text: "raw".to_string(),
+            }]),
+            encrypted_content: Some("opaque".to_string()),
+        }
+    }
+
+    #[test]
+    fn summaries_are_grouped_by_user_turn() {
+        let log = ReasoningLog::default();
+        log.record(&[reasoning("before any turn"), user("first")]);
+        log.record(&[reasoning("plan"), reasoning("check"), user("second")]);
+        log.record(&[user("third"), reasoning("answer")]);
+
+        assert_eq!(
+            log.for_turn(0),
+            Some(vec!["plan".to_string(), "check".to_string()])
+        );
+        assert_eq!(log.for_turn(1), Some(Vec::new()));
+        assert_eq!(log.for_turn(2), Some(vec!["answer".to_string()]));
+        assert_eq!(log.for_turn(3), None);
+    }
+
+    #[test]
+    fn stripping_keeps_encrypted_content_only() {
+        assert_eq!(
+            without_raw_content(reasoning("plan")),
+            ResponseItem::Reasoning {
+                id: "r".to_string(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "plan".to_string(),
+                }],
+                content: None,
+                encrypted_content: Some("opaque".to_string()),
+            }
+        );
+
+        let stripped = strip_for_rollout(&[
+            RolloutItem::ResponseItem(reasoning("plan")),
+            RolloutItem::EventMsg(EventMsg::AgentReasoning(
+                codex_protocol::protocol::AgentReasoningEvent {
+                    text: "plan".to_string(),
+                },
+            )),
+        ]);
+        let serialized = serde_json::to_string(&stripped).expect("serialize");
+        assert!(!serialized.contains("plan"), "{serialized}");
+        assert!(!serialized.contains("raw"), "{serialized}");
+        assert!(serialized.contains("opaque"), "{serialized}");
+    }
+}
@@ -0,0 +1,541 @@
+//! Debug tracing of the raw requests sent to model providers and the raw
+//! responses received from them.
+//!
+//! Tracing is configured programmatically through [`Config::request_trace`]
+//! and is off by default. Traces are never written to the rollout: they are
+//! only delivered to the configured [`TraceTarget`].
+//!
+//! [`Config::request_trace`]: crate::config::Config::request_trace
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use codex_client::HttpTransport;
+use codex_client::Request;
+use codex_client::Response;
+use codex_client::StreamResponse;
+use codex_client::TransportError;
+use codex_protocol::ConversationId;
+use codex_utils_string::take_bytes_at_char_boundary;
+use futures::Stream;
+use http::HeaderMap;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+use url::Url;
+
+/// Headers that carry credentials and must never appear in a trace.
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "api-key",
+    "x-api-key",
+    "chatgpt-account-id",
+];
+
+/// Query parameters that carry credentials, such as Azure's `api-key`. They
+/// are removed from traced URLs.
+const SENSITIVE_QUERY_PARAMS: &[&str] =
+    &["api-key", "api_key", "key", "access_token", "token", "sig"];
+
+/// Receives every traced request/response pair when tracing in-process.
+pub trait RequestTracer: Send + Sync {
+    fn on_exchange(&self, exchange: &TracedExchange);
+}
+
+/// Where request traces are delivered.
+#[derive(Clone)]
+pub enum TraceTarget {
+    /// Write one `<conversation>-turn-<n>-<seq>-request.json` and one
+    /// matching `-response.json` file per exchange into `path`. Bodies larger
+    /// than `max_body_bytes` are truncated.
+    Directory {
+        path: PathBuf,
+        max_body_bytes: Option<usize>,
+    },
+    /// Hand every exchange to an in-process tracer.
+    Callback(Arc<dyn RequestTracer>),
+}
+
+impl TraceTarget {
+    fn max_body_bytes(&self) -> Option<usize> {
+        match self {
+            TraceTarget::Directory { max_body_bytes, .. } => *max_body_bytes,
+            TraceTarget::Callback(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for TraceTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceTarget::Directory {
+                path,
+                max_body_bytes,
+            } => f
+                .debug_struct("Directory")
+                .field("path", path)
+                .field("max_body_bytes", max_body_bytes)
+                .finish(),
+            TraceTarget::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+impl PartialEq for TraceTarget {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                TraceTarget::Directory {
+                    path,
+                    max_body_bytes,
+                },
+                TraceTarget::Directory {
+                    path: other_path,
+                    max_body_bytes: other_max_body_bytes,
+                },
+            ) => path == other_path && max_body_bytes == other_max_body_bytes,
+            (TraceTarget::Callback(tracer), TraceTarget::Callback(other_tracer)) => {
+                Arc::ptr_eq(tracer, other_tracer)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A single request sent to the provider, with credentials removed.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TracedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<Value>,
+    pub body_truncated: bool,
+}
+
+/// The provider's response to a [`TracedRequest`]. `body` holds the raw
+/// bytes received (SSE frames for streaming requests).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TracedResponse {
+    pub status: Option<u16>,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+    pub body_truncated: bool,
+    pub error: Option<String>,
+}
+
+/// A request/response pair, identified by conversation, turn, and the
+/// position of the request within the turn.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TracedExchange {
+    pub conversation_id: String,
+    pub turn: u64,
+    pub sequence: u64,
+    pub request: TracedRequest,
+    pub response: TracedResponse,
+}
+
+/// Session-wide handle that hands out per-turn tracers.
+#[derive(Debug)]
+pub(crate) struct RequestTraceSink {
+    target: TraceTarget,
+    conversation_id: ConversationId,
+    turns: AtomicU64,
+}
+
+impl RequestTraceSink {
+    pub(crate) fn new(target: TraceTarget, conversation_id: ConversationId) -> Self {
+        Self {
+            target,
+            conversation_id,
+            turns: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn next_turn(&self) -> TurnRequestTracer {
+        let turn = self.turns.fetch_add(1, Ordering::Relaxed) + 1;
+        TurnRequestTracer {
+            target: self.target.clone(),
+            conversation_id: self.conversation_id.to_string(),
+            turn,
+            sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+/// Traces the requests made while running a single turn.
+#[derive(Debug, Clone)]
+pub(crate) struct TurnRequestTracer {
+    target: TraceTarget,
+    conversation_id: String,
+    turn: u64,
+    sequence: Arc<AtomicU64>,
+}
+
+impl TurnRequestTracer {
+    fn begin(&self, req: &Request) -> PendingExchange {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        PendingExchange {
+            tracer: self.clone(),
+            sequence,
+            request: trace_request(req, self.target.max_body_bytes()),
+        }
+    }
+}
+
+struct PendingExchange {
+    tracer: TurnRequestTracer,
+    sequence: u64,
+    request: TracedRequest,
+}
+
+impl PendingExchange {
+    fn finish(self, response: TracedResponse) {
+        let exchange = TracedExchange {
+            conversation_id: self.tracer.conversation_id,
+            turn: self.tracer.turn,
+            sequence: self.sequence,
+            request: self.request,
+            response,
+        };
+        match &self.tracer.target {
+            TraceTarget::Directory { path, .. } => {
+                let path = path.clone();
+                let write = move || {
+                    if let Err(err) = write_exchange(&path, &exchange) {
+                        warn!("failed to write request trace to {}: {err}", path.display());
+                    }
+                };
+                // Keep the file writes off the runtime. A stream dropped
+                // outside of it finishes its exchange in place.
+                match tokio::runtime::Handle::try_current() {
+                    Ok(handle) => {
+                        handle.spawn_blocking(write);
+                    }
+                    Err(_) => write(),
+                }
+            }
+            TraceTarget::Callback(tracer) => tracer.on_exchange(&exchange),
+        }
+    }
+
+    fn finish_with_error(self, err: &TransportError) {
+        let (status, headers, body) = match err {
+            TransportError::Http {
+                status,
+                headers,
+                body,
+            } => (
+                Some(status.as_u16()),
+                headers.as_ref().map(sanitize_headers).unwrap_or_default(),
+                body.clone().unwrap_or_default(),
+            ),
+            _ => (None, BTreeMap::new(), String::new()),
+        };
+        let max_body_bytes = self.tracer.target.max_body_bytes();
+        let (body, body_truncated) = cap_text(body, max_body_bytes);
+        self.finish(TracedResponse {
+            status,
+            headers,
+            body,
+            body_truncated,
+            error: Some(err.to_string()),
+        });
+    }
+}
+
+fn write_exchange(dir: &Path, exchange: &TracedExchange) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let stem = format!(
+        "{}-turn-{:04}-{:02}",
+        exchange.conversation_id, exchange.turn, exchange.sequence
+    );
+    let request = serde_json::json!({
+        "conversation_id": exchange.conversation_id,
+        "turn": exchange.turn,
+        "sequence": exchange.sequence,
+        "request": exchange.request,
+    });
+    let response = serde_json::json!({
+        "conversation_id": exchange.conversation_id,
+        "turn": exchange.turn,
+        "sequence": exchange.sequence,
+        "response": exchange.response,
+    });
+    write_atomically(
+        &dir.join(format!("{stem}-request.json")),
+        &serde_json::to_vec_pretty(&request)?,
+    )?;
+    write_atomically(
+        &dir.join(format!("{stem}-response.json")),
+        &serde_json::to_vec_pretty(&response)?,
+    )
+}
+
+/// Write `contents` to `path` through a temporary file, so that a trace file
+/// is never seen half written.
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn trace_request(req: &Request, max_body_bytes: Option<usize>) -> TracedRequest {
+    let (body, body_truncated) = match &req.body {
+        Some(body) => {
+            let serialized = body.to_string();
+            match max_body_bytes {
+                Some(max) if serialized.len() > max => (
+                    Some(Value::String(
+                        take_bytes_at_char_boundary(&serialized, max).to_string(),
+                    )),
+                    true,
+                ),
+                _ => (Some(body.clone()), false),
+            }
+        }
+        None => (None, false),
+    };
+    TracedRequest {
+        method: req.method.to_string(),
+        url: sanitize_url(&req.url),
+        headers: sanitize_headers(&req.headers),
+        body,
+        body_truncated,
+    }
+}
+
+fn sanitize_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SENSITIVE_HEADERS.contains(&name.as_str()))
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// `url` without its credential query parameters. A URL that does not parse
+/// is traced without any query.
+fn sanitize_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.split('?').next().unwrap_or_default().to_string();
+    };
+    if parsed.query().is_none() {
+        return url.to_string();
+    }
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| {
+            !SENSITIVE_QUERY_PARAMS
+                .iter()
+                .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+        })
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.to_string()
+}
+
+fn cap_text(text: String, max_body_bytes: Option<usize>) -> (String, bool) {
+    match max_body_bytes {
+        Some(max) if text.len() > max => {
+            (take_bytes_at_char_boundary(&text, max).to_string(), true)
+        }
+        _ => (text, false),
+    }
+}
+
+/// [`HttpTransport`] wrapper that reports every exchange to the turn's tracer.
+/// Without a tracer it forwards to the inner transport unchanged.
+pub(crate) struct TracingTransport<T> {
+    inner: T,
+    tracer: Option<TurnRequestTracer>,
+}
+
+impl<T> TracingTransport<T> {
+    pub(crate) fn new(inner: T, tracer: Option<TurnRequestTracer>) -> Self {
+        Self { inner, tracer }
+    }
+}
+
+#[async_trait]
+impl<T: HttpTransport> HttpTransport for TracingTransport<T> {
+    async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+        let Some(pending) = self.tracer.as_ref().map(|tracer| tracer.begin(&req)) else {
+            return self.inner.execute(req).await;
+        };
+        let result = self.inner.execute(req).await;
+        match &result {
+            Ok(response) => {
+                let body = String::from_utf8_lossy(&response.body).into_owned();
+                let (body, body_truncated) = cap_text(body, pending.tracer.target.max_body_bytes());
+                pending.finish(TracedResponse {
+                    status: Some(response.status.as_u16()),
+                    headers: sanitize_headers(&response.headers),
+                    body,
+                    body_truncated,
+                    error: None,
+                });
+            }
+            Err(err) => pending.finish_with_error(err),
+        }
+        result
+    }
+
+    async fn stream(&self, req: Request) -> Result<StreamResponse, TransportError> {
+        let Some(pending) = self.tracer.as_ref().map(|tracer| tracer.begin(&req)) else {
+            return self.inner.stream(req).await;
+        };
+        match self.inner.stream(req).await {
+            Ok(response) => {
+                let traced = TracedByteStream {
+                    inner: response.bytes,
+                    status: response.status.as_u16(),
+                    headers: sanitize_headers(&response.headers),
+                    buffer: Vec::new(),
+                    error: None,
+                    pending: Some(pending),
+                };
+                Ok(StreamResponse {
+                    status: response.status,
+                    headers: response.headers,
+                    bytes: Box::pin(traced),
+                })
+            }
+            Err(err) => {
+                pending.finish_with_error(&err);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Copies every chunk of a streaming response and reports the exchange once
+/// the stream ends or is dropped.
+struct TracedByteStream {
+    inner: codex_client::ByteStream,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    buffer: Vec<u8>,
+    error: Option<String>,
+    pending: Option<PendingExchange>,
+}
+
+impl TracedByteStream {
+    fn finish(&mut self) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let body = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+        let (body, body_truncated) = cap_text(body, pending.tracer.target.max_body_bytes());
+        pending.finish(TracedResponse {
+            status: Some(self.status),
+            headers: std::mem::take(&mut self.headers),
+            body,
+            body_truncated,
+            error: self.error.take(),
+        });
+    }
+}
+
+impl Stream for TracedByteStream {
+    type Item = Result<Bytes, TransportError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let poll = this.inner.as_mut().poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => this.buffer.extend_from_slice(chunk),
+            Poll::Ready(Some(Err(err))) => this.error = Some(err.to_string()),
+            Poll::Ready(None) => this.finish(),
+            Poll::Pending => {}
+        }
+        poll
+    }
+}
+
+impl Drop for TracedByteStream {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use http::Method;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn trace_request_strips_credentials() {
+        let mut req = Request::new(Method::POST, "https://example.com/v1/responses".into());
+        req.headers.insert(
+            http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        req.headers
+            .insert("x-api-key", HeaderValue::from_static("secret"));
+        req.headers
+            .insert("originator", HeaderValue::from_static("codex_cli_rs"));
+
+        let traced = trace_request(&req, None);
+
+        assert_eq!(
+            traced.headers,
+            BTreeMap::from([("originator".to_string(), "codex_cli_rs".to_string())])
+        );
+    }
+
+    #[test]
+    fn trace_request_strips_credential_query_params() {
+        let req = Request::new(
+            Method::POST,
+            "https://example.openai.azure.com/openai/responses?api-version=2025-04-01&api-key=secret"
+                .into(),
+        );
+
+        let traced = trace_request(&req, None);
+
+        assert_eq!(
+            traced.url,
+            "https://example.openai.azure.com/openai/responses?api-version=2025-04-01"
+        );
+        assert_eq!(
+            sanitize_url("https://example.com/v1/responses?key=secret"),
+            "https://example.com/v1/responses"
+        );
+    }
+
+    #[test]
+    fn trace_request_caps_body() {
+        let mut req = Request::new(Method::POST, "https://example.com/v1/responses".into());
+        req.body = Some(serde_json::json!({ "input": "abcdefghij" }));
+
+        let traced = trace_request(&req, Some(8));
+
+        assert_eq!(traced.body, Some(Value::String("{\"input\"".to_string())));
+        assert!(traced.body_truncated);
+    }
+}
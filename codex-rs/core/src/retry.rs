@@ -0,0 +1,536 @@
+//! Retries with backoff for the requests Codex makes on its own.
+//!
+//! Each subsystem that retries has a [`RetryPolicy`], one profile of the
+//! `[retry]` config table: `model_requests` for streaming a turn,
+//! `preset_fetch` for fetching the model list and `auth_refresh` for
+//! refreshing ChatGPT tokens after a request was rejected as unauthorized.
+//! The defaults keep the behavior each subsystem had before it had a
+//! profile. [`retry`] runs an operation under a policy; loops that do more
+//! between attempts drive a [`RetryBackoff`] themselves. Either way every
+//! retry is described by a [`RetryingEvent`], which sessions report as
+//! `EventMsg::Retrying`.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use codex_protocol::protocol::RetrySubsystem;
+use codex_protocol::protocol::RetryingEvent;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::auth::RefreshTokenError;
+use crate::error::CodexErr;
+
+/// Each retry waits twice as long as the one before.
+const BACKOFF_FACTOR: f64 = 2.0;
+
+/// What kind of failure an error is, which decides whether a policy retries
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryClass {
+    /// `429 Too Many Requests`.
+    RateLimited,
+    /// Any other `4xx` status.
+    ClientError,
+    /// A `5xx` status.
+    ServerError,
+    /// The request did not get a status: the connection failed or the
+    /// response stream broke off.
+    Transport,
+    /// Retrying cannot help.
+    Permanent,
+}
+
+impl RetryClass {
+    pub fn of_status(status: StatusCode) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            RetryClass::RateLimited
+        } else if status.is_client_error() {
+            RetryClass::ClientError
+        } else if status.is_server_error() {
+            RetryClass::ServerError
+        } else {
+            RetryClass::Permanent
+        }
+    }
+}
+
+/// Errors a [`RetryPolicy`] can classify.
+pub trait Retryable {
+    fn retry_class(&self) -> RetryClass;
+
+    /// How long the server asked to wait before the next attempt.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Retryable for CodexErr {
+    fn retry_class(&self) -> RetryClass {
+        match self {
+            CodexErr::UnexpectedStatus(err) => RetryClass::of_status(err.status),
+            CodexErr::RetryLimit(err) => RetryClass::of_status(err.status),
+            CodexErr::InternalServerError => RetryClass::ServerError,
+            CodexErr::TurnAborted
+            | CodexErr::Interrupted
+            | CodexErr::TurnRejected(_)
+            | CodexErr::EnvVar(_)
+            | CodexErr::Fatal(_)
+            | CodexErr::ContextWindowExceeded
+            | CodexErr::UsageLimitReached(_)
+            | CodexErr::UsageNotIncluded
+            | CodexErr::QuotaExceeded
+            | CodexErr::InvalidImageRequest()
+            | CodexErr::InvalidRequest(_)
+            | CodexErr::RefreshTokenFailed(_)
+            | CodexErr::Offline => RetryClass::Permanent,
+            _ => RetryClass::Transport,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            CodexErr::Stream(_, delay) => *delay,
+            _ => None,
+        }
+    }
+}
+
+impl Retryable for RefreshTokenError {
+    fn retry_class(&self) -> RetryClass {
+        match self {
+            RefreshTokenError::Permanent(_) => RetryClass::Permanent,
+            RefreshTokenError::Transient(_) => RetryClass::Transport,
+        }
+    }
+}
+
+/// How often and how patiently a subsystem retries a failed request.
+///
+/// The `n`th retry waits `base_delay * 2^(n-1)`, at most `max_delay`, then
+/// lengthened or shortened at random by up to `jitter` of that delay. A
+/// delay the server asked for is used as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt. `None` leaves the limit to the
+    /// subsystem: model requests use the provider's `stream_max_retries`,
+    /// the others do not retry.
+    pub max_retries: Option<u64>,
+    pub base_delay: Duration,
+    /// Longest delay before the jitter is applied. `None` lets it grow.
+    pub max_delay: Option<Duration>,
+    /// Fraction of the delay added or removed at random, `0.1` for ±10%.
+    pub jitter: f64,
+    /// Retry `429 Too Many Requests`.
+    pub retry_429: bool,
+    /// Retry the other `4xx` statuses.
+    pub retry_4xx: bool,
+    /// Retry `5xx` statuses.
+    pub retry_5xx: bool,
+    /// Retry failed connections and broken response streams.
+    pub retry_transport: bool,
+}
+
+impl RetryPolicy {
+    /// Streaming a turn: every failure that is not permanent is retried as
+    /// often as the provider allows.
+    pub fn model_requests() -> Self {
+        Self {
+            max_retries: None,
+            base_delay: Duration::from_millis(200),
+            max_delay: None,
+            jitter: 0.1,
+            retry_429: true,
+            retry_4xx: true,
+            retry_5xx: true,
+            retry_transport: true,
+        }
+    }
+
+    /// Fetching the model list: one attempt, after which the cached or
+    /// built-in list is used.
+    pub fn preset_fetch() -> Self {
+        Self {
+            max_retries: Some(0),
+            base_delay: Duration::from_millis(200),
+            max_delay: None,
+            jitter: 0.1,
+            retry_429: false,
+            retry_4xx: false,
+            retry_5xx: true,
+            retry_transport: true,
+        }
+    }
+
+    /// Refreshing tokens after a `401`: one attempt. The delays are those of
+    /// the background refresh, see [`crate::auth::AutoRefreshPolicy`].
+    pub fn auth_refresh() -> Self {
+        Self {
+            max_retries: Some(0),
+            base_delay: Duration::from_secs(5),
+            max_delay: Some(Duration::from_secs(60)),
+            jitter: 0.0,
+            retry_429: false,
+            retry_4xx: false,
+            retry_5xx: true,
+            retry_transport: true,
+        }
+    }
+
+    pub fn retries(&self, class: RetryClass) -> bool {
+        match class {
+            RetryClass::RateLimited => self.retry_429,
+            RetryClass::ClientError => self.retry_4xx,
+            RetryClass::ServerError => self.retry_5xx,
+            RetryClass::Transport => self.retry_transport,
+            RetryClass::Permanent => false,
+        }
+    }
+
+    /// The delay before the `retry`th retry, from 1, with the jitter drawn
+    /// from `rng`.
+    pub fn delay(&self, retry: u64, rng: &mut impl Rng) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u64) as i32;
+        let mut delay = self.base_delay.as_secs_f64() * BACKOFF_FACTOR.powi(exponent);
+        if let Some(max_delay) = self.max_delay {
+            delay = delay.min(max_delay.as_secs_f64());
+        }
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            delay *= rng.random_range(1.0 - jitter..=1.0 + jitter);
+        }
+        Duration::try_from_secs_f64(delay).unwrap_or(Duration::MAX)
+    }
+}
+
+/// The retry profiles, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryProfiles {
+    pub model_requests: RetryPolicy,
+    pub preset_fetch: RetryPolicy,
+    pub auth_refresh: RetryPolicy,
+}
+
+impl Default for RetryProfiles {
+    fn default() -> Self {
+        Self {
+            model_requests: RetryPolicy::model_requests(),
+            preset_fetch: RetryPolicy::preset_fetch(),
+            auth_refresh: RetryPolicy::auth_refresh(),
+        }
+    }
+}
+
+/// A retry profile as written in `config.toml`. Unset fields keep the
+/// defaults of the profile.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RetryPolicyToml {
+    pub max_retries: Option<u64>,
+    pub base_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub jitter: Option<f64>,
+    pub retry_429: Option<bool>,
+    pub retry_4xx: Option<bool>,
+    pub retry_5xx: Option<bool>,
+    pub retry_transport: Option<bool>,
+}
+
+impl RetryPolicyToml {
+    fn apply_to(self, defaults: RetryPolicy) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.max_retries.or(defaults.max_retries),
+            base_delay: self
+                .base_delay_ms
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            max_delay: self
+                .max_delay_ms
+                .map(Duration::from_millis)
+                .or(defaults.max_delay),
+            jitter: self.jitter.unwrap_or(defaults.jitter),
+            retry_429: self.retry_429.unwrap_or(defaults.retry_429),
+            retry_4xx: self.retry_4xx.unwrap_or(defaults.retry_4xx),
+            retry_5xx: self.retry_5xx.unwrap_or(defaults.retry_5xx),
+            retry_transport: self.retry_transport.unwrap_or(defaults.retry_transport),
+        }
+    }
+}
+
+/// The `[retry]` table of `config.toml`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RetryProfilesToml {
+    pub model_requests: Option<RetryPolicyToml>,
+    pub preset_fetch: Option<RetryPolicyToml>,
+    pub auth_refresh: Option<RetryPolicyToml>,
+}
+
+impl From<RetryProfilesToml> for RetryProfiles {
+    fn from(toml: RetryProfilesToml) -> Self {
+        let resolve = |profile: Option<RetryPolicyToml>, defaults: RetryPolicy| match profile {
+            Some(profile) => profile.apply_to(defaults),
+            None => defaults,
+        };
+        Self {
+            model_requests: resolve(toml.model_requests, RetryPolicy::model_requests()),
+            preset_fetch: resolve(toml.preset_fetch, RetryPolicy::preset_fetch()),
+            auth_refresh: resolve(toml.auth_refresh, RetryPolicy::auth_refresh()),
+        }
+    }
+}
+
+/// The retries of one request under a [`RetryPolicy`].
+pub struct RetryBackoff {
+    policy: RetryPolicy,
+    subsystem: RetrySubsystem,
+    max_retries: u64,
+    retries: u64,
+    rng: StdRng,
+}
+
+impl RetryBackoff {
+    pub fn new(policy: RetryPolicy, subsystem: RetrySubsystem) -> Self {
+        Self::with_rng(policy, subsystem, StdRng::from_os_rng())
+    }
+
+    /// Like [`Self::new`], with the jitter drawn from a generator seeded
+    /// with `seed`.
+    pub fn seeded(policy: RetryPolicy, subsystem: RetrySubsystem, seed: u64) -> Self {
+        Self::with_rng(policy, subsystem, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(policy: RetryPolicy, subsystem: RetrySubsystem, rng: StdRng) -> Self {
+        Self {
+            max_retries: policy.max_retries.unwrap_or(0),
+            policy,
+            subsystem,
+            retries: 0,
+            rng,
+        }
+    }
+
+    /// Use `max_retries` when the policy leaves the limit to the subsystem.
+    pub fn with_default_max_retries(mut self, max_retries: u64) -> Self {
+        self.max_retries = self.policy.max_retries.unwrap_or(max_retries);
+        self
+    }
+
+    pub fn max_retries(&self) -> u64 {
+        self.max_retries
+    }
+
+    /// The retry to make after `err`, or `None` when the policy does not
+    /// retry it or the retries are used up.
+    pub fn next_retry(&mut self, err: &impl Retryable) -> Option<RetryingEvent> {
+        if self.retries >= self.max_retries || !self.policy.retries(err.retry_class()) {
+            return None;
+        }
+        self.retries += 1;
+        let delay = err
+            .retry_after()
+            .unwrap_or_else(|| self.policy.delay(self.retries, &mut self.rng));
+        Some(RetryingEvent {
+            subsystem: self.subsystem,
+            attempt: self.retries,
+            delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+        })
+    }
+}
+
+/// Run `op` until it succeeds or `policy` gives up, waiting between
+/// attempts. `on_retry` is told about each retry before its delay.
+pub(crate) async fn retry<T, E, Op, Fut>(
+    policy: &RetryPolicy,
+    subsystem: RetrySubsystem,
+    mut on_retry: impl FnMut(&RetryingEvent),
+    mut op: Op,
+) -> Result<T, E>
+where
+    E: Retryable + fmt::Display,
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut backoff = RetryBackoff::new(policy.clone(), subsystem);
+    loop {
+        let err = match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let Some(event) = backoff.next_retry(&err) else {
+            return Err(err);
+        };
+        warn!(
+            "{subsystem} failed, retrying ({}/{} in {}ms): {err}",
+            event.attempt,
+            backoff.max_retries(),
+            event.delay_ms
+        );
+        on_retry(&event);
+        tokio::time::sleep(Duration::from_millis(event.delay_ms)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::UnexpectedResponseError;
+    use pretty_assertions::assert_eq;
+
+    fn status(code: u16) -> CodexErr {
+        CodexErr::UnexpectedStatus(UnexpectedResponseError {
+            status: StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            body: String::new(),
+            request_id: None,
+        })
+    }
+
+    #[test]
+    fn seeded_jitter_stays_within_bounds() {
+        let policy = RetryPolicy {
+            jitter: 0.25,
+            ..RetryPolicy::model_requests()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        for retry in 1..=6 {
+            let nominal = 200.0 * BACKOFF_FACTOR.powi(retry as i32 - 1);
+            for _ in 0..200 {
+                let delay = policy.delay(retry, &mut rng).as_secs_f64() * 1000.0;
+                assert!(
+                    (nominal * 0.75..=nominal * 1.25).contains(&delay),
+                    "retry {retry}: {delay}ms outside ±25% of {nominal}ms"
+                );
+            }
+        }
+
+        // The same seed draws the same delays.
+        let delays = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (1..=4)
+                .map(|retry| policy.delay(retry, &mut rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(delays(42), delays(42));
+    }
+
+    #[test]
+    fn delays_double_up_to_the_cap() {
+        let policy = RetryPolicy::auth_refresh();
+        let mut rng = StdRng::seed_from_u64(0);
+        let delays = (1..=6)
+            .map(|retry| policy.delay(retry, &mut rng).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![5, 10, 20, 40, 60, 60]);
+    }
+
+    #[test]
+    fn backoff_honors_the_limit_and_the_classification() {
+        let policy = RetryPolicy {
+            max_retries: Some(2),
+            jitter: 0.0,
+            ..RetryPolicy::preset_fetch()
+        };
+        let mut backoff = RetryBackoff::seeded(policy.clone(), RetrySubsystem::PresetFetch, 1);
+        assert_eq!(backoff.next_retry(&status(429)), None);
+        assert_eq!(backoff.next_retry(&CodexErr::QuotaExceeded), None);
+        assert_eq!(
+            backoff.next_retry(&status(503)),
+            Some(RetryingEvent {
+                subsystem: RetrySubsystem::PresetFetch,
+                attempt: 1,
+                delay_ms: 200,
+            })
+        );
+        // A delay the server asked for wins.
+        assert_eq!(
+            backoff.next_retry(&CodexErr::Stream(
+                "closed".to_string(),
+                Some(Duration::from_millis(1_500))
+            )),
+            Some(RetryingEvent {
+                subsystem: RetrySubsystem::PresetFetch,
+                attempt: 2,
+                delay_ms: 1_500,
+            })
+        );
+        assert_eq!(backoff.next_retry(&status(503)), None);
+
+        let backoff =
+            RetryBackoff::new(RetryPolicy::model_requests(), RetrySubsystem::ModelRequests)
+                .with_default_max_retries(5);
+        assert_eq!(backoff.max_retries(), 5);
+        let backoff =
+            RetryBackoff::new(policy, RetrySubsystem::PresetFetch).with_default_max_retries(5);
+        assert_eq!(backoff.max_retries(), 2);
+    }
+
+    #[test]
+    fn profiles_keep_the_defaults_of_unset_fields() -> anyhow::Result<()> {
+        let toml: RetryProfilesToml = toml::from_str(
+            r#"
+            [preset_fetch]
+            max_retries = 3
+            max_delay_ms = 1000
+
+            [auth_refresh]
+            jitter = 0.5
+            "#,
+        )?;
+        let profiles = RetryProfiles::from(toml);
+        assert_eq!(
+            profiles,
+            RetryProfiles {
+                model_requests: RetryPolicy::model_requests(),
+                preset_fetch: RetryPolicy {
+                    max_retries: Some(3),
+                    max_delay: Some(Duration::from_secs(1)),
+                    ..RetryPolicy::preset_fetch()
+                },
+                auth_refresh: RetryPolicy {
+                    jitter: 0.5,
+                    ..RetryPolicy::auth_refresh()
+                },
+            }
+        );
+        assert_eq!(
+            RetryProfiles::from(RetryProfilesToml::default()),
+            RetryProfiles::default()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_reports_each_retry_and_returns_the_last_error() {
+        let policy = RetryPolicy {
+            max_retries: Some(2),
+            jitter: 0.0,
+            ..RetryPolicy::preset_fetch()
+        };
+        let mut attempts = 0;
+        let mut events = Vec::new();
+        let result: Result<(), CodexErr> = retry(
+            &policy,
+            RetrySubsystem::PresetFetch,
+            |event| events.push(event.clone()),
+            || {
+                attempts += 1;
+                async { Err(status(500)) }
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(CodexErr::UnexpectedStatus(_))));
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.attempt, event.delay_ms))
+                .collect::<Vec<_>>(),
+            vec![(1, 200), (2, 400)]
+        );
+    }
+}
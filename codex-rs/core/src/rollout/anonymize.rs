@@ -0,0 +1,436 @@
+//! Anonymized copies of rollouts, for attaching a session to a bug report.
+//!
+//! [`anonymize`] rewrites every string of a rollout, object keys included:
+//! the home directory and repository root become `<HOME>` and `<REPO>`, the
+//! user and host names of the environment become `<USER>` and `<HOST>`, and
+//! email addresses become `<EMAIL-n>`. Timestamps are rounded down to the
+//! minute. The lines, their item kinds and every id are kept, so the copy
+//! still resumes and preserves the sequencing of the original.
+//!
+//! The placeholders are mapped back to what they replaced in a manifest
+//! written next to the copy, for the reporter's own reference; it is not
+//! meant to be shared.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use chrono::DateTime;
+use chrono::Utc;
+use regex::Regex;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+
+use super::chain::is_link;
+use super::chain::read_chain_to_string;
+use crate::git_info::get_git_repo_root;
+
+pub const HOME_PLACEHOLDER: &str = "<HOME>";
+pub const REPO_PLACEHOLDER: &str = "<REPO>";
+const USER_PLACEHOLDER: &str = "<USER>";
+const HOST_PLACEHOLDER: &str = "<HOST>";
+const EMAIL_PLACEHOLDER: &str = "EMAIL";
+
+/// Fields holding tool output, by the `type` of the item or event they
+/// belong to, replaced when [`AnonymizeOptions::hash_tool_outputs`] is set.
+const TOOL_OUTPUT_FIELDS: &[(&str, &[&str])] = &[
+    ("function_call_output", &["output"]),
+    ("custom_tool_call_output", &["output"]),
+    (
+        "exec_command_end",
+        &["stdout", "stderr", "aggregated_output", "formatted_output"],
+    ),
+    ("mcp_tool_call_end", &["result"]),
+];
+
+#[expect(clippy::expect_used)]
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").expect("valid email pattern")
+});
+
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizeOptions {
+    /// Directory replaced with `<HOME>`; defaults to the current user's home.
+    pub home: Option<PathBuf>,
+    /// Directory replaced with `<REPO>`; defaults to the git root of the
+    /// session's working directory, or the working directory itself.
+    pub repo_root: Option<PathBuf>,
+    /// Names replaced like the user names read from the environment.
+    pub names: Vec<String>,
+    /// Replace tool outputs, such as command output and the contents of files
+    /// read, with hashes of the same length, line by line.
+    pub hash_tool_outputs: bool,
+}
+
+/// Outcome of [`anonymize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnonymizeReport {
+    /// Where the manifest was written.
+    pub manifest_path: PathBuf,
+    /// What each placeholder used in the copy replaced.
+    pub placeholders: BTreeMap<String, String>,
+}
+
+/// Where [`anonymize`] writes the manifest for the copy at `dest`.
+pub fn manifest_path(dest: &Path) -> PathBuf {
+    dest.with_extension("manifest.json")
+}
+
+/// Whether a recorded working directory was rewritten by [`anonymize`].
+pub fn is_anonymized_cwd(cwd: &Path) -> bool {
+    cwd.starts_with(REPO_PLACEHOLDER) || cwd.starts_with(HOME_PLACEHOLDER)
+}
+
+/// Write an anonymized copy of the rollout at `src` to `dest`, and its
+/// manifest to [`manifest_path`]. Lines that are not JSON are anonymized as
+/// plain text.
+pub async fn anonymize(
+    src: &Path,
+    dest: &Path,
+    options: AnonymizeOptions,
+) -> io::Result<AnonymizeReport> {
+    // The copy is a single file, without the lines linking the parts.
+    let text = read_chain_to_string(src).await?;
+    let mut lines: Vec<Result<Value, &str>> = text
+        .lines()
+        .filter(|line| !is_link(line))
+        .map(|line| serde_json::from_str::<Value>(line).map_err(|_| line))
+        .collect();
+
+    let cwd = lines.iter().find_map(|line| match line {
+        Ok(value) if value["type"] == "session_meta" => {
+            value["payload"]["cwd"].as_str().map(PathBuf::from)
+        }
+        _ => None,
+    });
+    let mut anonymizer = Anonymizer::new(options, cwd.as_deref());
+
+    let mut out = String::with_capacity(text.len());
+    for line in &mut lines {
+        match line {
+            Ok(value) => {
+                anonymizer.value(value);
+                out.push_str(&serde_json::to_string(value)?);
+            }
+            Err(line) => out.push_str(&anonymizer.text(line)),
+        }
+        out.push('\n');
+    }
+    tokio::fs::write(dest, out).await?;
+
+    let report = AnonymizeReport {
+        manifest_path: manifest_path(dest),
+        placeholders: anonymizer.used,
+    };
+    tokio::fs::write(
+        &report.manifest_path,
+        serde_json::to_string_pretty(&report.placeholders)?,
+    )
+    .await?;
+    Ok(report)
+}
+
+struct Anonymizer {
+    /// Paths, longest first so a repository inside the home directory
+    /// becomes `<REPO>`.
+    paths: Vec<Replacement>,
+    emails: BTreeMap<String, String>,
+    /// User and host names, replaced after email addresses so those are
+    /// replaced whole.
+    names: Vec<Replacement>,
+    hash_tool_outputs: bool,
+    used: BTreeMap<String, String>,
+}
+
+struct Replacement {
+    regex: Regex,
+    template: String,
+    placeholder: String,
+    original: String,
+}
+
+impl Anonymizer {
+    fn new(options: AnonymizeOptions, cwd: Option<&Path>) -> Self {
+        let home = options.home.or_else(dirs::home_dir);
+        let repo_root = options
+            .repo_root
+            .or_else(|| cwd.map(|cwd| get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf())));
+
+        let mut paths: Vec<(String, &str)> =
+            [(repo_root, REPO_PLACEHOLDER), (home, HOME_PLACEHOLDER)]
+                .into_iter()
+                .filter_map(|(path, placeholder)| {
+                    let path = path?.to_str()?.trim_end_matches('/').to_string();
+                    (!path.is_empty()).then_some((path, placeholder))
+                })
+                .collect();
+        paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+        let paths = paths
+            .into_iter()
+            .filter_map(|(path, placeholder)| {
+                // Only whole path components: `/home/al` must not match
+                // `/home/alice`.
+                let pattern = format!(r"{}(?P<end>$|[^\w.-])", regex::escape(&path));
+                Some(Replacement {
+                    regex: Regex::new(&pattern).ok()?,
+                    template: format!("{placeholder}${{end}}"),
+                    placeholder: placeholder.to_string(),
+                    original: path,
+                })
+            })
+            .collect();
+
+        let names = numbered(USER_PLACEHOLDER, user_names(options.names))
+            .chain(numbered(HOST_PLACEHOLDER, host_names()))
+            .filter_map(|(placeholder, name)| {
+                let pattern = format!(r"\b{}\b", regex::escape(&name));
+                Some(Replacement {
+                    regex: Regex::new(&pattern).ok()?,
+                    template: placeholder.clone(),
+                    placeholder,
+                    original: name,
+                })
+            })
+            .collect();
+
+        Self {
+            paths,
+            emails: BTreeMap::new(),
+            names,
+            hash_tool_outputs: options.hash_tool_outputs,
+            used: BTreeMap::new(),
+        }
+    }
+
+    fn value(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                let hashed: &[&str] = match object.get("type").and_then(Value::as_str) {
+                    Some(kind) if self.hash_tool_outputs => TOOL_OUTPUT_FIELDS
+                        .iter()
+                        .find(|(item, _)| *item == kind)
+                        .map(|(_, fields)| *fields)
+                        .unwrap_or_default(),
+                    _ => &[],
+                };
+                let entries = std::mem::take(object);
+                for (key, mut value) in entries {
+                    if hashed.contains(&key.as_str()) {
+                        hash_value(&mut value);
+                    } else if key == "timestamp"
+                        && let Value::String(timestamp) = &value
+                        && let Some(rounded) = round_to_minute(timestamp)
+                    {
+                        value = Value::String(rounded);
+                    } else {
+                        self.value(&mut value);
+                    }
+                    object.insert(self.text(&key), value);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.value(item);
+                }
+            }
+            Value::String(text) => *text = self.text(text),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) -> String {
+        let text = replace(&self.paths, &mut self.used, text.to_string());
+        let emails = &mut self.emails;
+        let used = &mut self.used;
+        let text = EMAIL
+            .replace_all(&text, |captures: &regex::Captures| {
+                let address = captures[0].to_string();
+                let next = emails.len() + 1;
+                let placeholder = emails
+                    .entry(address.clone())
+                    .or_insert_with(|| format!("<{EMAIL_PLACEHOLDER}-{next}>"))
+                    .clone();
+                used.insert(placeholder.clone(), address);
+                placeholder
+            })
+            .into_owned();
+        replace(&self.names, &mut self.used, text)
+    }
+}
+
+fn replace(
+    replacements: &[Replacement],
+    used: &mut BTreeMap<String, String>,
+    mut text: String,
+) -> String {
+    for replacement in replacements {
+        if let Cow::Owned(replaced) = replacement
+            .regex
+            .replace_all(&text, replacement.template.as_str())
+        {
+            used.insert(
+                replacement.placeholder.clone(),
+                replacement.original.clone(),
+            );
+            text = replaced;
+        }
+    }
+    text
+}
+
+/// Placeholders for `names`: `<USER>`, then `<USER-2>` and so on.
+fn numbered(kind: &'static str, names: Vec<String>) -> impl Iterator<Item = (String, String)> {
+    names.into_iter().enumerate().map(move |(index, name)| {
+        let placeholder = match index {
+            0 => kind.to_string(),
+            n => format!("{}-{}>", kind.trim_end_matches('>'), n + 1),
+        };
+        (placeholder, name)
+    })
+}
+
+fn user_names(extra: Vec<String>) -> Vec<String> {
+    let from_env = ["USER", "USERNAME", "LOGNAME"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok());
+    dedup(extra.into_iter().chain(from_env))
+}
+
+fn host_names() -> Vec<String> {
+    let from_env = ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok());
+    let from_file = std::fs::read_to_string("/etc/hostname").ok();
+    dedup(from_env.chain(from_file))
+        .into_iter()
+        .filter(|name| name != "localhost")
+        .collect()
+}
+
+fn dedup(names: impl Iterator<Item = String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim().to_string();
+        if !name.is_empty() && !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+    unique
+}
+
+/// Replace every string under `value` with a hash of the same length, line
+/// by line, so identical outputs still look identical.
+fn hash_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = hash_text(text),
+        Value::Array(items) => items.iter_mut().for_each(hash_value),
+        Value::Object(object) => object.values_mut().for_each(hash_value),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+fn hash_text(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let digest = format!("{:x}", Sha256::digest(content.as_bytes()));
+            let hashed: String = digest
+                .chars()
+                .cycle()
+                .take(content.chars().count())
+                .collect();
+            if line.ends_with('\n') {
+                format!("{hashed}\n")
+            } else {
+                hashed
+            }
+        })
+        .collect()
+}
+
+fn round_to_minute(timestamp: &str) -> Option<String> {
+    let parsed = DateTime::parse_from_rfc3339(timestamp).ok()?;
+    Some(
+        parsed
+            .with_timezone(&Utc)
+            .format("%Y-%m-%dT%H:%M:00.000Z")
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn anonymizer(hash_tool_outputs: bool) -> Anonymizer {
+        Anonymizer::new(
+            AnonymizeOptions {
+                home: Some(PathBuf::from("/home/alice")),
+                repo_root: Some(PathBuf::from("/home/alice/src/app")),
+                names: vec!["alice".to_string()],
+                hash_tool_outputs,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn rewrites_paths_names_emails_and_timestamps() {
+        let mut value = json!({
+            "timestamp": "2025-01-02T03:04:05.678Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "patch_apply_begin",
+                "changes": { "/home/alice/src/app/main.rs": "edit" },
+                "note": "alice <alice@example.com> edited /home/alice/notes and /home/alicex",
+            },
+        });
+
+        let mut anonymizer = anonymizer(false);
+        anonymizer.value(&mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "timestamp": "2025-01-02T03:04:00.000Z",
+                "type": "event_msg",
+                "payload": {
+                    "type": "patch_apply_begin",
+                    "changes": { "<REPO>/main.rs": "edit" },
+                    "note": "<USER> <<EMAIL-1>> edited <HOME>/notes and /home/alicex",
+                },
+            })
+        );
+        assert_eq!(
+            anonymizer.used.get("<EMAIL-1>").map(String::as_str),
+            Some("alice@example.com")
+        );
+        assert_eq!(
+            anonymizer.used.get("<REPO>").map(String::as_str),
+            Some("/home/alice/src/app")
+        );
+    }
+
+    #[test]
+    fn tool_outputs_are_hashed_to_the_same_shape() {
+        let mut value = json!({
+            "type": "function_call_output",
+            "call_id": "call-1",
+            "output": "fn main() {}\n\nsecret\n",
+        });
+
+        anonymizer(true).value(&mut value);
+
+        let output = value["output"].as_str().expect("output is a string");
+        assert_eq!(output.len(), "fn main() {}\n\nsecret\n".len());
+        assert_eq!(output.lines().map(str::len).collect::<Vec<_>>(), [12, 0, 6]);
+        assert!(!output.contains("secret"));
+        assert_eq!(value["call_id"], "call-1");
+    }
+}
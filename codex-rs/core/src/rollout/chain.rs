@@ -0,0 +1,439 @@
+//! Rollouts split across several files.
+//!
+//! With [`Config::max_rollout_bytes`] set, the writer starts a new part once
+//! the current one would grow past the limit: it ends the part with a
+//! [`RolloutItem::ContinuedIn`] line naming the next part, and starts that
+//! part with a [`RolloutItem::ContinuedFrom`] line naming the previous one.
+//! The first part keeps the rollout's name, so listing and resuming find the
+//! conversation by it as before; later parts are named
+//! `<stem>.part<n>.jsonl` next to it. Readers follow the chain from the first
+//! part and see a single rollout.
+//!
+//! [`Config::max_rollout_bytes`]: crate::config::Config::max_rollout_bytes
+
+use std::ffi::OsStr;
+use std::io;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::BufReader;
+use tokio::io::Lines;
+
+/// Marks the file names of the parts after the first.
+pub(crate) const PART_INFIX: &str = ".part";
+
+/// Footer lines are short; a longer last line is not one.
+const MAX_FOOTER_BYTES: u64 = 4096;
+
+/// Path of part `part`, counted from 1, of the rollout whose first part is
+/// `head`.
+pub(crate) fn part_path(head: &Path, part: u32) -> PathBuf {
+    if part <= 1 {
+        return head.to_path_buf();
+    }
+    let stem = head
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    head.with_file_name(format!("{stem}{PART_INFIX}{part}.jsonl"))
+}
+
+/// The files of the rollout whose first part is `head`, in order.
+pub async fn rollout_parts(head: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![head.to_path_buf()];
+    let mut current = head.to_path_buf();
+    while let Some(next) = next_part(&current).await? {
+        // A writer stopped between the footer and the next part leaves a
+        // footer naming a part that does not exist.
+        if paths.contains(&next) || !tokio::fs::try_exists(&next).await? {
+            break;
+        }
+        paths.push(next.clone());
+        current = next;
+    }
+    Ok(paths)
+}
+
+/// The bytes of every part of the rollout at `head`, one after the other.
+pub(crate) async fn read_chain(head: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for path in rollout_parts(head).await? {
+        bytes.extend(tokio::fs::read(&path).await?);
+    }
+    Ok(bytes)
+}
+
+/// Like [`read_chain`], for rollouts read as text.
+pub(crate) async fn read_chain_to_string(head: &Path) -> io::Result<String> {
+    String::from_utf8(read_chain(head).await?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Length of [`read_chain`] without reading it.
+pub(crate) async fn chain_len(head: &Path) -> io::Result<u64> {
+    let mut len = 0;
+    for path in rollout_parts(head).await? {
+        len += tokio::fs::metadata(&path).await?.len();
+    }
+    Ok(len)
+}
+
+/// Whether `line` links two parts rather than recording anything.
+pub(crate) fn is_link(line: &str) -> bool {
+    link(line).is_some()
+}
+
+/// The lines of a rollout, read part by part, without the lines that link
+/// the parts.
+pub(crate) struct ChainLines {
+    path: PathBuf,
+    lines: Lines<BufReader<tokio::fs::File>>,
+    visited: Vec<PathBuf>,
+    /// The part named by the last non-empty line, when it was a footer.
+    next: Option<PathBuf>,
+}
+
+impl ChainLines {
+    pub(crate) async fn open(head: &Path) -> io::Result<Self> {
+        let file = tokio::fs::File::open(head).await?;
+        Ok(Self {
+            path: head.to_path_buf(),
+            lines: BufReader::new(file).lines(),
+            visited: vec![head.to_path_buf()],
+            next: None,
+        })
+    }
+
+    pub(crate) async fn next_line(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let Some(line) = self.lines.next_line().await? else {
+                let Some(next) = self.next.take() else {
+                    return Ok(None);
+                };
+                if self.visited.contains(&next) || !tokio::fs::try_exists(&next).await? {
+                    return Ok(None);
+                }
+                let file = tokio::fs::File::open(&next).await?;
+                self.lines = BufReader::new(file).lines();
+                self.visited.push(next.clone());
+                self.path = next;
+                continue;
+            };
+            if line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+            match link(&line) {
+                Some(Link::Footer(file)) => {
+                    self.next = file.map(|file| self.path.with_file_name(file));
+                }
+                Some(Link::Header) => self.next = None,
+                None => {
+                    self.next = None;
+                    return Ok(Some(line));
+                }
+            }
+        }
+    }
+}
+
+/// Where a line of a rollout starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct LineOffset {
+    /// The part holding the line, counted from 1.
+    pub(crate) part: u32,
+    /// Bytes before the line in that part.
+    pub(crate) byte: u64,
+}
+
+impl LineOffset {
+    /// The first line of a rollout.
+    pub(crate) const START: Self = Self { part: 1, byte: 0 };
+}
+
+impl Default for LineOffset {
+    fn default() -> Self {
+        Self::START
+    }
+}
+
+/// Like [`ChainLines`], from any line on and with where each line starts, so
+/// a later read can seek straight back to it. Parts are followed by number,
+/// as the writer names them.
+pub(crate) struct OffsetLines {
+    head: PathBuf,
+    reader: BufReader<tokio::fs::File>,
+    /// Where the next line starts.
+    position: LineOffset,
+    /// Whether the last non-empty line was a footer naming the next part.
+    footer: bool,
+}
+
+impl OffsetLines {
+    pub(crate) async fn open_at(head: &Path, offset: LineOffset) -> io::Result<Self> {
+        let mut file = tokio::fs::File::open(part_path(head, offset.part)).await?;
+        file.seek(SeekFrom::Start(offset.byte)).await?;
+        Ok(Self {
+            head: head.to_path_buf(),
+            reader: BufReader::new(file),
+            position: offset,
+            footer: false,
+        })
+    }
+
+    /// Where the next line starts.
+    pub(crate) fn position(&self) -> LineOffset {
+        self.position
+    }
+
+    /// Read on from `offset`, which must start a line.
+    pub(crate) async fn seek(&mut self, offset: LineOffset) -> io::Result<()> {
+        if offset == self.position {
+            return Ok(());
+        }
+        if offset.part == self.position.part {
+            self.reader.seek(SeekFrom::Start(offset.byte)).await?;
+            self.position = offset;
+            self.footer = false;
+        } else {
+            *self = Self::open_at(&self.head, offset).await?;
+        }
+        Ok(())
+    }
+
+    /// The next line that records something, with where it starts. A last
+    /// line without its newline is not returned, since the writer may still
+    /// be appending to it.
+    pub(crate) async fn next_line(&mut self) -> io::Result<Option<(LineOffset, String)>> {
+        let mut bytes = Vec::new();
+        loop {
+            bytes.clear();
+            let read = self.reader.read_until(b'\n', &mut bytes).await?;
+            if read == 0 {
+                let next = part_path(&self.head, self.position.part + 1);
+                if !self.footer || !tokio::fs::try_exists(&next).await? {
+                    return Ok(None);
+                }
+                *self = Self::open_at(
+                    &self.head,
+                    LineOffset {
+                        part: self.position.part + 1,
+                        byte: 0,
+                    },
+                )
+                .await?;
+                continue;
+            }
+            if bytes.last() != Some(&b'\n') {
+                // Read the line again once it is complete.
+                self.reader
+                    .seek(SeekFrom::Start(self.position.byte))
+                    .await?;
+                return Ok(None);
+            }
+            let start = self.position;
+            self.position.byte += read as u64;
+            let line = String::from_utf8_lossy(&bytes).trim_end().to_string();
+            if line.is_empty() {
+                continue;
+            }
+            match link(&line) {
+                Some(Link::Footer(file)) => {
+                    let next = part_path(&self.head, self.position.part + 1);
+                    self.footer =
+                        file.is_some_and(|file| next.file_name() == Some(OsStr::new(&file)));
+                }
+                Some(Link::Header) => self.footer = false,
+                None => {
+                    self.footer = false;
+                    return Ok(Some((start, line)));
+                }
+            }
+        }
+    }
+}
+
+enum Link {
+    /// Ends a part, naming the file of the next one.
+    Footer(Option<String>),
+    /// Starts a part.
+    Header,
+}
+
+fn link(line: &str) -> Option<Link> {
+    // Only parse the lines that can be links.
+    if !line.contains("\"continued_in\"") && !line.contains("\"continued_from\"") {
+        return None;
+    }
+    match serde_json::from_str::<RolloutLine>(line.trim()).ok()?.item {
+        // Parts live next to each other; a footer naming anything but a file
+        // name is not followed.
+        RolloutItem::ContinuedIn(continuation) => Some(Link::Footer(
+            (Path::new(&continuation.file).file_name() == Some(OsStr::new(&continuation.file)))
+                .then_some(continuation.file),
+        )),
+        RolloutItem::ContinuedFrom(_) => Some(Link::Header),
+        _ => None,
+    }
+}
+
+/// The part that the one at `path` continues in, read from its last line.
+async fn next_part(path: &Path) -> io::Result<Option<PathBuf>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_FOOTER_BYTES)))
+        .await?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).await?;
+    let tail = String::from_utf8_lossy(&tail);
+    let last = tail.trim_end().rsplit('\n').next().unwrap_or_default();
+    Ok(match link(last) {
+        Some(Link::Footer(file)) => file.map(|file| path.with_file_name(file)),
+        Some(Link::Header) | None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::RolloutContinuation;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn line(item: RolloutItem) -> String {
+        let line = RolloutLine {
+            timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+            correlation_id: None,
+            item,
+        };
+        format!("{}\n", serde_json::to_string(&line).unwrap())
+    }
+
+    fn continued_in(head: &Path, part: u32) -> String {
+        let file = part_path(head, part);
+        line(RolloutItem::ContinuedIn(RolloutContinuation {
+            file: file.file_name().unwrap().to_string_lossy().into_owned(),
+        }))
+    }
+
+    fn continued_from(head: &Path, part: u32) -> String {
+        let file = part_path(head, part);
+        line(RolloutItem::ContinuedFrom(RolloutContinuation {
+            file: file.file_name().unwrap().to_string_lossy().into_owned(),
+        }))
+    }
+
+    async fn lines(head: &Path) -> Vec<String> {
+        let mut lines = ChainLines::open(head).await.unwrap();
+        let mut out = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            out.push(line);
+        }
+        out
+    }
+
+    #[test]
+    fn later_parts_are_named_after_the_head() {
+        let head = Path::new("/sessions/rollout-2025-01-01T00-00-00-abc.jsonl");
+        assert_eq!(part_path(head, 1), head);
+        assert_eq!(
+            part_path(head, 3),
+            Path::new("/sessions/rollout-2025-01-01T00-00-00-abc.part3.jsonl")
+        );
+    }
+
+    #[tokio::test]
+    async fn readers_follow_the_chain_and_skip_its_links() {
+        let dir = TempDir::new().unwrap();
+        let head = dir.path().join("rollout.jsonl");
+        std::fs::write(&head, format!("one\ntwo\n{}", continued_in(&head, 2))).unwrap();
+        std::fs::write(
+            part_path(&head, 2),
+            format!(
+                "{}three\n{}",
+                continued_from(&head, 1),
+                continued_in(&head, 3)
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            part_path(&head, 3),
+            format!("{}four\n", continued_from(&head, 2)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            rollout_parts(&head).await.unwrap(),
+            vec![head.clone(), part_path(&head, 2), part_path(&head, 3)]
+        );
+        assert_eq!(lines(&head).await, vec!["one", "two", "three", "four"]);
+        let text = read_chain_to_string(&head).await.unwrap();
+        assert_eq!(chain_len(&head).await.unwrap(), text.len() as u64);
+        let recorded: Vec<&str> = text.lines().filter(|line| !is_link(line)).collect();
+        assert_eq!(recorded, vec!["one", "two", "three", "four"]);
+    }
+
+    #[tokio::test]
+    async fn offset_lines_seek_back_to_lines_of_any_part() {
+        let dir = TempDir::new().unwrap();
+        let head = dir.path().join("rollout.jsonl");
+        let footer = continued_in(&head, 2);
+        std::fs::write(&head, format!("one\ntwo\n{footer}")).unwrap();
+        let header = continued_from(&head, 1);
+        std::fs::write(part_path(&head, 2), format!("{header}three\nfour")).unwrap();
+
+        let mut lines = OffsetLines::open_at(&head, LineOffset::START)
+            .await
+            .unwrap();
+        let mut read = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            read.push(line);
+        }
+        let three = LineOffset {
+            part: 2,
+            byte: header.len() as u64,
+        };
+        // The last line has no newline yet, so it is not read.
+        assert_eq!(
+            read,
+            vec![
+                (LineOffset::START, "one".to_string()),
+                (LineOffset { part: 1, byte: 4 }, "two".to_string()),
+                (three, "three".to_string()),
+            ]
+        );
+        assert_eq!(
+            lines.position(),
+            LineOffset {
+                part: 2,
+                byte: three.byte + 6,
+            }
+        );
+
+        lines.seek(LineOffset { part: 1, byte: 4 }).await.unwrap();
+        assert_eq!(
+            lines.next_line().await.unwrap(),
+            Some((LineOffset { part: 1, byte: 4 }, "two".to_string()))
+        );
+        lines.seek(three).await.unwrap();
+        assert_eq!(
+            lines.next_line().await.unwrap(),
+            Some((three, "three".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn a_footer_naming_a_missing_part_ends_the_chain() {
+        let dir = TempDir::new().unwrap();
+        let head = dir.path().join("rollout.jsonl");
+        std::fs::write(&head, format!("one\n{}", continued_in(&head, 2))).unwrap();
+
+        assert_eq!(rollout_parts(&head).await.unwrap(), vec![head.clone()]);
+        assert_eq!(lines(&head).await, vec!["one"]);
+    }
+}
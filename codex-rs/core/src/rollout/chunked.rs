@@ -0,0 +1,258 @@
+//! Rollout items too large for a single line.
+//!
+//! When a serialized rollout line is longer than the recorder's limit it is
+//! written as a run of [`RolloutItem::Chunked`] lines that share an `id`, each
+//! carrying the next slice of the original line. [`ChunkAssembler`] joins the
+//! parts back into the original [`RolloutLine`], so consumers of a rollout
+//! never see the parts. A run that is cut short, for example because Codex
+//! exited while writing it, is dropped as a whole.
+
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use tracing::warn;
+
+use crate::runtime_env::RuntimeEnv;
+
+/// Smallest slice of the original line carried by one part, so that a tiny
+/// limit does not produce one line per character.
+const MIN_PART_BYTES: usize = 1024;
+
+/// Split the serialized rollout `line` into parts whose payloads take at most
+/// `max_line_bytes` once escaped, or `None` when the line already fits. The
+/// parts share an id from `env`.
+pub(crate) fn split_line(
+    line: &str,
+    max_line_bytes: usize,
+    env: &RuntimeEnv,
+) -> Option<Vec<RolloutItem>> {
+    if line.len() <= max_line_bytes {
+        return None;
+    }
+    let budget = max_line_bytes.max(MIN_PART_BYTES);
+    let mut slices = Vec::new();
+    let mut start = 0;
+    let mut escaped = 0;
+    for (index, ch) in line.char_indices() {
+        let cost = escaped_len(ch);
+        if escaped + cost > budget && index > start {
+            slices.push(&line[start..index]);
+            start = index;
+            escaped = 0;
+        }
+        escaped += cost;
+    }
+    slices.push(&line[start..]);
+
+    let of = u32::try_from(slices.len()).ok()?;
+    let id = env.next_id();
+    Some(
+        (0..of)
+            .zip(slices)
+            .map(|(part, payload)| RolloutItem::Chunked {
+                id: id.clone(),
+                part,
+                of,
+                payload: payload.to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// Length of `ch` inside a JSON string.
+fn escaped_len(ch: char) -> usize {
+    match ch {
+        '"' | '\\' => 2,
+        ch if ch < ' ' => 6,
+        ch => ch.len_utf8(),
+    }
+}
+
+/// Joins runs of [`RolloutItem::Chunked`] lines back into the lines they were
+/// split from. Feed it every line of a rollout in order.
+#[derive(Default)]
+pub(crate) struct ChunkAssembler {
+    pending: Option<PendingRun>,
+}
+
+struct PendingRun {
+    id: String,
+    of: u32,
+    next: u32,
+    line: String,
+}
+
+impl ChunkAssembler {
+    /// Returns the line to hand to consumers: `line` itself, the original line
+    /// once the last part of a run arrives, or `None` while a run is still
+    /// incomplete.
+    pub(crate) fn push(&mut self, line: RolloutLine) -> Option<RolloutLine> {
+        self.try_push(line).unwrap_or_else(|err| {
+            warn!("failed to parse chunked rollout item: {err}");
+            None
+        })
+    }
+
+    /// Like [`Self::push`], but fails when the line a run reassembles into
+    /// does not parse. The run is dropped either way.
+    pub(crate) fn try_push(
+        &mut self,
+        line: RolloutLine,
+    ) -> Result<Option<RolloutLine>, serde_json::Error> {
+        match line.item {
+            RolloutItem::Chunked {
+                id,
+                part,
+                of,
+                payload,
+            } => self.push_part(id, part, of, &payload),
+            item => {
+                self.drop_pending();
+                Ok(Some(RolloutLine {
+                    timestamp: line.timestamp,
+                    correlation_id: line.correlation_id,
+                    item,
+                }))
+            }
+        }
+    }
+
+    /// Drop a run left incomplete at the end of the rollout.
+    pub(crate) fn finish(mut self) {
+        self.drop_pending();
+    }
+
+    fn push_part(
+        &mut self,
+        id: String,
+        part: u32,
+        of: u32,
+        payload: &str,
+    ) -> Result<Option<RolloutLine>, serde_json::Error> {
+        if part == 0 {
+            self.drop_pending();
+            self.pending = Some(PendingRun {
+                id: id.clone(),
+                of,
+                next: 0,
+                line: String::new(),
+            });
+        }
+        let Some(run) = self
+            .pending
+            .as_mut()
+            .filter(|run| run.id == id && run.of == of && run.next == part && part < of)
+        else {
+            warn!("dropping unexpected part {part} of chunked rollout item {id}");
+            self.drop_pending();
+            return Ok(None);
+        };
+        run.line.push_str(payload);
+        run.next += 1;
+        if run.next < run.of {
+            return Ok(None);
+        }
+
+        let Some(run) = self.pending.take() else {
+            return Ok(None);
+        };
+        serde_json::from_str::<RolloutLine>(&run.line).map(Some)
+    }
+
+    fn drop_pending(&mut self) {
+        if let Some(run) = self.pending.take() {
+            warn!(
+                "dropping incomplete chunked rollout item {} ({} of {} parts)",
+                run.id, run.next, run.of
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use pretty_assertions::assert_eq;
+
+    fn message_line(text: &str) -> RolloutLine {
+        RolloutLine {
+            timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+            item: RolloutItem::ResponseItem(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: text.to_string(),
+                }],
+            }),
+            correlation_id: None,
+        }
+    }
+
+    fn chunk_lines(line: &RolloutLine, max_line_bytes: usize) -> Vec<RolloutLine> {
+        let json = serde_json::to_string(line).expect("serialize line");
+        split_line(&json, max_line_bytes, &RuntimeEnv::default())
+            .expect("line is split")
+            .into_iter()
+            .map(|item| RolloutLine {
+                timestamp: line.timestamp.clone(),
+                item,
+                correlation_id: None,
+            })
+            .collect()
+    }
+
+    fn assemble(lines: Vec<RolloutLine>) -> Vec<serde_json::Value> {
+        let mut assembler = ChunkAssembler::default();
+        let assembled = lines
+            .into_iter()
+            .filter_map(|line| assembler.push(line))
+            .map(|line| serde_json::to_value(line).expect("serialize line"))
+            .collect();
+        assembler.finish();
+        assembled
+    }
+
+    #[test]
+    fn split_lines_round_trip() {
+        let original = message_line(&"quoted \"ünïcode\" \\ text ".repeat(400));
+        let parts = chunk_lines(&original, 2048);
+        assert!(parts.len() > 1);
+        for part in &parts {
+            let RolloutItem::Chunked { payload, .. } = &part.item else {
+                panic!("expected a chunk, got {:?}", part.item);
+            };
+            assert!(serde_json::to_string(payload).expect("payload").len() <= 2048 + 2);
+        }
+
+        assert_eq!(
+            assemble(parts),
+            vec![serde_json::to_value(&original).expect("serialize line")]
+        );
+    }
+
+    #[test]
+    fn short_lines_are_not_split() {
+        let json = serde_json::to_string(&message_line("short")).expect("serialize line");
+        assert!(split_line(&json, 2048, &RuntimeEnv::default()).is_none());
+    }
+
+    #[test]
+    fn interrupted_runs_are_dropped_whole() {
+        let big = message_line(&"x".repeat(10_000));
+        let after = message_line("after");
+        let mut parts = chunk_lines(&big, 2048);
+        parts.pop();
+
+        // Cut short by another line.
+        let mut lines = parts.clone();
+        lines.push(after.clone());
+        assert_eq!(
+            assemble(lines),
+            vec![serde_json::to_value(&after).expect("serialize line")]
+        );
+
+        // Cut short by the end of the rollout.
+        assert_eq!(assemble(parts), Vec::<serde_json::Value>::new());
+    }
+}
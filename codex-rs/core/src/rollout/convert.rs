@@ -0,0 +1,799 @@
+//! Conversion between rollout items and the OpenAI chat `messages` format.
+//!
+//! Exported transcripts can be continued by other tools, and imported ones can
+//! seed a conversation through `InitialHistory::Forked`. Neither direction is
+//! lossless for every item kind, so both return a [`ConversionReport`]
+//! describing what was dropped or altered.
+
+use std::collections::HashSet;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// One entry of a chat completions `messages` array.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<ChatContent>,
+    /// Reasoning text preceding an assistant message, as accepted by
+    /// providers that support `reasoning` on chat messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ChatToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ChatContent {
+    Text(String),
+    Parts(Vec<ChatContentPart>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ChatImageUrl },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatImageUrl {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatToolCall {
+    Function {
+        id: String,
+        function: ChatFunctionCall,
+    },
+    Custom {
+        id: String,
+        custom: ChatCustomToolCall,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatFunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments, exactly as the model produced them.
+    pub arguments: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatCustomToolCall {
+    pub name: String,
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatExportOptions {
+    /// Attach reasoning text to the assistant message that follows it. When
+    /// false, reasoning items are dropped.
+    pub include_reasoning: bool,
+}
+
+impl Default for ChatExportOptions {
+    fn default() -> Self {
+        Self {
+            include_reasoning: true,
+        }
+    }
+}
+
+/// An input item that did not survive conversion unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionNote {
+    /// Index of the item in the input slice.
+    pub index: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub dropped: Vec<ConversionNote>,
+    pub altered: Vec<ConversionNote>,
+}
+
+impl ConversionReport {
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty() && self.altered.is_empty()
+    }
+
+    fn drop_item(&mut self, index: usize, reason: impl Into<String>) {
+        self.dropped.push(ConversionNote {
+            index,
+            reason: reason.into(),
+        });
+    }
+
+    fn alter_item(&mut self, index: usize, reason: impl Into<String>) {
+        self.altered.push(ConversionNote {
+            index,
+            reason: reason.into(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatExport {
+    pub messages: Vec<ChatMessage>,
+    pub report: ConversionReport,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatImport {
+    pub items: Vec<ResponseItem>,
+    pub report: ConversionReport,
+}
+
+/// Flatten rollout items into chat messages. Only items that form the model
+/// transcript are exported; session metadata, turn context, events, and
+/// checkpoint markers are skipped without being reported.
+pub fn to_chat_messages(items: &[RolloutItem], options: ChatExportOptions) -> ChatExport {
+    let mut exporter = ChatExporter {
+        options,
+        messages: Vec::new(),
+        report: ConversionReport::default(),
+        pending_reasoning: None,
+    };
+    for (index, item) in items.iter().enumerate() {
+        match item {
+            RolloutItem::ResponseItem(item) => exporter.push_response_item(index, item),
+            RolloutItem::Compacted(compacted) => {
+                exporter
+                    .report
+                    .alter_item(index, "compaction summary exported as an assistant message");
+                exporter.push_response_item(index, &ResponseItem::from(compacted.clone()));
+            }
+            RolloutItem::SessionMeta(_)
+            | RolloutItem::TurnContext(_)
+            | RolloutItem::EventMsg(_)
+            | RolloutItem::Checkpoint(_)
+            | RolloutItem::CheckpointRollback(_)
+            | RolloutItem::PendingInput(_)
+            | RolloutItem::InstructionsReplaced(_)
+            | RolloutItem::ReadReference(_)
+            | RolloutItem::InputRejected(_)
+            | RolloutItem::ItemPinned(_)
+            | RolloutItem::ItemUnpinned(_)
+            | RolloutItem::TranscriptMutation(_)
+            | RolloutItem::GitContext(_)
+            | RolloutItem::RequestFingerprint(_)
+            | RolloutItem::Annotation(_)
+            | RolloutItem::PartialMessage { .. }
+            | RolloutItem::TurnStarted(_)
+            | RolloutItem::ContinuedIn(_)
+            | RolloutItem::ContinuedFrom(_)
+            | RolloutItem::Chunked { .. }
+            | RolloutItem::Unknown { .. } => {}
+        }
+    }
+    exporter.finish()
+}
+
+struct ChatExporter {
+    options: ChatExportOptions,
+    messages: Vec<ChatMessage>,
+    report: ConversionReport,
+    /// Reasoning waiting for the assistant message it belongs to, with the
+    /// index of the first reasoning item that contributed to it.
+    pending_reasoning: Option<(usize, String)>,
+}
+
+impl ChatExporter {
+    fn push_response_item(&mut self, index: usize, item: &ResponseItem) {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let reasoning = if role == "assistant" {
+                    self.pending_reasoning.take().map(|(_, text)| text)
+                } else {
+                    self.flush_reasoning();
+                    None
+                };
+                self.messages.push(ChatMessage {
+                    role: role.clone(),
+                    content: Some(message_content(content)),
+                    reasoning,
+                    tool_calls: Vec::new(),
+                    tool_call_id: None,
+                });
+            }
+            ResponseItem::Reasoning {
+                summary,
+                content,
+                encrypted_content,
+                ..
+            } => {
+                if !self.options.include_reasoning {
+                    self.report
+                        .drop_item(index, "reasoning excluded from export");
+                    return;
+                }
+                if !summary.is_empty() || encrypted_content.is_some() {
+                    self.report.alter_item(
+                        index,
+                        "reasoning flattened to text, encrypted content dropped",
+                    );
+                }
+                let text = reasoning_text(summary, content.as_deref());
+                match &mut self.pending_reasoning {
+                    Some((_, pending)) => pending.push_str(&text),
+                    None => self.pending_reasoning = Some((index, text)),
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => self.push_tool_call(ChatToolCall::Function {
+                id: call_id.clone(),
+                function: ChatFunctionCall {
+                    name: name.clone(),
+                    arguments: arguments.clone(),
+                },
+            }),
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => self.push_tool_call(ChatToolCall::Custom {
+                id: call_id.clone(),
+                custom: ChatCustomToolCall {
+                    name: name.clone(),
+                    input: input.clone(),
+                },
+            }),
+            ResponseItem::LocalShellCall {
+                id,
+                call_id,
+                action,
+                ..
+            } => {
+                let LocalShellAction::Exec(exec) = action;
+                let arguments = serde_json::to_string(exec).unwrap_or_default();
+                self.report
+                    .alter_item(index, "local shell call exported as a function call");
+                self.push_tool_call(ChatToolCall::Function {
+                    id: call_id.clone().or_else(|| id.clone()).unwrap_or_default(),
+                    function: ChatFunctionCall {
+                        name: "local_shell".to_string(),
+                        arguments,
+                    },
+                });
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                self.flush_reasoning();
+                let content = match &output.content_items {
+                    Some(content_items) => {
+                        if output.content != output_items_text(content_items) {
+                            self.report
+                                .alter_item(index, "plain-text tool output replaced by its parts");
+                        }
+                        ChatContent::Parts(
+                            content_items
+                                .iter()
+                                .map(|item| match item {
+                                    FunctionCallOutputContentItem::InputText { text } => {
+                                        ChatContentPart::Text { text: text.clone() }
+                                    }
+                                    FunctionCallOutputContentItem::InputImage { image_url } => {
+                                        ChatContentPart::ImageUrl {
+                                            image_url: ChatImageUrl {
+                                                url: image_url.clone(),
+                                            },
+                                        }
+                                    }
+                                })
+                                .collect(),
+                        )
+                    }
+                    None => ChatContent::Text(output.content.clone()),
+                };
+                if output.success.is_some() {
+                    self.report
+                        .alter_item(index, "tool output success flag dropped");
+                }
+                self.push_tool_output(call_id, content);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                self.flush_reasoning();
+                self.push_tool_output(call_id, ChatContent::Text(output.clone()));
+            }
+            ResponseItem::WebSearchCall { .. } => {
+                self.report
+                    .drop_item(index, "web search calls have no chat form");
+            }
+            ResponseItem::GhostSnapshot { .. } => {
+                self.report
+                    .drop_item(index, "ghost snapshots are codex-only");
+            }
+            ResponseItem::Compaction { .. } => {
+                self.report
+                    .drop_item(index, "encrypted compaction has no chat form");
+            }
+            ResponseItem::Other => {
+                self.report.drop_item(index, "unknown item");
+            }
+        }
+    }
+
+    /// Consecutive tool calls share one assistant message, as parallel tool
+    /// calls do in the chat format.
+    fn push_tool_call(&mut self, call: ChatToolCall) {
+        if self.pending_reasoning.is_none()
+            && let Some(last) = self.messages.last_mut()
+            && last.role == "assistant"
+            && !last.tool_calls.is_empty()
+        {
+            last.tool_calls.push(call);
+            return;
+        }
+        self.messages.push(ChatMessage {
+            role: "assistant".to_string(),
+            content: None,
+            reasoning: self.pending_reasoning.take().map(|(_, text)| text),
+            tool_calls: vec![call],
+            tool_call_id: None,
+        });
+    }
+
+    fn push_tool_output(&mut self, call_id: &str, content: ChatContent) {
+        self.messages.push(ChatMessage {
+            role: "tool".to_string(),
+            content: Some(content),
+            reasoning: None,
+            tool_calls: Vec::new(),
+            tool_call_id: Some(call_id.to_string()),
+        });
+    }
+
+    /// Reasoning not followed by an assistant message has nowhere to go.
+    fn flush_reasoning(&mut self) {
+        if let Some((index, _)) = self.pending_reasoning.take() {
+            self.report
+                .drop_item(index, "reasoning not followed by an assistant message");
+        }
+    }
+
+    fn finish(mut self) -> ChatExport {
+        self.flush_reasoning();
+        ChatExport {
+            messages: self.messages,
+            report: self.report,
+        }
+    }
+}
+
+fn message_content(content: &[ContentItem]) -> ChatContent {
+    if let [ContentItem::InputText { text } | ContentItem::OutputText { text }] = content {
+        return ChatContent::Text(text.clone());
+    }
+    ChatContent::Parts(
+        content
+            .iter()
+            .map(|item| match item {
+                ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                    ChatContentPart::Text { text: text.clone() }
+                }
+                ContentItem::InputImage { image_url } => ChatContentPart::ImageUrl {
+                    image_url: ChatImageUrl {
+                        url: image_url.clone(),
+                    },
+                },
+            })
+            .collect(),
+    )
+}
+
+fn reasoning_text(
+    summary: &[ReasoningItemReasoningSummary],
+    content: Option<&[ReasoningItemContent]>,
+) -> String {
+    let mut text = String::new();
+    for ReasoningItemReasoningSummary::SummaryText { text: segment } in summary {
+        text.push_str(segment);
+    }
+    for entry in content.unwrap_or_default() {
+        match entry {
+            ReasoningItemContent::ReasoningText { text: segment }
+            | ReasoningItemContent::Text { text: segment } => text.push_str(segment),
+        }
+    }
+    text
+}
+
+fn output_items_text(items: &[FunctionCallOutputContentItem]) -> String {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            FunctionCallOutputContentItem::InputText { text } => Some(text.as_str()),
+            FunctionCallOutputContentItem::InputImage { .. } => None,
+        })
+        .collect()
+}
+
+/// Build response items from chat messages. `system` messages become
+/// `developer` messages, since codex does not replay system messages.
+pub fn from_chat_messages(messages: Vec<ChatMessage>) -> ChatImport {
+    let mut importer = ChatImporter::default();
+    for (index, message) in messages.into_iter().enumerate() {
+        importer.push_message(index, message);
+    }
+    importer.finish()
+}
+
+/// Incremental form of [`from_chat_messages`] for callers that interleave
+/// their own items with imported messages.
+#[derive(Default)]
+pub(crate) struct ChatImporter {
+    pub(crate) items: Vec<ResponseItem>,
+    pub(crate) report: ConversionReport,
+    custom_call_ids: HashSet<String>,
+}
+
+impl ChatImporter {
+    pub(crate) fn push_message(&mut self, index: usize, message: ChatMessage) {
+        let ChatMessage {
+            role,
+            content,
+            reasoning,
+            tool_calls,
+            tool_call_id,
+        } = message;
+        match role.as_str() {
+            "system" | "developer" | "user" => {
+                if role == "system" {
+                    self.report
+                        .alter_item(index, "system message imported as a developer message");
+                }
+                if reasoning.is_some() || !tool_calls.is_empty() || tool_call_id.is_some() {
+                    self.report
+                        .alter_item(index, "tool fields ignored on a non-assistant message");
+                }
+                let role = if role == "system" {
+                    "developer".to_string()
+                } else {
+                    role
+                };
+                self.items.push(ResponseItem::Message {
+                    id: None,
+                    content: content_items(content, false),
+                    role,
+                });
+            }
+            "assistant" => {
+                if let Some(text) = reasoning {
+                    self.items.push(ResponseItem::Reasoning {
+                        id: String::new(),
+                        summary: Vec::new(),
+                        content: Some(vec![ReasoningItemContent::ReasoningText { text }]),
+                        encrypted_content: None,
+                    });
+                }
+                let content = content_items(content, true);
+                if !content.is_empty() {
+                    self.items.push(ResponseItem::Message {
+                        id: None,
+                        role,
+                        content,
+                    });
+                }
+                for call in tool_calls {
+                    self.items.push(match call {
+                        ChatToolCall::Function { id, function } => ResponseItem::FunctionCall {
+                            id: None,
+                            name: function.name,
+                            arguments: function.arguments,
+                            call_id: id,
+                        },
+                        ChatToolCall::Custom { id, custom } => {
+                            self.custom_call_ids.insert(id.clone());
+                            ResponseItem::CustomToolCall {
+                                id: None,
+                                status: None,
+                                call_id: id,
+                                name: custom.name,
+                                input: custom.input,
+                            }
+                        }
+                    });
+                }
+            }
+            "tool" => {
+                let Some(call_id) = tool_call_id else {
+                    self.report
+                        .drop_item(index, "tool message without tool_call_id");
+                    return;
+                };
+                if self.custom_call_ids.contains(&call_id) {
+                    let output = match content {
+                        Some(ChatContent::Text(text)) => text,
+                        Some(ChatContent::Parts(parts)) => {
+                            self.report
+                                .alter_item(index, "custom tool output flattened to text");
+                            parts_text(&parts)
+                        }
+                        None => String::new(),
+                    };
+                    self.items
+                        .push(ResponseItem::CustomToolCallOutput { call_id, output });
+                    return;
+                }
+                let output = match content {
+                    Some(ChatContent::Text(text)) => FunctionCallOutputPayload {
+                        content: text,
+                        ..Default::default()
+                    },
+                    Some(ChatContent::Parts(parts)) => FunctionCallOutputPayload {
+                        content: parts_text(&parts),
+                        content_items: Some(
+                            parts
+                                .into_iter()
+                                .map(|part| match part {
+                                    ChatContentPart::Text { text } => {
+                                        FunctionCallOutputContentItem::InputText { text }
+                                    }
+                                    ChatContentPart::ImageUrl { image_url } => {
+                                        FunctionCallOutputContentItem::InputImage {
+                                            image_url: image_url.url,
+                                        }
+                                    }
+                                })
+                                .collect(),
+                        ),
+                        success: None,
+                    },
+                    None => FunctionCallOutputPayload::default(),
+                };
+                self.items
+                    .push(ResponseItem::FunctionCallOutput { call_id, output });
+            }
+            _ => self
+                .report
+                .drop_item(index, format!("unsupported role `{role}`")),
+        }
+    }
+
+    pub(crate) fn finish(self) -> ChatImport {
+        ChatImport {
+            items: self.items,
+            report: self.report,
+        }
+    }
+}
+
+fn content_items(content: Option<ChatContent>, assistant: bool) -> Vec<ContentItem> {
+    let text_item = |text: String| {
+        if assistant {
+            ContentItem::OutputText { text }
+        } else {
+            ContentItem::InputText { text }
+        }
+    };
+    match content {
+        None => Vec::new(),
+        Some(ChatContent::Text(text)) if text.is_empty() => Vec::new(),
+        Some(ChatContent::Text(text)) => vec![text_item(text)],
+        Some(ChatContent::Parts(parts)) => parts
+            .into_iter()
+            .map(|part| match part {
+                ChatContentPart::Text { text } => text_item(text),
+                ChatContentPart::ImageUrl { image_url } => ContentItem::InputImage {
+                    image_url: image_url.url,
+                },
+            })
+            .collect(),
+    }
+}
+
+fn parts_text(parts: &[ChatContentPart]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            ChatContentPart::Text { text } => Some(text.as_str()),
+            ChatContentPart::ImageUrl { .. } => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::LocalShellExecAction;
+    use codex_protocol::models::LocalShellStatus;
+    use codex_protocol::models::WebSearchAction;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, content: Vec<ContentItem>) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content,
+        }
+    }
+
+    fn transcript() -> Vec<ResponseItem> {
+        vec![
+            message(
+                "user",
+                vec![
+                    ContentItem::InputText {
+                        text: "what is in this picture?".to_string(),
+                    },
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string(),
+                    },
+                ],
+            ),
+            ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText {
+                    text: "Let me look closer.".to_string(),
+                }]),
+                encrypted_content: None,
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "view_image".to_string(),
+                arguments: r#"{"path":"a.png"}"#.to_string(),
+                call_id: "call-1".to_string(),
+            },
+            ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call-2".to_string(),
+                name: "apply_patch".to_string(),
+                input: "*** Begin Patch\n*** End Patch".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "attached".to_string(),
+                    content_items: Some(vec![
+                        FunctionCallOutputContentItem::InputText {
+                            text: "attached".to_string(),
+                        },
+                        FunctionCallOutputContentItem::InputImage {
+                            image_url: "data:image/png;base64,BBBB".to_string(),
+                        },
+                    ]),
+                    success: None,
+                },
+            },
+            ResponseItem::CustomToolCallOutput {
+                call_id: "call-2".to_string(),
+                output: "Done!".to_string(),
+            },
+            message(
+                "assistant",
+                vec![ContentItem::OutputText {
+                    text: "A cat.".to_string(),
+                }],
+            ),
+        ]
+    }
+
+    #[test]
+    fn transcript_round_trips_through_chat_messages() {
+        let items = transcript();
+        let rollout: Vec<RolloutItem> = items
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+
+        let export = to_chat_messages(&rollout, ChatExportOptions::default());
+        assert_eq!(export.report, ConversionReport::default());
+        let json = serde_json::to_value(&export.messages).expect("serialize messages");
+        assert_eq!(
+            json[1],
+            serde_json::json!({
+                "role": "assistant",
+                "content": null,
+                "reasoning": "Let me look closer.",
+                "tool_calls": [
+                    {
+                        "type": "function",
+                        "id": "call-1",
+                        "function": {"name": "view_image", "arguments": "{\"path\":\"a.png\"}"},
+                    },
+                    {
+                        "type": "custom",
+                        "id": "call-2",
+                        "custom": {"name": "apply_patch", "input": "*** Begin Patch\n*** End Patch"},
+                    },
+                ],
+            })
+        );
+
+        let messages: Vec<ChatMessage> =
+            serde_json::from_value(json).expect("deserialize messages");
+        let import = from_chat_messages(messages);
+        assert_eq!(import.report, ConversionReport::default());
+        assert_eq!(import.items, items);
+    }
+
+    #[test]
+    fn lossy_items_are_reported() {
+        let rollout = vec![
+            RolloutItem::ResponseItem(ResponseItem::WebSearchCall {
+                id: None,
+                status: None,
+                action: WebSearchAction::Search {
+                    query: Some("weather".to_string()),
+                },
+            }),
+            RolloutItem::ResponseItem(ResponseItem::LocalShellCall {
+                id: None,
+                call_id: Some("call-1".to_string()),
+                status: LocalShellStatus::Completed,
+                action: LocalShellAction::Exec(LocalShellExecAction {
+                    command: vec!["ls".to_string()],
+                    timeout_ms: None,
+                    working_directory: None,
+                    env: None,
+                    user: None,
+                }),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("opaque".to_string()),
+            }),
+        ];
+
+        let export = to_chat_messages(&rollout, ChatExportOptions::default());
+        let indices = |notes: &[ConversionNote]| notes.iter().map(|n| n.index).collect::<Vec<_>>();
+        assert_eq!(indices(&export.report.dropped), vec![0, 2]);
+        assert_eq!(indices(&export.report.altered), vec![1, 2]);
+        assert_eq!(export.messages.len(), 1);
+
+        let without_reasoning = to_chat_messages(
+            &rollout[2..],
+            ChatExportOptions {
+                include_reasoning: false,
+            },
+        );
+        assert_eq!(indices(&without_reasoning.report.dropped), vec![0]);
+        assert!(without_reasoning.report.altered.is_empty());
+    }
+
+    #[test]
+    fn system_messages_import_as_developer_messages() {
+        let import = from_chat_messages(vec![ChatMessage {
+            role: "system".to_string(),
+            content: Some(ChatContent::Text("be brief".to_string())),
+            reasoning: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }]);
+        assert_eq!(
+            import.items,
+            vec![message(
+                "developer",
+                vec![ContentItem::InputText {
+                    text: "be brief".to_string()
+                }]
+            )]
+        );
+        assert_eq!(import.report.altered.len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+//! Deleting a rollout and everything stored for it, see
+//! [`ConversationManager::remove_conversation_and_delete`].
+//!
+//! A rollout's files are its parts and every file named after it next to
+//! them: the event log, its rotated copies and other sidecars. The blobs its
+//! command outputs were stored in go with it, unless another rollout or
+//! event log under codex home mentions them too.
+//!
+//! [`ConversationManager::remove_conversation_and_delete`]: crate::ConversationManager::remove_conversation_and_delete
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::BlobRef;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+use super::chain::rollout_parts;
+use super::event_log::EventLogLine;
+use crate::blob_store::BlobStore;
+
+/// What [`delete_rollout`] removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeletionReport {
+    pub bytes_freed: u64,
+    pub files_removed: usize,
+}
+
+/// Delete the rollout whose first part is `head`, its sidecars and the blobs
+/// only it refers to. Files that are already gone are skipped.
+pub(crate) async fn delete_rollout(codex_home: &Path, head: &Path) -> io::Result<DeletionReport> {
+    let files = rollout_files(head).await?;
+    let mut blobs = BTreeMap::new();
+    for file in &files {
+        collect_blobs(file, &mut blobs).await?;
+    }
+    if !blobs.is_empty() {
+        drop_shared_blobs(codex_home, &files, &mut blobs).await?;
+    }
+
+    let store = BlobStore::new(codex_home);
+    let mut report = DeletionReport::default();
+    let blob_paths = blobs.values().map(|blob| store.path(blob));
+    for path in files.iter().cloned().chain(blob_paths) {
+        if let Some(bytes) = remove_file(&path).await? {
+            report.bytes_freed += bytes;
+            report.files_removed += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// The parts of the rollout at `head` and the files next to them named
+/// `<stem>.*` after it.
+async fn rollout_files(head: &Path) -> io::Result<BTreeSet<PathBuf>> {
+    let mut files: BTreeSet<PathBuf> = rollout_parts(head).await?.into_iter().collect();
+    let (Some(dir), Some(stem)) = (head.parent(), head.file_stem()) else {
+        return Ok(files);
+    };
+    let prefix = format!("{}.", stem.to_string_lossy());
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(files),
+        Err(err) => return Err(err),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_file()
+            && entry.file_name().to_string_lossy().starts_with(&prefix)
+        {
+            files.insert(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+/// Add the blobs the rollout or event log lines of `path` refer to.
+async fn collect_blobs(path: &Path, blobs: &mut BTreeMap<String, BlobRef>) -> io::Result<()> {
+    let text = match tokio::fs::read_to_string(path).await {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for line in text.lines() {
+        let msg = if let Ok(RolloutLine {
+            item: RolloutItem::EventMsg(msg),
+            ..
+        }) = serde_json::from_str(line)
+        {
+            msg
+        } else if let Ok(EventLogLine { event, .. }) = serde_json::from_str(line) {
+            event.msg
+        } else {
+            continue;
+        };
+        if let EventMsg::ExecCommandEnd(end) = msg
+            && let Some(blob) = end.output_blob
+        {
+            blobs.insert(blob.sha256.clone(), blob);
+        }
+    }
+    Ok(())
+}
+
+/// Remove from `blobs` those mentioned by a rollout or event log under
+/// `codex_home` other than `deleted`.
+async fn drop_shared_blobs(
+    codex_home: &Path,
+    deleted: &BTreeSet<PathBuf>,
+    blobs: &mut BTreeMap<String, BlobRef>,
+) -> io::Result<()> {
+    for subdir in [SESSIONS_SUBDIR, ARCHIVED_SESSIONS_SUBDIR] {
+        let mut pending = vec![codex_home.join(subdir)];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                if deleted.contains(&path) || path.extension().is_none_or(|ext| ext != "jsonl") {
+                    continue;
+                }
+                let text = match tokio::fs::read_to_string(&path).await {
+                    Ok(text) => text,
+                    // Deleted since it was listed.
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err),
+                };
+                blobs.retain(|sha256, _| !text.contains(sha256.as_str()));
+                if blobs.is_empty() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove `path` and return its size, or `None` if it did not exist.
+async fn remove_file(path: &Path) -> io::Result<Option<u64>> {
+    let bytes = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata.len(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(Some(bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::ExecCommandEndEvent;
+    use codex_protocol::protocol::ExecCommandSource;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn exec_end_line(blob: &BlobRef) -> String {
+        let end = ExecCommandEndEvent {
+            call_id: "call-1".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec!["echo".to_string()],
+            cwd: PathBuf::from("/"),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::default(),
+            interaction_input: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            aggregated_output: String::new(),
+            exit_code: 0,
+            duration: Duration::ZERO,
+            formatted_output: String::new(),
+            output_blob: Some(blob.clone()),
+        };
+        let line = RolloutLine {
+            timestamp: "2025-01-02T00:00:00.000Z".to_string(),
+            correlation_id: None,
+            item: RolloutItem::EventMsg(EventMsg::ExecCommandEnd(end)),
+        };
+        serde_json::to_string(&line).expect("rollout line")
+    }
+
+    #[tokio::test]
+    async fn deletes_parts_sidecars_and_unshared_blobs() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let day = home.path().join("sessions/2025/01/02");
+        tokio::fs::create_dir_all(&day).await?;
+        let store = BlobStore::new(home.path());
+        let own = store.put(b"only mine").await?;
+        let shared = store.put(b"shared").await?;
+
+        let head = day.join("rollout-x.jsonl");
+        let lines = format!("{}\n{}\n", exec_end_line(&own), exec_end_line(&shared));
+        tokio::fs::write(&head, &lines).await?;
+        tokio::fs::write(day.join("rollout-x.part2.jsonl"), "{}\n").await?;
+        tokio::fs::write(day.join("rollout-x.events.jsonl"), "{}\n").await?;
+        tokio::fs::write(day.join("rollout-x.events.1.jsonl"), "{}\n").await?;
+        let other = day.join("rollout-y.jsonl");
+        tokio::fs::write(&other, format!("{}\n", exec_end_line(&shared))).await?;
+
+        let report = delete_rollout(home.path(), &head).await?;
+        assert_eq!(
+            report,
+            DeletionReport {
+                bytes_freed: lines.len() as u64 + 3 * 3 + b"only mine".len() as u64,
+                files_removed: 5,
+            }
+        );
+        let mut left = Vec::new();
+        let mut entries = tokio::fs::read_dir(&day).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            left.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        assert_eq!(left, vec!["rollout-y.jsonl".to_string()]);
+        assert!(!tokio::fs::try_exists(store.path(&own)).await?);
+        assert_eq!(store.get(&shared).await?, b"shared".to_vec());
+        Ok(())
+    }
+}
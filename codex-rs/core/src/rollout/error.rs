@@ -1,8 +1,8 @@
 use std::io::ErrorKind;
 use std::path::Path;
 
+use crate::codex_home::CodexHome;
 use crate::error::CodexErr;
-use crate::rollout::SESSIONS_SUBDIR;
 
 pub(crate) fn map_session_init_error(err: &anyhow::Error, codex_home: &Path) -> CodexErr {
     if let Some(mapped) = err
@@ -17,7 +17,7 @@ pub(crate) fn map_session_init_error(err: &anyhow::Error, codex_home: &Path) ->
 }
 
 fn map_rollout_io_error(io_err: &std::io::Error, codex_home: &Path) -> Option<CodexErr> {
-    let sessions_dir = codex_home.join(SESSIONS_SUBDIR);
+    let sessions_dir = CodexHome::new(codex_home).sessions_dir();
     let hint = match io_err.kind() {
         ErrorKind::PermissionDenied => format!(
             "Codex cannot access session files at {} (permission denied). If sessions were created using sudo, fix ownership: sudo chown -R $(whoami) {}",
@@ -0,0 +1,298 @@
+//! Optional audit log of every [`Event`] a conversation emits.
+//!
+//! The event log lives next to the rollout as `<rollout>.events.jsonl` and is
+//! independent of it: the rollout keeps only what is needed to rebuild model
+//! context, while the event log records deltas, approvals, and metrics too.
+//! The event log is never read back into model context.
+//!
+//! The `SessionConfigured` event's `rollout_path` is stored relative to codex
+//! home and resolved against the codex home given to [`read_event_log`].
+
+use std::io::Error as IoError;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_channel::Receiver;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+use super::location;
+use crate::runtime_env::RuntimeEnv;
+use crate::sensitive_input::SensitiveInputGuard;
+
+/// File name suffix of event logs, replacing the rollout's `.jsonl`.
+pub const EVENT_LOG_SUFFIX: &str = ".events.jsonl";
+
+const DEFAULT_EVENT_LOG_MAX_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_EVENT_LOG_MAX_ROTATED_FILES: usize = 1;
+
+/// Controls how the event log grows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventLogPolicy {
+    /// Rotate the active log once it would grow past this many bytes. `None`
+    /// keeps a single, unbounded file.
+    pub max_bytes: Option<u64>,
+
+    /// Number of rotated files (`<rollout>.events.1.jsonl`, ...) kept next to
+    /// the active log. Older files are deleted.
+    pub max_rotated_files: usize,
+}
+
+impl Default for EventLogPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(DEFAULT_EVENT_LOG_MAX_BYTES),
+            max_rotated_files: DEFAULT_EVENT_LOG_MAX_ROTATED_FILES,
+        }
+    }
+}
+
+/// One line of an event log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventLogLine {
+    pub timestamp: String,
+    pub event: Event,
+}
+
+/// Path of the event log that accompanies `rollout_path`.
+pub fn event_log_path(rollout_path: &Path) -> PathBuf {
+    rollout_path.with_extension(EVENT_LOG_SUFFIX.trim_start_matches('.'))
+}
+
+fn rotated_event_log_path(path: &Path, index: usize) -> PathBuf {
+    let base = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(EVENT_LOG_SUFFIX))
+        .unwrap_or_default();
+    path.with_file_name(format!("{base}.events.{index}.jsonl"))
+}
+
+/// Read every event recorded in the event log at `path`, skipping lines that
+/// fail to parse. Rollout locations are resolved against `codex_home`.
+pub async fn read_event_log(codex_home: &Path, path: &Path) -> std::io::Result<Vec<EventLogLine>> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<EventLogLine>(line) {
+            Ok(mut line) => {
+                if let EventMsg::SessionConfigured(configured) = &mut line.event.msg {
+                    configured.rollout_path =
+                        location::resolve(codex_home, &configured.rollout_path);
+                }
+                lines.push(line);
+            }
+            Err(e) => warn!("failed to parse event log line: {e}"),
+        }
+    }
+    Ok(lines)
+}
+
+/// Tee `rx` into the event log at `path`. Events are forwarded on the
+/// returned receiver in order; failing to write the log never drops events.
+pub(crate) fn spawn_event_log(
+    path: PathBuf,
+    codex_home: PathBuf,
+    policy: EventLogPolicy,
+    sensitive_input: Arc<SensitiveInputGuard>,
+    env: RuntimeEnv,
+    rx: Receiver<Event>,
+) -> Receiver<Event> {
+    let (tx, forwarded) = async_channel::unbounded();
+    tokio::spawn(async move {
+        let mut writer = match EventLogWriter::open(path, codex_home, policy, env).await {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                warn!("failed to open event log: {e}");
+                None
+            }
+        };
+        while let Ok(event) = rx.recv().await {
+            let redacted = sensitive_input.redact_event(&event);
+            if let Some(active) = writer.as_mut()
+                && let Err(e) = active.append(redacted.as_ref().unwrap_or(&event)).await
+            {
+                warn!("failed to write event log, disabling it: {e}");
+                writer = None;
+            }
+            if tx.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+    forwarded
+}
+
+struct EventLogWriter {
+    path: PathBuf,
+    codex_home: PathBuf,
+    policy: EventLogPolicy,
+    file: tokio::fs::File,
+    written: u64,
+    env: RuntimeEnv,
+}
+
+impl EventLogWriter {
+    async fn open(
+        path: PathBuf,
+        codex_home: PathBuf,
+        policy: EventLogPolicy,
+        env: RuntimeEnv,
+    ) -> std::io::Result<Self> {
+        let file = Self::open_append(&path).await?;
+        let written = file.metadata().await?.len();
+        Ok(Self {
+            path,
+            codex_home,
+            policy,
+            file,
+            written,
+            env,
+        })
+    }
+
+    async fn open_append(path: &Path) -> std::io::Result<tokio::fs::File> {
+        tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+    }
+
+    async fn append(&mut self, event: &Event) -> std::io::Result<()> {
+        let timestamp_format: &[FormatItem] = format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+        );
+        let timestamp = OffsetDateTime::from(self.env.now())
+            .format(timestamp_format)
+            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+        let mut event = event.clone();
+        if let EventMsg::SessionConfigured(configured) = &mut event.msg {
+            configured.rollout_path =
+                location::to_stored(&self.codex_home, &configured.rollout_path);
+        }
+        let line = EventLogLine { timestamp, event };
+        let mut json = serde_json::to_string(&line)?;
+        json.push('\n');
+
+        let len = json.len() as u64;
+        if let Some(max_bytes) = self.policy.max_bytes
+            && self.written > 0
+            && self.written.saturating_add(len) > max_bytes
+        {
+            self.rotate().await?;
+        }
+
+        self.file.write_all(json.as_bytes()).await?;
+        self.file.flush().await?;
+        self.written = self.written.saturating_add(len);
+        Ok(())
+    }
+
+    /// Shift `<rollout>.events.N.jsonl` files up by one, dropping the oldest,
+    /// and start a fresh active log.
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush().await?;
+        let keep = self.policy.max_rotated_files;
+        if keep == 0 {
+            tokio::fs::remove_file(&self.path).await?;
+        } else {
+            let oldest = rotated_event_log_path(&self.path, keep);
+            if tokio::fs::try_exists(&oldest).await? {
+                tokio::fs::remove_file(&oldest).await?;
+            }
+            for index in (1..keep).rev() {
+                let from = rotated_event_log_path(&self.path, index);
+                if tokio::fs::try_exists(&from).await? {
+                    tokio::fs::rename(&from, rotated_event_log_path(&self.path, index + 1)).await?;
+                }
+            }
+            tokio::fs::rename(&self.path, rotated_event_log_path(&self.path, 1)).await?;
+        }
+        self.file = Self::open_append(&self.path).await?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::AgentMessageDeltaEvent;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn delta(text: &str) -> Event {
+        Event {
+            id: "sub".to_string(),
+            msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: text.to_string(),
+            }),
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn event_log_path_replaces_rollout_extension() {
+        assert_eq!(
+            event_log_path(Path::new("/tmp/rollout-2025-01-01T00-00-00-abc.jsonl")),
+            PathBuf::from("/tmp/rollout-2025-01-01T00-00-00-abc.events.jsonl")
+        );
+        assert_eq!(
+            rotated_event_log_path(Path::new("/tmp/rollout-abc.events.jsonl"), 2),
+            PathBuf::from("/tmp/rollout-abc.events.2.jsonl")
+        );
+    }
+
+    #[tokio::test]
+    async fn rotates_once_max_bytes_is_exceeded() -> std::io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("rollout-abc.events.jsonl");
+        let policy = EventLogPolicy {
+            max_bytes: Some(1),
+            max_rotated_files: 1,
+        };
+        let mut writer = EventLogWriter::open(
+            path.clone(),
+            dir.path().to_path_buf(),
+            policy,
+            RuntimeEnv::default(),
+        )
+        .await?;
+        for text in ["one", "two", "three"] {
+            writer.append(&delta(text)).await?;
+        }
+
+        let deltas = |lines: Vec<EventLogLine>| -> Vec<String> {
+            lines
+                .into_iter()
+                .filter_map(|line| match line.event.msg {
+                    EventMsg::AgentMessageDelta(ev) => Some(ev.delta),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(
+            deltas(read_event_log(dir.path(), &path).await?),
+            vec!["three"]
+        );
+        assert_eq!(
+            deltas(read_event_log(dir.path(), &rotated_event_log_path(&path, 1)).await?),
+            vec!["two"]
+        );
+        assert!(!rotated_event_log_path(&path, 2).exists());
+        Ok(())
+    }
+}
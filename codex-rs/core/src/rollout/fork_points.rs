@@ -0,0 +1,230 @@
+//! The places a rollout can be forked at, labeled for display.
+//!
+//! A fork point is a user message: forking at ordinal `n` keeps every item
+//! before the nth user message. Positions are found with
+//! [`user_message_positions`]'s rule, so an ordinal returned here
+//! always cuts where `ConversationManager::fork_conversation` cuts. A message
+//! that starts a turn also carries the turn's id, for
+//! `ConversationManager::fork_conversation_at_turn`.
+//!
+//! [`user_message_positions`]: crate::history_truncation::user_message_positions
+
+use std::io;
+use std::path::Path;
+
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TurnId;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::chain::ChainLines;
+use super::chunked::ChunkAssembler;
+use crate::history_truncation::user_message_at;
+
+/// Snippets are cut to this many characters.
+const MAX_SNIPPET_CHARS: usize = 80;
+
+/// A user message a rollout can be forked at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkPoint {
+    /// Position of the message among the user messages, as passed to
+    /// `fork_conversation`.
+    pub ordinal: usize,
+    /// Position of the message among the items of the rollout history.
+    pub item_index: usize,
+    /// The turn the message starts. `None` for messages added to a running
+    /// turn and for rollouts recorded before turns had ids.
+    #[serde(default)]
+    pub turn_id: Option<TurnId>,
+    /// When the message was recorded.
+    pub timestamp: String,
+    /// Start of the message text on a single line.
+    pub snippet: String,
+}
+
+/// List the fork points of the rollout at `path`, reading it line by line.
+pub async fn fork_points(path: &Path) -> io::Result<Vec<ForkPoint>> {
+    let mut lines = ChainLines::open(path).await?;
+    let mut chunks = ChunkAssembler::default();
+    let mut points = Vec::new();
+    // Counts the items the history loaded for a fork holds, which skips the
+    // same lines as this loop.
+    let mut item_index = 0;
+    // The turn whose first user message is still ahead.
+    let mut starting_turn = None;
+
+    while let Some(line) = lines.next_line().await? {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(trimmed) else {
+            continue;
+        };
+        let Some(rollout_line) = chunks.push(rollout_line) else {
+            continue;
+        };
+        if matches!(rollout_line.item, RolloutItem::Chunked { .. }) {
+            continue;
+        }
+        if let RolloutItem::TurnStarted(started) = &rollout_line.item {
+            starting_turn = Some(started.turn_id);
+        }
+        if let Some(message) = user_message_at(&rollout_line.item) {
+            points.push(ForkPoint {
+                ordinal: points.len(),
+                item_index,
+                turn_id: starting_turn.take(),
+                timestamp: rollout_line.timestamp,
+                snippet: snippet(&message.message()),
+            });
+        }
+        item_index += 1;
+    }
+    chunks.finish();
+
+    Ok(points)
+}
+
+/// `text` with whitespace runs collapsed to single spaces, cut to
+/// [`MAX_SNIPPET_CHARS`] characters.
+pub(crate) fn snippet(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= MAX_SNIPPET_CHARS {
+        return collapsed;
+    }
+    let mut cut: String = collapsed.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_truncation::try_truncate_before_nth_user_message;
+    use crate::rollout::RolloutRecorder;
+    use codex_protocol::ConversationId;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use codex_protocol::protocol::TurnStartedItem;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn message(role: &str, text: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        })
+    }
+
+    fn write_rollout(dir: &TempDir, items: Vec<RolloutItem>) -> std::path::PathBuf {
+        let path = dir.path().join("rollout.jsonl");
+        let lines: Vec<String> = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let line = RolloutLine {
+                    timestamp: format!("2025-01-01T00:00:{index:02}Z"),
+                    item,
+                    correlation_id: None,
+                };
+                serde_json::to_string(&line).expect("serialize")
+            })
+            .collect();
+        // Blank and unparsable lines are not items of the history.
+        let text = format!(
+            "{}\n\nnot json\n{}\n",
+            lines[..2].join("\n"),
+            lines[2..].join("\n")
+        );
+        std::fs::write(&path, text).expect("write rollout");
+        path
+    }
+
+    #[tokio::test]
+    async fn forking_at_each_point_cuts_before_its_message() {
+        let dir = TempDir::new().expect("tempdir");
+        let long = format!("third\n\n  message {}", "x".repeat(200));
+        let path = write_rollout(
+            &dir,
+            vec![
+                RolloutItem::SessionMeta(SessionMetaLine {
+                    meta: SessionMeta {
+                        id: ConversationId::new(),
+                        ..Default::default()
+                    },
+                    git: None,
+                }),
+                message("user", "first"),
+                message("assistant", "reply"),
+                message("user", "second"),
+                message("assistant", "reply"),
+                message("user", &long),
+            ],
+        );
+
+        let points = fork_points(&path).await.expect("fork points");
+        assert_eq!(
+            points
+                .iter()
+                .map(|point| (point.ordinal, point.item_index, point.timestamp.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 1, "2025-01-01T00:00:01Z"),
+                (1, 3, "2025-01-01T00:00:03Z"),
+                (2, 5, "2025-01-01T00:00:05Z"),
+            ]
+        );
+        assert_eq!(points[0].snippet, "first");
+        assert_eq!(points[2].snippet.chars().count(), MAX_SNIPPET_CHARS);
+        assert!(points[2].snippet.starts_with("third message xxx"));
+        assert!(points[2].snippet.ends_with('…'));
+
+        let history = RolloutRecorder::get_rollout_history(&path)
+            .await
+            .expect("history");
+        let items = history.get_rollout_items();
+        for point in &points {
+            let prefix = try_truncate_before_nth_user_message(history.clone(), point.ordinal)
+                .expect("fork")
+                .get_rollout_items();
+            assert_eq!(prefix.len(), point.item_index);
+            let labeled = user_message_at(&items[point.item_index]).expect("user message");
+            assert_eq!(snippet(&labeled.message()), point.snippet);
+        }
+    }
+
+    #[tokio::test]
+    async fn messages_that_start_a_turn_carry_its_id() {
+        let dir = TempDir::new().expect("tempdir");
+        let started = |turn_id: u64| {
+            RolloutItem::TurnStarted(TurnStartedItem {
+                turn_id: TurnId::new(turn_id),
+            })
+        };
+        let path = write_rollout(
+            &dir,
+            vec![
+                message("user", "before turn ids"),
+                message("assistant", "reply"),
+                started(1),
+                message("user", "first"),
+                message("user", "added while running"),
+                started(2),
+                message("user", "second"),
+            ],
+        );
+
+        let points = fork_points(&path).await.expect("fork points");
+        assert_eq!(
+            points.iter().map(|point| point.turn_id).collect::<Vec<_>>(),
+            vec![None, Some(TurnId::new(1)), None, Some(TurnId::new(2))]
+        );
+    }
+}
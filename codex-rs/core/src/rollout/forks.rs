@@ -0,0 +1,67 @@
+//! The recorded forks of a conversation, found by the parent their session
+//! meta names.
+//!
+//! Like every listing of codex home, the scan only sees rollouts holding a
+//! user message: a fork cut before the first one is found once it has run a
+//! turn.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::ParentRef;
+use codex_protocol::protocol::SessionMetaLine;
+
+use super::list::get_conversations;
+
+/// Rollouts read per listing page while scanning.
+const PAGE_SIZE: usize = 100;
+
+/// A conversation forked from another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkedChild {
+    pub conversation_id: ConversationId,
+    /// Absolute path to the fork's rollout file.
+    pub path: PathBuf,
+    pub parent: ParentRef,
+}
+
+/// The forks of `parent_id` recorded under `codex_home`, newest first.
+pub(crate) async fn forks_of(
+    codex_home: &Path,
+    parent_id: ConversationId,
+) -> io::Result<Vec<ForkedChild>> {
+    let mut forks = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let page = get_conversations(codex_home, PAGE_SIZE, cursor.as_ref(), &[], None, "").await?;
+        for item in page.items {
+            let Some(SessionMetaLine { meta, .. }) = item
+                .head
+                .first()
+                .and_then(|head| serde_json::from_value(head.clone()).ok())
+            else {
+                continue;
+            };
+            let Some(parent) = meta
+                .parent
+                .filter(|parent| parent.conversation_id == parent_id)
+            else {
+                continue;
+            };
+            forks.push(ForkedChild {
+                conversation_id: meta.id,
+                path: item.path,
+                parent,
+            });
+        }
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(forks)
+}
@@ -0,0 +1,472 @@
+//! Import session logs written by other tools as resumable rollouts.
+//!
+//! Each supported [`ImportFormat`] is mapped to [`ResponseItem`]s and written
+//! to a new rollout under `$CODEX_HOME/sessions` with a fresh
+//! [`ConversationId`], so the result can be passed to
+//! `ConversationManager::resume_conversation_from_rollout`. Entries with no
+//! codex equivalent are kept in the transcript as annotations instead of
+//! being dropped, and counted in the [`ImportReport`].
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionSource;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::convert::ChatImporter;
+use super::convert::ChatMessage;
+use super::recorder::RolloutRecorder;
+use super::recorder::RolloutRecorderParams;
+use crate::config::Config;
+use crate::error::Result as CodexResult;
+use crate::parse_turn_item;
+
+pub(crate) const IMPORTED_ENTRY_OPEN_TAG: &str = "<imported_entry>";
+const IMPORTED_ENTRY_CLOSE_TAG: &str = "</imported_entry>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// One chat completions message (`{"role": ..., "content": ...}`) per
+    /// line, as produced by most chat exports.
+    ChatMessages,
+    /// Claude Code session logs (`~/.claude/projects/<project>/<session>.jsonl`).
+    ClaudeCode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub conversation_id: ConversationId,
+    pub rollout_path: PathBuf,
+    /// Transcript items written to the rollout, annotations included.
+    pub items: usize,
+    /// Entries, or parts of entries, preserved as annotations because they
+    /// have no codex equivalent.
+    pub annotations: usize,
+}
+
+/// Convert the session log at `path` into a new rollout and return where it
+/// was written.
+pub async fn from_jsonl(
+    config: &Config,
+    path: &Path,
+    format: ImportFormat,
+) -> CodexResult<ImportReport> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let transcript = match format {
+        ImportFormat::ChatMessages => import_chat_messages(&text),
+        ImportFormat::ClaudeCode => import_claude_code(&text),
+    };
+
+    let conversation_id = ConversationId::new();
+    // Recorded as a CLI session so the import shows up in `codex resume`.
+    let recorder = RolloutRecorder::new(
+        config,
+        RolloutRecorderParams::new(conversation_id, None, SessionSource::Cli),
+    )
+    .await?;
+    let mut rollout_items = Vec::new();
+    for item in &transcript.items {
+        rollout_items.push(RolloutItem::ResponseItem(item.clone()));
+        // Mirror the events a live turn records so clients can render the
+        // imported history when it is resumed.
+        if let Some(turn_item) = parse_turn_item(item) {
+            rollout_items.extend(
+                turn_item
+                    .as_legacy_events(config.show_raw_agent_reasoning)
+                    .into_iter()
+                    .map(RolloutItem::EventMsg),
+            );
+        }
+    }
+    recorder.record_items(&rollout_items).await?;
+    recorder.flush().await?;
+
+    Ok(ImportReport {
+        conversation_id,
+        rollout_path: recorder.rollout_path.clone(),
+        items: transcript.items.len(),
+        annotations: transcript.annotations,
+    })
+}
+
+#[derive(Debug, Default)]
+struct Transcript {
+    items: Vec<ResponseItem>,
+    annotations: usize,
+}
+
+impl Transcript {
+    fn annotate(&mut self, line_number: usize, reason: &str, raw: &str) {
+        self.items.push(annotation(line_number, reason, raw));
+        self.annotations += 1;
+    }
+}
+
+/// Transcript item preserving an entry that could not be mapped. It is
+/// recorded with the user role, like the environment context, so the model
+/// still sees it, but it is not shown as a user message.
+fn annotation(line_number: usize, reason: &str, raw: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "{IMPORTED_ENTRY_OPEN_TAG}\nLine {line_number} of the imported session was kept verbatim ({reason}):\n{raw}\n{IMPORTED_ENTRY_CLOSE_TAG}"
+            ),
+        }],
+    }
+}
+
+/// Non-blank lines of `text` with their 1-based line numbers.
+fn entries(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn import_chat_messages(text: &str) -> Transcript {
+    let mut transcript = Transcript::default();
+    let mut importer = ChatImporter::default();
+    for (line_number, line) in entries(text) {
+        let message = match serde_json::from_str::<ChatMessage>(line) {
+            Ok(message) => message,
+            Err(err) => {
+                importer.items.push(annotation(
+                    line_number,
+                    &format!("not a chat message: {err}"),
+                    line,
+                ));
+                transcript.annotations += 1;
+                continue;
+            }
+        };
+        let dropped = importer.report.dropped.len();
+        importer.push_message(line_number, message);
+        if let Some(note) = importer.report.dropped.get(dropped) {
+            importer
+                .items
+                .push(annotation(line_number, &note.reason, line));
+            transcript.annotations += 1;
+        }
+    }
+    transcript.items = importer.finish().items;
+    transcript
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClaudeCodeEntry {
+    User { message: ClaudeCodeMessage },
+    Assistant { message: ClaudeCodeMessage },
+}
+
+#[derive(Deserialize)]
+struct ClaudeCodeMessage {
+    content: ClaudeCodeContent,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClaudeCodeContent {
+    Text(String),
+    Blocks(Vec<Value>),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClaudeCodeBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ClaudeCodeImageSource,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        #[serde(default)]
+        content: Option<ClaudeCodeContent>,
+        #[serde(default)]
+        is_error: bool,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClaudeCodeImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+impl ClaudeCodeImageSource {
+    fn into_url(self) -> String {
+        match self {
+            Self::Base64 { media_type, data } => format!("data:{media_type};base64,{data}"),
+            Self::Url { url } => url,
+        }
+    }
+}
+
+/// Accumulates the content of one Claude Code message, flushing it as a
+/// single codex message whenever a non-message item has to be emitted so the
+/// original order is kept.
+struct ClaudeCodeMessageBuilder<'a> {
+    transcript: &'a mut Transcript,
+    role: &'static str,
+    content: Vec<ContentItem>,
+}
+
+impl ClaudeCodeMessageBuilder<'_> {
+    fn push_text(&mut self, text: String) {
+        self.content.push(if self.role == "assistant" {
+            ContentItem::OutputText { text }
+        } else {
+            ContentItem::InputText { text }
+        });
+    }
+
+    fn push_item(&mut self, item: ResponseItem) {
+        self.flush();
+        self.transcript.items.push(item);
+    }
+
+    fn annotate(&mut self, line_number: usize, reason: &str, raw: &str) {
+        self.flush();
+        self.transcript.annotate(line_number, reason, raw);
+    }
+
+    fn flush(&mut self) {
+        if self.content.is_empty() {
+            return;
+        }
+        self.transcript.items.push(ResponseItem::Message {
+            id: None,
+            role: self.role.to_string(),
+            content: std::mem::take(&mut self.content),
+        });
+    }
+}
+
+fn import_claude_code(text: &str) -> Transcript {
+    let mut transcript = Transcript::default();
+    for (line_number, line) in entries(text) {
+        let (role, content) = match serde_json::from_str::<ClaudeCodeEntry>(line) {
+            Ok(ClaudeCodeEntry::User { message }) => ("user", message.content),
+            Ok(ClaudeCodeEntry::Assistant { message }) => ("assistant", message.content),
+            Err(err) => {
+                transcript.annotate(line_number, &format!("unsupported entry: {err}"), line);
+                continue;
+            }
+        };
+        let mut builder = ClaudeCodeMessageBuilder {
+            transcript: &mut transcript,
+            role,
+            content: Vec::new(),
+        };
+        let blocks = match content {
+            ClaudeCodeContent::Text(text) => {
+                builder.push_text(text);
+                Vec::new()
+            }
+            ClaudeCodeContent::Blocks(blocks) => blocks,
+        };
+        for raw in blocks {
+            let block = match serde_json::from_value::<ClaudeCodeBlock>(raw.clone()) {
+                Ok(block) => block,
+                Err(err) => {
+                    builder.annotate(
+                        line_number,
+                        &format!("unsupported content block: {err}"),
+                        &raw.to_string(),
+                    );
+                    continue;
+                }
+            };
+            match block {
+                ClaudeCodeBlock::Text { text } => builder.push_text(text),
+                ClaudeCodeBlock::Image { source } => {
+                    builder.content.push(ContentItem::InputImage {
+                        image_url: source.into_url(),
+                    })
+                }
+                ClaudeCodeBlock::Thinking { thinking } => {
+                    builder.push_item(ResponseItem::Reasoning {
+                        id: String::new(),
+                        summary: Vec::new(),
+                        content: Some(vec![ReasoningItemContent::ReasoningText { text: thinking }]),
+                        encrypted_content: None,
+                    });
+                }
+                ClaudeCodeBlock::ToolUse { id, name, input } => {
+                    builder.push_item(ResponseItem::FunctionCall {
+                        id: None,
+                        name,
+                        arguments: input.to_string(),
+                        call_id: id,
+                    });
+                }
+                ClaudeCodeBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => {
+                    let output = tool_result_output(content, is_error);
+                    builder.push_item(ResponseItem::FunctionCallOutput {
+                        call_id: tool_use_id,
+                        output,
+                    });
+                }
+            }
+        }
+        builder.flush();
+    }
+    transcript
+}
+
+fn tool_result_output(
+    content: Option<ClaudeCodeContent>,
+    is_error: bool,
+) -> FunctionCallOutputPayload {
+    let success = Some(!is_error);
+    let blocks = match content {
+        None => Vec::new(),
+        Some(ClaudeCodeContent::Text(text)) => {
+            return FunctionCallOutputPayload {
+                content: text,
+                content_items: None,
+                success,
+            };
+        }
+        Some(ClaudeCodeContent::Blocks(blocks)) => blocks,
+    };
+    let mut text = String::new();
+    let mut content_items = Vec::new();
+    for block in blocks {
+        match serde_json::from_value::<ClaudeCodeBlock>(block.clone()) {
+            Ok(ClaudeCodeBlock::Text { text: segment }) => {
+                text.push_str(&segment);
+                content_items.push(FunctionCallOutputContentItem::InputText { text: segment });
+            }
+            Ok(ClaudeCodeBlock::Image { source }) => {
+                content_items.push(FunctionCallOutputContentItem::InputImage {
+                    image_url: source.into_url(),
+                });
+            }
+            // Anything else stays readable as its raw JSON.
+            _ => {
+                let segment = block.to_string();
+                text.push_str(&segment);
+                content_items.push(FunctionCallOutputContentItem::InputText { text: segment });
+            }
+        }
+    }
+    let has_images = content_items
+        .iter()
+        .any(|item| matches!(item, FunctionCallOutputContentItem::InputImage { .. }));
+    FunctionCallOutputPayload {
+        content: text,
+        content_items: has_images.then_some(content_items),
+        success,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text_of(item: &ResponseItem) -> Option<&str> {
+        match item {
+            ResponseItem::Message { content, .. } => match content.as_slice() {
+                [ContentItem::InputText { text } | ContentItem::OutputText { text }] => {
+                    Some(text.as_str())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn chat_messages_keep_unmappable_lines_in_place() {
+        let transcript = import_chat_messages(
+            r#"{"role": "user", "content": "hi"}
+not json
+{"role": "narrator", "content": "meanwhile"}
+{"role": "assistant", "content": "hello"}
+"#,
+        );
+
+        assert_eq!(transcript.annotations, 2);
+        assert_eq!(transcript.items.len(), 4);
+        assert_eq!(text_of(&transcript.items[0]), Some("hi"));
+        assert!(text_of(&transcript.items[1]).is_some_and(|text| {
+            text.starts_with(IMPORTED_ENTRY_OPEN_TAG)
+                && text.contains("Line 2")
+                && text.contains("not json")
+        }));
+        assert!(
+            text_of(&transcript.items[2])
+                .is_some_and(|text| text.contains("unsupported role `narrator`"))
+        );
+        assert_eq!(text_of(&transcript.items[3]), Some("hello"));
+    }
+
+    #[test]
+    fn claude_code_blocks_map_to_response_items() {
+        let transcript = import_claude_code(
+            r#"{"type": "summary", "summary": "Listing files", "leafUuid": "abc"}
+{"type": "user", "message": {"role": "user", "content": "list the files"}}
+{"type": "assistant", "message": {"role": "assistant", "content": [{"type": "thinking", "thinking": "Use ls."}, {"type": "text", "text": "Listing."}, {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "ls"}}]}}
+{"type": "user", "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": [{"type": "text", "text": "a.txt"}]}]}}
+{"type": "assistant", "message": {"role": "assistant", "content": [{"type": "redacted_thinking", "data": "opaque"}, {"type": "text", "text": "Just a.txt."}]}}
+"#,
+        );
+
+        assert_eq!(transcript.annotations, 2);
+        let items = transcript.items;
+        assert!(text_of(&items[0]).is_some_and(|text| text.contains("Listing files")));
+        assert_eq!(text_of(&items[1]), Some("list the files"));
+        assert!(matches!(&items[2], ResponseItem::Reasoning { .. }));
+        assert_eq!(text_of(&items[3]), Some("Listing."));
+        assert_eq!(
+            items[4],
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "Bash".to_string(),
+                arguments: r#"{"command":"ls"}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            }
+        );
+        assert_eq!(
+            items[5],
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "a.txt".to_string(),
+                    content_items: None,
+                    success: Some(true),
+                },
+            }
+        );
+        assert!(text_of(&items[6]).is_some_and(|text| text.contains("redacted_thinking")));
+        assert_eq!(text_of(&items[7]), Some("Just a.txt."));
+        assert_eq!(items.len(), 8);
+    }
+}
@@ -0,0 +1,101 @@
+//! The most recent session recorded for a workspace, to continue it.
+//!
+//! Sessions match when they were started in the same project as the
+//! workspace: the same git repository, whatever branch or worktree was
+//! checked out, or the same canonicalized directory outside git. Sessions of
+//! sub-agents and rollouts without a user message are never returned.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::SessionSource;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use super::list::get_conversations;
+use crate::project_approvals::ProjectScope;
+
+/// Rollouts read per listing page while scanning.
+const PAGE_SIZE: usize = 100;
+
+/// A recorded session, as found by [`latest_session_for_cwd`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub conversation_id: ConversationId,
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    /// Working directory the session was started in.
+    pub cwd: PathBuf,
+    /// Git branch checked out when the session started, which can differ
+    /// from the workspace's current one.
+    pub git_branch: Option<String>,
+    /// RFC3339 timestamp of when the session was created.
+    pub created_at: Option<String>,
+    /// RFC3339 timestamp of the last write to the rollout.
+    pub updated_at: Option<String>,
+}
+
+/// The session under `codex_home` whose rollout was written last among those
+/// started in the project containing `cwd`.
+pub(crate) async fn latest_session_for_cwd(
+    codex_home: &Path,
+    cwd: &Path,
+) -> io::Result<Option<SessionSummary>> {
+    let project = ProjectScope::for_cwd(cwd);
+    // Sessions often share a cwd; resolve each project once.
+    let mut in_project: HashMap<PathBuf, bool> = HashMap::new();
+    let mut latest: Option<(OffsetDateTime, SessionSummary)> = None;
+    let mut cursor = None;
+
+    loop {
+        let page = get_conversations(codex_home, PAGE_SIZE, cursor.as_ref(), &[], None, "").await?;
+        for item in page.items {
+            let Some(SessionMetaLine { meta, git }) = item
+                .head
+                .first()
+                .and_then(|head| serde_json::from_value(head.clone()).ok())
+            else {
+                continue;
+            };
+            if matches!(meta.source, SessionSource::SubAgent(_)) {
+                continue;
+            }
+            let matches = *in_project
+                .entry(meta.cwd.clone())
+                .or_insert_with(|| ProjectScope::for_cwd(&meta.cwd) == project);
+            if !matches {
+                continue;
+            }
+            let updated = item
+                .updated_at
+                .as_deref()
+                .and_then(|timestamp| OffsetDateTime::parse(timestamp, &Rfc3339).ok())
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+            // Pages are ordered newest first, so ties go to the newer session.
+            if latest.as_ref().is_some_and(|(best, _)| *best >= updated) {
+                continue;
+            }
+            latest = Some((
+                updated,
+                SessionSummary {
+                    conversation_id: meta.id,
+                    path: item.path,
+                    cwd: meta.cwd,
+                    git_branch: git.and_then(|git| git.branch),
+                    created_at: item.created_at,
+                    updated_at: item.updated_at,
+                },
+            ));
+        }
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(latest.map(|(_, summary)| summary))
+}
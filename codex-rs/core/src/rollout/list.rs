@@ -13,7 +13,10 @@ use time::format_description::well_known::Rfc3339;
 use time::macros::format_description;
 use uuid::Uuid;
 
-use super::SESSIONS_SUBDIR;
+use super::chain::PART_INFIX;
+use super::chunked::ChunkAssembler;
+use super::event_log::EVENT_LOG_SUFFIX;
+use crate::codex_home::CodexHome;
 use crate::protocol::EventMsg;
 use codex_file_search as file_search;
 use codex_protocol::protocol::RolloutItem;
@@ -110,8 +113,7 @@ pub(crate) async fn get_conversations(
     model_providers: Option<&[String]>,
     default_provider: &str,
 ) -> io::Result<ConversationsPage> {
-    let mut root = codex_home.to_path_buf();
-    root.push(SESSIONS_SUBDIR);
+    let root = CodexHome::new(codex_home).sessions_dir();
 
     if !root.exists() {
         return Ok(ConversationsPage {
@@ -383,6 +385,7 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
     let reader = tokio::io::BufReader::new(file);
     let mut lines = reader.lines();
     let mut summary = HeadTailSummary::default();
+    let mut chunks = ChunkAssembler::default();
 
     while summary.head.len() < head_limit {
         let line_opt = lines.next_line().await?;
@@ -394,6 +397,9 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
 
         let parsed: Result<RolloutLine, _> = serde_json::from_str(trimmed);
         let Ok(rollout_line) = parsed else { continue };
+        let Some(rollout_line) = chunks.push(rollout_line) else {
+            continue;
+        };
 
         match rollout_line.item {
             RolloutItem::SessionMeta(session_meta_line) => {
@@ -420,7 +426,25 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
             RolloutItem::TurnContext(_) => {
                 // Not included in `head`; skip.
             }
-            RolloutItem::Compacted(_) => {
+            RolloutItem::Compacted(_)
+            | RolloutItem::Checkpoint(_)
+            | RolloutItem::CheckpointRollback(_)
+            | RolloutItem::PendingInput(_)
+            | RolloutItem::InstructionsReplaced(_)
+            | RolloutItem::ReadReference(_)
+            | RolloutItem::InputRejected(_)
+            | RolloutItem::ItemPinned(_)
+            | RolloutItem::ItemUnpinned(_)
+            | RolloutItem::TranscriptMutation(_)
+            | RolloutItem::GitContext(_)
+            | RolloutItem::RequestFingerprint(_)
+            | RolloutItem::Annotation(_)
+            | RolloutItem::PartialMessage { .. }
+            | RolloutItem::TurnStarted(_)
+            | RolloutItem::ContinuedIn(_)
+            | RolloutItem::ContinuedFrom(_)
+            | RolloutItem::Chunked { .. }
+            | RolloutItem::Unknown { .. } => {
                 // Not included in `head`; skip.
             }
             RolloutItem::EventMsg(ev) => {
@@ -445,7 +469,7 @@ pub async fn read_head_for_summary(path: &Path) -> io::Result<Vec<serde_json::Va
     Ok(summary.head)
 }
 
-async fn file_modified_rfc3339(path: &Path) -> io::Result<Option<String>> {
+pub(crate) async fn file_modified_rfc3339(path: &Path) -> io::Result<Option<String>> {
     let meta = tokio::fs::metadata(path).await?;
     let modified = meta.modified().ok();
     let Some(modified) = modified else {
@@ -467,8 +491,7 @@ pub async fn find_conversation_path_by_id_str(
         return Ok(None);
     }
 
-    let mut root = codex_home.to_path_buf();
-    root.push(SESSIONS_SUBDIR);
+    let root = CodexHome::new(codex_home).sessions_dir();
     if !root.exists() {
         return Ok(None);
     }
@@ -479,7 +502,12 @@ pub async fn find_conversation_path_by_id_str(
     #[allow(clippy::unwrap_used)]
     let threads = NonZero::new(2).unwrap();
     let cancel = Arc::new(AtomicBool::new(false));
-    let exclude: Vec<String> = Vec::new();
+    // Event logs and later parts share the rollout's file name stem; only
+    // match the first part, which names the rollout.
+    let exclude: Vec<String> = vec![
+        format!("*{EVENT_LOG_SUFFIX}"),
+        format!("*{PART_INFIX}*.jsonl"),
+    ];
     let compute_indices = false;
 
     let results = file_search::run(
@@ -0,0 +1,123 @@
+//! Consistent snapshots of a rollout that is still being written.
+//!
+//! A running session appends to its rollout while a turn streams, so reading
+//! the file directly can observe half of a turn (or half of a line). The
+//! session marks where each turn starts; a snapshot taken while a turn is in
+//! flight stops at that mark, i.e. at the end of the last completed turn.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::RolloutItem;
+use tokio::sync::watch;
+
+use crate::rollout::RolloutParseMode;
+use crate::rollout::RolloutRecorder;
+use crate::rollout::chain::chain_len;
+use crate::rollout::chain::read_chain;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TurnBoundary {
+    Idle,
+    /// A turn is running; `completed_bytes` is the length of the rollout, all
+    /// its parts together, when it started.
+    InFlight {
+        completed_bytes: u64,
+    },
+}
+
+/// Handle shared between a session and its conversation.
+#[derive(Clone)]
+pub(crate) struct LiveRollout {
+    recorder: RolloutRecorder,
+    boundary: Arc<watch::Sender<TurnBoundary>>,
+}
+
+impl LiveRollout {
+    pub(crate) fn new(recorder: RolloutRecorder) -> Self {
+        let (boundary, _) = watch::channel(TurnBoundary::Idle);
+        Self {
+            recorder,
+            boundary: Arc::new(boundary),
+        }
+    }
+
+    pub(crate) fn rollout_path(&self) -> &Path {
+        &self.recorder.rollout_path
+    }
+
+    /// Must be called before the turn records anything.
+    pub(crate) async fn turn_started(&self) -> std::io::Result<()> {
+        self.recorder.flush_written().await?;
+        let completed_bytes = chain_len(self.rollout_path()).await?;
+        self.boundary
+            .send_replace(TurnBoundary::InFlight { completed_bytes });
+        Ok(())
+    }
+
+    pub(crate) fn turn_finished(&self) {
+        self.boundary.send_replace(TurnBoundary::Idle);
+        self.recorder.turn_ended();
+    }
+
+    /// Wait until everything recorded so far is written to the rollout and
+    /// synced to disk.
+    pub(crate) async fn flush(&self) -> std::io::Result<()> {
+        self.recorder.flush().await
+    }
+
+    /// Record `items` on behalf of a client and wait until they are written.
+    pub(crate) async fn record(&self, items: &[RolloutItem]) -> std::io::Result<()> {
+        self.recorder.record_items(items).await?;
+        self.recorder.flush().await
+    }
+
+    /// Read the rollout up to the end of the last completed turn. With
+    /// `wait_for_turn`, wait for a running turn to finish first so it is
+    /// included.
+    pub(crate) async fn snapshot(&self, wait_for_turn: bool) -> std::io::Result<InitialHistory> {
+        if wait_for_turn {
+            let mut boundary = self.boundary.subscribe();
+            // The sender lives as long as `self`, so this cannot fail.
+            let _ = boundary
+                .wait_for(|boundary| *boundary == TurnBoundary::Idle)
+                .await;
+        }
+        self.recorder.flush().await?;
+        let bytes = read_chain(self.rollout_path()).await?;
+        // Read the boundary after the file so a turn that starts meanwhile
+        // still cuts off anything it wrote.
+        let end = match *self.boundary.borrow() {
+            TurnBoundary::InFlight { completed_bytes } => {
+                usize::try_from(completed_bytes).unwrap_or(usize::MAX)
+            }
+            TurnBoundary::Idle => bytes.len(),
+        };
+        let text = String::from_utf8_lossy(complete_lines(&bytes, end));
+        RolloutRecorder::parse_rollout_history(&text, self.rollout_path(), RolloutParseMode::Lossy)
+    }
+}
+
+/// The prefix of `bytes` up to `end` that ends on a line boundary.
+fn complete_lines(bytes: &[u8], end: usize) -> &[u8] {
+    let prefix = &bytes[..end.min(bytes.len())];
+    match prefix.iter().rposition(|b| *b == b'\n') {
+        Some(last_newline) => &prefix[..=last_newline],
+        None => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn complete_lines_drops_partial_trailing_line() {
+        assert_eq!(complete_lines(b"a\nb\nc", 5), b"a\nb\n");
+        assert_eq!(complete_lines(b"a\nb\nc", 3), b"a\n");
+        assert_eq!(complete_lines(b"a\nb\n", 100), b"a\nb\n");
+        assert_eq!(complete_lines(b"partial", 7), b"");
+    }
+}
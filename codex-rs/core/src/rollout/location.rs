@@ -0,0 +1,212 @@
+//! Rollout locations that survive moving codex home, e.g. when it is synced
+//! between machines or users.
+//!
+//! Rollouts never record their own location: conversations are found by id
+//! by scanning the current codex home, and `SessionConfiguredEvent`'s
+//! `rollout_path` is derived when a session starts. Files that do persist a
+//! rollout location (the event log's copy of `SessionConfigured`) store it
+//! relative to codex home and resolve it against the current one when read.
+//! [`relocate`] rewrites absolute locations recorded by older builds.
+
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+
+/// Key under which persisted records store a rollout location.
+const ROLLOUT_PATH_KEY: &str = "rollout_path";
+
+/// Form of `path` to persist: relative to `codex_home` when it lives there.
+pub fn to_stored(codex_home: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(codex_home)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Absolute location of a persisted rollout path under `codex_home`.
+///
+/// Relative paths are joined onto `codex_home`. Absolute paths recorded under
+/// a different codex home are re-anchored at their `sessions` (or
+/// `archived_sessions`) directory; paths outside any sessions directory are
+/// not owned by codex home and are returned unchanged.
+pub fn resolve(codex_home: &Path, stored: &Path) -> PathBuf {
+    if stored.is_relative() {
+        return codex_home.join(stored);
+    }
+    if stored.starts_with(codex_home) {
+        return stored.to_path_buf();
+    }
+    let components: Vec<Component> = stored.components().collect();
+    match components.iter().rposition(|component| {
+        let name = component.as_os_str();
+        name == SESSIONS_SUBDIR || name == ARCHIVED_SESSIONS_SUBDIR
+    }) {
+        Some(index) => codex_home.join(components[index..].iter().collect::<PathBuf>()),
+        None => stored.to_path_buf(),
+    }
+}
+
+/// Files and paths changed by [`relocate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelocateReport {
+    pub files_rewritten: usize,
+    pub paths_rewritten: usize,
+}
+
+/// Rewrite absolute rollout locations persisted under `codex_home` into the
+/// relative form, re-anchoring ones recorded under a previous codex home.
+/// Safe to run repeatedly; lines that do not parse are kept as they are.
+pub async fn relocate(codex_home: &Path) -> io::Result<RelocateReport> {
+    let mut report = RelocateReport::default();
+    for subdir in [SESSIONS_SUBDIR, ARCHIVED_SESSIONS_SUBDIR] {
+        let mut pending = vec![codex_home.join(subdir)];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    let rewritten = relocate_file(codex_home, &path).await?;
+                    if rewritten > 0 {
+                        report.files_rewritten += 1;
+                        report.paths_rewritten += rewritten;
+                    }
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+async fn relocate_file(codex_home: &Path, path: &Path) -> io::Result<usize> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let mut rewritten = 0;
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => {
+                let count = relocate_value(codex_home, &mut value);
+                if count > 0 {
+                    rewritten += count;
+                    out.push_str(&serde_json::to_string(&value)?);
+                } else {
+                    out.push_str(line);
+                }
+            }
+            Err(_) => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    if rewritten > 0 {
+        tokio::fs::write(path, out).await?;
+    }
+    Ok(rewritten)
+}
+
+fn relocate_value(codex_home: &Path, value: &mut Value) -> usize {
+    match value {
+        Value::Object(object) => object
+            .iter_mut()
+            .map(|(key, value)| match value {
+                Value::String(stored) if key == ROLLOUT_PATH_KEY => {
+                    let relocated = to_stored(codex_home, &resolve(codex_home, Path::new(stored)));
+                    match relocated.to_str() {
+                        Some(relocated) if relocated != stored => {
+                            *stored = relocated.to_string();
+                            1
+                        }
+                        _ => 0,
+                    }
+                }
+                value => relocate_value(codex_home, value),
+            })
+            .sum(),
+        Value::Array(items) => items
+            .iter_mut()
+            .map(|item| relocate_value(codex_home, item))
+            .sum(),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn stored_paths_round_trip_through_another_home() {
+        let old_home = Path::new("/home/alice/.codex");
+        let new_home = Path::new("/Users/bob/Dropbox/codex");
+        let rollout = old_home.join("sessions/2025/01/02/rollout-x.jsonl");
+
+        let stored = to_stored(old_home, &rollout);
+        assert_eq!(stored, PathBuf::from("sessions/2025/01/02/rollout-x.jsonl"));
+        assert_eq!(
+            resolve(new_home, &stored),
+            new_home.join("sessions/2025/01/02/rollout-x.jsonl")
+        );
+        // Absolute paths from older builds are re-anchored too.
+        assert_eq!(
+            resolve(new_home, &rollout),
+            new_home.join("sessions/2025/01/02/rollout-x.jsonl")
+        );
+        // Paths that never belonged to a codex home are left alone.
+        assert_eq!(
+            resolve(new_home, Path::new("/tmp/rollout-x.jsonl")),
+            PathBuf::from("/tmp/rollout-x.jsonl")
+        );
+    }
+
+    #[tokio::test]
+    async fn relocate_rewrites_absolute_paths_once() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let day = home.path().join("sessions/2025/01/02");
+        tokio::fs::create_dir_all(&day).await?;
+        let log = day.join("rollout-x.events.jsonl");
+        let absolute = "/home/alice/.codex/sessions/2025/01/02/rollout-x.jsonl";
+        tokio::fs::write(
+            &log,
+            format!(
+                "{{\"event\":{{\"msg\":{{\"type\":\"session_configured\",\"rollout_path\":\"{absolute}\"}}}}}}\nnot json\n"
+            ),
+        )
+        .await?;
+
+        assert_eq!(
+            relocate(home.path()).await?,
+            RelocateReport {
+                files_rewritten: 1,
+                paths_rewritten: 1,
+            }
+        );
+        let text = tokio::fs::read_to_string(&log).await?;
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            serde_json::from_str::<Value>(lines[0])?,
+            json!({
+                "event": {
+                    "msg": {
+                        "type": "session_configured",
+                        "rollout_path": "sessions/2025/01/02/rollout-x.jsonl",
+                    }
+                }
+            })
+        );
+        assert_eq!(lines[1], "not json");
+        assert_eq!(relocate(home.path()).await?, RelocateReport::default());
+        Ok(())
+    }
+}
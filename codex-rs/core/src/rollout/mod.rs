@@ -7,16 +7,58 @@ pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 pub const INTERACTIVE_SESSION_SOURCES: &[SessionSource] =
     &[SessionSource::Cli, SessionSource::VSCode];
 
+pub mod anonymize;
+pub(crate) mod chain;
+pub(crate) mod chunked;
+pub mod convert;
+pub mod delete;
 pub(crate) mod error;
+pub mod event_log;
+pub mod fork_points;
+pub mod forks;
+pub mod import;
+pub mod latest;
 pub mod list;
+pub(crate) mod live;
+pub mod location;
 pub(crate) mod policy;
+pub mod prompt_script;
+pub mod read_only;
+pub mod reconstruct;
 pub mod recorder;
+pub(crate) mod resumed_summary;
+pub(crate) mod sanitize;
+pub(crate) mod transcript;
+pub(crate) mod writer;
 
+pub use anonymize::AnonymizeOptions;
+pub use anonymize::AnonymizeReport;
+pub use anonymize::anonymize;
+pub use chain::rollout_parts;
 pub use codex_protocol::protocol::SessionMeta;
+pub use delete::DeletionReport;
 pub(crate) use error::map_session_init_error;
+pub use event_log::EventLogPolicy;
+pub use event_log::event_log_path;
+pub use event_log::read_event_log;
+pub use fork_points::ForkPoint;
+pub use fork_points::fork_points;
 pub use list::find_conversation_path_by_id_str;
+pub use location::RelocateReport;
+pub use location::relocate;
+pub use prompt_script::PromptScript;
+pub use prompt_script::ScriptPrompt;
+pub use prompt_script::extract_prompt_script;
+pub use read_only::ReadOnlyTranscript;
+pub use reconstruct::ReconstructedRequest;
+pub use reconstruct::RequestMismatch;
+pub use reconstruct::reconstruct_request;
+pub use recorder::RolloutLogFile;
 pub use recorder::RolloutRecorder;
+pub use recorder::RolloutParseMode;
 pub use recorder::RolloutRecorderParams;
+pub use transcript::TranscriptTurn;
+pub use writer::RolloutFsyncPolicy;
 
 #[cfg(test)]
 pub mod tests;
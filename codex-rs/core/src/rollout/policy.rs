@@ -9,9 +9,28 @@ pub(crate) fn is_persisted_response_item(item: &RolloutItem) -> bool {
         RolloutItem::ResponseItem(item) => should_persist_response_item(item),
         RolloutItem::EventMsg(ev) => should_persist_event_msg(ev),
         // Persist Codex executive markers so we can analyze flows (e.g., compaction, API turns).
-        RolloutItem::Compacted(_) | RolloutItem::TurnContext(_) | RolloutItem::SessionMeta(_) => {
-            true
-        }
+        RolloutItem::Compacted(_)
+        | RolloutItem::TurnContext(_)
+        | RolloutItem::SessionMeta(_)
+        | RolloutItem::Checkpoint(_)
+        | RolloutItem::CheckpointRollback(_)
+        | RolloutItem::PendingInput(_)
+        | RolloutItem::InstructionsReplaced(_)
+        | RolloutItem::ReadReference(_)
+        | RolloutItem::InputRejected(_)
+        | RolloutItem::ItemPinned(_)
+        | RolloutItem::ItemUnpinned(_)
+        | RolloutItem::TranscriptMutation(_)
+        | RolloutItem::GitContext(_)
+        | RolloutItem::RequestFingerprint(_)
+        | RolloutItem::Annotation(_)
+        | RolloutItem::PartialMessage { .. }
+        | RolloutItem::TurnStarted(_)
+        | RolloutItem::ContinuedIn(_)
+        | RolloutItem::ContinuedFrom(_)
+        | RolloutItem::Chunked { .. } => true,
+        // Written by a newer version; keep it so that version can read it back.
+        RolloutItem::Unknown { .. } => true,
     }
 }
 
@@ -46,6 +65,25 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::EnteredReviewMode(_)
         | EventMsg::ExitedReviewMode(_)
         | EventMsg::UndoCompleted(_)
+        | EventMsg::InterruptedTurnRecovered(_)
+        | EventMsg::EffortEscalated(_)
+        | EventMsg::EffortDeescalated(_)
+        | EventMsg::SkillUsage(_)
+        | EventMsg::PersistenceRecovered(_)
+        | EventMsg::ForkedContextStale(_)
+        | EventMsg::SafeModeChanged(_)
+        | EventMsg::ContextRecovered(_)
+        | EventMsg::ConversationPaused(_)
+        | EventMsg::ConversationResumed
+        | EventMsg::TurnDeadlineExceeded(_)
+        | EventMsg::ToolsDowngraded(_)
+        | EventMsg::SessionEnded(_)
+        | EventMsg::TurnDiffSummary(_)
+        | EventMsg::ToolPolicyUpdated(_)
+        | EventMsg::StructuredOutput(_)
+        | EventMsg::ConversationLinked(_)
+        | EventMsg::TurnRejected(_)
+        | EventMsg::WorkspaceChanged(_)
         | EventMsg::TurnAborted(_) => true,
         EventMsg::Error(_)
         | EventMsg::Warning(_)
@@ -89,6 +127,28 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::AgentMessageContentDelta(_)
         | EventMsg::ReasoningContentDelta(_)
         | EventMsg::ReasoningRawContentDelta(_)
-        | EventMsg::SkillsUpdateAvailable => false,
+        | EventMsg::Retrying(_)
+        | EventMsg::PersistenceDegraded(_)
+        | EventMsg::ModelDeprecated(_)
+        | EventMsg::QueuedOffline(_)
+        | EventMsg::SkillToolCall(_)
+        | EventMsg::Forked(_)
+        | EventMsg::HistoryTrimmedOnResume(_)
+        | EventMsg::ItemPinned(_)
+        | EventMsg::ItemUnpinned(_)
+        | EventMsg::ToolTimedOut(_)
+        | EventMsg::AuthRefreshFailed(_)
+        | EventMsg::InstructionsReloaded(_)
+        | EventMsg::ModelListRefreshed(_)
+        | EventMsg::PendingInputsRestored(_)
+        | EventMsg::SkillsSelected(_)
+        | EventMsg::InputRejected(_)
+        | EventMsg::ContextUsageUpdated(_)
+        | EventMsg::PlannedToolCall(_)
+        | EventMsg::Heartbeat(_)
+        | EventMsg::ProviderEndpointSwitched(_)
+        | EventMsg::SkillsUpdateAvailable
+        | EventMsg::CheckpointCreated(_)
+        | EventMsg::CheckpointRolledBack(_) => false,
     }
 }
@@ -0,0 +1,77 @@
+//! The user prompts of a recorded session, to run again against another
+//! model or config.
+//!
+//! A [`PromptScript`] keeps the user messages of a rollout in the order they
+//! were recorded, with their images, and leaves everything the model and
+//! the tools answered behind. Messages are found as forks find them, see
+//! [`super::fork_points`], so step `n` of a script is fork point `n` of the
+//! rollout. Run a script with `ConversationManager::run_script`.
+
+use std::io;
+use std::path::Path;
+
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TurnId;
+use codex_protocol::user_input::UserInput;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::chain::ChainLines;
+use super::chunked::ChunkAssembler;
+use crate::history_truncation::user_message_at;
+
+/// The user prompts of a rollout, in order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptScript {
+    pub prompts: Vec<ScriptPrompt>,
+}
+
+/// One user message of a recorded session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptPrompt {
+    /// Position of the message among the user messages of the rollout.
+    pub ordinal: usize,
+    /// The turn the message started in the recorded session. `None` for
+    /// messages added to a running turn and for rollouts recorded before
+    /// turns had ids.
+    #[serde(default)]
+    pub turn_id: Option<TurnId>,
+    /// The text and images of the message, as submitted again.
+    pub items: Vec<UserInput>,
+}
+
+/// Read the user prompts of the rollout at `path`, line by line.
+pub async fn extract_prompt_script(path: &Path) -> io::Result<PromptScript> {
+    let mut lines = ChainLines::open(path).await?;
+    let mut chunks = ChunkAssembler::default();
+    let mut prompts = Vec::new();
+    // The turn whose first user message is still ahead.
+    let mut starting_turn = None;
+
+    while let Some(line) = lines.next_line().await? {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(trimmed) else {
+            continue;
+        };
+        let Some(rollout_line) = chunks.push(rollout_line) else {
+            continue;
+        };
+        if let RolloutItem::TurnStarted(started) = &rollout_line.item {
+            starting_turn = Some(started.turn_id);
+        }
+        if let Some(message) = user_message_at(&rollout_line.item) {
+            prompts.push(ScriptPrompt {
+                ordinal: prompts.len(),
+                turn_id: starting_turn.take(),
+                items: message.content,
+            });
+        }
+    }
+    chunks.finish();
+
+    Ok(PromptScript { prompts })
+}
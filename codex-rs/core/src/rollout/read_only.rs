@@ -0,0 +1,237 @@
+//! A rollout opened for inspection only.
+//!
+//! Browsing a past session should not need credentials or a model provider,
+//! so a [`ReadOnlyTranscript`] only reads the rollout: it never spawns a
+//! session and has no way to submit ops. Upgrading it to a live session goes
+//! through the regular resume path, see
+//! `ConversationManager::resume_read_only`. The one thing it writes is
+//! the annotation of a handoff, see [`ReadOnlyTranscript::generate_handoff`].
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::DateTime;
+use codex_app_server_protocol::Turn;
+use codex_app_server_protocol::TurnStatus;
+use codex_app_server_protocol::build_turns_from_event_msgs;
+use codex_protocol::ConversationId;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::HandoffDocument;
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutParseError;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::SessionStats;
+use codex_protocol::protocol::TokenUsage;
+use serde::Deserialize;
+
+use super::AnonymizeOptions;
+use super::AnonymizeReport;
+use super::ForkPoint;
+use super::RolloutParseMode;
+use super::RolloutRecorder;
+use super::TranscriptTurn;
+use super::anonymize;
+use super::chain::read_chain_to_string;
+use super::fork_points;
+use super::transcript::read_transcript;
+use super::transcript::read_transcript_turns;
+use crate::AuthManager;
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::handoff::HandoffOptions;
+use crate::handoff::generate_handoff;
+use crate::handoff::handoff_model;
+use crate::handoff::record_handoff;
+
+/// The history of a rollout, loaded without a session.
+#[derive(Debug, Clone)]
+pub struct ReadOnlyTranscript {
+    rollout_path: PathBuf,
+    history: InitialHistory,
+    fork_points: Vec<ForkPoint>,
+    /// Timestamps of the first and last lines of the rollout.
+    span: Option<(String, String)>,
+}
+
+/// The part of a rollout line [`ReadOnlyTranscript::open`] reads to time the
+/// session, so lines of a newer version still count.
+#[derive(Deserialize)]
+struct Timestamped {
+    timestamp: String,
+}
+
+impl ReadOnlyTranscript {
+    /// Load the rollout at `path`. Unparsable lines are skipped and listed in
+    /// [`Self::parse_errors`], as they are when resuming.
+    pub async fn open(path: &Path) -> io::Result<Self> {
+        let text = read_chain_to_string(path).await?;
+        let history = RolloutRecorder::parse_rollout_history(&text, path, RolloutParseMode::Lossy)?;
+        let mut timestamps = text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Timestamped>(line).ok())
+            .map(|line| line.timestamp);
+        let span = timestamps
+            .next()
+            .map(|first| (first.clone(), timestamps.next_back().unwrap_or(first)));
+        Ok(Self {
+            rollout_path: path.to_path_buf(),
+            history,
+            fork_points: fork_points(path).await?,
+            span,
+        })
+    }
+
+    pub fn rollout_path(&self) -> &Path {
+        &self.rollout_path
+    }
+
+    /// The id of the recorded conversation, from its session meta.
+    pub fn conversation_id(&self) -> Option<ConversationId> {
+        match &self.history {
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                Some(resumed.conversation_id)
+            }
+            InitialHistory::New | InitialHistory::Forked(_) => None,
+        }
+    }
+
+    pub fn session_meta(&self) -> Option<SessionMeta> {
+        self.items().into_iter().find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta),
+            _ => None,
+        })
+    }
+
+    /// The history a resumed session would start from.
+    pub fn history(&self) -> &InitialHistory {
+        &self.history
+    }
+
+    pub fn items(&self) -> Vec<RolloutItem> {
+        self.history.get_rollout_items()
+    }
+
+    pub fn parse_errors(&self) -> &[RolloutParseError] {
+        self.history.parse_errors()
+    }
+
+    /// The recorded turns, as the app server lists them for a resumed thread.
+    pub fn turns(&self) -> Vec<Turn> {
+        build_turns_from_event_msgs(&self.history.get_event_msgs().unwrap_or_default())
+    }
+
+    pub fn fork_points(&self) -> &[ForkPoint] {
+        &self.fork_points
+    }
+
+    /// Totals of the recorded session. The duration runs from the first to
+    /// the last line of the rollout.
+    pub fn stats(&self) -> SessionStats {
+        let turns = self
+            .turns()
+            .iter()
+            .filter(|turn| turn.status == TurnStatus::Completed)
+            .count();
+        let mut token_usage = TokenUsage::default();
+        let mut files_changed = BTreeSet::new();
+        for event in self.history.get_event_msgs().unwrap_or_default() {
+            match event {
+                // Token counts are cumulative.
+                EventMsg::TokenCount(token_count) => {
+                    if let Some(info) = token_count.info {
+                        token_usage = info.total_token_usage;
+                    }
+                }
+                EventMsg::TurnDiffSummary(summary) => {
+                    files_changed.extend(summary.files.into_iter().map(|file| file.path));
+                }
+                _ => {}
+            }
+        }
+        SessionStats {
+            turns: u64::try_from(turns).unwrap_or(u64::MAX),
+            token_usage,
+            duration_ms: self.duration_ms(),
+            files_changed: files_changed.into_iter().collect(),
+            rollout_path: Some(self.rollout_path.clone()),
+        }
+    }
+
+    fn duration_ms(&self) -> u64 {
+        let Some((first, last)) = &self.span else {
+            return 0;
+        };
+        let (Ok(first), Ok(last)) = (
+            DateTime::parse_from_rfc3339(first),
+            DateTime::parse_from_rfc3339(last),
+        ) else {
+            return 0;
+        };
+        u64::try_from((last - first).num_milliseconds()).unwrap_or(0)
+    }
+
+    /// Everything said in the session, read back from the rollout, including
+    /// what compaction summarized away.
+    pub async fn transcript(&self) -> io::Result<Vec<ResponseItem>> {
+        read_transcript(&self.rollout_path).await
+    }
+
+    /// [`Self::transcript`], grouped by the user turn that recorded each
+    /// item.
+    pub async fn transcript_turns(&self) -> io::Result<Vec<TranscriptTurn>> {
+        read_transcript_turns(&self.rollout_path).await
+    }
+
+    /// Summarize the session for a human taking it over, with the caller's
+    /// `config` and credentials, and record the summary in the rollout. The
+    /// model defaults to the one of the last recorded turn, see
+    /// [`crate::handoff`]. The annotation is in [`Self::items`] once the
+    /// rollout is opened again.
+    pub async fn generate_handoff(
+        &self,
+        config: &Config,
+        auth_manager: Arc<AuthManager>,
+        options: HandoffOptions,
+    ) -> CodexResult<HandoffDocument> {
+        let Some(conversation_id) = self.conversation_id() else {
+            return Err(CodexErr::InvalidRequest(format!(
+                "{} has no session meta",
+                self.rollout_path.display()
+            )));
+        };
+        let recorded_model = self.items().into_iter().rev().find_map(|item| match item {
+            RolloutItem::TurnContext(context) => Some(context.model),
+            _ => None,
+        });
+        let model = handoff_model(
+            &options,
+            config,
+            recorded_model.as_deref().or(config.model.as_deref()),
+        )?;
+        let document = generate_handoff(
+            config,
+            auth_manager,
+            conversation_id,
+            &self.rollout_path,
+            model,
+        )
+        .await?;
+        record_handoff(config, &self.rollout_path, &document).await?;
+        Ok(document)
+    }
+
+    /// Write an anonymized copy of the rollout to `dest`.
+    pub async fn anonymize(
+        &self,
+        dest: &Path,
+        options: AnonymizeOptions,
+    ) -> io::Result<AnonymizeReport> {
+        anonymize(&self.rollout_path, dest, options).await
+    }
+}
@@ -0,0 +1,414 @@
+//! Rebuilding the request a recorded turn sent to the model, see
+//! [`reconstruct_request`].
+//!
+//! Every Responses API request is recorded as a [`RequestFingerprint`]: the
+//! hashes of its instructions, tool roster and input items, and its other
+//! parameters verbatim. The instructions and tool roster are kept the first
+//! time the rollout sends them. The input is not kept at all: it is rebuilt
+//! by replaying the history recorded before the fingerprint, the way a
+//! resumed session does, and each item is checked against its hash. An item
+//! that does not match means something the rollout does not record shaped
+//! the prompt.
+//!
+//! Requests sent over the Chat Completions API are not fingerprinted.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RequestFingerprint;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::TurnId;
+use serde_json::Map;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+
+use super::recorder::RolloutRecorder;
+use crate::client_common::reserialize_shell_outputs;
+use crate::compact::build_compacted_history;
+use crate::compact::collect_user_messages;
+use crate::context_manager::ContextManager;
+use crate::event_mapping::parse_turn_item;
+use crate::state::Checkpoints;
+use crate::truncate::TruncationPolicy;
+
+/// Fields of the request body recorded as segments rather than verbatim.
+const INSTRUCTIONS: &str = "instructions";
+const TOOLS: &str = "tools";
+const INPUT: &str = "input";
+
+/// Longest piece of an input item quoted in a [`RequestMismatch`].
+const SNIPPET_CHARS: usize = 200;
+
+/// The request a turn sent to the model, rebuilt from its rollout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconstructedRequest {
+    pub turn_id: TurnId,
+    pub fingerprint: RequestFingerprint,
+    /// The request body, as sent.
+    pub body: Value,
+}
+
+/// Why the request of a turn could not be rebuilt: what the rollout records
+/// does not produce what the fingerprint says was sent. Carried by the
+/// [`io::ErrorKind::InvalidData`] error [`reconstruct_request`] returns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestMismatch {
+    pub turn_id: TurnId,
+    /// One line per segment that differs.
+    pub diff: Vec<String>,
+}
+
+impl fmt::Display for RequestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the request of turn {} does not match its fingerprint:",
+            self.turn_id
+        )?;
+        for line in &self.diff {
+            write!(f, "\n  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RequestMismatch {}
+
+/// Rebuild the last request turn `turn_id` of the rollout at `path` sent to
+/// the model: the one its final answer came from.
+pub async fn reconstruct_request(path: &Path, turn_id: TurnId) -> io::Result<ReconstructedRequest> {
+    let items = RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items();
+    let mut replay = Replay::default();
+    let mut segments = BTreeMap::new();
+    let mut current_turn = None;
+    let mut found = None;
+    for item in &items {
+        match item {
+            RolloutItem::TurnStarted(started) => current_turn = Some(started.turn_id),
+            RolloutItem::RequestFingerprint(fingerprint) => {
+                segments.extend(fingerprint.segments.clone());
+                if current_turn == Some(turn_id) {
+                    found = Some((fingerprint.clone(), replay.history.clone()));
+                }
+            }
+            item => replay.apply(item),
+        }
+    }
+    let Some((fingerprint, mut history)) = found else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "turn {turn_id} of {} has no recorded request",
+                path.display()
+            ),
+        ));
+    };
+
+    let mut diff = Vec::new();
+    let mut segment = |name: &str, hash: &str| match segments.get(hash) {
+        Some(value) => value.clone(),
+        None => {
+            diff.push(format!("{name}: {hash} is not kept in the rollout"));
+            Value::Null
+        }
+    };
+    let instructions = segment(INSTRUCTIONS, &fingerprint.instructions);
+    let tools = segment(TOOLS, &fingerprint.tools);
+    let input = rebuild_input(history.get_history_for_prompt(), &tools, &fingerprint)?;
+    diff.extend(input_diff(&fingerprint.input, &input));
+    if !diff.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            RequestMismatch { turn_id, diff },
+        ));
+    }
+
+    let Value::Object(mut body) = fingerprint.parameters.clone() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the request parameters of turn {turn_id} are not an object"),
+        ));
+    };
+    // Replacing the placeholders keeps the fields in the order they were sent.
+    body.insert(INSTRUCTIONS.to_string(), instructions);
+    body.insert(TOOLS.to_string(), tools);
+    body.insert(INPUT.to_string(), Value::Array(input));
+    Ok(ReconstructedRequest {
+        turn_id,
+        fingerprint,
+        body: Value::Object(body),
+    })
+}
+
+/// Fingerprint the Responses API request `body`, keeping its instructions
+/// and tool roster in `segments`. `None` when the body is not an object.
+pub(crate) fn fingerprint(body: &Value) -> Option<RequestFingerprint> {
+    let Value::Object(fields) = body else {
+        return None;
+    };
+    let mut parameters = Map::new();
+    let mut segments = BTreeMap::new();
+    let mut instructions = String::new();
+    let mut tools = String::new();
+    let mut input = Vec::new();
+    for (name, value) in fields {
+        match name.as_str() {
+            INSTRUCTIONS | TOOLS => {
+                let hash = segment_hash(value);
+                segments.insert(hash.clone(), value.clone());
+                if name == INSTRUCTIONS {
+                    instructions = hash;
+                } else {
+                    tools = hash;
+                }
+            }
+            INPUT => {
+                input = value
+                    .as_array()
+                    .map(|items| items.iter().map(segment_hash).collect())
+                    .unwrap_or_default();
+            }
+            _ => {
+                parameters.insert(name.clone(), value.clone());
+                continue;
+            }
+        }
+        parameters.insert(name.clone(), Value::Null);
+    }
+    let model = fields
+        .get("model")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    Some(RequestFingerprint {
+        model,
+        instructions,
+        tools,
+        input,
+        parameters: Value::Object(parameters),
+        segments,
+    })
+}
+
+/// The segments the fingerprints among `items` keep.
+pub(crate) fn recorded_segments(items: &[RolloutItem]) -> HashSet<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::RequestFingerprint(fingerprint) => Some(fingerprint.segments.keys()),
+            _ => None,
+        })
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// The first 16 hex digits of the sha256 of `value`'s JSON encoding.
+fn segment_hash(value: &Value) -> String {
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    format!("{digest:x}")[..16].to_string()
+}
+
+/// The input items as the request encoded them.
+fn rebuild_input(
+    mut items: Vec<ResponseItem>,
+    tools: &Value,
+    fingerprint: &RequestFingerprint,
+) -> io::Result<Vec<Value>> {
+    let freeform_apply_patch = tools.as_array().is_some_and(|tools| {
+        tools
+            .iter()
+            .any(|tool| tool["type"] == "custom" && tool["name"] == "apply_patch")
+    });
+    if freeform_apply_patch {
+        reserialize_shell_outputs(&mut items);
+    }
+    let mut encoded = items
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    // Stored requests name the items they send, see `attach_item_ids` in
+    // codex-api.
+    if fingerprint.parameters["store"] == true {
+        for (value, item) in encoded.iter_mut().zip(&items) {
+            if let Some(id) = item_id(item)
+                && let Some(fields) = value.as_object_mut()
+            {
+                fields.insert("id".to_string(), Value::String(id.to_string()));
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+fn item_id(item: &ResponseItem) -> Option<&str> {
+    let id = match item {
+        ResponseItem::Reasoning { id, .. } => Some(id.as_str()),
+        ResponseItem::Message { id: Some(id), .. }
+        | ResponseItem::WebSearchCall { id: Some(id), .. }
+        | ResponseItem::FunctionCall { id: Some(id), .. }
+        | ResponseItem::LocalShellCall { id: Some(id), .. }
+        | ResponseItem::CustomToolCall { id: Some(id), .. } => Some(id.as_str()),
+        _ => None,
+    };
+    id.filter(|id| !id.is_empty())
+}
+
+/// One line per input item whose hash differs from the one `sent`.
+fn input_diff(sent: &[String], rebuilt: &[Value]) -> Vec<String> {
+    let mut diff = Vec::new();
+    for index in 0..sent.len().max(rebuilt.len()) {
+        let rebuilt = rebuilt.get(index).map(|item| (segment_hash(item), item));
+        match (sent.get(index), rebuilt) {
+            (Some(sent), Some((hash, _))) if *sent == hash => {}
+            (Some(sent), Some((hash, item))) => diff.push(format!(
+                "input[{index}]: sent {sent}, rebuilt {hash}: {}",
+                snippet(item)
+            )),
+            (Some(sent), None) => diff.push(format!(
+                "input[{index}]: sent {sent}, missing from the recorded history"
+            )),
+            (None, Some((hash, item))) => diff.push(format!(
+                "input[{index}]: rebuilt {hash} but not sent: {}",
+                snippet(item)
+            )),
+            (None, None) => {}
+        }
+    }
+    diff
+}
+
+fn snippet(item: &Value) -> String {
+    item.to_string().chars().take(SNIPPET_CHARS).collect()
+}
+
+/// The history a session rebuilds from its rollout, as far as the rollout
+/// alone tells.
+#[derive(Default)]
+struct Replay {
+    history: ContextManager,
+    /// Truncation of the latest turn context. Items recorded before the
+    /// first one are the initial context, which is never truncated.
+    truncation_policy: Option<TruncationPolicy>,
+    checkpoints: Checkpoints,
+}
+
+impl Replay {
+    fn apply(&mut self, item: &RolloutItem) {
+        match item {
+            RolloutItem::TurnContext(context) => {
+                if let Some(policy) = context.truncation_policy {
+                    self.truncation_policy = Some(policy.into());
+                }
+            }
+            RolloutItem::ResponseItem(item) => {
+                let policy = self
+                    .truncation_policy
+                    .unwrap_or(TruncationPolicy::Bytes(usize::MAX));
+                self.history.record_items(std::iter::once(item), policy);
+            }
+            RolloutItem::Compacted(compacted) => {
+                let replacement = match &compacted.replacement_history {
+                    Some(replacement) => replacement.clone(),
+                    None => {
+                        // Compacted locally: the initial context, then the
+                        // user messages and the summary.
+                        let snapshot = self.history.get_history();
+                        let initial_context = snapshot
+                            .iter()
+                            .take_while(|item| {
+                                !matches!(parse_turn_item(item), Some(TurnItem::UserMessage(_)))
+                            })
+                            .cloned()
+                            .collect();
+                        build_compacted_history(
+                            initial_context,
+                            &collect_user_messages(&snapshot),
+                            &compacted.message,
+                        )
+                    }
+                };
+                self.history.replace(replacement);
+                self.checkpoints.clear();
+            }
+            RolloutItem::Checkpoint(checkpoint) => {
+                self.checkpoints
+                    .record(checkpoint.id.clone(), self.history.item_count());
+            }
+            RolloutItem::CheckpointRollback(rollback) => {
+                if let Some((history_len, _)) = self.checkpoints.rollback(&rollback.id) {
+                    self.history.truncate(history_len);
+                }
+            }
+            RolloutItem::InstructionsReplaced(replaced) => {
+                self.history
+                    .replace_user_instructions(replaced.instructions.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn fingerprint_keeps_parameters_in_order_and_hashes_segments() {
+        let body = json!({
+            "model": "gpt-5.1",
+            "instructions": "be brief",
+            "input": [{"type": "message", "role": "user", "content": []}],
+            "tools": [],
+            "stream": true,
+        });
+        let fingerprint = fingerprint(&body).expect("object body");
+
+        assert_eq!(fingerprint.model, "gpt-5.1");
+        assert_eq!(fingerprint.instructions, segment_hash(&json!("be brief")));
+        assert_eq!(
+            fingerprint.segments,
+            BTreeMap::from([
+                (fingerprint.instructions.clone(), json!("be brief")),
+                (fingerprint.tools.clone(), json!([])),
+            ])
+        );
+        assert_eq!(fingerprint.input, vec![segment_hash(&body["input"][0])]);
+        assert_eq!(
+            fingerprint.parameters,
+            json!({
+                "model": "gpt-5.1",
+                "instructions": null,
+                "input": null,
+                "tools": null,
+                "stream": true,
+            })
+        );
+    }
+
+    #[test]
+    fn input_diff_names_each_differing_item() {
+        let kept = json!({"type": "message"});
+        let changed = json!({"type": "function_call_output"});
+        let sent = vec![segment_hash(&kept), "0000000000000000".to_string()];
+
+        assert_eq!(input_diff(&sent, &[kept.clone(), changed.clone()]).len(), 1);
+        assert_eq!(
+            input_diff(&sent, std::slice::from_ref(&kept)),
+            vec!["input[1]: sent 0000000000000000, missing from the recorded history".to_string()]
+        );
+        let extra = input_diff(&sent[..1], &[kept, changed]);
+        assert_eq!(extra.len(), 1);
+        assert!(extra[0].starts_with("input[1]: rebuilt"), "{extra:?}");
+    }
+}
@@ -2,40 +2,68 @@
 
 use std::fs::File;
 use std::fs::{self};
+use std::future::Future;
 use std::io::Error as IoError;
+use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
 use codex_protocol::ConversationId;
 use serde_json::Value;
 use time::OffsetDateTime;
 use time::format_description::FormatItem;
 use time::macros::format_description;
-use tokio::io::AsyncWriteExt;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::{self};
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tracing::info;
 use tracing::warn;
 
-use super::SESSIONS_SUBDIR;
+use super::chain::read_chain_to_string;
+use super::chain::rollout_parts;
+use super::chunked::ChunkAssembler;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
+use super::writer::ChainPosition;
+use super::writer::RolloutCmd;
+use super::writer::RolloutFsyncPolicy;
+use super::writer::RolloutLimits;
+use super::writer::RolloutSink;
+use super::writer::RolloutWriter;
+use super::writer::WriterStatus;
+use crate::codex_home::CodexHome;
 use crate::config::Config;
 use crate::default_client::originator;
-use crate::git_info::collect_git_info;
+use crate::partial_messages::collapse_partial_messages;
+use crate::runtime_env::RuntimeEnv;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::ModelListSource;
+use codex_protocol::protocol::ParentRef;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::RolloutParseError;
 use codex_protocol::protocol::SessionMeta;
-use codex_protocol::protocol::SessionMetaLine;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::SkillSelection;
 
-/// Records all [`ResponseItem`]s for a session and flushes them to disk after
-/// every update.
+/// Records all [`ResponseItem`]s for a session.
+///
+/// Recording only queues the items; a writer task appends them to disk and
+/// syncs the file as [`Config::rollout_fsync`] says, so turns never wait for
+/// the disk. [`Self::flush`] waits until everything recorded is durable.
+///
+/// Lines longer than [`Config::rollout_max_line_bytes`] are split into
+/// [`RolloutItem::Chunked`] parts, which readers join back transparently.
+/// Likewise a rollout longer than [`Config::max_rollout_bytes`] continues in
+/// further files, which readers follow from the first.
 ///
 /// Rollouts are recorded as JSONL and can be inspected with tools such as:
 ///
@@ -45,33 +73,50 @@ use codex_protocol::protocol::SessionSource;
 /// ```
 #[derive(Clone)]
 pub struct RolloutRecorder {
-    tx: Sender<RolloutCmd>,
+    tx: UnboundedSender<RolloutCmd>,
+    /// Why the writer task stopped, once it has.
+    failure: watch::Receiver<Option<String>>,
+    /// Why the writer holds items in memory, while it does.
+    degraded: watch::Receiver<Option<String>>,
+    /// See [`Self::take_persistence_events`].
+    persistence_events: Arc<Mutex<Option<mpsc::UnboundedReceiver<EventMsg>>>>,
     pub(crate) rollout_path: PathBuf,
 }
 
+// Built once per session, so the size of `Create` does not matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone)]
 pub enum RolloutRecorderParams {
     Create {
         conversation_id: ConversationId,
         instructions: Option<String>,
         source: SessionSource,
+        skills: Option<SkillSelection>,
+        model_list_source: Option<ModelListSource>,
+        project_notes: Option<Vec<String>>,
+        parent: Option<ParentRef>,
+        /// The file to record to, when it was created ahead of the recorder.
+        log_file: Option<RolloutLogFile>,
     },
     Resume {
         path: PathBuf,
     },
 }
 
-enum RolloutCmd {
-    AddItems(Vec<RolloutItem>),
-    /// Ensure all prior writes are processed; respond when flushed.
-    Flush {
-        ack: oneshot::Sender<()>,
-    },
-    Shutdown {
-        ack: oneshot::Sender<()>,
-    },
+/// What reading a rollout does with lines that do not parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RolloutParseMode {
+    /// Fail on the first one, with an [`ErrorKind::InvalidData`] error
+    /// wrapping its [`RolloutParseError`].
+    Strict,
+    /// Skip them, listing each in [`ResumedHistory::parse_errors`].
+    #[default]
+    Lossy,
 }
 
+/// Characters of an unparsable line quoted in its [`RolloutParseError`].
+const PARSE_ERROR_SNIPPET_CHARS: usize = 200;
+
 impl RolloutRecorderParams {
     pub fn new(
         conversation_id: ConversationId,
@@ -82,7 +127,57 @@ impl RolloutRecorderParams {
             conversation_id,
             instructions,
             source,
+            skills: None,
+            model_list_source: None,
+            project_notes: None,
+            parent: None,
+            log_file: None,
+        }
+    }
+
+    /// Record the skills selected for the session in its meta. Resumed
+    /// rollouts keep the meta they were created with.
+    pub fn with_skills(mut self, selection: Option<SkillSelection>) -> Self {
+        if let Self::Create { skills, .. } = &mut self {
+            *skills = selection;
+        }
+        self
+    }
+
+    /// Record where the session's model list came from in its meta.
+    pub fn with_model_list_source(mut self, source: Option<ModelListSource>) -> Self {
+        if let Self::Create {
+            model_list_source, ..
+        } = &mut self
+        {
+            *model_list_source = source;
+        }
+        self
+    }
+
+    /// Record the ids of the project notes in the session prefix in its
+    /// meta.
+    pub fn with_project_notes(mut self, ids: Option<Vec<String>>) -> Self {
+        if let Self::Create { project_notes, .. } = &mut self {
+            *project_notes = ids;
+        }
+        self
+    }
+
+    /// Record the conversation a fork was cut from in its meta.
+    pub fn with_parent(mut self, parent_ref: Option<ParentRef>) -> Self {
+        if let Self::Create { parent, .. } = &mut self {
+            *parent = parent_ref;
+        }
+        self
+    }
+
+    /// Record to `file` instead of creating a new one.
+    pub fn with_log_file(mut self, file: Option<RolloutLogFile>) -> Self {
+        if let Self::Create { log_file, .. } = &mut self {
+            *log_file = file;
         }
+        self
     }
 
     pub fn resume(path: PathBuf) -> Self {
@@ -115,18 +210,36 @@ impl RolloutRecorder {
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
     pub async fn new(config: &Config, params: RolloutRecorderParams) -> std::io::Result<Self> {
-        let (file, rollout_path, meta) = match params {
+        Self::new_with_env(config, params, &RuntimeEnv::default()).await
+    }
+
+    /// Like [`Self::new`], taking the file name and every timestamp from the
+    /// clock of `env`.
+    pub async fn new_with_env(
+        config: &Config,
+        params: RolloutRecorderParams,
+        env: &RuntimeEnv,
+    ) -> std::io::Result<Self> {
+        let (file, position, meta) = match params {
             RolloutRecorderParams::Create {
                 conversation_id,
                 instructions,
                 source,
+                skills,
+                model_list_source,
+                project_notes,
+                parent,
+                log_file,
             } => {
                 let LogFileInfo {
                     file,
                     path,
                     conversation_id: session_id,
                     timestamp,
-                } = create_log_file(config, conversation_id)?;
+                } = match log_file {
+                    Some(log_file) => log_file.open()?,
+                    None => create_log_file(config, conversation_id, env)?,
+                };
 
                 let timestamp_format: &[FormatItem] = format_description!(
                     "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
@@ -138,7 +251,7 @@ impl RolloutRecorder {
 
                 (
                     tokio::fs::File::from_std(file),
-                    path,
+                    ChainPosition::new(path),
                     Some(SessionMeta {
                         id: session_id,
                         timestamp,
@@ -148,36 +261,143 @@ impl RolloutRecorder {
                         instructions,
                         source,
                         model_provider: Some(config.model_provider_id.clone()),
+                        session_profile: config.session_profile.clone(),
+                        skills,
+                        model_list_source,
+                        tool_capabilities: config.model_provider.tool_capabilities.clone(),
+                        project_notes,
+                        parent,
                     }),
                 )
             }
-            RolloutRecorderParams::Resume { path } => (
-                tokio::fs::OpenOptions::new()
-                    .append(true)
-                    .open(&path)
-                    .await?,
-                path,
-                None,
-            ),
+            RolloutRecorderParams::Resume { path } => {
+                // Append to the last part of the chain.
+                let parts = rollout_parts(&path).await?;
+                let mut total_bytes = 0;
+                let mut part_bytes = 0;
+                for part in &parts {
+                    part_bytes = tokio::fs::metadata(part).await?.len();
+                    total_bytes += part_bytes;
+                }
+                let tail = parts.last().unwrap_or(&path);
+                (
+                    tokio::fs::OpenOptions::new()
+                        .append(true)
+                        .open(tail)
+                        .await?,
+                    ChainPosition {
+                        part: u32::try_from(parts.len()).unwrap_or(u32::MAX),
+                        part_bytes,
+                        total_bytes,
+                        head: path,
+                    },
+                    None,
+                )
+            }
+        };
+        let limits = RolloutLimits {
+            max_line_bytes: config.rollout_max_line_bytes,
+            max_part_bytes: config.max_rollout_bytes,
+            hard_cap_bytes: config.rollout_hard_cap_bytes,
+            max_overflow_bytes: None,
         };
 
-        // Clone the cwd for the spawned task to collect git info asynchronously
-        let cwd = config.cwd.clone();
-
-        // A reasonably-sized bounded channel. If the buffer fills up the send
-        // future will yield, which is fine – we only need to ensure we do not
-        // perform *blocking* I/O on the caller's thread.
-        let (tx, rx) = mpsc::channel::<RolloutCmd>(256);
+        #[cfg(feature = "test-support")]
+        if let Some(plan) = crate::fault_injection::rollout_faults(&config.codex_home) {
+            return Ok(Self::start(
+                crate::fault_injection::FaultySink::new(file, plan),
+                position,
+                meta,
+                config.cwd.clone(),
+                limits,
+                config.rollout_fsync,
+                env.clone(),
+            ));
+        }
 
-        // Spawn a Tokio task that owns the file handle and performs async
-        // writes. Using `tokio::fs::File` keeps everything on the async I/O
+        // Using `tokio::fs::File` keeps the writer task on the async I/O
         // driver instead of blocking the runtime.
-        tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
+        Ok(Self::start(
+            file,
+            position,
+            meta,
+            config.cwd.clone(),
+            limits,
+            config.rollout_fsync,
+            env.clone(),
+        ))
+    }
+
+    /// Spawn the writer task appending to `sink` at `position`.
+    fn start(
+        sink: impl RolloutSink,
+        position: ChainPosition,
+        meta: Option<SessionMeta>,
+        cwd: PathBuf,
+        limits: RolloutLimits,
+        fsync: RolloutFsyncPolicy,
+        env: RuntimeEnv,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (failure_tx, failure) = watch::channel(None);
+        let (degraded_tx, degraded) = watch::channel(None);
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let status = WriterStatus {
+            failure: failure_tx,
+            degraded: degraded_tx,
+            events: events_tx,
+        };
+        let rollout_path = position.head.clone();
+        let writer = RolloutWriter::new(sink, limits, position, fsync, status, env);
+        tokio::task::spawn(writer.run(rx, meta, cwd));
+        Self {
+            tx,
+            failure,
+            degraded,
+            persistence_events: Arc::new(Mutex::new(Some(events))),
+            rollout_path,
+        }
+    }
+
+    /// A recorder appending to `sink` without a session meta line.
+    #[cfg(test)]
+    pub(crate) fn with_sink(
+        sink: impl RolloutSink,
+        rollout_path: PathBuf,
+        fsync: RolloutFsyncPolicy,
+    ) -> Self {
+        Self::with_limits(sink, rollout_path, RolloutLimits::default(), fsync)
+    }
 
-        Ok(Self { tx, rollout_path })
+    /// Like [`Self::with_sink`], holding the rollout to `limits`.
+    #[cfg(test)]
+    pub(crate) fn with_limits(
+        sink: impl RolloutSink,
+        rollout_path: PathBuf,
+        limits: RolloutLimits,
+        fsync: RolloutFsyncPolicy,
+    ) -> Self {
+        Self::start(
+            sink,
+            ChainPosition::new(rollout_path),
+            None,
+            PathBuf::new(),
+            limits,
+            fsync,
+            RuntimeEnv::default(),
+        )
     }
 
     pub(crate) async fn record_items(&self, items: &[RolloutItem]) -> std::io::Result<()> {
+        self.record_correlated_items(items, None).await
+    }
+
+    /// Like [`Self::record_items`], tagging every line with `correlation_id`.
+    pub(crate) async fn record_correlated_items(
+        &self,
+        items: &[RolloutItem],
+        correlation_id: Option<String>,
+    ) -> std::io::Result<()> {
         let mut filtered = Vec::new();
         for item in items {
             // Note that function calls may look a bit strange if they are
@@ -191,73 +411,231 @@ impl RolloutRecorder {
             return Ok(());
         }
         self.tx
-            .send(RolloutCmd::AddItems(filtered))
-            .await
-            .map_err(|e| IoError::other(format!("failed to queue rollout items: {e}")))
+            .send(RolloutCmd::AddItems {
+                items: filtered,
+                correlation_id,
+            })
+            .map_err(|_| self.writer_error())
     }
 
-    /// Flush all queued writes and wait until they are committed by the writer task.
+    /// Wait until everything recorded so far is written and synced to disk.
+    /// Fails once the writer task has failed.
     pub async fn flush(&self) -> std::io::Result<()> {
-        let (tx, rx) = oneshot::channel();
-        self.tx
-            .send(RolloutCmd::Flush { ack: tx })
+        self.barrier(|ack| RolloutCmd::Flush { durable: true, ack })
             .await
-            .map_err(|e| IoError::other(format!("failed to queue rollout flush: {e}")))?;
-        rx.await
-            .map_err(|e| IoError::other(format!("failed waiting for rollout flush: {e}")))
     }
 
+    /// Wait until everything recorded so far is written to the file, so
+    /// readers see it, without waiting for it to be synced.
+    pub(crate) async fn flush_written(&self) -> std::io::Result<()> {
+        self.barrier(|ack| RolloutCmd::Flush {
+            durable: false,
+            ack,
+        })
+        .await
+    }
+
+    /// Tell the writer a turn ended, which syncs the file under
+    /// [`RolloutFsyncPolicy::TurnEnd`]. Does not wait for it.
+    pub(crate) fn turn_ended(&self) {
+        let _ = self.tx.send(RolloutCmd::TurnEnded);
+    }
+
+    /// Resolves with the error that stopped the writer task, or `None` when
+    /// it ends without one.
+    pub(crate) fn writer_failure(&self) -> impl Future<Output = Option<String>> + Send + 'static {
+        let mut failure = self.failure.clone();
+        async move {
+            failure
+                .wait_for(Option::is_some)
+                .await
+                .ok()
+                .and_then(|failure| failure.clone())
+        }
+    }
+
+    /// The [`EventMsg::PersistenceDegraded`] and
+    /// [`EventMsg::PersistenceRecovered`] events of the writer, for the
+    /// session to send. Only the first call gets them.
+    pub(crate) fn take_persistence_events(&self) -> Option<mpsc::UnboundedReceiver<EventMsg>> {
+        self.persistence_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    async fn barrier(
+        &self,
+        cmd: impl FnOnce(oneshot::Sender<()>) -> RolloutCmd,
+    ) -> std::io::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.tx.send(cmd(tx)).map_err(|_| self.writer_error())?;
+        // The writer drops the ack only after publishing its failure, or
+        // while it holds items in memory.
+        rx.await.map_err(|_| self.writer_error())
+    }
+
+    fn writer_error(&self) -> IoError {
+        if let Some(failure) = &*self.failure.borrow() {
+            return IoError::other(format!("rollout writer failed: {failure}"));
+        }
+        match &*self.degraded.borrow() {
+            Some(reason) => IoError::other(format!(
+                "rollout held in memory until the disk has room: {reason}"
+            )),
+            None => IoError::other("rollout writer stopped"),
+        }
+    }
+
+    /// Read the rollout at `path`, skipping lines that do not parse.
     pub async fn get_rollout_history(path: &Path) -> std::io::Result<InitialHistory> {
+        Self::get_rollout_history_with_mode(path, RolloutParseMode::Lossy).await
+    }
+
+    pub async fn get_rollout_history_with_mode(
+        path: &Path,
+        mode: RolloutParseMode,
+    ) -> std::io::Result<InitialHistory> {
         info!("Resuming rollout from {path:?}");
-        let text = tokio::fs::read_to_string(path).await?;
+        let text = read_chain_to_string(path).await?;
+        Self::parse_rollout_history(&text, path, mode)
+    }
+
+    /// Parse rollout `text` previously read from `path`.
+    pub(crate) fn parse_rollout_history(
+        text: &str,
+        path: &Path,
+        mode: RolloutParseMode,
+    ) -> std::io::Result<InitialHistory> {
+        // A session writes its meta line when it starts, so a rollout without
+        // one is damaged rather than empty.
         if text.trim().is_empty() {
-            return Err(IoError::other("empty session file"));
+            return Err(IoError::new(ErrorKind::InvalidData, "empty session file"));
         }
 
         let mut items: Vec<RolloutItem> = Vec::new();
         let mut conversation_id: Option<ConversationId> = None;
-        for line in text.lines() {
+        let mut parse_errors: Vec<RolloutParseError> = Vec::new();
+        let mut skip = |error: RolloutParseError| match mode {
+            RolloutParseMode::Strict => Err(IoError::new(ErrorKind::InvalidData, error)),
+            RolloutParseMode::Lossy => {
+                warn!("skipping unparsable line of rollout {path:?}: {error}");
+                parse_errors.push(error);
+                Ok(())
+            }
+        };
+        let mut chunks = ChunkAssembler::default();
+        // The first line of the chunked item being reassembled.
+        let mut run_start = (0, "");
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
             if line.trim().is_empty() {
                 continue;
             }
-            let v: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(e) => {
-                    warn!("failed to parse line as JSON: {line:?}, error: {e}");
+            let rollout_line = match serde_json::from_str::<Value>(line)
+                .and_then(serde_json::from_value::<RolloutLine>)
+            {
+                Ok(rollout_line) => rollout_line,
+                Err(err) => {
+                    skip(parse_error(line_number, line, &err))?;
                     continue;
                 }
             };
+            if matches!(rollout_line.item, RolloutItem::Chunked { part: 0, .. }) {
+                run_start = (line_number, line);
+            }
 
-            // Parse the rollout line structure
-            match serde_json::from_value::<RolloutLine>(v.clone()) {
-                Ok(rollout_line) => match rollout_line.item {
-                    RolloutItem::SessionMeta(session_meta_line) => {
-                        // Use the FIRST SessionMeta encountered in the file as the canonical
-                        // conversation id and main session information. Keep all items intact.
-                        if conversation_id.is_none() {
-                            conversation_id = Some(session_meta_line.meta.id);
-                        }
-                        items.push(RolloutItem::SessionMeta(session_meta_line));
-                    }
-                    RolloutItem::ResponseItem(item) => {
-                        items.push(RolloutItem::ResponseItem(item));
-                    }
-                    RolloutItem::Compacted(item) => {
-                        items.push(RolloutItem::Compacted(item));
-                    }
-                    RolloutItem::TurnContext(item) => {
-                        items.push(RolloutItem::TurnContext(item));
-                    }
-                    RolloutItem::EventMsg(_ev) => {
-                        items.push(RolloutItem::EventMsg(_ev));
+            // Parts of a chunked item are held back until it is complete.
+            let rollout_line = match chunks.try_push(rollout_line) {
+                Ok(Some(rollout_line)) => rollout_line,
+                Ok(None) => continue,
+                Err(err) => {
+                    let (start_number, start_line) = run_start;
+                    skip(parse_error(start_number, start_line, &err))?;
+                    continue;
+                }
+            };
+            match rollout_line.item {
+                RolloutItem::SessionMeta(session_meta_line) => {
+                    // Use the FIRST SessionMeta encountered in the file as the canonical
+                    // conversation id and main session information. Keep all items intact.
+                    if conversation_id.is_none() {
+                        conversation_id = Some(session_meta_line.meta.id);
                     }
-                },
-                Err(e) => {
-                    warn!("failed to parse rollout line: {v:?}, error: {e}");
+                    items.push(RolloutItem::SessionMeta(session_meta_line));
+                }
+                RolloutItem::ResponseItem(item) => {
+                    items.push(RolloutItem::ResponseItem(item));
+                }
+                RolloutItem::Compacted(item) => {
+                    items.push(RolloutItem::Compacted(item));
+                }
+                RolloutItem::TurnContext(item) => {
+                    items.push(RolloutItem::TurnContext(item));
+                }
+                RolloutItem::EventMsg(_ev) => {
+                    items.push(RolloutItem::EventMsg(_ev));
+                }
+                RolloutItem::Checkpoint(item) => {
+                    items.push(RolloutItem::Checkpoint(item));
+                }
+                RolloutItem::CheckpointRollback(item) => {
+                    items.push(RolloutItem::CheckpointRollback(item));
+                }
+                RolloutItem::PendingInput(item) => {
+                    items.push(RolloutItem::PendingInput(item));
+                }
+                RolloutItem::InstructionsReplaced(item) => {
+                    items.push(RolloutItem::InstructionsReplaced(item));
+                }
+                RolloutItem::ReadReference(item) => {
+                    items.push(RolloutItem::ReadReference(item));
+                }
+                RolloutItem::InputRejected(item) => {
+                    items.push(RolloutItem::InputRejected(item));
+                }
+                RolloutItem::ItemPinned(item) => {
+                    items.push(RolloutItem::ItemPinned(item));
+                }
+                RolloutItem::ItemUnpinned(item) => {
+                    items.push(RolloutItem::ItemUnpinned(item));
+                }
+                RolloutItem::TranscriptMutation(item) => {
+                    items.push(RolloutItem::TranscriptMutation(item));
+                }
+                RolloutItem::GitContext(item) => {
+                    items.push(RolloutItem::GitContext(item));
+                }
+                RolloutItem::RequestFingerprint(item) => {
+                    items.push(RolloutItem::RequestFingerprint(item));
+                }
+                RolloutItem::Annotation(item) => {
+                    items.push(RolloutItem::Annotation(item));
+                }
+                RolloutItem::TurnStarted(item) => {
+                    items.push(RolloutItem::TurnStarted(item));
                 }
+                RolloutItem::PartialMessage { turn_id, seq, text } => {
+                    items.push(RolloutItem::PartialMessage { turn_id, seq, text });
+                }
+                // Links between the parts of a chain, which is read as one.
+                RolloutItem::ContinuedIn(_) | RolloutItem::ContinuedFrom(_) => {}
+                RolloutItem::Unknown { raw } => {
+                    warn!(
+                        "keeping rollout item written by a newer version: {:?}",
+                        raw.get("type")
+                    );
+                    items.push(RolloutItem::Unknown { raw });
+                }
+                // Parts never nest; a chunk that reassembles into another is junk.
+                RolloutItem::Chunked { .. } => {}
             }
         }
 
+        chunks.finish();
+        // Only a partial message nothing superseded is still of interest.
+        collapse_partial_messages(&mut items);
+
         info!(
             "Resumed rollout with {} items, conversation ID: {:?}",
             items.len(),
@@ -266,31 +644,77 @@ impl RolloutRecorder {
         let conversation_id = conversation_id
             .ok_or_else(|| IoError::other("failed to parse conversation ID from rollout file"))?;
 
-        if items.is_empty() {
-            return Ok(InitialHistory::New);
-        }
-
-        info!("Resumed rollout successfully from {path:?}");
-        Ok(InitialHistory::Resumed(ResumedHistory {
+        let empty = items
+            .iter()
+            .all(|item| matches!(item, RolloutItem::SessionMeta(_)));
+        let resumed = ResumedHistory {
             conversation_id,
             history: items,
             rollout_path: path.to_path_buf(),
-        }))
+            parse_errors,
+        };
+        if empty {
+            info!("Rollout {path:?} has no turns; resuming it as a new session");
+            return Ok(InitialHistory::ResumedEmpty(resumed));
+        }
+
+        info!("Resumed rollout successfully from {path:?}");
+        Ok(InitialHistory::Resumed(resumed))
     }
 
+    /// Like [`Self::flush`], before the session ends.
     pub async fn shutdown(&self) -> std::io::Result<()> {
-        let (tx_done, rx_done) = oneshot::channel();
-        match self.tx.send(RolloutCmd::Shutdown { ack: tx_done }).await {
-            Ok(_) => rx_done
-                .await
-                .map_err(|e| IoError::other(format!("failed waiting for rollout shutdown: {e}"))),
-            Err(e) => {
-                warn!("failed to send rollout shutdown command: {e}");
-                Err(IoError::other(format!(
-                    "failed to send rollout shutdown command: {e}"
-                )))
-            }
-        }
+        self.barrier(|ack| RolloutCmd::Shutdown { ack }).await
+    }
+}
+
+fn parse_error(line: usize, text: &str, err: &serde_json::Error) -> RolloutParseError {
+    RolloutParseError {
+        line,
+        snippet: text.chars().take(PARSE_ERROR_SNIPPET_CHARS).collect(),
+        error: err.to_string(),
+    }
+}
+
+/// A rollout file created before its recorder, so its path is known while
+/// the session is still being configured.
+#[derive(Debug, Clone)]
+pub struct RolloutLogFile {
+    path: PathBuf,
+    conversation_id: ConversationId,
+    timestamp: OffsetDateTime,
+}
+
+impl RolloutLogFile {
+    /// Create the empty rollout file of `conversation_id`, named after the
+    /// clock of `env`.
+    pub fn create(
+        config: &Config,
+        conversation_id: ConversationId,
+        env: &RuntimeEnv,
+    ) -> std::io::Result<Self> {
+        let LogFileInfo {
+            path, timestamp, ..
+        } = create_log_file(config, conversation_id, env)?;
+        Ok(Self {
+            path,
+            conversation_id,
+            timestamp,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(self) -> std::io::Result<LogFileInfo> {
+        let file = std::fs::OpenOptions::new().append(true).open(&self.path)?;
+        Ok(LogFileInfo {
+            file,
+            path: self.path,
+            conversation_id: self.conversation_id,
+            timestamp: self.timestamp,
+        })
     }
 }
 
@@ -311,12 +735,13 @@ struct LogFileInfo {
 fn create_log_file(
     config: &Config,
     conversation_id: ConversationId,
+    env: &RuntimeEnv,
 ) -> std::io::Result<LogFileInfo> {
     // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
-    let timestamp = OffsetDateTime::now_local()
+    let offset = time::UtcOffset::current_local_offset()
         .map_err(|e| IoError::other(format!("failed to get local time: {e}")))?;
-    let mut dir = config.codex_home.clone();
-    dir.push(SESSIONS_SUBDIR);
+    let timestamp = OffsetDateTime::from(env.now()).to_offset(offset);
+    let mut dir = CodexHome::new(config.codex_home.as_path()).sessions_dir();
     dir.push(timestamp.year().to_string());
     dir.push(format!("{:02}", u8::from(timestamp.month())));
     dir.push(format!("{:02}", timestamp.day()));
@@ -345,80 +770,3 @@ fn create_log_file(
         timestamp,
     })
 }
-
-async fn rollout_writer(
-    file: tokio::fs::File,
-    mut rx: mpsc::Receiver<RolloutCmd>,
-    mut meta: Option<SessionMeta>,
-    cwd: std::path::PathBuf,
-) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
-
-    // If we have a meta, collect git info asynchronously and write meta first
-    if let Some(session_meta) = meta.take() {
-        let git_info = collect_git_info(&cwd).await;
-        let session_meta_line = SessionMetaLine {
-            meta: session_meta,
-            git: git_info,
-        };
-
-        // Write the SessionMeta as the first item in the file, wrapped in a rollout line
-        writer
-            .write_rollout_item(RolloutItem::SessionMeta(session_meta_line))
-            .await?;
-    }
-
-    // Process rollout commands
-    while let Some(cmd) = rx.recv().await {
-        match cmd {
-            RolloutCmd::AddItems(items) => {
-                for item in items {
-                    if is_persisted_response_item(&item) {
-                        writer.write_rollout_item(item).await?;
-                    }
-                }
-            }
-            RolloutCmd::Flush { ack } => {
-                // Ensure underlying file is flushed and then ack.
-                if let Err(e) = writer.file.flush().await {
-                    let _ = ack.send(());
-                    return Err(e);
-                }
-                let _ = ack.send(());
-            }
-            RolloutCmd::Shutdown { ack } => {
-                let _ = ack.send(());
-            }
-        }
-    }
-
-    Ok(())
-}
-
-struct JsonlWriter {
-    file: tokio::fs::File,
-}
-
-impl JsonlWriter {
-    async fn write_rollout_item(&mut self, rollout_item: RolloutItem) -> std::io::Result<()> {
-        let timestamp_format: &[FormatItem] = format_description!(
-            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-        );
-        let timestamp = OffsetDateTime::now_utc()
-            .format(timestamp_format)
-            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
-
-        let line = RolloutLine {
-            timestamp,
-            item: rollout_item,
-        };
-        self.write_line(&line).await
-    }
-    async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
-        let mut json = serde_json::to_string(item)?;
-        json.push('\n');
-        self.file.write_all(json.as_bytes()).await?;
-        self.file.flush().await?;
-        Ok(())
-    }
-}
@@ -0,0 +1,88 @@
+//! The [`ResumedSummary`] a resumed or forked session reports in its
+//! `SessionConfigured` event.
+
+use codex_protocol::protocol::HistoryOrigin;
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::ResumeTrimReport;
+use codex_protocol::protocol::ResumedSummary;
+use codex_protocol::protocol::RolloutItem;
+
+use super::fork_points::snippet;
+use super::list::file_modified_rfc3339;
+use crate::history_truncation::user_message_at;
+use crate::sensitive_input::REDACTED_PREFIX;
+
+/// Summarize `history`; `None` for a new session. `sanitized` lists what
+/// was removed from it before it reached the model, and `trim` how much of
+/// it fits the context window.
+pub(crate) async fn resumed_summary(
+    history: &InitialHistory,
+    sanitized: Vec<String>,
+    trim: Option<ResumeTrimReport>,
+) -> Option<ResumedSummary> {
+    let (origin, items, last_activity) = match history {
+        InitialHistory::New => return None,
+        InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => (
+            HistoryOrigin::Resumed,
+            &resumed.history,
+            file_modified_rfc3339(&resumed.rollout_path)
+                .await
+                .ok()
+                .flatten(),
+        ),
+        InitialHistory::Forked(items) => (HistoryOrigin::Forked, items, None),
+    };
+
+    let user_messages: Vec<String> = items
+        .iter()
+        .filter_map(user_message_at)
+        .map(|message| message.message())
+        .collect();
+    let truncated = items.iter().any(|item| {
+        matches!(
+            item,
+            RolloutItem::Compacted(_) | RolloutItem::CheckpointRollback(_)
+        )
+    });
+
+    let mut warnings = Vec::new();
+    let unknown = history.unknown_item_count();
+    if unknown > 0 {
+        warnings.push(format!(
+            "{unknown} {} written by a newer version of Codex {} not shown to the model.",
+            plural(unknown, "item", "items"),
+            plural(unknown, "is", "are"),
+        ));
+    }
+    warnings.extend(
+        history
+            .parse_errors()
+            .iter()
+            .map(|error| format!("Skipped unreadable rollout {error}.")),
+    );
+    warnings.extend(sanitized);
+    let redacted = user_messages
+        .iter()
+        .filter(|message| message.contains(REDACTED_PREFIX))
+        .count();
+    if redacted > 0 {
+        warnings.push(format!(
+            "{redacted} user {} redacted text.",
+            plural(redacted, "message contains", "messages contain"),
+        ));
+    }
+
+    Some(ResumedSummary {
+        origin,
+        turns: user_messages.len(),
+        last_user_message: user_messages.last().map(|message| snippet(message)),
+        last_activity,
+        truncated,
+        warnings,
+        trim,
+    })
+}
+
+fn plural(count: usize, one: &'static str, many: &'static str) -> &'static str {
+    if count == 1 { one } else { many }
+}
@@ -0,0 +1,96 @@
+//! Resume-time removal of recorded items the session's model cannot read.
+//!
+//! Reasoning items and compaction summaries carry content encrypted for the
+//! model family that produced them. Sent to a model of another family, for
+//! instance after the recorded model was retired, they make the provider
+//! reject the first request of the resumed session. They are dropped from the
+//! history handed to the model and reported in the resume warnings; the
+//! rollout being resumed keeps them.
+//!
+//! Item ids are never sent back to the provider, so they need no cleanup.
+
+use std::collections::BTreeMap;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::RolloutItem;
+
+use crate::models_manager::model_family::ModelFamily;
+use crate::models_manager::model_family::find_family_for_model;
+
+/// What [`sanitize_for_model`] removed, counted by the model that recorded it.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SanitizeReport {
+    reasoning: BTreeMap<String, usize>,
+    compaction_summaries: BTreeMap<String, usize>,
+}
+
+impl SanitizeReport {
+    /// One warning per kind of item and recording model.
+    pub(crate) fn warnings(&self, target_model: &str) -> Vec<String> {
+        let reasoning = self.reasoning.iter().map(|(model, count)| {
+            format!(
+                "Removed {count} reasoning {} recorded with `{model}`, whose encrypted_content `{target_model}` cannot read.",
+                if *count == 1 { "item" } else { "items" },
+            )
+        });
+        let summaries = self.compaction_summaries.iter().map(|(model, count)| {
+            format!(
+                "Removed {count} compaction {} recorded with `{model}`, whose encrypted_content `{target_model}` cannot read.",
+                if *count == 1 { "summary" } else { "summaries" },
+            )
+        });
+        reasoning.chain(summaries).collect()
+    }
+
+    /// Whether `item`, recorded by a model of another family, can be kept.
+    fn keep(&mut self, item: &ResponseItem, model: &str) -> bool {
+        let counts = match item {
+            ResponseItem::Reasoning { .. } => &mut self.reasoning,
+            ResponseItem::Compaction { .. } => &mut self.compaction_summaries,
+            _ => return true,
+        };
+        *counts.entry(model.to_string()).or_default() += 1;
+        false
+    }
+}
+
+/// Drop the encrypted items of `history` recorded by a model outside the
+/// family of `target`. Items preceding the first recorded turn context, whose
+/// model is unknown, are kept.
+pub(crate) fn sanitize_for_model(
+    mut history: InitialHistory,
+    target: &ModelFamily,
+) -> (InitialHistory, SanitizeReport) {
+    let mut report = SanitizeReport::default();
+    let items = match &mut history {
+        InitialHistory::New | InitialHistory::ResumedEmpty(_) => return (history, report),
+        InitialHistory::Resumed(resumed) => &mut resumed.history,
+        InitialHistory::Forked(items) => items,
+    };
+
+    // The model of the turn being replayed and whether its family differs.
+    let mut recorded: Option<(String, bool)> = None;
+    items.retain_mut(|item| match item {
+        RolloutItem::TurnContext(context) => {
+            let foreign = find_family_for_model(&context.model).family != target.family;
+            recorded = Some((context.model.clone(), foreign));
+            true
+        }
+        RolloutItem::ResponseItem(response_item) => match &recorded {
+            Some((model, true)) => report.keep(response_item, model),
+            _ => true,
+        },
+        RolloutItem::Compacted(compacted) => {
+            if let Some((model, true)) = &recorded
+                && let Some(replacement) = &mut compacted.replacement_history
+            {
+                replacement.retain(|response_item| report.keep(response_item, model));
+            }
+            true
+        }
+        _ => true,
+    });
+
+    (history, report)
+}
@@ -592,9 +592,16 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
                 cli_version: "test_version".into(),
                 source: SessionSource::VSCode,
                 model_provider: Some("test-provider".into()),
+                session_profile: None,
+                skills: None,
+                model_list_source: None,
+                tool_capabilities: None,
+                project_notes: None,
+                parent: None,
             },
             git: None,
         }),
+        correlation_id: None,
     };
     writeln!(file, "{}", serde_json::to_string(&meta_line)?)?;
 
@@ -604,6 +611,7 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
             message: "hello".into(),
             images: None,
         })),
+        correlation_id: None,
     };
     writeln!(file, "{}", serde_json::to_string(&user_event_line)?)?;
 
@@ -618,6 +626,7 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
                     text: format!("reply-{idx}"),
                 }],
             }),
+            correlation_id: None,
         };
         writeln!(file, "{}", serde_json::to_string(&response_line)?)?;
     }
@@ -0,0 +1,411 @@
+//! The full transcript of a rollout, read line by line.
+//!
+//! Compaction replaces the in-memory history with a summary, so the session
+//! only holds what the model still sees. Everything said before is read back
+//! from the rollout on demand: every recorded [`ResponseItem`] except those a
+//! rollback discarded, including the ones compaction summarized away.
+//! [`read_transcript_turns`] groups the same items by the user turn that
+//! recorded them.
+//!
+//! A session keeps its transcript up to the last compaction as a
+//! [`SpilledTranscript`]: for each item, where it starts in the rollout, its
+//! hash, and whether it is a user message. [`read_items`] reads items back by
+//! seeking straight to them, and [`SpilledTranscript::read_on`] reads only
+//! the lines recorded since, so neither reads the whole rollout again.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TurnId;
+
+use sha2::Digest;
+use sha2::Sha256;
+
+use super::chain::ChainLines;
+use super::chain::LineOffset;
+use super::chain::OffsetLines;
+use super::chunked::ChunkAssembler;
+use crate::history_truncation::CutPoint;
+use crate::history_truncation::truncate_at_index;
+use crate::state::Checkpoints;
+use crate::truncate::TruncationPolicy;
+
+/// The items of a transcript recorded by one user turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptTurn {
+    /// `None` for the items recorded outside a turn with an id: the initial
+    /// context, and the turns of sessions recorded before turns had ids.
+    pub turn_id: Option<TurnId>,
+    pub items: Vec<ResponseItem>,
+}
+
+/// An item of the transcript left in the rollout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RolloutRef {
+    /// Where the line of the item, or the first of its chunks, starts.
+    pub(crate) offset: LineOffset,
+    /// SHA-256 of the serialized item, checked when it is read back.
+    pub(crate) sha256: [u8; 32],
+    /// Whether the item is a user message, which starts a turn.
+    pub(crate) turn_boundary: bool,
+}
+
+impl RolloutRef {
+    fn new(offset: LineOffset, item: &ResponseItem) -> Self {
+        Self {
+            offset,
+            sha256: item_sha256(item),
+            turn_boundary: item.is_user_message(),
+        }
+    }
+}
+
+impl CutPoint for RolloutRef {
+    fn is_user_message(&self) -> bool {
+        self.turn_boundary
+    }
+}
+
+/// SHA-256 of `item` as serialized.
+pub(crate) fn item_sha256(item: &ResponseItem) -> [u8; 32] {
+    let bytes = serde_json::to_vec(item).unwrap_or_default();
+    Sha256::digest(&bytes).into()
+}
+
+/// Read the transcript of the rollout at `path`.
+pub(crate) async fn read_transcript(path: &Path) -> io::Result<Vec<ResponseItem>> {
+    Ok(read_turn_items(path)
+        .await?
+        .into_iter()
+        .map(|(_, item)| item)
+        .collect())
+}
+
+/// Read the transcript of the rollout at `path`, in turns.
+pub(crate) async fn read_transcript_turns(path: &Path) -> io::Result<Vec<TranscriptTurn>> {
+    let mut turns: Vec<TranscriptTurn> = Vec::new();
+    for (turn_id, item) in read_turn_items(path).await? {
+        match turns.last_mut() {
+            Some(turn) if turn.turn_id == turn_id => turn.items.push(item),
+            _ => turns.push(TranscriptTurn {
+                turn_id,
+                items: vec![item],
+            }),
+        }
+    }
+    Ok(turns)
+}
+
+/// Read back the items of `refs` from the rollout at `path`, seeking to
+/// each. Fails with [`io::ErrorKind::InvalidData`] when one is no longer there
+/// as it was recorded.
+pub(crate) async fn read_items(path: &Path, refs: &[RolloutRef]) -> io::Result<Vec<ResponseItem>> {
+    let Some(first) = refs.first() else {
+        return Ok(Vec::new());
+    };
+    let mut items = OffsetItems::open_at(path, first.offset).await?;
+    let mut read = Vec::with_capacity(refs.len());
+    for rollout_ref in refs {
+        items.seek(rollout_ref.offset).await?;
+        match items.next().await? {
+            Some((offset, RolloutItem::ResponseItem(item)))
+                if offset == rollout_ref.offset && item_sha256(&item) == rollout_ref.sha256 =>
+            {
+                read.push(item);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} no longer holds the item recorded at byte {} of part {}",
+                        path.display(),
+                        rollout_ref.offset.byte,
+                        rollout_ref.offset.part
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(read)
+}
+
+/// The transcript of a rollout up to some line, as references, see the
+/// module docs.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpilledTranscript {
+    /// The transcript items before `end`, as [`read_transcript`] reads them.
+    items: Vec<RolloutRef>,
+    /// Where the lines not read yet start.
+    end: LineOffset,
+    /// The checkpoints reachable at `end`, at positions in `items`.
+    checkpoints: Checkpoints,
+    /// The truncation policy recorded last before `end`.
+    policy: Option<TruncationPolicy>,
+}
+
+/// The lines of a rollout read past a [`SpilledTranscript`].
+pub(crate) struct ReadOn {
+    /// The transcript up to the end of the rollout.
+    pub(crate) transcript: SpilledTranscript,
+    /// The items read, in full, rolled back ones included.
+    pub(crate) read: Vec<ReadItem>,
+}
+
+/// An item read by [`SpilledTranscript::read_on`].
+pub(crate) struct ReadItem {
+    pub(crate) rollout_ref: RolloutRef,
+    pub(crate) item: ResponseItem,
+    /// The truncation policy the session recorded the item into its history
+    /// with, `None` when the rollout records none.
+    pub(crate) policy: Option<TruncationPolicy>,
+}
+
+impl SpilledTranscript {
+    /// Read the rollout at `path` on from where `self` ends, as far as it is
+    /// written.
+    pub(crate) async fn read_on(mut self, path: &Path) -> io::Result<ReadOn> {
+        let mut read = Vec::new();
+        let mut items = OffsetItems::open_at(path, self.end).await?;
+        while let Some((offset, item)) = items.next().await? {
+            match item {
+                RolloutItem::ResponseItem(item) => {
+                    let rollout_ref = RolloutRef::new(offset, &item);
+                    self.items.push(rollout_ref.clone());
+                    read.push(ReadItem {
+                        rollout_ref,
+                        item,
+                        policy: self.policy,
+                    });
+                }
+                RolloutItem::TurnContext(context) => {
+                    if let Some(policy) = context.truncation_policy {
+                        self.policy = Some(policy.into());
+                    }
+                }
+                RolloutItem::Compacted(_) => self.checkpoints.clear(),
+                RolloutItem::Checkpoint(checkpoint) => {
+                    self.checkpoints.record(checkpoint.id, self.items.len());
+                }
+                RolloutItem::CheckpointRollback(rollback) => {
+                    if let Some((len, _)) = self.checkpoints.rollback(&rollback.id) {
+                        self.items = truncate_at_index(std::mem::take(&mut self.items), len);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.end = items.position();
+        items.finish();
+        Ok(ReadOn {
+            transcript: self,
+            read,
+        })
+    }
+}
+
+impl ReadOn {
+    /// Every item of the transcript: those just read as they are, the others
+    /// read back from the rollout at `path`.
+    pub(crate) async fn into_items(self, path: &Path) -> io::Result<Vec<ResponseItem>> {
+        let mut read: HashMap<LineOffset, ResponseItem> = self
+            .read
+            .into_iter()
+            .map(|read| (read.rollout_ref.offset, read.item))
+            .collect();
+        let spilled: Vec<RolloutRef> = self
+            .transcript
+            .items
+            .iter()
+            .filter(|rollout_ref| !read.contains_key(&rollout_ref.offset))
+            .cloned()
+            .collect();
+        let mut spilled = read_items(path, &spilled).await?.into_iter();
+        let mut items = Vec::with_capacity(self.transcript.items.len());
+        for rollout_ref in &self.transcript.items {
+            let item = match read.remove(&rollout_ref.offset) {
+                Some(item) => Some(item),
+                None => spilled.next(),
+            };
+            items.extend(item);
+        }
+        Ok(items)
+    }
+}
+
+/// The transcript items with the id of the turn that recorded each.
+async fn read_turn_items(path: &Path) -> io::Result<Vec<(Option<TurnId>, ResponseItem)>> {
+    let mut lines = RolloutItems::open(path).await?;
+    let mut items = Vec::new();
+    let mut turn_id = None;
+    // Checkpoints hold positions in the transcript rather than the history,
+    // and are dropped by compaction as the history drops them.
+    let mut checkpoints = Checkpoints::default();
+
+    while let Some(item) = lines.next().await? {
+        match item {
+            RolloutItem::ResponseItem(item) => items.push((turn_id, item)),
+            RolloutItem::TurnStarted(started) => turn_id = Some(started.turn_id),
+            RolloutItem::Compacted(_) => checkpoints.clear(),
+            RolloutItem::Checkpoint(checkpoint) => checkpoints.record(checkpoint.id, items.len()),
+            RolloutItem::CheckpointRollback(rollback) => {
+                if let Some((len, _)) = checkpoints.rollback(&rollback.id) {
+                    items.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+    lines.finish();
+
+    Ok(items)
+}
+
+/// The items of a rollout, read line by line with chunked items joined back.
+/// Lines that do not parse are skipped.
+struct RolloutItems {
+    lines: ChainLines,
+    chunks: ChunkAssembler,
+}
+
+impl RolloutItems {
+    async fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            lines: ChainLines::open(path).await?,
+            chunks: ChunkAssembler::default(),
+        })
+    }
+
+    async fn next(&mut self) -> io::Result<Option<RolloutItem>> {
+        while let Some(line) = self.lines.next_line().await? {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(trimmed) else {
+                continue;
+            };
+            if let Some(rollout_line) = self.chunks.push(rollout_line) {
+                return Ok(Some(rollout_line.item));
+            }
+        }
+        Ok(None)
+    }
+
+    fn finish(self) {
+        self.chunks.finish();
+    }
+}
+
+/// Like [`RolloutItems`], from any line on and with where each item starts.
+struct OffsetItems {
+    lines: OffsetLines,
+    chunks: ChunkAssembler,
+}
+
+impl OffsetItems {
+    async fn open_at(path: &Path, offset: LineOffset) -> io::Result<Self> {
+        Ok(Self {
+            lines: OffsetLines::open_at(path, offset).await?,
+            chunks: ChunkAssembler::default(),
+        })
+    }
+
+    fn position(&self) -> LineOffset {
+        self.lines.position()
+    }
+
+    /// Read on from the item starting at `offset`.
+    async fn seek(&mut self, offset: LineOffset) -> io::Result<()> {
+        if offset != self.lines.position() {
+            self.chunks = ChunkAssembler::default();
+        }
+        self.lines.seek(offset).await
+    }
+
+    async fn next(&mut self) -> io::Result<Option<(LineOffset, RolloutItem)>> {
+        let mut start = self.lines.position();
+        while let Some((offset, line)) = self.lines.next_line().await? {
+            let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(&line) else {
+                continue;
+            };
+            // A chunked item starts at its first part.
+            if !matches!(&rollout_line.item, RolloutItem::Chunked { part, .. } if *part > 0) {
+                start = offset;
+            }
+            if let Some(rollout_line) = self.chunks.push(rollout_line) {
+                return Ok(Some((start, rollout_line.item)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn finish(self) {
+        self.chunks.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_truncation::user_message_positions;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn lines(items: &[ResponseItem]) -> String {
+        items
+            .iter()
+            .map(|item| {
+                let line = RolloutLine {
+                    timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+                    correlation_id: None,
+                    item: RolloutItem::ResponseItem(item.clone()),
+                };
+                format!("{}\n", serde_json::to_string(&line).unwrap())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn spilled_transcript_reads_on_from_where_it_ends() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let first = vec![message("user", "u1"), message("assistant", "a1")];
+        std::fs::write(&path, lines(&first)).unwrap();
+        let read_on = SpilledTranscript::default().read_on(&path).await.unwrap();
+        assert_eq!(read_on.read.len(), 2);
+        let transcript = read_on.transcript;
+
+        let second = vec![message("user", "u2"), message("assistant", "a2")];
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, lines(&second).as_bytes()).unwrap();
+        let read_on = transcript.read_on(&path).await.unwrap();
+        let read: Vec<ResponseItem> = read_on.read.iter().map(|read| read.item.clone()).collect();
+        assert_eq!(read, second);
+
+        // The references cut at the user messages without being read back.
+        let full = read_transcript(&path).await.unwrap();
+        assert_eq!(
+            user_message_positions(&read_on.transcript.items),
+            user_message_positions(&full)
+        );
+        assert_eq!(read_on.into_items(&path).await.unwrap(), full);
+    }
+}
@@ -0,0 +1,1094 @@
+//! The task that appends a session's rollout to disk.
+//!
+//! [`RolloutRecorder`](super::RolloutRecorder) pushes commands onto an
+//! unbounded queue and returns immediately, so a turn never waits for the
+//! disk. The writer drains whatever is queued as one batch, appends it with a
+//! single write, and syncs the file as [`RolloutFsyncPolicy`] says. Flush and
+//! shutdown commands are barriers: they are acknowledged only once every item
+//! queued before them is written (and, for durable flushes, synced).
+//!
+//! When a write or sync fails the writer records the error and stops; every
+//! later command fails with it. The same happens when the rollout would grow
+//! past its hard cap. Before that, a batch that would take the current part
+//! past its size limit goes to a new part (see [`super::chain`]).
+//!
+//! A full disk or quota is waited out instead: the writer reports
+//! [`EventMsg::PersistenceDegraded`], holds later items in memory, up to
+//! [`RolloutLimits::max_overflow_bytes`], and retries every
+//! [`OVERFLOW_RETRY_INTERVAL`]. Once the disk takes them again it reports
+//! [`EventMsg::PersistenceRecovered`]. When the buffer fills, streaming
+//! events go before conversation items, oldest first. Barriers fail while
+//! items are held.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::event_filter::EventCategory;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::PersistenceDegradedEvent;
+use codex_protocol::protocol::PersistenceRecoveredEvent;
+use codex_protocol::protocol::RolloutContinuation;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::SessionMetaLine;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::warn;
+
+use super::chain::part_path;
+use super::chunked::split_line;
+use super::policy::is_persisted_response_item;
+use crate::git_info::collect_git_info;
+use crate::runtime_env::RuntimeEnv;
+
+/// Commands drained from the queue at once, at most.
+const MAX_BATCH_COMMANDS: usize = 256;
+
+/// Bytes held in memory while the disk is full, unless configured otherwise.
+const DEFAULT_MAX_OVERFLOW_BYTES: u64 = 32 * 1024 * 1024;
+
+/// How often a full disk is tried again.
+const OVERFLOW_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// When the rollout file is synced to disk. Items are always written to the
+/// file as soon as the writer gets to them; syncing makes them survive a
+/// crash of the machine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum RolloutFsyncPolicy {
+    /// After every recorded batch of items.
+    EveryItem,
+    /// At most `interval_ms` after an item is written.
+    Interval { interval_ms: u64 },
+    /// When a turn ends.
+    #[default]
+    TurnEnd,
+}
+
+pub(crate) enum RolloutCmd {
+    AddItems {
+        items: Vec<RolloutItem>,
+        correlation_id: Option<String>,
+    },
+    /// A turn ended; sync under [`RolloutFsyncPolicy::TurnEnd`].
+    TurnEnded,
+    /// Acknowledge once all prior items are written and, when `durable`,
+    /// synced.
+    Flush {
+        durable: bool,
+        ack: oneshot::Sender<()>,
+    },
+    /// Like a durable flush; the writer keeps serving later commands.
+    Shutdown { ack: oneshot::Sender<()> },
+}
+
+/// Where the writer puts the rollout.
+pub(crate) trait RolloutSink: Send + 'static {
+    /// Append `bytes` to the file.
+    fn append(&mut self, bytes: &[u8]) -> impl Future<Output = std::io::Result<()>> + Send;
+
+    /// Make everything appended so far durable.
+    fn sync(&mut self) -> impl Future<Output = std::io::Result<()>> + Send;
+
+    /// Append to the new part at `path` from now on.
+    fn continue_in(&mut self, _path: &Path) -> impl Future<Output = std::io::Result<()>> + Send {
+        async {
+            Err(IoError::new(
+                ErrorKind::Unsupported,
+                "this rollout cannot be split into parts",
+            ))
+        }
+    }
+}
+
+impl RolloutSink for tokio::fs::File {
+    async fn append(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.write_all(bytes).await?;
+        self.flush().await
+    }
+
+    async fn sync(&mut self) -> std::io::Result<()> {
+        self.sync_data().await
+    }
+
+    async fn continue_in(&mut self, path: &Path) -> std::io::Result<()> {
+        *self = tokio::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Limits on the lines and size of a rollout.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RolloutLimits {
+    /// See [`Config::rollout_max_line_bytes`](crate::config::Config::rollout_max_line_bytes).
+    pub(crate) max_line_bytes: Option<usize>,
+    /// See [`Config::max_rollout_bytes`](crate::config::Config::max_rollout_bytes).
+    pub(crate) max_part_bytes: Option<u64>,
+    /// See [`Config::rollout_hard_cap_bytes`](crate::config::Config::rollout_hard_cap_bytes).
+    pub(crate) hard_cap_bytes: Option<u64>,
+    /// Bytes held in memory while the disk is full; defaults to
+    /// [`DEFAULT_MAX_OVERFLOW_BYTES`].
+    pub(crate) max_overflow_bytes: Option<u64>,
+}
+
+/// How the writer reports on itself to the recorder and the session.
+pub(crate) struct WriterStatus {
+    /// Why the writer stopped, see [`RolloutWriter::failed`].
+    pub(crate) failure: watch::Sender<Option<String>>,
+    /// Why items are held in memory, while they are.
+    pub(crate) degraded: watch::Sender<Option<String>>,
+    /// [`EventMsg::PersistenceDegraded`] and
+    /// [`EventMsg::PersistenceRecovered`], for the session to send.
+    pub(crate) events: mpsc::UnboundedSender<EventMsg>,
+}
+
+/// One rollout item, encoded.
+struct EncodedItem {
+    /// Its lines, each ending in a newline.
+    bytes: Vec<u8>,
+    /// Whether the overflow buffer drops it first, see [`is_expendable`].
+    expendable: bool,
+}
+
+/// Items held in memory while the disk is full, oldest first.
+struct Overflow {
+    items: VecDeque<EncodedItem>,
+    bytes: u64,
+    max_bytes: u64,
+    retry_at: Instant,
+    dropped_events: u64,
+    dropped_items: u64,
+}
+
+impl Overflow {
+    fn new(max_bytes: u64) -> Self {
+        Self {
+            items: VecDeque::new(),
+            bytes: 0,
+            max_bytes,
+            retry_at: Instant::now() + OVERFLOW_RETRY_INTERVAL,
+            dropped_events: 0,
+            dropped_items: 0,
+        }
+    }
+
+    /// Hold `items`, then drop the oldest expendable items, and after them
+    /// the oldest of any kind, until at most `max_bytes` are held.
+    fn extend(&mut self, items: Vec<EncodedItem>) {
+        for item in items {
+            self.bytes += item.bytes.len() as u64;
+            self.items.push_back(item);
+        }
+        while self.bytes > self.max_bytes {
+            let index = self
+                .items
+                .iter()
+                .position(|item| item.expendable)
+                .unwrap_or(0);
+            let Some(item) = self.items.remove(index) else {
+                break;
+            };
+            self.bytes -= item.bytes.len() as u64;
+            if item.expendable {
+                self.dropped_events += 1;
+            } else {
+                self.dropped_items += 1;
+            }
+        }
+    }
+}
+
+/// Where the writer appends in the rollout's chain of parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChainPosition {
+    /// The first part, which names the rollout.
+    pub(crate) head: PathBuf,
+    /// The part appended to, counted from 1.
+    pub(crate) part: u32,
+    pub(crate) part_bytes: u64,
+    /// Bytes in all parts together.
+    pub(crate) total_bytes: u64,
+}
+
+impl ChainPosition {
+    /// The start of the new rollout at `head`.
+    pub(crate) fn new(head: PathBuf) -> Self {
+        Self {
+            head,
+            part: 1,
+            part_bytes: 0,
+            total_bytes: 0,
+        }
+    }
+}
+
+pub(crate) struct RolloutWriter<S> {
+    sink: S,
+    limits: RolloutLimits,
+    position: ChainPosition,
+    fsync: RolloutFsyncPolicy,
+    /// Encoded items of the current batch.
+    pending: Vec<EncodedItem>,
+    /// Items written to disk so far.
+    persisted_items: u64,
+    /// Items held while the disk is full.
+    overflow: Option<Overflow>,
+    /// Whether anything was appended since the last sync.
+    unsynced: bool,
+    status: WriterStatus,
+    /// Stamps lines and numbers chunked runs.
+    env: RuntimeEnv,
+}
+
+impl<S: RolloutSink> RolloutWriter<S> {
+    pub(crate) fn new(
+        sink: S,
+        limits: RolloutLimits,
+        position: ChainPosition,
+        fsync: RolloutFsyncPolicy,
+        status: WriterStatus,
+        env: RuntimeEnv,
+    ) -> Self {
+        Self {
+            sink,
+            limits,
+            position,
+            fsync,
+            pending: Vec::new(),
+            persisted_items: 0,
+            overflow: None,
+            unsynced: false,
+            status,
+            env,
+        }
+    }
+
+    /// Serve `rx` until every sender is gone or a write fails. A session
+    /// `meta` is written first, with git info collected from `cwd`.
+    pub(crate) async fn run(
+        mut self,
+        mut rx: mpsc::UnboundedReceiver<RolloutCmd>,
+        meta: Option<SessionMeta>,
+        cwd: PathBuf,
+    ) {
+        if let Err(err) = self.serve(&mut rx, meta, cwd).await {
+            warn!("rollout writer stopped: {err}");
+        }
+    }
+
+    async fn serve(
+        &mut self,
+        rx: &mut mpsc::UnboundedReceiver<RolloutCmd>,
+        meta: Option<SessionMeta>,
+        cwd: PathBuf,
+    ) -> std::io::Result<()> {
+        if let Some(meta) = meta {
+            let git = collect_git_info(&cwd).await;
+            self.push(
+                RolloutItem::SessionMeta(SessionMetaLine { meta, git }),
+                None,
+            )?;
+            self.write_pending().await?;
+        }
+
+        let mut sync_at: Option<Instant> = None;
+        loop {
+            let retry_at = self.overflow.as_ref().map(|overflow| overflow.retry_at);
+            let cmd = tokio::select! {
+                cmd = rx.recv() => cmd,
+                () = sleep_until(sync_at) => {
+                    sync_at = None;
+                    self.sync().await?;
+                    continue;
+                }
+                () = sleep_until(retry_at) => {
+                    self.retry_overflow().await?;
+                    continue;
+                }
+            };
+            let Some(cmd) = cmd else {
+                break;
+            };
+
+            let mut turn_ended = false;
+            let mut next = Some(cmd);
+            let mut taken = 0;
+            while let Some(cmd) = next.take() {
+                match cmd {
+                    RolloutCmd::AddItems {
+                        items,
+                        correlation_id,
+                    } => {
+                        for item in items {
+                            if is_persisted_response_item(&item) {
+                                self.push(item, correlation_id.clone())?;
+                            }
+                        }
+                        if self.fsync == RolloutFsyncPolicy::EveryItem {
+                            self.sync().await?;
+                        }
+                    }
+                    RolloutCmd::TurnEnded => turn_ended = true,
+                    RolloutCmd::Flush { durable, ack } => {
+                        self.retry_overflow().await?;
+                        if durable {
+                            self.sync().await?;
+                        } else {
+                            self.write_pending().await?;
+                        }
+                        // Items held in memory are not written yet.
+                        if self.overflow.is_none() {
+                            let _ = ack.send(());
+                        }
+                    }
+                    RolloutCmd::Shutdown { ack } => {
+                        self.retry_overflow().await?;
+                        self.sync().await?;
+                        if self.overflow.is_none() {
+                            let _ = ack.send(());
+                        }
+                    }
+                }
+                taken += 1;
+                if taken < MAX_BATCH_COMMANDS {
+                    next = rx.try_recv().ok();
+                }
+            }
+
+            self.write_pending().await?;
+            match self.fsync {
+                RolloutFsyncPolicy::EveryItem => {}
+                RolloutFsyncPolicy::Interval { interval_ms } => {
+                    if !self.unsynced {
+                        sync_at = None;
+                    } else if sync_at.is_none() {
+                        sync_at = Some(Instant::now() + Duration::from_millis(interval_ms));
+                    }
+                }
+                RolloutFsyncPolicy::TurnEnd => {
+                    if turn_ended {
+                        self.sync().await?;
+                    }
+                }
+            }
+        }
+
+        self.retry_overflow().await?;
+        if let Some(overflow) = &self.overflow {
+            warn!(
+                "{} rollout items held in memory were never written",
+                overflow.items.len()
+            );
+        }
+        self.sync().await
+    }
+
+    /// Encode `item` into the current batch, splitting lines longer than
+    /// `max_line_bytes` into chunks.
+    fn push(
+        &mut self,
+        rollout_item: RolloutItem,
+        correlation_id: Option<String>,
+    ) -> std::io::Result<()> {
+        self.encode(rollout_item, correlation_id)
+            .map_err(|err| self.failed(err))
+    }
+
+    fn encode(
+        &mut self,
+        rollout_item: RolloutItem,
+        correlation_id: Option<String>,
+    ) -> std::io::Result<()> {
+        let expendable = is_expendable(&rollout_item);
+        let line = Self::line(self.env.now(), rollout_item, correlation_id)?;
+        let json = serde_json::to_string(&line)?;
+        let mut bytes = Vec::new();
+        match self
+            .limits
+            .max_line_bytes
+            .and_then(|max_line_bytes| split_line(&json, max_line_bytes, &self.env))
+        {
+            Some(parts) => {
+                for item in parts {
+                    let part = RolloutLine {
+                        timestamp: line.timestamp.clone(),
+                        correlation_id: line.correlation_id.clone(),
+                        item,
+                    };
+                    push_json(&mut bytes, &serde_json::to_string(&part)?);
+                }
+            }
+            None => push_json(&mut bytes, &json),
+        }
+        self.pending.push(EncodedItem { bytes, expendable });
+        Ok(())
+    }
+
+    fn line(
+        now: SystemTime,
+        rollout_item: RolloutItem,
+        correlation_id: Option<String>,
+    ) -> std::io::Result<RolloutLine> {
+        let timestamp_format: &[FormatItem] = format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+        );
+        let timestamp = OffsetDateTime::from(now)
+            .format(timestamp_format)
+            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+
+        Ok(RolloutLine {
+            timestamp,
+            correlation_id,
+            item: rollout_item,
+        })
+    }
+
+    async fn write_pending(&mut self) -> std::io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let items = std::mem::take(&mut self.pending);
+        // Nothing goes to disk ahead of the items held in memory.
+        if let Some(overflow) = &mut self.overflow {
+            overflow.extend(items);
+            return Ok(());
+        }
+        let bytes: Vec<u8> = items.iter().flat_map(|item| item.bytes.clone()).collect();
+        match self.write_batch(&bytes).await {
+            Ok(()) => {
+                self.persisted_items += items.len() as u64;
+                Ok(())
+            }
+            Err(err) if is_out_of_space(&err) => {
+                self.degrade(&err, items);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Start holding items in memory, with `items`, because of `err`.
+    fn degrade(&mut self, err: &IoError, items: Vec<EncodedItem>) {
+        warn!("rollout held in memory until the disk has room: {err}");
+        self.status.degraded.send_replace(Some(err.to_string()));
+        let _ = self
+            .status
+            .events
+            .send(EventMsg::PersistenceDegraded(PersistenceDegradedEvent {
+                reason: err.to_string(),
+                last_persisted_item: (self.persisted_items > 0).then_some(self.persisted_items),
+            }));
+        let mut overflow = Overflow::new(
+            self.limits
+                .max_overflow_bytes
+                .unwrap_or(DEFAULT_MAX_OVERFLOW_BYTES),
+        );
+        overflow.extend(items);
+        self.overflow = Some(overflow);
+    }
+
+    /// Write the items held in memory, if the disk takes them now.
+    async fn retry_overflow(&mut self) -> std::io::Result<()> {
+        let Some(overflow) = &self.overflow else {
+            return Ok(());
+        };
+        // The append that failed may have left part of a line behind; a
+        // newline ends it, and readers skip it like any line that does not
+        // parse.
+        let mut bytes = vec![b'\n'];
+        for item in &overflow.items {
+            bytes.extend_from_slice(&item.bytes);
+        }
+        match self.write_batch(&bytes).await {
+            Ok(()) => {}
+            Err(err) if is_out_of_space(&err) => {
+                if let Some(overflow) = &mut self.overflow {
+                    overflow.retry_at = Instant::now() + OVERFLOW_RETRY_INTERVAL;
+                }
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        }
+        let Some(overflow) = self.overflow.take() else {
+            return Ok(());
+        };
+        let flushed_items = overflow.items.len() as u64;
+        self.persisted_items += flushed_items;
+        self.status.degraded.send_replace(None);
+        let _ =
+            self.status
+                .events
+                .send(EventMsg::PersistenceRecovered(PersistenceRecoveredEvent {
+                    flushed_items,
+                    dropped_events: overflow.dropped_events,
+                    dropped_items: overflow.dropped_items,
+                }));
+        Ok(())
+    }
+
+    /// Append `bytes` within the hard cap, in a new part when they would take
+    /// the current one past its limit.
+    async fn write_batch(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let len = bytes.len() as u64;
+        if let Some(hard_cap_bytes) = self.limits.hard_cap_bytes
+            && self.position.total_bytes + len > hard_cap_bytes
+        {
+            return Err(self.failed(IoError::other(format!(
+                "the rollout reached its hard cap of {hard_cap_bytes} bytes"
+            ))));
+        }
+        // A batch always goes to a single part, so a part holds at least one
+        // batch however large it is.
+        if let Some(max_part_bytes) = self.limits.max_part_bytes
+            && self.position.part_bytes > 0
+            && self.position.part_bytes + len > max_part_bytes
+        {
+            self.rotate().await?;
+        }
+        self.append(bytes).await
+    }
+
+    /// End the current part with a footer naming the next one, and continue
+    /// in that part after a header naming the current one.
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        let current = part_path(&self.position.head, self.position.part);
+        let next = part_path(&self.position.head, self.position.part + 1);
+        let footer = self.link_line(RolloutItem::ContinuedIn(continuation(&next)))?;
+        self.append(&footer).await?;
+        // The footer is durable before anything goes to the next part.
+        if let Err(err) = self.sink.sync().await {
+            return Err(self.failed(err));
+        }
+        self.unsynced = false;
+        if let Err(err) = self.sink.continue_in(&next).await {
+            return Err(self.failed(err));
+        }
+        self.position.part += 1;
+        self.position.part_bytes = 0;
+        let header = self.link_line(RolloutItem::ContinuedFrom(continuation(&current)))?;
+        self.append(&header).await
+    }
+
+    fn link_line(&self, item: RolloutItem) -> std::io::Result<Vec<u8>> {
+        let mut json = Self::line(self.env.now(), item, None)
+            .and_then(|line| Ok(serde_json::to_vec(&line)?))
+            .map_err(|err| self.failed(err))?;
+        json.push(b'\n');
+        Ok(json)
+    }
+
+    /// Append `bytes`. Running out of space is not a failure, see
+    /// [`Self::write_pending`].
+    async fn append(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if let Err(err) = self.sink.append(bytes).await {
+            if is_out_of_space(&err) {
+                return Err(err);
+            }
+            return Err(self.failed(err));
+        }
+        let len = bytes.len() as u64;
+        self.position.part_bytes += len;
+        self.position.total_bytes += len;
+        self.unsynced = true;
+        Ok(())
+    }
+
+    async fn sync(&mut self) -> std::io::Result<()> {
+        self.write_pending().await?;
+        if !self.unsynced {
+            return Ok(());
+        }
+        if let Err(err) = self.sink.sync().await {
+            return Err(self.failed(err));
+        }
+        self.unsynced = false;
+        Ok(())
+    }
+
+    /// Publish `err` before the queue and any unacknowledged barriers are
+    /// dropped, so waiters see why.
+    fn failed(&self, err: IoError) -> IoError {
+        self.status.failure.send_replace(Some(err.to_string()));
+        err
+    }
+}
+
+fn push_json(bytes: &mut Vec<u8>, json: &str) {
+    bytes.extend_from_slice(json.as_bytes());
+    bytes.push(b'\n');
+}
+
+/// Streaming events, which the overflow buffer drops before conversation
+/// items and the events that matter for a resume.
+fn is_expendable(item: &RolloutItem) -> bool {
+    matches!(
+        item,
+        RolloutItem::EventMsg(msg) if matches!(
+            EventCategory::of(msg),
+            EventCategory::Reasoning | EventCategory::ToolDetail | EventCategory::Metrics
+        )
+    )
+}
+
+/// Whether `err` says the disk or the quota is full, which the writer waits
+/// out instead of stopping.
+fn is_out_of_space(err: &IoError) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded
+    )
+}
+
+/// Sleep until `deadline`, or forever without one.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Names the part at `path` from the one next to it.
+fn continuation(path: &Path) -> RolloutContinuation {
+    RolloutContinuation {
+        file: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::protocol::TokenCountEvent;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::rollout::RolloutRecorder;
+
+    const DISK_LATENCY: Duration = Duration::from_secs(1);
+
+    #[derive(Default)]
+    struct Disk {
+        written: Vec<u8>,
+        /// Length of `written` as of the last sync.
+        durable: usize,
+        appends: usize,
+    }
+
+    /// A disk on which every append and sync takes [`DISK_LATENCY`].
+    #[derive(Clone, Default)]
+    struct SlowDisk(Arc<Mutex<Disk>>);
+
+    impl SlowDisk {
+        fn durable_lines(&self) -> Vec<String> {
+            let disk = self.0.lock().unwrap();
+            String::from_utf8_lossy(&disk.written[..disk.durable])
+                .lines()
+                .map(|text| {
+                    let line: RolloutLine = serde_json::from_str(text).unwrap();
+                    let RolloutItem::ResponseItem(ResponseItem::Message { content, .. }) =
+                        line.item
+                    else {
+                        panic!("unexpected rollout line {text}");
+                    };
+                    let [ContentItem::InputText { text }] = content.as_slice() else {
+                        panic!("unexpected content {content:?}");
+                    };
+                    text.clone()
+                })
+                .collect()
+        }
+    }
+
+    impl RolloutSink for SlowDisk {
+        async fn append(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+            tokio::time::sleep(DISK_LATENCY).await;
+            let mut disk = self.0.lock().unwrap();
+            disk.written.extend_from_slice(bytes);
+            disk.appends += 1;
+            Ok(())
+        }
+
+        async fn sync(&mut self) -> std::io::Result<()> {
+            tokio::time::sleep(DISK_LATENCY).await;
+            let mut disk = self.0.lock().unwrap();
+            disk.durable = disk.written.len();
+            Ok(())
+        }
+    }
+
+    struct FullDisk;
+
+    impl RolloutSink for FullDisk {
+        async fn append(&mut self, _bytes: &[u8]) -> std::io::Result<()> {
+            Err(IoError::other("no space left on device"))
+        }
+
+        async fn sync(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A disk that rejects appends while `full` is set.
+    #[derive(Clone, Default)]
+    struct FillingDisk(Arc<Mutex<(bool, Vec<u8>)>>);
+
+    impl FillingDisk {
+        fn set_full(&self, full: bool) {
+            self.0.lock().unwrap().0 = full;
+        }
+
+        /// The message texts written, skipping the lines that do not parse.
+        fn messages(&self) -> Vec<String> {
+            String::from_utf8_lossy(&self.0.lock().unwrap().1)
+                .lines()
+                .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+                .filter_map(|line| match line.item {
+                    RolloutItem::ResponseItem(ResponseItem::Message { content, .. }) => {
+                        match content.as_slice() {
+                            [ContentItem::InputText { text }] => Some(text.clone()),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    impl RolloutSink for FillingDisk {
+        async fn append(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+            let mut disk = self.0.lock().unwrap();
+            if disk.0 {
+                return Err(IoError::from(ErrorKind::StorageFull));
+            }
+            disk.1.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        async fn sync(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The path of a rollout part and its bytes.
+    type Part = (PathBuf, Vec<u8>);
+
+    /// A disk keeping each part of the rollout apart.
+    #[derive(Clone, Default)]
+    struct PartedDisk(Arc<Mutex<Vec<Part>>>);
+
+    impl PartedDisk {
+        /// Each part with its lines: message texts, and `-> file` or `<- file`
+        /// for the links to the next and previous parts.
+        fn parts(&self) -> Vec<(String, Vec<String>)> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(path, written)| {
+                    let items = String::from_utf8_lossy(written)
+                        .lines()
+                        .map(
+                            |line| match serde_json::from_str::<RolloutLine>(line).unwrap().item {
+                                RolloutItem::ContinuedIn(next) => format!("-> {}", next.file),
+                                RolloutItem::ContinuedFrom(previous) => {
+                                    format!("<- {}", previous.file)
+                                }
+                                RolloutItem::ResponseItem(ResponseItem::Message {
+                                    content,
+                                    ..
+                                }) => match content.as_slice() {
+                                    [ContentItem::InputText { text }] => text.clone(),
+                                    _ => panic!("unexpected content {content:?}"),
+                                },
+                                item => panic!("unexpected rollout item {item:?}"),
+                            },
+                        )
+                        .collect();
+                    (path.display().to_string(), items)
+                })
+                .collect()
+        }
+    }
+
+    impl RolloutSink for PartedDisk {
+        async fn append(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+            let mut parts = self.0.lock().unwrap();
+            if parts.is_empty() {
+                parts.push((PathBuf::from("rollout.jsonl"), Vec::new()));
+            }
+            if let Some((_, written)) = parts.last_mut() {
+                written.extend_from_slice(bytes);
+            }
+            Ok(())
+        }
+
+        async fn sync(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        async fn continue_in(&mut self, path: &Path) -> std::io::Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), Vec::new()));
+            Ok(())
+        }
+    }
+
+    fn message(text: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn recording_does_not_wait_for_a_slow_disk() {
+        let disk = SlowDisk::default();
+        let recorder = RolloutRecorder::with_sink(
+            disk.clone(),
+            PathBuf::from("rollout.jsonl"),
+            RolloutFsyncPolicy::TurnEnd,
+        );
+
+        let start = Instant::now();
+        let expected: Vec<String> = (0..20).map(|i| format!("item {i}")).collect();
+        for text in &expected {
+            recorder.record_items(&[message(text)]).await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        recorder.flush().await.unwrap();
+        assert!(start.elapsed() >= 2 * DISK_LATENCY);
+        assert_eq!(disk.durable_lines(), expected);
+        // Everything queued by then went out in one append.
+        assert_eq!(disk.0.lock().unwrap().appends, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn policies_sync_without_a_flush() {
+        let fsyncs = [
+            RolloutFsyncPolicy::EveryItem,
+            RolloutFsyncPolicy::Interval { interval_ms: 500 },
+        ];
+        for fsync in fsyncs {
+            let disk = SlowDisk::default();
+            let recorder =
+                RolloutRecorder::with_sink(disk.clone(), PathBuf::from("rollout.jsonl"), fsync);
+            recorder.record_items(&[message("hello")]).await.unwrap();
+            tokio::time::sleep(3 * DISK_LATENCY).await;
+            assert_eq!(disk.durable_lines(), vec!["hello".to_string()], "{fsync:?}");
+        }
+
+        let disk = SlowDisk::default();
+        let recorder = RolloutRecorder::with_sink(
+            disk.clone(),
+            PathBuf::from("rollout.jsonl"),
+            RolloutFsyncPolicy::TurnEnd,
+        );
+        recorder.record_items(&[message("hello")]).await.unwrap();
+        tokio::time::sleep(3 * DISK_LATENCY).await;
+        assert_eq!(disk.durable_lines(), Vec::<String>::new());
+        recorder.turn_ended();
+        tokio::time::sleep(3 * DISK_LATENCY).await;
+        assert_eq!(disk.durable_lines(), vec!["hello".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn batches_past_the_part_limit_continue_in_a_new_part() {
+        let disk = PartedDisk::default();
+        let line_bytes = serde_json::to_string(
+            &RolloutWriter::<PartedDisk>::line(SystemTime::now(), message("item 0"), None).unwrap(),
+        )
+        .unwrap()
+        .len() as u64
+            + 1;
+        let recorder = RolloutRecorder::with_limits(
+            disk.clone(),
+            PathBuf::from("rollout.jsonl"),
+            RolloutLimits {
+                max_part_bytes: Some(3 * line_bytes),
+                ..Default::default()
+            },
+            RolloutFsyncPolicy::TurnEnd,
+        );
+        for index in 0..7 {
+            recorder
+                .record_items(&[message(&format!("item {index}"))])
+                .await
+                .unwrap();
+            recorder.flush().await.unwrap();
+        }
+
+        // Links are shorter than the messages, so each later part holds its
+        // header and two messages.
+        assert_eq!(
+            disk.parts(),
+            vec![
+                (
+                    "rollout.jsonl".to_string(),
+                    vec![
+                        "item 0".to_string(),
+                        "item 1".to_string(),
+                        "item 2".to_string(),
+                        "-> rollout.part2.jsonl".to_string(),
+                    ]
+                ),
+                (
+                    "rollout.part2.jsonl".to_string(),
+                    vec![
+                        "<- rollout.jsonl".to_string(),
+                        "item 3".to_string(),
+                        "item 4".to_string(),
+                        "-> rollout.part3.jsonl".to_string(),
+                    ]
+                ),
+                (
+                    "rollout.part3.jsonl".to_string(),
+                    vec![
+                        "<- rollout.part2.jsonl".to_string(),
+                        "item 5".to_string(),
+                        "item 6".to_string(),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn the_hard_cap_stops_recording() {
+        let recorder = RolloutRecorder::with_limits(
+            PartedDisk::default(),
+            PathBuf::from("rollout.jsonl"),
+            RolloutLimits {
+                hard_cap_bytes: Some(64),
+                ..Default::default()
+            },
+            RolloutFsyncPolicy::TurnEnd,
+        );
+        recorder.record_items(&[message("hello")]).await.unwrap();
+
+        assert_eq!(
+            recorder.writer_failure().await.as_deref(),
+            Some("the rollout reached its hard cap of 64 bytes")
+        );
+        assert!(recorder.flush().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn writer_failure_fails_later_commands() {
+        let recorder = RolloutRecorder::with_sink(
+            FullDisk,
+            PathBuf::from("rollout.jsonl"),
+            RolloutFsyncPolicy::TurnEnd,
+        );
+        recorder.record_items(&[message("hello")]).await.unwrap();
+
+        assert_eq!(
+            recorder.writer_failure().await.as_deref(),
+            Some("no space left on device")
+        );
+        let err = recorder.flush().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rollout writer failed: no space left on device"
+        );
+        assert!(recorder.record_items(&[message("again")]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn a_full_disk_holds_items_in_memory_until_it_has_room() {
+        let disk = FillingDisk::default();
+        let line_bytes = serde_json::to_string(
+            &RolloutWriter::<FillingDisk>::line(SystemTime::now(), message("b"), None).unwrap(),
+        )
+        .unwrap()
+        .len() as u64
+            + 1;
+        let recorder = RolloutRecorder::with_limits(
+            disk.clone(),
+            PathBuf::from("rollout.jsonl"),
+            RolloutLimits {
+                max_overflow_bytes: Some(2 * line_bytes),
+                ..Default::default()
+            },
+            RolloutFsyncPolicy::TurnEnd,
+        );
+        let mut events = recorder.take_persistence_events().unwrap();
+        recorder.record_items(&[message("a")]).await.unwrap();
+        recorder.flush().await.unwrap();
+
+        disk.set_full(true);
+        let token_count = RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+            info: None,
+            rate_limits: None,
+        }));
+        recorder.record_items(&[token_count]).await.unwrap();
+        for text in ["b", "c", "d"] {
+            recorder.record_items(&[message(text)]).await.unwrap();
+        }
+        let err = recorder.flush().await.unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("rollout held in memory until the disk has room:"),
+            "{err}"
+        );
+        let Some(EventMsg::PersistenceDegraded(degraded)) = events.recv().await else {
+            panic!("expected the writer to report the full disk");
+        };
+        assert_eq!(
+            degraded,
+            PersistenceDegradedEvent {
+                reason: IoError::from(ErrorKind::StorageFull).to_string(),
+                last_persisted_item: Some(1),
+            }
+        );
+
+        // The token count goes first, then the oldest message.
+        disk.set_full(false);
+        recorder.flush().await.unwrap();
+        let Some(EventMsg::PersistenceRecovered(recovered)) = events.recv().await else {
+            panic!("expected the writer to report the recovery");
+        };
+        assert_eq!(
+            recovered,
+            PersistenceRecoveredEvent {
+                flushed_items: 2,
+                dropped_events: 1,
+                dropped_items: 1,
+            }
+        );
+        recorder.record_items(&[message("e")]).await.unwrap();
+        recorder.flush().await.unwrap();
+        assert_eq!(disk.messages(), vec!["a", "c", "d", "e"]);
+    }
+}
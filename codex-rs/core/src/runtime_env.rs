@@ -0,0 +1,187 @@
+//! The clock and id generator sessions read, so embedders can make runs
+//! reproducible.
+//!
+//! A [`RuntimeEnv`] is set on a [`ConversationManager`] and shared by every
+//! conversation it spawns. Conversation ids, rollout and event log
+//! timestamps, rollout file names, the ids of queued inputs and user shell
+//! commands, the current time told to the model and the duration reported
+//! when a session ends all come from it. The default reads the system clock
+//! and generates random ids. With the `test-support` feature,
+//! [`RuntimeEnv::deterministic`] stops the clock at a fixed instant and
+//! numbers ids from 1, so a scripted session records the same rollout every
+//! time it runs.
+//!
+//! [`ConversationManager`]: crate::ConversationManager
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::ConversationId;
+use uuid::Uuid;
+
+/// Wall-clock time as sessions see it.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// Ids sessions give to what they create.
+pub trait IdGenerator: Send + Sync {
+    /// The id of a new conversation. Later ids sort after earlier ones.
+    fn conversation_id(&self) -> ConversationId;
+
+    /// A unique id for anything else, such as a queued input.
+    fn next_id(&self) -> String;
+}
+
+/// Reads [`SystemTime::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Time-ordered conversation ids and random ids otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn conversation_id(&self) -> ConversationId {
+        ConversationId::new()
+    }
+
+    fn next_id(&self) -> String {
+        Uuid::new_v4().to_string()
+    }
+}
+
+/// The [`Clock`] and [`IdGenerator`] of the conversations a manager spawns.
+#[derive(Clone)]
+pub struct RuntimeEnv {
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl Default for RuntimeEnv {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock), Arc::new(RandomIds))
+    }
+}
+
+impl fmt::Debug for RuntimeEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeEnv").finish_non_exhaustive()
+    }
+}
+
+impl RuntimeEnv {
+    pub fn new(clock: Arc<dyn Clock>, ids: Arc<dyn IdGenerator>) -> Self {
+        Self { clock, ids }
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// Time elapsed since `earlier`, zero when the clock went back.
+    pub fn since(&self, earlier: SystemTime) -> Duration {
+        self.now().duration_since(earlier).unwrap_or_default()
+    }
+
+    pub fn conversation_id(&self) -> ConversationId {
+        self.ids.conversation_id()
+    }
+
+    pub fn next_id(&self) -> String {
+        self.ids.next_id()
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+mod deterministic {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    /// Where the clock of [`RuntimeEnv::deterministic`] stands:
+    /// 2030-01-01T00:00:00Z.
+    pub const DETERMINISTIC_EPOCH: Duration = Duration::from_secs(1_893_456_000);
+
+    /// A clock that always reads the same instant.
+    #[derive(Debug, Clone, Copy)]
+    pub struct FixedClock(pub SystemTime);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> SystemTime {
+            self.0
+        }
+    }
+
+    /// Ids numbered from 1, conversation ids included, formatted as UUIDs.
+    #[derive(Debug, Default)]
+    pub struct CounterIds(AtomicU64);
+
+    impl CounterIds {
+        fn next(&self) -> Uuid {
+            Uuid::from_u128(u128::from(self.0.fetch_add(1, Ordering::Relaxed) + 1))
+        }
+    }
+
+    impl IdGenerator for CounterIds {
+        fn conversation_id(&self) -> ConversationId {
+            ConversationId::from(self.next())
+        }
+
+        fn next_id(&self) -> String {
+            self.next().to_string()
+        }
+    }
+
+    impl RuntimeEnv {
+        /// A [`FixedClock`] at [`DETERMINISTIC_EPOCH`] and fresh
+        /// [`CounterIds`].
+        pub fn deterministic() -> Self {
+            Self::new(
+                Arc::new(FixedClock(SystemTime::UNIX_EPOCH + DETERMINISTIC_EPOCH)),
+                Arc::new(CounterIds::default()),
+            )
+        }
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+pub use deterministic::CounterIds;
+#[cfg(any(test, feature = "test-support"))]
+pub use deterministic::DETERMINISTIC_EPOCH;
+#[cfg(any(test, feature = "test-support"))]
+pub use deterministic::FixedClock;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn deterministic_envs_repeat_the_same_ids_and_time() {
+        let run = || {
+            let env = RuntimeEnv::deterministic();
+            (
+                env.conversation_id().to_string(),
+                env.next_id(),
+                env.now(),
+                env.since(env.now()),
+            )
+        };
+        let first = run();
+        assert_eq!(first, run());
+        assert_eq!(first.0, "00000000-0000-0000-0000-000000000001");
+        assert_eq!(first.1, "00000000-0000-0000-0000-000000000002");
+        assert_eq!(first.2, SystemTime::UNIX_EPOCH + DETERMINISTIC_EPOCH);
+        assert_eq!(first.3, Duration::ZERO);
+    }
+}
@@ -0,0 +1,158 @@
+//! Safe mode: one switch for working in a workspace that is not trusted.
+//!
+//! Turning it on replaces three policies at once, from the next turn on: the
+//! approval policy becomes `untrusted`, commands run in a read-only sandbox,
+//! and the tools that reach the network (web search and every MCP tool) are
+//! denied. The replaced policies are kept and restored when safe mode is
+//! turned off, which the user has to approve like any other escalation. While
+//! it is on, turn and session overrides of these policies are ignored, rules
+//! granted for the project approve nothing, and approvals save no new rule.
+//!
+//! Each switch is recorded in the rollout as an
+//! [`EventMsg::SafeModeChanged`] with the policies before and after it, and a
+//! conversation resumed in safe mode stays in it.
+
+use std::path::PathBuf;
+
+use codex_protocol::parse_command::ParsedCommand;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecApprovalRequestEvent;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SafeModeChangedEvent;
+use codex_protocol::protocol::SafeModePolicies;
+use codex_protocol::protocol::SandboxPolicy;
+
+use crate::parse_command::shlex_join;
+
+/// Built-in tools that reach the network. MCP tools are denied by name too.
+const NETWORK_TOOLS: [&str; 4] = [
+    "web_search",
+    "list_mcp_resources",
+    "list_mcp_resource_templates",
+    "read_mcp_resource",
+];
+
+/// Command shown in the approval request for leaving safe mode.
+const EXIT_COMMAND: [&str; 2] = ["safe_mode", "off"];
+
+/// The policies of safe mode, for a conversation currently using `current`
+/// with the MCP tools `mcp_tools`.
+pub(crate) fn safe_policies(
+    current: &SafeModePolicies,
+    mcp_tools: impl IntoIterator<Item = String>,
+) -> SafeModePolicies {
+    let mut tool_policy = current.tool_policy.clone();
+    let denied = NETWORK_TOOLS
+        .iter()
+        .map(ToString::to_string)
+        .chain(mcp_tools);
+    for name in denied {
+        if !tool_policy.deny.contains(&name) {
+            tool_policy.deny.push(name);
+        }
+    }
+    SafeModePolicies {
+        approval_policy: AskForApproval::UnlessTrusted,
+        sandbox_policy: SandboxPolicy::new_read_only_policy(),
+        tool_policy,
+    }
+}
+
+/// The last switch to safe mode of a resumed conversation, if it was still
+/// in safe mode.
+pub(crate) fn last_recorded_safe_mode(items: &[RolloutItem]) -> Option<SafeModeChangedEvent> {
+    items
+        .iter()
+        .rev()
+        .find_map(|item| match item {
+            RolloutItem::EventMsg(EventMsg::SafeModeChanged(event)) => Some(event.clone()),
+            _ => None,
+        })
+        .filter(|event| event.enabled)
+}
+
+/// The approval request for leaving safe mode and going back to `restored`.
+pub(crate) fn exit_approval_request(
+    sub_id: &str,
+    cwd: PathBuf,
+    restored: &SafeModePolicies,
+) -> ExecApprovalRequestEvent {
+    let command: Vec<String> = EXIT_COMMAND.iter().map(ToString::to_string).collect();
+    let cmd = shlex_join(&command);
+    ExecApprovalRequestEvent {
+        call_id: format!("safe-mode-off-{sub_id}"),
+        turn_id: sub_id.to_string(),
+        command,
+        cwd,
+        reason: Some(format!(
+            "Leave safe mode: approval policy back to `{}`, sandbox back to `{}`, network tools allowed again.",
+            restored.approval_policy, restored.sandbox_policy
+        )),
+        proposed_execpolicy_amendment: None,
+        parsed_cmd: vec![ParsedCommand::Unknown { cmd }],
+    }
+}
+
+pub(crate) fn is_approved(decision: &ReviewDecision) -> bool {
+    !matches!(decision, ReviewDecision::Denied | ReviewDecision::Abort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::config_types::ToolPolicy;
+    use pretty_assertions::assert_eq;
+
+    fn policies(deny: &[&str]) -> SafeModePolicies {
+        SafeModePolicies {
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            tool_policy: ToolPolicy {
+                allow: None,
+                deny: deny.iter().map(ToString::to_string).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn safe_policies_deny_network_tools_once() {
+        let safe = safe_policies(
+            &policies(&["web_search", "shell"]),
+            ["mcp__docs__search".to_string()],
+        );
+
+        assert_eq!(safe.approval_policy, AskForApproval::UnlessTrusted);
+        assert_eq!(safe.sandbox_policy, SandboxPolicy::new_read_only_policy());
+        assert_eq!(
+            safe.tool_policy.deny,
+            vec![
+                "web_search",
+                "shell",
+                "list_mcp_resources",
+                "list_mcp_resource_templates",
+                "read_mcp_resource",
+                "mcp__docs__search",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_a_rollout_left_in_safe_mode_resumes_in_it() {
+        let switch = |enabled| {
+            RolloutItem::EventMsg(EventMsg::SafeModeChanged(SafeModeChangedEvent {
+                enabled,
+                before: policies(&[]),
+                after: policies(&[]),
+            }))
+        };
+
+        assert_eq!(
+            last_recorded_safe_mode(&[switch(true), switch(false)]),
+            None
+        );
+        assert!(last_recorded_safe_mode(&[switch(false), switch(true)]).is_some());
+        assert_eq!(last_recorded_safe_mode(&[]), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! Conversation-scoped sampling parameters (`temperature`, `top_p`, `seed`).
+//!
+//! The parameters come from `Config` and can be replaced between turns with
+//! `Op::OverrideTurnContext`. They are checked against the model when the
+//! session starts, sent with every request of a turn, and recorded in the
+//! turn's `TurnContextItem`. A parameter the provider or model does not accept
+//! is left out of the request and reported once with `EventMsg::Warning`.
+
+use codex_protocol::config_types::SamplingParams;
+
+use crate::config::ConstraintError;
+use crate::config::ConstraintResult;
+use crate::model_provider_info::WireApi;
+use crate::models_manager::model_family::ModelFamily;
+
+/// Reject values outside the ranges providers accept.
+pub(crate) fn check_ranges(sampling: &SamplingParams) -> ConstraintResult<()> {
+    if let Some(temperature) = sampling.temperature
+        && !(0.0..=2.0).contains(&temperature)
+    {
+        return Err(ConstraintError::invalid_value(
+            format!("temperature = {temperature}"),
+            "[0, 2]",
+        ));
+    }
+    if let Some(top_p) = sampling.top_p
+        && !(top_p > 0.0 && top_p <= 1.0)
+    {
+        return Err(ConstraintError::invalid_value(
+            format!("top_p = {top_p}"),
+            "(0, 1]",
+        ));
+    }
+    Ok(())
+}
+
+/// Check `sampling` against what `model_family` accepts, for the session's
+/// initial configuration.
+pub(crate) fn validate_for_model(
+    sampling: &SamplingParams,
+    model_family: &ModelFamily,
+) -> Result<(), String> {
+    check_ranges(sampling).map_err(|err| err.to_string())?;
+    match rejected_by_model(sampling, model_family).first() {
+        Some(name) => Err(format!(
+            "model `{}` does not support `{name}`; remove it from the configuration",
+            model_family.slug
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parameters of `sampling` sent to `model_family` over `wire_api`, together
+/// with the names of the configured ones that were left out.
+pub(crate) fn effective(
+    sampling: &SamplingParams,
+    model_family: &ModelFamily,
+    wire_api: WireApi,
+) -> (SamplingParams, Vec<&'static str>) {
+    let mut ignored = rejected_by_model(sampling, model_family);
+    if wire_api == WireApi::Responses && sampling.seed.is_some() {
+        ignored.push("seed");
+    }
+    let mut sent = *sampling;
+    for name in &ignored {
+        match *name {
+            "temperature" => sent.temperature = None,
+            "top_p" => sent.top_p = None,
+            _ => sent.seed = None,
+        }
+    }
+    (sent, ignored)
+}
+
+/// Reasoning models sample with fixed parameters.
+fn rejected_by_model(sampling: &SamplingParams, model_family: &ModelFamily) -> Vec<&'static str> {
+    if !model_family.supports_reasoning_summaries {
+        return Vec::new();
+    }
+    [
+        ("temperature", sampling.temperature.is_some()),
+        ("top_p", sampling.top_p.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(name, set)| set.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models_manager::model_family::find_family_for_model;
+    use pretty_assertions::assert_eq;
+
+    fn pinned() -> SamplingParams {
+        SamplingParams {
+            temperature: Some(0.2),
+            top_p: Some(0.9),
+            seed: Some(7),
+        }
+    }
+
+    #[test]
+    fn seed_is_dropped_for_the_responses_api() {
+        let family = find_family_for_model("gpt-4.1");
+        assert_eq!(
+            effective(&pinned(), &family, WireApi::Responses),
+            (
+                SamplingParams {
+                    seed: None,
+                    ..pinned()
+                },
+                vec!["seed"]
+            )
+        );
+        assert_eq!(
+            effective(&pinned(), &family, WireApi::Chat),
+            (pinned(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn reasoning_models_reject_temperature_and_top_p() {
+        let family = find_family_for_model("o3");
+        assert_eq!(
+            validate_for_model(&pinned(), &family),
+            Err(
+                "model `o3` does not support `temperature`; remove it from the configuration"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate_for_model(
+                &SamplingParams {
+                    seed: Some(7),
+                    ..Default::default()
+                },
+                &family
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let sampling = SamplingParams {
+            top_p: Some(0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_ranges(&sampling).map_err(|err| err.to_string()),
+            Err("value `top_p = 0` is not in the allowed set (0, 1]".to_string())
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Running the prompts of a [`PromptScript`] in a fresh conversation.
+//!
+//! [`ConversationManager::run_script`] submits each prompt once the turn of
+//! the one before has ended, so the new rollout holds the same user
+//! messages as the recorded one with new answers, and can be compared with
+//! it turn by turn. A turn that fails or runs out of time is reported as its
+//! step's outcome; [`OnStepFailure`] decides whether the remaining prompts
+//! are still submitted.
+//!
+//! [`ConversationManager::run_script`]: crate::ConversationManager::run_script
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use tracing::warn;
+
+use crate::CodexConversation;
+use crate::rollout::PromptScript;
+use crate::turn_results::TurnError;
+use crate::turn_results::TurnResult;
+
+/// What a script run does after a step that did not complete.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnStepFailure {
+    /// Skip the remaining prompts.
+    #[default]
+    Abort,
+    /// Submit the remaining prompts anyway.
+    Continue,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScriptRunOptions {
+    /// How long each turn may take before it is interrupted. `None` waits
+    /// for as long as the turn runs.
+    pub turn_timeout: Option<Duration>,
+    pub on_failure: OnStepFailure,
+}
+
+/// How the turn of one prompt ended.
+#[derive(Debug, Clone)]
+pub enum ScriptStepOutcome {
+    /// The turn completed.
+    Completed(TurnResult),
+    /// The turn, or the submission of the prompt, failed, see
+    /// [`TurnResult::error`].
+    Failed(TurnResult),
+    /// The turn did not end within the turn timeout and was interrupted.
+    TimedOut,
+    /// The prompt was not submitted since an earlier step failed.
+    Skipped,
+}
+
+impl ScriptStepOutcome {
+    pub fn is_completed(&self) -> bool {
+        matches!(self, Self::Completed(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptStep {
+    /// [`crate::rollout::ScriptPrompt::ordinal`] of the prompt.
+    pub ordinal: usize,
+    pub outcome: ScriptStepOutcome,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptRunReport {
+    /// The conversation the prompts were submitted to. It has been shut
+    /// down.
+    pub conversation_id: ConversationId,
+    /// The rollout of that conversation, to compare with the recorded one.
+    pub rollout_path: PathBuf,
+    /// One step per prompt of the script, in order.
+    pub steps: Vec<ScriptStep>,
+}
+
+/// Submit the prompts of `script` to `conversation` one turn at a time.
+pub(crate) async fn run_prompts(
+    conversation: &CodexConversation,
+    script: &PromptScript,
+    options: &ScriptRunOptions,
+) -> Vec<ScriptStep> {
+    let mut steps = Vec::with_capacity(script.prompts.len());
+    let mut aborted = false;
+    for prompt in &script.prompts {
+        let outcome = if aborted {
+            ScriptStepOutcome::Skipped
+        } else {
+            run_prompt(conversation, prompt.items.clone(), options.turn_timeout).await
+        };
+        if !outcome.is_completed() && options.on_failure == OnStepFailure::Abort {
+            aborted = true;
+        }
+        steps.push(ScriptStep {
+            ordinal: prompt.ordinal,
+            outcome,
+        });
+    }
+    steps
+}
+
+async fn run_prompt(
+    conversation: &CodexConversation,
+    items: Vec<UserInput>,
+    turn_timeout: Option<Duration>,
+) -> ScriptStepOutcome {
+    let op = Op::UserInput {
+        items,
+        allow_sensitive: false,
+    };
+    let ticket = match conversation.submit_with_ticket(op).await {
+        Ok(ticket) => ticket,
+        Err(err) => return failed(err.to_string()),
+    };
+    let result = match turn_timeout {
+        None => conversation.wait_for_turn(ticket).await,
+        Some(timeout) => {
+            let wait = conversation.wait_for_turn(ticket);
+            tokio::pin!(wait);
+            match tokio::time::timeout(timeout, &mut wait).await {
+                Ok(result) => result,
+                Err(_) => {
+                    // The next prompt starts once this turn is gone.
+                    if let Err(err) = conversation.submit(Op::Interrupt).await {
+                        warn!("failed to interrupt a timed out script turn: {err}");
+                    }
+                    if tokio::time::timeout(timeout, wait).await.is_err() {
+                        warn!("a timed out script turn did not stop after an interrupt");
+                    }
+                    return ScriptStepOutcome::TimedOut;
+                }
+            }
+        }
+    };
+    match result {
+        Ok(result) if result.error.is_none() => ScriptStepOutcome::Completed(result),
+        Ok(result) => ScriptStepOutcome::Failed(result),
+        Err(err) => failed(err.to_string()),
+    }
+}
+
+fn failed(error: String) -> ScriptStepOutcome {
+    ScriptStepOutcome::Failed(TurnResult {
+        error: Some(TurnError::Failed(error)),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,327 @@
+//! Named session presets stored as `profiles/<name>.toml` under codex home.
+//!
+//! A session profile bundles the model, developer instructions, enabled
+//! skills and approval, sandbox and tool policies under one name, e.g.
+//! `code-review`:
+//!
+//! ```toml
+//! description = "Read-only review"
+//! model = "gpt-5.1-codex"
+//! sandbox_mode = "read-only"
+//! developer_instructions = "Review the change; do not edit files."
+//! tool_policy = { deny = ["shell"] }
+//! ```
+//!
+//! Keys have the same meaning as in `config.toml`. The profile is layered over
+//! the user's configuration like `-c` overrides, and the [`ConfigOverrides`]
+//! passed to [`ConversationManager::new_conversation_with_profile`] take
+//! precedence over both.
+//!
+//! [`ConfigOverrides`]: crate::config::ConfigOverrides
+//! [`ConversationManager::new_conversation_with_profile`]: crate::ConversationManager::new_conversation_with_profile
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::ToolPolicy;
+use codex_protocol::openai_models::ReasoningEffort;
+use serde::Deserialize;
+use thiserror::Error;
+use toml::Value as TomlValue;
+
+use crate::codex_home::CodexHome;
+use crate::protocol::AskForApproval;
+
+/// Directory under codex home holding one `<name>.toml` per profile.
+pub const SESSION_PROFILES_SUBDIR: &str = "profiles";
+
+const PROFILE_EXTENSION: &str = "toml";
+
+/// A session profile loaded from `path`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionProfile {
+    pub name: String,
+    pub description: Option<String>,
+    pub path: PathBuf,
+    /// Configuration keys set by the profile, without `description`.
+    settings: toml::Table,
+}
+
+impl SessionProfile {
+    /// The profile's settings as `config.toml` overrides.
+    pub(crate) fn config_overrides(&self) -> Vec<(String, TomlValue)> {
+        self.settings
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Result of scanning the profiles directory. A file that fails to load is
+/// reported in `errors` instead of hiding the others.
+#[derive(Debug, Default)]
+pub struct SessionProfiles {
+    /// Sorted by name.
+    pub profiles: Vec<SessionProfile>,
+    pub errors: Vec<SessionProfileError>,
+}
+
+#[derive(Debug, Error)]
+pub enum SessionProfileError {
+    #[error("session profile `{name}` not found")]
+    NotFound { name: String },
+
+    /// The file is not valid TOML.
+    #[error("failed to parse session profile {}: {message}", path.display())]
+    Malformed { path: PathBuf, message: String },
+
+    /// `field` is not a profile setting or has a value of the wrong type.
+    #[error("invalid `{field}` in session profile {}: {message}", path.display())]
+    InvalidField {
+        path: PathBuf,
+        field: String,
+        message: String,
+    },
+
+    #[error("failed to read session profile {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Settings a profile may set. Only used to validate profile files; the
+/// values are applied through the regular configuration loader.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct SessionProfileToml {
+    description: Option<String>,
+    model: Option<String>,
+    model_provider: Option<String>,
+    model_reasoning_effort: Option<ReasoningEffort>,
+    approval_policy: Option<AskForApproval>,
+    sandbox_mode: Option<SandboxMode>,
+    developer_instructions: Option<String>,
+    enabled_skills: Option<Vec<String>>,
+    tool_policy: Option<ToolPolicy>,
+}
+
+/// Every profile under `codex_home`. A missing profiles directory means no
+/// profiles.
+pub(crate) async fn list_profiles(codex_home: &Path) -> SessionProfiles {
+    let dir = CodexHome::new(codex_home).profiles_dir();
+    let mut listing = SessionProfiles::default();
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return listing,
+        Err(source) => {
+            listing
+                .errors
+                .push(SessionProfileError::Io { path: dir, source });
+            return listing;
+        }
+    };
+    loop {
+        let path = match entries.next_entry().await {
+            Ok(Some(entry)) => entry.path(),
+            Ok(None) => break,
+            Err(source) => {
+                listing.errors.push(SessionProfileError::Io {
+                    path: dir.clone(),
+                    source,
+                });
+                break;
+            }
+        };
+        let Some(name) = profile_name(&path) else {
+            continue;
+        };
+        match read_profile(name, &path).await {
+            Ok(profile) => listing.profiles.push(profile),
+            Err(err) => listing.errors.push(err),
+        }
+    }
+    listing
+        .profiles
+        .sort_by(|left, right| left.name.cmp(&right.name));
+    listing
+}
+
+/// The profile called `name` under `codex_home`.
+pub(crate) async fn load_profile(
+    codex_home: &Path,
+    name: &str,
+) -> Result<SessionProfile, SessionProfileError> {
+    let not_found = || SessionProfileError::NotFound {
+        name: name.to_string(),
+    };
+    let path = CodexHome::new(codex_home)
+        .profiles_dir()
+        .join(format!("{name}.{PROFILE_EXTENSION}"));
+    // Reject names that would resolve outside the profiles directory.
+    if profile_name(&path) != Some(name) {
+        return Err(not_found());
+    }
+    match read_profile(name, &path).await {
+        Err(SessionProfileError::Io { source, .. }) if source.kind() == ErrorKind::NotFound => {
+            Err(not_found())
+        }
+        result => result,
+    }
+}
+
+/// Name of the profile stored at `path`, if it is a profile file.
+fn profile_name(path: &Path) -> Option<&str> {
+    if path.extension()? != PROFILE_EXTENSION {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()
+        .filter(|name| !name.is_empty() && !name.starts_with('.'))
+}
+
+async fn read_profile(name: &str, path: &Path) -> Result<SessionProfile, SessionProfileError> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|source| SessionProfileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    parse_profile(name, path, &text)
+}
+
+fn parse_profile(
+    name: &str,
+    path: &Path,
+    text: &str,
+) -> Result<SessionProfile, SessionProfileError> {
+    let mut settings: toml::Table =
+        text.parse()
+            .map_err(|err: toml::de::Error| SessionProfileError::Malformed {
+                path: path.to_path_buf(),
+                message: err.message().to_string(),
+            })?;
+    // Check keys one at a time so the error names the offending one.
+    for (field, value) in &settings {
+        let single = toml::Table::from_iter([(field.clone(), value.clone())]);
+        if let Err(err) = TomlValue::Table(single).try_into::<SessionProfileToml>() {
+            return Err(SessionProfileError::InvalidField {
+                path: path.to_path_buf(),
+                field: field.clone(),
+                message: err.message().to_string(),
+            });
+        }
+    }
+    let description = match settings.remove("description") {
+        Some(TomlValue::String(description)) => Some(description),
+        _ => None,
+    };
+    Ok(SessionProfile {
+        name: name.to_string(),
+        description,
+        path: path.to_path_buf(),
+        settings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write_profile(codex_home: &Path, name: &str, contents: &str) {
+        let dir = codex_home.join(SESSION_PROFILES_SUBDIR);
+        std::fs::create_dir_all(&dir).expect("create profiles dir");
+        std::fs::write(dir.join(name), contents).expect("write profile");
+    }
+
+    #[tokio::test]
+    async fn lists_valid_profiles_and_reports_broken_ones() {
+        let codex_home = TempDir::new().expect("tempdir");
+        write_profile(
+            codex_home.path(),
+            "refactor.toml",
+            "model = \"gpt-5.1-codex\"\n",
+        );
+        write_profile(
+            codex_home.path(),
+            "code-review.toml",
+            "description = \"Read-only review\"\nsandbox_mode = \"read-only\"\n",
+        );
+        write_profile(codex_home.path(), "broken.toml", "sandbox_mode = 3\n");
+        write_profile(codex_home.path(), "notes.md", "not a profile");
+
+        let listing = list_profiles(codex_home.path()).await;
+
+        let names: Vec<&str> = listing
+            .profiles
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["code-review", "refactor"]);
+        assert_eq!(
+            listing.profiles[0].description.as_deref(),
+            Some("Read-only review")
+        );
+        assert_eq!(
+            listing.profiles[0].config_overrides(),
+            vec![(
+                "sandbox_mode".to_string(),
+                TomlValue::String("read-only".to_string())
+            )]
+        );
+        assert_matches!(
+            listing.errors.as_slice(),
+            [SessionProfileError::InvalidField { field, .. }] if field == "sandbox_mode"
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_directory_has_no_profiles() {
+        let codex_home = TempDir::new().expect("tempdir");
+        let listing = list_profiles(codex_home.path()).await;
+        assert!(listing.profiles.is_empty());
+        assert!(listing.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_names_are_not_found() {
+        let codex_home = TempDir::new().expect("tempdir");
+        write_profile(codex_home.path(), "review.toml", "model = \"o3\"\n");
+
+        for name in ["missing", "../review", ""] {
+            assert_matches!(
+                load_profile(codex_home.path(), name).await,
+                Err(SessionProfileError::NotFound { name: missing }) if missing == name
+            );
+        }
+        assert!(load_profile(codex_home.path(), "review").await.is_ok());
+    }
+
+    #[test]
+    fn errors_name_the_offending_field() {
+        let path = Path::new("/home/user/.codex/profiles/review.toml");
+
+        let unknown = parse_profile("review", path, "model = \"o3\"\nshell = false\n");
+        assert_matches!(
+            unknown,
+            Err(SessionProfileError::InvalidField { field, .. }) if field == "shell"
+        );
+
+        let wrong_type = parse_profile("review", path, "tool_policy = { deny = \"shell\" }\n");
+        assert_matches!(
+            wrong_type,
+            Err(SessionProfileError::InvalidField { field, .. }) if field == "tool_policy"
+        );
+
+        assert_matches!(
+            parse_profile("review", path, "model = "),
+            Err(SessionProfileError::Malformed { .. })
+        );
+    }
+}
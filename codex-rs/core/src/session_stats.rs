@@ -0,0 +1,193 @@
+//! Totals of a session, reported in [`EventMsg::SessionEnded`] when it ends.
+//!
+//! Like the manager's metrics, the totals are accounted from the events the
+//! session emits, so they agree with what clients were shown.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::SessionEndedEvent;
+use codex_protocol::protocol::SessionStats;
+use codex_protocol::protocol::TokenUsage;
+use tokio::sync::watch;
+
+use crate::runtime_env::RuntimeEnv;
+
+/// Receives the [`SessionEndedEvent`] once the session has sent it. The
+/// channel closes without one when the session stops without ending cleanly.
+pub(crate) type SessionEndReceiver = watch::Receiver<Option<SessionEndedEvent>>;
+
+pub(crate) struct SessionStatsTracker {
+    env: RuntimeEnv,
+    started: SystemTime,
+    totals: Mutex<Totals>,
+    ended: watch::Sender<Option<SessionEndedEvent>>,
+}
+
+#[derive(Default)]
+struct Totals {
+    turns: u64,
+    token_usage: TokenUsage,
+    files_changed: BTreeSet<PathBuf>,
+}
+
+impl SessionStatsTracker {
+    pub(crate) fn new(env: RuntimeEnv) -> Self {
+        Self {
+            started: env.now(),
+            env,
+            totals: Mutex::new(Totals::default()),
+            ended: watch::channel(None).0,
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> SessionEndReceiver {
+        self.ended.subscribe()
+    }
+
+    /// Account for `msg` as the session emits it.
+    pub(crate) fn observe(&self, msg: &EventMsg) {
+        let mut totals = self
+            .totals
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match msg {
+            EventMsg::TaskComplete(_) => totals.turns += 1,
+            // Token counts are cumulative.
+            EventMsg::TokenCount(token_count) => {
+                if let Some(info) = &token_count.info {
+                    totals.token_usage = info.total_token_usage.clone();
+                }
+            }
+            EventMsg::TurnDiffSummary(summary) => {
+                totals
+                    .files_changed
+                    .extend(summary.files.iter().map(|file| file.path.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    /// Turns completed so far.
+    pub(crate) fn turns(&self) -> u64 {
+        self.totals
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .turns
+    }
+
+    pub(crate) fn stats(&self, rollout_path: Option<PathBuf>) -> SessionStats {
+        let totals = self
+            .totals
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        SessionStats {
+            turns: totals.turns,
+            token_usage: totals.token_usage.clone(),
+            duration_ms: u64::try_from(self.env.since(self.started).as_millis())
+                .unwrap_or(u64::MAX),
+            files_changed: totals.files_changed.iter().cloned().collect(),
+            rollout_path,
+        }
+    }
+
+    /// Wake the [`SessionEndReceiver`]s once `event` has been sent.
+    pub(crate) fn mark_ended(&self, event: SessionEndedEvent) {
+        self.ended.send_replace(Some(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::SessionEndReason;
+    use codex_protocol::protocol::TaskCompleteEvent;
+    use codex_protocol::protocol::TokenCountEvent;
+    use codex_protocol::protocol::TokenUsageInfo;
+    use codex_protocol::protocol::TurnDiffSummaryEvent;
+    use codex_protocol::protocol::TurnFileChange;
+    use codex_protocol::protocol::TurnFileChangeKind;
+    use pretty_assertions::assert_eq;
+
+    fn token_count(total_tokens: i64) -> EventMsg {
+        EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: TokenUsage {
+                    total_tokens,
+                    ..Default::default()
+                },
+                last_token_usage: TokenUsage::default(),
+                model_context_window: None,
+            }),
+            rate_limits: None,
+        })
+    }
+
+    fn diff(paths: &[&str]) -> EventMsg {
+        EventMsg::TurnDiffSummary(TurnDiffSummaryEvent {
+            files: paths
+                .iter()
+                .map(|path| TurnFileChange {
+                    path: PathBuf::from(path),
+                    kind: TurnFileChangeKind::Modified,
+                    additions: 1,
+                    deletions: 0,
+                    outside_cwd: false,
+                })
+                .collect(),
+            additions: 1,
+            deletions: 0,
+        })
+    }
+
+    #[tokio::test]
+    async fn totals_follow_the_emitted_events() {
+        let tracker = SessionStatsTracker::new(RuntimeEnv::default());
+        let mut receiver = tracker.subscribe();
+        let complete = EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
+        });
+        for msg in [
+            token_count(10),
+            diff(&["b.rs", "a.rs"]),
+            complete.clone(),
+            token_count(25),
+            diff(&["a.rs"]),
+            complete,
+        ] {
+            tracker.observe(&msg);
+        }
+
+        let stats = tracker.stats(Some(PathBuf::from("rollout.jsonl")));
+        assert_eq!(stats.turns, 2);
+        assert_eq!(stats.token_usage.total_tokens, 25);
+        assert_eq!(
+            stats.files_changed,
+            vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")]
+        );
+        assert_eq!(stats.rollout_path, Some(PathBuf::from("rollout.jsonl")));
+
+        tracker.mark_ended(SessionEndedEvent {
+            reason: SessionEndReason::Shutdown,
+            stats,
+        });
+        let ended = receiver
+            .wait_for(Option::is_some)
+            .await
+            .expect("ended")
+            .clone();
+        assert_eq!(
+            ended.map(|event| event.reason),
+            Some(SessionEndReason::Shutdown)
+        );
+    }
+}
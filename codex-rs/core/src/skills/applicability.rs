@@ -0,0 +1,221 @@
+//! Whether a skill applies to a workspace, from the `applies-to` rules in its
+//! metadata.
+
+use std::cell::OnceCell;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use wildmatch::WildMatch;
+
+use crate::skills::model::SkillApplicability;
+
+/// Stop listing workspace files after this many, so a huge checkout does not
+/// delay the start of a session.
+const MAX_WORKSPACE_FILES: usize = 20_000;
+
+/// Directories that are never listed: dependencies and build output.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Languages that can be required, with the file names and extensions that
+/// reveal them.
+const LANGUAGES: &[(&str, &[&str], &[&str])] = &[
+    ("rust", &["Cargo.toml"], &["rs"]),
+    (
+        "python",
+        &["pyproject.toml", "setup.py", "requirements.txt"],
+        &["py"],
+    ),
+    (
+        "javascript",
+        &["package.json"],
+        &["js", "jsx", "mjs", "cjs"],
+    ),
+    ("typescript", &["tsconfig.json"], &["ts", "tsx"]),
+    ("go", &["go.mod"], &["go"]),
+    ("java", &["pom.xml", "build.gradle"], &["java"]),
+    ("kotlin", &["build.gradle.kts"], &["kt", "kts"]),
+    ("ruby", &["Gemfile"], &["rb"]),
+    ("swift", &["Package.swift"], &["swift"]),
+    ("c", &[], &["c", "h"]),
+    ("cpp", &["CMakeLists.txt"], &["cpp", "cc", "cxx", "hpp"]),
+    ("csharp", &[], &["cs", "csproj"]),
+    ("php", &["composer.json"], &["php"]),
+];
+
+/// A workspace whose files and languages are listed the first time a rule
+/// needs them.
+pub(crate) struct Workspace {
+    root: PathBuf,
+    files: OnceCell<Vec<String>>,
+    languages: OnceCell<BTreeSet<&'static str>>,
+}
+
+impl Workspace {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: OnceCell::new(),
+            languages: OnceCell::new(),
+        }
+    }
+
+    /// Why a skill with `rules` does not apply to this workspace, or `None`
+    /// when it does.
+    pub(crate) fn exclusion_reason(&self, rules: &SkillApplicability) -> Option<String> {
+        if !rules.files.is_empty() {
+            let patterns: Vec<WildMatch> = rules
+                .files
+                .iter()
+                .map(|pattern| WildMatch::new(pattern))
+                .collect();
+            let matched = self
+                .files()
+                .iter()
+                .any(|file| patterns.iter().any(|pattern| pattern.matches(file)));
+            if !matched {
+                return Some(format!(
+                    "no workspace file matches {}",
+                    quoted_list(&rules.files)
+                ));
+            }
+        }
+
+        if !rules.languages.is_empty() {
+            let detected = self.languages();
+            if !rules
+                .languages
+                .iter()
+                .any(|language| detected.contains(language.as_str()))
+            {
+                return Some(format!(
+                    "{} not detected in the workspace",
+                    quoted_list(&rules.languages)
+                ));
+            }
+        }
+
+        if let Some(missing) = rules
+            .binaries
+            .iter()
+            .find(|binary| which::which(binary).is_err())
+        {
+            return Some(format!("`{missing}` is not on PATH"));
+        }
+
+        None
+    }
+
+    fn files(&self) -> &[String] {
+        self.files.get_or_init(|| list_files(&self.root))
+    }
+
+    fn languages(&self) -> &BTreeSet<&'static str> {
+        self.languages.get_or_init(|| {
+            let mut detected = BTreeSet::new();
+            for file in self.files() {
+                let name = file.rsplit('/').next().unwrap_or(file);
+                let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+                for (language, markers, extensions) in LANGUAGES {
+                    if markers.contains(&name)
+                        || extension.is_some_and(|extension| extensions.contains(&extension))
+                    {
+                        detected.insert(*language);
+                    }
+                }
+            }
+            detected
+        })
+    }
+}
+
+/// Paths of the files under `root`, relative to it and `/`-separated, nearest
+/// first. Hidden and [`SKIPPED_DIRS`] directories are not entered.
+fn list_files(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut pending = VecDeque::from([PathBuf::new()]);
+    while let Some(relative) = pending.pop_front() {
+        let Ok(entries) = fs::read_dir(root.join(&relative)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let path = relative.join(&*name);
+            if file_type.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&&*name) {
+                    pending.push_back(path);
+                }
+            } else {
+                files.push(path.to_string_lossy().replace('\\', "/"));
+                if files.len() >= MAX_WORKSPACE_FILES {
+                    return files;
+                }
+            }
+        }
+    }
+    files
+}
+
+fn quoted_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("`{value}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn rules(files: &[&str], binaries: &[&str], languages: &[&str]) -> SkillApplicability {
+        let strings = |values: &[&str]| values.iter().map(ToString::to_string).collect();
+        SkillApplicability {
+            files: strings(files),
+            binaries: strings(binaries),
+            languages: strings(languages),
+        }
+    }
+
+    #[test]
+    fn rules_are_checked_against_the_workspace() {
+        let dir = TempDir::new().expect("tempdir");
+        fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).expect("mkdir");
+        fs::write(dir.path().join("Cargo.toml"), "[package]\n").expect("write");
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").expect("write");
+        fs::write(dir.path().join("node_modules/pkg/index.js"), "").expect("write");
+        let workspace = Workspace::new(dir.path());
+
+        assert_eq!(workspace.exclusion_reason(&rules(&[], &[], &[])), None);
+        assert_eq!(
+            workspace.exclusion_reason(&rules(&["Cargo.toml"], &[], &["rust"])),
+            None
+        );
+        assert_eq!(
+            workspace.exclusion_reason(&rules(&["src/*.rs"], &[], &[])),
+            None
+        );
+        assert_eq!(
+            workspace.exclusion_reason(&rules(&["*.py", "pyproject.toml"], &[], &[])),
+            Some("no workspace file matches `*.py`, `pyproject.toml`".to_string())
+        );
+        // Files under skipped directories do not count.
+        assert_eq!(
+            workspace.exclusion_reason(&rules(&[], &[], &["javascript"])),
+            Some("`javascript` not detected in the workspace".to_string())
+        );
+        assert_eq!(
+            workspace.exclusion_reason(&rules(&[], &["codex-no-such-binary"], &[])),
+            Some("`codex-no-such-binary` is not on PATH".to_string())
+        );
+    }
+}
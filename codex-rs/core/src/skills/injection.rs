@@ -10,6 +10,8 @@ use tokio::fs;
 #[derive(Debug, Default)]
 pub(crate) struct SkillInjections {
     pub(crate) items: Vec<ResponseItem>,
+    /// Names of the skills in `items`.
+    pub(crate) skills: Vec<String>,
     pub(crate) warnings: Vec<String>,
 }
 
@@ -32,12 +34,14 @@ pub(crate) async fn build_skill_injections(
 
     let mut result = SkillInjections {
         items: Vec::with_capacity(mentioned_skills.len()),
+        skills: Vec::with_capacity(mentioned_skills.len()),
         warnings: Vec::new(),
     };
 
     for skill in mentioned_skills {
         match fs::read_to_string(&skill.path).await {
             Ok(contents) => {
+                result.skills.push(skill.name.clone());
                 result.items.push(ResponseItem::from(SkillInstructions {
                     name: skill.name,
                     path: skill.path.to_string_lossy().into_owned(),
@@ -1,13 +1,18 @@
+use crate::codex_home::CodexHome;
+use crate::codex_home::SKILLS_SUBDIR;
 use crate::config::Config;
 use crate::git_info::resolve_root_git_project_for_trust;
+use crate::skills::model::SkillApplicability;
 use crate::skills::model::SkillError;
 use crate::skills::model::SkillLoadOutcome;
 use crate::skills::model::SkillMetadata;
+use crate::skills::model::SkillParameter;
+use crate::skills::remote::skill_pack_roots;
 use crate::skills::system::system_cache_root_dir;
 use codex_protocol::protocol::SkillScope;
 use dunce::canonicalize as normalize_path;
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt;
@@ -28,15 +33,38 @@ struct SkillFrontmatter {
 struct SkillFrontmatterMetadata {
     #[serde(default, rename = "short-description")]
     short_description: Option<String>,
+    #[serde(default, rename = "applies-to")]
+    applies_to: SkillFrontmatterAppliesTo,
+    #[serde(default)]
+    parameters: Vec<SkillFrontmatterParameter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SkillFrontmatterParameter {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    required: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SkillFrontmatterAppliesTo {
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    binaries: Vec<String>,
+    #[serde(default)]
+    languages: Vec<String>,
 }
 
 const SKILLS_FILENAME: &str = "SKILL.md";
-const SKILLS_DIR_NAME: &str = "skills";
 const REPO_ROOT_CONFIG_DIR_NAME: &str = ".codex";
 const ADMIN_SKILLS_ROOT: &str = "/etc/codex/skills";
 const MAX_NAME_LEN: usize = 64;
 const MAX_DESCRIPTION_LEN: usize = 1024;
 const MAX_SHORT_DESCRIPTION_LEN: usize = MAX_DESCRIPTION_LEN;
+const MAX_PARAMETER_NAME_LEN: usize = MAX_NAME_LEN;
 
 #[derive(Debug)]
 enum SkillParseError {
@@ -72,6 +100,8 @@ pub fn load_skills(config: &Config) -> SkillLoadOutcome {
 pub(crate) struct SkillRoot {
     pub(crate) path: PathBuf,
     pub(crate) scope: SkillScope,
+    /// Name of the skill pack this root holds, if any.
+    pub(crate) pack: Option<String>,
 }
 
 pub(crate) fn load_skills_from_roots<I>(roots: I) -> SkillLoadOutcome
@@ -79,15 +109,30 @@ where
     I: IntoIterator<Item = SkillRoot>,
 {
     let mut outcome = SkillLoadOutcome::default();
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
     for root in roots {
-        discover_skills_under_root(&root.path, root.scope, &mut outcome);
+        let mut found = SkillLoadOutcome::default();
+        discover_skills_under_root(&root.path, root.scope, &mut found);
+        outcome.errors.append(&mut found.errors);
+        for skill in found.skills {
+            if let Some(winner) = seen.get(&skill.name) {
+                if let Some(pack) = &root.pack {
+                    outcome.errors.push(SkillError {
+                        message: format!(
+                            "skill `{}` from skill pack `{pack}` is overridden by {}",
+                            skill.name,
+                            winner.display()
+                        ),
+                        path: skill.path,
+                    });
+                }
+                continue;
+            }
+            seen.insert(skill.name.clone(), skill.path.clone());
+            outcome.skills.push(skill);
+        }
     }
 
-    let mut seen: HashSet<String> = HashSet::new();
-    outcome
-        .skills
-        .retain(|skill| seen.insert(skill.name.clone()));
-
     outcome
         .skills
         .sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
@@ -97,8 +142,9 @@ where
 
 pub(crate) fn user_skills_root(codex_home: &Path) -> SkillRoot {
     SkillRoot {
-        path: codex_home.join(SKILLS_DIR_NAME),
+        path: CodexHome::new(codex_home).skills_dir(),
         scope: SkillScope::User,
+        pack: None,
     }
 }
 
@@ -106,6 +152,7 @@ pub(crate) fn system_skills_root(codex_home: &Path) -> SkillRoot {
     SkillRoot {
         path: system_cache_root_dir(codex_home),
         scope: SkillScope::System,
+        pack: None,
     }
 }
 
@@ -113,6 +160,7 @@ pub(crate) fn admin_skills_root() -> SkillRoot {
     SkillRoot {
         path: PathBuf::from(ADMIN_SKILLS_ROOT),
         scope: SkillScope::Admin,
+        pack: None,
     }
 }
 
@@ -126,11 +174,12 @@ pub(crate) fn repo_skills_root(cwd: &Path) -> Option<SkillRoot> {
     let scope = SkillScope::Repo;
     if let Some(repo_root) = repo_root.as_deref() {
         for dir in base.ancestors() {
-            let skills_root = dir.join(REPO_ROOT_CONFIG_DIR_NAME).join(SKILLS_DIR_NAME);
+            let skills_root = dir.join(REPO_ROOT_CONFIG_DIR_NAME).join(SKILLS_SUBDIR);
             if skills_root.is_dir() {
                 return Some(SkillRoot {
                     path: skills_root,
                     scope,
+                    pack: None,
                 });
             }
 
@@ -141,14 +190,19 @@ pub(crate) fn repo_skills_root(cwd: &Path) -> Option<SkillRoot> {
         return None;
     }
 
-    let skills_root = base.join(REPO_ROOT_CONFIG_DIR_NAME).join(SKILLS_DIR_NAME);
+    let skills_root = base.join(REPO_ROOT_CONFIG_DIR_NAME).join(SKILLS_SUBDIR);
     skills_root.is_dir().then_some(SkillRoot {
         path: skills_root,
         scope,
+        pack: None,
     })
 }
 
-pub(crate) fn skill_roots_for_cwd(codex_home: &Path, cwd: &Path) -> Vec<SkillRoot> {
+pub(crate) fn skill_roots_for_cwd(
+    codex_home: &Path,
+    cwd: &Path,
+    pack_roots: Vec<SkillRoot>,
+) -> Vec<SkillRoot> {
     let mut roots = Vec::new();
 
     if let Some(repo_root) = repo_skills_root(cwd) {
@@ -156,8 +210,9 @@ pub(crate) fn skill_roots_for_cwd(codex_home: &Path, cwd: &Path) -> Vec<SkillRoo
     }
 
     // Load order matters: we dedupe by name, keeping the first occurrence.
-    // Priority order: repo, user, system, then admin.
+    // Priority order: repo, user, skill packs, system, then admin.
     roots.push(user_skills_root(codex_home));
+    roots.extend(pack_roots);
     roots.push(system_skills_root(codex_home));
     if cfg!(unix) {
         roots.push(admin_skills_root());
@@ -167,7 +222,11 @@ pub(crate) fn skill_roots_for_cwd(codex_home: &Path, cwd: &Path) -> Vec<SkillRoo
 }
 
 fn skill_roots(config: &Config) -> Vec<SkillRoot> {
-    skill_roots_for_cwd(&config.codex_home, &config.cwd)
+    skill_roots_for_cwd(
+        &config.codex_home,
+        &config.cwd,
+        skill_pack_roots(&config.codex_home, &config.skill_packs),
+    )
 }
 
 fn discover_skills_under_root(root: &Path, scope: SkillScope, outcome: &mut SkillLoadOutcome) {
@@ -259,6 +318,43 @@ fn parse_skill_file(path: &Path, scope: SkillScope) -> Result<SkillMetadata, Ski
         )?;
     }
 
+    let applies_to = parsed.metadata.applies_to;
+    let applies_to = SkillApplicability {
+        files: applies_to.files,
+        binaries: applies_to.binaries,
+        languages: applies_to
+            .languages
+            .iter()
+            .map(|language| language.to_ascii_lowercase())
+            .collect(),
+    };
+
+    let mut parameters: Vec<SkillParameter> = Vec::new();
+    for parameter in parsed.metadata.parameters {
+        let name = parameter.name.trim().to_string();
+        validate_field(&name, MAX_PARAMETER_NAME_LEN, "metadata.parameters.name")?;
+        if !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+        {
+            return Err(SkillParseError::InvalidField {
+                field: "metadata.parameters.name",
+                reason: format!("`{name}` may only contain letters, digits, `_` and `-`"),
+            });
+        }
+        if parameters.iter().any(|existing| existing.name == name) {
+            return Err(SkillParseError::InvalidField {
+                field: "metadata.parameters.name",
+                reason: format!("`{name}` is declared more than once"),
+            });
+        }
+        parameters.push(SkillParameter {
+            name,
+            description: sanitize_single_line(&parameter.description),
+            required: parameter.required,
+        });
+    }
+
     let resolved_path = normalize_path(path).unwrap_or_else(|_| path.to_path_buf());
 
     Ok(SkillMetadata {
@@ -267,6 +363,8 @@ fn parse_skill_file(path: &Path, scope: SkillScope) -> Result<SkillMetadata, Ski
         short_description,
         path: resolved_path,
         scope,
+        applies_to,
+        parameters,
     })
 }
 
@@ -411,6 +509,83 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn loads_applicability_rules_from_metadata() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let skill_dir = codex_home.path().join("skills/demo");
+        fs::create_dir_all(&skill_dir).unwrap();
+        let contents = "---\nname: demo-skill\ndescription: long description\nmetadata:\n  applies-to:\n    files: [\"Cargo.toml\"]\n    binaries: [cargo]\n    languages: [Rust]\n---\n\n# Body\n";
+        fs::write(skill_dir.join(SKILLS_FILENAME), contents).unwrap();
+
+        let cfg = make_config(&codex_home).await;
+        let outcome = load_skills(&cfg);
+        assert!(
+            outcome.errors.is_empty(),
+            "unexpected errors: {:?}",
+            outcome.errors
+        );
+        assert_eq!(
+            outcome.skills[0].applies_to,
+            SkillApplicability {
+                files: vec!["Cargo.toml".to_string()],
+                binaries: vec!["cargo".to_string()],
+                languages: vec!["rust".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn loads_parameters_from_metadata() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let skill_dir = codex_home.path().join("skills/demo");
+        fs::create_dir_all(&skill_dir).unwrap();
+        let contents = "---\nname: demo-skill\ndescription: long description\nmetadata:\n  parameters:\n    - name: target\n      description: What to build\n      required: true\n    - name: profile\n---\n\n# Body\n";
+        fs::write(skill_dir.join(SKILLS_FILENAME), contents).unwrap();
+
+        let cfg = make_config(&codex_home).await;
+        let outcome = load_skills(&cfg);
+        assert!(
+            outcome.errors.is_empty(),
+            "unexpected errors: {:?}",
+            outcome.errors
+        );
+        assert_eq!(
+            outcome.skills[0].parameters,
+            vec![
+                SkillParameter {
+                    name: "target".to_string(),
+                    description: "What to build".to_string(),
+                    required: true,
+                },
+                SkillParameter {
+                    name: "profile".to_string(),
+                    description: String::new(),
+                    required: false,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_parameter_names() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let skill_dir = codex_home.path().join("skills/demo");
+        fs::create_dir_all(&skill_dir).unwrap();
+        let contents = "---\nname: demo-skill\ndescription: long description\nmetadata:\n  parameters:\n    - name: \"two words\"\n---\n\n# Body\n";
+        fs::write(skill_dir.join(SKILLS_FILENAME), contents).unwrap();
+
+        let cfg = make_config(&codex_home).await;
+        let outcome = load_skills(&cfg);
+        assert_eq!(outcome.skills.len(), 0);
+        assert!(
+            outcome.errors[0]
+                .message
+                .contains("invalid metadata.parameters.name"),
+            "expected parameter name error, got: {:?}",
+            outcome.errors
+        );
+    }
+
     #[tokio::test]
     async fn enforces_short_description_length_limits() {
         let codex_home = tempfile::tempdir().expect("tempdir");
@@ -504,7 +679,7 @@ mod tests {
         let skills_root = repo_dir
             .path()
             .join(REPO_ROOT_CONFIG_DIR_NAME)
-            .join(SKILLS_DIR_NAME);
+            .join(SKILLS_SUBDIR);
         write_skill_at(&skills_root, "repo", "repo-skill", "from repo");
         let mut cfg = make_config(&codex_home).await;
         cfg.cwd = repo_dir.path().to_path_buf();
@@ -541,7 +716,7 @@ mod tests {
             &repo_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "root",
             "root-skill",
             "from root",
@@ -551,7 +726,7 @@ mod tests {
                 .path()
                 .join("nested")
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "nested",
             "nested-skill",
             "from nested",
@@ -579,7 +754,7 @@ mod tests {
             &work_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "local",
             "local-skill",
             "from cwd",
@@ -616,7 +791,7 @@ mod tests {
             &repo_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "repo",
             "dupe-skill",
             "from repo",
@@ -666,7 +841,7 @@ mod tests {
             &outer_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "outer",
             "outer-skill",
             "from outer",
@@ -707,7 +882,7 @@ mod tests {
             &repo_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "repo",
             "repo-skill",
             "from repo",
@@ -740,7 +915,7 @@ mod tests {
             &outer_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "outer",
             "outer-skill",
             "from outer",
@@ -807,10 +982,12 @@ mod tests {
             SkillRoot {
                 path: system_dir.path().to_path_buf(),
                 scope: SkillScope::System,
+                pack: None,
             },
             SkillRoot {
                 path: admin_dir.path().to_path_buf(),
                 scope: SkillScope::Admin,
+                pack: None,
             },
         ]);
 
@@ -862,7 +1039,7 @@ mod tests {
             &repo_dir
                 .path()
                 .join(REPO_ROOT_CONFIG_DIR_NAME)
-                .join(SKILLS_DIR_NAME),
+                .join(SKILLS_SUBDIR),
             "repo",
             "dupe-skill",
             "from repo",
@@ -1,33 +1,105 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::RwLock;
 
+use codex_protocol::protocol::ExcludedSkill;
+use codex_protocol::protocol::SkillUsageEvent;
+
+use crate::codex_home::CodexHome;
 use crate::skills::SkillLoadOutcome;
+use crate::skills::applicability::Workspace;
 use crate::skills::loader::load_skills_from_roots;
 use crate::skills::loader::skill_roots_for_cwd;
+use crate::skills::remote::SkillPackSource;
+use crate::skills::remote::SkillPackSyncReport;
+use crate::skills::remote::skill_pack_roots;
+use crate::skills::remote::sync_skill_packs;
 use crate::skills::system::install_system_skills;
+use crate::skills::usage::SkillUsageStats;
+use crate::skills::usage::SkillUsageStore;
+
 pub struct SkillsManager {
-    codex_home: PathBuf,
+    codex_home: CodexHome,
+    skill_packs: RwLock<BTreeMap<String, SkillPackSource>>,
     cache_by_cwd: RwLock<HashMap<PathBuf, SkillLoadOutcome>>,
+    usage: SkillUsageStore,
 }
 
 impl SkillsManager {
-    pub fn new(codex_home: PathBuf) -> Self {
-        if let Err(err) = install_system_skills(&codex_home) {
+    pub fn new(codex_home: impl Into<CodexHome>) -> Self {
+        let codex_home = codex_home.into();
+        if let Err(err) = install_system_skills(codex_home.root()) {
             tracing::error!("failed to install system skills: {err}");
         }
 
         Self {
+            usage: SkillUsageStore::new(codex_home.root()),
             codex_home,
+            skill_packs: RwLock::new(BTreeMap::new()),
             cache_by_cwd: RwLock::new(HashMap::new()),
         }
     }
 
+    /// The codex home whose `skills/` directory this manager loads from.
+    pub fn home(&self) -> &CodexHome {
+        &self.codex_home
+    }
+
+    /// Replace the configured skill packs. Skills from packs that are already
+    /// cached are loaded right away; call [`Self::sync`] to fetch the rest.
+    pub fn set_skill_packs(&self, packs: BTreeMap<String, SkillPackSource>) {
+        let mut current = match self.skill_packs.write() {
+            Ok(current) => current,
+            Err(err) => err.into_inner(),
+        };
+        if *current != packs {
+            *current = packs;
+            self.clear_cache();
+        }
+    }
+
+    /// Fetch configured skill packs whose cached copy is missing or does not
+    /// match the pinned version.
+    pub async fn sync(&self) -> SkillPackSyncReport {
+        let packs = self.skill_packs();
+        let report = sync_skill_packs(self.codex_home.root(), &packs).await;
+        if !report.fetched.is_empty() {
+            self.clear_cache();
+        }
+        report
+    }
+
     pub fn skills_for_cwd(&self, cwd: &Path) -> SkillLoadOutcome {
         self.skills_for_cwd_with_options(cwd, false)
     }
 
+    /// The skills for `cwd` whose `applies-to` rules match the workspace.
+    /// The others are moved to [`SkillLoadOutcome::excluded`] with the reason.
+    pub fn skills_for_workspace(&self, cwd: &Path) -> SkillLoadOutcome {
+        let mut outcome = self.skills_for_cwd(cwd);
+        let workspace = Workspace::new(cwd);
+        let (skills, excluded): (Vec<_>, Vec<_>) = outcome
+            .skills
+            .into_iter()
+            .map(|skill| {
+                let reason = workspace.exclusion_reason(&skill.applies_to);
+                (skill, reason)
+            })
+            .partition(|(_, reason)| reason.is_none());
+        outcome.skills = skills.into_iter().map(|(skill, _)| skill).collect();
+        outcome
+            .excluded
+            .extend(excluded.into_iter().filter_map(|(skill, reason)| {
+                reason.map(|reason| ExcludedSkill {
+                    name: skill.name,
+                    reason,
+                })
+            }));
+        outcome
+    }
+
     pub fn skills_for_cwd_with_options(&self, cwd: &Path, force_reload: bool) -> SkillLoadOutcome {
         let cached = match self.cache_by_cwd.read() {
             Ok(cache) => cache.get(cwd).cloned(),
@@ -37,7 +109,8 @@ impl SkillsManager {
             return outcome;
         }
 
-        let roots = skill_roots_for_cwd(&self.codex_home, cwd);
+        let pack_roots = skill_pack_roots(self.codex_home.root(), &self.skill_packs());
+        let roots = skill_roots_for_cwd(self.codex_home.root(), cwd, pack_roots);
         let outcome = load_skills_from_roots(roots);
         match self.cache_by_cwd.write() {
             Ok(mut cache) => {
@@ -49,4 +122,35 @@ impl SkillsManager {
         }
         outcome
     }
+
+    /// How often each skill was available to, and invoked by, the user turns
+    /// of every session sharing this codex home, see [`crate::skills::usage`].
+    pub async fn usage_stats(&self) -> std::io::Result<BTreeMap<String, SkillUsageStats>> {
+        self.usage.stats().await
+    }
+
+    /// Count the skills of one user turn in [`Self::usage_stats`].
+    pub(crate) async fn record_usage(&self, usage: &SkillUsageEvent) {
+        if let Err(err) = self
+            .usage
+            .record_turn(&usage.available, &usage.invoked)
+            .await
+        {
+            tracing::warn!("failed to record skill usage: {err}");
+        }
+    }
+
+    fn skill_packs(&self) -> BTreeMap<String, SkillPackSource> {
+        match self.skill_packs.read() {
+            Ok(packs) => packs.clone(),
+            Err(err) => err.into_inner().clone(),
+        }
+    }
+
+    fn clear_cache(&self) {
+        match self.cache_by_cwd.write() {
+            Ok(mut cache) => cache.clear(),
+            Err(err) => err.into_inner().clear(),
+        }
+    }
 }
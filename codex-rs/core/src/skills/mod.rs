@@ -1,15 +1,25 @@
+pub mod applicability;
 pub mod injection;
 pub mod loader;
 pub mod manager;
 pub mod model;
+pub mod remote;
 pub mod render;
 pub mod system;
+pub mod tools;
+pub mod usage;
 
 pub(crate) use injection::SkillInjections;
 pub(crate) use injection::build_skill_injections;
 pub use loader::load_skills;
 pub use manager::SkillsManager;
+pub use model::SkillApplicability;
 pub use model::SkillError;
 pub use model::SkillLoadOutcome;
 pub use model::SkillMetadata;
+pub use model::SkillParameter;
+pub use remote::SkillPackError;
+pub use remote::SkillPackSource;
+pub use remote::SkillPackSyncReport;
 pub use render::render_skills_section;
+pub use usage::SkillUsageStats;
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use codex_protocol::protocol::ExcludedSkill;
 use codex_protocol::protocol::SkillScope;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,6 +10,41 @@ pub struct SkillMetadata {
     pub short_description: Option<String>,
     pub path: PathBuf,
     pub scope: SkillScope,
+    pub applies_to: SkillApplicability,
+    /// From `metadata.parameters` in `SKILL.md`. A skill that declares
+    /// parameters is offered as a tool in [`SkillsMode::Tools`].
+    ///
+    /// [`SkillsMode::Tools`]: codex_protocol::config_types::SkillsMode::Tools
+    pub parameters: Vec<SkillParameter>,
+}
+
+/// An argument of a skill offered as a tool, substituted for `{{name}}` in
+/// the body of its `SKILL.md`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillParameter {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// When a skill is offered to the model, from `metadata.applies-to` in
+/// `SKILL.md`. Every kind of rule that is present must pass; a skill without
+/// rules applies everywhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkillApplicability {
+    /// Glob patterns, at least one of which must match a file in the
+    /// workspace, relative to its root.
+    pub files: Vec<String>,
+    /// Programs that must all be found on `PATH`.
+    pub binaries: Vec<String>,
+    /// Languages, at least one of which must be detected in the workspace.
+    pub languages: Vec<String>,
+}
+
+impl SkillApplicability {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.binaries.is_empty() && self.languages.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,4 +57,7 @@ pub struct SkillError {
 pub struct SkillLoadOutcome {
     pub skills: Vec<SkillMetadata>,
     pub errors: Vec<SkillError>,
+    /// Skills that were loaded but left out of `skills`, see
+    /// [`crate::skills::SkillsManager::skills_for_workspace`].
+    pub excluded: Vec<ExcludedSkill>,
 }
@@ -0,0 +1,349 @@
+//! Skill packs fetched from a URL and pinned by version.
+//!
+//! Each `[skill_packs.<name>]` entry in `config.toml` points at an HTTPS
+//! tarball or a git repository:
+//!
+//! ```toml
+//! [skill_packs.platform]
+//! url = "https://example.com/platform-skills-1.4.0.tar.gz"
+//! version = "1.4.0"
+//! sha256 = "<sha256 of the tarball>"
+//!
+//! [skill_packs.team]
+//! url = "https://github.com/example/team-skills.git"
+//! version = "<full commit SHA>"
+//! ```
+//!
+//! [`SkillsManager::sync`] downloads packs into
+//! `CODEX_HOME/skills/.packs/<name>`. A tarball must match its `sha256`; a git
+//! pack must pin a full commit, which is verified after checkout. A pack
+//! whose cached copy already matches its entry is not fetched again, and when
+//! fetching fails the previously cached copy keeps being used.
+//!
+//! [`SkillsManager::sync`]: crate::skills::SkillsManager::sync
+
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_protocol::protocol::SkillScope;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::codex_home::CodexHome;
+use crate::default_client::build_reqwest_client;
+use crate::skills::loader::SkillRoot;
+
+const SKILL_PACKS_DIR_NAME: &str = ".packs";
+/// Records the source a cached pack was fetched from.
+const SKILL_PACK_MARKER_FILENAME: &str = ".codex-skill-pack.json";
+const ARCHIVE_FILENAME: &str = "pack.tar.gz";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Where a skill pack comes from and which version to use.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SkillPackSource {
+    /// HTTPS URL of a `.tar.gz`, or a git repository URL ending in `.git`.
+    pub url: String,
+
+    /// Version label for a tarball; full commit SHA for a git repository.
+    pub version: String,
+
+    /// Hex sha256 of the tarball. Required for tarballs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl SkillPackSource {
+    fn is_git(&self) -> bool {
+        self.url.ends_with(".git") || self.url.starts_with("git@") || self.url.starts_with("git://")
+    }
+}
+
+/// Result of [`SkillsManager::sync`].
+///
+/// [`SkillsManager::sync`]: crate::skills::SkillsManager::sync
+#[derive(Debug, Default)]
+pub struct SkillPackSyncReport {
+    /// Packs downloaded by this sync.
+    pub fetched: Vec<String>,
+    /// Packs served from the cache, including ones that could not be
+    /// refreshed because their source was unreachable.
+    pub cached: Vec<String>,
+    pub errors: Vec<SkillPackError>,
+}
+
+#[derive(Debug, Error)]
+pub enum SkillPackError {
+    #[error("skill pack name `{name}` may only contain letters, digits, `-` and `_`")]
+    InvalidName { name: String },
+
+    #[error("skill pack `{name}`: {message}")]
+    InvalidSource { name: String, message: String },
+
+    #[error("failed to fetch skill pack `{name}` from {url}: {message}")]
+    Fetch {
+        name: String,
+        url: String,
+        message: String,
+    },
+
+    #[error("skill pack `{name}` checksum mismatch: expected sha256 {expected}, got {actual}")]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("failed to install skill pack `{name}`: {message}")]
+    Install { name: String, message: String },
+}
+
+/// Roots of the cached packs among `packs`, in name order.
+pub(crate) fn skill_pack_roots(
+    codex_home: &Path,
+    packs: &BTreeMap<String, SkillPackSource>,
+) -> Vec<SkillRoot> {
+    packs
+        .keys()
+        .filter(|name| valid_pack_name(name))
+        .filter_map(|name| {
+            let path = pack_dir(codex_home, name);
+            path.is_dir().then(|| SkillRoot {
+                path,
+                scope: SkillScope::User,
+                pack: Some(name.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Bring the cache for every entry in `packs` up to date.
+pub(crate) async fn sync_skill_packs(
+    codex_home: &Path,
+    packs: &BTreeMap<String, SkillPackSource>,
+) -> SkillPackSyncReport {
+    let mut report = SkillPackSyncReport::default();
+    for (name, source) in packs {
+        if !valid_pack_name(name) {
+            report
+                .errors
+                .push(SkillPackError::InvalidName { name: name.clone() });
+            continue;
+        }
+        let dir = pack_dir(codex_home, name);
+        if read_marker(&dir).await.as_ref() == Some(source) {
+            report.cached.push(name.clone());
+            continue;
+        }
+        match fetch_pack(codex_home, name, source).await {
+            Ok(()) => report.fetched.push(name.clone()),
+            // Offline: keep using whatever was cached before.
+            Err(err @ SkillPackError::Fetch { .. }) if dir.is_dir() => {
+                debug!("using cached skill pack `{name}`: {err}");
+                report.cached.push(name.clone());
+            }
+            Err(err) => report.errors.push(err),
+        }
+    }
+    report
+}
+
+async fn fetch_pack(
+    codex_home: &Path,
+    name: &str,
+    source: &SkillPackSource,
+) -> Result<(), SkillPackError> {
+    let install_err = |message: String| SkillPackError::Install {
+        name: name.to_string(),
+        message,
+    };
+    let packs_dir = CodexHome::new(codex_home)
+        .skills_dir()
+        .join(SKILL_PACKS_DIR_NAME);
+    let staging = packs_dir.join(format!(".staging-{name}"));
+    remove_dir_if_exists(&staging)
+        .await
+        .map_err(|err| install_err(format!("failed to clear {}: {err}", staging.display())))?;
+    tokio::fs::create_dir_all(&staging)
+        .await
+        .map_err(|err| install_err(format!("failed to create {}: {err}", staging.display())))?;
+
+    let result = if source.is_git() {
+        fetch_git(name, source, &staging).await
+    } else {
+        fetch_tarball(name, source, &staging).await
+    };
+    if let Err(err) = result {
+        let _ = remove_dir_if_exists(&staging).await;
+        return Err(err);
+    }
+
+    let marker = serde_json::to_vec_pretty(source)
+        .map_err(|err| install_err(format!("failed to record source: {err}")))?;
+    tokio::fs::write(staging.join(SKILL_PACK_MARKER_FILENAME), marker)
+        .await
+        .map_err(|err| install_err(format!("failed to record source: {err}")))?;
+
+    // Replace the previous copy only once the new one is complete.
+    let dir = pack_dir(codex_home, name);
+    remove_dir_if_exists(&dir)
+        .await
+        .map_err(|err| install_err(format!("failed to remove {}: {err}", dir.display())))?;
+    tokio::fs::rename(&staging, &dir)
+        .await
+        .map_err(|err| install_err(format!("failed to move pack into place: {err}")))
+}
+
+async fn fetch_tarball(
+    name: &str,
+    source: &SkillPackSource,
+    staging: &Path,
+) -> Result<(), SkillPackError> {
+    let Some(expected) = source.sha256.as_deref() else {
+        return Err(SkillPackError::InvalidSource {
+            name: name.to_string(),
+            message: "a tarball needs a `sha256`".to_string(),
+        });
+    };
+    let fetch_err = |message: String| SkillPackError::Fetch {
+        name: name.to_string(),
+        url: source.url.clone(),
+        message,
+    };
+    let bytes = build_reqwest_client()
+        .get(&source.url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| fetch_err(err.to_string()))?
+        .bytes()
+        .await
+        .map_err(|err| fetch_err(err.to_string()))?;
+
+    let actual = format!("{:x}", Sha256::digest(&bytes));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(SkillPackError::ChecksumMismatch {
+            name: name.to_string(),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+
+    let install_err = |message: String| SkillPackError::Install {
+        name: name.to_string(),
+        message,
+    };
+    let archive = staging.join(ARCHIVE_FILENAME);
+    tokio::fs::write(&archive, &bytes)
+        .await
+        .map_err(|err| install_err(format!("failed to write archive: {err}")))?;
+    run(Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(staging))
+    .await
+    .map_err(|message| install_err(format!("failed to unpack archive: {message}")))?;
+    tokio::fs::remove_file(&archive)
+        .await
+        .map_err(|err| install_err(format!("failed to remove archive: {err}")))
+}
+
+async fn fetch_git(
+    name: &str,
+    source: &SkillPackSource,
+    staging: &Path,
+) -> Result<(), SkillPackError> {
+    let commit = source.version.to_ascii_lowercase();
+    if commit.len() != 40 || !commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(SkillPackError::InvalidSource {
+            name: name.to_string(),
+            message: "a git pack must pin a full commit SHA as its `version`".to_string(),
+        });
+    }
+    let fetch_err = |message: String| SkillPackError::Fetch {
+        name: name.to_string(),
+        url: source.url.clone(),
+        message,
+    };
+    run(Command::new("git")
+        .args(["clone", "--quiet", "--no-checkout", &source.url])
+        .arg(staging))
+    .await
+    .map_err(fetch_err)?;
+
+    let install_err = |message: String| SkillPackError::Install {
+        name: name.to_string(),
+        message,
+    };
+    run(Command::new("git")
+        .args(["checkout", "--quiet", "--detach", &commit])
+        .current_dir(staging))
+    .await
+    .map_err(|message| install_err(format!("failed to check out {commit}: {message}")))?;
+    let head = run(Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(staging))
+    .await
+    .map_err(install_err)?;
+    if head.trim() != commit {
+        return Err(install_err(format!(
+            "checked out {} instead of {commit}",
+            head.trim()
+        )));
+    }
+
+    tokio::fs::remove_dir_all(staging.join(".git"))
+        .await
+        .map_err(|err| install_err(format!("failed to remove git metadata: {err}")))
+}
+
+/// Run `command`, returning its stdout or a description of the failure.
+async fn run(command: &mut Command) -> Result<String, String> {
+    let output = tokio::time::timeout(FETCH_TIMEOUT, command.output())
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", output.status, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn read_marker(dir: &Path) -> Option<SkillPackSource> {
+    let contents = tokio::fs::read(dir.join(SKILL_PACK_MARKER_FILENAME))
+        .await
+        .ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+async fn remove_dir_if_exists(dir: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_dir_all(dir).await {
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn pack_dir(codex_home: &Path, name: &str) -> PathBuf {
+    CodexHome::new(codex_home)
+        .skills_dir()
+        .join(SKILL_PACKS_DIR_NAME)
+        .join(name)
+}
+
+fn valid_pack_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
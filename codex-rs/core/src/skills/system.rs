@@ -9,11 +9,12 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
+use crate::codex_home::SKILLS_SUBDIR;
+
 const SYSTEM_SKILLS_DIR: Dir =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/src/skills/assets/samples");
 
 const SYSTEM_SKILLS_DIR_NAME: &str = ".system";
-const SKILLS_DIR_NAME: &str = "skills";
 const SYSTEM_SKILLS_MARKER_FILENAME: &str = ".codex-system-skills.marker";
 const SYSTEM_SKILLS_MARKER_SALT: &str = "v1";
 
@@ -24,17 +25,11 @@ pub(crate) fn system_cache_root_dir(codex_home: &Path) -> PathBuf {
     AbsolutePathBuf::try_from(codex_home)
         .and_then(|codex_home| system_cache_root_dir_abs(&codex_home))
         .map(AbsolutePathBuf::into_path_buf)
-        .unwrap_or_else(|_| {
-            codex_home
-                .join(SKILLS_DIR_NAME)
-                .join(SYSTEM_SKILLS_DIR_NAME)
-        })
+        .unwrap_or_else(|_| codex_home.join(SKILLS_SUBDIR).join(SYSTEM_SKILLS_DIR_NAME))
 }
 
 fn system_cache_root_dir_abs(codex_home: &AbsolutePathBuf) -> std::io::Result<AbsolutePathBuf> {
-    codex_home
-        .join(SKILLS_DIR_NAME)?
-        .join(SYSTEM_SKILLS_DIR_NAME)
+    codex_home.join(SKILLS_SUBDIR)?.join(SYSTEM_SKILLS_DIR_NAME)
 }
 
 /// Installs embedded system skills into `CODEX_HOME/skills/.system`.
@@ -49,7 +44,7 @@ pub(crate) fn install_system_skills(codex_home: &Path) -> Result<(), SystemSkill
     let codex_home = AbsolutePathBuf::try_from(codex_home)
         .map_err(|source| SystemSkillsError::io("normalize codex home dir", source))?;
     let skills_root_dir = codex_home
-        .join(SKILLS_DIR_NAME)
+        .join(SKILLS_SUBDIR)
         .map_err(|source| SystemSkillsError::io("resolve skills root dir", source))?;
     fs::create_dir_all(skills_root_dir.as_path())
         .map_err(|source| SystemSkillsError::io("create skills root dir", source))?;
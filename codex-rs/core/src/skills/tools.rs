@@ -0,0 +1,184 @@
+//! Skills offered to the model as tools, see
+//! [`SkillsMode::Tools`](codex_protocol::config_types::SkillsMode::Tools).
+//!
+//! Each skill that declares parameters becomes a function tool named after
+//! it. Calling the tool returns the body of its `SKILL.md`, without the
+//! frontmatter, with every `{{parameter}}` replaced by the argument the model
+//! passed; parameters the model left out are replaced by nothing.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use crate::skills::SkillMetadata;
+
+/// Prefix of the tool a skill is offered as.
+pub(crate) const SKILL_TOOL_PREFIX: &str = "skill_";
+
+/// Longest tool name providers accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// The name of the tool `skill` is offered as: its name with everything but
+/// letters, digits, `_` and `-` replaced by `_`.
+pub(crate) fn skill_tool_name(skill: &SkillMetadata) -> String {
+    let name: String = skill
+        .name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{SKILL_TOOL_PREFIX}{name}")
+        .chars()
+        .take(MAX_TOOL_NAME_LEN)
+        .collect()
+}
+
+/// Split `skills` into the ones offered as tools and the ones still listed
+/// in the instructions: skills without parameters, and skills whose tool
+/// name is taken by an earlier one.
+pub(crate) fn partition_tool_skills(
+    skills: Vec<SkillMetadata>,
+) -> (Vec<SkillMetadata>, Vec<SkillMetadata>) {
+    let mut names = HashSet::new();
+    skills
+        .into_iter()
+        .partition(|skill| !skill.parameters.is_empty() && names.insert(skill_tool_name(skill)))
+}
+
+/// The body of `contents`, a `SKILL.md`, with the parameters of `skill`
+/// substituted from `arguments`. Fails with a message for the model when a
+/// required argument is missing or an argument is not a parameter.
+pub(crate) fn render_skill(
+    skill: &SkillMetadata,
+    contents: &str,
+    arguments: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    if let Some(unknown) = arguments
+        .keys()
+        .find(|name| !skill.parameters.iter().any(|param| &param.name == *name))
+    {
+        return Err(format!("skill {} has no parameter `{unknown}`", skill.name));
+    }
+    if let Some(missing) = skill
+        .parameters
+        .iter()
+        .find(|param| param.required && !arguments.contains_key(&param.name))
+    {
+        return Err(format!(
+            "skill {} requires the parameter `{}`",
+            skill.name, missing.name
+        ));
+    }
+
+    let mut body = strip_frontmatter(contents).to_string();
+    for param in &skill.parameters {
+        let value = arguments.get(&param.name).map(String::as_str).unwrap_or("");
+        body = body.replace(&format!("{{{{{}}}}}", param.name), value);
+    }
+    Ok(body)
+}
+
+/// `contents` after its `---` delimited frontmatter, if it has one.
+fn strip_frontmatter(contents: &str) -> &str {
+    let mut rest = contents;
+    let Some(first) = next_line(&mut rest) else {
+        return contents;
+    };
+    if first.trim() != "---" {
+        return contents;
+    }
+    while let Some(line) = next_line(&mut rest) {
+        if line.trim() == "---" {
+            return rest.trim_start_matches(['\r', '\n']);
+        }
+    }
+    contents
+}
+
+fn next_line<'a>(rest: &mut &'a str) -> Option<&'a str> {
+    if rest.is_empty() {
+        return None;
+    }
+    let (line, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+    *rest = tail;
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skills::SkillParameter;
+    use codex_protocol::protocol::SkillScope;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn skill(name: &str, parameters: &[(&str, bool)]) -> SkillMetadata {
+        SkillMetadata {
+            name: name.to_string(),
+            description: "description".to_string(),
+            short_description: None,
+            path: PathBuf::from("/skills").join(name).join("SKILL.md"),
+            scope: SkillScope::User,
+            applies_to: Default::default(),
+            parameters: parameters
+                .iter()
+                .map(|(name, required)| SkillParameter {
+                    name: name.to_string(),
+                    description: String::new(),
+                    required: *required,
+                })
+                .collect(),
+        }
+    }
+
+    fn arguments(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn only_distinct_parameterized_skills_become_tools() {
+        let (tools, prompt) = partition_tool_skills(vec![
+            skill("deploy app", &[("env", true)]),
+            skill("deploy_app", &[("env", true)]),
+            skill("notes", &[]),
+        ]);
+        let names = |skills: &[SkillMetadata]| {
+            skills
+                .iter()
+                .map(|skill| skill.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&tools), vec!["deploy app"]);
+        assert_eq!(names(&prompt), vec!["deploy_app", "notes"]);
+        assert_eq!(skill_tool_name(&tools[0]), "skill_deploy_app");
+    }
+
+    #[test]
+    fn renders_the_body_with_arguments_substituted() {
+        let skill = skill("deploy", &[("env", true), ("tag", false)]);
+        let contents = "---\nname: deploy\ndescription: d\n---\n\nDeploy {{tag}} to {{env}}.\n";
+        assert_eq!(
+            render_skill(&skill, contents, &arguments(&[("env", "prod")])),
+            Ok("Deploy  to prod.\n".to_string())
+        );
+        assert_eq!(
+            render_skill(&skill, contents, &arguments(&[("tag", "v1")])),
+            Err("skill deploy requires the parameter `env`".to_string())
+        );
+        assert_eq!(
+            render_skill(
+                &skill,
+                contents,
+                &arguments(&[("env", "prod"), ("region", "eu")])
+            ),
+            Err("skill deploy has no parameter `region`".to_string())
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Per-skill usage counts across sessions, see [`SkillsManager::usage_stats`].
+//!
+//! Every user turn with skills loaded counts each skill in its context as
+//! available, and each skill it explicitly invoked as invoked, so a skill
+//! that was only listed in the instructions is never credited with a turn.
+//! The counts are stored in `$CODEX_HOME/skill_usage.json`.
+//!
+//! [`SkillsManager::usage_stats`]: crate::skills::SkillsManager::usage_stats
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::json_store::JsonStore;
+
+const SKILL_USAGE_FILENAME: &str = "skill_usage.json";
+
+/// How often one skill was used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillUsageStats {
+    /// User turns the skill was in context for.
+    #[serde(default)]
+    pub available_turns: u64,
+    /// User turns that explicitly invoked the skill, through its tool or by
+    /// the user mentioning it.
+    #[serde(default)]
+    pub invoked_turns: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SkillUsageFile {
+    #[serde(default)]
+    skills: BTreeMap<String, SkillUsageStats>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SkillUsageStore {
+    store: JsonStore<SkillUsageFile>,
+}
+
+impl SkillUsageStore {
+    pub(crate) fn new(codex_home: &Path) -> Self {
+        Self {
+            store: JsonStore::new(codex_home.join(SKILL_USAGE_FILENAME)),
+        }
+    }
+
+    /// Count one turn that had `available` in context and invoked `invoked`.
+    pub(crate) async fn record_turn(
+        &self,
+        available: &[String],
+        invoked: &[String],
+    ) -> std::io::Result<()> {
+        if available.is_empty() && invoked.is_empty() {
+            return Ok(());
+        }
+        self.store
+            .update(|file| {
+                for name in available {
+                    file.skills.entry(name.clone()).or_default().available_turns += 1;
+                }
+                for name in invoked {
+                    file.skills.entry(name.clone()).or_default().invoked_turns += 1;
+                }
+                true
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn stats(&self) -> std::io::Result<BTreeMap<String, SkillUsageStats>> {
+        Ok(self.store.load().await?.skills)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn counts_accumulate_across_stores() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let store = SkillUsageStore::new(codex_home.path());
+        store
+            .record_turn(&names(&["deploy", "lint"]), &names(&["deploy"]))
+            .await?;
+        store.record_turn(&names(&["deploy", "lint"]), &[]).await?;
+
+        let reloaded = SkillUsageStore::new(codex_home.path());
+        assert_eq!(
+            reloaded.stats().await?,
+            BTreeMap::from([
+                (
+                    "deploy".to_string(),
+                    SkillUsageStats {
+                        available_turns: 2,
+                        invoked_turns: 1,
+                    }
+                ),
+                (
+                    "lint".to_string(),
+                    SkillUsageStats {
+                        available_turns: 2,
+                        invoked_turns: 0,
+                    }
+                ),
+            ])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Progress of a conversation that is being spawned.
+//!
+//! [`ConversationManager::new_conversation`] only returns once the session
+//! reported `SessionConfigured`. A [`SpawnObserver`] passed to
+//! [`ConversationManager::new_conversation_with_observer`] hears about each
+//! [`SpawnMilestone`] as the spawn reaches it, in the order they are listed,
+//! with the id of the conversation. When the spawn fails after the id was
+//! allocated, the observer is told with [`SpawnMilestone::Failed`], so a hung
+//! or failed spawn can still be logged against its id and its rollout file
+//! cleaned up. Failures before that, such as the manager being at its
+//! conversation limit, are only returned.
+//!
+//! [`ConversationManager::new_conversation`]: crate::ConversationManager::new_conversation
+//! [`ConversationManager::new_conversation_with_observer`]: crate::ConversationManager::new_conversation_with_observer
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_protocol::ConversationId;
+
+use crate::error::CodexErr;
+
+/// A step of a spawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnMilestone {
+    /// The conversation got its id. Resumed conversations keep the id their
+    /// rollout recorded.
+    IdAllocated,
+    /// The rollout file the session records to exists at `path`. It is empty
+    /// until the session is configured; a resumed rollout is reported as it
+    /// is.
+    RolloutCreated { path: PathBuf },
+    /// The session asked the provider for its model list. Skipped when the
+    /// list comes from a fresh cache or the built-in presets.
+    ProviderRequestIssued,
+    /// The session reported `SessionConfigured` and the conversation is
+    /// returned to the caller.
+    SessionConfigured,
+    /// The spawn failed with `error`, and the rollout it created was
+    /// removed.
+    Failed { error: String },
+}
+
+/// Told about the milestones of the spawns it is passed to.
+pub trait SpawnObserver: Send + Sync {
+    fn on_milestone(&self, conversation_id: ConversationId, milestone: SpawnMilestone);
+}
+
+/// The observer of a spawn, if there is one.
+#[derive(Clone, Default)]
+pub struct SpawnProgress {
+    observer: Option<Arc<dyn SpawnObserver>>,
+}
+
+impl SpawnProgress {
+    pub(crate) fn new(observer: Arc<dyn SpawnObserver>) -> Self {
+        Self {
+            observer: Some(observer),
+        }
+    }
+
+    pub(crate) fn report(&self, conversation_id: ConversationId, milestone: SpawnMilestone) {
+        if let Some(observer) = &self.observer {
+            observer.on_milestone(conversation_id, milestone);
+        }
+    }
+
+    pub(crate) fn failed(&self, conversation_id: ConversationId, error: &CodexErr) {
+        self.report(
+            conversation_id,
+            SpawnMilestone::Failed {
+                error: error.to_string(),
+            },
+        );
+    }
+}
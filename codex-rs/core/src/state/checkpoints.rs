@@ -0,0 +1,101 @@
+//! Named checkpoints into the in-memory conversation history.
+
+use std::ops::Range;
+
+use codex_protocol::protocol::CheckpointId;
+
+/// A checkpoint records how many history items existed when it was taken.
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    id: CheckpointId,
+    history_len: usize,
+}
+
+/// Checkpoints ordered from oldest to newest.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Checkpoints {
+    entries: Vec<Checkpoint>,
+}
+
+impl Checkpoints {
+    pub(crate) fn record(&mut self, id: CheckpointId, history_len: usize) {
+        self.entries.push(Checkpoint { id, history_len });
+    }
+
+    /// Forget the checkpoints recorded after `id` and return the history length
+    /// captured by `id` together with the ids that were invalidated. Returns
+    /// `None` when `id` is unknown.
+    pub(crate) fn rollback(&mut self, id: &CheckpointId) -> Option<(usize, Vec<CheckpointId>)> {
+        let position = self.entries.iter().position(|entry| &entry.id == id)?;
+        let history_len = self.entries[position].history_len;
+        let invalidated = self
+            .entries
+            .split_off(position + 1)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        Some((history_len, invalidated))
+    }
+
+    /// Account for the history items of `dropped` being removed. Rolling
+    /// back to a checkpoint among them restores the history up to
+    /// `dropped.start`, since the items in between are gone.
+    pub(crate) fn drop_items(&mut self, dropped: Range<usize>) {
+        for entry in &mut self.entries {
+            if entry.history_len > dropped.start {
+                entry.history_len = entry
+                    .history_len
+                    .saturating_sub(dropped.len())
+                    .max(dropped.start);
+            }
+        }
+    }
+
+    /// Drop every checkpoint, e.g. after the history was rewritten by compaction.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rollback_invalidates_later_checkpoints() {
+        let mut checkpoints = Checkpoints::default();
+        let first = CheckpointId::new();
+        let second = CheckpointId::new();
+        let third = CheckpointId::new();
+        checkpoints.record(first.clone(), 3);
+        checkpoints.record(second.clone(), 7);
+        checkpoints.record(third.clone(), 9);
+
+        assert_eq!(
+            checkpoints.rollback(&second),
+            Some((7, vec![third.clone()]))
+        );
+        assert_eq!(checkpoints.rollback(&third), None);
+        assert_eq!(checkpoints.rollback(&first), Some((3, vec![second])));
+        assert_eq!(checkpoints.rollback(&first), Some((3, Vec::new())));
+    }
+
+    #[test]
+    fn dropping_items_moves_later_checkpoints_back() {
+        let mut checkpoints = Checkpoints::default();
+        let ids: Vec<CheckpointId> = (0..4).map(|_| CheckpointId::new()).collect();
+        for (id, history_len) in ids.iter().zip([2, 3, 6, 9]) {
+            checkpoints.record(id.clone(), history_len);
+        }
+
+        checkpoints.drop_items(2..6);
+
+        let lens: Vec<usize> = checkpoints
+            .entries
+            .iter()
+            .map(|entry| entry.history_len)
+            .collect();
+        assert_eq!(lens, vec![2, 2, 2, 5]);
+    }
+}
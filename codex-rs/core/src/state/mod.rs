@@ -1,7 +1,12 @@
+mod checkpoints;
+mod pins;
 mod service;
 mod session;
 mod turn;
 
+pub(crate) use checkpoints::Checkpoints;
+pub(crate) use pins::Pins;
+pub(crate) use pins::resolve_item;
 pub(crate) use service::SessionServices;
 pub(crate) use session::SessionState;
 pub(crate) use turn::ActiveTurn;
@@ -0,0 +1,206 @@
+//! History items pinned against compaction and token-budget truncation.
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ItemRef;
+
+use crate::classify::ItemClass;
+use crate::classify::classify_item;
+use crate::compact::is_compaction_summary;
+
+/// Pinned items, kept by value so a pin follows its item wherever compaction
+/// moves it. Identical items are pinned together.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Pins {
+    items: Vec<ResponseItem>,
+}
+
+impl Pins {
+    /// Pin `item`. Returns `false` when it was already pinned.
+    pub(crate) fn pin(&mut self, item: ResponseItem) -> bool {
+        if self.contains(&item) {
+            return false;
+        }
+        self.items.push(item);
+        true
+    }
+
+    /// Unpin `item`. Returns `false` when it was not pinned.
+    pub(crate) fn unpin(&mut self, item: &ResponseItem) -> bool {
+        let before = self.items.len();
+        self.items.retain(|pinned| pinned != item);
+        self.items.len() != before
+    }
+
+    pub(crate) fn contains(&self, item: &ResponseItem) -> bool {
+        self.items.contains(item)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Forget the pins of items no longer in `history`, e.g. after a rollback
+    /// cut them, so compaction does not bring them back.
+    pub(crate) fn retain_present(&mut self, history: &[ResponseItem]) {
+        self.items.retain(|pinned| history.contains(pinned));
+    }
+
+    /// Put the pinned items of `previous` that `replacement` lacks back into
+    /// it, in their original order, ahead of the compaction summary: the
+    /// local summary message or the remote compaction item, or the ghost
+    /// snapshots that follow it when there is neither.
+    pub(crate) fn restore(&self, previous: &[ResponseItem], replacement: &mut Vec<ResponseItem>) {
+        let missing: Vec<ResponseItem> = previous
+            .iter()
+            .filter(|item| self.contains(item) && !replacement.contains(item))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let summary = replacement
+            .iter()
+            .position(is_compaction_summary)
+            .or_else(|| {
+                replacement
+                    .iter()
+                    .position(|item| matches!(item, ResponseItem::GhostSnapshot { .. }))
+            })
+            .unwrap_or(replacement.len());
+        replacement.splice(summary..summary, missing);
+    }
+}
+
+/// The item of `history` that `item` names.
+pub(crate) fn resolve_item<'a>(
+    history: &'a [ResponseItem],
+    item: &ItemRef,
+) -> Option<&'a ResponseItem> {
+    match item {
+        ItemRef::Turn { turn, offset } => {
+            let mut starts = history
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| classify_item(item) == ItemClass::UserMessage)
+                .map(|(index, _)| index)
+                .skip(*turn);
+            let start = starts.next()?;
+            let end = starts.next().unwrap_or(history.len());
+            let index = start.checked_add(*offset)?;
+            if index < end {
+                history.get(index)
+            } else {
+                None
+            }
+        }
+        ItemRef::Id { id } => history
+            .iter()
+            .find(|item| item_id(item) == Some(id.as_str())),
+    }
+}
+
+fn item_id(item: &ResponseItem) -> Option<&str> {
+    match item {
+        ResponseItem::Reasoning { id, .. } => Some(id),
+        ResponseItem::Message { id, .. }
+        | ResponseItem::LocalShellCall { id, .. }
+        | ResponseItem::FunctionCall { id, .. }
+        | ResponseItem::CustomToolCall { id, .. }
+        | ResponseItem::WebSearchCall { id, .. } => id.as_deref(),
+        ResponseItem::FunctionCallOutput { .. }
+        | ResponseItem::CustomToolCallOutput { .. }
+        | ResponseItem::GhostSnapshot { .. }
+        | ResponseItem::Compaction { .. }
+        | ResponseItem::Other => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::compact::SUMMARY_PREFIX;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn assistant(id: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: Some(id.to_string()),
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn resolves_items_by_turn_offset_and_id() {
+        let history = vec![
+            user("<environment_context>cwd</environment_context>"),
+            user("first"),
+            assistant("msg-1", "one"),
+            user("second"),
+            assistant("msg-2", "two"),
+        ];
+
+        let resolve = |item: ItemRef| resolve_item(&history, &item).cloned();
+        assert_eq!(
+            resolve(ItemRef::Turn { turn: 0, offset: 0 }),
+            Some(user("first"))
+        );
+        assert_eq!(
+            resolve(ItemRef::Turn { turn: 1, offset: 1 }),
+            Some(assistant("msg-2", "two"))
+        );
+        // Offsets do not reach into the next turn.
+        assert_eq!(resolve(ItemRef::Turn { turn: 0, offset: 2 }), None);
+        assert_eq!(resolve(ItemRef::Turn { turn: 2, offset: 0 }), None);
+        assert_eq!(
+            resolve(ItemRef::Id {
+                id: "msg-1".to_string()
+            }),
+            Some(assistant("msg-1", "one"))
+        );
+    }
+
+    #[test]
+    fn restore_puts_missing_pins_ahead_of_the_summary() {
+        let mut pins = Pins::default();
+        pins.pin(user("the spec"));
+        pins.pin(assistant("msg-1", "the plan"));
+        pins.pin(user("gone"));
+        let previous = vec![
+            user("the spec"),
+            assistant("msg-1", "the plan"),
+            user("later"),
+        ];
+        let summary = user(&format!("{SUMMARY_PREFIX}\nall of it"));
+        let mut replacement = vec![user("later"), summary.clone()];
+
+        pins.restore(&previous, &mut replacement);
+
+        assert_eq!(
+            replacement,
+            vec![
+                user("later"),
+                user("the spec"),
+                assistant("msg-1", "the plan"),
+                summary,
+            ]
+        );
+
+        pins.retain_present(&replacement);
+        assert!(!pins.unpin(&user("gone")));
+        assert!(pins.unpin(&user("the spec")));
+    }
+}
@@ -2,14 +2,39 @@ use std::sync::Arc;
 
 use crate::AuthManager;
 use crate::RolloutRecorder;
+use crate::adaptive_effort::AdaptiveEffortPolicy;
+use crate::blob_store::BlobStore;
+use crate::context_usage::ContextUsageTracker;
+use crate::context_usage::TokenCounter;
+use crate::correlation::CorrelationIds;
 use crate::exec_policy::ExecPolicyManager;
+use crate::git_context::GitContextTracker;
+use crate::instructions_refresh::InstructionsWatcher;
+use crate::manager_load::ConversationLoad;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::models_manager::manager::ModelsManager;
+use crate::pause::PauseState;
+use crate::post_process::TurnPostProcessor;
+use crate::pre_process::TurnPreProcessor;
+use crate::project_approvals::ProjectApprovalStore;
+use crate::project_notes::ProjectNotes;
+use crate::reasoning_log::ReasoningLog;
+use crate::request_trace::RequestTraceSink;
+use crate::rollout::live::LiveRollout;
+use crate::runtime_env::RuntimeEnv;
+use crate::sensitive_input::SensitiveInputGuard;
+use crate::session_stats::SessionStatsTracker;
 use crate::skills::SkillsManager;
+use crate::tools::read_cache::ReadCache;
 use crate::tools::sandboxing::ApprovalStore;
+use crate::transcript_journal::TranscriptJournal;
+use crate::turn_results::TurnResults;
 use crate::unified_exec::UnifiedExecSessionManager;
+use crate::user_context::UserContextClock;
 use crate::user_notification::UserNotifier;
 use codex_otel::otel_manager::OtelManager;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::WarningSeverity;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
@@ -20,12 +45,44 @@ pub(crate) struct SessionServices {
     pub(crate) unified_exec_manager: UnifiedExecSessionManager,
     pub(crate) notifier: UserNotifier,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
+    pub(crate) live_rollout: Option<LiveRollout>,
     pub(crate) user_shell: Arc<crate::shell::Shell>,
     pub(crate) show_raw_agent_reasoning: bool,
+    /// See `Config::min_warning_severity`.
+    pub(crate) min_warning_severity: WarningSeverity,
+    /// See `Config::adaptive_effort`.
+    pub(crate) adaptive_effort: Option<AdaptiveEffortPolicy>,
     pub(crate) exec_policy: ExecPolicyManager,
     pub(crate) auth_manager: Arc<AuthManager>,
     pub(crate) models_manager: Arc<ModelsManager>,
     pub(crate) otel_manager: OtelManager,
     pub(crate) tool_approvals: Mutex<ApprovalStore>,
+    pub(crate) project_approvals: ProjectApprovalStore,
+    pub(crate) project_notes: ProjectNotes,
+    /// The `<project_notes>` item of the session prefix, `None` when there
+    /// is nothing to tell.
+    pub(crate) project_notes_prefix: Option<ResponseItem>,
+    pub(crate) pre_processors: Vec<Arc<dyn TurnPreProcessor>>,
+    pub(crate) post_processors: Vec<Arc<dyn TurnPostProcessor>>,
     pub(crate) skills_manager: Arc<SkillsManager>,
+    pub(crate) request_trace: Option<RequestTraceSink>,
+    pub(crate) token_counter: Arc<dyn TokenCounter>,
+    pub(crate) context_usage: Arc<ContextUsageTracker>,
+    pub(crate) sensitive_input: Arc<SensitiveInputGuard>,
+    pub(crate) reasoning_log: Arc<ReasoningLog>,
+    pub(crate) transcript_journal: Arc<TranscriptJournal>,
+    pub(crate) git_context: Arc<GitContextTracker>,
+    pub(crate) persist_reasoning_summaries: bool,
+    pub(crate) record_input_rejections: bool,
+    pub(crate) user_context: Option<UserContextClock>,
+    pub(crate) instructions_watcher: InstructionsWatcher,
+    pub(crate) session_stats: SessionStatsTracker,
+    pub(crate) correlation_ids: CorrelationIds,
+    pub(crate) turn_results: Arc<TurnResults>,
+    pub(crate) pause: Arc<PauseState>,
+    pub(crate) load: Arc<ConversationLoad>,
+    pub(crate) read_cache: ReadCache,
+    /// Where outputs cut to `max_tool_output_bytes` are kept in full.
+    pub(crate) blobs: BlobStore,
+    pub(crate) runtime_env: RuntimeEnv,
 }
@@ -1,19 +1,69 @@
 //! Session-wide mutable state.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::CheckpointId;
+use codex_protocol::protocol::PendingInput;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SafeModePolicies;
+use codex_protocol::protocol::ToolDowngradeReport;
+use codex_protocol::protocol::TurnId;
+use tokio::sync::oneshot;
 
+use crate::adaptive_effort::EffortAdapter;
 use crate::codex::SessionConfiguration;
 use crate::context_manager::ContextManager;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::state::Checkpoints;
+use crate::state::Pins;
 use crate::truncate::TruncationPolicy;
+use crate::turn_tally::TurnTally;
+use crate::workspace_watch::WorkspaceSnapshot;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
 pub(crate) struct SessionState {
     pub(crate) session_configuration: SessionConfiguration,
     pub(crate) history: ContextManager,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    pub(crate) checkpoints: Checkpoints,
+    pub(crate) pins: Pins,
+    /// Workspace state at the end of the previous turn, when
+    /// `Feature::WorkspaceWatch` is enabled.
+    pub(crate) workspace_snapshot: Option<WorkspaceSnapshot>,
+    /// Sampling parameters already reported as ignored by the provider or
+    /// model.
+    pub(crate) ignored_sampling_warned: HashSet<&'static str>,
+    /// Tool adjustments last reported to clients.
+    pub(crate) reported_tool_downgrades: ToolDowngradeReport,
+    /// Id of the latest user turn, recorded or restored from the rollout.
+    pub(crate) last_turn_id: Option<TurnId>,
+    /// Partial messages recorded in that turn, see
+    /// [`crate::partial_messages`].
+    pub(crate) partial_message_seq: u64,
+    /// Inputs submitted while offline, oldest first, see `Config::offline`.
+    pub(crate) offline_queue: VecDeque<PendingInput>,
+    /// Id of the `Op::FlushQueued` whose turns are running the offline
+    /// queue, one after the other.
+    pub(crate) offline_flush: Option<String>,
+    /// What each running task recorded, by sub id.
+    pub(crate) turn_tallies: HashMap<String, TurnTally>,
+    /// The policies safe mode replaced, restored when it is turned off;
+    /// `None` outside safe mode, see [`crate::safe_mode`].
+    pub(crate) safe_mode: Option<SafeModePolicies>,
+    /// Submission id of the pending approval to leave safe mode, with the
+    /// channel its decision is sent on.
+    pub(crate) safe_mode_exit: Option<(String, oneshot::Sender<ReviewDecision>)>,
+    /// Request segments a fingerprint in the rollout already keeps, see
+    /// [`crate::rollout::reconstruct`].
+    pub(crate) recorded_segments: HashSet<String>,
+    /// Reasoning effort adapted to how the previous turns went, see
+    /// [`crate::adaptive_effort`].
+    pub(crate) effort_adapter: EffortAdapter,
 }
 
 impl SessionState {
@@ -24,6 +74,20 @@ impl SessionState {
             session_configuration,
             history,
             latest_rate_limits: None,
+            checkpoints: Checkpoints::default(),
+            pins: Pins::default(),
+            workspace_snapshot: None,
+            ignored_sampling_warned: HashSet::new(),
+            reported_tool_downgrades: ToolDowngradeReport::default(),
+            last_turn_id: None,
+            partial_message_seq: 0,
+            offline_queue: VecDeque::new(),
+            offline_flush: None,
+            turn_tallies: HashMap::new(),
+            safe_mode: None,
+            safe_mode_exit: None,
+            recorded_segments: HashSet::new(),
+            effort_adapter: EffortAdapter::default(),
         }
     }
 
@@ -40,8 +104,43 @@ impl SessionState {
         self.history.clone()
     }
 
+    /// Replacing the history invalidates every checkpoint since their recorded
+    /// positions no longer refer to the same items. Pins of items that are
+    /// gone are dropped.
     pub(crate) fn replace_history(&mut self, items: Vec<ResponseItem>) {
         self.history.replace(items);
+        self.checkpoints.clear();
+        self.pins.retain_present(self.history.items());
+    }
+
+    /// Swap the user instructions in the history, see
+    /// [`ContextManager::replace_user_instructions`]. When that adds or removes
+    /// an item, checkpoints are dropped as in [`Self::replace_history`].
+    pub(crate) fn replace_user_instructions(&mut self, instructions: Option<ResponseItem>) {
+        let before = self.history.item_count();
+        self.history.replace_user_instructions(instructions);
+        if self.history.item_count() != before {
+            self.checkpoints.clear();
+        }
+        self.pins.retain_present(self.history.items());
+    }
+
+    // Checkpoint helpers
+    pub(crate) fn record_checkpoint(&mut self, id: CheckpointId) {
+        let history_len = self.history.item_count();
+        self.checkpoints.record(id, history_len);
+    }
+
+    /// Truncate the history to the position captured by `id` and return the
+    /// checkpoints invalidated by the rollback, or `None` if `id` is unknown.
+    pub(crate) fn rollback_to_checkpoint(
+        &mut self,
+        id: &CheckpointId,
+    ) -> Option<Vec<CheckpointId>> {
+        let (history_len, invalidated) = self.checkpoints.rollback(id)?;
+        self.history.truncate(history_len);
+        self.pins.retain_present(self.history.items());
+        Some(invalidated)
     }
 
     pub(crate) fn set_token_info(&mut self, info: Option<TokenUsageInfo>) {
@@ -67,7 +67,8 @@ impl ActiveTurn {
 #[derive(Default)]
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
-    pending_input: Vec<ResponseInputItem>,
+    /// Input queued behind the turn, keyed by its pending input id.
+    pending_input: Vec<(String, ResponseInputItem)>,
 }
 
 impl TurnState {
@@ -86,16 +87,21 @@ impl TurnState {
         self.pending_approvals.remove(key)
     }
 
-    pub(crate) fn clear_pending(&mut self) {
+    /// Returns the ids of the discarded pending input.
+    pub(crate) fn clear_pending(&mut self) -> Vec<String> {
         self.pending_approvals.clear();
-        self.pending_input.clear();
+        self.pending_input.drain(..).map(|(id, _)| id).collect()
     }
 
-    pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
-        self.pending_input.push(input);
+    pub(crate) fn push_pending_input(&mut self, id: String, input: ResponseInputItem) {
+        self.pending_input.push((id, input));
     }
 
-    pub(crate) fn take_pending_input(&mut self) -> Vec<ResponseInputItem> {
+    pub(crate) fn has_pending_input(&self) -> bool {
+        !self.pending_input.is_empty()
+    }
+
+    pub(crate) fn take_pending_input(&mut self) -> Vec<(String, ResponseInputItem)> {
         if self.pending_input.is_empty() {
             Vec::with_capacity(0)
         } else {
@@ -107,9 +113,10 @@ impl TurnState {
 }
 
 impl ActiveTurn {
-    /// Clear any pending approvals and input buffered for the current turn.
-    pub(crate) async fn clear_pending(&self) {
+    /// Clear any pending approvals and input buffered for the current turn,
+    /// returning the ids of the discarded input.
+    pub(crate) async fn clear_pending(&self) -> Vec<String> {
         let mut ts = self.turn_state.lock().await;
-        ts.clear_pending();
+        ts.clear_pending()
     }
 }
@@ -9,12 +9,14 @@ use crate::codex::TurnContext;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::function_tool::FunctionCallError;
+use crate::heartbeat;
 use crate::parse_turn_item;
 use crate::tools::parallel::ToolCallRuntime;
 use crate::tools::router::ToolRouter;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::HeartbeatPhase;
 use futures::Future;
 use tracing::debug;
 use tracing::instrument;
@@ -58,11 +60,18 @@ pub(crate) async fn handle_output_item_done(
                 .await;
 
             let cancellation_token = ctx.cancellation_token.child_token();
-            let tool_future: InFlightFuture<'static> = Box::pin(
-                ctx.tool_runtime
-                    .clone()
-                    .handle_tool_call(call, cancellation_token),
-            );
+            let sess = Arc::clone(&ctx.sess);
+            let turn_context = Arc::clone(&ctx.turn_context);
+            let phase = HeartbeatPhase::ExecutingTool {
+                call_id: call.call_id.clone(),
+            };
+            let tool_call = ctx
+                .tool_runtime
+                .clone()
+                .handle_tool_call(call, cancellation_token);
+            let tool_future: InFlightFuture<'static> = Box::pin(async move {
+                heartbeat::while_pending(&sess, &turn_context, phase, tool_call).await
+            });
 
             output.needs_follow_up = true;
             output.tool_future = Some(tool_future);
@@ -0,0 +1,244 @@
+//! Validation of final answers against the turn's `final_output_json_schema`.
+//!
+//! Providers with native structured outputs receive the schema with the
+//! request; for the others it is described in the instructions. Either way
+//! the final assistant message is checked against the schema when the model
+//! stops, and a mismatch is sent back to the model with the validation errors
+//! for up to `structured_output_repair_attempts` more tries.
+//!
+//! Validation covers the JSON Schema keywords structured outputs accept:
+//! `type`, `properties`, `required`, `additionalProperties`, `items`,
+//! `enum`, `const` and `anyOf`. Other keywords are ignored.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde_json::Value;
+
+pub(crate) const STRUCTURED_OUTPUT_REPAIR_OPEN_TAG: &str = "<structured_output_repair>";
+const STRUCTURED_OUTPUT_REPAIR_CLOSE_TAG: &str = "</structured_output_repair>";
+
+/// Parse `text` as JSON and validate it against `schema`, returning the
+/// parsed value or every validation error found.
+pub(crate) fn parse_and_validate(schema: &Value, text: &str) -> Result<Value, Vec<String>> {
+    let value: Value = serde_json::from_str(text.trim())
+        .map_err(|err| vec![format!("the final answer is not valid JSON: {err}")])?;
+    let mut errors = Vec::new();
+    validate_at(schema, &value, "", &mut errors);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // `true` and `{}` accept everything; `false` accepts nothing.
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{}: no value is allowed here", display_path(path)));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type")
+        && !type_matches(expected, value)
+    {
+        errors.push(format!(
+            "{}: expected type {expected}, got {}",
+            display_path(path),
+            type_name(value)
+        ));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        errors.push(format!(
+            "{}: {value} is not one of {}",
+            display_path(path),
+            Value::Array(allowed.clone())
+        ));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!(
+            "{}: expected {expected}, got {value}",
+            display_path(path)
+        ));
+    }
+
+    if let Some(variants) = schema.get("anyOf").and_then(Value::as_array) {
+        let matches_any = variants.iter().any(|variant| {
+            let mut variant_errors = Vec::new();
+            validate_at(variant, value, path, &mut variant_errors);
+            variant_errors.is_empty()
+        });
+        if !matches_any {
+            errors.push(format!(
+                "{}: does not match any of the allowed schemas",
+                display_path(path)
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(format!(
+                            "{}: missing required property \"{name}\"",
+                            display_path(path)
+                        ));
+                    }
+                }
+            }
+            for (name, property) in object {
+                let property_path = format!("{path}/{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property_schema) => {
+                        validate_at(property_schema, property, &property_path, errors);
+                    }
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => errors.push(format!(
+                            "{}: unexpected property \"{name}\"",
+                            display_path(path)
+                        )),
+                        Some(additional @ Value::Object(_)) => {
+                            validate_at(additional, property, &property_path, errors);
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+    }
+}
+
+fn type_matches(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(name) => type_name_matches(name, value),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| type_name_matches(name, value)),
+        _ => true,
+    }
+}
+
+fn type_name_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+/// Instructions for providers without native structured outputs.
+pub(crate) fn schema_instructions(schema: &Value) -> String {
+    format!(
+        "Your final answer must be a single JSON value, with no surrounding prose or code fences, that matches this JSON Schema:\n{schema}"
+    )
+}
+
+/// Transcript item asking the model to fix a final answer that failed
+/// validation. It is recorded with the user role, like the environment
+/// context, but is not a user message.
+pub(crate) fn repair_request(schema: &Value, errors: &[String]) -> ResponseItem {
+    let mut lines = vec![
+        STRUCTURED_OUTPUT_REPAIR_OPEN_TAG.to_string(),
+        "Your final answer does not match the required JSON Schema:".to_string(),
+    ];
+    lines.extend(errors.iter().map(|error| format!("- {error}")));
+    lines.push(schema_instructions(schema));
+    lines.push(STRUCTURED_OUTPUT_REPAIR_CLOSE_TAG.to_string());
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: lines.join("\n"),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "answer": { "type": "string" },
+                "confidence": { "type": "number" },
+                "tags": { "type": "array", "items": { "enum": ["a", "b"] } },
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+            },
+            "required": ["answer", "confidence"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_matching_output() {
+        let text = r#"{"answer": "42", "confidence": 1, "tags": ["a"], "note": null}"#;
+        assert_eq!(
+            parse_and_validate(&schema(), text),
+            Ok(json!({"answer": "42", "confidence": 1, "tags": ["a"], "note": null}))
+        );
+    }
+
+    #[test]
+    fn reports_every_mismatch() {
+        let text = r#"{"answer": 42, "tags": ["c"], "note": 1, "extra": true}"#;
+        // Property order depends on serde_json's `preserve_order` feature.
+        let mut errors = parse_and_validate(&schema(), text).unwrap_err();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "/: missing required property \"confidence\"".to_string(),
+                "/: unexpected property \"extra\"".to_string(),
+                "/answer: expected type \"string\", got number".to_string(),
+                "/note: does not match any of the allowed schemas".to_string(),
+                "/tags/0: \"c\" is not one of [\"a\",\"b\"]".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_prose() {
+        let errors = parse_and_validate(&schema(), "Sure! Here it is.").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("the final answer is not valid JSON"));
+    }
+}
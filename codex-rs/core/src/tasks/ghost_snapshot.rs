@@ -1,5 +1,6 @@
 use crate::codex::TurnContext;
 use crate::protocol::EventMsg;
+use crate::protocol::WarningCode;
 use crate::protocol::WarningEvent;
 use crate::state::TaskKind;
 use crate::tasks::SessionTask;
@@ -58,9 +59,10 @@ impl SessionTask for GhostSnapshotTask {
                             session_for_warning.session
                                 .send_event(
                                     &ctx_for_warning,
-                                    EventMsg::Warning(WarningEvent {
-                                        message: "Repository snapshot is taking longer than expected. Large untracked or ignored files can slow snapshots; consider adding large files or directories to .gitignore or disabling `undo` in your config.".to_string()
-                                    }),
+                                    EventMsg::Warning(WarningEvent::new(
+                                        WarningCode::SnapshotSlow,
+                                        "Repository snapshot is taking longer than expected. Large untracked or ignored files can slow snapshots; consider adding large files or directories to .gitignore or disabling `undo` in your config.",
+                                    )),
                                 )
                                 .await;
                         }
@@ -99,7 +101,10 @@ impl SessionTask for GhostSnapshotTask {
                                         .session
                                         .send_event(
                                             &ctx_for_task,
-                                            EventMsg::Warning(WarningEvent { message }),
+                                            EventMsg::Warning(WarningEvent::new(
+                                                WarningCode::SnapshotFilesSkipped,
+                                                message,
+                                            )),
                                         )
                                         .await;
                                 }
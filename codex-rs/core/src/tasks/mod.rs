@@ -21,12 +21,15 @@ use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::models_manager::manager::ModelsManager;
 use crate::protocol::EventMsg;
+use crate::protocol::StructuredOutputEvent;
 use crate::protocol::TaskCompleteEvent;
 use crate::protocol::TurnAbortReason;
 use crate::protocol::TurnAbortedEvent;
 use crate::state::ActiveTurn;
 use crate::state::RunningTask;
 use crate::state::TaskKind;
+use crate::structured_output;
+use crate::turn_tally::TurnTally;
 use codex_protocol::user_input::UserInput;
 
 pub(crate) use compact::CompactTask;
@@ -110,9 +113,11 @@ impl Session {
         task: T,
     ) {
         self.abort_all_tasks(TurnAbortReason::Replaced).await;
-
+        self.mark_turn_started().await;
+        self.services.turn_results.start(&turn_context.sub_id);
         let task: Arc<dyn SessionTask> = Arc::new(task);
         let task_kind = task.kind();
+        self.start_turn_tally(&turn_context.sub_id, task_kind).await;
 
         let cancellation_token = CancellationToken::new();
         let done = Arc::new(Notify::new());
@@ -156,10 +161,19 @@ impl Session {
     }
 
     pub async fn abort_all_tasks(self: &Arc<Self>, reason: TurnAbortReason) {
-        for task in self.take_all_running_tasks().await {
+        let (tasks, discarded_input) = self.take_all_running_tasks().await;
+        self.mark_pending_inputs_consumed(discarded_input).await;
+        let mut turn_contexts = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            turn_contexts.push(Arc::clone(&task.turn_context));
             self.handle_task_abort(task, reason.clone()).await;
         }
         self.close_unified_exec_sessions().await;
+        // Only once nothing the turns started is still running.
+        for turn_context in turn_contexts {
+            turn_context.turn_tmpdir.cleanup().await;
+        }
+        self.finish_pending_pause().await;
     }
 
     pub async fn on_task_finished(
@@ -168,20 +182,74 @@ impl Session {
         last_agent_message: Option<String>,
     ) {
         let mut active = self.active_turn.lock().await;
+        // This runs on the finished task itself: dropping the last handle to
+        // it would abort the teardown below at its next await.
+        let _finishing = active
+            .as_ref()
+            .and_then(|at| at.tasks.get(&turn_context.sub_id))
+            .map(|task| Arc::clone(&task.handle));
+        let mut discarded_input = Vec::new();
         let should_close_sessions = if let Some(at) = active.as_mut()
             && at.remove_task(&turn_context.sub_id)
         {
+            discarded_input = at.clear_pending().await;
             *active = None;
+            self.services.load.set_turn_in_flight(false);
             true
         } else {
             false
         };
         drop(active);
+        self.mark_pending_inputs_consumed(discarded_input).await;
         if should_close_sessions {
             self.close_unified_exec_sessions().await;
+            self.mark_turn_finished();
+        }
+        turn_context.turn_tmpdir.cleanup().await;
+        self.refresh_workspace_snapshot(turn_context.as_ref()).await;
+        self.update_context_usage(turn_context.as_ref()).await;
+        // `run_task` already asked for repairs; only a matching answer is
+        // reported as structured output.
+        let structured_output = turn_context
+            .final_output_json_schema
+            .as_ref()
+            .zip(last_agent_message.as_deref())
+            .and_then(|(schema, message)| {
+                structured_output::parse_and_validate(schema, message).ok()
+            });
+        if let Some(output) = &structured_output {
+            let event = EventMsg::StructuredOutput(StructuredOutputEvent {
+                output: output.clone(),
+            });
+            self.send_event(turn_context.as_ref(), event).await;
         }
-        let event = EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message });
+        let tally = self.take_turn_tally(&turn_context.sub_id).await;
+        self.report_skill_usage(turn_context.as_ref(), &tally).await;
+        self.adapt_reasoning_effort(turn_context.as_ref(), &tally)
+            .await;
+        let event = EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message,
+            structured_output,
+            final_message: tally.final_message(),
+            message_count: tally.message_count,
+            tool_call_count: tally.tool_call_count,
+            invoked_skills: tally.invoked_skills(),
+            error: tally.error,
+        });
         self.send_event(turn_context.as_ref(), event).await;
+        self.start_next_queued_turn().await;
+        self.finish_pending_pause().await;
+    }
+
+    /// Send and record the skill usage of a user turn, and count it in the
+    /// skills manager's usage stats.
+    async fn report_skill_usage(&self, turn_context: &TurnContext, tally: &TurnTally) {
+        let Some(usage) = &tally.skill_usage else {
+            return;
+        };
+        self.services.skills_manager.record_usage(usage).await;
+        self.send_event(turn_context, EventMsg::SkillUsage(usage.clone()))
+            .await;
     }
 
     async fn register_new_active_task(&self, task: RunningTask) {
@@ -189,17 +257,21 @@ impl Session {
         let mut turn = ActiveTurn::default();
         turn.add_task(task);
         *active = Some(turn);
+        self.services.load.set_turn_in_flight(true);
     }
 
-    async fn take_all_running_tasks(&self) -> Vec<RunningTask> {
+    /// Returns the running tasks and the ids of the pending input discarded
+    /// with them.
+    async fn take_all_running_tasks(&self) -> (Vec<RunningTask>, Vec<String>) {
         let mut active = self.active_turn.lock().await;
         match active.take() {
             Some(mut at) => {
-                at.clear_pending().await;
+                let discarded_input = at.clear_pending().await;
+                self.services.load.set_turn_in_flight(false);
 
-                at.drain_tasks()
+                (at.drain_tasks(), discarded_input)
             }
-            None => Vec::new(),
+            None => (Vec::new(), Vec::new()),
         }
     }
 
@@ -235,8 +307,35 @@ impl Session {
             .abort(session_ctx, Arc::clone(&task.turn_context))
             .await;
 
-        let event = EventMsg::TurnAborted(TurnAbortedEvent { reason });
+        self.refresh_workspace_snapshot(task.turn_context.as_ref())
+            .await;
+        let tally = self.take_turn_tally(&sub_id).await;
+        self.report_skill_usage(task.turn_context.as_ref(), &tally)
+            .await;
+        let event = EventMsg::TurnAborted(TurnAbortedEvent {
+            reason,
+            message_count: tally.message_count,
+            tool_call_count: tally.tool_call_count,
+        });
         self.send_event(task.turn_context.as_ref(), event).await;
+        self.flush_rollout().await;
+        self.mark_turn_finished();
+    }
+
+    /// Record where the rollout stood before this turn so concurrent forks can
+    /// exclude the turn's partial items.
+    async fn mark_turn_started(&self) {
+        if let Some(live_rollout) = &self.services.live_rollout
+            && let Err(e) = live_rollout.turn_started().await
+        {
+            warn!("failed to record rollout turn boundary: {e}");
+        }
+    }
+
+    fn mark_turn_finished(&self) {
+        if let Some(live_rollout) = &self.services.live_rollout {
+            live_rollout.turn_finished();
+        }
     }
 }
 
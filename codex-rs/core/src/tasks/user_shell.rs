@@ -7,7 +7,6 @@ use codex_async_utils::OrCancelExt;
 use codex_protocol::user_input::UserInput;
 use tokio_util::sync::CancellationToken;
 use tracing::error;
-use uuid::Uuid;
 
 use crate::codex::TurnContext;
 use crate::exec::ExecToolCallOutput;
@@ -15,7 +14,6 @@ use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
 use crate::exec::execute_exec_env;
-use crate::exec_env::create_env;
 use crate::parse_command::parse_command;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandBeginEvent;
@@ -60,6 +58,7 @@ impl SessionTask for UserShellCommandTask {
     ) -> Option<String> {
         let event = EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: turn_context.client.get_model_context_window(),
+            turn_id: None,
         });
         let session = session.clone_session();
         session.send_event(turn_context.as_ref(), event).await;
@@ -72,7 +71,7 @@ impl SessionTask for UserShellCommandTask {
             .user_shell()
             .derive_exec_args(&self.command, use_login_shell);
 
-        let call_id = Uuid::new_v4().to_string();
+        let call_id = session.services.runtime_env.next_id();
         let raw_command = self.command.clone();
         let cwd = turn_context.cwd.clone();
 
@@ -96,7 +95,7 @@ impl SessionTask for UserShellCommandTask {
         let exec_env = ExecEnv {
             command: command.clone(),
             cwd: cwd.clone(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: turn_context.tool_env(),
             // TODO(zhao-oai): Now that we have ExecExpiration::Cancellation, we
             // should use that instead of an "arbitrarily large" timeout here.
             expiration: USER_SHELL_TIMEOUT_MS.into(),
@@ -110,6 +109,7 @@ impl SessionTask for UserShellCommandTask {
             sub_id: turn_context.sub_id.clone(),
             call_id: call_id.clone(),
             tx_event: session.get_tx_event(),
+            correlation_id: session.correlation_id(&turn_context.sub_id),
         });
 
         let sandbox_policy = SandboxPolicy::DangerFullAccess;
@@ -154,6 +154,7 @@ impl SessionTask for UserShellCommandTask {
                             exit_code: -1,
                             duration: Duration::ZERO,
                             formatted_output: aborted_message,
+                            output_blob: None,
                         }),
                     )
                     .await;
@@ -180,6 +181,7 @@ impl SessionTask for UserShellCommandTask {
                                 &output,
                                 turn_context.truncation_policy,
                             ),
+                            output_blob: None,
                         }),
                     )
                     .await;
@@ -225,6 +227,7 @@ impl SessionTask for UserShellCommandTask {
                                 &exec_output,
                                 turn_context.truncation_policy,
                             ),
+                            output_blob: None,
                         }),
                     )
                     .await;
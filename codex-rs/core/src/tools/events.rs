@@ -14,8 +14,10 @@ use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::TurnDiffEvent;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::output_cap::cap_exec_output;
 use crate::tools::sandboxing::ToolError;
 use codex_protocol::parse_command::ParsedCommand;
+use codex_protocol::protocol::BlobRef;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
@@ -148,6 +150,17 @@ impl ToolEmitter {
     }
 
     pub async fn emit(&self, ctx: ToolEventCtx<'_>, stage: ToolEventStage) {
+        self.emit_with_blob(ctx, stage, None).await;
+    }
+
+    /// [`Self::emit`], with the blob holding the full output of a cut exec
+    /// output, see [`super::output_cap`].
+    async fn emit_with_blob(
+        &self,
+        ctx: ToolEventCtx<'_>,
+        stage: ToolEventStage,
+        output_blob: Option<BlobRef>,
+    ) {
         match (self, stage) {
             (
                 Self::Shell {
@@ -159,10 +172,12 @@ impl ToolEmitter {
                 },
                 stage,
             ) => {
+                track_command_stage(ctx, cwd, &stage).await;
                 emit_exec_stage(
                     ctx,
                     ExecCommandInput::new(command, cwd.as_path(), parsed_cmd, *source, None, None),
                     stage,
+                    output_blob,
                 )
                 .await;
             }
@@ -237,6 +252,7 @@ impl ToolEmitter {
                 },
                 stage,
             ) => {
+                track_command_stage(ctx, cwd, &stage).await;
                 emit_exec_stage(
                     ctx,
                     ExecCommandInput::new(
@@ -248,6 +264,7 @@ impl ToolEmitter {
                         process_id.as_deref(),
                     ),
                     stage,
+                    output_blob,
                 )
                 .await;
             }
@@ -276,8 +293,12 @@ impl ToolEmitter {
         ctx: ToolEventCtx<'_>,
         out: Result<ExecToolCallOutput, ToolError>,
     ) -> Result<String, FunctionCallError> {
+        let cap = ctx.turn.tool_output_cap;
+        let mut output_blob = None;
         let (event, result) = match out {
             Ok(output) => {
+                let (output, blob) = cap_exec_output(ctx.session, cap, output).await;
+                output_blob = blob;
                 let content = self.format_exec_output_for_model(&output, ctx);
                 let exit_code = output.exit_code;
                 let event = ToolEventStage::Success(output);
@@ -290,8 +311,10 @@ impl ToolEmitter {
             }
             Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
             | Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
+                let (output, blob) = cap_exec_output(ctx.session, cap, *output).await;
+                output_blob = blob;
                 let response = self.format_exec_output_for_model(&output, ctx);
-                let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
+                let event = ToolEventStage::Failure(ToolEventFailure::Output(output));
                 let result = Err(FunctionCallError::RespondToModel(response));
                 (event, result)
             }
@@ -314,7 +337,7 @@ impl ToolEmitter {
                 (event, result)
             }
         };
-        self.emit(ctx, event).await;
+        self.emit_with_blob(ctx, event, output_blob).await;
         result
     }
 }
@@ -355,12 +378,27 @@ struct ExecCommandResult {
     exit_code: i32,
     duration: Duration,
     formatted_output: String,
+    output_blob: Option<BlobRef>,
+}
+
+/// Snapshot the workspace before a command starts and track the files it
+/// changed once it is done.
+async fn track_command_stage(ctx: ToolEventCtx<'_>, cwd: &Path, stage: &ToolEventStage) {
+    let Some(tracker) = ctx.turn_diff_tracker else {
+        return;
+    };
+    let mut guard = tracker.lock().await;
+    match stage {
+        ToolEventStage::Begin => guard.on_command_begin(cwd),
+        ToolEventStage::Success(_) | ToolEventStage::Failure(_) => guard.on_command_end(),
+    }
 }
 
 async fn emit_exec_stage(
     ctx: ToolEventCtx<'_>,
     exec_input: ExecCommandInput<'_>,
     stage: ToolEventStage,
+    output_blob: Option<BlobRef>,
 ) {
     match stage {
         ToolEventStage::Begin => {
@@ -384,6 +422,7 @@ async fn emit_exec_stage(
                 exit_code: output.exit_code,
                 duration: output.duration,
                 formatted_output: format_exec_output_str(&output, ctx.turn.truncation_policy),
+                output_blob,
             };
             emit_exec_end(ctx, exec_input, exec_result).await;
         }
@@ -396,6 +435,7 @@ async fn emit_exec_stage(
                 exit_code: -1,
                 duration: Duration::ZERO,
                 formatted_output: text,
+                output_blob: None,
             };
             emit_exec_end(ctx, exec_input, exec_result).await;
         }
@@ -425,6 +465,7 @@ async fn emit_exec_end(
                 exit_code: exec_result.exit_code,
                 duration: exec_result.duration,
                 formatted_output: exec_result.formatted_output,
+                output_blob: exec_result.output_blob,
             }),
         )
         .await;
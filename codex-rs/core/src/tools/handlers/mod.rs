@@ -4,8 +4,10 @@ mod list_dir;
 mod mcp;
 mod mcp_resource;
 mod plan;
-mod read_file;
+mod project_notes;
+pub(crate) mod read_file;
 mod shell;
+mod skill;
 mod test_sync;
 mod unified_exec;
 mod view_image;
@@ -18,9 +20,11 @@ pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
 pub use mcp_resource::McpResourceHandler;
 pub use plan::PlanHandler;
+pub use project_notes::ProjectNoteHandler;
 pub use read_file::ReadFileHandler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
+pub use skill::SkillToolHandler;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::protocol::AskForApproval;
+use crate::protocol::ReviewDecision;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct ProjectNoteHandler;
+
+#[derive(Deserialize)]
+struct AddProjectNoteArgs {
+    note: String,
+}
+
+#[async_trait]
+impl ToolHandler for ProjectNoteHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    /// Waiting for the user to approve the note is not cut short by the tool
+    /// timeout; saving it is quick.
+    fn enforces_timeout(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            call_id,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "add_project_note handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: AddProjectNoteArgs = serde_json::from_str(&arguments).map_err(|e| {
+            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+        })?;
+        let note = args.note.trim();
+        if note.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "the note is empty".to_string(),
+            ));
+        }
+        if turn.approval_policy == AskForApproval::Never {
+            return Err(FunctionCallError::RespondToModel(
+                "notes are only saved with the user's approval, which this session never asks for"
+                    .to_string(),
+            ));
+        }
+
+        let decision = session
+            .request_note_approval(turn.as_ref(), call_id, note)
+            .await;
+        if matches!(decision, ReviewDecision::Denied | ReviewDecision::Abort) {
+            return Ok(ToolOutput::Function {
+                content: "The user declined to save the note.".to_string(),
+                content_items: None,
+                success: Some(false),
+            });
+        }
+
+        let saved = session
+            .services
+            .project_notes
+            .add(note)
+            .await
+            .map_err(|err| {
+                FunctionCallError::RespondToModel(format!("failed to save the note: {err}"))
+            })?;
+
+        Ok(ToolOutput::Function {
+            content: format!("Saved note {}.", saved.id),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -9,6 +9,7 @@ use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::read_cache;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
@@ -98,7 +99,13 @@ impl ToolHandler for ReadFileHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -109,54 +116,59 @@ impl ToolHandler for ReadFileHandler {
             }
         };
 
-        let args: ReadFileArgs = serde_json::from_str(&arguments).map_err(|err| {
-            FunctionCallError::RespondToModel(format!(
-                "failed to parse function arguments: {err:?}"
-            ))
-        })?;
-
-        let ReadFileArgs {
-            file_path,
-            offset,
-            limit,
-            mode,
-            indentation,
-        } = args;
-
-        if offset == 0 {
-            return Err(FunctionCallError::RespondToModel(
-                "offset must be a 1-indexed line number".to_string(),
-            ));
-        }
-
-        if limit == 0 {
-            return Err(FunctionCallError::RespondToModel(
-                "limit must be greater than zero".to_string(),
-            ));
-        }
-
-        let path = PathBuf::from(&file_path);
-        if !path.is_absolute() {
-            return Err(FunctionCallError::RespondToModel(
-                "file_path must be an absolute path".to_string(),
-            ));
-        }
-
-        let collected = match mode {
-            ReadMode::Slice => slice::read(&path, offset, limit).await?,
-            ReadMode::Indentation => {
-                let indentation = indentation.unwrap_or_default();
-                indentation::read_block(&path, offset, limit, indentation).await?
-            }
-        };
+        let collected = read(&arguments).await?;
+        let content = read_cache::dedupe(&session, &turn, &call_id, collected.join("\n")).await;
         Ok(ToolOutput::Function {
-            content: collected.join("\n"),
+            content,
             content_items: None,
             success: Some(true),
         })
     }
 }
 
+/// The lines a `read_file` call with `arguments` returns, before
+/// [`read_cache::dedupe`] can replace them with a reference.
+pub(crate) async fn read(arguments: &str) -> Result<Vec<String>, FunctionCallError> {
+    let args: ReadFileArgs = serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err:?}"))
+    })?;
+
+    let ReadFileArgs {
+        file_path,
+        offset,
+        limit,
+        mode,
+        indentation,
+    } = args;
+
+    if offset == 0 {
+        return Err(FunctionCallError::RespondToModel(
+            "offset must be a 1-indexed line number".to_string(),
+        ));
+    }
+
+    if limit == 0 {
+        return Err(FunctionCallError::RespondToModel(
+            "limit must be greater than zero".to_string(),
+        ));
+    }
+
+    let path = PathBuf::from(&file_path);
+    if !path.is_absolute() {
+        return Err(FunctionCallError::RespondToModel(
+            "file_path must be an absolute path".to_string(),
+        ));
+    }
+
+    match mode {
+        ReadMode::Slice => slice::read(&path, offset, limit).await,
+        ReadMode::Indentation => {
+            let indentation = indentation.unwrap_or_default();
+            indentation::read_block(&path, offset, limit, indentation).await
+        }
+    }
+}
+
 mod slice {
     use crate::function_tool::FunctionCallError;
     use crate::tools::handlers::read_file::format_line;
@@ -2,10 +2,13 @@ use async_trait::async_trait;
 use codex_protocol::models::ShellCommandToolCallParams;
 use codex_protocol::models::ShellToolCallParams;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
 use crate::exec::ExecParams;
-use crate::exec_env::create_env;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::ExecCommandSource;
@@ -22,18 +25,24 @@ use crate::tools::registry::ToolKind;
 use crate::tools::runtimes::shell::ShellRequest;
 use crate::tools::runtimes::shell::ShellRuntime;
 use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+use crate::tools::timeout;
 
 pub struct ShellHandler;
 
 pub struct ShellCommandHandler;
 
 impl ShellHandler {
-    fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> ExecParams {
+    fn to_exec_params(
+        params: ShellToolCallParams,
+        turn_context: &TurnContext,
+        call_id: &str,
+    ) -> ExecParams {
         ExecParams {
             command: params.command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
             expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: turn_context.call_tool_env(call_id),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -51,6 +60,7 @@ impl ShellCommandHandler {
         params: ShellCommandToolCallParams,
         session: &crate::codex::Session,
         turn_context: &TurnContext,
+        call_id: &str,
     ) -> ExecParams {
         let shell = session.user_shell();
         let command = Self::base_command(shell.as_ref(), &params.command, params.login);
@@ -59,7 +69,7 @@ impl ShellCommandHandler {
             command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
             expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: turn_context.call_tool_env(call_id),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -92,6 +102,10 @@ impl ToolHandler for ShellHandler {
         }
     }
 
+    fn enforces_timeout(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -110,7 +124,7 @@ impl ToolHandler for ShellHandler {
                             "failed to parse function arguments: {e:?}"
                         ))
                     })?;
-                let exec_params = Self::to_exec_params(params, turn.as_ref());
+                let exec_params = Self::to_exec_params(params, turn.as_ref(), &call_id);
                 Self::run_exec_like(
                     tool_name.as_str(),
                     exec_params,
@@ -123,7 +137,7 @@ impl ToolHandler for ShellHandler {
                 .await
             }
             ToolPayload::LocalShell { params } => {
-                let exec_params = Self::to_exec_params(params, turn.as_ref());
+                let exec_params = Self::to_exec_params(params, turn.as_ref(), &call_id);
                 Self::run_exec_like(
                     tool_name.as_str(),
                     exec_params,
@@ -166,6 +180,10 @@ impl ToolHandler for ShellCommandHandler {
             .unwrap_or(true)
     }
 
+    fn enforces_timeout(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -185,7 +203,7 @@ impl ToolHandler for ShellCommandHandler {
         let params: ShellCommandToolCallParams = serde_json::from_str(&arguments).map_err(|e| {
             FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
         })?;
-        let exec_params = Self::to_exec_params(params, session.as_ref(), turn.as_ref());
+        let exec_params = Self::to_exec_params(params, session.as_ref(), turn.as_ref(), &call_id);
         ShellHandler::run_exec_like(
             tool_name.as_str(),
             exec_params,
@@ -202,7 +220,7 @@ impl ToolHandler for ShellCommandHandler {
 impl ShellHandler {
     async fn run_exec_like(
         tool_name: &str,
-        exec_params: ExecParams,
+        mut exec_params: ExecParams,
         session: Arc<crate::codex::Session>,
         turn: Arc<TurnContext>,
         tracker: crate::tools::context::SharedTurnDiffTracker,
@@ -224,6 +242,16 @@ impl ShellHandler {
             )));
         }
 
+        // The tool timeout replaces the requested one when it is shorter.
+        let tool_timeout = turn.tool_timeouts.for_tool(tool_name);
+        let enforces_tool_timeout = exec_params
+            .expiration
+            .timeout_ms()
+            .is_some_and(|timeout_ms| Duration::from_millis(timeout_ms) > tool_timeout);
+        if enforces_tool_timeout {
+            exec_params.expiration = ExecExpiration::Timeout(tool_timeout);
+        }
+
         // Intercept apply_patch if present.
         if let Some(output) = intercept_apply_patch(
             &exec_params.command,
@@ -247,7 +275,8 @@ impl ShellHandler {
             source,
             freeform,
         );
-        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        let event_ctx =
+            ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, Some(&tracker));
         emitter.begin(event_ctx).await;
 
         let features = session.features();
@@ -283,8 +312,27 @@ impl ShellHandler {
         let out = orchestrator
             .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
             .await;
-        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
-        let content = emitter.finish(event_ctx, out).await?;
+        let captured_before_timeout = match &out {
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
+                if enforces_tool_timeout =>
+            {
+                Some(output.aggregated_output.text.clone())
+            }
+            _ => None,
+        };
+        let event_ctx =
+            ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, Some(&tracker));
+        let finished = emitter.finish(event_ctx, out).await;
+        if let Some(captured) = captured_before_timeout {
+            timeout::notify_timed_out(session.as_ref(), turn.as_ref(), &call_id, tool_timeout)
+                .await;
+            return Err(FunctionCallError::RespondToModel(timeout::timeout_output(
+                turn.as_ref(),
+                tool_timeout,
+                &captured,
+            )));
+        }
+        let content = finished?;
         Ok(ToolOutput::Function {
             content,
             content_items: None,
@@ -302,7 +350,6 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use crate::codex::make_session_and_context;
-    use crate::exec_env::create_env;
     use crate::is_safe_command::is_known_safe_command;
     use crate::powershell::try_find_powershell_executable_blocking;
     use crate::powershell::try_find_pwsh_executable_blocking;
@@ -372,7 +419,7 @@ mod tests {
 
         let expected_command = session.user_shell().derive_exec_args(&command, true);
         let expected_cwd = turn_context.resolve_path(workdir.clone());
-        let expected_env = create_env(&turn_context.shell_environment_policy);
+        let expected_env = turn_context.call_tool_env("call-1");
 
         let params = ShellCommandToolCallParams {
             command,
@@ -383,7 +430,8 @@ mod tests {
             justification: justification.clone(),
         };
 
-        let exec_params = ShellCommandHandler::to_exec_params(params, &session, &turn_context);
+        let exec_params =
+            ShellCommandHandler::to_exec_params(params, &session, &turn_context, "call-1");
 
         // ExecParams cannot derive Eq due to the CancellationToken field, so we manually compare the fields.
         assert_eq!(exec_params.command, expected_command);
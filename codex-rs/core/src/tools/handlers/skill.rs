@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use tokio::fs;
+
+use crate::function_tool::FunctionCallError;
+use crate::protocol::EventMsg;
+use crate::protocol::SkillToolCallEvent;
+use crate::skills::SkillMetadata;
+use crate::skills::tools::render_skill;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Runs one skill offered as a tool, see [`crate::skills::tools`].
+pub struct SkillToolHandler {
+    skill: SkillMetadata,
+}
+
+impl SkillToolHandler {
+    pub(crate) fn new(skill: SkillMetadata) -> Self {
+        Self { skill }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for SkillToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            call_id,
+            tool_name,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let arguments: BTreeMap<String, String> = if arguments.trim().is_empty() {
+            BTreeMap::new()
+        } else {
+            serde_json::from_str(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?
+        };
+
+        session
+            .record_skill_invoked(turn.as_ref(), &self.skill.name)
+            .await;
+        session
+            .send_event(
+                turn.as_ref(),
+                EventMsg::SkillToolCall(SkillToolCallEvent {
+                    call_id,
+                    name: self.skill.name.clone(),
+                    arguments: arguments.clone(),
+                }),
+            )
+            .await;
+
+        let contents = fs::read_to_string(&self.skill.path).await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to read skill {} at {}: {err}",
+                self.skill.name,
+                self.skill.path.display()
+            ))
+        })?;
+        let body = render_skill(&self.skill, &contents, &arguments)
+            .map_err(FunctionCallError::RespondToModel)?;
+
+        Ok(ToolOutput::Function {
+            content: body,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -176,7 +176,7 @@ impl ToolHandler for UnifiedExecHandler {
                     context.session.as_ref(),
                     context.turn.as_ref(),
                     &context.call_id,
-                    None,
+                    Some(&tracker),
                 );
                 let emitter = ToolEmitter::unified_exec(
                     &command,
@@ -186,7 +186,7 @@ impl ToolHandler for UnifiedExecHandler {
                 );
                 emitter.emit(event_ctx, ToolEventStage::Begin).await;
 
-                manager
+                let response = manager
                     .exec_command(
                         ExecCommandRequest {
                             command,
@@ -202,7 +202,11 @@ impl ToolHandler for UnifiedExecHandler {
                     .await
                     .map_err(|err| {
                         FunctionCallError::RespondToModel(format!("exec_command failed: {err:?}"))
-                    })?
+                    })?;
+                // The command may still be running; whatever it changes later is
+                // picked up after the next command.
+                tracker.lock().await.on_command_end();
+                response
             }
             "write_stdin" => {
                 let args: WriteStdinArgs = serde_json::from_str(&arguments).map_err(|err| {
@@ -1,13 +1,17 @@
 pub mod context;
 pub mod events;
 pub(crate) mod handlers;
+pub(crate) mod negotiation;
 pub mod orchestrator;
+pub(crate) mod output_cap;
 pub mod parallel;
+pub(crate) mod read_cache;
 pub mod registry;
 pub mod router;
 pub mod runtimes;
 pub mod sandboxing;
 pub mod spec;
+pub(crate) mod timeout;
 
 use crate::exec::ExecToolCallOutput;
 use crate::truncate::TruncationPolicy;
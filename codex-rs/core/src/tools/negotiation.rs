@@ -0,0 +1,269 @@
+//! Fitting the tools of a turn to what the provider accepts, as described by
+//! its [`ToolCapabilities`], instead of letting the request fail.
+//!
+//! Tools past `max_tools` are left out first, last in the roster first: the
+//! built-in tools come before MCP tools, so those are the ones pruned. The
+//! remaining parameter schemas lose the keywords the provider does not list,
+//! and tools lose their parallel support when the provider has none. What was
+//! changed is returned as a [`ToolDowngradeReport`].
+//!
+//! Keywords outside the subset [`JsonSchema`] models, such as `enum` or
+//! `format`, are already dropped when MCP schemas are converted.
+
+use std::collections::BTreeSet;
+
+use codex_protocol::config_types::SchemaKeyword;
+use codex_protocol::config_types::ToolCapabilities;
+use codex_protocol::protocol::ToolDowngradeReport;
+use codex_protocol::protocol::ToolSchemaDowngrade;
+
+use crate::client_common::tools::ToolSpec;
+use crate::tools::registry::ConfiguredToolSpec;
+use crate::tools::spec::AdditionalProperties;
+use crate::tools::spec::JsonSchema;
+
+/// Adjust `specs` to `capabilities` in place.
+pub(crate) fn negotiate(
+    capabilities: &ToolCapabilities,
+    specs: &mut Vec<ConfiguredToolSpec>,
+) -> ToolDowngradeReport {
+    let mut report = ToolDowngradeReport::default();
+
+    if let Some(max_tools) = capabilities.max_tools
+        && specs.len() > max_tools
+    {
+        report.pruned = specs
+            .drain(max_tools..)
+            .map(|config| config.spec.name().to_string())
+            .collect();
+    }
+
+    if let Some(keywords) = &capabilities.schema_keywords {
+        for config in specs.iter_mut() {
+            let ToolSpec::Function(tool) = &mut config.spec else {
+                continue;
+            };
+            let mut removed = BTreeSet::new();
+            simplify(&mut tool.parameters, keywords, &mut removed);
+            if removed.is_empty() {
+                continue;
+            }
+            // Strict schemas must list every property as required.
+            if removed.contains(&SchemaKeyword::Required) {
+                tool.strict = false;
+            }
+            report.simplified.push(ToolSchemaDowngrade {
+                tool: tool.name.clone(),
+                removed: removed.into_iter().collect(),
+            });
+        }
+    }
+
+    if !capabilities.supports_parallel_tool_calls {
+        for config in specs.iter_mut() {
+            if config.supports_parallel_tool_calls {
+                config.supports_parallel_tool_calls = false;
+                report.parallel_calls_serialized = true;
+            }
+        }
+    }
+
+    report
+}
+
+/// Remove the keywords not in `allowed` from `schema` and its subschemas,
+/// adding the removed ones to `removed`.
+fn simplify(
+    schema: &mut JsonSchema,
+    allowed: &[SchemaKeyword],
+    removed: &mut BTreeSet<SchemaKeyword>,
+) {
+    let mut strip = |keyword: SchemaKeyword, present: bool| {
+        let strip = present && !allowed.contains(&keyword);
+        if strip {
+            removed.insert(keyword);
+        }
+        strip
+    };
+    match schema {
+        JsonSchema::Boolean { description }
+        | JsonSchema::String { description }
+        | JsonSchema::Number { description } => {
+            if strip(SchemaKeyword::Description, description.is_some()) {
+                *description = None;
+            }
+        }
+        JsonSchema::Array { items, description } => {
+            if strip(SchemaKeyword::Description, description.is_some()) {
+                *description = None;
+            }
+            simplify(items, allowed, removed);
+        }
+        JsonSchema::Object {
+            properties,
+            required,
+            additional_properties,
+        } => {
+            if strip(SchemaKeyword::Required, required.is_some()) {
+                *required = None;
+            }
+            if strip(
+                SchemaKeyword::AdditionalProperties,
+                additional_properties.is_some(),
+            ) {
+                *additional_properties = None;
+            }
+            if let Some(AdditionalProperties::Schema(schema)) = additional_properties {
+                simplify(schema, allowed, removed);
+            }
+            for property in properties.values_mut() {
+                simplify(property, allowed, removed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::features::Features;
+    use crate::models_manager::manager::ModelsManager;
+    use crate::tools::ToolRouter;
+    use crate::tools::spec::ToolsConfig;
+    use crate::tools::spec::ToolsConfigParams;
+    use codex_protocol::config_types::ToolPolicy;
+    use mcp_types::ToolInputSchema;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+
+    fn mcp_tool(name: &str) -> mcp_types::Tool {
+        mcp_types::Tool {
+            name: name.to_string(),
+            input_schema: ToolInputSchema {
+                properties: Some(serde_json::json!({
+                    "query": {
+                        "type": "string",
+                        "description": "What to look for.",
+                    },
+                    "filters": {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "type": "string", "enum": ["file", "dir"] },
+                        },
+                        "additionalProperties": false,
+                    },
+                })),
+                required: Some(vec!["query".to_string()]),
+                r#type: "object".to_string(),
+            },
+            output_schema: None,
+            title: None,
+            annotations: None,
+            description: Some(format!("The {name} tool.")),
+        }
+    }
+
+    fn router(capabilities: Option<ToolCapabilities>) -> ToolRouter {
+        let config = test_config();
+        let model_family = ModelsManager::construct_model_family_offline("gpt-5-codex", &config);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &Features::with_defaults(),
+        })
+        .with_tool_capabilities(capabilities);
+        ToolRouter::from_config(
+            &tools_config,
+            &ToolPolicy::default(),
+            Some(HashMap::from([
+                ("docs__search".to_string(), mcp_tool("search")),
+                ("web__fetch".to_string(), mcp_tool("fetch")),
+            ])),
+        )
+    }
+
+    fn find_function<'a>(specs: &'a [ToolSpec], name: &str) -> &'a JsonSchema {
+        specs
+            .iter()
+            .find_map(|spec| match spec {
+                ToolSpec::Function(tool) if tool.name == name => Some(&tool.parameters),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("{name} is not in the roster"))
+    }
+
+    #[test]
+    fn unrestricted_capabilities_leave_the_roster_alone() {
+        let router = router(None);
+
+        assert!(router.downgrades().is_empty());
+        assert!(router.tool_supports_parallel("view_image"));
+        assert!(
+            router
+                .specs()
+                .iter()
+                .any(|spec| spec.name() == "web__fetch")
+        );
+    }
+
+    #[test]
+    fn restricted_capabilities_downgrade_the_roster() {
+        let builtin_tools = router(None).specs().len() - 2;
+        let router = router(Some(ToolCapabilities {
+            supports_parallel_tool_calls: false,
+            schema_keywords: Some(vec![SchemaKeyword::Required]),
+            max_tools: Some(builtin_tools + 1),
+        }));
+
+        let specs = router.specs();
+        assert_eq!(specs.len(), builtin_tools + 1);
+        assert_eq!(
+            find_function(&specs, "docs__search"),
+            &JsonSchema::Object {
+                properties: BTreeMap::from([
+                    (
+                        "filters".to_string(),
+                        JsonSchema::Object {
+                            properties: BTreeMap::from([(
+                                "kind".to_string(),
+                                JsonSchema::String { description: None },
+                            )]),
+                            required: None,
+                            additional_properties: None,
+                        },
+                    ),
+                    (
+                        "query".to_string(),
+                        JsonSchema::String { description: None },
+                    ),
+                ]),
+                required: Some(vec!["query".to_string()]),
+                additional_properties: None,
+            }
+        );
+        assert!(!router.tool_supports_parallel("view_image"));
+
+        let report = router.downgrades();
+        assert_eq!(report.pruned, vec!["web__fetch".to_string()]);
+        assert!(report.parallel_calls_serialized);
+        let search = report
+            .simplified
+            .iter()
+            .find(|downgrade| downgrade.tool == "docs__search")
+            .expect("docs__search is reported");
+        assert_eq!(
+            search.removed,
+            vec![
+                SchemaKeyword::Description,
+                SchemaKeyword::AdditionalProperties
+            ]
+        );
+        assert!(
+            report
+                .simplified
+                .iter()
+                .all(|downgrade| downgrade.tool != "web__fetch")
+        );
+    }
+}
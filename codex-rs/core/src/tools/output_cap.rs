@@ -0,0 +1,159 @@
+//! Cutting exec outputs longer than `max_tool_output_bytes`.
+//!
+//! A command that prints far more than any model request can hold would
+//! otherwise land in the history, the event stream and the rollout in full.
+//! Its output is cut to a head and a tail around a marker line giving the
+//! number of bytes left out, before it is formatted for the model or sent in
+//! `ExecCommandEnd`. The full aggregated output goes to the [`BlobStore`],
+//! and the event carries its [`BlobRef`].
+//!
+//! [`BlobStore`]: crate::blob_store::BlobStore
+
+use codex_protocol::protocol::BlobRef;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::config::Config;
+use crate::exec::ExecToolCallOutput;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ToolOutputCap {
+    pub(crate) max_bytes: usize,
+    /// Share of `max_bytes` kept from the head of the output.
+    pub(crate) head_ratio: f64,
+}
+
+impl ToolOutputCap {
+    pub(crate) fn from_config(config: &Config) -> Option<Self> {
+        config.max_tool_output_bytes.map(|max_bytes| Self {
+            max_bytes,
+            head_ratio: config.tool_output_head_ratio,
+        })
+    }
+
+    /// `text` with everything past the head and before the tail replaced by
+    /// a marker line, or `None` when it fits.
+    pub(crate) fn cut(&self, text: &str) -> Option<String> {
+        if text.len() <= self.max_bytes {
+            return None;
+        }
+        let head_bytes = ((self.max_bytes as f64) * self.head_ratio).round() as usize;
+        let tail_bytes = self.max_bytes.saturating_sub(head_bytes);
+        let head_end = floor_char_boundary(text, head_bytes);
+        let tail_start = ceil_char_boundary(text, text.len() - tail_bytes);
+        let head = &text[..head_end];
+        let tail = &text[tail_start..];
+        let omitted = tail_start - head_end;
+
+        let mut out = String::with_capacity(self.max_bytes + 64);
+        out.push_str(head);
+        if !head.is_empty() && !head.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&omitted_marker(omitted));
+        out.push('\n');
+        out.push_str(tail);
+        Some(out)
+    }
+}
+
+/// The line standing in for the `omitted` bytes of a cut output.
+pub(crate) fn omitted_marker(omitted: usize) -> String {
+    format!("[... {omitted} bytes omitted ...]")
+}
+
+/// `output` cut to `cap`, with the blob holding its full aggregated output
+/// when it was cut. Storing the blob is best effort: the output is cut
+/// either way.
+pub(crate) async fn cap_exec_output(
+    session: &Session,
+    cap: Option<ToolOutputCap>,
+    mut output: ExecToolCallOutput,
+) -> (ExecToolCallOutput, Option<BlobRef>) {
+    let Some(cap) = cap else {
+        return (output, None);
+    };
+    let Some(aggregated) = cap.cut(&output.aggregated_output.text) else {
+        return (output, None);
+    };
+    let blob = match session
+        .services
+        .blobs
+        .put(output.aggregated_output.text.as_bytes())
+        .await
+    {
+        Ok(blob) => Some(blob),
+        Err(err) => {
+            warn!("failed to store a cut tool output: {err}");
+            None
+        }
+    };
+    output.aggregated_output.text = aggregated;
+    for stream in [&mut output.stdout, &mut output.stderr] {
+        if let Some(cut) = cap.cut(&stream.text) {
+            stream.text = cut;
+        }
+    }
+    (output, blob)
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn cap(max_bytes: usize, head_ratio: f64) -> ToolOutputCap {
+        ToolOutputCap {
+            max_bytes,
+            head_ratio,
+        }
+    }
+
+    #[test]
+    fn output_within_the_cap_is_kept() {
+        assert_eq!(cap(10, 0.5).cut("0123456789"), None);
+    }
+
+    #[test]
+    fn head_and_tail_follow_the_ratio() {
+        let text = "a".repeat(30) + &"b".repeat(40) + &"c".repeat(10);
+
+        let cut = cap(40, 0.75).cut(&text).expect("cut");
+
+        assert_eq!(
+            cut,
+            format!(
+                "{}\n[... 40 bytes omitted ...]\n{}",
+                "a".repeat(30),
+                "c".repeat(10)
+            )
+        );
+    }
+
+    #[test]
+    fn cuts_never_split_a_character() {
+        let text = "é".repeat(20);
+
+        let cut = cap(5, 0.5).cut(&text).expect("cut");
+
+        let (head, rest) = cut.split_once('\n').expect("marker line");
+        let (marker, tail) = rest.split_once('\n').expect("tail");
+        assert_eq!(head, "é");
+        assert_eq!(tail, "é");
+        assert_eq!(marker, omitted_marker(40 - head.len() - tail.len()));
+    }
+}
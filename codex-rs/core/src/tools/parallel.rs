@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
 use tokio_util::either::Either;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
@@ -20,6 +21,15 @@ use crate::tools::router::ToolRouter;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 
+/// Runs the tool calls of one model response.
+///
+/// Each call starts as soon as the model emits it. Calls of tools that support
+/// parallel calls run together, up to `max_concurrency` of
+/// [`crate::config::types::ParallelToolCalls`]; any other call runs alone.
+/// Every call answers with an output for its own `call_id`, which the turn
+/// records in the order the model emitted the calls, whatever order they
+/// finish in. A call that fails answers the model with its error and leaves
+/// the others running; only interrupting the turn cancels them.
 #[derive(Clone)]
 pub(crate) struct ToolCallRuntime {
     router: Arc<ToolRouter>,
@@ -27,6 +37,10 @@ pub(crate) struct ToolCallRuntime {
     turn_context: Arc<TurnContext>,
     tracker: SharedTurnDiffTracker,
     parallel_execution: Arc<RwLock<()>>,
+    /// One permit per call allowed to run at once, see
+    /// [`crate::config::types::ParallelToolCalls::max_concurrency`]; `None`
+    /// when unlimited.
+    slots: Option<Arc<Semaphore>>,
 }
 
 impl ToolCallRuntime {
@@ -36,12 +50,19 @@ impl ToolCallRuntime {
         turn_context: Arc<TurnContext>,
         tracker: SharedTurnDiffTracker,
     ) -> Self {
+        let slots = turn_context
+            .client
+            .config()
+            .parallel_tool_calls
+            .max_concurrency
+            .map(|max| Arc::new(Semaphore::new(max.get())));
         Self {
             router,
             session,
             turn_context,
             tracker,
             parallel_execution: Arc::new(RwLock::new(())),
+            slots,
         }
     }
 
@@ -58,6 +79,8 @@ impl ToolCallRuntime {
         let turn = Arc::clone(&self.turn_context);
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
+        let slots = self.slots.clone();
+        let deadline = self.turn_context.deadline.clone();
         let started = Instant::now();
 
         let dispatch_span = trace_span!(
@@ -74,7 +97,7 @@ impl ToolCallRuntime {
                     _ = cancellation_token.cancelled() => {
                         let secs = started.elapsed().as_secs_f32().max(0.1);
                         dispatch_span.record("aborted", true);
-                        Ok(Self::aborted_response(&call, secs))
+                        Ok(Self::aborted_response(&call, secs, deadline.is_exceeded()))
                     },
                     res = async {
                         let _guard = if supports_parallel {
@@ -82,6 +105,11 @@ impl ToolCallRuntime {
                         } else {
                             Either::Right(lock.write().await)
                         };
+                        // Held until the call ends, whatever its outcome.
+                        let _slot = match slots {
+                            Some(slots) => slots.acquire_owned().await.ok(),
+                            None => None,
+                        };
 
                         router
                             .dispatch_tool_call(session, turn, tracker, call.clone())
@@ -106,32 +134,37 @@ impl ToolCallRuntime {
 }
 
 impl ToolCallRuntime {
-    fn aborted_response(call: &ToolCall, secs: f32) -> ResponseInputItem {
+    fn aborted_response(call: &ToolCall, secs: f32, deadline_exceeded: bool) -> ResponseInputItem {
         match &call.payload {
             ToolPayload::Custom { .. } => ResponseInputItem::CustomToolCallOutput {
                 call_id: call.call_id.clone(),
-                output: Self::abort_message(call, secs),
+                output: Self::abort_message(call, secs, deadline_exceeded),
             },
             ToolPayload::Mcp { .. } => ResponseInputItem::McpToolCallOutput {
                 call_id: call.call_id.clone(),
-                result: Err(Self::abort_message(call, secs)),
+                result: Err(Self::abort_message(call, secs, deadline_exceeded)),
             },
             _ => ResponseInputItem::FunctionCallOutput {
                 call_id: call.call_id.clone(),
                 output: FunctionCallOutputPayload {
-                    content: Self::abort_message(call, secs),
+                    content: Self::abort_message(call, secs, deadline_exceeded),
                     ..Default::default()
                 },
             },
         }
     }
 
-    fn abort_message(call: &ToolCall, secs: f32) -> String {
+    fn abort_message(call: &ToolCall, secs: f32, deadline_exceeded: bool) -> String {
+        let aborted = if deadline_exceeded {
+            "aborted, the turn ran out of time"
+        } else {
+            "aborted by user"
+        };
         match call.tool_name.as_str() {
             "shell" | "container.exec" | "local_shell" | "shell_command" | "unified_exec" => {
-                format!("Wall time: {secs:.1} seconds\naborted by user")
+                format!("Wall time: {secs:.1} seconds\n{aborted}")
             }
-            _ => format!("aborted by user after {secs:.1}s"),
+            _ => format!("{aborted} after {secs:.1}s"),
         }
     }
 }
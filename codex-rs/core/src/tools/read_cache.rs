@@ -0,0 +1,108 @@
+//! Replacing a `read_file` output that repeats one still in the history with
+//! a reference to it, as configured by `read_cache_min_bytes`.
+//!
+//! Full outputs at least that large are remembered by the SHA-256 of their
+//! content. A later identical output becomes a reference only while the call
+//! holding the content is still in the history, so rollbacks and compactions
+//! never leave a reference pointing at nothing; compaction keeps the calls
+//! that were referenced. Each replacement is recorded in the rollout as a
+//! [`ReadReferenceItem`].
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use codex_protocol::protocol::ReadReferenceItem;
+use codex_protocol::protocol::RolloutItem;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+
+/// Hex digits of the hash shown to the model.
+const SHORT_HASH_LEN: usize = 12;
+
+#[derive(Default)]
+pub(crate) struct ReadCache {
+    /// Full outputs by the hash of their content.
+    by_hash: Mutex<HashMap<String, CachedRead>>,
+}
+
+struct CachedRead {
+    call_id: String,
+    turn: u64,
+    referenced: bool,
+}
+
+impl ReadCache {
+    /// Calls whose output some reference points to.
+    pub(crate) fn referenced_call_ids(&self) -> HashSet<String> {
+        self.lock()
+            .values()
+            .filter(|read| read.referenced)
+            .map(|read| read.call_id.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedRead>> {
+        self.by_hash
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// The output to return for `content`, read by the call `call_id`: either
+/// `content` itself or a reference to an identical earlier output.
+pub(crate) async fn dedupe(
+    session: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    content: String,
+) -> String {
+    let Some(min_bytes) = turn_context.read_cache_min_bytes else {
+        return content;
+    };
+    if content.len() < min_bytes {
+        return content;
+    }
+
+    let hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+    let cache = &session.services.read_cache;
+    let original = cache
+        .lock()
+        .get(&hash)
+        .map(|read| (read.call_id.clone(), read.turn));
+    if let Some((original_call_id, turn)) = original
+        && original_call_id != call_id
+        && session.has_function_call_output(&original_call_id).await
+    {
+        if let Some(read) = cache.lock().get_mut(&hash) {
+            read.referenced = true;
+        }
+        session
+            .persist_turn_rollout_items(
+                &turn_context.sub_id,
+                &[RolloutItem::ReadReference(ReadReferenceItem {
+                    call_id: call_id.to_string(),
+                    original_call_id: original_call_id.clone(),
+                    hash: hash.clone(),
+                })],
+            )
+            .await;
+        let short_hash = &hash[..SHORT_HASH_LEN];
+        return format!(
+            "Unchanged since turn {turn} (call {original_call_id}, sha256 {short_hash})."
+        );
+    }
+
+    cache.lock().insert(
+        hash,
+        CachedRead {
+            call_id: call_id.to_string(),
+            turn: session.services.session_stats.turns() + 1,
+            referenced: false,
+        },
+    );
+    content
+}
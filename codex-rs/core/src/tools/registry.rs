@@ -7,6 +7,7 @@ use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::timeout;
 use async_trait::async_trait;
 use codex_protocol::models::ResponseInputItem;
 use codex_utils_readiness::Readiness;
@@ -34,6 +35,12 @@ pub trait ToolHandler: Send + Sync {
         false
     }
 
+    /// Whether `handle` stops at the turn's tool timeout by itself. Calls to
+    /// other handlers are abandoned by the registry when it expires.
+    fn enforces_timeout(&self) -> bool {
+        false
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError>;
 }
 
@@ -50,6 +57,11 @@ impl ToolRegistry {
         self.handlers.get(name).map(Arc::clone)
     }
 
+    /// Drop the handlers of tools for which `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.handlers.retain(|name, _| keep(name));
+    }
+
     // TODO(jif) for dynamic tools.
     // pub fn register(&mut self, name: impl Into<String>, handler: Arc<dyn ToolHandler>) {
     //     let name = name.into();
@@ -115,7 +127,7 @@ impl ToolRegistry {
                             invocation.turn.tool_call_gate.wait_ready().await;
                             tracing::trace!("tool gate released");
                         }
-                        match handler.handle(invocation).await {
+                        match handle_with_timeout(handler.as_ref(), invocation).await {
                             Ok(output) => {
                                 let preview = output.log_preview();
                                 let success = output.success_for_logging();
@@ -143,6 +155,30 @@ impl ToolRegistry {
     }
 }
 
+async fn handle_with_timeout(
+    handler: &dyn ToolHandler,
+    invocation: ToolInvocation,
+) -> Result<ToolOutput, FunctionCallError> {
+    if handler.enforces_timeout() {
+        return handler.handle(invocation).await;
+    }
+    let session = Arc::clone(&invocation.session);
+    let turn = Arc::clone(&invocation.turn);
+    let call_id = invocation.call_id.clone();
+    let limit = turn.tool_timeouts.for_tool(&invocation.tool_name);
+    match tokio::time::timeout(limit, handler.handle(invocation)).await {
+        Ok(result) => result,
+        Err(_) => {
+            timeout::notify_timed_out(session.as_ref(), turn.as_ref(), &call_id, limit).await;
+            Err(FunctionCallError::RespondToModel(timeout::timeout_output(
+                turn.as_ref(),
+                limit,
+                "",
+            )))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfiguredToolSpec {
     pub spec: ToolSpec,
@@ -6,18 +6,28 @@ use crate::sandboxing::SandboxPermissions;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolPayload;
+use crate::tools::negotiation::negotiate;
 use crate::tools::registry::ConfiguredToolSpec;
 use crate::tools::registry::ToolRegistry;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::build_specs;
+use codex_protocol::config_types::ExecutionMode;
+use codex_protocol::config_types::ToolPolicy;
 use codex_protocol::models::LocalShellAction;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::PlannedToolCallEvent;
+use codex_protocol::protocol::ToolDowngradeReport;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::instrument;
 
+/// Output the model receives for a tool call skipped in dry-run mode.
+const DRY_RUN_SKIPPED_OUTPUT: &str =
+    "execution skipped (dry run): the tool was not run and produced no output";
+
 #[derive(Clone, Debug)]
 pub struct ToolCall {
     pub tool_name: String,
@@ -28,17 +38,33 @@ pub struct ToolCall {
 pub struct ToolRouter {
     registry: ToolRegistry,
     specs: Vec<ConfiguredToolSpec>,
+    tool_policy: ToolPolicy,
+    downgrades: ToolDowngradeReport,
 }
 
 impl ToolRouter {
+    /// Build the tools for a turn. Tools `tool_policy` denies are neither
+    /// advertised nor dispatched. The rest are fitted to the provider's
+    /// capabilities; tools pruned for that are not dispatched either.
     pub fn from_config(
         config: &ToolsConfig,
+        tool_policy: &ToolPolicy,
         mcp_tools: Option<HashMap<String, mcp_types::Tool>>,
     ) -> Self {
         let builder = build_specs(config, mcp_tools);
-        let (specs, registry) = builder.build();
+        let (mut specs, mut registry) = builder.build();
+        specs.retain(|config| tool_policy.allows(config.spec.name()));
+        let downgrades = negotiate(&config.tool_capabilities, &mut specs);
+        registry.retain(|name| {
+            tool_policy.allows(name) && !downgrades.pruned.iter().any(|pruned| pruned == name)
+        });
 
-        Self { registry, specs }
+        Self {
+            registry,
+            specs,
+            tool_policy: tool_policy.clone(),
+            downgrades,
+        }
     }
 
     pub fn specs(&self) -> Vec<ToolSpec> {
@@ -48,6 +74,11 @@ impl ToolRouter {
             .collect()
     }
 
+    /// How the tools were fitted to the provider's capabilities.
+    pub fn downgrades(&self) -> &ToolDowngradeReport {
+        &self.downgrades
+    }
+
     pub fn tool_supports_parallel(&self, tool_name: &str) -> bool {
         self.specs
             .iter()
@@ -140,6 +171,28 @@ impl ToolRouter {
             payload,
         } = call;
         let payload_outputs_custom = matches!(payload, ToolPayload::Custom { .. });
+        if !self.tool_policy.allows(&tool_name) {
+            return Ok(Self::text_response(
+                call_id,
+                payload_outputs_custom,
+                format!("tool {tool_name} is not available in this conversation"),
+            ));
+        }
+        if turn.execution_mode == ExecutionMode::DryRun {
+            let planned = PlannedToolCallEvent {
+                call_id: call_id.clone(),
+                name: tool_name,
+                arguments: payload.log_payload().into_owned(),
+            };
+            session
+                .send_event(turn.as_ref(), EventMsg::PlannedToolCall(planned))
+                .await;
+            return Ok(Self::text_response(
+                call_id,
+                payload_outputs_custom,
+                DRY_RUN_SKIPPED_OUTPUT.to_string(),
+            ));
+        }
         let failure_call_id = call_id.clone();
 
         let invocation = ToolInvocation {
@@ -167,7 +220,14 @@ impl ToolRouter {
         payload_outputs_custom: bool,
         err: FunctionCallError,
     ) -> ResponseInputItem {
-        let message = err.to_string();
+        Self::text_response(call_id, payload_outputs_custom, err.to_string())
+    }
+
+    fn text_response(
+        call_id: String,
+        payload_outputs_custom: bool,
+        message: String,
+    ) -> ResponseInputItem {
         if payload_outputs_custom {
             ResponseInputItem::CustomToolCallOutput {
                 call_id,
@@ -73,6 +73,7 @@ impl ApplyPatchRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            correlation_id: ctx.session.correlation_id(&ctx.turn.sub_id),
         })
     }
 }
@@ -58,6 +58,7 @@ impl ShellRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            correlation_id: ctx.session.correlation_id(&ctx.turn.sub_id),
         })
     }
 }
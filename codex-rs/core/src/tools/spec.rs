@@ -3,10 +3,14 @@ use crate::client_common::tools::ToolSpec;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::models_manager::model_family::ModelFamily;
+use crate::project_notes::ADD_PROJECT_NOTE_TOOL_NAME;
+use crate::skills::SkillMetadata;
+use crate::skills::tools::skill_tool_name;
 use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
 use crate::tools::registry::ToolRegistryBuilder;
+use codex_protocol::config_types::ToolCapabilities;
 use codex_protocol::openai_models::ApplyPatchToolType;
 use codex_protocol::openai_models::ConfigShellToolType;
 use serde::Deserialize;
@@ -22,7 +26,13 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub include_project_notes_tool: bool,
+    /// Skills offered as tools, see [`crate::skills::tools`].
+    pub skill_tools: Vec<SkillMetadata>,
     pub experimental_supported_tools: Vec<String>,
+    /// What the provider accepts; the roster is adjusted to it by
+    /// [`crate::tools::negotiation::negotiate`].
+    pub tool_capabilities: ToolCapabilities,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -39,6 +49,7 @@ impl ToolsConfig {
         let include_apply_patch_tool = features.enabled(Feature::ApplyPatchFreeform);
         let include_web_search_request = features.enabled(Feature::WebSearchRequest);
         let include_view_image_tool = features.enabled(Feature::ViewImageTool);
+        let include_project_notes_tool = features.enabled(Feature::ProjectNotes);
 
         let shell_type = if !features.enabled(Feature::ShellTool) {
             ConfigShellToolType::Disabled
@@ -70,7 +81,27 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: include_web_search_request,
             include_view_image_tool,
+            include_project_notes_tool,
+            skill_tools: Vec::new(),
             experimental_supported_tools: model_family.experimental_supported_tools.clone(),
+            tool_capabilities: ToolCapabilities::default(),
+        }
+    }
+
+    /// Offer `skills` as tools.
+    pub(crate) fn with_skill_tools(self, skills: Vec<SkillMetadata>) -> Self {
+        Self {
+            skill_tools: skills,
+            ..self
+        }
+    }
+
+    /// Adjust the roster to `capabilities`, typically the provider's
+    /// `tool_capabilities`. `None` leaves it unrestricted.
+    pub(crate) fn with_tool_capabilities(self, capabilities: Option<ToolCapabilities>) -> Self {
+        Self {
+            tool_capabilities: capabilities.unwrap_or_default(),
+            ..self
         }
     }
 }
@@ -416,6 +447,61 @@ fn create_view_image_tool() -> ToolSpec {
     })
 }
 
+fn create_add_project_note_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "note".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "A short convention or fact about this project, in one or two sentences"
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: ADD_PROJECT_NOTE_TOOL_NAME.to_string(),
+        description: "Save a note that future sessions in this project are told about, once the user approves it. Use it for lasting conventions the user states, not for the current task.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["note".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_skill_tool(skill: &SkillMetadata) -> ToolSpec {
+    let properties = skill
+        .parameters
+        .iter()
+        .map(|param| {
+            let description = (!param.description.is_empty()).then(|| param.description.clone());
+            (param.name.clone(), JsonSchema::String { description })
+        })
+        .collect();
+    let required = skill
+        .parameters
+        .iter()
+        .filter(|param| param.required)
+        .map(|param| param.name.clone())
+        .collect();
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: skill_tool_name(skill),
+        description: format!(
+            "Use the skill {}: {} Returns its instructions for the given arguments.",
+            skill.name, skill.description
+        ),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(required),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_test_sync_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -986,9 +1072,11 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::McpHandler;
     use crate::tools::handlers::McpResourceHandler;
     use crate::tools::handlers::PlanHandler;
+    use crate::tools::handlers::ProjectNoteHandler;
     use crate::tools::handlers::ReadFileHandler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
+    use crate::tools::handlers::SkillToolHandler;
     use crate::tools::handlers::TestSyncHandler;
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::handlers::ViewImageHandler;
@@ -1102,6 +1190,19 @@ pub(crate) fn build_specs(
         builder.register_handler("view_image", view_image_handler);
     }
 
+    if config.include_project_notes_tool {
+        builder.push_spec(create_add_project_note_tool());
+        builder.register_handler(ADD_PROJECT_NOTE_TOOL_NAME, Arc::new(ProjectNoteHandler));
+    }
+
+    for skill in &config.skill_tools {
+        builder.push_spec(create_skill_tool(skill));
+        builder.register_handler(
+            skill_tool_name(skill),
+            Arc::new(SkillToolHandler::new(skill.clone())),
+        );
+    }
+
     if let Some(mcp_tools) = mcp_tools {
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
         entries.sort_by(|a, b| a.0.cmp(&b.0));
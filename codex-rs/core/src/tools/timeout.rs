@@ -0,0 +1,40 @@
+//! Tool calls that outlive their limit in `tool_timeouts`.
+//!
+//! Shell handlers lower the expiration of the command they run to the limit,
+//! so the command and its process group are killed and its output is kept.
+//! Other handlers are abandoned by the registry when the limit expires.
+//! Either way the model is told the call was stopped, not that it completed.
+
+use std::time::Duration;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::protocol::EventMsg;
+use crate::protocol::ToolTimedOutEvent;
+use crate::truncate::formatted_truncate_text;
+
+/// The output sent to the model for a call stopped after `after`, with what
+/// the tool had produced by then.
+pub(crate) fn timeout_output(turn: &TurnContext, after: Duration, captured: &str) -> String {
+    let content = format!(
+        "exit: timeout\nThe tool was killed after {after:?} and did not complete.\ncaptured:\n{captured}"
+    );
+    formatted_truncate_text(&content, turn.truncation_policy)
+}
+
+pub(crate) async fn notify_timed_out(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    after: Duration,
+) {
+    session
+        .send_event(
+            turn,
+            EventMsg::ToolTimedOut(ToolTimedOutEvent {
+                call_id: call_id.to_string(),
+                after,
+            }),
+        )
+        .await;
+}
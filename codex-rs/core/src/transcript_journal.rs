@@ -0,0 +1,463 @@
+//! Journal of the changes made to a conversation's history, for audit, see
+//! [`crate::CodexConversation::mutation_log`].
+//!
+//! Every change the session persists is journaled as a [`TranscriptMutation`]
+//! and recorded in the rollout after the items it added, so resuming rebuilds
+//! the journal the way it rebuilds the history. Besides the entries, the
+//! journal keeps the items each change added, which is what
+//! [`TranscriptJournal::transcript_as_of`] replays; an entry rebuilt on resume
+//! holds the items as the rollout kept them.
+//!
+//! Those items add up to everything the session ever sent the model. Once the
+//! history is compacted, [`TranscriptJournal::spill`] replaces the items the
+//! rollout holds with a [`RolloutRef`] to where it recorded them, read back by
+//! seeking there only when a transcript that holds them is asked for.
+//! Replaying entries works on the references and never reads an item back.
+//! The journal also keeps the transcript of the rollout up to the compaction
+//! as references, so [`TranscriptJournal::transcript`] reads through the lines
+//! recorded since only.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ItemRange;
+use codex_protocol::protocol::TranscriptMutation;
+use codex_protocol::protocol::TranscriptMutationKind;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::context_manager::ContextManager;
+use crate::history_truncation::truncate_at_index;
+use crate::rollout::transcript::RolloutRef;
+use crate::rollout::transcript::SpilledTranscript;
+use crate::rollout::transcript::item_sha256;
+use crate::rollout::transcript::read_items;
+use crate::truncate::TruncationPolicy;
+
+/// How much of a conversation's transcript is held in memory, see
+/// [`crate::CodexConversation::transcript_footprint`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranscriptFootprint {
+    /// Items held in full: the current history and the journaled items not
+    /// spilled to the rollout.
+    pub resident_items: usize,
+    /// Journaled items held as a reference into the rollout.
+    pub spilled_items: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct TranscriptJournal {
+    state: Mutex<JournalState>,
+    /// The transcript of the rollout up to the last spill.
+    spilled: Mutex<SpilledTranscript>,
+}
+
+#[derive(Default)]
+struct JournalState {
+    entries: Vec<JournalEntry>,
+    /// The history as of the last entry.
+    current: Vec<ResponseItem>,
+    /// Hash of `current`, open for appends.
+    hasher: Sha256,
+    /// Entries before this one were spilled already.
+    spilled_entries: usize,
+}
+
+struct JournalEntry {
+    mutation: TranscriptMutation,
+    /// The items of `mutation.added`.
+    added: Vec<JournalItem>,
+}
+
+#[derive(Clone)]
+enum JournalItem {
+    Resident(ResponseItem),
+    Spilled(SpilledItem),
+}
+
+/// A journaled item left in the rollout.
+#[derive(Clone)]
+struct SpilledItem {
+    /// Where the rollout recorded the item, as it was before the session
+    /// recorded it into its history.
+    rollout_ref: RolloutRef,
+    /// The truncation policy the session recorded it with.
+    policy: TruncationPolicy,
+}
+
+/// Entries rebuilt while replaying a rollout, see [`TranscriptJournal::seed`].
+#[derive(Default)]
+pub(crate) struct JournalReplay {
+    entries: Vec<JournalEntry>,
+}
+
+impl JournalReplay {
+    /// Account for `mutation`, read back when the replayed history was
+    /// `history`.
+    pub(crate) fn observe(&mut self, mutation: &TranscriptMutation, history: &[ResponseItem]) {
+        let start = mutation.added.start.min(history.len());
+        self.entries.push(JournalEntry {
+            mutation: mutation.clone(),
+            added: history[start..]
+                .iter()
+                .cloned()
+                .map(JournalItem::Resident)
+                .collect(),
+        });
+    }
+}
+
+/// The truncation policy of items the rollout records none for, as resuming
+/// replays them.
+const UNRECORDED_POLICY: TruncationPolicy = TruncationPolicy::Bytes(usize::MAX);
+
+impl TranscriptJournal {
+    /// Journal the change that left the history at `history`. Returns the
+    /// entry to persist, or `None` when the history did not change.
+    pub(crate) fn record(
+        &self,
+        kind: TranscriptMutationKind,
+        history: &[ResponseItem],
+    ) -> Option<TranscriptMutation> {
+        let mut state = self.lock_state();
+        let kept = common_prefix_len(&state.current, history);
+        if kept == state.current.len() && kept == history.len() {
+            return None;
+        }
+        let removed = ItemRange {
+            start: kept,
+            end: state.current.len(),
+        };
+        if kept < state.current.len() {
+            state.current.truncate(kept);
+            let mut hasher = Sha256::new();
+            hash_items(&mut hasher, &state.current);
+            state.hasher = hasher;
+        }
+        let mut added = Vec::with_capacity(history.len() - kept);
+        for item in &history[kept..] {
+            hash_item(&mut state.hasher, item);
+            state.current.push(item.clone());
+            added.push(JournalItem::Resident(item.clone()));
+        }
+        let mutation = TranscriptMutation {
+            seq: state
+                .entries
+                .last()
+                .map_or(1, |entry| entry.mutation.seq + 1),
+            kind,
+            removed,
+            added: ItemRange {
+                start: kept,
+                end: history.len(),
+            },
+            transcript_hash: format!("{:x}", state.hasher.clone().finalize()),
+        };
+        state.entries.push(JournalEntry {
+            mutation: mutation.clone(),
+            added,
+        });
+        Some(mutation)
+    }
+
+    /// Replace the journal with the entries of a resumed or forked rollout.
+    /// The history the session resumes with is journaled next, as
+    /// [`TranscriptMutationKind::Resumed`] when it differs.
+    pub(crate) fn seed(&self, replay: JournalReplay) {
+        let current: Vec<ResponseItem> = replay_entries(&replay.entries)
+            .into_iter()
+            .filter_map(|item| match item {
+                JournalItem::Resident(item) => Some(item),
+                JournalItem::Spilled(_) => None,
+            })
+            .collect();
+        let mut hasher = Sha256::new();
+        hash_items(&mut hasher, &current);
+        *self.lock_state() = JournalState {
+            entries: replay.entries,
+            current,
+            hasher,
+            spilled_entries: 0,
+        };
+        *self.lock_spilled() = SpilledTranscript::default();
+    }
+
+    /// Spill the journaled items that the rollout at `rollout_path` recorded
+    /// since the last spill, see the module docs. Called once the history
+    /// is compacted, with everything recorded so far written.
+    pub(crate) async fn spill(&self, rollout_path: &Path) -> io::Result<()> {
+        let spilled = self.lock_spilled().clone();
+        let read_on = spilled.read_on(rollout_path).await?;
+        // Journaled items are matched to the rollout by what recording them
+        // into the history made of them.
+        let refs: HashMap<[u8; 32], SpilledItem> = read_on
+            .read
+            .into_iter()
+            .filter_map(|read| {
+                let policy = read.policy.unwrap_or(UNRECORDED_POLICY);
+                let item = ContextManager::prepare_item(&read.item, policy)?;
+                let spilled = SpilledItem {
+                    rollout_ref: read.rollout_ref,
+                    policy,
+                };
+                Some((item_sha256(&item), spilled))
+            })
+            .collect();
+        {
+            let mut guard = self.lock_state();
+            let state = &mut *guard;
+            for entry in &mut state.entries[state.spilled_entries..] {
+                for item in &mut entry.added {
+                    if let JournalItem::Resident(resident) = item
+                        && let Some(spilled) = refs.get(&item_sha256(resident))
+                    {
+                        *item = JournalItem::Spilled(spilled.clone());
+                    }
+                }
+            }
+            state.spilled_entries = state.entries.len();
+        }
+        *self.lock_spilled() = read_on.transcript;
+        Ok(())
+    }
+
+    /// The transcript of the rollout at `rollout_path` as
+    /// [`crate::rollout::transcript::read_transcript`] reads it, reading
+    /// through the lines recorded since the last spill only.
+    pub(crate) async fn transcript(&self, rollout_path: &Path) -> io::Result<Vec<ResponseItem>> {
+        let spilled = self.lock_spilled().clone();
+        spilled
+            .read_on(rollout_path)
+            .await?
+            .into_items(rollout_path)
+            .await
+    }
+
+    pub(crate) fn footprint(&self) -> TranscriptFootprint {
+        let state = self.lock_state();
+        let mut footprint = TranscriptFootprint {
+            resident_items: state.current.len(),
+            spilled_items: 0,
+        };
+        for item in state.entries.iter().flat_map(|entry| &entry.added) {
+            match item {
+                JournalItem::Resident(_) => footprint.resident_items += 1,
+                JournalItem::Spilled(_) => footprint.spilled_items += 1,
+            }
+        }
+        footprint
+    }
+
+    /// Every entry, oldest first.
+    pub(crate) fn mutations(&self) -> Vec<TranscriptMutation> {
+        self.lock_state()
+            .entries
+            .iter()
+            .map(|entry| entry.mutation.clone())
+            .collect()
+    }
+
+    /// The history as the model saw it right after entry `seq`, or `None`
+    /// when the journal has no such entry. Spilled items are read back from
+    /// the rollout at `rollout_path`, which must hold everything journaled.
+    pub(crate) async fn transcript_as_of(
+        &self,
+        seq: u64,
+        rollout_path: &Path,
+    ) -> io::Result<Option<Vec<ResponseItem>>> {
+        let items = {
+            let state = self.lock_state();
+            let Some(last) = state
+                .entries
+                .iter()
+                .position(|entry| entry.mutation.seq == seq)
+            else {
+                return Ok(None);
+            };
+            replay_entries(&state.entries[..=last])
+        };
+        let refs: Vec<RolloutRef> = items
+            .iter()
+            .filter_map(|item| match item {
+                JournalItem::Spilled(spilled) => Some(spilled.rollout_ref.clone()),
+                JournalItem::Resident(_) => None,
+            })
+            .collect();
+        let mut read_back = read_items(rollout_path, &refs).await?.into_iter();
+        let items = items
+            .into_iter()
+            .map(|item| match item {
+                JournalItem::Resident(item) => Ok(item),
+                JournalItem::Spilled(spilled) => read_back
+                    .next()
+                    .and_then(|item| ContextManager::prepare_item(&item, spilled.policy))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "a spilled item was not read back",
+                        )
+                    }),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut history = ContextManager::new();
+        history.replace(items);
+        Ok(Some(history.get_history_for_prompt()))
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, JournalState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_spilled(&self) -> MutexGuard<'_, SpilledTranscript> {
+        self.spilled.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The history left by `entries`, applied in order to an empty one. Spilled
+/// items stay spilled.
+fn replay_entries(entries: &[JournalEntry]) -> Vec<JournalItem> {
+    let mut items = Vec::new();
+    for entry in entries {
+        items = truncate_at_index(items, entry.mutation.added.start);
+        items.extend(entry.added.iter().cloned());
+    }
+    items
+}
+
+fn common_prefix_len(a: &[ResponseItem], b: &[ResponseItem]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn hash_items(hasher: &mut Sha256, items: &[ResponseItem]) {
+    for item in items {
+        hash_item(hasher, item);
+    }
+}
+
+fn hash_item(hasher: &mut Sha256, item: &ResponseItem) {
+    let bytes = serde_json::to_vec(item).unwrap_or_default();
+    hasher.update(&bytes);
+    hasher.update(b"\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn each_entry_replays_the_history_it_left() {
+        let journal = TranscriptJournal::default();
+        let first = journal
+            .record(
+                TranscriptMutationKind::Append,
+                &[message("a"), message("b")],
+            )
+            .expect("append");
+        let second = journal
+            .record(TranscriptMutationKind::Compaction, &[message("summary")])
+            .expect("compaction");
+        assert_eq!(
+            journal.record(TranscriptMutationKind::Append, &[message("summary")]),
+            None
+        );
+
+        assert_eq!(
+            (first.seq, first.removed, first.added),
+            (1, ItemRange::default(), ItemRange { start: 0, end: 2 })
+        );
+        assert_eq!(
+            (second.seq, second.removed, second.added),
+            (
+                2,
+                ItemRange { start: 0, end: 2 },
+                ItemRange { start: 0, end: 1 }
+            )
+        );
+        // Nothing was spilled, so the rollout is never read.
+        let rollout = Path::new("/nonexistent/rollout.jsonl");
+        assert_eq!(
+            journal.transcript_as_of(1, rollout).await.unwrap(),
+            Some(vec![message("a"), message("b")])
+        );
+        assert_eq!(
+            journal.transcript_as_of(2, rollout).await.unwrap(),
+            Some(vec![message("summary")])
+        );
+        assert_eq!(journal.transcript_as_of(3, rollout).await.unwrap(), None);
+
+        let mut hasher = Sha256::new();
+        hash_items(&mut hasher, &[message("summary")]);
+        assert_eq!(second.transcript_hash, format!("{:x}", hasher.finalize()));
+    }
+
+    #[tokio::test]
+    async fn spilled_items_are_read_back_from_the_rollout() {
+        const ITEMS: usize = 3_000;
+        let item = |index: usize| message(&format!("item {index}"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let rollout = dir.path().join("rollout.jsonl");
+        let lines: String = (0..ITEMS)
+            .map(|index| {
+                let line = json!({
+                    "timestamp": "2030-01-01T00:00:00.000Z",
+                    "type": "response_item",
+                    "payload": item(index),
+                });
+                format!("{line}\n")
+            })
+            .collect();
+        std::fs::write(&rollout, lines).unwrap();
+
+        // Each change compacts the history down to its newest item.
+        let journal = TranscriptJournal::default();
+        for index in 0..ITEMS {
+            journal
+                .record(TranscriptMutationKind::Compaction, &[item(index)])
+                .expect("compaction");
+        }
+        journal.spill(&rollout).await.unwrap();
+        assert_eq!(
+            journal.footprint(),
+            TranscriptFootprint {
+                resident_items: 1,
+                spilled_items: ITEMS,
+            }
+        );
+
+        for index in [0, ITEMS / 2, ITEMS - 1] {
+            assert_eq!(
+                journal
+                    .transcript_as_of(index as u64 + 1, &rollout)
+                    .await
+                    .unwrap(),
+                Some(vec![item(index)])
+            );
+        }
+        let transcript: Vec<ResponseItem> = (0..ITEMS).map(item).collect();
+        assert_eq!(journal.transcript(&rollout).await.unwrap(), transcript);
+
+        // A rollout that lost the spilled items cannot bring them back.
+        std::fs::write(&rollout, "").unwrap();
+        let err = journal.transcript_as_of(1, &rollout).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,99 @@
+//! Wall-clock budget of a turn, covering the model's time and every tool call
+//! the turn makes.
+//!
+//! Once the budget is spent the turn's cancellation token is cancelled, which
+//! stops the model stream, the reconnect backoff and the running tools alike.
+//! The session then tells the stopped turn apart from an interrupted one with
+//! [`TurnDeadline::is_exceeded`] and completes it instead of aborting it.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::AbortOnDropHandle;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TurnDeadline {
+    budget: Option<Duration>,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl TurnDeadline {
+    pub(crate) fn new(budget: Option<Duration>) -> Self {
+        Self {
+            budget,
+            exceeded: Arc::default(),
+        }
+    }
+
+    pub(crate) fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// Cancel `token` once the budget, counted from now, is spent. The timer
+    /// stops when the returned handle is dropped; `None` without a budget.
+    pub(crate) fn start(&self, token: CancellationToken) -> Option<AbortOnDropHandle<()>> {
+        let budget = self.budget?;
+        let exceeded = Arc::clone(&self.exceeded);
+        Some(AbortOnDropHandle::new(tokio::spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                _ = tokio::time::sleep(budget) => {
+                    exceeded.store(true, Ordering::Release);
+                    token.cancel();
+                }
+            }
+        })))
+    }
+
+    /// Whether the budget ran out, as opposed to the turn being interrupted.
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn tool_sleeping_past_the_budget_is_stopped_at_the_budget() {
+        let deadline = TurnDeadline::new(Some(Duration::from_secs(5)));
+        let token = CancellationToken::new();
+        let _timer = deadline.start(token.clone());
+        let started = Instant::now();
+
+        let finished = tokio::select! {
+            _ = token.cancelled() => false,
+            _ = tokio::time::sleep(Duration::from_secs(60)) => true,
+        };
+
+        assert!(!finished);
+        assert!(deadline.is_exceeded());
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn interrupted_or_finished_turns_do_not_exceed_the_budget() {
+        let deadline = TurnDeadline::new(Some(Duration::from_secs(5)));
+        let interrupted = CancellationToken::new();
+        let timer = deadline.start(interrupted.clone());
+        interrupted.cancel();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert!(!deadline.is_exceeded());
+        drop(timer);
+
+        let finished = CancellationToken::new();
+        drop(deadline.start(finished.clone()));
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert!(!finished.is_cancelled());
+        assert!(!deadline.is_exceeded());
+
+        let unbounded = TurnDeadline::default();
+        assert!(unbounded.start(CancellationToken::new()).is_none());
+    }
+}
@@ -3,6 +3,7 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::SystemTime;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -11,10 +12,19 @@ use sha1::digest::Output;
 use uuid::Uuid;
 
 use crate::protocol::FileChange;
+use crate::protocol::TurnDiffSummaryEvent;
+use crate::protocol::TurnFileChange;
+use crate::protocol::TurnFileChangeKind;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
 
+/// Workspaces holding more bytes of regular files than this are not
+/// snapshotted, so edits made there by shell commands are not tracked.
+const WORKSPACE_SNAPSHOT_MAX_BYTES: u64 = 16 * 1024 * 1024;
+/// Same as [`WORKSPACE_SNAPSHOT_MAX_BYTES`], for the number of files.
+const WORKSPACE_SNAPSHOT_MAX_FILES: usize = 10_000;
+
 struct BaselineFileInfo {
     path: PathBuf,
     content: Vec<u8>,
@@ -40,6 +50,9 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// Files under the working directory of the last shell command, used to
+    /// find the files shell commands change.
+    workspace: Option<WorkspaceFiles>,
 }
 
 impl TurnDiffTracker {
@@ -127,6 +140,57 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Front-run shell commands run in `cwd` by snapshotting the workspace, so that
+    /// [`Self::on_command_end`] can find the files the command changed.
+    pub fn on_command_begin(&mut self, cwd: &Path) {
+        if self
+            .workspace
+            .as_ref()
+            .is_some_and(|workspace| workspace.root == cwd)
+        {
+            return;
+        }
+        let mut workspace = WorkspaceFiles {
+            root: cwd.to_path_buf(),
+            files: Some(HashMap::new()),
+        };
+        workspace.rescan();
+        self.workspace = Some(workspace);
+    }
+
+    /// Compare the workspace against the snapshot taken before the command and
+    /// track the starting contents of any file the command added, changed, or removed.
+    pub fn on_command_end(&mut self) {
+        let Some(workspace) = self.workspace.as_mut() else {
+            return;
+        };
+        for (path, previous) in workspace.rescan() {
+            if self.external_to_temp_name.contains_key(&path) {
+                continue;
+            }
+            let internal = Uuid::new_v4().to_string();
+            self.external_to_temp_name
+                .insert(path.clone(), internal.clone());
+            self.temp_name_to_current_path
+                .insert(internal.clone(), path.clone());
+            let baseline_file_info = match previous {
+                Some(file) => BaselineFileInfo {
+                    path,
+                    content: file.content,
+                    mode: file.mode,
+                    oid: file.oid,
+                },
+                None => BaselineFileInfo {
+                    path,
+                    content: vec![],
+                    mode: FileMode::Regular,
+                    oid: ZERO_OID.to_string(),
+                },
+            };
+            self.baseline_file_info.insert(internal, baseline_file_info);
+        }
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -249,6 +313,52 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Summarize the tracked files whose contents changed during the turn, flagging
+    /// those outside of `cwd`. Returns `None` when no file changed.
+    pub fn get_summary(&self, cwd: &Path) -> Option<TurnDiffSummaryEvent> {
+        let mut files = Vec::new();
+        for (internal, info) in &self.baseline_file_info {
+            let Some(path) = self.get_path_for_internal(internal) else {
+                continue;
+            };
+            let left_bytes = (info.oid != ZERO_OID).then_some(info.content.as_slice());
+            let current_mode = file_mode_for_path(&path).unwrap_or(FileMode::Regular);
+            let right_bytes = blob_bytes(&path, current_mode);
+            if left_bytes == right_bytes.as_deref() {
+                continue;
+            }
+
+            let kind = match (left_bytes, &right_bytes) {
+                (None, _) => TurnFileChangeKind::Added,
+                (Some(_), None) => TurnFileChangeKind::Deleted,
+                (Some(_), Some(_)) => TurnFileChangeKind::Modified,
+            };
+            let left_text = std::str::from_utf8(left_bytes.unwrap_or_default()).ok();
+            let right_text = std::str::from_utf8(right_bytes.as_deref().unwrap_or_default()).ok();
+            let (additions, deletions) = match (left_text, right_text) {
+                (Some(l), Some(r)) => count_changed_lines(l, r),
+                _ => (0, 0),
+            };
+            files.push(TurnFileChange {
+                outside_cwd: !path.starts_with(cwd),
+                path,
+                kind,
+                additions,
+                deletions,
+            });
+        }
+        if files.is_empty() {
+            return None;
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Some(TurnDiffSummaryEvent {
+            additions: files.iter().map(|file| file.additions).sum(),
+            deletions: files.iter().map(|file| file.deletions).sum(),
+            files,
+        })
+    }
+
     fn get_file_diff(&mut self, internal_file_name: &str) -> String {
         let mut aggregated = String::new();
 
@@ -368,6 +478,125 @@ impl TurnDiffTracker {
     }
 }
 
+/// Count the lines added to and deleted from `old` to get `new`.
+fn count_changed_lines(old: &str, new: &str) -> (u64, u64) {
+    let mut additions = 0;
+    let mut deletions = 0;
+    for change in similar::TextDiff::from_lines(old, new).iter_all_changes() {
+        match change.tag() {
+            similar::ChangeTag::Insert => additions += 1,
+            similar::ChangeTag::Delete => deletions += 1,
+            similar::ChangeTag::Equal => {}
+        }
+    }
+    (additions, deletions)
+}
+
+struct WorkspaceFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    mode: FileMode,
+    oid: String,
+    content: Vec<u8>,
+}
+
+/// Regular files under `root`, skipping `.git` directories.
+struct WorkspaceFiles {
+    root: PathBuf,
+    /// `None` once the workspace grew past the snapshot size cap.
+    files: Option<HashMap<PathBuf, WorkspaceFile>>,
+}
+
+impl WorkspaceFiles {
+    /// Bring the snapshot up to date with the disk and return the files that were
+    /// added, changed, or removed since the last scan, with their previous state.
+    /// Files whose modification time and size did not change are not read again.
+    fn rescan(&mut self) -> Vec<(PathBuf, Option<WorkspaceFile>)> {
+        let Some(files) = self.files.as_mut() else {
+            return Vec::new();
+        };
+        let Some(listing) = list_workspace_files(&self.root) else {
+            self.files = None;
+            return Vec::new();
+        };
+
+        let mut changed = Vec::new();
+        let mut seen = HashMap::with_capacity(listing.len());
+        for (path, modified, len) in listing {
+            if let Some(file) = files.remove(&path) {
+                if file.modified == modified && file.len == len {
+                    seen.insert(path, file);
+                    continue;
+                }
+                let Ok(content) = fs::read(&path) else {
+                    changed.push((path, Some(file)));
+                    continue;
+                };
+                let oid = format!("{:x}", git_blob_sha1_hex_bytes(&content));
+                let unchanged = oid == file.oid;
+                let current = WorkspaceFile {
+                    modified,
+                    len,
+                    mode: file_mode_for_path(&path).unwrap_or(FileMode::Regular),
+                    oid,
+                    content,
+                };
+                seen.insert(path.clone(), current);
+                if !unchanged {
+                    changed.push((path, Some(file)));
+                }
+            } else if let Ok(content) = fs::read(&path) {
+                let file = WorkspaceFile {
+                    modified,
+                    len,
+                    mode: file_mode_for_path(&path).unwrap_or(FileMode::Regular),
+                    oid: format!("{:x}", git_blob_sha1_hex_bytes(&content)),
+                    content,
+                };
+                seen.insert(path.clone(), file);
+                changed.push((path, None));
+            }
+        }
+        // Whatever was not seen again has been removed.
+        changed.extend(files.drain().map(|(path, file)| (path, Some(file))));
+        *files = seen;
+        changed
+    }
+}
+
+/// Walk `root` for regular files and their modification times and sizes, or
+/// return `None` when the workspace is over the snapshot size cap.
+fn list_workspace_files(root: &Path) -> Option<Vec<(PathBuf, Option<SystemTime>, u64)>> {
+    let mut listing = Vec::new();
+    let mut total_bytes = 0u64;
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_dir() {
+                if entry.file_name() != ".git" {
+                    pending.push(path);
+                }
+            } else if meta.is_file() {
+                total_bytes += meta.len();
+                if total_bytes > WORKSPACE_SNAPSHOT_MAX_BYTES
+                    || listing.len() >= WORKSPACE_SNAPSHOT_MAX_FILES
+                {
+                    return None;
+                }
+                listing.push((path, meta.modified().ok(), meta.len()));
+            }
+        }
+    }
+    Some(listing)
+}
+
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
 fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
@@ -893,4 +1122,94 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[test]
+    fn command_changes_are_summarized() {
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let edited = dir.path().join("edited.txt");
+        let removed = dir.path().join("removed.txt");
+        let created = dir.path().join("nested/created.txt");
+        fs::write(&edited, "a\nb\n").unwrap();
+        fs::write(&removed, "gone\n").unwrap();
+        fs::write(dir.path().join("same.txt"), "same\n").unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_command_begin(dir.path());
+        fs::write(&edited, "a\nB\nc\n").unwrap();
+        fs::remove_file(&removed).unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(&created, "new\n").unwrap();
+        fs::write(dir.path().join(".git/index"), "ignored\n").unwrap();
+        acc.on_command_end();
+
+        // Files outside the workspace show up when changed through apply_patch.
+        let patched = outside.path().join("patched.txt");
+        acc.on_patch_begin(&HashMap::from([(
+            patched.clone(),
+            FileChange::Add {
+                content: "x\n".to_string(),
+            },
+        )]));
+        fs::write(&patched, "x\n").unwrap();
+
+        let summary = acc.get_summary(dir.path()).unwrap();
+        let mut expected = vec![
+            TurnFileChange {
+                path: edited,
+                kind: TurnFileChangeKind::Modified,
+                additions: 2,
+                deletions: 1,
+                outside_cwd: false,
+            },
+            TurnFileChange {
+                path: removed,
+                kind: TurnFileChangeKind::Deleted,
+                additions: 0,
+                deletions: 1,
+                outside_cwd: false,
+            },
+            TurnFileChange {
+                path: created,
+                kind: TurnFileChangeKind::Added,
+                additions: 1,
+                deletions: 0,
+                outside_cwd: false,
+            },
+            TurnFileChange {
+                path: patched,
+                kind: TurnFileChangeKind::Added,
+                additions: 1,
+                deletions: 0,
+                outside_cwd: true,
+            },
+        ];
+        expected.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            summary,
+            TurnDiffSummaryEvent {
+                files: expected,
+                additions: 4,
+                deletions: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn reverted_changes_are_not_summarized() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "one\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_command_begin(dir.path());
+        fs::write(&file, "one\ntwo\n").unwrap();
+        acc.on_command_end();
+        acc.on_command_begin(dir.path());
+        fs::write(&file, "one\n").unwrap();
+        acc.on_command_end();
+
+        assert_eq!(acc.get_summary(dir.path()), None);
+    }
 }
@@ -0,0 +1,366 @@
+//! Results of individual submissions, for embedders that want to await the
+//! answer to one message while another task drains the event stream.
+//!
+//! A [`TurnTicket`] is registered before its submission is sent, and the
+//! session resolves it from the events it emits: the turn the submission
+//! started, or the running turn its input was folded into, fills in the
+//! result. Input folded into a running turn is answered separately once the
+//! model has finished answering the input before it; input the model sees
+//! mid-answer is answered together with it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use codex_protocol::items::TurnItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TurnAbortReason;
+use tokio::sync::oneshot;
+
+/// Handle to the result of one submission, returned by
+/// [`crate::CodexConversation::submit_with_ticket`].
+#[derive(Debug)]
+pub struct TurnTicket {
+    submission_id: String,
+    result: oneshot::Receiver<TurnResult>,
+}
+
+impl TurnTicket {
+    /// Id of the submission, as found on its events.
+    pub fn submission_id(&self) -> &str {
+        &self.submission_id
+    }
+
+    pub(crate) async fn wait(self) -> Option<TurnResult> {
+        self.result.await.ok()
+    }
+}
+
+/// How a submission's turn ended.
+#[derive(Debug, Clone, Default)]
+pub struct TurnResult {
+    /// The last assistant message answering the submission.
+    pub final_message: Option<String>,
+    /// Items completed while answering the submission.
+    pub items: Vec<TurnItem>,
+    /// Tokens used while answering the submission.
+    pub usage: TokenUsage,
+    /// Set when the turn did not run to completion, or reported an error
+    /// before completing.
+    pub error: Option<TurnError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TurnError {
+    /// The turn was interrupted, or replaced by another one such as an undo.
+    Aborted(TurnAbortReason),
+    /// The input or the turn was rejected before reaching the model.
+    Rejected(String),
+    /// The turn reported an error.
+    Failed(String),
+    /// The turn ran out of its wall-clock budget and completed with what it
+    /// had.
+    DeadlineExceeded,
+    /// The submission did not start a turn.
+    NotStarted,
+}
+
+/// Tickets waiting on the session, resolved from the events it sends.
+#[derive(Debug, Default)]
+pub(crate) struct TurnResults {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Tickets whose submission has not started or joined a turn yet.
+    waiting: HashMap<String, Waiter>,
+    turn: Option<RunningTurn>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    tx: oneshot::Sender<TurnResult>,
+    error: Option<TurnError>,
+}
+
+#[derive(Debug, Default)]
+struct RunningTurn {
+    sub_id: String,
+    /// Tickets whose input the model has seen.
+    answering: Vec<Waiter>,
+    /// Tickets whose input is queued behind the running turn, by pending
+    /// input id.
+    queued: Vec<(String, Waiter)>,
+    items: Vec<TurnItem>,
+    usage: TokenUsage,
+    error: Option<TurnError>,
+}
+
+impl RunningTurn {
+    /// Resolve the tickets whose input the model has seen with what happened
+    /// since the previous answer.
+    fn answer(&mut self, final_message: Option<String>, error: Option<TurnError>) {
+        let result = TurnResult {
+            final_message,
+            items: std::mem::take(&mut self.items),
+            usage: std::mem::take(&mut self.usage),
+            error: error.or(self.error.take()),
+        };
+        for waiter in self.answering.drain(..) {
+            let _ = waiter.tx.send(result.clone());
+        }
+    }
+}
+
+impl TurnResults {
+    /// Register a ticket for the submission `sub_id`, before it is sent.
+    pub(crate) fn track(&self, sub_id: String) -> TurnTicket {
+        let (tx, result) = oneshot::channel();
+        self.lock()
+            .waiting
+            .insert(sub_id.clone(), Waiter { tx, error: None });
+        TurnTicket {
+            submission_id: sub_id,
+            result,
+        }
+    }
+
+    /// A turn is starting for the submission `sub_id`.
+    pub(crate) fn start(&self, sub_id: &str) {
+        let mut state = self.lock();
+        if let Some(mut previous) = state.turn.take() {
+            finish(
+                &mut previous,
+                None,
+                Some(TurnError::Aborted(TurnAbortReason::Replaced)),
+            );
+        }
+        let answering = state.waiting.remove(sub_id).into_iter().collect();
+        state.turn = Some(RunningTurn {
+            sub_id: sub_id.to_string(),
+            answering,
+            ..Default::default()
+        });
+    }
+
+    /// The input of `sub_id` was queued behind the running turn as the
+    /// pending input `pending_id`.
+    pub(crate) fn queue(&self, sub_id: &str, pending_id: String) {
+        let mut state = self.lock();
+        let Some(waiter) = state.waiting.remove(sub_id) else {
+            return;
+        };
+        match state.turn.as_mut() {
+            Some(turn) => turn.queued.push((pending_id, waiter)),
+            None => {
+                let _ = waiter.tx.send(not_started(waiter.error));
+            }
+        }
+    }
+
+    /// The pending inputs `pending_ids` were added to the history.
+    pub(crate) fn consumed(&self, pending_ids: &[String]) {
+        let mut state = self.lock();
+        let Some(turn) = state.turn.as_mut() else {
+            return;
+        };
+        let (consumed, queued) = std::mem::take(&mut turn.queued)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(id, _)| pending_ids.contains(id));
+        turn.queued = queued;
+        turn.answering
+            .extend(consumed.into_iter().map(|(_, waiter)| waiter));
+    }
+
+    /// The model answered with `message` and the turn goes on with queued
+    /// input.
+    pub(crate) fn answered(&self, sub_id: &str, message: Option<String>) {
+        let mut state = self.lock();
+        if let Some(turn) = state.turn.as_mut()
+            && turn.sub_id == sub_id
+        {
+            turn.answer(message, None);
+        }
+    }
+
+    /// Account for `msg`, sent for the submission `sub_id`.
+    pub(crate) fn observe(&self, sub_id: &str, msg: &EventMsg) {
+        let mut state = self.lock();
+        if let Some(turn) = state.turn.as_mut()
+            && turn.sub_id == sub_id
+        {
+            let ended = match msg {
+                EventMsg::ItemCompleted(event) => {
+                    turn.items.push(event.item.clone());
+                    false
+                }
+                EventMsg::TokenCount(event) => {
+                    if let Some(info) = &event.info {
+                        add_usage(&mut turn.usage, &info.last_token_usage);
+                    }
+                    false
+                }
+                EventMsg::Error(event) => {
+                    turn.error = Some(TurnError::Failed(event.message.clone()));
+                    false
+                }
+                EventMsg::TurnRejected(event) => {
+                    turn.error = Some(TurnError::Rejected(event.reason.clone()));
+                    false
+                }
+                EventMsg::TurnDeadlineExceeded(_) => {
+                    turn.error = Some(TurnError::DeadlineExceeded);
+                    false
+                }
+                EventMsg::TaskComplete(event) => {
+                    finish(turn, event.last_agent_message.clone(), None);
+                    true
+                }
+                EventMsg::TurnAborted(event) => {
+                    finish(turn, None, Some(TurnError::Aborted(event.reason.clone())));
+                    true
+                }
+                _ => false,
+            };
+            if ended {
+                state.turn = None;
+            }
+            return;
+        }
+        if let Some(waiter) = state.waiting.get_mut(sub_id) {
+            match msg {
+                EventMsg::InputRejected(event) => {
+                    waiter.error = Some(TurnError::Rejected(event.reason.clone()));
+                }
+                EventMsg::Error(event) => {
+                    waiter.error = Some(TurnError::Failed(event.message.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The submission `sub_id` was handled; resolve its ticket if it neither
+    /// started nor joined a turn.
+    pub(crate) fn handled(&self, sub_id: &str) {
+        if let Some(waiter) = self.lock().waiting.remove(sub_id) {
+            let _ = waiter.tx.send(not_started(waiter.error));
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Resolve every ticket of the ending `turn`, including those whose input
+/// the model never saw.
+fn finish(turn: &mut RunningTurn, final_message: Option<String>, error: Option<TurnError>) {
+    let queued = std::mem::take(&mut turn.queued);
+    turn.answering
+        .extend(queued.into_iter().map(|(_, waiter)| waiter));
+    turn.answer(final_message, error);
+}
+
+fn not_started(error: Option<TurnError>) -> TurnResult {
+    TurnResult {
+        error: Some(error.unwrap_or(TurnError::NotStarted)),
+        ..Default::default()
+    }
+}
+
+fn add_usage(total: &mut TokenUsage, usage: &TokenUsage) {
+    total.input_tokens += usage.input_tokens;
+    total.cached_input_tokens += usage.cached_input_tokens;
+    total.output_tokens += usage.output_tokens;
+    total.reasoning_output_tokens += usage.reasoning_output_tokens;
+    total.total_tokens += usage.total_tokens;
+}
+
+#[cfg(test)]
+mod tests {
+    use codex_protocol::protocol::TaskCompleteEvent;
+    use codex_protocol::protocol::TurnAbortedEvent;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn complete(message: &str) -> EventMsg {
+        EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: Some(message.to_string()),
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn queued_input_is_answered_after_the_input_before_it() {
+        let results = TurnResults::default();
+        let first = results.track("1".to_string());
+        let second = results.track("2".to_string());
+        let late = results.track("3".to_string());
+
+        results.start("1");
+        results.queue("2", "pending-2".to_string());
+        results.answered("1", Some("first answer".to_string()));
+        results.consumed(&["pending-2".to_string()]);
+        results.queue("3", "pending-3".to_string());
+        results.observe("1", &complete("second answer"));
+
+        let final_message = |result: Option<TurnResult>| result.and_then(|r| r.final_message);
+        assert_eq!(
+            final_message(first.wait().await),
+            Some("first answer".to_string())
+        );
+        assert_eq!(
+            final_message(second.wait().await),
+            Some("second answer".to_string())
+        );
+        // The turn ended before the model saw the late input.
+        assert_eq!(
+            final_message(late.wait().await),
+            Some("second answer".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn aborted_and_unstarted_submissions_resolve_with_an_error() {
+        let results = TurnResults::default();
+        let aborted = results.track("1".to_string());
+        let rejected = results.track("2".to_string());
+
+        results.start("1");
+        results.observe(
+            "1",
+            &EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::Interrupted,
+                message_count: 0,
+                tool_call_count: 0,
+            }),
+        );
+        results.observe(
+            "2",
+            &EventMsg::Error(codex_protocol::protocol::ErrorEvent {
+                message: "bad cwd".to_string(),
+                codex_error_info: None,
+            }),
+        );
+        results.handled("2");
+
+        assert_eq!(
+            aborted.wait().await.and_then(|r| r.error),
+            Some(TurnError::Aborted(TurnAbortReason::Interrupted))
+        );
+        assert_eq!(
+            rejected.wait().await.and_then(|r| r.error),
+            Some(TurnError::Failed("bad cwd".to_string()))
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! What a turn recorded, reported when it ends.
+//!
+//! The items a turn records are classified with [`classify_item`], as
+//! [`crate::CodexConversation::transcript_turns`] readers are told to, so
+//! the counts in `TaskComplete` agree with the turn's transcript. The final
+//! message is the last assistant message the turn recorded, so it is the
+//! text after post-processing.
+//!
+//! User turns also tally the skills that were in context and those they
+//! invoked, reported as [`SkillUsageEvent`]. With an adaptive effort policy
+//! the items are kept too, for [`crate::adaptive_effort`] to check.
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::SkillUsageEvent;
+
+use crate::classify::ItemClass;
+use crate::classify::classify_item;
+use crate::stream_events_utils::last_assistant_message_from_item;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TurnTally {
+    pub(crate) final_message: Option<String>,
+    pub(crate) message_count: u32,
+    pub(crate) tool_call_count: u32,
+    /// Why the turn ended without a final message.
+    pub(crate) error: Option<String>,
+    /// Skill usage of a user turn with skills loaded.
+    pub(crate) skill_usage: Option<SkillUsageEvent>,
+    /// The items recorded, when they are kept.
+    pub(crate) items: Option<Vec<ResponseItem>>,
+}
+
+impl TurnTally {
+    /// Account for `items` as the turn records them.
+    pub(crate) fn observe(&mut self, items: &[ResponseItem]) {
+        if let Some(kept) = &mut self.items {
+            kept.extend_from_slice(items);
+        }
+        for item in items {
+            match classify_item(item) {
+                ItemClass::AssistantMessage => {
+                    self.message_count = self.message_count.saturating_add(1);
+                    if let Some(text) = last_assistant_message_from_item(item) {
+                        self.final_message = Some(text);
+                    }
+                }
+                ItemClass::ToolCall => {
+                    self.tool_call_count = self.tool_call_count.saturating_add(1);
+                }
+                ItemClass::UserMessage
+                | ItemClass::Reasoning
+                | ItemClass::ToolOutput
+                | ItemClass::SessionPrefix
+                | ItemClass::Other => {}
+            }
+        }
+    }
+
+    /// Account for the turn invoking the skill `name`. A skill the turn
+    /// brought into context, such as one the user mentioned, becomes
+    /// available too.
+    pub(crate) fn skill_invoked(&mut self, name: &str) {
+        let Some(usage) = &mut self.skill_usage else {
+            return;
+        };
+        if !usage.available.iter().any(|available| available == name) {
+            usage.available.push(name.to_string());
+        }
+        if !usage.invoked.iter().any(|invoked| invoked == name) {
+            usage.invoked.push(name.to_string());
+        }
+    }
+
+    /// The skills the turn invoked, for `TaskComplete`.
+    pub(crate) fn invoked_skills(&self) -> Vec<String> {
+        self.skill_usage
+            .as_ref()
+            .map(|usage| usage.invoked.clone())
+            .unwrap_or_default()
+    }
+
+    /// The final message to report: none once the turn failed.
+    pub(crate) fn final_message(&self) -> Option<String> {
+        if self.error.is_some() {
+            None
+        } else {
+            self.final_message.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        let text = text.to_string();
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![if role == "assistant" {
+                ContentItem::OutputText { text }
+            } else {
+                ContentItem::InputText { text }
+            }],
+        }
+    }
+
+    fn call(call_id: &str) -> [ResponseItem; 2] {
+        [
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload::default(),
+            },
+        ]
+    }
+
+    #[test]
+    fn counts_messages_and_calls_and_keeps_the_last_answer() {
+        let mut tally = TurnTally::default();
+        tally.observe(&[message("user", "fix it"), message("assistant", "looking")]);
+        tally.observe(&call("call-1"));
+        tally.observe(&call("call-2"));
+        tally.observe(&[message("assistant", "fixed")]);
+
+        assert_eq!(
+            tally,
+            TurnTally {
+                final_message: Some("fixed".to_string()),
+                message_count: 2,
+                tool_call_count: 2,
+                error: None,
+                skill_usage: None,
+                items: None,
+            }
+        );
+        assert_eq!(tally.final_message(), Some("fixed".to_string()));
+
+        tally.error = Some("stream disconnected".to_string());
+        assert_eq!(tally.final_message(), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Scratch directory of a turn, exported to the commands the turn runs as
+//! [`TURN_TMPDIR_ENV_VAR`] so they have somewhere to write other than the
+//! workspace.
+//!
+//! The directory is created by the first command that needs it and removed
+//! when the turn ends, unless `keep_turn_tmpdirs` is set. Under
+//! [`ToolCallIsolation::PerCallTmp`] each tool call gets a directory of its
+//! own inside it instead, see [`TurnTmpdir::for_call`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::config::types::ToolCallIsolation;
+use crate::config::types::TurnTmpdirLocation;
+
+pub(crate) const TURN_TMPDIR_ENV_VAR: &str = "CODEX_TURN_TMPDIR";
+
+/// Directory under the configured location that holds the turn directories.
+const TURN_TMPDIRS_DIR: &str = "codex-turns";
+
+/// Directory of a turn directory that holds the directories of its calls.
+const CALL_TMPDIRS_DIR: &str = "calls";
+
+#[derive(Debug, Clone)]
+pub(crate) struct TurnTmpdir {
+    path: PathBuf,
+    keep: bool,
+    isolation: ToolCallIsolation,
+}
+
+impl TurnTmpdir {
+    pub(crate) fn new(config: &Config) -> Self {
+        let root = match config.turn_tmpdir_location {
+            TurnTmpdirLocation::System => std::env::temp_dir(),
+            TurnTmpdirLocation::CodexHome => config.codex_home.join("tmp"),
+        };
+        Self {
+            path: root.join(TURN_TMPDIRS_DIR).join(Uuid::new_v4().to_string()),
+            keep: config.keep_turn_tmpdirs,
+            isolation: config.parallel_tool_calls.isolation,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The scratch directory of tool call `call_id`: the turn's own, or a
+    /// directory of the call's inside it under
+    /// [`ToolCallIsolation::PerCallTmp`]. It goes away with the turn's.
+    pub(crate) fn for_call(&self, call_id: &str) -> Self {
+        match self.isolation {
+            ToolCallIsolation::SharedCwd => self.clone(),
+            ToolCallIsolation::PerCallTmp => {
+                let name: String = call_id
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                Self {
+                    path: self.path.join(CALL_TMPDIRS_DIR).join(name),
+                    ..self.clone()
+                }
+            }
+        }
+    }
+
+    /// Create the directory if needed and point [`TURN_TMPDIR_ENV_VAR`] at
+    /// it. The variable is left unset when the directory cannot be created.
+    pub(crate) fn export(&self, env: &mut HashMap<String, String>) {
+        match fs::create_dir_all(&self.path) {
+            Ok(()) => {
+                env.insert(
+                    TURN_TMPDIR_ENV_VAR.to_string(),
+                    self.path.to_string_lossy().into_owned(),
+                );
+            }
+            Err(err) => warn!(
+                "failed to create turn directory {}: {err}",
+                self.path.display()
+            ),
+        }
+    }
+
+    /// Remove the directory at the end of the turn, unless it is kept for
+    /// debugging.
+    pub(crate) async fn cleanup(&self) {
+        if self.keep {
+            return;
+        }
+        let path = self.path.clone();
+        let removed = tokio::task::spawn_blocking(move || remove_tree(&path)).await;
+        match removed {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!(
+                "failed to remove turn directory {}: {err}",
+                self.path.display()
+            ),
+            Err(err) => warn!("turn directory cleanup panicked: {err}"),
+        }
+    }
+}
+
+/// Remove `path` and everything under it. [`fs::remove_dir_all`] walks the
+/// tree from open directory handles, so paths longer than the platform limit
+/// are removed too. Entries that cannot be removed because they, or the
+/// directory holding them, are read-only are made writable and removed on a
+/// second pass. A missing `path` is not an error.
+fn remove_tree(path: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(path) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(_) => {}
+    }
+    make_writable(path)?;
+    fs::remove_dir_all(path)
+}
+
+/// Give the owner write access to `root` and every entry under it, and
+/// read and search access to directories, without following links.
+fn make_writable(root: &Path) -> io::Result<()> {
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.file_type().is_symlink() {
+            continue;
+        }
+        let mut permissions = metadata.permissions();
+        #[cfg(unix)]
+        let changed = {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = permissions.mode();
+            let wanted = if metadata.is_dir() {
+                mode | 0o700
+            } else {
+                mode | 0o200
+            };
+            permissions.set_mode(wanted);
+            wanted != mode
+        };
+        #[cfg(not(unix))]
+        let changed = {
+            let readonly = permissions.readonly();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            readonly
+        };
+        if changed {
+            fs::set_permissions(&path, permissions)?;
+        }
+        if metadata.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                pending.push(entry?.path());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_tree_handles_read_only_entries() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().join("turn");
+        let nested = root.join("nested");
+        fs::create_dir_all(&nested).expect("mkdir");
+        let file = nested.join("locked.txt");
+        fs::write(&file, "scratch").expect("write");
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o400)).expect("chmod file");
+        fs::set_permissions(&nested, fs::Permissions::from_mode(0o500)).expect("chmod dir");
+
+        remove_tree(&root).expect("remove");
+
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn per_call_isolation_gives_each_call_its_own_directory() {
+        let turn = |isolation| TurnTmpdir {
+            path: PathBuf::from("/tmp/codex-turns/turn"),
+            keep: false,
+            isolation,
+        };
+
+        let shared = turn(ToolCallIsolation::SharedCwd);
+        assert_eq!(shared.for_call("call-1").path(), shared.path());
+
+        let isolated = turn(ToolCallIsolation::PerCallTmp);
+        let first = isolated.for_call("call-1");
+        let second = isolated.for_call("../call 2");
+        assert_ne!(first.path(), second.path());
+        for call in [&first, &second] {
+            assert_eq!(
+                call.path().parent(),
+                Some(isolated.path().join(CALL_TMPDIRS_DIR).as_path())
+            );
+        }
+    }
+
+    #[test]
+    fn remove_tree_ignores_missing_directories() {
+        let dir = TempDir::new().expect("tempdir");
+
+        remove_tree(&dir.path().join("never-created")).expect("remove");
+    }
+}
@@ -0,0 +1,230 @@
+//! Time zone, locale and current time of the user, told to the model.
+//!
+//! With [`Config::user_context`](crate::config::Config::user_context) set, the
+//! session prefix carries a `<user_context>` item. Each turn that starts in a
+//! later minute than the one last reported records a fresh item, so the
+//! model's idea of "now" stays current without rewriting the prefix.
+
+use std::sync::Mutex;
+
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+use tokio::time::Instant;
+
+use crate::config::types::UserContext;
+use crate::runtime_env::RuntimeEnv;
+
+pub(crate) const USER_CONTEXT_OPEN_TAG: &str = "<user_context>";
+const USER_CONTEXT_CLOSE_TAG: &str = "</user_context>";
+
+/// A session's [`UserContext`] and the clock its current time is read from.
+#[derive(Debug)]
+pub(crate) struct UserContextClock {
+    context: UserContext,
+    /// Time reported at `started`.
+    base: DateTime<Utc>,
+    started: Instant,
+    /// Current time as last reported to the model.
+    last_reported: Mutex<Option<String>>,
+}
+
+impl UserContextClock {
+    /// Report the time of `env`, or the override of `context`, from now on.
+    pub(crate) fn new(context: UserContext, env: &RuntimeEnv) -> Self {
+        let base = context
+            .now_override
+            .unwrap_or_else(|| DateTime::<Utc>::from(env.now()));
+        Self {
+            context,
+            base,
+            started: Instant::now(),
+            last_reported: Mutex::new(None),
+        }
+    }
+
+    /// The item for the session prefix.
+    pub(crate) fn prefix_item(&self) -> ResponseItem {
+        let now = self.current_time();
+        self.remember(&now);
+        self.item(now)
+    }
+
+    /// An item reporting the current time, unless the model already knows
+    /// it.
+    pub(crate) fn refresh_item(&self) -> Option<ResponseItem> {
+        let now = self.current_time();
+        if self.last_reported() == Some(now.clone()) {
+            return None;
+        }
+        self.remember(&now);
+        Some(self.item(now))
+    }
+
+    /// The current time to the minute, as told to the model.
+    fn current_time(&self) -> String {
+        let elapsed = TimeDelta::from_std(self.started.elapsed()).unwrap_or(TimeDelta::MAX);
+        let now = self
+            .base
+            .checked_add_signed(elapsed)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        now.format("%Y-%m-%d %H:%M UTC").to_string()
+    }
+
+    fn last_reported(&self) -> Option<String> {
+        match self.last_reported.lock() {
+            Ok(last_reported) => last_reported.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn remember(&self, now: &str) {
+        let mut last_reported = match self.last_reported.lock() {
+            Ok(last_reported) => last_reported,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *last_reported = Some(now.to_string());
+    }
+
+    fn item(&self, now: String) -> ResponseItem {
+        let mut lines = vec![USER_CONTEXT_OPEN_TAG.to_string()];
+        if let Some(timezone) = &self.context.timezone {
+            lines.push(format!("  <timezone>{timezone}</timezone>"));
+        }
+        if let Some(locale) = &self.context.locale {
+            lines.push(format!("  <locale>{locale}</locale>"));
+        }
+        lines.push(format!("  <current_time>{now}</current_time>"));
+        lines.push(USER_CONTEXT_CLOSE_TAG.to_string());
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: lines.join("\n"),
+            }],
+        }
+    }
+}
+
+/// The user context reported last in `items`, so resumed and forked
+/// conversations keep telling the model about it. The time zone and locale
+/// are recovered; the time is read from the clock again.
+pub(crate) fn recorded_user_context(items: &[RolloutItem]) -> Option<UserContext> {
+    items.iter().rev().find_map(|item| {
+        let RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. }) = item else {
+            return None;
+        };
+        if role != "user" {
+            return None;
+        }
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            return None;
+        };
+        if !text.starts_with(USER_CONTEXT_OPEN_TAG) {
+            return None;
+        }
+        Some(UserContext {
+            timezone: tag_value(text, "timezone"),
+            locale: tag_value(text, "locale"),
+            now_override: None,
+        })
+    })
+}
+
+fn tag_value(text: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = text.find(&open)? + open.len();
+    let len = text[start..].find(&close)?;
+    Some(text[start..start + len].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::codex::make_session_and_context;
+
+    fn context() -> UserContext {
+        UserContext {
+            timezone: Some("Europe/Berlin".to_string()),
+            locale: Some("de-DE".to_string()),
+            now_override: Some("2025-03-01T09:30:15Z".parse().unwrap()),
+        }
+    }
+
+    fn text(item: &ResponseItem) -> &str {
+        let ResponseItem::Message { content, .. } = item else {
+            panic!("expected a message, got {item:?}");
+        };
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            panic!("expected one text, got {content:?}");
+        };
+        text
+    }
+
+    #[tokio::test]
+    async fn initial_context_carries_the_user_context() {
+        let (mut session, turn_context) = make_session_and_context().await;
+        session.services.user_context =
+            Some(UserContextClock::new(context(), &RuntimeEnv::default()));
+
+        let items = session.build_initial_context(&turn_context);
+
+        assert_eq!(
+            items.last().map(text),
+            Some(
+                "<user_context>\n  <timezone>Europe/Berlin</timezone>\n  <locale>de-DE</locale>\n  <current_time>2025-03-01 09:30 UTC</current_time>\n</user_context>"
+            )
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refresh_reports_the_time_once_it_changed() {
+        let clock = UserContextClock::new(context(), &RuntimeEnv::default());
+        clock.prefix_item();
+
+        // A turn in the same minute adds nothing.
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(clock.refresh_item(), None);
+
+        tokio::time::advance(Duration::from_secs(90)).await;
+        let refreshed = clock.refresh_item().expect("the minute changed");
+        assert!(
+            text(&refreshed).contains("<current_time>2025-03-01 09:32 UTC</current_time>"),
+            "{refreshed:?}"
+        );
+        assert_eq!(clock.refresh_item(), None);
+    }
+
+    #[test]
+    fn recorded_user_context_recovers_timezone_and_locale() {
+        let clock = UserContextClock::new(context(), &RuntimeEnv::default());
+        let items = [
+            RolloutItem::ResponseItem(clock.prefix_item()),
+            RolloutItem::ResponseItem(ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "what time is it?".to_string(),
+                }],
+            }),
+        ];
+
+        assert_eq!(
+            recorded_user_context(&items),
+            Some(UserContext {
+                timezone: Some("Europe/Berlin".to_string()),
+                locale: Some("de-DE".to_string()),
+                now_override: None,
+            })
+        );
+        assert_eq!(recorded_user_context(&items[1..]), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! Detects changes made to the workspace outside codex between turns, such as
+//! switching git branches underneath a session, so the model does not keep
+//! reasoning about stale file contents.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::WorkspaceChangedEvent;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::git_info::run_git_command_with_timeout;
+
+pub(crate) const WORKSPACE_CHANGED_OPEN_TAG: &str = "<workspace_changed>";
+const WORKSPACE_CHANGED_CLOSE_TAG: &str = "</workspace_changed>";
+
+/// Changed files listed by name in the notice; the rest are only counted.
+const MAX_LISTED_FILES: usize = 20;
+
+/// State of the workspace at a turn boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WorkspaceSnapshot {
+    cwd: PathBuf,
+    branch: Option<String>,
+    head: Option<String>,
+    /// Content hash of every file with uncommitted changes, keyed by path
+    /// relative to the repository root.
+    dirty_files: BTreeMap<String, String>,
+}
+
+impl WorkspaceSnapshot {
+    pub(crate) async fn capture(cwd: &Path) -> Self {
+        let mut snapshot = Self {
+            cwd: cwd.to_path_buf(),
+            branch: None,
+            head: None,
+            dirty_files: BTreeMap::new(),
+        };
+        let Some(root) = git_stdout(&["rev-parse", "--show-toplevel"], cwd).await else {
+            return snapshot;
+        };
+        let root = PathBuf::from(root.trim());
+
+        snapshot.head = git_stdout(&["rev-parse", "HEAD"], cwd)
+            .await
+            .map(|head| head.trim().to_string());
+        snapshot.branch = git_stdout(&["branch", "--show-current"], cwd)
+            .await
+            .map(|branch| branch.trim().to_string())
+            .filter(|branch| !branch.is_empty());
+        if let Some(status) = git_stdout(
+            &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+            cwd,
+        )
+        .await
+        {
+            for path in parse_porcelain_paths(&status) {
+                let hash = hash_file(&root.join(&path)).await;
+                snapshot.dirty_files.insert(path, hash);
+            }
+        }
+        snapshot
+    }
+
+    /// Describe how the workspace changed from `self` to `current`, or `None`
+    /// when nothing did.
+    pub(crate) async fn changes_since(
+        &self,
+        current: &WorkspaceSnapshot,
+    ) -> Option<WorkspaceChangedEvent> {
+        if self == current {
+            return None;
+        }
+
+        let mut changed_files = BTreeSet::new();
+        if let (Some(previous_head), Some(head)) = (&self.head, &current.head)
+            && previous_head != head
+            && let Some(diff) = git_stdout(
+                &["diff", "--name-only", "-z", previous_head, head],
+                &current.cwd,
+            )
+            .await
+        {
+            changed_files.extend(
+                diff.split('\0')
+                    .filter(|path| !path.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        for path in self.dirty_files.keys().chain(current.dirty_files.keys()) {
+            if self.dirty_files.get(path) != current.dirty_files.get(path) {
+                changed_files.insert(path.clone());
+            }
+        }
+
+        let mut event = WorkspaceChangedEvent {
+            message: String::new(),
+            previous_cwd: self.cwd.clone(),
+            cwd: current.cwd.clone(),
+            previous_branch: self.branch.clone(),
+            branch: current.branch.clone(),
+            previous_head: self.head.clone(),
+            head: current.head.clone(),
+            changed_files: changed_files.into_iter().collect(),
+        };
+        event.message = summarize(&event);
+        Some(event)
+    }
+}
+
+/// Transcript item telling the model about `event`. It is recorded with the
+/// user role, like the environment context, but is not a user message.
+pub(crate) fn workspace_changed_notice(event: &WorkspaceChangedEvent) -> ResponseItem {
+    let mut lines = vec![
+        WORKSPACE_CHANGED_OPEN_TAG.to_string(),
+        event.message.clone(),
+    ];
+    if !event.changed_files.is_empty() {
+        lines.push("Changed files:".to_string());
+        for path in event.changed_files.iter().take(MAX_LISTED_FILES) {
+            lines.push(format!("- {path}"));
+        }
+        let unlisted = event.changed_files.len().saturating_sub(MAX_LISTED_FILES);
+        if unlisted > 0 {
+            lines.push(format!("- ... and {unlisted} more"));
+        }
+    }
+    lines.push(
+        "These changes were made outside this conversation; re-read files before relying on earlier observations of them."
+            .to_string(),
+    );
+    lines.push(WORKSPACE_CHANGED_CLOSE_TAG.to_string());
+
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: lines.join("\n"),
+        }],
+    }
+}
+
+fn summarize(event: &WorkspaceChangedEvent) -> String {
+    let mut parts = Vec::new();
+    if event.previous_cwd != event.cwd {
+        parts.push(format!(
+            "cwd {} → {}",
+            event.previous_cwd.display(),
+            event.cwd.display()
+        ));
+    }
+    if event.previous_branch != event.branch {
+        let name =
+            |branch: &Option<String>| branch.clone().unwrap_or_else(|| "(detached)".to_string());
+        parts.push(format!(
+            "branch {} → {}",
+            name(&event.previous_branch),
+            name(&event.branch)
+        ));
+    } else if event.previous_head != event.head {
+        let short = |head: &Option<String>| {
+            head.as_deref()
+                .map(|head| head.chars().take(7).collect::<String>())
+                .unwrap_or_else(|| "(none)".to_string())
+        };
+        parts.push(format!(
+            "HEAD {} → {}",
+            short(&event.previous_head),
+            short(&event.head)
+        ));
+    }
+    match event.changed_files.len() {
+        0 => {}
+        1 => parts.push("1 file modified".to_string()),
+        count => parts.push(format!("{count} files modified")),
+    }
+    if parts.is_empty() {
+        parts.push("files modified".to_string());
+    }
+    format!("workspace changed: {}", parts.join(", "))
+}
+
+async fn git_stdout(args: &[&str], cwd: &Path) -> Option<String> {
+    let output = run_git_command_with_timeout(args, cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Paths from `git status --porcelain=v1 -z`. Renames and copies list both
+/// the new and the original path.
+fn parse_porcelain_paths(status: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        let (Some(code), Some(path)) = (entry.get(..2), entry.get(3..)) else {
+            continue;
+        };
+        paths.push(path.to_string());
+        if (code.starts_with('R') || code.starts_with('C'))
+            && let Some(original) = entries.next()
+        {
+            paths.push(original.to_string());
+        }
+    }
+    paths
+}
+
+async fn hash_file(path: &Path) -> String {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => format!("{:x}", Sha256::digest(&bytes)),
+        Err(_) => "missing".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn snapshot(branch: &str, head: &str) -> WorkspaceSnapshot {
+        WorkspaceSnapshot {
+            cwd: PathBuf::from("/repo"),
+            branch: Some(branch.to_string()),
+            head: Some(head.to_string()),
+            dirty_files: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn parses_porcelain_entries_including_renames() {
+        assert_eq!(
+            parse_porcelain_paths(" M src/lib.rs\0R  new.rs\0old.rs\0?? notes.txt\0"),
+            vec!["src/lib.rs", "new.rs", "old.rs", "notes.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn unchanged_workspace_reports_nothing() {
+        let previous = snapshot("main", "abc");
+        assert!(previous.changes_since(&previous.clone()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn dirty_file_changes_are_summarized() {
+        let previous = snapshot("main", "abc");
+        let mut current = previous.clone();
+        current.branch = Some("dev".to_string());
+        current
+            .dirty_files
+            .insert("a.rs".to_string(), "hash".to_string());
+
+        let event = previous
+            .changes_since(&current)
+            .await
+            .expect("workspace changed");
+        assert_eq!(
+            event.message,
+            "workspace changed: branch main → dev, 1 file modified"
+        );
+        assert_eq!(event.changed_files, vec!["a.rs".to_string()]);
+    }
+}
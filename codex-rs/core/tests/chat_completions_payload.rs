@@ -58,6 +58,9 @@ async fn run_request(input: Vec<ResponseItem>) -> Value {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
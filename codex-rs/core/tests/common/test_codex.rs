@@ -164,7 +164,7 @@ impl TestCodexBuilder {
             config,
             codex: new_conversation.conversation,
             session_configured: new_conversation.session_configured,
-            conversation_manager: Arc::new(conversation_manager),
+            conversation_manager,
         })
     }
 
@@ -210,7 +210,7 @@ pub struct TestCodex {
     pub codex: Arc<CodexConversation>,
     pub session_configured: SessionConfiguredEvent,
     pub config: Config,
-    pub conversation_manager: Arc<ConversationManager>,
+    pub conversation_manager: ConversationManager,
 }
 
 impl TestCodex {
@@ -250,27 +250,76 @@ impl TestCodex {
         approval_policy: AskForApproval,
         sandbox_policy: SandboxPolicy,
     ) -> Result<()> {
-        let session_model = self.session_configured.model.clone();
-        self.codex
-            .submit(Op::UserTurn {
-                items: vec![UserInput::Text {
-                    text: prompt.into(),
-                }],
-                final_output_json_schema: None,
-                cwd: self.cwd.path().to_path_buf(),
-                approval_policy,
-                sandbox_policy,
-                model: session_model,
-                effort: None,
-                summary: ReasoningSummary::Auto,
-            })
-            .await?;
-
-        wait_for_event(&self.codex, |event| {
+        let op = self.user_turn(prompt, approval_policy, sandbox_policy);
+        Self::submit_and_wait(&self.codex, op).await?;
+        Ok(())
+    }
+
+    /// [`Self::submit_turn`], returning the events of the turn up to and
+    /// including `TaskComplete`.
+    pub async fn submit_turn_events(&self, prompt: &str) -> Result<Vec<EventMsg>> {
+        let op = self.user_turn(
+            prompt,
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+        );
+        Self::submit_and_wait(&self.codex, op).await
+    }
+
+    /// Submit `prompt` as [`Op::UserInput`], which keeps the settings the
+    /// session runs with, and return the events of the turn up to and
+    /// including `TaskComplete`.
+    pub async fn submit_input(&self, prompt: &str) -> Result<Vec<EventMsg>> {
+        Self::submit_input_to(&self.codex, prompt).await
+    }
+
+    /// [`Self::submit_input`] for another conversation, such as one started
+    /// or resumed through the conversation manager.
+    pub async fn submit_input_to(
+        conversation: &CodexConversation,
+        prompt: &str,
+    ) -> Result<Vec<EventMsg>> {
+        let op = Op::UserInput {
+            items: vec![UserInput::Text {
+                text: prompt.into(),
+            }],
+            allow_sensitive: false,
+        };
+        Self::submit_and_wait(conversation, op).await
+    }
+
+    fn user_turn(
+        &self,
+        prompt: &str,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+    ) -> Op {
+        Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: prompt.into(),
+            }],
+            final_output_json_schema: None,
+            cwd: self.cwd.path().to_path_buf(),
+            approval_policy,
+            sandbox_policy,
+            model: self.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        }
+    }
+
+    async fn submit_and_wait(conversation: &CodexConversation, op: Op) -> Result<Vec<EventMsg>> {
+        conversation.submit(op).await?;
+        let mut events = Vec::new();
+        wait_for_event(conversation, |event| {
+            events.push(event.clone());
             matches!(event, EventMsg::TaskComplete(_))
         })
         .await;
-        Ok(())
+        Ok(events)
     }
 }
 
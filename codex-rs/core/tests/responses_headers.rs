@@ -52,6 +52,9 @@ async fn responses_stream_includes_subagent_header_on_review() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -146,6 +149,9 @@ async fn responses_stream_includes_subagent_header_on_other() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -236,6 +242,9 @@ async fn responses_respects_model_family_overrides_from_config() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -49,6 +49,7 @@ async fn interrupt_long_running_tool_emits_turn_aborted() {
             items: vec![UserInput::Text {
                 text: "start sleep".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -101,6 +102,7 @@ async fn interrupt_tool_records_history_entries() {
             items: vec![UserInput::Text {
                 text: "start history recording".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -117,6 +119,7 @@ async fn interrupt_tool_records_history_entries() {
             items: vec![UserInput::Text {
                 text: "follow up".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::adaptive_effort::AdaptiveEffortPolicy;
+use codex_core::protocol::EffortDeescalatedEvent;
+use codex_core::protocol::EffortEscalatedEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::get_responses_requests;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(id),
+    ])
+}
+
+fn retry(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_shell_command_call(&format!("call-{id}"), "echo retry"),
+        ev_completed(id),
+    ])
+}
+
+/// Submit `prompt` and return the events of its turn.
+async fn turn(test: &TestCodex, prompt: &str) -> Result<Vec<EventMsg>> {
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: prompt.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let mut events = Vec::new();
+    loop {
+        let event =
+            tokio::time::timeout(Duration::from_secs(10), test.codex.next_event()).await??;
+        let done = matches!(event.msg, EventMsg::TaskComplete(_));
+        events.push(event.msg);
+        if done {
+            return Ok(events);
+        }
+    }
+}
+
+fn effort(body: &[u8]) -> Result<Option<String>> {
+    let body: Value = serde_json::from_slice(body)?;
+    Ok(body["reasoning"]["effort"].as_str().map(str::to_string))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn floundering_turns_escalate_the_effort_of_the_next_one() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            answer("resp-1", "I'm not sure what breaks the build."),
+            retry("resp-2"),
+            retry("resp-3"),
+            retry("resp-4"),
+            answer("resp-5", "Still failing, I'm not sure why."),
+            answer("resp-6", "Fixed: the feature flag was missing."),
+            answer("resp-7", "Done."),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.model_reasoning_effort = Some(ReasoningEffort::Low);
+            config.adaptive_effort = Some(AdaptiveEffortPolicy {
+                threshold: 2,
+                deescalate_after: 1,
+                ..AdaptiveEffortPolicy::default()
+            });
+        })
+        .build(&server)
+        .await?;
+
+    let mut escalations = Vec::new();
+    let mut deescalations = Vec::new();
+    for prompt in ["fix the build", "try again", "and now?", "thanks"] {
+        for event in turn(&test, prompt).await? {
+            match event {
+                EventMsg::EffortEscalated(event) => escalations.push(event),
+                EventMsg::EffortDeescalated(event) => deescalations.push(event),
+                _ => {}
+            }
+        }
+    }
+
+    // One unsure answer scores one point; the retry loop and the second
+    // unsure answer cross the threshold.
+    assert_eq!(
+        escalations,
+        vec![EffortEscalatedEvent {
+            from: ReasoningEffort::Low,
+            to: ReasoningEffort::Medium,
+            reason: "repeated `shell_command` calls, said it is unsure".to_string(),
+        }]
+    );
+    // The clean third turn brings it back down.
+    assert_eq!(
+        deescalations,
+        vec![EffortDeescalatedEvent {
+            from: ReasoningEffort::Medium,
+            to: ReasoningEffort::Low,
+        }]
+    );
+
+    let requests = get_responses_requests(&server).await;
+    let efforts = requests
+        .iter()
+        .map(|request| effort(&request.body))
+        .collect::<Result<Vec<_>>>()?;
+    let expected =
+        ["low", "low", "low", "low", "low", "medium", "low"].map(|effort| Some(effort.to_string()));
+    assert_eq!(efforts, expected);
+
+    Ok(())
+}
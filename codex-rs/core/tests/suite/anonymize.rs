@@ -0,0 +1,113 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::rollout::AnonymizeOptions;
+use codex_core::rollout::anonymize;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use regex_lite::Regex;
+use serde_json::Value;
+
+const EMAIL: &str = "reporter@example.com";
+
+fn reply(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), text),
+        ev_completed(id),
+    ])
+}
+
+fn kinds(rollout: &str) -> Result<Vec<String>> {
+    rollout
+        .lines()
+        .map(|line| {
+            let value: Value = serde_json::from_str(line)?;
+            Ok(format!("{}/{}", value["type"], value["payload"]["type"]))
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn anonymized_rollout_hides_identity_and_still_resumes() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The user name of the environment, or one passed explicitly where the
+    // environment has none.
+    let env_user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty());
+    let user = env_user
+        .clone()
+        .unwrap_or_else(|| "codex-reporter".to_string());
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let cwd = test.cwd_path().display().to_string();
+    mount_sse_once(&server, reply("resp-1", &format!("Looking in {cwd}/src"))).await;
+    test.submit_turn(&format!(
+        "I am {user}, mail me at {EMAIL} about {cwd}/Cargo.toml"
+    ))
+    .await?;
+
+    let source = test.session_configured.rollout_path.clone();
+    let original = std::fs::read_to_string(&source)?;
+    assert!(original.contains(&cwd) && original.contains(EMAIL));
+
+    let dest = test.home.path().join("anonymized.jsonl");
+    let report = anonymize(
+        &source,
+        &dest,
+        AnonymizeOptions {
+            names: env_user
+                .is_none()
+                .then(|| user.clone())
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let anonymized = std::fs::read_to_string(&dest)?;
+    let whole_word = Regex::new(&format!(r"\b{}\b", regex_lite::escape(&user)))?;
+    assert!(!whole_word.is_match(&anonymized), "{anonymized}");
+    assert!(!anonymized.contains(&cwd), "{anonymized}");
+    assert!(!anonymized.contains(EMAIL), "{anonymized}");
+    assert!(anonymized.contains("I am <USER>, mail me at <EMAIL-1> about <REPO>/Cargo.toml"));
+    // The shape of the session is kept.
+    assert_eq!(kinds(&anonymized)?, kinds(&original)?);
+
+    // The manifest maps the placeholders back, separately from the copy.
+    let manifest: Value = serde_json::from_str(&std::fs::read_to_string(&report.manifest_path)?)?;
+    assert_eq!(manifest["<REPO>"], Value::String(cwd.clone()));
+    assert_eq!(manifest["<EMAIL-1>"], EMAIL);
+    assert_eq!(manifest["<USER>"], Value::String(user));
+
+    let resumed = test_codex()
+        .resume(&server, test.home.clone(), dest)
+        .await?;
+    wait_for_event(&resumed.codex, |event| {
+        matches!(event, EventMsg::Warning(warning) if warning.message.starts_with("This session was anonymized"))
+    })
+    .await;
+    let follow_up = mount_sse_once(&server, reply("resp-2", "still here")).await;
+    resumed.submit_turn("what did I ask?").await?;
+    let texts = follow_up.single_request().message_input_texts("user");
+    assert!(
+        texts
+            .iter()
+            .any(|text| text.starts_with("I am <USER>, mail me at <EMAIL-1>")),
+        "{texts:?}"
+    );
+
+    Ok(())
+}
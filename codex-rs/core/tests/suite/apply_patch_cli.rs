@@ -310,6 +310,9 @@ async fn apply_patch_cli_move_without_content_change_has_no_turn_diff(
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -896,6 +899,9 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -973,6 +979,9 @@ async fn apply_patch_shell_command_failure_propagates_error_and_skips_diff() ->
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -1120,6 +1129,9 @@ async fn apply_patch_emits_turn_diff_event_with_unified_diff(
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -1180,6 +1192,9 @@ async fn apply_patch_turn_diff_for_rename_with_content_change(
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -1248,6 +1263,9 @@ async fn apply_patch_aggregates_diff_across_multiple_tool_calls() -> Result<()>
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -1316,6 +1334,9 @@ async fn apply_patch_aggregates_diff_preserves_success_after_failure() -> Result
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
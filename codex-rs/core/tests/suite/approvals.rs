@@ -500,6 +500,9 @@ async fn submit_turn(
             model: session_model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -3,22 +3,45 @@ use anyhow::Result;
 use base64::Engine;
 use chrono::Duration;
 use chrono::Utc;
+use codex_core::AuthManager;
 use codex_core::CodexAuth;
+use codex_core::ModelProviderInfo;
 use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::AuthDotJson;
+use codex_core::auth::AuthNotification;
+use codex_core::auth::AutoRefreshPolicy;
 use codex_core::auth::REFRESH_TOKEN_URL_OVERRIDE_ENV_VAR;
 use codex_core::auth::RefreshTokenError;
+use codex_core::auth::TokenStatus;
 use codex_core::auth::load_auth_dot_json;
 use codex_core::auth::save_auth;
+use codex_core::built_in_model_providers;
 use codex_core::error::RefreshTokenFailedReason;
+use codex_core::fault_injection::FaultPlan;
+use codex_core::fault_injection::FaultyProvider;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RetrySubsystem;
+use codex_core::protocol::RetryingEvent;
+use codex_core::retry::RetryPolicy;
 use codex_core::token_data::IdTokenInfo;
 use codex_core::token_data::TokenData;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
 use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
 use pretty_assertions::assert_eq;
 use serde::Serialize;
 use serde_json::json;
 use std::ffi::OsString;
+use std::sync::Arc;
 use tempfile::TempDir;
+use tokio::sync::broadcast;
 use wiremock::Mock;
 use wiremock::MockServer;
 use wiremock::ResponseTemplate;
@@ -27,6 +50,11 @@ use wiremock::matchers::path;
 
 const INITIAL_ACCESS_TOKEN: &str = "initial-access-token";
 const INITIAL_REFRESH_TOKEN: &str = "initial-refresh-token";
+const TEST_POLICY: AutoRefreshPolicy = AutoRefreshPolicy {
+    margin: std::time::Duration::from_secs(10 * 60),
+    retry_delay: std::time::Duration::from_millis(10),
+    max_retry_delay: std::time::Duration::from_millis(40),
+};
 
 #[serial_test::serial(auth_refresh)]
 #[tokio::test]
@@ -161,6 +189,265 @@ async fn refresh_token_returns_transient_error_on_server_failure() -> Result<()>
     Ok(())
 }
 
+#[test]
+fn api_key_auth_is_not_refreshable() {
+    let manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("sk-test"));
+
+    assert_eq!(
+        manager.token_status(),
+        TokenStatus {
+            expires_at: None,
+            refreshable: false,
+        }
+    );
+}
+
+#[serial_test::serial(auth_refresh)]
+#[tokio::test]
+async fn auto_refresh_refreshes_tokens_before_they_expire() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    mount_refresh_success(&server).await;
+
+    let expires_at = Utc::now().timestamp() + 60;
+    let ctx = RefreshTokenTestContext::with_access_token(&server, expiring_jwt(expires_at))?;
+    let manager = ctx.auth_manager();
+    assert_eq!(
+        manager.token_status(),
+        TokenStatus {
+            expires_at: chrono::DateTime::from_timestamp(expires_at, 0),
+            refreshable: true,
+        }
+    );
+
+    let mut notifications = manager.subscribe();
+    manager.enable_auto_refresh(TEST_POLICY);
+    let notification = next_notification(&mut notifications).await?;
+    assert!(
+        matches!(notification, AuthNotification::Changed),
+        "{notification:?}"
+    );
+
+    let auth = manager.auth().context("auth should remain")?;
+    assert_eq!(auth.get_token().await?, "new-access-token");
+    let stored = ctx.load_auth()?;
+    let tokens = stored.tokens.as_ref().context("tokens should exist")?;
+    assert_eq!(tokens.access_token, "new-access-token");
+    assert_eq!(manager.token_status().expires_at, None);
+
+    server.verify().await;
+    Ok(())
+}
+
+#[serial_test::serial(auth_refresh)]
+#[tokio::test]
+async fn auto_refresh_retries_failed_refreshes_with_backoff() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    mount_refresh_failures(&server, 2).await;
+    mount_refresh_success(&server).await;
+
+    let expires_at = Utc::now().timestamp() + 60;
+    let ctx = RefreshTokenTestContext::with_access_token(&server, expiring_jwt(expires_at))?;
+    let manager = ctx.auth_manager();
+    let mut notifications = manager.subscribe();
+    manager.enable_auto_refresh(TEST_POLICY);
+
+    let mut failures = Vec::new();
+    while let AuthNotification::RefreshFailed(failed) =
+        next_notification(&mut notifications).await?
+    {
+        failures.push(failed);
+    }
+    assert_eq!(
+        failures
+            .iter()
+            .map(|failed| (failed.attempt, failed.retry_in_ms, failed.expires_at))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Some(10), Some(expires_at)),
+            (2, Some(20), Some(expires_at))
+        ]
+    );
+    let auth = manager.auth().context("auth should remain")?;
+    assert_eq!(auth.get_token().await?, "new-access-token");
+
+    server.verify().await;
+    Ok(())
+}
+
+#[serial_test::serial(auth_refresh)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn auto_refresh_reaches_the_next_request_of_a_conversation() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    mount_refresh_failures(&server, 1).await;
+    mount_refresh_success(&server).await;
+    let responses = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    let expires_at = Utc::now().timestamp() + 60;
+    let ctx = RefreshTokenTestContext::with_access_token(&server, expiring_jwt(expires_at))?;
+    let codex_home = ctx.codex_home.path().to_path_buf();
+    let test = test_codex()
+        .with_auth(ctx.auth.clone())
+        .with_config(move |config| config.codex_home = codex_home)
+        .build(&server)
+        .await?;
+    let manager = test.conversation_manager.auth_manager();
+    let mut notifications = manager.subscribe();
+    manager.enable_auto_refresh(TEST_POLICY);
+
+    let failed = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::AuthRefreshFailed(failed) => Some(failed.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(failed.attempt, 1);
+    assert_eq!(failed.expires_at, Some(expires_at));
+    while !matches!(
+        next_notification(&mut notifications).await?,
+        AuthNotification::Changed
+    ) {}
+
+    test.submit_turn("hello").await?;
+    assert_eq!(
+        responses.single_request().header("authorization"),
+        Some("Bearer new-access-token".to_string())
+    );
+
+    Ok(())
+}
+
+#[serial_test::serial(auth_refresh)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unauthorized_request_retries_the_refresh_as_the_profile_allows() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    mount_refresh_success(&server).await;
+    let responses = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    // The first request is rejected as unauthorized, and so is the first
+    // refresh it triggers.
+    let plan = FaultPlan::new()
+        .fail_provider_request(1, 401)
+        .fail_token_refresh(1, 500);
+    let provider = FaultyProvider::start(
+        ModelProviderInfo {
+            base_url: Some(server.uri()),
+            request_max_retries: Some(0),
+            ..built_in_model_providers()["openai"].clone()
+        },
+        &plan,
+    )
+    .await?;
+    let provider_info: ModelProviderInfo = (&provider).into();
+    let refresh_url = format!(
+        "{}/oauth/token",
+        provider_info.base_url.as_deref().unwrap_or_default()
+    );
+    let ctx =
+        RefreshTokenTestContext::with_refresh_url(refresh_url, INITIAL_ACCESS_TOKEN.to_string())?;
+    let codex_home = ctx.codex_home.path().to_path_buf();
+    let test = test_codex()
+        .with_auth(ctx.auth.clone())
+        .with_config(move |config| {
+            config.codex_home = codex_home;
+            config.model_provider = provider_info;
+            config.retry.auth_refresh = RetryPolicy {
+                max_retries: Some(1),
+                base_delay: std::time::Duration::from_millis(1),
+                ..RetryPolicy::auth_refresh()
+            };
+        })
+        .build(&server)
+        .await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let retry = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::Retrying(retry) => Some(retry.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(
+        retry,
+        RetryingEvent {
+            subsystem: RetrySubsystem::AuthRefresh,
+            attempt: 1,
+            delay_ms: 1,
+        }
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(
+        responses.single_request().header("authorization"),
+        Some("Bearer new-access-token".to_string())
+    );
+    assert_eq!(plan.remaining_faults(), Vec::new());
+    server.verify().await;
+    Ok(())
+}
+
+async fn mount_refresh_success(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "new-access-token",
+            "refresh_token": "new-refresh-token"
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn mount_refresh_failures(server: &MockServer, times: u64) {
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(json!({
+            "error": "temporary-failure"
+        })))
+        .up_to_n_times(times)
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+async fn next_notification(
+    notifications: &mut broadcast::Receiver<AuthNotification>,
+) -> Result<AuthNotification> {
+    Ok(
+        tokio::time::timeout(std::time::Duration::from_secs(10), notifications.recv())
+            .await
+            .context("timed out waiting for an auth notification")??,
+    )
+}
+
 struct RefreshTokenTestContext {
     codex_home: TempDir,
     auth: CodexAuth,
@@ -170,13 +457,21 @@ struct RefreshTokenTestContext {
 
 impl RefreshTokenTestContext {
     fn new(server: &MockServer) -> Result<Self> {
+        Self::with_access_token(server, INITIAL_ACCESS_TOKEN.to_string())
+    }
+
+    fn with_access_token(server: &MockServer, access_token: String) -> Result<Self> {
+        Self::with_refresh_url(format!("{}/oauth/token", server.uri()), access_token)
+    }
+
+    fn with_refresh_url(refresh_url: String, access_token: String) -> Result<Self> {
         let codex_home = TempDir::new()?;
         let initial_last_refresh = Utc::now() - Duration::days(1);
         let mut id_token = IdTokenInfo::default();
         id_token.raw_jwt = minimal_jwt();
         let tokens = TokenData {
             id_token,
-            access_token: INITIAL_ACCESS_TOKEN.to_string(),
+            access_token,
             refresh_token: INITIAL_REFRESH_TOKEN.to_string(),
             account_id: Some("account-id".to_string()),
         };
@@ -191,8 +486,7 @@ impl RefreshTokenTestContext {
             AuthCredentialsStoreMode::File,
         )?;
 
-        let endpoint = format!("{}/oauth/token", server.uri());
-        let env_guard = EnvGuard::set(REFRESH_TOKEN_URL_OVERRIDE_ENV_VAR, endpoint);
+        let env_guard = EnvGuard::set(REFRESH_TOKEN_URL_OVERRIDE_ENV_VAR, refresh_url);
 
         let auth = CodexAuth::from_auth_storage(codex_home.path(), AuthCredentialsStoreMode::File)?
             .context("auth should load from storage")?;
@@ -205,6 +499,13 @@ impl RefreshTokenTestContext {
         })
     }
 
+    fn auth_manager(&self) -> Arc<AuthManager> {
+        AuthManager::from_auth_for_testing_with_home(
+            self.auth.clone(),
+            self.codex_home.path().to_path_buf(),
+        )
+    }
+
     fn load_auth(&self) -> Result<AuthDotJson> {
         load_auth_dot_json(self.codex_home.path(), AuthCredentialsStoreMode::File)
             .context("load auth.json")?
@@ -241,6 +542,14 @@ impl Drop for EnvGuard {
 }
 
 fn minimal_jwt() -> String {
+    jwt(json!({ "sub": "user-123" }))
+}
+
+fn expiring_jwt(expires_at: i64) -> String {
+    jwt(json!({ "sub": "user-123", "exp": expires_at }))
+}
+
+fn jwt(payload: serde_json::Value) -> String {
     #[derive(Serialize)]
     struct Header {
         alg: &'static str,
@@ -251,8 +560,6 @@ fn minimal_jwt() -> String {
         alg: "none",
         typ: "JWT",
     };
-    let payload = json!({ "sub": "user-123" });
-
     fn b64(data: &[u8]) -> String {
         base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
     }
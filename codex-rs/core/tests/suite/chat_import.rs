@@ -0,0 +1,102 @@
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::NewConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InitialHistory;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::rollout::convert::ChatMessage;
+use codex_core::rollout::convert::from_chat_messages;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn conversation_continues_from_imported_chat_transcript() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let response = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "still a cat"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+
+    let messages: Vec<ChatMessage> = serde_json::from_value(json!([
+        {"role": "user", "content": "what animal says meow?"},
+        {
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call-1",
+                "type": "function",
+                "function": {"name": "lookup", "arguments": "{\"sound\":\"meow\"}"},
+            }],
+        },
+        {"role": "tool", "tool_call_id": "call-1", "content": "cat"},
+        {"role": "assistant", "content": "A cat."},
+    ]))?;
+    let import = from_chat_messages(messages);
+    assert!(import.report.is_lossless(), "{:?}", import.report);
+
+    let history = InitialHistory::Forked(
+        import
+            .items
+            .into_iter()
+            .map(RolloutItem::ResponseItem)
+            .collect(),
+    );
+    let NewConversation { conversation, .. } = test
+        .conversation_manager
+        .resume_conversation_with_history(
+            test.config.clone(),
+            history,
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy")),
+        )
+        .await?;
+
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "are you sure?".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let request = response.single_request();
+    let user_texts = request.message_input_texts("user");
+    assert!(user_texts.contains(&"what animal says meow?".to_string()));
+    assert_eq!(user_texts.last().map(String::as_str), Some("are you sure?"));
+    let assistant_texts: Vec<Value> = request
+        .inputs_of_type("message")
+        .into_iter()
+        .filter(|item| item["role"] == "assistant")
+        .map(|item| item["content"][0]["text"].clone())
+        .collect();
+    assert_eq!(assistant_texts, vec![json!("A cat.")]);
+    assert!(request.has_function_call("call-1"));
+    assert_eq!(
+        request.function_call_output_text("call-1"),
+        Some("cat".to_string())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+fn assistant_turn(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), "ok"),
+        ev_completed(id),
+    ])
+}
+
+fn user_prompts(texts: Vec<String>) -> Vec<String> {
+    texts
+        .into_iter()
+        .filter(|text| text.starts_with("turn "))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rollback_to_checkpoint_drops_later_turns() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            assistant_turn("resp-1"),
+            assistant_turn("resp-2"),
+            assistant_turn("resp-3"),
+            assistant_turn("resp-4"),
+        ],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+
+    test.submit_turn("turn 1").await?;
+    let checkpoint = test.codex.checkpoint("after turn 1").await?;
+    let created = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::CheckpointCreated(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(created.id, checkpoint);
+    assert_eq!(created.label, "after turn 1");
+
+    test.submit_turn("turn 2").await?;
+    let later = test.codex.checkpoint("after turn 2").await?;
+    test.submit_turn("turn 3").await?;
+
+    test.codex
+        .rollback_to_checkpoint(checkpoint.clone())
+        .await?;
+    let rolled_back = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::CheckpointRolledBack(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(rolled_back.id, checkpoint);
+    assert_eq!(rolled_back.invalidated, vec![later]);
+
+    test.submit_turn("turn 4").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 4);
+    let last = requests.last().expect("last request");
+    assert_eq!(
+        user_prompts(last.message_input_texts("user")),
+        vec!["turn 1".to_string(), "turn 4".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn checkpoints_survive_resume() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            assistant_turn("resp-1"),
+            assistant_turn("resp-2"),
+            assistant_turn("resp-3"),
+        ],
+    )
+    .await;
+    let mut builder = test_codex();
+    let initial = builder.build(&server).await?;
+
+    initial.submit_turn("turn 1").await?;
+    let checkpoint = initial.codex.checkpoint("after turn 1").await?;
+    wait_for_event_match(&initial.codex, |event| match event {
+        EventMsg::CheckpointCreated(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    initial.submit_turn("turn 2").await?;
+
+    let home = initial.home.clone();
+    let rollout_path = initial.codex.rollout_path();
+    let resumed = builder.resume(&server, home, rollout_path).await?;
+
+    resumed
+        .codex
+        .rollback_to_checkpoint(checkpoint.clone())
+        .await?;
+    let rolled_back = wait_for_event_match(&resumed.codex, |event| match event {
+        EventMsg::CheckpointRolledBack(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(rolled_back.id, checkpoint);
+
+    resumed.submit_turn("turn 3").await?;
+
+    let requests = responses.requests();
+    let last = requests.last().expect("last request");
+    assert_eq!(
+        user_prompts(last.message_input_texts("user")),
+        vec!["turn 1".to_string(), "turn 3".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interleaved_transcript_mutations_replay_to_the_live_history() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    const ROUNDS: usize = 8;
+    const COMPACT_AFTER: usize = 4;
+
+    let server = start_mock_server().await;
+    let mut bodies: Vec<String> = (1..=ROUNDS)
+        .map(|round| assistant_turn(&format!("resp-{round}")))
+        .collect();
+    bodies.insert(COMPACT_AFTER, assistant_turn("resp-compact"));
+    bodies.push(assistant_turn("resp-live"));
+    bodies.push(assistant_turn("resp-resumed"));
+    let responses = mount_sse_sequence(&server, bodies).await;
+    let mut builder = test_codex();
+    let initial = builder.build(&server).await?;
+    let codex = &initial.codex;
+
+    // Each round queues a checkpoint, a settings update and a rollback to the
+    // previous round's checkpoint behind a turn. Whether the rollback lands
+    // while the turn runs depends on timing; either way the rollout must
+    // record what was applied, in the order it was applied.
+    let mut previous = None;
+    for round in 1..=ROUNDS {
+        codex
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text {
+                    text: format!("turn {round}"),
+                }],
+                allow_sensitive: false,
+            })
+            .await?;
+        let checkpoint = codex.checkpoint(format!("round {round}")).await?;
+        codex
+            .submit(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: Some(if round % 2 == 0 {
+                    AskForApproval::Never
+                } else {
+                    AskForApproval::OnRequest
+                }),
+                sandbox_policy: None,
+                model: None,
+                effort: None,
+                summary: None,
+                execution_mode: None,
+                sampling: None,
+                offline: None,
+            })
+            .await?;
+        if let Some(previous) = previous.replace(checkpoint) {
+            codex.rollback_to_checkpoint(previous).await?;
+        }
+        wait_for_event(codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+        if round == COMPACT_AFTER {
+            codex.submit(Op::Compact).await?;
+            if let Some(previous) = previous.clone() {
+                codex.rollback_to_checkpoint(previous).await?;
+            }
+            wait_for_event(codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+        }
+    }
+
+    initial.submit_turn("turn live").await?;
+    let live = user_prompts(
+        responses
+            .requests()
+            .last()
+            .expect("live request")
+            .message_input_texts("user"),
+    );
+    assert_eq!(live.last().map(String::as_str), Some("turn live"));
+
+    let home = initial.home.clone();
+    let rollout_path = initial.codex.rollout_path();
+    let resumed = builder.resume(&server, home, rollout_path).await?;
+    resumed.submit_turn("turn resumed").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), ROUNDS + 3);
+    let mut expected = live;
+    expected.push("turn resumed".to_string());
+    assert_eq!(
+        user_prompts(
+            requests
+                .last()
+                .expect("resumed request")
+                .message_input_texts("user")
+        ),
+        expected
+    );
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use codex_core::child_conversations::ChildConversationOverrides;
+use codex_core::child_conversations::ConversationLineage;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::protocol::ConversationLink;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parent_ingests_child_final_answer() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let parent = test.codex.clone();
+    let parent_id = parent.conversation_id();
+
+    let child_request = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-child"),
+            ev_assistant_message("msg-child", "X is configured in config.toml"),
+            ev_completed("resp-child"),
+        ]),
+    )
+    .await;
+    let parent_request = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-parent"),
+            ev_assistant_message("msg-parent", "thanks"),
+            ev_completed("resp-parent"),
+        ]),
+    )
+    .await;
+
+    let child_id = parent
+        .spawn_child(ChildConversationOverrides::default(), "research X")
+        .await?;
+    let link = ConversationLink {
+        parent_id,
+        child_id,
+    };
+    let linked = wait_for_event(&parent, |ev| matches!(ev, EventMsg::ConversationLinked(_))).await;
+    let EventMsg::ConversationLinked(linked) = linked else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(linked, link);
+
+    let child = test.conversation_manager.get_conversation(child_id).await?;
+    let linked = wait_for_event(&child, |ev| matches!(ev, EventMsg::ConversationLinked(_))).await;
+    let EventMsg::ConversationLinked(linked) = linked else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(linked, link);
+    wait_for_event(&child, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(
+        child_request
+            .single_request()
+            .message_input_texts("user")
+            .last(),
+        Some(&"research X".to_string())
+    );
+
+    assert_eq!(
+        test.conversation_manager.lineage(parent_id).await,
+        ConversationLineage {
+            parent: None,
+            children: vec![child_id],
+        }
+    );
+    assert_eq!(
+        test.conversation_manager.lineage(child_id).await,
+        ConversationLineage {
+            parent: Some(parent_id),
+            children: Vec::new(),
+        }
+    );
+
+    parent.ingest_child_result(child_id).await?;
+    parent
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "carry on".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&parent, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let texts = parent_request.single_request().message_input_texts("user");
+    let result = texts
+        .iter()
+        .find(|text| text.starts_with("<child_conversation_result"))
+        .unwrap_or_else(|| panic!("parent request should include the child's result: {texts:?}"));
+    assert!(result.contains(&format!("child_id=\"{child_id}\"")));
+    assert!(result.contains("X is configured in config.toml"));
+    assert_eq!(texts.last().map(String::as_str), Some("carry on"));
+
+    test.conversation_manager
+        .remove_conversation(&parent_id)
+        .await;
+    assert!(matches!(
+        test.conversation_manager.get_conversation(child_id).await,
+        Err(CodexErr::ConversationNotFound(id)) if id == child_id
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn children_count_against_conversation_limit() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    test.conversation_manager.set_max_conversations(Some(1));
+
+    let result = test
+        .codex
+        .spawn_child(ChildConversationOverrides::default(), "research X")
+        .await;
+    assert!(matches!(result, Err(CodexErr::ConversationLimitReached(1))));
+
+    Ok(())
+}
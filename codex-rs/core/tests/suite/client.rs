@@ -290,6 +290,7 @@ async fn resume_includes_initial_messages_and_sends_prior_items() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -355,6 +356,7 @@ async fn includes_conversation_id_and_model_headers_in_request() {
         conversation: codex,
         conversation_id,
         session_configured: _,
+        ..
     } = conversation_manager
         .new_conversation(config)
         .await
@@ -365,6 +367,7 @@ async fn includes_conversation_id_and_model_headers_in_request() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -424,6 +427,7 @@ async fn includes_base_instructions_override_in_request() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -478,6 +482,7 @@ async fn chatgpt_auth_sends_correct_request() {
         conversation: codex,
         conversation_id,
         session_configured: _,
+        ..
     } = conversation_manager
         .new_conversation(config)
         .await
@@ -488,6 +493,7 @@ async fn chatgpt_auth_sends_correct_request() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -582,6 +588,7 @@ async fn prefers_apikey_when_config_prefers_apikey_even_with_chatgpt_tokens() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -622,6 +629,7 @@ async fn includes_user_instructions_message_in_request() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -692,6 +700,7 @@ async fn skills_append_to_instructions() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -741,6 +750,7 @@ async fn includes_configured_effort_in_request() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -777,6 +787,7 @@ async fn includes_no_effort_in_request() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -811,6 +822,7 @@ async fn includes_default_reasoning_effort_in_request_when_defined_by_model_fami
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -849,6 +861,7 @@ async fn configured_reasoning_summary_is_sent() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -887,6 +900,7 @@ async fn reasoning_summary_is_omitted_when_disabled() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -919,6 +933,7 @@ async fn includes_default_verbosity_in_request() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -958,6 +973,7 @@ async fn configured_verbosity_not_sent_for_models_without_support() -> anyhow::R
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -996,6 +1012,7 @@ async fn configured_verbosity_is_sent() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1050,6 +1067,7 @@ async fn includes_developer_instructions_message_in_request() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1115,6 +1133,9 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -1280,6 +1301,7 @@ async fn token_count_includes_rate_limits_snapshot() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1437,6 +1459,7 @@ async fn usage_limit_error_emits_rate_limit_event() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .expect("submission should succeed while emitting usage limit error events");
@@ -1506,6 +1529,7 @@ async fn context_window_error_sets_total_tokens_to_model_window() -> anyhow::Res
             items: vec![UserInput::Text {
                 text: "seed turn".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -1516,6 +1540,7 @@ async fn context_window_error_sets_total_tokens_to_model_window() -> anyhow::Res
             items: vec![UserInput::Text {
                 text: "trigger context window".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -1611,6 +1636,9 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -1635,6 +1663,7 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1693,6 +1722,9 @@ async fn env_var_overrides_loaded_auth() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        fallback_base_urls: None,
+        endpoint_cooldown_ms: None,
+        tool_capabilities: None,
         requires_openai_auth: false,
     };
 
@@ -1717,6 +1749,7 @@ async fn env_var_overrides_loaded_auth() {
             items: vec![UserInput::Text {
                 text: "hello".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1800,6 +1833,7 @@ async fn history_dedupes_streamed_and_final_messages_across_turns() {
     codex
         .submit(Op::UserInput {
             items: vec![UserInput::Text { text: "U1".into() }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1809,6 +1843,7 @@ async fn history_dedupes_streamed_and_final_messages_across_turns() {
     codex
         .submit(Op::UserInput {
             items: vec![UserInput::Text { text: "U2".into() }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1818,6 +1853,7 @@ async fn history_dedupes_streamed_and_final_messages_across_turns() {
     codex
         .submit(Op::UserInput {
             items: vec![UserInput::Text { text: "U3".into() }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
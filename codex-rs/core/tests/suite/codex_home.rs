@@ -0,0 +1,55 @@
+use anyhow::Result;
+use codex_core::CodexHome;
+use codex_core::blob_store::BlobStore;
+use codex_core::protocol::BlobRef;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn managers_resolve_paths_under_the_same_home() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ])],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    test.submit_turn("hello").await?;
+
+    let home = test.conversation_manager.codex_home();
+    assert_eq!(home, &CodexHome::new(test.codex_home_path()));
+    assert_eq!(test.conversation_manager.auth_manager().home(), home);
+    assert_eq!(test.conversation_manager.skills_manager().home(), home);
+
+    let rollout = test.codex.rollout_path();
+    assert!(
+        rollout.starts_with(home.sessions_dir()),
+        "{} is outside {}",
+        rollout.display(),
+        home.sessions_dir().display()
+    );
+    let blob = BlobRef {
+        sha256: "0".repeat(64),
+        size_bytes: 0,
+    };
+    assert!(
+        BlobStore::new(home.root())
+            .path(&blob)
+            .starts_with(home.blobs_dir())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::expect_used)]
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::CodexAuth;
+use codex_core::CodexRuntime;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::auth::login_with_api_key;
+use codex_core::built_in_model_providers;
+use codex_core::config::Config;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::openai_models::ModelsResponse;
+use codex_protocol::protocol::SessionSource;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_models_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn message_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+async fn home_config(home: &TempDir, cwd: &Path, provider: &ModelProviderInfo) -> Config {
+    let mut config = load_default_config_for_test(home).await;
+    config.cwd = cwd.to_path_buf();
+    config.model = Some("gpt-5.1".to_string());
+    config.model_provider = provider.clone();
+    config.features.enable(Feature::RemoteModels);
+    config
+}
+
+fn write_skill(home: &Path, name: &str) {
+    let skill_dir = home.join("skills").join(name);
+    fs::create_dir_all(&skill_dir).expect("create skill dir");
+    fs::write(
+        skill_dir.join("SKILL.md"),
+        format!("---\nname: {name}\ndescription: {name} skill\n---\n\nbody\n"),
+    )
+    .expect("write skill");
+}
+
+fn skill_names(manager: &ConversationManager, cwd: &Path) -> Vec<String> {
+    manager
+        .skills_manager()
+        .skills_for_cwd(cwd)
+        .skills
+        .into_iter()
+        .map(|skill| skill.name)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn homes_on_one_runtime_share_models_and_nothing_else() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let models = mount_models_once(
+        &server,
+        ModelsResponse {
+            models: Vec::new(),
+            etag: String::new(),
+        },
+    )
+    .await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![message_response("resp-a"), message_response("resp-b")],
+    )
+    .await;
+    let provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let runtime_home = TempDir::new()?;
+    let runtime = CodexRuntime::with_models_provider(
+        CodexAuth::create_dummy_chatgpt_auth_for_testing(),
+        provider.clone(),
+        runtime_home.path().to_path_buf(),
+    );
+
+    let (home_a, home_b, cwd) = (TempDir::new()?, TempDir::new()?, TempDir::new()?);
+    login_with_api_key(home_a.path(), "key-a", AuthCredentialsStoreMode::File)?;
+    login_with_api_key(home_b.path(), "key-b", AuthCredentialsStoreMode::File)?;
+    write_skill(home_a.path(), "only-in-a");
+    let config_a = home_config(&home_a, cwd.path(), &provider).await;
+    let config_b = home_config(&home_b, cwd.path(), &provider).await;
+    let manager_a = runtime.manager_for_home(home_a.path(), SessionSource::Exec);
+    let manager_b = runtime.manager_for_home(home_b.path(), SessionSource::Exec);
+
+    assert!(skill_names(&manager_a, cwd.path()).contains(&"only-in-a".to_string()));
+    assert!(!skill_names(&manager_b, cwd.path()).contains(&"only-in-a".to_string()));
+    assert!(manager_a.new_conversation(config_b.clone()).await.is_err());
+
+    let conversation_a = manager_a.new_conversation(config_a).await?.conversation;
+    TestCodex::submit_input_to(&conversation_a, "hello").await?;
+    let rollout_a = conversation_a.rollout_path();
+    conversation_a.submit(Op::Shutdown).await?;
+    wait_for_event(&conversation_a, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    drop(conversation_a);
+    drop(manager_a);
+
+    // The runtime outlives the manager of the first home.
+    let conversation_b = manager_b.new_conversation(config_b).await?.conversation;
+    TestCodex::submit_input_to(&conversation_b, "hello").await?;
+
+    assert!(rollout_a.starts_with(home_a.path()), "{rollout_a:?}");
+    let rollout_b = conversation_b.rollout_path();
+    assert!(rollout_b.starts_with(home_b.path()), "{rollout_b:?}");
+    let authorizations: Vec<Option<String>> = responses
+        .requests()
+        .iter()
+        .map(|request| request.header("authorization"))
+        .collect();
+    assert_eq!(
+        authorizations,
+        vec![
+            Some("Bearer key-a".to_string()),
+            Some("Bearer key-b".to_string())
+        ]
+    );
+    assert_eq!(models.requests().len(), 1);
+
+    Ok(())
+}
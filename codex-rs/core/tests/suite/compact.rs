@@ -158,6 +158,7 @@ async fn summarize_context_three_requests_and_instructions() {
             items: vec![UserInput::Text {
                 text: "hello world".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -166,7 +167,7 @@ async fn summarize_context_three_requests_and_instructions() {
     // 2) Summarize – second hit should include the summarization prompt.
     codex.submit(Op::Compact).await.unwrap();
     let warning_event = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Warning(_))).await;
-    let EventMsg::Warning(WarningEvent { message }) = warning_event else {
+    let EventMsg::Warning(WarningEvent { message, .. }) = warning_event else {
         panic!("expected warning event after compact");
     };
     assert_eq!(message, COMPACT_WARNING_MESSAGE);
@@ -178,6 +179,7 @@ async fn summarize_context_three_requests_and_instructions() {
             items: vec![UserInput::Text {
                 text: THIRD_USER_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -347,7 +349,7 @@ async fn manual_compact_uses_custom_prompt() {
 
     codex.submit(Op::Compact).await.expect("trigger compact");
     let warning_event = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Warning(_))).await;
-    let EventMsg::Warning(WarningEvent { message }) = warning_event else {
+    let EventMsg::Warning(WarningEvent { message, .. }) = warning_event else {
         panic!("expected warning event after compact");
     };
     assert_eq!(message, COMPACT_WARNING_MESSAGE);
@@ -577,6 +579,7 @@ async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
             items: vec![UserInput::Text {
                 text: user_message.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .expect("submit user input");
@@ -1081,6 +1084,7 @@ async fn auto_compact_runs_after_token_limit_hit() {
             items: vec![UserInput::Text {
                 text: FIRST_AUTO_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1092,6 +1096,7 @@ async fn auto_compact_runs_after_token_limit_hit() {
             items: vec![UserInput::Text {
                 text: SECOND_AUTO_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1103,6 +1108,7 @@ async fn auto_compact_runs_after_token_limit_hit() {
             items: vec![UserInput::Text {
                 text: POST_AUTO_USER_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1304,6 +1310,7 @@ async fn auto_compact_persists_rollout_entries() {
             items: vec![UserInput::Text {
                 text: FIRST_AUTO_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1314,6 +1321,7 @@ async fn auto_compact_persists_rollout_entries() {
             items: vec![UserInput::Text {
                 text: SECOND_AUTO_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1324,6 +1332,7 @@ async fn auto_compact_persists_rollout_entries() {
             items: vec![UserInput::Text {
                 text: POST_AUTO_USER_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1415,6 +1424,7 @@ async fn manual_compact_retries_after_context_window_error() {
             items: vec![UserInput::Text {
                 text: "first turn".into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1432,7 +1442,7 @@ async fn manual_compact_retries_after_context_window_error() {
         event.message
     );
     let warning_event = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Warning(_))).await;
-    let EventMsg::Warning(WarningEvent { message }) = warning_event else {
+    let EventMsg::Warning(WarningEvent { message, .. }) = warning_event else {
         panic!("expected warning event after compact retry");
     };
     assert_eq!(message, COMPACT_WARNING_MESSAGE);
@@ -1547,6 +1557,7 @@ async fn manual_compact_twice_preserves_latest_user_messages() {
             items: vec![UserInput::Text {
                 text: first_user_message.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1560,6 +1571,7 @@ async fn manual_compact_twice_preserves_latest_user_messages() {
             items: vec![UserInput::Text {
                 text: second_user_message.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1573,6 +1585,7 @@ async fn manual_compact_twice_preserves_latest_user_messages() {
             items: vec![UserInput::Text {
                 text: final_user_message.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1752,6 +1765,7 @@ async fn auto_compact_allows_multiple_attempts_when_interleaved_with_other_turn_
         codex
             .submit(Op::UserInput {
                 items: vec![UserInput::Text { text: user.into() }],
+                allow_sensitive: false,
             })
             .await
             .unwrap();
@@ -1864,6 +1878,7 @@ async fn auto_compact_triggers_after_function_call_over_95_percent_usage() {
             items: vec![UserInput::Text {
                 text: FUNCTION_CALL_LIMIT_MSG.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1875,6 +1890,7 @@ async fn auto_compact_triggers_after_function_call_over_95_percent_usage() {
             items: vec![UserInput::Text {
                 text: follow_up_user.into(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -1989,6 +2005,7 @@ async fn auto_compact_counts_encrypted_reasoning_before_last_user() {
         codex
             .submit(Op::UserInput {
                 items: vec![UserInput::Text { text: user.into() }],
+                allow_sensitive: false,
             })
             .await
             .unwrap();
@@ -74,6 +74,7 @@ async fn remote_compact_replaces_history_for_followups() -> Result<()> {
             items: vec![UserInput::Text {
                 text: "hello remote compact".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -86,6 +87,7 @@ async fn remote_compact_replaces_history_for_followups() -> Result<()> {
             items: vec![UserInput::Text {
                 text: "after compact".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -191,6 +193,7 @@ async fn remote_compact_runs_automatically() -> Result<()> {
             items: vec![UserInput::Text {
                 text: "hello remote compact".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     let message = wait_for_event_match(&codex, |ev| match ev {
@@ -263,6 +266,7 @@ async fn remote_compact_persists_replacement_history_in_rollout() -> Result<()>
             items: vec![UserInput::Text {
                 text: "needs compaction".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -884,6 +884,7 @@ async fn user_turn(conversation: &Arc<CodexConversation>, text: &str) {
     conversation
         .submit(Op::UserInput {
             items: vec![UserInput::Text { text: text.into() }],
+            allow_sensitive: false,
         })
         .await
         .expect("submit user turn");
@@ -896,7 +897,7 @@ async fn compact_conversation(conversation: &Arc<CodexConversation>) {
         .await
         .expect("compact conversation");
     let warning_event = wait_for_event(conversation, |ev| matches!(ev, EventMsg::Warning(_))).await;
-    let EventMsg::Warning(WarningEvent { message }) = warning_event else {
+    let EventMsg::Warning(WarningEvent { message, .. }) = warning_event else {
         panic!("expected warning event after compact");
     };
     assert_eq!(message, COMPACT_WARNING_MESSAGE);
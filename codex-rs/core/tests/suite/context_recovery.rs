@@ -0,0 +1,175 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::ContextRecoveredEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_protocol::config_types::ContextRecoveryStrategy;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_failed;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(id),
+    ])
+}
+
+fn too_long(id: &str) -> String {
+    sse_failed(
+        id,
+        "context_length_exceeded",
+        "Your input exceeds the context window of this model.",
+    )
+}
+
+async fn build(server: &MockServer, strategy: ContextRecoveryStrategy) -> Result<TestCodex> {
+    test_codex()
+        .with_config(move |config| {
+            config.context_recovery = strategy;
+            // Compact locally, through the mocked responses endpoint.
+            config.model_provider.name = "OpenAI (test)".into();
+        })
+        .build(server)
+        .await
+}
+
+/// The recovery a turn reported, if any, with the turn's completion.
+fn outcome(events: Vec<EventMsg>) -> (Option<ContextRecoveredEvent>, TaskCompleteEvent) {
+    let mut recovered = None;
+    for event in events {
+        match event {
+            EventMsg::ContextRecovered(event) => recovered = Some(event),
+            EventMsg::TaskComplete(complete) => return (recovered, complete),
+            _ => {}
+        }
+    }
+    unreachable!("the events of a turn end with TaskComplete");
+}
+
+fn prompts_sent(requests: &ResponseMock, index: usize) -> Vec<String> {
+    requests.requests()[index]
+        .message_input_texts("user")
+        .into_iter()
+        .filter(|text| ["one", "two", "three"].contains(&text.as_str()))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fail_fast_ends_the_turn_with_the_error() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let requests = mount_sse_sequence(&server, vec![answer("1", "first"), too_long("2")]).await;
+    let test = build(&server, ContextRecoveryStrategy::FailFast).await?;
+
+    test.submit_input("one").await?;
+    let (recovered, complete) = outcome(test.submit_input("two").await?);
+
+    assert_eq!(recovered, None);
+    assert!(complete.error.is_some(), "the turn reports the error");
+    assert_eq!(requests.requests().len(), 2, "nothing is retried");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn auto_compact_compacts_and_retries_once() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let requests = mount_sse_sequence(
+        &server,
+        vec![
+            answer("1", "first"),
+            too_long("2"),
+            answer("3", "summary of the conversation"),
+            answer("4", "second"),
+        ],
+    )
+    .await;
+    let test = build(&server, ContextRecoveryStrategy::AutoCompact).await?;
+
+    test.submit_input("one").await?;
+    let (recovered, complete) = outcome(test.submit_input("two").await?);
+
+    assert_eq!(
+        recovered,
+        Some(ContextRecoveredEvent {
+            strategy: ContextRecoveryStrategy::AutoCompact,
+            dropped_turns: 0,
+        })
+    );
+    assert_eq!(complete.error, None);
+    assert_eq!(complete.final_message.as_deref(), Some("second"));
+    assert_eq!(requests.requests().len(), 4);
+    let retry = requests.requests()[3].body_json().to_string();
+    assert!(retry.contains("summary of the conversation"), "{retry}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn auto_truncate_drops_the_oldest_turn_and_records_it() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let requests = mount_sse_sequence(
+        &server,
+        vec![
+            answer("1", "first"),
+            answer("2", "second"),
+            too_long("3"),
+            answer("4", "third"),
+        ],
+    )
+    .await;
+    let test = build(&server, ContextRecoveryStrategy::AutoTruncate).await?;
+    let codex = &test.codex;
+
+    test.submit_input("one").await?;
+    test.submit_input("two").await?;
+    let (recovered, complete) = outcome(test.submit_input("three").await?);
+
+    assert_eq!(
+        recovered,
+        Some(ContextRecoveredEvent {
+            strategy: ContextRecoveryStrategy::AutoTruncate,
+            dropped_turns: 1,
+        })
+    );
+    assert_eq!(complete.final_message.as_deref(), Some("third"));
+    assert_eq!(prompts_sent(&requests, 2), vec!["one", "two", "three"]);
+    assert_eq!(prompts_sent(&requests, 3), vec!["two", "three"]);
+
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    let compacted = RolloutRecorder::get_rollout_history(&codex.rollout_path())
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .filter(|item| {
+            matches!(item, RolloutItem::Compacted(compacted)
+                if compacted.replacement_history.is_some())
+        })
+        .count();
+    assert!(compacted >= 1, "the shortened history is in the rollout");
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::context_usage::TokenCounter;
+use codex_core::protocol::ContextUsage;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+/// Counts 1000 tokens per "lorem" so usage only grows with the prompts.
+struct LoremCounter;
+
+impl TokenCounter for LoremCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.matches("lorem").count() * 1_000
+    }
+}
+
+/// Run a turn and return the thresholds reported while it ran.
+async fn thresholds_crossed(conversation: &CodexConversation, prompt: &str) -> Result<Vec<u8>> {
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: prompt.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let mut crossed = Vec::new();
+    loop {
+        match conversation.next_event().await?.msg {
+            EventMsg::ContextUsageUpdated(event) => crossed.push(event.threshold),
+            EventMsg::TaskComplete(_) => return Ok(crossed),
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn thresholds_are_reported_once_per_crossing() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    for id in ["resp-1", "resp-2", "resp-3", "resp-4"] {
+        mount_sse_once(
+            &server,
+            sse(vec![
+                ev_response_created(id),
+                ev_assistant_message(&format!("msg-{id}"), "ok"),
+                ev_completed(id),
+            ]),
+        )
+        .await;
+    }
+    let test = test_codex()
+        .with_config(|config| {
+            // 95% of it is usable, so the window is 9500 tokens.
+            config.model_context_window = Some(10_000);
+        })
+        .build(&server)
+        .await?;
+    test.conversation_manager
+        .set_token_counter(Arc::new(LoremCounter));
+    let conversation = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?
+        .conversation;
+
+    assert_eq!(
+        thresholds_crossed(&conversation, "lorem lorem lorem").await?,
+        Vec::<u8>::new()
+    );
+    assert_eq!(
+        thresholds_crossed(&conversation, "lorem lorem lorem lorem").await?,
+        vec![50]
+    );
+    assert_eq!(thresholds_crossed(&conversation, "lorem").await?, vec![80]);
+    assert_eq!(
+        thresholds_crossed(&conversation, "no filler").await?,
+        Vec::<u8>::new()
+    );
+    assert_eq!(
+        conversation.context_usage(),
+        ContextUsage {
+            used_tokens_estimate: 8_000,
+            window: Some(9_500),
+            percent: Some(84),
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::expect_used)]
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::ConversationManager;
+use codex_core::config::Config;
+use codex_protocol::ConversationId;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+
+const SPAWNS_PER_TASK: usize = 4;
+
+/// Spawn conversations through `manager`, removing every other one, and
+/// return the ids that were kept and the ids that were removed.
+async fn spawn_and_remove(
+    manager: ConversationManager,
+    config: Config,
+) -> Result<(Vec<ConversationId>, Vec<ConversationId>)> {
+    let mut kept = Vec::new();
+    let mut removed = Vec::new();
+    for index in 0..SPAWNS_PER_TASK {
+        let id = manager
+            .new_conversation(config.clone())
+            .await?
+            .conversation_id;
+        if index % 2 == 0 {
+            manager
+                .remove_conversation(&id)
+                .await
+                .expect("conversation was just spawned");
+            removed.push(id);
+        } else {
+            kept.push(id);
+        }
+    }
+    Ok((kept, removed))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn clones_share_the_conversation_map() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let first = test.conversation_manager.clone();
+    let second = test.conversation_manager.clone();
+
+    let first_task = tokio::spawn(spawn_and_remove(first.clone(), test.config.clone()));
+    let second_task = tokio::spawn(spawn_and_remove(second.clone(), test.config.clone()));
+    let (first_kept, first_removed) = first_task.await??;
+    let (second_kept, second_removed) = second_task.await??;
+
+    for id in first_kept.iter().chain(&second_kept) {
+        let from_first = first.get_conversation(*id).await?;
+        let from_second = second.get_conversation(*id).await?;
+        assert!(Arc::ptr_eq(&from_first, &from_second));
+    }
+    for id in first_removed.iter().chain(&second_removed) {
+        assert!(first.get_conversation(*id).await.is_err());
+        assert!(second.get_conversation(*id).await.is_err());
+    }
+
+    // The original conversation is still tracked by every handle.
+    let original = test.session_configured.session_id;
+    assert!(Arc::ptr_eq(
+        &first.get_conversation(original).await?,
+        &test.codex
+    ));
+
+    // A limit set through one handle applies to all of them.
+    let tracked = 1 + first_kept.len() + second_kept.len();
+    first.set_max_conversations(Some(tracked));
+    assert!(second.new_conversation(test.config.clone()).await.is_err());
+    second.set_max_conversations(None);
+    first.new_conversation(test.config.clone()).await?;
+
+    Ok(())
+}
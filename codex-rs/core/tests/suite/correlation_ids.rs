@@ -0,0 +1,181 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::RolloutLine;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::rollout::EventLogPolicy;
+use codex_core::rollout::event_log_path;
+use codex_core::rollout::read_event_log;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::json;
+
+const CORRELATION_ID: &str = "req-7f3a";
+
+fn user_turn(test: &TestCodex, text: &str) -> Op {
+    Op::UserTurn {
+        items: vec![UserInput::Text {
+            text: text.to_string(),
+        }],
+        final_output_json_schema: None,
+        cwd: test.cwd.path().to_path_buf(),
+        approval_policy: AskForApproval::Never,
+        sandbox_policy: SandboxPolicy::DangerFullAccess,
+        model: test.session_configured.model.clone(),
+        effort: None,
+        summary: ReasoningSummary::Auto,
+        allow_sensitive: false,
+        bypass_read_cache: false,
+        deadline: None,
+    }
+}
+
+/// Read events until the turn of the submission `sub_id` completes.
+async fn turn_events(codex: &CodexConversation, sub_id: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    loop {
+        let event = codex.next_event().await.expect("event");
+        let done = event.id == sub_id && matches!(event.msg, EventMsg::TaskComplete(_));
+        events.push(event);
+        if done {
+            return events;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn correlation_id_follows_the_turn_it_was_submitted_with() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let arguments = serde_json::to_string(&json!({
+        "command": "echo correlated",
+        "timeout_ms": 2_000,
+    }))?;
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("call-1", "shell_command", &arguments),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "again"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.event_log = Some(EventLogPolicy::default());
+        })
+        .build(&server)
+        .await?;
+
+    let correlated = test
+        .codex
+        .submit_with_correlation(user_turn(&test, "run echo"), CORRELATION_ID)
+        .await?;
+    let events = turn_events(&test.codex, &correlated).await;
+    let (turn, other): (Vec<&Event>, Vec<&Event>) =
+        events.iter().partition(|event| event.id == correlated);
+    assert!(
+        turn.iter()
+            .any(|event| matches!(event.msg, EventMsg::ExecCommandEnd(_))),
+        "{turn:?}"
+    );
+    for event in &turn {
+        assert_eq!(
+            event.correlation_id.as_deref(),
+            Some(CORRELATION_ID),
+            "{event:?}"
+        );
+    }
+    assert!(other.iter().all(|event| event.correlation_id.is_none()));
+
+    let unrelated = test.codex.submit(user_turn(&test, "say again")).await?;
+    let events = turn_events(&test.codex, &unrelated).await;
+    assert!(
+        events.iter().all(|event| event.correlation_id.is_none()),
+        "{events:?}"
+    );
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        assert!(!request.body_json().to_string().contains(CORRELATION_ID));
+    }
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    let rollout_path = test.codex.rollout_path();
+    let lines: Vec<RolloutLine> = std::fs::read_to_string(&rollout_path)?
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("rollout line"))
+        .collect();
+    let correlated_lines: Vec<&RolloutItem> = lines
+        .iter()
+        .filter(|line| line.correlation_id.as_deref() == Some(CORRELATION_ID))
+        .map(|line| &line.item)
+        .collect();
+    assert!(
+        correlated_lines
+            .iter()
+            .any(|item| matches!(item, RolloutItem::TurnContext(_)))
+    );
+    assert!(correlated_lines.iter().any(|item| matches!(
+        item,
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall { call_id, .. }) if call_id == "call-1"
+    )));
+    assert!(correlated_lines.iter().any(|item| matches!(
+        item,
+        RolloutItem::EventMsg(EventMsg::AgentMessage(message)) if message.message == "done"
+    )));
+    let untagged_reply = lines.iter().find(|line| {
+        matches!(
+            &line.item,
+            RolloutItem::EventMsg(EventMsg::AgentMessage(message)) if message.message == "again"
+        )
+    });
+    assert_eq!(
+        untagged_reply.map(|line| line.correlation_id.clone()),
+        Some(None)
+    );
+
+    let logged = read_event_log(&test.config.codex_home, &event_log_path(&rollout_path)).await?;
+    assert!(logged.iter().any(|line| {
+        line.event.id == correlated && line.event.correlation_id.as_deref() == Some(CORRELATION_ID)
+    }));
+
+    Ok(())
+}
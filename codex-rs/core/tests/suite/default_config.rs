@@ -0,0 +1,138 @@
+use anyhow::Result;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::built_in_model_providers;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+const BASE_MODEL: &str = "gpt-5.1";
+const OVERRIDE_MODEL: &str = "gpt-5.1-codex";
+
+/// Run one turn in `conversation`, shut it down, and return the model its
+/// rollout recorded for that turn along with the model it was sent to.
+async fn recorded_model(
+    server: &wiremock::MockServer,
+    conversation: NewConversation,
+) -> Result<(Option<String>, String)> {
+    let response = mount_sse_once(
+        server,
+        sse(vec![ev_response_created("resp"), ev_completed("resp")]),
+    )
+    .await;
+    let codex = conversation.conversation;
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+
+    let recorded =
+        RolloutRecorder::get_rollout_history(&conversation.session_configured.rollout_path)
+            .await?
+            .get_rollout_items()
+            .into_iter()
+            .find_map(|item| match item {
+                RolloutItem::TurnContext(turn_context) => Some(turn_context.model),
+                _ => None,
+            });
+    let sent = response.single_request().body_json()["model"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    Ok((recorded, sent))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn conversations_start_from_the_default_config_with_overrides() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let codex_home = TempDir::new()?;
+    let mut base = load_default_config_for_test(&codex_home).await;
+    base.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    base.model = Some(BASE_MODEL.to_string());
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("Test API Key"),
+        base.model_provider.clone(),
+        base.codex_home.clone(),
+    );
+    manager.set_default_config(base);
+
+    let default = manager.new_conversation_default().await?;
+    assert_eq!(default.session_configured.model, BASE_MODEL);
+    let overridden = manager
+        .new_conversation_with(|config| config.model = Some(OVERRIDE_MODEL.to_string()))
+        .await?;
+    assert_eq!(overridden.session_configured.model, OVERRIDE_MODEL);
+
+    assert_eq!(
+        recorded_model(&server, default).await?,
+        (Some(BASE_MODEL.to_string()), BASE_MODEL.to_string())
+    );
+    assert_eq!(
+        recorded_model(&server, overridden).await?,
+        (Some(OVERRIDE_MODEL.to_string()), OVERRIDE_MODEL.to_string())
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn invalid_override_fails_before_spawn() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let base = load_default_config_for_test(&codex_home).await;
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("Test API Key"),
+        base.model_provider.clone(),
+        base.codex_home.clone(),
+    );
+
+    let missing = codex_home.path().join("missing");
+    let Err(err) = manager
+        .new_conversation_with(|config| config.cwd = missing.clone())
+        .await
+    else {
+        panic!("a manager without a default config should not spawn");
+    };
+    assert!(matches!(err, CodexErr::UnsupportedOperation(_)), "{err}");
+
+    manager.set_default_config(base);
+    let Err(err) = manager
+        .new_conversation_with(|config| config.cwd = missing.clone())
+        .await
+    else {
+        panic!("a missing working directory should be rejected");
+    };
+    assert!(
+        matches!(err, CodexErr::InvalidWorkingDirectory { ref path, .. } if *path == missing),
+        "{err}"
+    );
+    assert_eq!(manager.failed_spawn_stats().discarded, 0);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use codex_core::delta_coalescing::DeltaCoalescingPolicy;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::items::TurnItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+const DELTAS: usize = 500;
+
+/// What the client sees of an answer streamed as [`DELTAS`] one-character
+/// deltas: the text of each delta event and whether the message item
+/// completed after all of them, along with the answer.
+async fn stream_answer(policy: DeltaCoalescingPolicy) -> Result<(Vec<String>, bool, String)> {
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(move |config| config.delta_coalescing = policy)
+        .build(&server)
+        .await?;
+
+    let answer: String = ('a'..='z').cycle().take(DELTAS).collect();
+    let mut events = vec![
+        ev_response_created("resp-1"),
+        ev_message_item_added("msg-1", ""),
+    ];
+    events.extend(
+        answer
+            .chars()
+            .map(|ch| ev_output_text_delta(&ch.to_string())),
+    );
+    events.push(ev_assistant_message("msg-1", &answer));
+    events.push(ev_completed("resp-1"));
+    mount_sse_once(&server, sse(events)).await;
+
+    let mut deltas = Vec::new();
+    let mut completed_after_deltas = false;
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "stream a long answer".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        match event {
+            EventMsg::AgentMessageContentDelta(delta) => {
+                completed_after_deltas = false;
+                deltas.push(delta.delta.clone());
+            }
+            EventMsg::ItemCompleted(item) if matches!(item.item, TurnItem::AgentMessage(_)) => {
+                completed_after_deltas = true;
+            }
+            _ => {}
+        }
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    Ok((deltas, completed_after_deltas, answer))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn deltas_pass_through_by_default() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (deltas, completed_after_deltas, answer) =
+        stream_answer(DeltaCoalescingPolicy::default()).await?;
+
+    assert_eq!(deltas.len(), DELTAS);
+    assert_eq!(deltas.concat(), answer);
+    assert!(completed_after_deltas);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn deltas_are_joined_up_to_the_byte_limit() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (deltas, completed_after_deltas, answer) = stream_answer(DeltaCoalescingPolicy {
+        max_latency_ms: 60_000,
+        max_bytes: 50,
+        flush_on_newline: false,
+    })
+    .await?;
+
+    assert_eq!(deltas.len(), DELTAS / 50);
+    assert!(deltas.iter().all(|delta| delta.len() == 50), "{deltas:?}");
+    assert_eq!(deltas.concat(), answer);
+    assert!(completed_after_deltas);
+    Ok(())
+}
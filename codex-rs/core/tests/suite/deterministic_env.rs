@@ -0,0 +1,92 @@
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::runtime_env::RuntimeEnv;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+fn done_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+/// Run the scripted session in a fresh deterministic env and return its
+/// rollout, removing the file so the next run can write it again.
+async fn scripted_run(test: &TestCodex) -> Result<(String, Vec<u8>)> {
+    let manager = &test.conversation_manager;
+    manager.set_runtime_env(RuntimeEnv::deterministic());
+    let NewConversation {
+        conversation_id,
+        conversation,
+        ..
+    } = manager.new_conversation(test.config.clone()).await?;
+
+    for prompt in ["first", "second"] {
+        conversation
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text {
+                    text: prompt.to_string(),
+                }],
+                allow_sensitive: false,
+            })
+            .await?;
+        wait_for_event(&conversation, |event| {
+            matches!(event, EventMsg::TaskComplete(_))
+        })
+        .await;
+    }
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(&conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    manager.remove_conversation(&conversation_id).await;
+
+    let path = conversation.rollout_path();
+    let rollout = std::fs::read(&path)?;
+    std::fs::remove_file(&path)?;
+    Ok((path.display().to_string(), rollout))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn deterministic_runs_record_identical_rollouts() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    // Both runs are answered alike.
+    mount_sse_sequence(
+        &server,
+        ["1", "2", "1", "2"]
+            .into_iter()
+            .map(done_response)
+            .collect(),
+    )
+    .await;
+
+    let (first_path, first) = scripted_run(&test).await?;
+    let (second_path, second) = scripted_run(&test).await?;
+
+    assert_eq!(first_path, second_path);
+    assert!(first_path.ends_with("00000000-0000-0000-0000-000000000001.jsonl"));
+    let text = String::from_utf8(first.clone())?;
+    assert!(text.contains("\"timestamp\":\"2030-01-01T00:00:00.000Z\""));
+    assert_eq!(text, String::from_utf8(second.clone())?);
+    assert!(first == second, "rollouts differ byte for byte");
+
+    Ok(())
+}
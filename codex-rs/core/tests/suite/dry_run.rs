@@ -0,0 +1,118 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::config_types::ExecutionMode;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dry_run_plans_tool_calls_without_running_them() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let command = "echo planned > created.txt";
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_shell_command_call("call-dry", command),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let after_dry_run = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-2"),
+            ev_assistant_message("msg-1", "I would create created.txt"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.execution_mode = ExecutionMode::DryRun;
+        })
+        .build(&server)
+        .await?;
+    let created = test.cwd.path().join("created.txt");
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "create created.txt".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let planned =
+        wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::PlannedToolCall(_))).await;
+    let EventMsg::PlannedToolCall(planned) = planned else {
+        unreachable!("filtered above");
+    };
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(planned.call_id, "call-dry");
+    assert_eq!(planned.name, "shell_command");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&planned.arguments)?,
+        json!({ "command": command })
+    );
+    assert!(!created.exists(), "dry run must not execute the command");
+    let output = after_dry_run
+        .single_request()
+        .function_call_output_text("call-dry")
+        .expect("skipped call should be answered");
+    assert!(output.contains("dry run"), "unexpected output: {output}");
+
+    // Leaving dry-run mode mid-session lets the next call run.
+    test.codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            execution_mode: Some(ExecutionMode::Normal),
+            sampling: None,
+            offline: None,
+        })
+        .await?;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-3"),
+            ev_shell_command_call("call-run", command),
+            ev_completed("resp-3"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-4"),
+            ev_assistant_message("msg-2", "created"),
+            ev_completed("resp-4"),
+        ]),
+    )
+    .await;
+
+    test.submit_turn("now really create it").await?;
+
+    assert!(created.exists(), "normal mode should run the command");
+
+    Ok(())
+}
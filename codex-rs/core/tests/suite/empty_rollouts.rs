@@ -0,0 +1,180 @@
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::InitialHistory;
+use codex_protocol::ConversationId;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+const RECORDED_INSTRUCTIONS: &str = "recorded instructions";
+
+fn meta_line(id: ConversationId, cwd: &Path) -> Value {
+    json!({
+        "timestamp": "2030-01-01T00:00:00.000Z",
+        "type": "session_meta",
+        "payload": {
+            "id": id.to_string(),
+            "timestamp": "2030-01-01T00:00:00.000Z",
+            "cwd": cwd,
+            "originator": "codex_exec",
+            "cli_version": "0.0.0",
+            "instructions": RECORDED_INSTRUCTIONS,
+            "source": "exec",
+            "model_provider": "openai",
+        },
+    })
+}
+
+fn message_line(role: &str, content_type: &str, text: &str) -> Value {
+    json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": role,
+            "content": [{ "type": content_type, "text": text }],
+        },
+    })
+}
+
+fn write_rollout(dir: &Path, lines: &[Value]) -> Result<PathBuf> {
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+fn session_meta_lines(path: &Path) -> Result<Vec<Value>> {
+    let mut metas = Vec::new();
+    for line in std::fs::read_to_string(path)?.lines() {
+        let value: Value = serde_json::from_str(line)?;
+        if value["type"] == "session_meta" {
+            metas.push(value);
+        }
+    }
+    Ok(metas)
+}
+
+fn answer() -> String {
+    sse(vec![
+        ev_assistant_message("msg-1", "done"),
+        ev_completed("resp-1"),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn zero_byte_rollout_is_corrupt() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(fixtures.path(), &[])?;
+
+    let err = RolloutRecorder::get_rollout_history(&path)
+        .await
+        .expect_err("a zero-byte rollout does not load");
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let server = start_mock_server().await;
+    let home = Arc::new(TempDir::new()?);
+    assert!(test_codex().resume(&server, home, path).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn meta_only_rollout_resumes_as_a_new_session_with_its_id() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let id = ConversationId::new();
+    let path = write_rollout(fixtures.path(), &[meta_line(id, fixtures.path())])?;
+
+    let history = RolloutRecorder::get_rollout_history(&path).await?;
+    let InitialHistory::ResumedEmpty(resumed) = history else {
+        panic!("expected an empty resumed history, got {history:?}");
+    };
+    assert_eq!(resumed.conversation_id, id);
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(&server, answer()).await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex().resume(&server, home, path.clone()).await?;
+    assert_eq!(test.session_configured.session_id, id);
+    assert_eq!(test.session_configured.rollout_path, path);
+
+    test.submit_turn("first turn").await?;
+    let texts = mock.single_request().message_input_texts("user");
+    assert!(
+        texts
+            .iter()
+            .any(|text| text.contains("<environment_context>")),
+        "{texts:?}"
+    );
+    assert!(texts.iter().any(|text| text == "first turn"), "{texts:?}");
+
+    let metas = session_meta_lines(&path)?;
+    assert_eq!(metas.len(), 1);
+    assert_eq!(metas[0]["payload"]["id"], json!(id.to_string()));
+    assert_eq!(
+        metas[0]["payload"]["instructions"],
+        json!(RECORDED_INSTRUCTIONS)
+    );
+    assert_eq!(metas[0]["payload"]["cwd"], json!(fixtures.path()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rollout_with_a_turn_resumes_its_history() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let id = ConversationId::new();
+    let path = write_rollout(
+        fixtures.path(),
+        &[
+            meta_line(id, fixtures.path()),
+            message_line("user", "input_text", "earlier turn"),
+            message_line("assistant", "output_text", "earlier answer"),
+        ],
+    )?;
+
+    let history = RolloutRecorder::get_rollout_history(&path).await?;
+    let InitialHistory::Resumed(resumed) = history else {
+        panic!("expected a resumed history, got {history:?}");
+    };
+    assert_eq!(resumed.conversation_id, id);
+    assert_eq!(resumed.history.len(), 3);
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(&server, answer()).await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex().resume(&server, home, path).await?;
+    assert_eq!(test.session_configured.session_id, id);
+
+    test.submit_turn("next turn").await?;
+    let texts = mock.single_request().message_input_texts("user");
+    assert_eq!(
+        texts
+            .iter()
+            .filter(|text| !text.starts_with('<'))
+            .cloned()
+            .collect::<Vec<_>>(),
+        vec!["earlier turn".to_string(), "next turn".to_string()]
+    );
+
+    Ok(())
+}
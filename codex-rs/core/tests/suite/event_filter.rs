@@ -0,0 +1,114 @@
+#![allow(clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::EventSubscription;
+use codex_core::protocol::EventMsg;
+use codex_protocol::event_filter::EventCategory;
+use codex_protocol::event_filter::EventFilter;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_reasoning_item;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+
+/// The events of `subscription` up to and including the end of the turn.
+async fn turn_events(subscription: &EventSubscription) -> Vec<EventMsg> {
+    let mut events = Vec::new();
+    loop {
+        let event = subscription
+            .next_event()
+            .await
+            .expect("subscription ended before the turn");
+        let done = matches!(event.msg, EventMsg::TaskComplete(_));
+        events.push(event.msg);
+        if done {
+            return events;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn subscriptions_only_see_the_events_their_filter_accepts() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_reasoning_item("reasoning-1", &["looking around"], &[]),
+                ev_shell_command_call("call-1", "echo filtered"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_message_item_added("msg-1", ""),
+                ev_output_text_delta("all "),
+                ev_output_text_delta("done"),
+                ev_assistant_message("msg-1", "all done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    let content = test
+        .codex
+        .subscribe_filtered(EventFilter::only([EventCategory::Content]).with_collapsed_deltas());
+    let everything = test.codex.subscribe_filtered(EventFilter::all());
+
+    // The main stream is read as usual next to the subscriptions.
+    test.submit_turn("run something").await?;
+
+    let filtered = turn_events(&content).await;
+    assert!(
+        filtered
+            .iter()
+            .all(|msg| EventCategory::of(msg) == EventCategory::Content
+                || matches!(msg, EventMsg::TaskComplete(_))),
+        "{filtered:?}"
+    );
+    for dropped in [
+        "agent_reasoning",
+        "agent_message_delta",
+        "exec_command_begin",
+        "token_count",
+        "task_started",
+    ] {
+        assert!(
+            !filtered.iter().any(|msg| msg.to_string() == dropped),
+            "{dropped} in {filtered:?}"
+        );
+    }
+    for kept in ["exec_command_end", "agent_message", "task_complete"] {
+        assert!(
+            filtered.iter().any(|msg| msg.to_string() == kept),
+            "{kept} missing from {filtered:?}"
+        );
+    }
+
+    let all = turn_events(&everything).await;
+    for kept in [
+        "agent_reasoning",
+        "agent_message_delta",
+        "exec_command_begin",
+        "token_count",
+    ] {
+        assert!(
+            all.iter().any(|msg| msg.to_string() == kept),
+            "{kept} missing from {all:?}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::rollout::EventLogPolicy;
+use codex_core::rollout::event_log_path;
+use codex_core::rollout::read_event_log;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn event_log_records_deltas_that_rollout_omits() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_message_item_added("msg-1", ""),
+            ev_output_text_delta("hel"),
+            ev_output_text_delta("lo"),
+            ev_assistant_message("msg-1", "hello"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.event_log = Some(EventLogPolicy::default());
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("say hello").await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    let rollout_path = test.codex.rollout_path();
+    let events = read_event_log(&test.config.codex_home, &event_log_path(&rollout_path)).await?;
+    let Some(EventMsg::SessionConfigured(configured)) = events.first().map(|line| &line.event.msg)
+    else {
+        panic!("event log should start with SessionConfigured");
+    };
+    assert_eq!(configured.rollout_path, rollout_path);
+    let raw_log = std::fs::read_to_string(event_log_path(&rollout_path))?;
+    assert!(!raw_log.contains(&*rollout_path.to_string_lossy()));
+    let deltas: Vec<String> = events
+        .iter()
+        .filter_map(|line| match &line.event.msg {
+            EventMsg::AgentMessageDelta(ev) => Some(ev.delta.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(deltas, vec!["hel".to_string(), "lo".to_string()]);
+    assert!(
+        events
+            .iter()
+            .any(|line| matches!(line.event.msg, EventMsg::TaskComplete(_)))
+    );
+
+    let rollout = std::fs::read_to_string(&rollout_path)?;
+    assert!(!rollout.contains("agent_message_delta"));
+
+    Ok(())
+}
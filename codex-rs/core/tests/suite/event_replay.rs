@@ -0,0 +1,96 @@
+use anyhow::Result;
+use codex_core::event_replay::ReplayGap;
+use codex_core::event_replay::SequencedEvent;
+use codex_core::protocol::EventMsg;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+/// Mount a one-message reply for the next turn.
+async fn mount_reply(server: &MockServer) {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+}
+
+fn seqs(events: &[SequencedEvent]) -> Vec<u64> {
+    events.iter().map(|event| event.seq).collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reconnecting_client_replays_everything_it_missed() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    mount_reply(&server).await;
+    let delivered = test.submit_input("hello").await?;
+
+    // Numbers continue after `SessionConfigured`, which is event 1.
+    let last = delivered.len() as u64 + 1;
+
+    let (replayed, current) = test.codex.events_since(0).expect("nothing evicted");
+    assert_eq!(current, last);
+    assert_eq!(seqs(&replayed), (1..=last).collect::<Vec<_>>());
+    assert!(matches!(
+        replayed[0].event.msg,
+        EventMsg::SessionConfigured(_)
+    ));
+    assert_eq!(
+        replayed[1..]
+            .iter()
+            .map(|event| serde_json::to_value(&event.event.msg))
+            .collect::<Result<Vec<_>, _>>()?,
+        delivered
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn evicted_events_are_reported_as_a_gap() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.event_replay.max_events = 3)
+        .build(&server)
+        .await?;
+    mount_reply(&server).await;
+    let delivered = test.submit_input("hello").await?;
+    let last = delivered.len() as u64 + 1;
+    assert!(last > 4, "a turn emits more events than the replay keeps");
+
+    assert_eq!(
+        test.codex.events_since(1).unwrap_err(),
+        ReplayGap {
+            earliest_available: last - 2
+        }
+    );
+
+    let (replayed, current) = test
+        .codex
+        .events_since(last - 3)
+        .expect("the last three events are retained");
+    assert_eq!(current, last);
+    assert_eq!(seqs(&replayed), vec![last - 2, last - 1, last]);
+    assert!(matches!(replayed[2].event.msg, EventMsg::TaskComplete(_)));
+
+    Ok(())
+}
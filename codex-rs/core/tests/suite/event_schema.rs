@@ -0,0 +1,251 @@
+//! Guards the `Event` wire format that external clients deserialize.
+//!
+//! `tests/fixtures/event_schema.json` records the schema of every `EventMsg`
+//! variant for the current `PROTOCOL_VERSION`, with the definitions it
+//! refers to inlined. New variants and new optional fields may be added
+//! freely; removing or changing a recorded one, or making a field required,
+//! requires bumping `PROTOCOL_VERSION`. Rerun with
+//! `UPDATE_EVENT_SCHEMA_SNAPSHOT=1` to record the current schemas.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_protocol::protocol::PROTOCOL_VERSION;
+use codex_protocol::schema::events_schema;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+const UPDATE_ENV: &str = "UPDATE_EVENT_SCHEMA_SNAPSHOT";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct EventSchemaSnapshot {
+    protocol_version: u32,
+    /// Serialized `type` tag of each variant to its resolved schema.
+    variants: BTreeMap<String, Value>,
+}
+
+fn snapshot_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/event_schema.json")
+}
+
+/// `schema` with the `$ref`s into `definitions` inlined, `allOf` parts merged
+/// and descriptions dropped. A definition that refers to itself is kept as a
+/// `$ref` inside its own expansion.
+fn resolve(schema: &Value, definitions: &Value, expanding: &mut Vec<String>) -> Value {
+    match schema {
+        Value::Object(object) => {
+            let mut resolved = Map::new();
+            for (key, value) in object {
+                match key.as_str() {
+                    "description" | "title" => {}
+                    "$ref" => {
+                        let name = value
+                            .as_str()
+                            .and_then(|reference| reference.strip_prefix("#/definitions/"));
+                        match name {
+                            Some(name) if !expanding.iter().any(|expanded| expanded == name) => {
+                                expanding.push(name.to_string());
+                                let target = resolve(&definitions[name], definitions, expanding);
+                                expanding.pop();
+                                merge(&mut resolved, target);
+                            }
+                            _ => {
+                                resolved.insert(key.clone(), value.clone());
+                            }
+                        }
+                    }
+                    "allOf" => {
+                        for part in value.as_array().into_iter().flatten() {
+                            merge(&mut resolved, resolve(part, definitions, expanding));
+                        }
+                    }
+                    _ => {
+                        let mut single = Map::new();
+                        single.insert(key.clone(), resolve(value, definitions, expanding));
+                        merge(&mut resolved, Value::Object(single));
+                    }
+                }
+            }
+            Value::Object(resolved)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| resolve(item, definitions, expanding))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Merge the object `from` into `into`: properties and required fields add
+/// up, other keys are replaced.
+fn merge(into: &mut Map<String, Value>, from: Value) {
+    let Value::Object(from) = from else {
+        return;
+    };
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(Value::Object(properties)), Value::Object(added)) if key == "properties" => {
+                properties.extend(added);
+            }
+            (Some(Value::Array(required)), Value::Array(added)) if key == "required" => {
+                for name in added {
+                    if !required.contains(&name) {
+                        required.push(name);
+                    }
+                }
+            }
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+fn required(schema: &Map<String, Value>) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+/// The changes from `recorded` to `current` that clients could fail to
+/// deserialize, at `path`. Properties that are new and optional are not
+/// among them.
+fn breaking_changes(path: &str, recorded: &Value, current: &Value, out: &mut Vec<String>) {
+    match (recorded, current) {
+        (Value::Object(recorded), Value::Object(current)) => {
+            for (key, value) in recorded {
+                let path = format!("{path}.{key}");
+                match (key.as_str(), current.get(key)) {
+                    ("required", _) => {}
+                    ("properties", Some(Value::Object(properties))) => {
+                        for (name, property) in value.as_object().into_iter().flatten() {
+                            match properties.get(name) {
+                                Some(current) => {
+                                    breaking_changes(
+                                        &format!("{path}.{name}"),
+                                        property,
+                                        current,
+                                        out,
+                                    );
+                                }
+                                None => out.push(format!("{path}.{name} was removed")),
+                            }
+                        }
+                    }
+                    (_, Some(current)) => breaking_changes(&path, value, current, out),
+                    (_, None) => out.push(format!("{path} was removed")),
+                }
+            }
+            for name in required(recorded).symmetric_difference(&required(current)) {
+                out.push(format!(
+                    "{path}.properties.{name} changed whether it is required"
+                ));
+            }
+            for key in current.keys() {
+                if !recorded.contains_key(key) && key != "properties" && key != "required" {
+                    out.push(format!("{path}.{key} was added"));
+                }
+            }
+        }
+        (Value::Array(recorded), Value::Array(current)) if recorded.len() == current.len() => {
+            for (index, (recorded, current)) in recorded.iter().zip(current).enumerate() {
+                breaking_changes(&format!("{path}[{index}]"), recorded, current, out);
+            }
+        }
+        _ if recorded == current => {}
+        _ => out.push(format!("{path} changed")),
+    }
+}
+
+fn current_snapshot() -> Result<EventSchemaSnapshot> {
+    let schema = serde_json::to_value(events_schema())?;
+    let definitions = &schema["definitions"];
+    let variants = definitions["EventMsg"]["oneOf"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("EventMsg schema should be a oneOf"))?;
+
+    let mut schemas = BTreeMap::new();
+    for variant in variants {
+        let resolved = resolve(variant, definitions, &mut vec!["EventMsg".to_string()]);
+        let tag = resolved["properties"]["type"]["enum"][0]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("EventMsg variant without a type tag: {variant}"))?;
+        schemas.insert(tag.to_string(), resolved);
+    }
+    Ok(EventSchemaSnapshot {
+        protocol_version: PROTOCOL_VERSION,
+        variants: schemas,
+    })
+}
+
+#[test]
+fn event_variants_match_snapshot() -> Result<()> {
+    let current = current_snapshot()?;
+    let recorded: EventSchemaSnapshot =
+        serde_json::from_str(&std::fs::read_to_string(snapshot_path())?)?;
+
+    let mut breaking = Vec::new();
+    for (tag, schema) in &recorded.variants {
+        match current.variants.get(tag) {
+            Some(current) => breaking_changes(tag, schema, current, &mut breaking),
+            None => breaking.push(format!("{tag} was removed")),
+        }
+    }
+    let version_bumped = recorded.protocol_version != current.protocol_version;
+    assert!(
+        breaking.is_empty() || version_bumped,
+        "the wire shape of existing event variants changed: {breaking:#?}; \
+         bump PROTOCOL_VERSION and rerun with {UPDATE_ENV}=1"
+    );
+
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::write(
+            snapshot_path(),
+            format!("{}\n", serde_json::to_string_pretty(&current)?),
+        )?;
+        return Ok(());
+    }
+
+    assert_eq!(
+        recorded.protocol_version, current.protocol_version,
+        "PROTOCOL_VERSION changed; rerun with {UPDATE_ENV}=1 to record the new schemas"
+    );
+    // What is left are additions, which need no new version but are
+    // recorded all the same.
+    assert!(
+        recorded == current,
+        "event variants or optional fields were added; rerun with {UPDATE_ENV}=1"
+    );
+    Ok(())
+}
+
+#[test]
+fn events_schema_declares_protocol_version() -> Result<()> {
+    let schema = serde_json::to_value(events_schema())?;
+    assert_eq!(schema["x-protocol-version"], Value::from(PROTOCOL_VERSION));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_configured_reports_protocol_version() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    assert_eq!(test.session_configured.protocol_version, PROTOCOL_VERSION);
+    Ok(())
+}
@@ -80,6 +80,9 @@ async fn execpolicy_blocks_shell_invocation() -> Result<()> {
             model: session_model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
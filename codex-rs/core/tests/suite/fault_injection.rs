@@ -0,0 +1,296 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::ModelProviderInfo;
+use codex_core::fault_injection::FaultPlan;
+use codex_core::fault_injection::FaultyProvider;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RetrySubsystem;
+use codex_core::protocol::RetryingEvent;
+use codex_core::retry::RetryPolicy;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+fn upstream(server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..codex_core::built_in_model_providers()["openai"].clone()
+    }
+}
+
+fn reply(id: &str) -> String {
+    sse(vec![
+        ev_response_created(&format!("resp-{id}")),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(&format!("resp-{id}")),
+    ])
+}
+
+async fn submit(conversation: &CodexConversation, text: &str) -> Result<()> {
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_provider_request_is_retried() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_once(&server, reply("1")).await;
+    let plan = FaultPlan::new().fail_provider_request(1, 500);
+    let provider = FaultyProvider::start(upstream(&server), &plan).await?;
+    let provider_info: ModelProviderInfo = (&provider).into();
+    let test = test_codex()
+        .with_config(move |config| config.model_provider = provider_info)
+        .build(&server)
+        .await?;
+
+    test.submit_turn("hello").await?;
+
+    // The failed request never reached the provider; its retry did.
+    assert_eq!(responses.requests().len(), 1);
+    assert_eq!(plan.remaining_faults(), Vec::new());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn model_requests_are_retried_as_the_profile_allows() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_once(&server, reply("1")).await;
+    let plan = FaultPlan::new()
+        .fail_provider_request(1, 500)
+        .fail_provider_request(2, 500);
+    let provider = FaultyProvider::start(
+        ModelProviderInfo {
+            request_max_retries: Some(0),
+            stream_max_retries: Some(5),
+            ..upstream(&server)
+        },
+        &plan,
+    )
+    .await?;
+    let provider_info: ModelProviderInfo = (&provider).into();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.model_provider = provider_info;
+            // One retry, fewer than the provider allows.
+            config.retry.model_requests = RetryPolicy {
+                max_retries: Some(1),
+                base_delay: Duration::from_millis(1),
+                jitter: 0.0,
+                ..RetryPolicy::model_requests()
+            };
+        })
+        .build(&server)
+        .await?;
+
+    submit(&test.codex, "hello").await?;
+    let mut retries = Vec::new();
+    loop {
+        let event =
+            tokio::time::timeout(Duration::from_secs(10), test.codex.next_event()).await??;
+        match event.msg {
+            EventMsg::Retrying(retry) => retries.push(retry),
+            EventMsg::Error(_) => break,
+            EventMsg::TaskComplete(_) => anyhow::bail!("the turn succeeded"),
+            _ => {}
+        }
+    }
+
+    assert_eq!(
+        retries,
+        vec![RetryingEvent {
+            subsystem: RetrySubsystem::ModelRequests,
+            attempt: 1,
+            delay_ms: 1,
+        }]
+    );
+    assert_eq!(responses.requests().len(), 0);
+    assert_eq!(plan.remaining_faults(), Vec::new());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stalled_stream_is_reconnected() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_output_text_delta("par"),
+                ev_output_text_delta("tial"),
+                ev_completed("resp-1"),
+            ]),
+            reply("2"),
+        ],
+    )
+    .await;
+    let plan = FaultPlan::new().stall_stream(1, 1);
+    let provider = FaultyProvider::start(upstream(&server), &plan).await?;
+    let provider_info = ModelProviderInfo {
+        stream_idle_timeout_ms: Some(200),
+        ..(&provider).into()
+    };
+    let test = test_codex()
+        .with_config(move |config| config.model_provider = provider_info)
+        .build(&server)
+        .await?;
+
+    submit(&test.codex, "hello").await?;
+    let reconnecting = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::StreamError(_))
+    })
+    .await;
+    let EventMsg::StreamError(reconnecting) = reconnecting else {
+        unreachable!("matched a stream error");
+    };
+    assert!(
+        reconnecting.message.starts_with("Reconnecting... 1/"),
+        "{}",
+        reconnecting.message
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(responses.requests().len(), 2);
+    assert_eq!(plan.remaining_faults(), Vec::new());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_rollout_append_warns_that_recording_stopped() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    mount_sse_once(&server, reply("1")).await;
+    // The session meta line is the first append; everything after it fails.
+    let plan = FaultPlan::new().fail_rollout_append(2);
+    test.conversation_manager.inject_rollout_faults(&plan);
+    let new = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?;
+
+    submit(&new.conversation, "hello").await?;
+    let warning = wait_for_event(&new.conversation, |event| {
+        matches!(event, EventMsg::Warning(_))
+    })
+    .await;
+    let EventMsg::Warning(warning) = warning else {
+        unreachable!("matched a warning");
+    };
+    assert!(
+        warning
+            .message
+            .starts_with("The session is no longer being recorded:"),
+        "{}",
+        warning.message
+    );
+    assert_eq!(plan.remaining_faults(), Vec::new());
+
+    let rollout = std::fs::read_to_string(&new.session_configured.rollout_path)?;
+    assert_eq!(rollout.lines().count(), 1, "{rollout}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn full_disk_degrades_recording_until_it_has_room() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    mount_sse_once(&server, reply("1")).await;
+    // The session meta line is the first append, and the second finds the
+    // disk full; the retry finds room again.
+    let plan = FaultPlan::new().fill_rollout_disk(2, 1);
+    test.conversation_manager.inject_rollout_faults(&plan);
+    let new = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?;
+
+    submit(&new.conversation, "hello").await?;
+    let mut degraded = None;
+    let mut completed = false;
+    wait_for_event(&new.conversation, |event| {
+        match event {
+            EventMsg::PersistenceDegraded(event) => degraded = Some(event.clone()),
+            EventMsg::TaskComplete(_) => completed = true,
+            _ => {}
+        }
+        degraded.is_some() && completed
+    })
+    .await;
+    let degraded = degraded.expect("degraded");
+    assert!(
+        degraded.reason.contains("No space left on device"),
+        "{}",
+        degraded.reason
+    );
+    assert_eq!(degraded.last_persisted_item, Some(1));
+
+    // Reading the transcript flushes the rollout, which retries the disk.
+    let transcript = new.conversation.transcript().await?;
+    let recovered = wait_for_event(&new.conversation, |event| {
+        matches!(event, EventMsg::PersistenceRecovered(_))
+    })
+    .await;
+    let EventMsg::PersistenceRecovered(recovered) = recovered else {
+        unreachable!("matched a recovery");
+    };
+    assert!(recovered.flushed_items > 0);
+    assert_eq!((recovered.dropped_events, recovered.dropped_items), (0, 0));
+    assert_eq!(plan.remaining_faults(), Vec::new());
+
+    let texts: Vec<String> = transcript
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::Message { content, .. } => match content.as_slice() {
+                [ContentItem::InputText { text }] | [ContentItem::OutputText { text }] => {
+                    Some(text.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert!(texts.iter().any(|text| text == "hello"), "{texts:?}");
+    assert!(texts.iter().any(|text| text == "done"), "{texts:?}");
+
+    Ok(())
+}
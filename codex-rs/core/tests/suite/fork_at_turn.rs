@@ -0,0 +1,142 @@
+#![allow(clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::error::CodexErr;
+use codex_core::protocol::CheckpointId;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::TurnId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+const PROMPTS: [&str; 5] = ["one", "two", "three", "four", "five"];
+
+fn assistant_turn(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), "ok"),
+        ev_completed(id),
+    ])
+}
+
+/// The id the `TaskStarted` among `events` reported.
+fn turn_id(events: &[EventMsg]) -> TurnId {
+    events
+        .iter()
+        .find_map(|event| match event {
+            EventMsg::TaskStarted(started) => started.turn_id,
+            _ => None,
+        })
+        .expect("user turns report their id")
+}
+
+/// The prompts among the user messages of `items`.
+fn prompts(items: &[ResponseItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::Message { role, content, .. } if role == "user" => {
+                content.iter().find_map(|content| match content {
+                    ContentItem::InputText { text } if PROMPTS.contains(&text.as_str()) => {
+                        Some(text.clone())
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+async fn rollback(codex: &CodexConversation, checkpoint: CheckpointId) {
+    codex
+        .rollback_to_checkpoint(checkpoint)
+        .await
+        .expect("submit rollback");
+    wait_for_event(codex, |event| {
+        matches!(event, EventMsg::CheckpointRolledBack(_))
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn forking_at_a_turn_id_survives_rollbacks() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        (1..=5)
+            .map(|turn| assistant_turn(&format!("resp-{turn}")))
+            .collect(),
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let codex = &test.codex;
+
+    let one = turn_id(&test.submit_input("one").await?);
+    let after_one = codex.checkpoint("after one").await?;
+    let two = turn_id(&test.submit_input("two").await?);
+    rollback(codex, after_one).await;
+    let three = turn_id(&test.submit_input("three").await?);
+    let four = turn_id(&test.submit_input("four").await?);
+    assert!(one < two && two < three && three < four, "ids increase");
+
+    // A client picks the turn to fork at from what it shows.
+    let turns = codex.transcript_turns().await?;
+    let shown: Vec<Option<TurnId>> = turns
+        .iter()
+        .filter(|turn| !prompts(&turn.items).is_empty())
+        .map(|turn| turn.turn_id)
+        .collect();
+    assert_eq!(shown, vec![Some(one), Some(three), Some(four)]);
+    let ordinal = shown
+        .iter()
+        .position(|turn_id| *turn_id == Some(four))
+        .expect("four is shown");
+
+    // The history changes again before the client forks.
+    let after_four = codex.checkpoint("after four").await?;
+    test.submit_input("five").await?;
+    rollback(codex, after_four).await;
+
+    let manager = &test.conversation_manager;
+    let by_id = manager
+        .fork_conversation_at_turn(four, test.config.clone(), codex.rollout_path())
+        .await?;
+    assert_eq!(
+        prompts(&by_id.conversation.transcript().await?),
+        vec!["one", "three"]
+    );
+
+    // The rollout still records the rolled back turn, so the ordinal the
+    // client saw lands one turn early.
+    let by_ordinal = manager
+        .fork_conversation(ordinal, test.config.clone(), codex.rollout_path())
+        .await?;
+    assert_eq!(
+        prompts(&by_ordinal.conversation.transcript().await?),
+        vec!["one"]
+    );
+
+    let unknown = manager
+        .fork_conversation_at_turn(TurnId::new(99), test.config.clone(), codex.rollout_path())
+        .await;
+    assert!(
+        matches!(unknown, Err(CodexErr::UnknownTurn(turn_id)) if turn_id == TurnId::new(99)),
+        "{:?}",
+        unknown.err()
+    );
+
+    Ok(())
+}
@@ -1,19 +1,43 @@
+#![allow(clippy::expect_used)]
+
 use codex_core::CodexAuth;
 use codex_core::ConversationManager;
+use codex_core::ForkedChild;
 use codex_core::ModelProviderInfo;
 use codex_core::NewConversation;
 use codex_core::built_in_model_providers;
 use codex_core::parse_turn_item;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ForkConsistencyReport;
+use codex_core::protocol::ForkedEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::RolloutItem;
 use codex_core::protocol::RolloutLine;
 use codex_protocol::items::TurnItem;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
 use codex_protocol::user_input::UserInput;
 use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
 use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::StreamingSseServer;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use std::time::Duration;
 use tempfile::TempDir;
+use tokio::sync::oneshot;
 use wiremock::Mock;
 use wiremock::MockServer;
 use wiremock::ResponseTemplate;
@@ -74,6 +98,7 @@ async fn fork_conversation_twice_drops_to_first_message() {
                 items: vec![UserInput::Text {
                     text: text.to_string(),
                 }],
+                allow_sensitive: false,
             })
             .await
             .unwrap();
@@ -169,3 +194,332 @@ async fn fork_conversation_twice_drops_to_first_message() {
         serde_json::to_value(&expected_after_second).unwrap()
     );
 }
+
+/// Message texts recorded in the rollout at `path`, in order.
+fn rollout_message_texts(path: &std::path::Path) -> Vec<String> {
+    let text = std::fs::read_to_string(path).expect("read rollout file");
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+        .filter_map(|line| match line.item {
+            RolloutItem::ResponseItem(ResponseItem::Message { content, .. }) => Some(content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => Some(text),
+            ContentItem::InputImage { .. } => None,
+        })
+        .collect()
+}
+
+/// Completes a "first" turn, then starts a "second" turn whose response
+/// streams an assistant message but holds back `response.completed` until the
+/// returned sender fires.
+async fn streaming_second_turn() -> (TestCodex, StreamingSseServer, oneshot::Sender<()>) {
+    let (completion_tx, completion_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![
+        vec![StreamingSseChunk {
+            gate: None,
+            body: sse(vec![
+                ev_response_created("resp-1"),
+                ev_assistant_message("msg-1", "first answer"),
+                ev_completed("resp-1"),
+            ]),
+        }],
+        vec![
+            StreamingSseChunk {
+                gate: None,
+                body: sse(vec![
+                    ev_response_created("resp-2"),
+                    ev_assistant_message("msg-2", "partial answer"),
+                ]),
+            },
+            StreamingSseChunk {
+                gate: Some(completion_rx),
+                body: sse(vec![ev_completed("resp-2")]),
+            },
+        ],
+    ])
+    .await;
+
+    let test = test_codex()
+        .build_with_streaming_server(&server)
+        .await
+        .expect("build test codex");
+    test.submit_turn("first").await.expect("first turn");
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "second".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await
+        .expect("submit second turn");
+    wait_for_event(
+        &test.codex,
+        |ev| matches!(ev, EventMsg::AgentMessage(msg) if msg.message == "partial answer"),
+    )
+    .await;
+
+    (test, server, completion_tx)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fork_while_streaming_ends_at_last_completed_turn() {
+    skip_if_no_network!();
+
+    let (test, server, completion_tx) = streaming_second_turn().await;
+
+    let NewConversation {
+        conversation: by_id,
+        ..
+    } = test
+        .conversation_manager
+        .fork_conversation_by_id(
+            test.session_configured.session_id,
+            1,
+            test.config.clone(),
+            false,
+        )
+        .await
+        .expect("fork by id");
+    let NewConversation {
+        conversation: by_path,
+        ..
+    } = test
+        .conversation_manager
+        .fork_conversation(1, test.config.clone(), test.codex.rollout_path())
+        .await
+        .expect("fork by path");
+
+    for fork in [by_id, by_path] {
+        let texts = rollout_message_texts(&fork.rollout_path());
+        assert!(texts.iter().any(|text| text == "first answer"), "{texts:?}");
+        assert!(
+            !texts.iter().any(|text| text == "partial answer"),
+            "fork must not include the in-flight turn: {texts:?}"
+        );
+    }
+
+    let _ = completion_tx.send(());
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    server.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fork_waiting_for_turn_blocks_until_it_completes() {
+    skip_if_no_network!();
+
+    let (test, server, completion_tx) = streaming_second_turn().await;
+
+    let conversation_manager = test.conversation_manager.clone();
+    let conversation_id = test.session_configured.session_id;
+    let config = test.config.clone();
+    let fork = tokio::spawn(async move {
+        conversation_manager
+            .fork_conversation_by_id(conversation_id, 1, config, true)
+            .await
+    });
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!fork.is_finished(), "fork must wait for the running turn");
+
+    let _ = completion_tx.send(());
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let NewConversation { conversation, .. } =
+        fork.await.expect("fork task").expect("fork after turn");
+    let texts = rollout_message_texts(&conversation.rollout_path());
+    assert!(texts.iter().any(|text| text == "first answer"), "{texts:?}");
+    server.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn forks_are_listed_as_children_of_their_parent() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await.expect("build test codex");
+    for (turn, text) in ["first", "second", "third"].into_iter().enumerate() {
+        let id = format!("resp-{turn}");
+        mount_sse_once(
+            &server,
+            sse(vec![
+                ev_response_created(&id),
+                ev_assistant_message(&format!("msg-{turn}"), &format!("{text} answer")),
+                ev_completed(&id),
+            ]),
+        )
+        .await;
+        test.submit_turn(text).await.expect("turn");
+    }
+    let manager = &test.conversation_manager;
+    let parent_id = test.session_configured.session_id;
+    let parent_path = test.codex.rollout_path();
+
+    let mut forks = Vec::new();
+    for cut_ordinal in [1, 2] {
+        let fork = manager
+            .fork_conversation(cut_ordinal, test.config.clone(), parent_path.clone())
+            .await
+            .expect("fork");
+        let forked =
+            wait_for_event(&fork.conversation, |ev| matches!(ev, EventMsg::Forked(_))).await;
+        let EventMsg::Forked(forked) = forked else {
+            unreachable!("filtered above");
+        };
+        assert_eq!(
+            forked,
+            ForkedEvent {
+                parent_id,
+                child_id: fork.conversation_id,
+                cut_ordinal,
+            }
+        );
+        forks.push(fork);
+    }
+
+    let expected: Vec<_> = forks
+        .iter()
+        .zip([1, 2])
+        .map(|(fork, cut_ordinal)| (fork.conversation_id, cut_ordinal))
+        .collect();
+    let listed = |children: Vec<ForkedChild>| -> Vec<_> {
+        children
+            .into_iter()
+            .map(|child| {
+                assert_eq!(child.parent.conversation_id, parent_id);
+                assert_eq!(child.parent.rollout_path, parent_path);
+                (child.conversation_id, child.parent.cut_ordinal)
+            })
+            .collect()
+    };
+    assert_eq!(listed(manager.children_of(parent_id).await), expected);
+
+    // Once the forks are gone, their rollouts still name the parent.
+    for fork in &forks {
+        fork.conversation
+            .submit(Op::Shutdown)
+            .await
+            .expect("shutdown");
+        wait_for_event(&fork.conversation, |ev| {
+            matches!(ev, EventMsg::ShutdownComplete)
+        })
+        .await;
+        manager.remove_conversation(&fork.conversation_id).await;
+    }
+    let children = manager.children_of(parent_id).await;
+    assert_eq!(listed(children.clone()), expected);
+
+    // Forking the parent again at a listed ordinal keeps the same history.
+    let again = manager
+        .fork_conversation(
+            children[0].parent.cut_ordinal,
+            test.config.clone(),
+            parent_path.clone(),
+        )
+        .await
+        .expect("fork again");
+    assert_eq!(
+        rollout_message_texts(&again.conversation.rollout_path()),
+        rollout_message_texts(&children[0].path)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fork_reports_files_that_changed_since_they_were_read() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .build(&server)
+        .await
+        .expect("build test codex");
+    let notes = test.cwd.path().join("notes.txt");
+    let todo = test.cwd.path().join("todo.txt");
+    let unchanged = test.cwd.path().join("unchanged.txt");
+    for path in [&notes, &todo, &unchanged] {
+        std::fs::write(path, "first line\nsecond line\n").expect("write file");
+    }
+    let read = |call_id: &str, path: &std::path::Path| {
+        let arguments = serde_json::json!({ "file_path": path }).to_string();
+        ev_function_call(call_id, "read_file", &arguments)
+    };
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                read("call-notes", &notes),
+                read("call-todo", &todo),
+                read("call-unchanged", &unchanged),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-1", "read them"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_assistant_message("msg-2", "second answer"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+    test.submit_turn("read the files")
+        .await
+        .expect("first turn");
+    test.submit_turn("second").await.expect("second turn");
+
+    std::fs::write(&notes, "first line\nchanged line\n").expect("modify notes");
+    std::fs::remove_file(&todo).expect("delete todo");
+    let fork = test
+        .conversation_manager
+        .fork_conversation(1, test.config.clone(), test.codex.rollout_path())
+        .await
+        .expect("fork");
+
+    let expected = ForkConsistencyReport {
+        stale_files: vec![notes.clone()],
+        missing_files: vec![todo.clone()],
+    };
+    assert_eq!(fork.fork_consistency, Some(expected.clone()));
+    let stale = wait_for_event_match(&fork.conversation, |ev| match ev {
+        EventMsg::ForkedContextStale(report) => Some(report.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(stale, expected);
+
+    let texts = rollout_message_texts(&fork.conversation.rollout_path());
+    let notice = texts.last().expect("notice");
+    assert!(
+        notice.contains(&format!("{} (modified)", notes.display())),
+        "{notice}"
+    );
+    assert!(
+        notice.contains(&format!("{} (deleted)", todo.display())),
+        "{notice}"
+    );
+    assert!(!notice.contains("unchanged.txt"), "{notice}");
+
+    // Without the check, the fork carries no report and no notice.
+    let mut config = test.config.clone();
+    config.fork_consistency_check = false;
+    let unchecked = test
+        .conversation_manager
+        .fork_conversation(1, config, test.codex.rollout_path())
+        .await
+        .expect("unchecked fork");
+    assert_eq!(unchecked.fork_consistency, None);
+    assert_eq!(
+        rollout_message_texts(&unchecked.conversation.rollout_path()).last(),
+        Some(&"read them".to_string())
+    );
+}
@@ -0,0 +1,140 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::GitInfo;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+fn git(path: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .with_context(|| format!("failed to run git {args:?}"))?;
+    if output.status.success() {
+        return Ok(String::from_utf8(output.stdout)?.trim().to_string());
+    }
+    bail!("git {args:?} exited with {}", output.status);
+}
+
+fn init_git_repo(path: &Path) -> Result<()> {
+    git(path, &["init", "--initial-branch=main"])?;
+    git(path, &["config", "user.name", "Codex Tests"])?;
+    git(path, &["config", "user.email", "codex-tests@example.com"])?;
+    fs::write(path.join("lib.rs"), "fn main() {}\n")?;
+    git(path, &["add", "lib.rs"])?;
+    git(path, &["commit", "-m", "initial"])?;
+    Ok(())
+}
+
+fn replies(count: usize) -> Vec<String> {
+    (1..=count)
+        .map(|index| {
+            sse(vec![
+                ev_response_created(&format!("resp-{index}")),
+                ev_assistant_message(&format!("msg-{index}"), "done"),
+                ev_completed(&format!("resp-{index}")),
+            ])
+        })
+        .collect()
+}
+
+/// Shut `test` down and return the git context of its session meta and the
+/// contexts recorded by its turns.
+async fn recorded_contexts(test: &TestCodex) -> Result<(Option<GitInfo>, Vec<GitInfo>)> {
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let items = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items();
+    let meta = items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta_line) => Some(meta_line.git.clone()),
+        _ => None,
+    });
+    let turns = items
+        .into_iter()
+        .filter_map(|item| match item {
+            RolloutItem::GitContext(context) => Some(context),
+            _ => None,
+        })
+        .collect();
+    Ok((meta.flatten(), turns))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn commit_between_turns_records_one_git_context() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(&server, replies(3)).await;
+    // The repository exists before the session starts.
+    let test = test_codex()
+        .with_config(|config| init_git_repo(&config.cwd).expect("init git repo"))
+        .build(&server)
+        .await?;
+    let repo = test.cwd_path();
+    let initial = git(repo, &["rev-parse", "HEAD"])?;
+
+    // HEAD did not move since the session started.
+    test.submit_turn("look at lib.rs").await?;
+
+    fs::write(repo.join("lib.rs"), "fn main() { run() }\n")?;
+    git(repo, &["commit", "-am", "change lib.rs"])?;
+    let committed = git(repo, &["rev-parse", "HEAD"])?;
+    test.submit_turn("what now?").await?;
+    test.submit_turn("and now?").await?;
+
+    let current = test.codex.git_context().expect("git context");
+    assert_eq!(current.commit_hash.as_deref(), Some(committed.as_str()));
+
+    let (meta, turns) = recorded_contexts(&test).await?;
+    let meta = meta.expect("session meta git context");
+    assert_eq!(meta.commit_hash, Some(initial));
+    assert_eq!(meta.branch.as_deref(), Some("main"));
+    assert_eq!(meta.dirty, Some(false));
+    assert_eq!(
+        meta.root.map(|root| root.canonicalize()).transpose()?,
+        Some(repo.canonicalize()?)
+    );
+    assert_eq!(turns, vec![current]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn non_git_workspace_records_no_git_context() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(&server, replies(1)).await;
+    let test = test_codex().build(&server).await?;
+
+    test.submit_turn("hello").await?;
+
+    assert_eq!(test.codex.git_context(), None);
+    assert_eq!(recorded_contexts(&test).await?, (None, Vec::new()));
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::CodexConversation;
+use codex_core::NewConversation;
+use codex_core::ReadOnlyTranscript;
+use codex_core::RolloutRecorder;
+use codex_core::handoff::HandoffOptions;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::HandoffDocument;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutAnnotation;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::TurnFileChange;
+use codex_core::protocol::TurnFileChangeKind;
+use core_test_support::responses::ev_apply_patch_function_call;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const HANDOFF_MODEL: &str = "gpt-5.1-codex";
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(id),
+    ])
+}
+
+/// The canned summary the handoff model answers with.
+fn summary(id: &str) -> String {
+    let sections = json!({
+        "goal": "Add a notes file.",
+        "actions_taken": ["Created notes.txt with apply_patch."],
+        "failures": ["The linter was not run."],
+        "suggested_next_steps": ["Review notes.txt.", "Run the linter."],
+    });
+    answer(id, &sections.to_string())
+}
+
+async fn shutdown(codex: &CodexConversation) -> Result<()> {
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    Ok(())
+}
+
+async fn recorded_handoffs(path: &Path) -> Result<Vec<HandoffDocument>> {
+    Ok(RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .filter_map(|item| match item {
+            RolloutItem::Annotation(RolloutAnnotation::Handoff(document)) => Some(document),
+            _ => None,
+        })
+        .collect())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn handoff_summarizes_the_conversation_and_is_recorded() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let patch = "*** Begin Patch\n*** Add File: notes.txt\n+first\n+second\n*** End Patch";
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_apply_patch_function_call("patch-call", patch),
+                ev_completed("resp-1"),
+            ]),
+            answer("resp-2", "Added notes.txt."),
+            summary("resp-3"),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.include_apply_patch_tool = true;
+            config.handoff_model = Some(HANDOFF_MODEL.to_string());
+        })
+        .build(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+
+    test.submit_turn("add a notes file").await?;
+    let document = test
+        .codex
+        .generate_handoff(HandoffOptions::default())
+        .await?;
+
+    let notes = test.cwd.path().join("notes.txt");
+    let expected = HandoffDocument {
+        goal: "Add a notes file.".to_string(),
+        actions_taken: vec!["Created notes.txt with apply_patch.".to_string()],
+        files_changed: vec![TurnFileChange {
+            path: notes.clone(),
+            kind: TurnFileChangeKind::Added,
+            additions: 2,
+            deletions: 0,
+            outside_cwd: false,
+        }],
+        failures: vec!["The linter was not run.".to_string()],
+        suggested_next_steps: vec![
+            "Review notes.txt.".to_string(),
+            "Run the linter.".to_string(),
+        ],
+        markdown: format!(
+            "# Handoff\n\n## Goal\n\nAdd a notes file.\n\n## Actions taken\n\n- Created notes.txt with apply_patch.\n\n## Files changed\n\n- `{}` (added, +2 -0)\n\n## Failures\n\n- The linter was not run.\n\n## Suggested next steps\n\n- Review notes.txt.\n- Run the linter.\n",
+            notes.display()
+        ),
+        model: HANDOFF_MODEL.to_string(),
+    };
+    assert_eq!(document, expected);
+
+    // The summary is a request of its own, over the transcript.
+    let request = responses.requests().pop().expect("handoff request");
+    assert_eq!(request.body_json()["model"], HANDOFF_MODEL);
+    let input = request.message_input_texts("user").join("\n");
+    assert!(input.contains("add a notes file"), "{input}");
+    assert!(input.contains("Added notes.txt."), "{input}");
+
+    shutdown(&test.codex).await?;
+    assert_eq!(recorded_handoffs(&rollout_path).await?, vec![expected]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resumed_and_read_only_transcripts_write_handoffs() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            answer("resp-1", "Nothing to change."),
+            summary("resp-2"),
+            summary("resp-3"),
+        ],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+    test.submit_turn("add a notes file").await?;
+    shutdown(&test.codex).await?;
+
+    let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"));
+    let NewConversation {
+        conversation: resumed,
+        ..
+    } = test
+        .conversation_manager
+        .resume_conversation_from_rollout(test.config.clone(), rollout_path.clone(), auth_manager)
+        .await?;
+    let from_resumed = resumed
+        .generate_handoff(HandoffOptions {
+            model: Some(HANDOFF_MODEL.to_string()),
+        })
+        .await?;
+    assert_eq!(from_resumed.model, HANDOFF_MODEL);
+    assert_eq!(from_resumed.files_changed, Vec::new());
+    shutdown(&resumed).await?;
+
+    // A read-only transcript has no session: it uses the caller's
+    // credentials and the model of the last recorded turn.
+    let transcript = ReadOnlyTranscript::open(&rollout_path).await?;
+    let caller = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("caller-key"));
+    let from_read_only = transcript
+        .generate_handoff(&test.config, caller, HandoffOptions::default())
+        .await?;
+    assert_eq!(from_read_only.model, test.session_configured.model);
+    assert_eq!(from_read_only.goal, "Add a notes file.");
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[2].header("authorization"),
+        Some("Bearer caller-key".to_string())
+    );
+    assert_eq!(
+        recorded_handoffs(&rollout_path).await?,
+        vec![from_resumed, from_read_only]
+    );
+
+    Ok(())
+}
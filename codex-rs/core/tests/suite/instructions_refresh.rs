@@ -0,0 +1,123 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::fs;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::RolloutRecorder;
+use codex_core::config::types::RefreshPolicy;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+
+async fn submit_text(codex: &CodexConversation, text: &str) -> Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn edited_agents_md_replaces_the_instructions_of_the_next_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_assistant_message("msg-1", "first"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "second"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.instructions_refresh = RefreshPolicy::OnChange;
+            fs::write(config.cwd.join("AGENTS.md"), "use tabs").unwrap();
+        })
+        .build(&server)
+        .await?;
+    let agents_md = test.cwd.path().join("AGENTS.md");
+
+    submit_text(&test.codex, "first turn").await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    fs::write(&agents_md, "use spaces").unwrap();
+    submit_text(&test.codex, "second turn").await?;
+    let reloaded = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::InstructionsReloaded(reloaded) => Some(reloaded.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(
+        reloaded
+            .sources
+            .iter()
+            .any(|source| source.ends_with("AGENTS.md")),
+        "{reloaded:?}"
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let first = requests[0].message_input_texts("user").join("\n");
+    assert!(first.contains("use tabs"), "{first}");
+    let second = requests[1].message_input_texts("user").join("\n");
+    assert!(second.contains("use spaces"), "{second}");
+    assert!(!second.contains("use tabs"), "{second}");
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let replaced = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::InstructionsReplaced(replaced) => Some(replaced),
+            _ => None,
+        })
+        .expect("rollout records the replacement");
+    let Some(ResponseItem::Message { content, .. }) = replaced.instructions else {
+        panic!("replacement is not a message: {replaced:?}");
+    };
+    let [ContentItem::InputText { text }] = content.as_slice() else {
+        panic!("unexpected instructions content: {content:?}");
+    };
+    assert!(text.contains("use spaces"), "{text}");
+
+    Ok(())
+}
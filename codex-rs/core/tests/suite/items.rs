@@ -43,6 +43,7 @@ async fn user_message_item_is_emitted() -> anyhow::Result<()> {
             items: (vec![UserInput::Text {
                 text: "please inspect sample.txt".into(),
             }]),
+            allow_sensitive: false,
         })
         .await?;
 
@@ -99,6 +100,7 @@ async fn assistant_message_item_is_emitted() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "please summarize results".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -155,6 +157,7 @@ async fn reasoning_item_is_emitted() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "explain your reasoning".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -213,6 +216,7 @@ async fn web_search_item_is_emitted() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "find the weather".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -265,6 +269,7 @@ async fn agent_message_content_delta_has_item_metadata() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "please stream text".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -330,6 +335,7 @@ async fn reasoning_content_delta_has_item_metadata() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "reason through it".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -387,6 +393,7 @@ async fn reasoning_raw_content_delta_respects_flag() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "show raw reasoning".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -1,6 +1,7 @@
 #![cfg(not(target_os = "windows"))]
 
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::CodexErrorInfo;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
 use codex_core::protocol::SandboxPolicy;
@@ -14,6 +15,8 @@ use core_test_support::wait_for_event;
 use pretty_assertions::assert_eq;
 use responses::ev_assistant_message;
 use responses::ev_completed;
+use responses::ev_response_created;
+use responses::mount_sse_once;
 use responses::sse;
 use responses::start_mock_server;
 
@@ -84,6 +87,9 @@ async fn codex_returns_json_result(model: String) -> anyhow::Result<()> {
             model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -104,3 +110,114 @@ async fn codex_returns_json_result(model: String) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+async fn submit_with_schema(test: &TestCodex, text: &str) -> anyhow::Result<()> {
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text { text: text.into() }],
+            final_output_json_schema: Some(serde_json::from_str(SCHEMA)?),
+            cwd: test.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+    Ok(())
+}
+
+fn reply(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(&format!("resp-{id}")),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(&format!("resp-{id}")),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn malformed_final_answer_is_repaired() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("1", "Sure! The answer is 42.")).await;
+    let repair = mount_sse_once(
+        &server,
+        reply(
+            "2",
+            r#"{"explanation": "asked to fix it", "final_answer": "42"}"#,
+        ),
+    )
+    .await;
+
+    let test = test_codex().build(&server).await?;
+    submit_with_schema(&test, "what is the answer?").await?;
+
+    let structured = wait_for_event(&test.codex, |ev| {
+        matches!(ev, EventMsg::StructuredOutput(_))
+    })
+    .await;
+    let expected = serde_json::json!({"explanation": "asked to fix it", "final_answer": "42"});
+    let EventMsg::StructuredOutput(structured) = structured else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(structured.output, expected);
+    let complete = wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(complete.structured_output, Some(expected));
+
+    // The retry tells the model what was wrong with its first answer.
+    let repair_prompt = repair
+        .single_request()
+        .message_input_texts("user")
+        .into_iter()
+        .last()
+        .unwrap_or_default();
+    assert!(
+        repair_prompt.starts_with("<structured_output_repair>"),
+        "{repair_prompt}"
+    );
+    assert!(repair_prompt.contains("not valid JSON"), "{repair_prompt}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn final_answer_that_never_matches_fails_the_turn() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let attempts = vec![
+        mount_sse_once(&server, reply("1", "not json")).await,
+        mount_sse_once(&server, reply("2", r#"{"explanation": "missing a field"}"#)).await,
+        mount_sse_once(&server, reply("3", "[]")).await,
+    ];
+
+    let test = test_codex().build(&server).await?;
+    submit_with_schema(&test, "what is the answer?").await?;
+
+    let error = wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::Error(_))).await;
+    let EventMsg::Error(error) = error else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(
+        error.codex_error_info,
+        Some(CodexErrorInfo::StructuredOutputInvalid)
+    );
+    let complete = wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(complete.structured_output, None);
+    // The first answer plus the two default repair attempts.
+    for attempt in attempts {
+        attempt.single_request();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::ManagerLoad;
+use codex_core::protocol::PauseMode;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use wiremock::Mock;
+use wiremock::matchers::method;
+use wiremock::matchers::path_regex;
+
+const WORKERS: usize = 8;
+const TURNS: usize = 3;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn load_follows_concurrent_spawns_turns_and_removals() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let reply = sse(vec![
+        ev_response_created("resp-1"),
+        ev_assistant_message("msg-1", "done"),
+        ev_completed("resp-1"),
+    ]);
+    // Slow enough for turns to overlap.
+    Mock::given(method("POST"))
+        .and(path_regex(".*/responses$"))
+        .respond_with(sse_response(reply).set_delay(Duration::from_millis(50)))
+        .mount(&server)
+        .await;
+    let test = test_codex().build(&server).await?;
+    let manager = test.conversation_manager.clone();
+    let before = manager.load();
+    assert_eq!(before.turn_in_flight, 0);
+    assert_eq!(before.total, before.idle);
+
+    let done = Arc::new(AtomicBool::new(false));
+    let sampler = tokio::spawn({
+        let manager = manager.clone();
+        let done = Arc::clone(&done);
+        async move {
+            let mut most_in_flight = 0;
+            while !done.load(Ordering::Acquire) {
+                // A counter that went below zero wraps around.
+                let load = manager.load();
+                assert!(load.total <= before.total + WORKERS, "{load:?}");
+                assert!(load.turn_in_flight <= WORKERS, "{load:?}");
+                assert!(load.queued_submissions <= WORKERS, "{load:?}");
+                most_in_flight = most_in_flight.max(load.turn_in_flight);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            most_in_flight
+        }
+    });
+
+    let workers = (0..WORKERS).map(|worker| {
+        let manager = manager.clone();
+        let config = test.config.clone();
+        tokio::spawn(async move {
+            let new = manager.new_conversation(config).await?;
+            for turn in 0..TURNS {
+                TestCodex::submit_input_to(
+                    &new.conversation,
+                    &format!("worker {worker} turn {turn}"),
+                )
+                .await?;
+            }
+            // Every other worker leaves its conversation running.
+            if worker % 2 == 0 {
+                manager.remove_conversation(&new.conversation_id).await;
+            }
+            anyhow::Ok(())
+        })
+    });
+    for worker in futures::future::join_all(workers).await {
+        worker??;
+    }
+    done.store(true, Ordering::Release);
+    let most_in_flight = sampler.await?;
+    assert!(most_in_flight > 0);
+
+    tokio::time::timeout(Duration::from_secs(5), manager.wait_for_capacity(1)).await?;
+    let kept = WORKERS / 2;
+    assert_eq!(
+        manager.load(),
+        ManagerLoad {
+            total: before.total + kept,
+            idle: before.total + kept,
+            ..ManagerLoad::default()
+        }
+    );
+
+    test.codex.pause(PauseMode::FinishTurn).await?;
+    assert_eq!(
+        manager.load(),
+        ManagerLoad {
+            total: before.total + kept,
+            idle: before.total + kept - 1,
+            paused: 1,
+            ..ManagerLoad::default()
+        }
+    );
+    test.codex.resume().await?;
+    assert_eq!(manager.load().paused, 0);
+
+    Ok(())
+}
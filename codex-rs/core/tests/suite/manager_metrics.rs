@@ -0,0 +1,153 @@
+#![allow(clippy::expect_used)]
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use codex_core::ManagerMetrics;
+use codex_core::error::CodexErr;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+fn turn_body(id: &str, tokens: i64) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed_with_tokens(id, tokens),
+    ])
+}
+
+/// Parse Prometheus text exposition into `name{labels}` -> value, checking
+/// that every sample follows the `HELP` and `TYPE` lines of its metric.
+fn parse_exposition(text: &str) -> BTreeMap<String, f64> {
+    let mut samples = BTreeMap::new();
+    let mut described = None;
+    let mut typed = None;
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("# HELP ") {
+            let (name, help) = rest.split_once(' ').expect("HELP has a name and text");
+            assert!(!help.is_empty());
+            described = Some(name.to_string());
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').expect("TYPE has a name and kind");
+            assert_eq!(Some(name), described.as_deref());
+            assert!(matches!(kind, "counter" | "gauge"), "{kind}");
+            typed = Some(name.to_string());
+            continue;
+        }
+        let (series, value) = line.rsplit_once(' ').expect("sample has a value");
+        let name = series.split('{').next().unwrap_or_default();
+        assert_eq!(Some(name), typed.as_deref(), "{line}");
+        if let Some(labels) = series
+            .strip_prefix(name)
+            .filter(|labels| !labels.is_empty())
+        {
+            let labels = labels
+                .strip_prefix('{')
+                .and_then(|labels| labels.strip_suffix('}'))
+                .expect("labels are braced");
+            for label in labels.split(',') {
+                let (key, value) = label.split_once('=').expect("label is key=value");
+                assert!(key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+                assert!(value.starts_with('"') && value.ends_with('"'), "{value}");
+            }
+        }
+        let value: f64 = value.parse().expect("sample value is a number");
+        samples.insert(series.to_string(), value);
+    }
+    samples
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn manager_metrics_count_conversations_turns_and_tokens() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            turn_body("a1", 100),
+            turn_body("b1", 40),
+            turn_body("a2", 60),
+        ],
+    )
+    .await;
+
+    let test = test_codex().build(&server).await?;
+    let manager = &test.conversation_manager;
+    let first = &test.codex;
+    let second = manager
+        .new_conversation(test.config.clone())
+        .await?
+        .conversation;
+
+    test.submit_input("first question").await?;
+    TestCodex::submit_input_to(&second, "other question").await?;
+    test.submit_input("second question").await?;
+
+    // A spawn over the limit fails and is counted by its error code.
+    manager.set_max_conversations(Some(2));
+    let err = manager
+        .new_conversation(test.config.clone())
+        .await
+        .err()
+        .expect("spawn over the limit fails");
+    assert!(matches!(err, CodexErr::ConversationLimitReached(2)));
+    manager.set_max_conversations(None);
+
+    manager
+        .fork_conversation(1, test.config.clone(), first.rollout_path())
+        .await?;
+    manager
+        .remove_conversation(&second.conversation_id())
+        .await
+        .expect("second conversation is tracked");
+
+    let metrics = manager.metrics_snapshot();
+    assert!(metrics.event_queue_high_water >= 1);
+    assert_eq!(
+        metrics,
+        ManagerMetrics {
+            active_conversations: 2,
+            paused_conversations: 0,
+            spawns: 2,
+            resumes: 0,
+            forks: 1,
+            spawn_failures: BTreeMap::from([("bad_request".to_string(), 1)]),
+            turns_completed: 3,
+            total_tokens: 200,
+            event_queue_high_water: metrics.event_queue_high_water,
+        }
+    );
+
+    let samples = parse_exposition(&metrics.to_prometheus_text());
+    let expected = [
+        ("codex_active_conversations", 2.0),
+        ("codex_paused_conversations", 0.0),
+        ("codex_conversations_started_total{kind=\"new\"}", 2.0),
+        ("codex_conversations_started_total{kind=\"resume\"}", 0.0),
+        ("codex_conversations_started_total{kind=\"fork\"}", 1.0),
+        (
+            "codex_conversation_spawn_failures_total{code=\"bad_request\"}",
+            1.0,
+        ),
+        ("codex_turns_completed_total", 3.0),
+        ("codex_tokens_total", 200.0),
+    ];
+    for (series, value) in expected {
+        assert_eq!(samples.get(series), Some(&value), "{series}");
+    }
+    assert!(samples["codex_event_queue_high_water"] >= 1.0);
+    assert_eq!(samples.len(), expected.len() + 1);
+
+    Ok(())
+}
@@ -15,52 +15,135 @@ pub static CODEX_ALIASES_TEMP_DIR: TempDir = unsafe {
 
 #[cfg(not(target_os = "windows"))]
 mod abort_tasks;
+mod adaptive_effort;
+mod anonymize;
 mod apply_patch_cli;
 #[cfg(not(target_os = "windows"))]
 mod approvals;
 mod auth_refresh;
+mod chat_import;
+mod checkpoints;
+mod child_conversations;
 mod cli_stream;
 mod client;
 mod codex_delegate;
+mod codex_home;
+mod codex_runtime;
 mod compact;
 mod compact_remote;
 mod compact_resume_fork;
+mod context_recovery;
+mod context_usage;
+mod conversation_manager_handle;
+mod correlation_ids;
+mod default_config;
+mod delta_coalescing;
 mod deprecation_notice;
+mod deterministic_env;
+mod dry_run;
+mod empty_rollouts;
+mod event_filter;
+mod event_log;
+mod event_replay;
+mod event_schema;
 mod exec;
 mod exec_policy;
+mod fault_injection;
+mod fork_at_turn;
 mod fork_conversation;
+mod git_context;
 mod grep_files;
+mod handoff;
+mod instructions_refresh;
 mod items;
 mod json_result;
 mod list_dir;
 mod list_models;
 mod live_cli;
+mod manager_load;
+mod manager_metrics;
+mod model_list_fallback;
+mod model_list_updates;
 mod model_overrides;
 mod model_tools;
+mod offline_mode;
 mod otel;
+mod partial_messages;
+mod pause_conversation;
+mod pending_inputs;
+mod pinned_items;
+mod post_process;
+mod pre_process;
+mod project_approvals;
+mod project_notes;
 mod prompt_caching;
+mod prompt_script;
+mod provider_cassette;
+mod provider_failover;
 mod quota_exceeded;
+mod reasoning_summaries;
+mod read_cache;
 mod read_file;
+mod read_only;
+mod request_reconstruction;
 mod remote_models;
+mod remove_conversation;
+mod request_trace;
 mod resume;
+mod resume_conversation_id;
+mod resume_latest;
+mod resume_sanitize;
+mod resume_trim;
 mod resume_warning;
+mod resumed_summary;
 mod review;
 mod rmcp_client;
+mod rollout_chunking;
+mod rollout_deletion;
+mod rollout_import;
 mod rollout_list_find;
+mod rollout_parse_errors;
+mod rollout_relocation;
+mod rollout_rotation;
+mod safe_mode;
+mod sampling;
 mod seatbelt;
+mod sensitive_input;
+mod session_ended;
+mod session_profiles;
 mod shell_command;
 mod shell_serialization;
 mod shell_snapshot;
+mod skill_packs;
+mod skill_selection;
+mod skill_tools;
+mod skill_usage;
 mod skills;
+mod spawn_observer;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
 mod text_encoding_fix;
 mod tool_harness;
+mod tool_output_cap;
 mod tool_parallelism;
+mod tool_policy;
+mod tool_timeouts;
 mod tools;
+mod transcript;
+mod transcript_journal;
 mod truncation;
+mod turn_deadline;
+mod turn_diff_summary;
+mod turn_results;
+mod turn_summary;
+mod turn_tmpdir;
 mod undo;
 mod unified_exec;
+mod unknown_rollout_items;
+mod user_context;
 mod user_notification;
 mod user_shell_cmd;
 mod view_image;
+mod warnings;
+mod working_directory;
+mod workspace_watch;
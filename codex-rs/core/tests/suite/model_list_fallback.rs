@@ -0,0 +1,272 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::built_in_model_providers;
+use codex_core::config::Config;
+use codex_core::fault_injection::FaultPlan;
+use codex_core::fault_injection::FaultyProvider;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ModelListSource;
+use codex_core::protocol::Op;
+use codex_core::protocol::RetrySubsystem;
+use codex_core::protocol::RetryingEvent;
+use codex_core::protocol::RolloutItem;
+use codex_core::retry::RetryPolicy;
+use codex_protocol::openai_models::ConfigShellToolType;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::openai_models::ModelVisibility;
+use codex_protocol::openai_models::ModelsResponse;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::openai_models::ReasoningEffortPreset;
+use codex_protocol::openai_models::TruncationPolicyConfig;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::mount_models_once;
+use core_test_support::skip_if_no_network;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path_regex;
+
+const CACHED_MODEL: &str = "cached-model";
+const NETWORK_MODEL: &str = "network-model";
+
+fn model_info(slug: &str) -> ModelInfo {
+    ModelInfo {
+        slug: slug.to_string(),
+        display_name: slug.to_string(),
+        description: None,
+        default_reasoning_level: ReasoningEffort::Medium,
+        supported_reasoning_levels: vec![ReasoningEffortPreset {
+            effort: ReasoningEffort::Medium,
+            description: ReasoningEffort::Medium.to_string(),
+        }],
+        shell_type: ConfigShellToolType::ShellCommand,
+        visibility: ModelVisibility::List,
+        supported_in_api: true,
+        priority: 1,
+        upgrade: None,
+        base_instructions: None,
+        supports_reasoning_summaries: false,
+        support_verbosity: false,
+        default_verbosity: None,
+        apply_patch_tool_type: None,
+        truncation_policy: TruncationPolicyConfig::bytes(10_000),
+        supports_parallel_tool_calls: false,
+        context_window: None,
+        experimental_supported_tools: Vec::new(),
+    }
+}
+
+/// Fail the next `times` model list fetches, or all of them when `None`.
+async fn fail_model_fetches(server: &MockServer, times: Option<u64>) {
+    let mock = Mock::given(method("GET"))
+        .and(path_regex(".*/models$"))
+        .respond_with(ResponseTemplate::new(500));
+    match times {
+        Some(times) => mock.up_to_n_times(times).mount(server).await,
+        None => mock.mount(server).await,
+    }
+}
+
+/// Cache a model list fetched long enough ago to be stale.
+fn write_stale_cache(home: &Path) -> Result<()> {
+    let cache = json!({
+        "fetched_at": "2020-01-01T00:00:00Z",
+        "models": [model_info(CACHED_MODEL)],
+    });
+    std::fs::write(
+        home.join("models_cache.json"),
+        serde_json::to_vec_pretty(&cache)?,
+    )?;
+    Ok(())
+}
+
+async fn manager_and_config(
+    server: &MockServer,
+    home: &TempDir,
+    cwd: &TempDir,
+) -> (ConversationManager, Config) {
+    let mut config = load_default_config_for_test(home).await;
+    config.cwd = cwd.path().to_path_buf();
+    config.features.enable(Feature::RemoteModels);
+    let provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        request_max_retries: Some(0),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.model_provider = provider.clone();
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::create_dummy_chatgpt_auth_for_testing(),
+        provider,
+        home.path().to_path_buf(),
+    );
+    (manager, config)
+}
+
+async fn recorded_source(
+    conversation: &codex_core::CodexConversation,
+) -> Result<Option<ModelListSource>> {
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(
+        RolloutRecorder::get_rollout_history(&conversation.rollout_path())
+            .await?
+            .get_rollout_items()
+            .into_iter()
+            .find_map(|item| match item {
+                RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.model_list_source),
+                _ => None,
+            })
+            .flatten(),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_fetch_uses_the_cache_and_refreshes_in_the_background() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    fail_model_fetches(&server, Some(1)).await;
+    let models_mock = mount_models_once(
+        &server,
+        ModelsResponse {
+            models: vec![model_info(NETWORK_MODEL)],
+            etag: String::new(),
+        },
+    )
+    .await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    write_stale_cache(home.path())?;
+    let (manager, mut config) = manager_and_config(&server, &home, &cwd).await;
+    config.model = Some(CACHED_MODEL.to_string());
+
+    let NewConversation {
+        conversation,
+        session_configured,
+        ..
+    } = manager.new_conversation(config).await?;
+    assert_eq!(session_configured.model, CACHED_MODEL);
+
+    let refreshed = wait_for_event_match(&conversation, |event| match event {
+        EventMsg::ModelListRefreshed(refreshed) => Some(refreshed.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(
+        refreshed.models.iter().any(|model| model == NETWORK_MODEL),
+        "{:?}",
+        refreshed.models
+    );
+    assert_eq!(models_mock.requests().len(), 1);
+
+    assert_eq!(
+        recorded_source(&conversation).await?,
+        Some(ModelListSource::Cache)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_fetch_without_cache_uses_builtin_models() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    fail_model_fetches(&server, None).await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let (manager, mut config) = manager_and_config(&server, &home, &cwd).await;
+
+    config.model = Some("gpt-5.1".to_string());
+    let conversation = manager.new_conversation(config.clone()).await?.conversation;
+    assert_eq!(
+        recorded_source(&conversation).await?,
+        Some(ModelListSource::Builtin)
+    );
+
+    // A model that no source knows about fails the spawn.
+    config.model = Some("no-such-model".to_string());
+    let err = manager
+        .new_conversation(config)
+        .await
+        .err()
+        .expect("unknown model fails the spawn");
+    assert!(err.to_string().contains("no-such-model"), "{err}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_fetch_is_retried_as_the_preset_fetch_profile_allows() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    let models_mock = mount_models_once(
+        &server,
+        ModelsResponse {
+            models: vec![model_info(NETWORK_MODEL)],
+            etag: String::new(),
+        },
+    )
+    .await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let (_, mut config) = manager_and_config(&server, &home, &cwd).await;
+    let plan = FaultPlan::new().fail_models_fetch(1, 503);
+    let provider = FaultyProvider::start(config.model_provider.clone(), &plan).await?;
+    config.model_provider = (&provider).into();
+    config.model = Some(NETWORK_MODEL.to_string());
+    config.retry.preset_fetch = RetryPolicy {
+        max_retries: Some(1),
+        base_delay: Duration::from_millis(1),
+        jitter: 0.0,
+        ..RetryPolicy::preset_fetch()
+    };
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::create_dummy_chatgpt_auth_for_testing(),
+        config.model_provider.clone(),
+        home.path().to_path_buf(),
+    );
+
+    let NewConversation { conversation, .. } = manager.new_conversation(config).await?;
+    let retry = wait_for_event_match(&conversation, |event| match event {
+        EventMsg::Retrying(retry) => Some(retry.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(
+        retry,
+        RetryingEvent {
+            subsystem: RetrySubsystem::PresetFetch,
+            attempt: 1,
+            delay_ms: 1,
+        }
+    );
+    assert_eq!(models_mock.requests().len(), 1);
+    assert_eq!(plan.remaining_faults(), Vec::new());
+    assert_eq!(
+        recorded_source(&conversation).await?,
+        Some(ModelListSource::Network)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_protocol::openai_models::ConfigShellToolType;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::openai_models::ModelVisibility;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::openai_models::ReasoningEffortPreset;
+use codex_protocol::openai_models::TruncationPolicyConfig;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+const CURRENT_MODEL: &str = "codex-current";
+const NEXT_MODEL: &str = "codex-next";
+
+fn remote_model(slug: &str, priority: i32) -> ModelInfo {
+    ModelInfo {
+        slug: slug.to_string(),
+        display_name: slug.to_string(),
+        description: None,
+        default_reasoning_level: ReasoningEffort::Medium,
+        supported_reasoning_levels: vec![ReasoningEffortPreset {
+            effort: ReasoningEffort::Medium,
+            description: ReasoningEffort::Medium.to_string(),
+        }],
+        shell_type: ConfigShellToolType::ShellCommand,
+        visibility: ModelVisibility::List,
+        supported_in_api: true,
+        priority,
+        upgrade: None,
+        base_instructions: None,
+        supports_reasoning_summaries: false,
+        support_verbosity: false,
+        default_verbosity: None,
+        apply_patch_tool_type: None,
+        truncation_policy: TruncationPolicyConfig::bytes(10_000),
+        supports_parallel_tool_calls: false,
+        context_window: None,
+        experimental_supported_tools: Vec::new(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn removing_the_current_model_notifies_once_and_deprecates_it() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::RemoteModels);
+        })
+        .build(&server)
+        .await?;
+    let models_manager = test.conversation_manager.get_models_manager();
+    models_manager
+        .set_remote_models_for_testing(
+            vec![remote_model(CURRENT_MODEL, 1), remote_model(NEXT_MODEL, 2)],
+            &test.config,
+        )
+        .await;
+    let mut models = test.conversation_manager.subscribe_models();
+    let mut config = test.config.clone();
+    config.model = Some(CURRENT_MODEL.to_string());
+    let conversation = test
+        .conversation_manager
+        .new_conversation(config)
+        .await?
+        .conversation;
+
+    // The same models in another order are not a change.
+    models_manager
+        .set_remote_models_for_testing(
+            vec![remote_model(NEXT_MODEL, 2), remote_model(CURRENT_MODEL, 1)],
+            &test.config,
+        )
+        .await;
+    assert!(!models.has_changed()?);
+
+    models_manager
+        .set_remote_models_for_testing(vec![remote_model(NEXT_MODEL, 2)], &test.config)
+        .await;
+    assert!(models.has_changed()?);
+    let listed = models.borrow_and_update().clone();
+    assert!(listed.iter().any(|preset| preset.model == NEXT_MODEL));
+    assert!(!listed.iter().any(|preset| preset.model == CURRENT_MODEL));
+    assert!(!models.has_changed()?);
+
+    let deprecated = wait_for_event_match(&conversation, |event| match event {
+        EventMsg::ModelDeprecated(deprecated) => Some(deprecated.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(deprecated.model, CURRENT_MODEL);
+    assert_eq!(deprecated.suggested, Some(NEXT_MODEL.to_string()));
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+fn done_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+/// Submit `text` and return how many inputs are queued once it is.
+async fn queue(codex: &CodexConversation, text: &str) -> Result<usize> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let EventMsg::QueuedOffline(queued) =
+        wait_for_event(codex, |event| matches!(event, EventMsg::QueuedOffline(_))).await
+    else {
+        unreachable!();
+    };
+    Ok(queued.queued)
+}
+
+async fn set_offline(codex: &CodexConversation, offline: bool) -> Result<()> {
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            execution_mode: None,
+            sampling: None,
+            offline: Some(offline),
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queued_offline_turns_run_in_order_once_flushed() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(&server, vec![done_response("1"), done_response("2")]).await;
+    let test = test_codex()
+        .with_config(|config| config.offline = true)
+        .build(&server)
+        .await?;
+    let codex = &test.codex;
+
+    assert_eq!(queue(codex, "first").await?, 1);
+    assert_eq!(queue(codex, "second").await?, 2);
+
+    // Still offline, the queue is kept.
+    codex.flush_queued().await?;
+    let EventMsg::Error(error) =
+        wait_for_event(codex, |event| matches!(event, EventMsg::Error(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(error.message, "the conversation is offline");
+    assert!(
+        mock.requests().is_empty(),
+        "offline never calls the provider"
+    );
+
+    set_offline(codex, false).await?;
+    codex.flush_queued().await?;
+    let mut completed = 0;
+    wait_for_event(codex, |event| {
+        completed += usize::from(matches!(event, EventMsg::TaskComplete(_)));
+        completed == 2
+    })
+    .await;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let prompts = |index: usize| {
+        requests[index]
+            .message_input_texts("user")
+            .into_iter()
+            .filter(|text| text == "first" || text == "second")
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(prompts(0), vec!["first"]);
+    assert_eq!(prompts(1), vec!["first", "second"]);
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::CodexConversation;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::config::Config;
+use codex_core::partial_messages::PartialMessagePolicy;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InterruptedTurnRecoveredEvent;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::TurnId;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+const DELTAS: [&str; 3] = ["First sentence. ", "Second sentence. ", "and the rest"];
+
+fn record_every_sentence(config: &mut Config) {
+    config.partial_messages = Some(PartialMessagePolicy { min_interval_ms: 0 });
+}
+
+async fn submit_text(codex: &CodexConversation, text: &str) -> Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+fn recorded_partial_messages(path: &Path) -> Result<usize> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|line| line["type"] == "partial_message")
+        .count())
+}
+
+/// Wait until the rollout at `path` holds `count` partial messages.
+async fn wait_for_partial_messages(path: &Path, count: usize) -> Result<()> {
+    for _ in 0..100 {
+        if recorded_partial_messages(path)? >= count {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    anyhow::bail!("partial messages were not recorded in {}", path.display())
+}
+
+async fn shutdown(codex: &CodexConversation) -> Result<()> {
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    Ok(())
+}
+
+fn partial_message_texts(items: &[RolloutItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::PartialMessage { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn partial_answer_is_recovered_once_after_a_crash() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The answer stops streaming after its second sentence.
+    let (_rest_tx, rest_rx) = oneshot::channel();
+    let mut first = vec![
+        ev_response_created("resp-1"),
+        ev_message_item_added("msg-1", ""),
+    ];
+    first.extend(DELTAS.map(ev_output_text_delta));
+    let (server, _completions) = start_streaming_sse_server(vec![vec![
+        StreamingSseChunk {
+            gate: None,
+            body: sse(first),
+        },
+        StreamingSseChunk {
+            gate: Some(rest_rx),
+            body: sse(vec![ev_completed("resp-1")]),
+        },
+    ]])
+    .await;
+    let test = test_codex()
+        .with_config(record_every_sentence)
+        .build_with_streaming_server(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+
+    submit_text(&test.codex, "explain it").await?;
+    wait_for_partial_messages(&rollout_path, 2).await?;
+
+    // Keep the rollout as it was on disk at this point, as if the process had
+    // crashed, then drop the session.
+    let crashed_path = test.home.path().join("crashed-rollout.jsonl");
+    fs::copy(&rollout_path, &crashed_path)?;
+    test.codex.submit(Op::Interrupt).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnAborted(_))
+    })
+    .await;
+    shutdown(&test.codex).await?;
+    test.conversation_manager
+        .remove_conversation(&test.session_configured.session_id)
+        .await;
+    server.shutdown().await;
+
+    let expected = InterruptedTurnRecoveredEvent {
+        turn_id: TurnId::new(1),
+        text: "First sentence. Second sentence.".to_string(),
+    };
+    let history = RolloutRecorder::get_rollout_history(&crashed_path).await?;
+    assert_eq!(history.interrupted_turn(), Some(expected.clone()));
+    assert_eq!(
+        partial_message_texts(&history.get_rollout_items()),
+        vec![expected.text.clone()]
+    );
+
+    let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"));
+    let NewConversation {
+        conversation: resumed,
+        ..
+    } = test
+        .conversation_manager
+        .resume_conversation_from_rollout(test.config.clone(), crashed_path.clone(), auth_manager)
+        .await?;
+    let recovered = wait_for_event_match(&resumed, |event| match event {
+        EventMsg::InterruptedTurnRecovered(recovered) => Some(recovered.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(recovered, expected);
+    shutdown(&resumed).await?;
+
+    // The recovery supersedes the partial message, so it is not reported again.
+    let history = RolloutRecorder::get_rollout_history(&crashed_path).await?;
+    assert_eq!(history.interrupted_turn(), None);
+    assert_eq!(
+        partial_message_texts(&history.get_rollout_items()),
+        Vec::<String>::new()
+    );
+    let recoveries = history
+        .get_event_msgs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|event| matches!(event, EventMsg::InterruptedTurnRecovered(_)))
+        .count();
+    assert_eq!(recoveries, 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn completed_answers_supersede_their_partial_messages() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut events = vec![
+        ev_response_created("resp-1"),
+        ev_message_item_added("msg-1", ""),
+    ];
+    events.extend(DELTAS.map(ev_output_text_delta));
+    events.push(ev_assistant_message("msg-1", &DELTAS.concat()));
+    events.push(ev_completed("resp-1"));
+    mount_sse_sequence(&server, vec![sse(events)]).await;
+    let test = test_codex()
+        .with_config(record_every_sentence)
+        .build(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+
+    test.submit_turn("explain it").await?;
+    shutdown(&test.codex).await?;
+
+    assert_eq!(recorded_partial_messages(&rollout_path)?, 2);
+    let history = RolloutRecorder::get_rollout_history(&rollout_path).await?;
+    assert_eq!(history.interrupted_turn(), None);
+    assert_eq!(
+        partial_message_texts(&history.get_rollout_items()),
+        Vec::<String>::new()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::RolloutRecorder;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::PauseMode;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn user_input(text: &str) -> Op {
+    Op::UserInput {
+        items: vec![UserInput::Text {
+            text: text.to_string(),
+        }],
+        allow_sensitive: false,
+    }
+}
+
+fn done_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+/// The rollout items of `codex` once it has shut down.
+async fn shutdown_rollout(codex: &CodexConversation) -> Result<Vec<RolloutItem>> {
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    Ok(RolloutRecorder::get_rollout_history(&codex.rollout_path())
+        .await?
+        .get_rollout_items())
+}
+
+/// Names of the session meta and pause events recorded in `items`, in order.
+fn lifecycle(items: &[RolloutItem]) -> Vec<&'static str> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::SessionMeta(_) => Some("session_meta"),
+            RolloutItem::EventMsg(EventMsg::ConversationPaused(_)) => Some("paused"),
+            RolloutItem::EventMsg(EventMsg::ConversationResumed) => Some("resumed"),
+            RolloutItem::EventMsg(EventMsg::UserMessage(_)) => Some("user_message"),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pausing_an_idle_conversation_refuses_turns_until_resumed() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let codex = &test.codex;
+    mount_sse_sequence(&server, vec![done_response("1"), done_response("2")]).await;
+
+    test.submit_input("before").await?;
+    codex.pause(PauseMode::FinishTurn).await?;
+    assert!(codex.is_paused());
+    let EventMsg::ConversationPaused(paused) = wait_for_event(codex, |event| {
+        matches!(event, EventMsg::ConversationPaused(_))
+    })
+    .await
+    else {
+        unreachable!();
+    };
+    assert!(!paused.had_running_turn);
+
+    let refused = codex.submit(user_input("while paused")).await;
+    assert!(
+        matches!(refused, Err(CodexErr::ConversationPaused)),
+        "{refused:?}"
+    );
+    // Pausing again is a no-op.
+    codex.pause(PauseMode::AbortTurn).await?;
+
+    codex.resume().await?;
+    assert!(!codex.is_paused());
+    wait_for_event(codex, |event| {
+        matches!(event, EventMsg::ConversationResumed)
+    })
+    .await;
+    test.submit_input("after").await?;
+
+    assert_eq!(
+        lifecycle(&shutdown_rollout(codex).await?),
+        vec![
+            "session_meta",
+            "user_message",
+            "paused",
+            "resumed",
+            "user_message"
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pausing_mid_turn_aborts_the_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let codex = &test.codex;
+    let args = json!({"command": "sleep 60", "timeout_ms": 60_000}).to_string();
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("1"),
+            ev_function_call("call-sleep", "shell_command", &args),
+            ev_completed("1"),
+        ]),
+    )
+    .await;
+
+    codex.submit(user_input("start sleep")).await?;
+    wait_for_event(codex, |event| {
+        matches!(event, EventMsg::ExecCommandBegin(_))
+    })
+    .await;
+    codex.pause(PauseMode::AbortTurn).await?;
+    assert!(codex.is_paused());
+
+    let mut aborted = false;
+    let EventMsg::ConversationPaused(paused) = wait_for_event(codex, |event| {
+        aborted |= matches!(event, EventMsg::TurnAborted(_));
+        matches!(event, EventMsg::ConversationPaused(_))
+    })
+    .await
+    else {
+        unreachable!();
+    };
+    assert!(aborted, "the turn is aborted before the pause completes");
+    assert!(paused.had_running_turn);
+    assert!(matches!(
+        codex.submit(user_input("while paused")).await,
+        Err(CodexErr::ConversationPaused)
+    ));
+
+    codex.resume().await?;
+    let mock = mount_sse_once(&server, done_response("2")).await;
+    test.submit_input("after").await?;
+    // The resumed turn continues the same history.
+    let request = mock.single_request();
+    assert!(request.has_function_call("call-sleep"));
+    assert!(
+        request
+            .message_input_texts("user")
+            .iter()
+            .any(|text| text == "start sleep")
+    );
+
+    assert_eq!(
+        lifecycle(&shutdown_rollout(codex).await?),
+        vec![
+            "session_meta",
+            "user_message",
+            "paused",
+            "resumed",
+            "user_message"
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::CodexConversation;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+const QUEUED: [&str; 2] = ["first queued", "second queued"];
+
+async fn submit_text(codex: &CodexConversation, text: &str) -> Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Wait until the rollout at `path` holds `count` queued pending inputs.
+async fn wait_for_queued_inputs(path: &Path, count: usize) -> Result<()> {
+    for _ in 0..100 {
+        let queued = fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter(|line| line["type"] == "pending_input" && line["payload"]["status"] == "queued")
+            .count();
+        if queued >= count {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    anyhow::bail!("inputs were not queued in {}", path.display())
+}
+
+async fn shutdown(codex: &CodexConversation) -> Result<()> {
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queued_inputs_are_replayed_once_after_a_crash() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            // The turn that is running when the inputs are queued.
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("call-sleep", "sleep 60"),
+                ev_completed("resp-1"),
+            ]),
+            // The replayed turn.
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_shell_command_call("call-echo", "echo replayed"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.replay_pending_inputs = true)
+        .build(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+
+    submit_text(&test.codex, "start sleep").await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ExecCommandBegin(_))
+    })
+    .await;
+    for text in QUEUED {
+        submit_text(&test.codex, text).await?;
+    }
+    wait_for_queued_inputs(&rollout_path, QUEUED.len()).await?;
+
+    // Keep the rollout as it was on disk at this point, as if the process had
+    // crashed, then drop the session.
+    let crashed_path = test.home.path().join("crashed-rollout.jsonl");
+    fs::copy(&rollout_path, &crashed_path)?;
+    test.codex.submit(Op::Interrupt).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnAborted(_))
+    })
+    .await;
+    shutdown(&test.codex).await?;
+    test.conversation_manager
+        .remove_conversation(&test.session_configured.session_id)
+        .await;
+
+    let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"));
+    let NewConversation {
+        conversation: resumed,
+        ..
+    } = test
+        .conversation_manager
+        .resume_conversation_from_rollout(test.config.clone(), crashed_path.clone(), auth_manager)
+        .await?;
+    let restored = wait_for_event_match(&resumed, |event| match event {
+        EventMsg::PendingInputsRestored(restored) => Some(restored.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(restored.replayed);
+    let restored_items: Vec<Vec<UserInput>> = restored
+        .inputs
+        .into_iter()
+        .map(|input| input.items)
+        .collect();
+    assert_eq!(
+        restored_items,
+        QUEUED
+            .iter()
+            .map(|text| vec![UserInput::Text {
+                text: text.to_string(),
+            }])
+            .collect::<Vec<_>>()
+    );
+    wait_for_event(&resumed, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+    // Each queued input reaches the model exactly once, in order.
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    let user_texts: Vec<String> = requests[2]
+        .message_input_texts("user")
+        .into_iter()
+        .filter(|text| QUEUED.contains(&text.as_str()))
+        .collect();
+    assert_eq!(user_texts, QUEUED.map(String::from).to_vec());
+
+    // Every replayed input is marked consumed, so resuming again replays
+    // nothing.
+    shutdown(&resumed).await?;
+    let pending = RolloutRecorder::get_rollout_history(&crashed_path)
+        .await?
+        .pending_inputs();
+    assert_eq!(pending, Vec::new());
+
+    Ok(())
+}
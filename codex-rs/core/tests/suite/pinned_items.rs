@@ -0,0 +1,186 @@
+use anyhow::Result;
+use codex_core::compact::SUMMARIZATION_PROMPT;
+use codex_core::compact::SUMMARY_PREFIX;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ItemRef;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::TestCodexBuilder;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+/// Total tokens above which the next turn starts with a compaction.
+const AUTO_COMPACT_LIMIT: i64 = 1_000;
+const SUMMARY: &str = "SUMMARY_OF_EVERYTHING";
+
+/// A user message too long to survive compaction's user message budget.
+fn spec() -> String {
+    format!("SPEC: {}", "keep every detail ".repeat(6_000))
+}
+
+fn reply(id: &str, text: &str, total_tokens: i64) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), text),
+        ev_completed_with_tokens(id, total_tokens),
+    ])
+}
+
+/// A tiny window and local compaction.
+fn builder() -> TestCodexBuilder {
+    test_codex().with_config(|config| {
+        config.model_provider.name = "OpenAI (test)".to_string();
+        config.model_auto_compact_token_limit = Some(AUTO_COMPACT_LIMIT);
+        config.compact_prompt = Some(SUMMARIZATION_PROMPT.to_string());
+    })
+}
+
+async fn pin(test: &TestCodex, item: ItemRef) -> Result<()> {
+    test.codex.pin_item(item.clone()).await?;
+    let pinned = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ItemPinned(ev) => Some(ev.item.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(pinned, item);
+    Ok(())
+}
+
+/// The user texts of the request following the compaction, checking that the
+/// spec survived verbatim ahead of the summary.
+fn assert_spec_survived(request: &ResponsesRequest) {
+    let texts = request.message_input_texts("user");
+    let summary = format!("{SUMMARY_PREFIX}\n{SUMMARY}");
+    let spec_at = texts.iter().position(|text| *text == spec());
+    let summary_at = texts.iter().position(|text| *text == summary);
+    assert!(
+        matches!((spec_at, summary_at), (Some(spec_at), Some(summary_at)) if spec_at + 1 == summary_at),
+        "spec at {spec_at:?}, summary at {summary_at:?}"
+    );
+    assert_eq!(
+        texts
+            .iter()
+            .filter(|text| text.starts_with("SPEC:"))
+            .count(),
+        1
+    );
+    // The neighbors were summarized.
+    assert!(request.message_input_texts("assistant").is_empty());
+    assert_eq!(texts.last().map(String::as_str), Some("third turn"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pinned_message_survives_compaction_verbatim() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            reply("resp-1", "read the spec", 10),
+            reply("resp-2", "over budget", 10 * AUTO_COMPACT_LIMIT),
+            reply("resp-3", SUMMARY, 10),
+            reply("resp-4", "done", 10),
+        ],
+    )
+    .await;
+    let test = builder().build(&server).await?;
+
+    test.submit_turn(&spec()).await?;
+    pin(&test, ItemRef::Turn { turn: 0, offset: 0 }).await?;
+    test.submit_turn("second turn").await?;
+    test.submit_turn("third turn").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(
+        requests[2]
+            .message_input_texts("user")
+            .iter()
+            .any(|text| text == SUMMARIZATION_PROMPT)
+    );
+    assert_spec_survived(&requests[3]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pins_are_restored_on_resume() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            reply("resp-1", "read the spec", 10),
+            reply("resp-2", "over budget", 10 * AUTO_COMPACT_LIMIT),
+            reply("resp-3", SUMMARY, 10),
+            reply("resp-4", "done", 10),
+        ],
+    )
+    .await;
+    let initial = builder().build(&server).await?;
+    initial.submit_turn(&spec()).await?;
+    pin(&initial, ItemRef::Turn { turn: 0, offset: 0 }).await?;
+
+    let resumed = builder()
+        .resume(&server, initial.home.clone(), initial.codex.rollout_path())
+        .await?;
+    resumed.submit_turn("second turn").await?;
+    resumed.submit_turn("third turn").await?;
+
+    assert_spec_survived(&responses.requests()[3]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unknown_and_unpinned_items_are_rejected() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(&server, vec![reply("resp-1", "ok", 10)]).await;
+    let test = builder().build(&server).await?;
+    test.submit_turn("first turn").await?;
+
+    test.codex
+        .pin_item(ItemRef::Turn { turn: 1, offset: 0 })
+        .await?;
+    let EventMsg::Error(error) =
+        wait_for_event(&test.codex, |event| matches!(event, EventMsg::Error(_))).await
+    else {
+        unreachable!("matched an error");
+    };
+    assert_eq!(error.message, "Unknown history item: item 0 of turn 1");
+
+    let first = ItemRef::Turn { turn: 0, offset: 0 };
+    pin(&test, first.clone()).await?;
+    test.codex.unpin_item(first.clone()).await?;
+    let unpinned = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ItemUnpinned(ev) => Some(ev.item.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(unpinned, first);
+    test.codex.unpin_item(first).await?;
+    let EventMsg::Error(error) =
+        wait_for_event(&test.codex, |event| matches!(event, EventMsg::Error(_))).await
+    else {
+        unreachable!("matched an error");
+    };
+    assert_eq!(
+        error.message,
+        "History item is not pinned: item 0 of turn 0"
+    );
+
+    Ok(())
+}
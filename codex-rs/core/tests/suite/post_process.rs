@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::post_process::PostProcessOutcome;
+use codex_core::post_process::TurnOutput;
+use codex_core::post_process::TurnPostProcessor;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+struct Uppercase;
+
+impl TurnPostProcessor for Uppercase {
+    fn process(&self, turn: &mut TurnOutput) -> PostProcessOutcome {
+        for text in turn.assistant_texts_mut() {
+            *text = text.to_uppercase();
+        }
+        PostProcessOutcome::Continue
+    }
+}
+
+struct RejectTodo;
+
+impl TurnPostProcessor for RejectTodo {
+    fn process(&self, turn: &mut TurnOutput) -> PostProcessOutcome {
+        if turn.assistant_texts_mut().any(|text| text.contains("TODO")) {
+            return PostProcessOutcome::RejectTurn {
+                reason: "output contains TODO".to_string(),
+            };
+        }
+        PostProcessOutcome::Continue
+    }
+}
+
+async fn mount_reply(server: &MockServer, id: &str, text: &str) -> ResponseMock {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created(&format!("resp-{id}")),
+            ev_assistant_message(&format!("msg-{id}"), text),
+            ev_completed(&format!("resp-{id}")),
+        ]),
+    )
+    .await
+}
+
+async fn conversation_with(
+    test: &TestCodex,
+    post_processors: Vec<Arc<dyn TurnPostProcessor>>,
+) -> Result<Arc<CodexConversation>> {
+    Ok(test
+        .conversation_manager
+        .new_conversation_with_post_processors(test.config.clone(), post_processors)
+        .await?
+        .conversation)
+}
+
+async fn submit(conversation: &CodexConversation, text: &str) -> Result<()> {
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+fn assistant_texts(request: &ResponsesRequest) -> Vec<String> {
+    request
+        .inputs_of_type("message")
+        .into_iter()
+        .filter(|item| item["role"] == "assistant")
+        .filter_map(|item| item["content"][0]["text"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn uppercase_processor_rewrites_committed_output() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let conversation = conversation_with(&test, vec![Arc::new(Uppercase)]).await?;
+
+    mount_reply(&server, "1", "hello world").await;
+    let follow_up = mount_reply(&server, "2", "again").await;
+
+    submit(&conversation, "greet me").await?;
+    let message = wait_for_event(&conversation, |ev| matches!(ev, EventMsg::AgentMessage(_))).await;
+    let EventMsg::AgentMessage(message) = message else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(message.message, "HELLO WORLD");
+    let complete =
+        wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(complete.last_agent_message.as_deref(), Some("HELLO WORLD"));
+
+    submit(&conversation, "once more").await?;
+    wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(
+        assistant_texts(&follow_up.single_request()),
+        vec!["HELLO WORLD".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn todo_processor_rejects_turn_without_committing_it() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    // Uppercasing first turns "todo" into "TODO", so the order matters.
+    let conversation =
+        conversation_with(&test, vec![Arc::new(Uppercase), Arc::new(RejectTodo)]).await?;
+
+    mount_reply(&server, "1", "todo: write the code").await;
+    let follow_up = mount_reply(&server, "2", "done").await;
+
+    submit(&conversation, "write the code").await?;
+    let event = wait_for_event(&conversation, |ev| {
+        matches!(ev, EventMsg::TurnRejected(_) | EventMsg::AgentMessage(_))
+    })
+    .await;
+    let EventMsg::TurnRejected(rejected) = event else {
+        panic!("expected the turn to be rejected, got {event:?}");
+    };
+    assert_eq!(rejected.reason, "output contains TODO");
+    let complete =
+        wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(complete.last_agent_message, None);
+
+    submit(&conversation, "try again").await?;
+    wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let request = follow_up.single_request();
+    assert_eq!(assistant_texts(&request), Vec::<String>::new());
+    assert_eq!(
+        request
+            .message_input_texts("user")
+            .last()
+            .map(String::as_str),
+        Some("try again")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::pre_process::PreflightDecision;
+use codex_core::pre_process::TurnPreProcessor;
+use codex_core::pre_process::UserTurnInput;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+const PASSWORD: &str = "hunter2";
+
+struct RedactPassword;
+
+impl TurnPreProcessor for RedactPassword {
+    fn process(&self, input: &mut UserTurnInput) -> PreflightDecision {
+        let mut rewritten = false;
+        for text in input.texts_mut() {
+            if text.contains(PASSWORD) {
+                *text = text.replace(PASSWORD, "[redacted]");
+                rewritten = true;
+            }
+        }
+        if rewritten {
+            PreflightDecision::Rewrite
+        } else {
+            PreflightDecision::Proceed
+        }
+    }
+}
+
+struct RejectProdDeploys;
+
+impl TurnPreProcessor for RejectProdDeploys {
+    fn process(&self, input: &mut UserTurnInput) -> PreflightDecision {
+        if input.texts_mut().any(|text| text.contains("to prod")) {
+            return PreflightDecision::Reject {
+                reason: "production changes need a ticket".to_string(),
+            };
+        }
+        PreflightDecision::Proceed
+    }
+}
+
+async fn mount_reply(server: &MockServer, id: &str) -> ResponseMock {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created(&format!("resp-{id}")),
+            ev_assistant_message(&format!("msg-{id}"), "ok"),
+            ev_completed(&format!("resp-{id}")),
+        ]),
+    )
+    .await
+}
+
+async fn submit(conversation: &CodexConversation, text: &str) -> Result<()> {
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Shut the conversation down so its rollout is flushed, then read it.
+async fn rollout_contents(conversation: &CodexConversation, path: &Path) -> Result<String> {
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rewriting_processor_changes_what_the_model_sees() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    test.conversation_manager
+        .register_turn_pre_processor(Arc::new(RedactPassword));
+    let new = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?;
+    let conversation = &new.conversation;
+
+    let turn = mount_reply(&server, "1").await;
+    submit(conversation, &format!("log in with {PASSWORD}")).await?;
+    wait_for_event(conversation, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let request = turn.single_request();
+    assert_eq!(
+        request
+            .message_input_texts("user")
+            .last()
+            .map(String::as_str),
+        Some("log in with [redacted]")
+    );
+    assert!(!request.body_json().to_string().contains(PASSWORD));
+
+    let rollout = rollout_contents(conversation, &new.session_configured.rollout_path).await?;
+    assert!(rollout.contains("log in with [redacted]"));
+    assert!(!rollout.contains(PASSWORD));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rejected_input_leaves_only_an_audit_marker() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.record_input_rejections = true)
+        .build(&server)
+        .await?;
+    let new = test
+        .conversation_manager
+        .new_conversation_with_pre_processors(
+            test.config.clone(),
+            vec![Arc::new(RedactPassword), Arc::new(RejectProdDeploys)],
+        )
+        .await?;
+    let conversation = &new.conversation;
+
+    submit(conversation, &format!("deploy with {PASSWORD} to prod")).await?;
+    let rejected = wait_for_event_match(conversation, |event| match event {
+        EventMsg::InputRejected(rejected) => Some(rejected.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(rejected.reason, "production changes need a ticket");
+    assert_eq!(rejected.rule, None);
+    assert_eq!(rejected.span, None);
+
+    // The conversation carries on without the rejected submission.
+    let turn = mount_reply(&server, "1").await;
+    submit(conversation, "what is deployed?").await?;
+    wait_for_event(conversation, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    let body = turn.single_request().body_json().to_string();
+    assert!(body.contains("what is deployed?"));
+    assert!(!body.contains("to prod"));
+
+    let rollout = rollout_contents(conversation, &new.session_configured.rollout_path).await?;
+    assert!(rollout.contains(r#""type":"input_rejected""#));
+    assert!(rollout.contains("production changes need a ticket"));
+    assert!(!rollout.contains("to prod"));
+    assert!(!rollout.contains(PASSWORD));
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::WarningCode;
+use codex_protocol::approvals::ProjectApprovalRule;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::MockServer;
+
+async fn mount_touch_turn(server: &MockServer, call_id: &str, file: &str) -> Result<()> {
+    let args = serde_json::to_string(&json!({
+        "command": format!("touch {file}"),
+        "timeout_ms": 1_000,
+    }))?;
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created(&format!("resp-{call_id}-1")),
+            ev_function_call(call_id, "shell_command", &args),
+            ev_completed(&format!("resp-{call_id}-1")),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_assistant_message(&format!("msg-{call_id}"), "done"),
+            ev_completed(&format!("resp-{call_id}-2")),
+        ]),
+    )
+    .await;
+    Ok(())
+}
+
+async fn submit_unless_trusted(
+    test: &TestCodex,
+    conversation: &CodexConversation,
+    prompt: &str,
+) -> Result<()> {
+    conversation
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: prompt.into(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd_path().to_path_buf(),
+            approval_policy: AskForApproval::UnlessTrusted,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn project_approval_carries_over_to_new_conversations() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    let rule = ProjectApprovalRule::Prefix {
+        prefix: vec!["touch".to_string()],
+    };
+
+    mount_touch_turn(&server, "first-touch", "first.txt").await?;
+    submit_unless_trusted(&test, &test.codex, "first").await?;
+    let approval = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ExecApprovalRequest(_))
+    })
+    .await;
+    let EventMsg::ExecApprovalRequest(approval) = approval else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(
+        approval.command.last().map(String::as_str),
+        Some("touch first.txt")
+    );
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id.clone(),
+            decision: ReviewDecision::ApprovedForProject { rule: rule.clone() },
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(test.cwd_path().join("first.txt").exists());
+
+    let second = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?
+        .conversation;
+    mount_touch_turn(&server, "second-touch", "second.txt").await?;
+    submit_unless_trusted(&test, &second, "second").await?;
+    let event = wait_for_event(&second, |event| {
+        matches!(
+            event,
+            EventMsg::ExecApprovalRequest(_) | EventMsg::TaskComplete(_)
+        )
+    })
+    .await;
+    assert!(
+        matches!(event, EventMsg::TaskComplete(_)),
+        "unexpected event: {event:?}"
+    );
+    assert!(test.cwd_path().join("second.txt").exists());
+
+    let manager = &test.conversation_manager;
+    assert_eq!(
+        manager.list_project_approval_rules(test.cwd_path()).await?,
+        vec![rule.clone()]
+    );
+    assert!(
+        manager
+            .revoke_project_approval_rule(test.cwd_path(), &rule)
+            .await?
+    );
+    assert_eq!(
+        manager.list_project_approval_rules(test.cwd_path()).await?,
+        Vec::new()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn project_rule_not_covering_the_command_is_not_saved() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    let rule = ProjectApprovalRule::Prefix {
+        prefix: vec!["rm".to_string()],
+    };
+
+    mount_touch_turn(&server, "first-touch", "first.txt").await?;
+    submit_unless_trusted(&test, &test.codex, "first").await?;
+    let approval = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ExecApprovalRequest(_))
+    })
+    .await;
+    let EventMsg::ExecApprovalRequest(approval) = approval else {
+        unreachable!("filtered above");
+    };
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id.clone(),
+            decision: ReviewDecision::ApprovedForProject { rule },
+        })
+        .await?;
+    let warning = wait_for_event(&test.codex, |event| matches!(event, EventMsg::Warning(_))).await;
+    let EventMsg::Warning(warning) = warning else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(warning.code, WarningCode::ProjectApprovalNotSaved);
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(test.cwd_path().join("first.txt").exists());
+    assert_eq!(
+        test.conversation_manager
+            .list_project_approval_rules(test.cwd_path())
+            .await?,
+        Vec::new()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn safe_mode_asks_for_commands_a_project_rule_allows() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    let rule = ProjectApprovalRule::Prefix {
+        prefix: vec!["touch".to_string()],
+    };
+
+    mount_touch_turn(&server, "first-touch", "first.txt").await?;
+    submit_unless_trusted(&test, &test.codex, "first").await?;
+    let approval = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ExecApprovalRequest(_))
+    })
+    .await;
+    let EventMsg::ExecApprovalRequest(approval) = approval else {
+        unreachable!("filtered above");
+    };
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id.clone(),
+            decision: ReviewDecision::ApprovedForProject { rule: rule.clone() },
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    test.codex.set_safe_mode(true).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::SafeModeChanged(_))
+    })
+    .await;
+    mount_touch_turn(&server, "second-touch", "second.txt").await?;
+    submit_unless_trusted(&test, &test.codex, "second").await?;
+    let event = wait_for_event(&test.codex, |event| {
+        matches!(
+            event,
+            EventMsg::ExecApprovalRequest(_) | EventMsg::TaskComplete(_)
+        )
+    })
+    .await;
+    let EventMsg::ExecApprovalRequest(approval) = event else {
+        panic!("the project rule approved a command in safe mode: {event:?}");
+    };
+    assert_eq!(
+        approval.command.last().map(String::as_str),
+        Some("touch second.txt")
+    );
+    assert_eq!(approval.proposed_execpolicy_amendment, None);
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id.clone(),
+            decision: ReviewDecision::Denied,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(!test.cwd_path().join("second.txt").exists());
+
+    Ok(())
+}
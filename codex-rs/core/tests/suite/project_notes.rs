@@ -0,0 +1,259 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::RolloutRecorder;
+use codex_core::config::Config;
+use codex_core::features::Feature;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const NOTE: &str = "We use workspace-hack; never touch Cargo.lock by hand.";
+
+fn init_git_repo(path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["init", "--initial-branch=main"])
+        .current_dir(path)
+        .output()
+        .context("failed to run git init")?;
+    if !output.status.success() {
+        bail!("git init exited with {}", output.status);
+    }
+    Ok(())
+}
+
+async fn mount_reply(server: &MockServer) -> ResponseMock {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created("resp"),
+            ev_assistant_message("msg", "done"),
+            ev_completed("resp"),
+        ]),
+    )
+    .await
+}
+
+/// The texts of the user messages in the single request `mock` received.
+fn user_texts(mock: &ResponseMock) -> Vec<String> {
+    mock.single_request()
+        .input()
+        .iter()
+        .filter(|item| item["role"] == "user")
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .map(str::to_string)
+        .collect()
+}
+
+fn in_cwd(test: &TestCodex, cwd: &Path) -> Config {
+    let mut config = test.config.clone();
+    config.cwd = cwd.to_path_buf();
+    config
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn notes_reach_new_sessions_in_the_same_repository_only() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::ProjectNotes);
+        })
+        .build(&server)
+        .await?;
+    let repo = test.cwd_path();
+    init_git_repo(repo)?;
+    let subdir = repo.join("crates");
+    std::fs::create_dir(&subdir)?;
+    let other_repo = TempDir::new()?;
+    init_git_repo(other_repo.path())?;
+
+    let manager = &test.conversation_manager;
+    let note = manager.notes(repo).add(NOTE).await?;
+    assert_eq!(manager.notes(&subdir).list().await?, vec![note.clone()]);
+
+    // A session anywhere in the repository is told about the note.
+    let same = manager.new_conversation(in_cwd(&test, &subdir)).await?;
+    let mock = mount_reply(&server).await;
+    TestCodex::submit_input_to(&same.conversation, "hello").await?;
+    let prefix = user_texts(&mock)
+        .into_iter()
+        .find(|text| text.starts_with("<project_notes>"))
+        .context("the prefix carries the notes")?;
+    assert!(prefix.contains(NOTE), "{prefix}");
+
+    same.conversation.submit(Op::Shutdown).await?;
+    wait_for_event(&same.conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let meta = RolloutRecorder::get_rollout_history(&same.session_configured.rollout_path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta),
+            _ => None,
+        })
+        .context("session meta")?;
+    assert_eq!(meta.project_notes, Some(vec![note.id]));
+
+    // A session in another repository is not.
+    let other = manager
+        .new_conversation(in_cwd(&test, other_repo.path()))
+        .await?;
+    let mock = mount_reply(&server).await;
+    TestCodex::submit_input_to(&other.conversation, "hello").await?;
+    assert!(
+        !user_texts(&mock)
+            .iter()
+            .any(|text| text.starts_with("<project_notes>") || text.contains(NOTE))
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn notes_injection_can_be_disabled_per_conversation() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::ProjectNotes);
+        })
+        .build(&server)
+        .await?;
+    test.conversation_manager
+        .notes(test.cwd_path())
+        .add(NOTE)
+        .await?;
+
+    let mut config = test.config.clone();
+    config.features.disable(Feature::ProjectNotes);
+    let conversation = test.conversation_manager.new_conversation(config).await?;
+    let mock = mount_reply(&server).await;
+    TestCodex::submit_input_to(&conversation.conversation, "hello").await?;
+
+    assert!(!user_texts(&mock).iter().any(|text| text.contains(NOTE)));
+    let tools = mock.single_request().body_json()["tools"].to_string();
+    assert!(!tools.contains("add_project_note"), "{tools}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn the_model_saves_notes_the_user_approves() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::ProjectNotes);
+        })
+        .build(&server)
+        .await?;
+
+    let args = serde_json::to_string(&json!({ "note": NOTE }))?;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_function_call("call-1", "add_project_note", &args),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let reply = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-2", "noted"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "remember that we never touch Cargo.lock by hand".to_string(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd_path().to_path_buf(),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+
+    let approval = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ExecApprovalRequest(_))
+    })
+    .await;
+    let EventMsg::ExecApprovalRequest(approval) = approval else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(approval.call_id, "call-1");
+    assert_eq!(approval.command, vec!["add_project_note", NOTE]);
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id.clone(),
+            decision: ReviewDecision::Approved,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let notes = test
+        .conversation_manager
+        .notes(test.cwd_path())
+        .list()
+        .await?;
+    assert_eq!(
+        notes
+            .iter()
+            .map(|note| note.text.as_str())
+            .collect::<Vec<_>>(),
+        vec![NOTE]
+    );
+    let output = reply
+        .single_request()
+        .function_call_output_text("call-1")
+        .context("the tool output")?;
+    assert!(output.contains(&notes[0].id), "{output}");
+
+    Ok(())
+}
@@ -102,6 +102,7 @@ async fn prompt_tools_are_consistent_across_requests() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello 1".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -111,6 +112,7 @@ async fn prompt_tools_are_consistent_across_requests() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello 2".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -175,6 +177,7 @@ async fn codex_mini_latest_tools() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello 1".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -184,6 +187,7 @@ async fn codex_mini_latest_tools() -> anyhow::Result<()> {
             items: vec![UserInput::Text {
                 text: "hello 2".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -238,6 +242,7 @@ async fn prefixes_context_and_instructions_once_and_consistently_across_requests
             items: vec![UserInput::Text {
                 text: "hello 1".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -247,6 +252,7 @@ async fn prefixes_context_and_instructions_once_and_consistently_across_requests
             items: vec![UserInput::Text {
                 text: "hello 2".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -307,6 +313,7 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() -> an
             items: vec![UserInput::Text {
                 text: "hello 1".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -325,6 +332,9 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() -> an
             model: Some("o3".to_string()),
             effort: Some(Some(ReasoningEffort::High)),
             summary: Some(ReasoningSummary::Detailed),
+            execution_mode: None,
+            sampling: None,
+            offline: None,
         })
         .await?;
 
@@ -334,6 +344,7 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() -> an
             items: vec![UserInput::Text {
                 text: "hello 2".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -404,6 +415,9 @@ async fn override_before_first_turn_emits_environment_context() -> anyhow::Resul
             model: None,
             effort: None,
             summary: None,
+            execution_mode: None,
+            sampling: None,
+            offline: None,
         })
         .await?;
 
@@ -412,6 +426,7 @@ async fn override_before_first_turn_emits_environment_context() -> anyhow::Resul
             items: vec![UserInput::Text {
                 text: "first message".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -504,6 +519,7 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() -> anyhow::Res
             items: vec![UserInput::Text {
                 text: "hello 1".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -528,6 +544,9 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() -> anyhow::Res
             effort: Some(ReasoningEffort::High),
             summary: ReasoningSummary::Detailed,
             final_output_json_schema: None,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -622,6 +641,9 @@ async fn send_user_turn_with_no_changes_does_not_send_environment_context() -> a
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -638,6 +660,9 @@ async fn send_user_turn_with_no_changes_does_not_send_environment_context() -> a
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -712,6 +737,9 @@ async fn send_user_turn_with_changes_sends_environment_context() -> anyhow::Resu
             effort: default_effort,
             summary: default_summary,
             final_output_json_schema: None,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
@@ -728,6 +756,9 @@ async fn send_user_turn_with_changes_sends_environment_context() -> anyhow::Resu
             effort: Some(ReasoningEffort::High),
             summary: ReasoningSummary::Detailed,
             final_output_json_schema: None,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
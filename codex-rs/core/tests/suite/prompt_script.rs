@@ -0,0 +1,207 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::rollout::PromptScript;
+use codex_core::rollout::ScriptPrompt;
+use codex_core::rollout::extract_prompt_script;
+use codex_core::script_run::OnStepFailure;
+use codex_core::script_run::ScriptRunOptions;
+use codex_core::script_run::ScriptStepOutcome;
+use codex_protocol::protocol::TurnId;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_failed;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/prompt_script/session.jsonl")
+}
+
+fn text(text: &str) -> UserInput {
+    UserInput::Text {
+        text: text.to_string(),
+    }
+}
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(id),
+    ])
+}
+
+fn final_message(outcome: &ScriptStepOutcome) -> Option<&str> {
+    match outcome {
+        ScriptStepOutcome::Completed(result) => result.final_message.as_deref(),
+        other => panic!("step did not complete: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn extract_keeps_the_prompts_and_drops_the_answers() -> Result<()> {
+    let script = extract_prompt_script(&fixture()).await?;
+
+    assert_eq!(
+        script,
+        PromptScript {
+            prompts: vec![
+                ScriptPrompt {
+                    ordinal: 0,
+                    turn_id: Some(TurnId::new(1)),
+                    items: vec![text("list the files")],
+                },
+                ScriptPrompt {
+                    ordinal: 1,
+                    turn_id: Some(TurnId::new(2)),
+                    items: vec![
+                        text("what does this diagram show?"),
+                        UserInput::Image {
+                            image_url: "https://example.com/diagram.png".to_string(),
+                        },
+                    ],
+                },
+                ScriptPrompt {
+                    ordinal: 2,
+                    turn_id: Some(TurnId::new(3)),
+                    items: vec![text("summarize the README")],
+                },
+            ],
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn run_script_records_the_same_prompts_with_new_answers() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let requests = mount_sse_sequence(
+        &server,
+        vec![
+            answer("1", "new files answer"),
+            answer("2", "new diagram answer"),
+            answer("3", "new summary"),
+        ],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let script = extract_prompt_script(&fixture()).await?;
+
+    let report = test
+        .conversation_manager
+        .run_script(&script, test.config.clone(), ScriptRunOptions::default())
+        .await?;
+
+    let answers: Vec<_> = report
+        .steps
+        .iter()
+        .map(|step| (step.ordinal, final_message(&step.outcome)))
+        .collect();
+    assert_eq!(
+        answers,
+        vec![
+            (0, Some("new files answer")),
+            (1, Some("new diagram answer")),
+            (2, Some("new summary")),
+        ]
+    );
+    assert_eq!(requests.requests().len(), 3);
+    let diagram_request = requests.requests()[1].body_json().to_string();
+    assert!(
+        diagram_request.contains("https://example.com/diagram.png"),
+        "{diagram_request}"
+    );
+
+    let replayed = extract_prompt_script(&report.rollout_path).await?;
+    let prompts = |script: &PromptScript| -> Vec<Vec<UserInput>> {
+        script
+            .prompts
+            .iter()
+            .map(|prompt| prompt.items.clone())
+            .collect()
+    };
+    assert_eq!(prompts(&replayed), prompts(&script));
+    let rollout = std::fs::read_to_string(&report.rollout_path)?;
+    assert!(rollout.contains("new summary"), "{rollout}");
+    assert!(
+        !rollout.contains("It explains how to build the project."),
+        "the recorded answers are not replayed"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_failed_step_skips_the_rest_unless_asked_to_continue() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let too_long = || {
+        sse_failed(
+            "2",
+            "context_length_exceeded",
+            "Your input exceeds the context window of this model.",
+        )
+    };
+    let server = start_mock_server().await;
+    let requests = mount_sse_sequence(
+        &server,
+        vec![
+            answer("1", "first"),
+            too_long(),
+            answer("3", "first again"),
+            too_long(),
+            answer("5", "third"),
+        ],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let script = extract_prompt_script(&fixture()).await?;
+
+    let aborted = test
+        .conversation_manager
+        .run_script(&script, test.config.clone(), ScriptRunOptions::default())
+        .await?;
+    assert!(aborted.steps[0].outcome.is_completed());
+    assert!(matches!(
+        aborted.steps[1].outcome,
+        ScriptStepOutcome::Failed(_)
+    ));
+    assert!(matches!(
+        aborted.steps[2].outcome,
+        ScriptStepOutcome::Skipped
+    ));
+    assert_eq!(requests.requests().len(), 2);
+
+    let continued = test
+        .conversation_manager
+        .run_script(
+            &script,
+            test.config.clone(),
+            ScriptRunOptions {
+                on_failure: OnStepFailure::Continue,
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert_ne!(continued.conversation_id, aborted.conversation_id);
+    assert!(matches!(
+        continued.steps[1].outcome,
+        ScriptStepOutcome::Failed(_)
+    ));
+    assert_eq!(final_message(&continued.steps[2].outcome), Some("third"));
+    assert_eq!(requests.requests().len(), 5);
+
+    Ok(())
+}
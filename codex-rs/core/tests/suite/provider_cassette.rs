@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::ModelProviderInfo;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::provider_cassette::PlaybackProvider;
+use codex_core::provider_cassette::RecordingProvider;
+use codex_protocol::models::ResponseItem;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const PROMPTS: [&str; 2] = ["first question", "second question"];
+
+/// Run the same two turns against `provider` from `cwd` and return the
+/// resulting transcript.
+async fn run_session(
+    server: &MockServer,
+    provider: ModelProviderInfo,
+    cwd: Arc<TempDir>,
+) -> Result<Vec<ResponseItem>> {
+    let mut builder = test_codex().with_config(move |config| {
+        config.model_provider = provider;
+        config.cwd = cwd.path().to_path_buf();
+    });
+    let test = builder.build(server).await?;
+    for prompt in PROMPTS {
+        test.submit_turn(prompt).await?;
+    }
+    let transcript = test.codex.transcript().await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(transcript)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn played_back_session_matches_the_recorded_one() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let upstream = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_assistant_message("msg-1", "first answer"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-2", "second answer"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let cwd = Arc::new(TempDir::new()?);
+    let cassettes = TempDir::new()?;
+    let cassette_path = cassettes.path().join("session.json");
+
+    let upstream_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..codex_core::built_in_model_providers()["openai"].clone()
+    };
+    let recorder = RecordingProvider::start(upstream_provider, &cassette_path).await?;
+    let recorded = run_session(&server, (&recorder).into(), Arc::clone(&cwd)).await?;
+    recorder.finish().await?;
+    assert_eq!(upstream.requests().len(), PROMPTS.len());
+
+    let cassette = std::fs::read_to_string(&cassette_path)?;
+    assert!(!cassette.contains("Bearer"), "{cassette}");
+
+    let playback = PlaybackProvider::load(&cassette_path).await?;
+    let played = run_session(&server, (&playback).into(), cwd).await?;
+
+    assert_eq!(playback.mismatches(), Vec::<String>::new());
+    assert_eq!(playback.unplayed(), 0);
+    assert_eq!(played, recorded);
+    // Playback never reaches the recorded provider.
+    assert_eq!(upstream.requests().len(), PROMPTS.len());
+
+    Ok(())
+}
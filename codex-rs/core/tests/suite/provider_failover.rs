@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ProviderEndpointSwitchedEvent;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_response_once;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use wiremock::ResponseTemplate;
+
+const COOLDOWN_MS: u64 = 200;
+
+fn assistant_turn(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), "ok"),
+        ev_completed(id),
+    ])
+}
+
+/// The endpoint switches among the events of a turn.
+fn endpoint_switches(events: Vec<EventMsg>) -> Vec<ProviderEndpointSwitchedEvent> {
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            EventMsg::ProviderEndpointSwitched(switch) => Some(switch),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fails_over_to_fallback_and_recovers_after_cooldown() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let primary = start_mock_server().await;
+    let fallback = start_mock_server().await;
+    let primary_failure = mount_response_once(&primary, ResponseTemplate::new(503)).await;
+    let primary_recovered = mount_sse_once(&primary, assistant_turn("resp-3")).await;
+    let fallback_responses = mount_sse_sequence(
+        &fallback,
+        vec![assistant_turn("resp-1"), assistant_turn("resp-2")],
+    )
+    .await;
+
+    let primary_url = format!("{}/v1", primary.uri());
+    let fallback_url = format!("{}/v1", fallback.uri());
+    let fallback_for_config = fallback_url.clone();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.model_provider.fallback_base_urls = Some(vec![fallback_for_config]);
+            config.model_provider.endpoint_cooldown_ms = Some(COOLDOWN_MS);
+            config.model_provider.request_max_retries = Some(0);
+        })
+        .build(&primary)
+        .await?;
+
+    let switches = endpoint_switches(test.submit_turn_events("first").await?);
+    assert_eq!(primary_failure.requests().len(), 1);
+    assert_eq!(switches.len(), 1);
+    assert_eq!(switches[0].from, primary_url);
+    assert_eq!(switches[0].to, fallback_url);
+
+    // The primary is still cooling down, so the fallback serves directly.
+    let switches = endpoint_switches(test.submit_turn_events("second").await?);
+    assert!(switches.is_empty());
+    assert_eq!(fallback_responses.requests().len(), 2);
+    assert!(primary_recovered.requests().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(COOLDOWN_MS * 2)).await;
+
+    let switches = endpoint_switches(test.submit_turn_events("third").await?);
+    assert_eq!(primary_recovered.requests().len(), 1);
+    assert_eq!(switches.len(), 1);
+    assert_eq!(switches[0].from, fallback_url);
+    assert_eq!(switches[0].to, primary_url);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::RolloutLine;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn read_then_reply(call_id: &str, arguments: &str, turn: usize) -> Vec<String> {
+    vec![
+        sse(vec![
+            ev_response_created(&format!("resp-{turn}-1")),
+            ev_function_call(call_id, "read_file", arguments),
+            ev_completed(&format!("resp-{turn}-1")),
+        ]),
+        sse(vec![
+            ev_assistant_message(&format!("msg-{turn}"), "read it"),
+            ev_completed(&format!("resp-{turn}-2")),
+        ]),
+    ]
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_reads_are_replaced_by_a_reference() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .with_config(|config| {
+            config.read_cache_min_bytes = Some(16);
+        })
+        .build(&server)
+        .await?;
+
+    let file_path = test.cwd.path().join("notes.txt");
+    std::fs::write(&file_path, "first line\nsecond line\nthird line\n")?;
+    let arguments = json!({ "file_path": file_path.to_string_lossy() }).to_string();
+
+    let mock = mount_sse_sequence(
+        &server,
+        [
+            read_then_reply("call-1", &arguments, 1),
+            read_then_reply("call-2", &arguments, 2),
+            read_then_reply("call-3", &arguments, 3),
+        ]
+        .concat(),
+    )
+    .await;
+
+    test.submit_turn("read the notes").await?;
+    test.submit_turn("read the notes again").await?;
+    std::fs::write(&file_path, "first line\nchanged line\nthird line\n")?;
+    test.submit_turn("read the notes once more").await?;
+
+    let full = mock
+        .function_call_output_text("call-1")
+        .expect("call-1 output");
+    assert_eq!(full, "L1: first line\nL2: second line\nL3: third line");
+    let reference = mock
+        .function_call_output_text("call-2")
+        .expect("call-2 output");
+    assert!(
+        reference.starts_with("Unchanged since turn 1 (call call-1, sha256 "),
+        "{reference}"
+    );
+    let fresh = mock
+        .function_call_output_text("call-3")
+        .expect("call-3 output");
+    assert_eq!(fresh, "L1: first line\nL2: changed line\nL3: third line");
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    let references: Vec<(String, String)> = std::fs::read_to_string(test.codex.rollout_path())?
+        .lines()
+        .map(|line| serde_json::from_str::<RolloutLine>(line).expect("rollout line"))
+        .filter_map(|line| match line.item {
+            RolloutItem::ReadReference(item) => Some((item.call_id, item.original_call_id)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        references,
+        vec![("call-2".to_string(), "call-1".to_string())]
+    );
+
+    Ok(())
+}
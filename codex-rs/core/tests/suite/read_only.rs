@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_app_server_protocol::ThreadItem;
+use codex_app_server_protocol::TurnStatus;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::protocol::SessionSource;
+use codex_protocol::user_input::UserInput;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+const CONVERSATION_ID: &str = "0195468e-7a1b-7c3d-9e4f-5a6b7c8d9e0f";
+
+/// Copy the recorded session into `dir`, so resuming it leaves the fixture
+/// untouched.
+fn fixture_rollout(dir: &TempDir) -> Result<PathBuf> {
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/read_only/session.jsonl");
+    let path = dir.path().join("rollout.jsonl");
+    std::fs::copy(fixture, &path)?;
+    Ok(path)
+}
+
+/// A manager whose auth cannot be loaded, so any attempt to reach a model
+/// would fail.
+fn broken_auth_manager(codex_home: &TempDir) -> Result<ConversationManager> {
+    std::fs::write(codex_home.path().join("auth.json"), "not json")?;
+    let auth_manager = AuthManager::shared(
+        codex_home.path().to_path_buf(),
+        false,
+        AuthCredentialsStoreMode::File,
+    );
+    assert!(auth_manager.auth().is_none());
+    Ok(ConversationManager::new(auth_manager, SessionSource::Exec))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn browsing_a_rollout_needs_no_auth() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = fixture_rollout(&dir)?;
+    let codex_home = TempDir::new()?;
+    let manager = broken_auth_manager(&codex_home)?;
+
+    let transcript = manager.open_readonly(&path).await?;
+    assert_eq!(
+        transcript.conversation_id().map(|id| id.to_string()),
+        Some(CONVERSATION_ID.to_string())
+    );
+    assert_eq!(
+        transcript.session_meta().map(|meta| meta.cwd),
+        Some(PathBuf::from("/workspace/demo"))
+    );
+    assert!(transcript.parse_errors().is_empty());
+
+    let turns = transcript.turns();
+    assert_eq!(
+        turns
+            .iter()
+            .map(|turn| turn.status.clone())
+            .collect::<Vec<_>>(),
+        vec![TurnStatus::Completed, TurnStatus::Interrupted]
+    );
+    assert!(matches!(
+        turns[0].items.last(),
+        Some(ThreadItem::AgentMessage { text, .. }) if text == "Added README.md."
+    ));
+
+    assert_eq!(
+        transcript
+            .fork_points()
+            .iter()
+            .map(|point| point.snippet.as_str())
+            .collect::<Vec<_>>(),
+        vec!["add a README", "now add a license"]
+    );
+
+    let stats = transcript.stats();
+    assert_eq!(stats.turns, 1);
+    assert_eq!(stats.token_usage.total_tokens, 1200);
+    assert_eq!(stats.duration_ms, 150_000);
+    assert_eq!(
+        stats.files_changed,
+        vec![PathBuf::from("/workspace/demo/README.md")]
+    );
+    assert_eq!(stats.rollout_path, Some(path.clone()));
+
+    assert_eq!(transcript.transcript().await?.len(), 3);
+    // Nothing was spawned for the rollout.
+    let conversation_id = transcript.conversation_id().expect("conversation id");
+    assert!(manager.get_conversation(conversation_id).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_only_transcript_upgrades_to_a_live_session() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let dir = TempDir::new()?;
+    let path = fixture_rollout(&dir)?;
+    let broken_home = TempDir::new()?;
+    let transcript = broken_auth_manager(&broken_home)?
+        .open_readonly(&path)
+        .await?;
+
+    let server = start_mock_server().await;
+    let codex_home = TempDir::new()?;
+    let mut config = load_default_config_for_test(&codex_home).await;
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("Test API Key"),
+        config.model_provider.clone(),
+        config.codex_home.clone(),
+    );
+    let response = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp"),
+            ev_assistant_message("msg", "Added LICENSE."),
+            ev_completed("resp"),
+        ]),
+    )
+    .await;
+
+    let resumed = manager
+        .resume_read_only(
+            transcript,
+            config,
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key")),
+        )
+        .await?;
+    assert_eq!(
+        resumed.conversation_id.to_string(),
+        CONVERSATION_ID.to_string()
+    );
+    let codex = resumed.conversation;
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "try the license again".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+    let body = response.single_request().body_json().to_string();
+    assert!(body.contains("add a README"), "{body}");
+    assert!(body.contains("try the license again"), "{body}");
+
+    Ok(())
+}
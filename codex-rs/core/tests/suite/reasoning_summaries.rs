@@ -0,0 +1,107 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_reasoning_item;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+const FIRST_SUMMARY: &str = "reading the manifest";
+const SECOND_SUMMARIES: [&str; 2] = ["listing the tests", "picking the failing one"];
+
+/// Run two turns whose responses carry reasoning summaries.
+async fn run_two_turns(persist_reasoning_summaries: bool) -> Result<TestCodex> {
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_reasoning_item("reasoning-1", &[FIRST_SUMMARY], &[]),
+                ev_assistant_message("msg-1", "first answer"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_reasoning_item("reasoning-2", &SECOND_SUMMARIES, &[]),
+                ev_assistant_message("msg-2", "second answer"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(move |config| {
+            config.persist_reasoning_summaries = persist_reasoning_summaries;
+        })
+        .build(&server)
+        .await?;
+    test.submit_input("first question").await?;
+    test.submit_input("second question").await?;
+    Ok(test)
+}
+
+async fn rollout_text(test: &TestCodex) -> Result<String> {
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(std::fs::read_to_string(
+        &test.session_configured.rollout_path,
+    )?)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reasoning_summaries_are_retrievable_by_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = run_two_turns(true).await?;
+    assert_eq!(
+        test.codex.reasoning_for_turn(0),
+        Some(vec![FIRST_SUMMARY.to_string()])
+    );
+    assert_eq!(
+        test.codex.reasoning_for_turn(1),
+        Some(SECOND_SUMMARIES.map(String::from).to_vec())
+    );
+    assert_eq!(test.codex.reasoning_for_turn(2), None);
+
+    let rollout = rollout_text(&test).await?;
+    assert!(rollout.contains(FIRST_SUMMARY), "{rollout}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn disabled_persistence_keeps_summaries_out_of_the_rollout() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = run_two_turns(false).await?;
+    // Summaries are still available to the running session.
+    assert_eq!(
+        test.codex.reasoning_for_turn(0),
+        Some(vec![FIRST_SUMMARY.to_string()])
+    );
+
+    let rollout = rollout_text(&test).await?;
+    for summary in std::iter::once(FIRST_SUMMARY).chain(SECOND_SUMMARIES) {
+        assert!(!rollout.contains(summary), "{rollout}");
+    }
+    // The encrypted content is kept so the provider can continue the
+    // conversation after a resume.
+    assert!(rollout.contains("encrypted_content"), "{rollout}");
+
+    Ok(())
+}
@@ -135,6 +135,9 @@ async fn remote_models_remote_model_uses_unified_exec() -> Result<()> {
             model: Some(REMOTE_MODEL_SLUG.to_string()),
             effort: None,
             summary: None,
+            execution_mode: None,
+            sampling: None,
+            offline: None,
         })
         .await?;
 
@@ -169,6 +172,9 @@ async fn remote_models_remote_model_uses_unified_exec() -> Result<()> {
             model: REMOTE_MODEL_SLUG.to_string(),
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -266,6 +272,9 @@ async fn remote_models_apply_remote_base_instructions() -> Result<()> {
             model: Some(model.to_string()),
             effort: None,
             summary: None,
+            execution_mode: None,
+            sampling: None,
+            offline: None,
         })
         .await?;
 
@@ -281,6 +290,9 @@ async fn remote_models_apply_remote_base_instructions() -> Result<()> {
             model: model.to_string(),
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -419,7 +431,7 @@ struct RemoteModelsHarness {
     codex: Arc<CodexConversation>,
     cwd: Arc<TempDir>,
     config: Config,
-    conversation_manager: Arc<ConversationManager>,
+    conversation_manager: ConversationManager,
 }
 
 // todo(aibrahim): move this to with_model_provier in test_codex
@@ -446,7 +458,7 @@ where
 
     mutate_config(&mut config);
 
-    let conversation_manager = Arc::new(ConversationManager::with_models_provider(auth, provider));
+    let conversation_manager = ConversationManager::with_models_provider(auth, provider);
 
     let new_conversation = conversation_manager
         .new_conversation(config.clone())
@@ -0,0 +1,218 @@
+use std::fs;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::RemovedConversation;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SessionEndReason;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tokio::sync::oneshot;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn removing_an_idle_conversation_reports_its_references() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    test.submit_turn("hello").await?;
+
+    let removed = test
+        .conversation_manager
+        .remove_conversation(&test.session_configured.session_id)
+        .await
+        .expect("conversation is tracked");
+    let RemovedConversation {
+        was_turn_in_flight,
+        rollout_flushed,
+        remaining_refs,
+        ..
+    } = removed;
+    // `test.codex` still holds the conversation.
+    assert_eq!(
+        (was_turn_in_flight, rollout_flushed, remaining_refs),
+        (false, true, 1)
+    );
+    let rollout = fs::read_to_string(&test.session_configured.rollout_path)?;
+    assert!(rollout.contains("\"task_complete\""), "{rollout}");
+
+    // Nothing else holds a conversation whose handle was dropped.
+    let NewConversation {
+        conversation_id, ..
+    } = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?;
+    let removed = test
+        .conversation_manager
+        .remove_conversation(&conversation_id)
+        .await
+        .expect("conversation is tracked");
+    assert_eq!(removed.remaining_refs, 0);
+    assert!(!removed.was_turn_in_flight);
+    assert!(
+        test.conversation_manager
+            .remove_conversation(&conversation_id)
+            .await
+            .is_none()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn removing_a_streaming_conversation_reports_the_running_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The answer stops streaming until `rest_tx` fires.
+    let (rest_tx, rest_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![vec![
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![
+                ev_response_created("resp-1"),
+                ev_message_item_added("msg-1", ""),
+                ev_output_text_delta("Working on it. "),
+            ]),
+        },
+        StreamingSseChunk {
+            gate: Some(rest_rx),
+            body: sse(vec![
+                ev_assistant_message("msg-1", "Working on it. "),
+                ev_completed("resp-1"),
+            ]),
+        },
+    ]])
+    .await;
+    let test = test_codex().build_with_streaming_server(&server).await?;
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "explain it".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(
+            event,
+            EventMsg::AgentMessageDelta(_) | EventMsg::AgentMessageContentDelta(_)
+        )
+    })
+    .await;
+
+    let removed = test
+        .conversation_manager
+        .remove_conversation(&test.session_configured.session_id)
+        .await
+        .expect("conversation is tracked");
+    assert!(removed.was_turn_in_flight);
+    assert!(removed.rollout_flushed);
+    assert_eq!(removed.remaining_refs, 1);
+
+    // Removing the conversation does not stop its turn.
+    let _ = rest_tx.send(());
+    wait_for_event(&removed.conversation, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    server.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn removing_gracefully_ends_the_session_and_completes_the_rollout() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The answer never finishes streaming, so the turn is still running.
+    let (_rest_tx, rest_rx) = oneshot::channel::<()>();
+    let (server, _completions) = start_streaming_sse_server(vec![vec![
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![
+                ev_response_created("resp-1"),
+                ev_message_item_added("msg-1", ""),
+                ev_output_text_delta("Working on it. "),
+            ]),
+        },
+        StreamingSseChunk {
+            gate: Some(rest_rx),
+            body: sse(vec![ev_completed("resp-1")]),
+        },
+    ]])
+    .await;
+    let test = test_codex().build_with_streaming_server(&server).await?;
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "explain it".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(
+            event,
+            EventMsg::AgentMessageDelta(_) | EventMsg::AgentMessageContentDelta(_)
+        )
+    })
+    .await;
+
+    let removed = test
+        .conversation_manager
+        .remove_conversation_gracefully(
+            &test.session_configured.session_id,
+            Duration::from_secs(10),
+        )
+        .await
+        .expect("conversation is tracked");
+    assert!(removed.was_turn_in_flight);
+    assert!(removed.rollout_flushed);
+    let ended = removed.session_ended.expect("session ended in time");
+    assert_eq!(ended.reason, SessionEndReason::Shutdown);
+
+    // The end record is the last thing the rollout holds.
+    let items = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items();
+    let Some(RolloutItem::EventMsg(EventMsg::SessionEnded(recorded))) = items.last() else {
+        panic!("rollout does not end with SessionEnded: {:?}", items.last());
+    };
+    assert_eq!(recorded.reason, ended.reason);
+    assert!(
+        test.conversation_manager
+            .remove_conversation(&test.session_configured.session_id)
+            .await
+            .is_none()
+    );
+    server.shutdown().await;
+
+    Ok(())
+}
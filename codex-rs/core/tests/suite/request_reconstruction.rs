@@ -0,0 +1,133 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RequestFingerprint;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::TurnId;
+use codex_core::rollout::RequestMismatch;
+use codex_core::rollout::reconstruct_request;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::get_responses_requests;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+/// Run a plain turn, then a turn that calls a command before answering, and
+/// shut down so the rollout is complete.
+async fn two_turns(server: &MockServer) -> Result<TestCodex> {
+    mount_sse_sequence(
+        server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_assistant_message("msg-1", "first answer"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_shell_command_call("call-1", "echo reconstructed"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_assistant_message("msg-2", "second answer"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex().with_model("gpt-5.1").build(server).await?;
+    test.submit_turn("first question").await?;
+    test.submit_turn("run a command").await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(test)
+}
+
+async fn fingerprints(path: &Path) -> Result<Vec<RequestFingerprint>> {
+    Ok(RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .filter_map(|item| match item {
+            RolloutItem::RequestFingerprint(fingerprint) => Some(fingerprint),
+            _ => None,
+        })
+        .collect())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reconstructed_request_matches_the_bytes_sent() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = two_turns(&server).await?;
+    let path = test.codex.rollout_path();
+    let sent = get_responses_requests(&server).await;
+    assert_eq!(sent.len(), 3);
+
+    // Turn 2 asked twice; the request its answer came from is the last.
+    let second = reconstruct_request(&path, TurnId::new(2)).await?;
+    assert_eq!(second.turn_id, TurnId::new(2));
+    assert_eq!(
+        String::from_utf8(serde_json::to_vec(&second.body)?)?,
+        String::from_utf8(sent[2].body.clone())?
+    );
+    let first = reconstruct_request(&path, TurnId::new(1)).await?;
+    assert_eq!(serde_json::to_vec(&first.body)?, sent[0].body);
+
+    // The instructions and tools are kept once, by the first request.
+    let recorded = fingerprints(&path).await?;
+    assert_eq!(recorded.len(), 3);
+    assert_eq!(recorded[0].segments.len(), 2);
+    assert!(recorded[1..].iter().all(|fp| fp.segments.is_empty()));
+    assert_eq!(recorded[2], second.fingerprint);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn history_edited_after_the_fact_is_reported_with_a_diff() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = two_turns(&server).await?;
+    let path = test.codex.rollout_path();
+    let text = std::fs::read_to_string(&path)?;
+    std::fs::write(&path, text.replace("first question", "another question"))?;
+
+    let err = reconstruct_request(&path, TurnId::new(2))
+        .await
+        .expect_err("the edited message no longer matches its hash");
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let mismatch = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<RequestMismatch>())
+        .expect("the error carries the diff");
+    assert_eq!(mismatch.turn_id, TurnId::new(2));
+    assert_eq!(mismatch.diff.len(), 1, "{mismatch}");
+    assert!(mismatch.diff[0].contains("another question"), "{mismatch}");
+
+    let missing = reconstruct_request(&path, TurnId::new(3))
+        .await
+        .expect_err("there is no third turn");
+    assert_eq!(missing.kind(), ErrorKind::NotFound);
+
+    Ok(())
+}
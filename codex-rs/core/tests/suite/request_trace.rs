@@ -0,0 +1,148 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use codex_core::request_trace::RequestTracer;
+use codex_core::request_trace::TraceTarget;
+use codex_core::request_trace::TracedExchange;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn assistant_turn(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), "ok"),
+        ev_completed(id),
+    ])
+}
+
+#[derive(Default)]
+struct CollectingTracer {
+    exchanges: Mutex<Vec<TracedExchange>>,
+}
+
+impl RequestTracer for CollectingTracer {
+    #[allow(clippy::unwrap_used)]
+    fn on_exchange(&self, exchange: &TracedExchange) {
+        self.exchanges.lock().unwrap().push(exchange.clone());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn directory_trace_writes_one_sanitized_pair_per_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![assistant_turn("resp-1"), assistant_turn("resp-2")],
+    )
+    .await;
+    let trace_dir = TempDir::new()?;
+    let trace_path = trace_dir.path().to_path_buf();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.request_trace = Some(TraceTarget::Directory {
+                path: trace_path,
+                max_body_bytes: None,
+            });
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("first").await?;
+    test.submit_turn("second").await?;
+
+    // Trace files are written in the background.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let names = loop {
+        let mut names: Vec<String> = std::fs::read_dir(trace_dir.path())?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|name| name.ends_with(".json"))
+            .collect();
+        names.sort();
+        if names.len() >= 4 || Instant::now() >= deadline {
+            break names;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(names.len(), 4, "unexpected trace files: {names:?}");
+
+    let conversation_id = test.session_configured.session_id.to_string();
+    let requests: Vec<&String> = names
+        .iter()
+        .filter(|name| name.ends_with("-request.json"))
+        .collect();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert!(request.starts_with(&conversation_id));
+        let response = request.replace("-request.json", "-response.json");
+        assert!(names.contains(&response), "missing {response}");
+    }
+
+    for name in &names {
+        let contents = std::fs::read_to_string(trace_dir.path().join(name))?;
+        assert!(
+            !contents.to_ascii_lowercase().contains("authorization"),
+            "{name} contains an authorization header"
+        );
+        assert!(
+            !contents.contains("Bearer dummy"),
+            "{name} contains the api key"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn callback_trace_receives_each_exchange() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![assistant_turn("resp-1"), assistant_turn("resp-2")],
+    )
+    .await;
+    let tracer = Arc::new(CollectingTracer::default());
+    let target = TraceTarget::Callback(tracer.clone());
+    let test = test_codex()
+        .with_config(move |config| {
+            config.request_trace = Some(target);
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("first").await?;
+    test.submit_turn("second").await?;
+
+    let exchanges = tracer.exchanges.lock().unwrap().clone();
+    assert_eq!(exchanges.len(), 2);
+    assert!(exchanges[0].turn < exchanges[1].turn);
+    for exchange in &exchanges {
+        assert_eq!(exchange.response.status, Some(200));
+        assert!(exchange.response.body.contains("response.completed"));
+        assert!(
+            exchange
+                .request
+                .headers
+                .keys()
+                .all(|name| name != "authorization")
+        );
+    }
+
+    Ok(())
+}
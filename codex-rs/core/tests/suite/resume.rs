@@ -37,6 +37,7 @@ async fn resume_includes_initial_messages_from_rollout_events() -> Result<()> {
             items: vec![UserInput::Text {
                 text: "Record some messages".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
@@ -89,6 +90,7 @@ async fn resume_includes_initial_messages_from_reasoning_events() -> Result<()>
             items: vec![UserInput::Text {
                 text: "Record reasoning messages".into(),
             }],
+            allow_sensitive: false,
         })
         .await?;
 
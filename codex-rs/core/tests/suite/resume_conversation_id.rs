@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::RolloutLine;
+use codex_protocol::ConversationId;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+const CONVERSATION_ID: &str = "0195468e-7a1b-7c3d-9e4f-5a6b7c8d9e0f";
+
+/// Copy the recorded session into `dir`, so resuming it leaves the fixture
+/// untouched.
+fn fixture_rollout(dir: &TempDir) -> Result<PathBuf> {
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/read_only/session.jsonl");
+    let path = dir.path().join("rollout.jsonl");
+    std::fs::copy(fixture, &path)?;
+    Ok(path)
+}
+
+/// Ids of the session meta lines recorded in the rollout at `path`.
+fn recorded_ids(path: &Path) -> Result<Vec<ConversationId>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+        .filter_map(|line| match line.item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.id),
+            _ => None,
+        })
+        .collect())
+}
+
+fn auth_manager() -> std::sync::Arc<AuthManager> {
+    AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resuming_keeps_the_recorded_conversation_id() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let dir = TempDir::new()?;
+    let path = fixture_rollout(&dir)?;
+    let recorded = ConversationId::from_string(CONVERSATION_ID)?;
+
+    let resumed = test
+        .conversation_manager
+        .resume_conversation_from_rollout(test.config.clone(), path.clone(), auth_manager())
+        .await?;
+    assert_eq!(resumed.conversation_id, recorded);
+    assert_eq!(resumed.session_configured.session_id, recorded);
+    assert_eq!(resumed.session_configured.rollout_path, path);
+
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let lines_before = std::fs::read_to_string(&path)?.lines().count();
+    resumed
+        .conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "continue".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&resumed.conversation, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(std::fs::read_to_string(&path)?.lines().count() > lines_before);
+    assert_eq!(recorded_ids(&path)?, vec![recorded]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resuming_a_running_conversation_conflicts() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let dir = TempDir::new()?;
+    let path = fixture_rollout(&dir)?;
+    let recorded = ConversationId::from_string(CONVERSATION_ID)?;
+    let manager = &test.conversation_manager;
+
+    let (first, second) = tokio::join!(
+        manager.resume_conversation_from_rollout(test.config.clone(), path.clone(), auth_manager()),
+        manager.resume_conversation_from_rollout(test.config.clone(), path.clone(), auth_manager()),
+    );
+    let (resumed, conflict) = match (first, second) {
+        (Ok(resumed), Err(conflict)) | (Err(conflict), Ok(resumed)) => (resumed, conflict),
+        (Ok(_), Ok(_)) => panic!("both resumes succeeded"),
+        (Err(first), Err(second)) => panic!("both resumes failed: {first}; {second}"),
+    };
+    assert_eq!(resumed.conversation_id, recorded);
+    assert!(
+        matches!(conflict, CodexErr::ConversationAlreadyExists(id) if id == recorded),
+        "{conflict:?}"
+    );
+
+    // Once it is gone, the rollout can be resumed again.
+    resumed.conversation.submit(Op::Shutdown).await?;
+    wait_for_event(&resumed.conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    manager.remove_conversation(&recorded).await;
+    let again = manager
+        .resume_conversation_from_rollout(test.config.clone(), path, auth_manager())
+        .await?;
+    assert_eq!(again.conversation_id, recorded);
+
+    Ok(())
+}
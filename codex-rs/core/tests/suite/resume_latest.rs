@@ -0,0 +1,134 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::ConversationId;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn git(path: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .status()
+        .with_context(|| format!("failed to run git {args:?}"))?;
+    if status.success() {
+        return Ok(());
+    }
+    bail!("git {args:?} exited with {status}");
+}
+
+fn init_git_repo(path: &Path) -> Result<()> {
+    git(path, &["init", "--initial-branch=main"])?;
+    git(path, &["config", "user.name", "Codex Tests"])?;
+    git(path, &["config", "user.email", "codex-tests@example.com"])?;
+    fs::create_dir(path.join("src"))?;
+    fs::write(path.join("src/lib.rs"), "fn main() {}\n")?;
+    git(path, &["add", "src/lib.rs"])?;
+    git(path, &["commit", "-m", "initial"])?;
+    Ok(())
+}
+
+/// Run one turn of a new session in `cwd` and shut it down, so its rollout
+/// is complete.
+async fn record_session(test: &TestCodex, cwd: &Path, prompt: &str) -> Result<ConversationId> {
+    let mut config = test.config.clone();
+    config.cwd = cwd.to_path_buf();
+    let new = test.conversation_manager.new_conversation(config).await?;
+    let conversation = &new.conversation;
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: prompt.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(conversation, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+    Ok(new.conversation_id)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn latest_session_is_found_per_workspace_and_resumed() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        (1..=2)
+            .map(|turn| {
+                sse(vec![
+                    ev_response_created(&format!("resp-{turn}")),
+                    ev_assistant_message(&format!("msg-{turn}"), "done"),
+                    ev_completed(&format!("resp-{turn}")),
+                ])
+            })
+            .collect(),
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let manager = &test.conversation_manager;
+
+    let repo = TempDir::new()?;
+    init_git_repo(repo.path())?;
+    git(repo.path(), &["checkout", "-b", "feature"])?;
+    let repo_session = record_session(&test, repo.path(), "work on the feature").await?;
+    git(repo.path(), &["checkout", "main"])?;
+
+    let scratch = TempDir::new()?;
+    let scratch_session = record_session(&test, scratch.path(), "try something").await?;
+
+    // A session started on another branch still belongs to the repository,
+    // from any directory inside it.
+    let summary = manager
+        .latest_session_for_cwd(&repo.path().join("src"))
+        .await
+        .context("no session found for the repository")?;
+    assert_eq!(summary.conversation_id, repo_session);
+    assert_eq!(summary.git_branch.as_deref(), Some("feature"));
+
+    let summary = manager
+        .latest_session_for_cwd(scratch.path())
+        .await
+        .context("no session found for the scratch directory")?;
+    assert_eq!(summary.conversation_id, scratch_session);
+    assert_eq!(summary.git_branch, None);
+
+    let unrelated = TempDir::new()?;
+    assert_eq!(manager.latest_session_for_cwd(unrelated.path()).await, None);
+    let Err(CodexErr::NoSessionForCwd(path)) = manager
+        .resume_latest_for_cwd(test.config.clone(), unrelated.path())
+        .await
+    else {
+        panic!("expected NoSessionForCwd");
+    };
+    assert_eq!(path, unrelated.path());
+
+    let mut config = test.config.clone();
+    config.cwd = repo.path().to_path_buf();
+    let resumed = manager.resume_latest_for_cwd(config, repo.path()).await?;
+    assert_eq!(resumed.conversation_id, repo_session);
+
+    Ok(())
+}
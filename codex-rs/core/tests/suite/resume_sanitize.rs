@@ -0,0 +1,165 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_protocol::ConversationId;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn line(item_type: &str, payload: Value) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": item_type,
+        "payload": payload,
+    })
+    .to_string()
+}
+
+fn turn_context(cwd: &Path, model: &str) -> String {
+    line(
+        "turn_context",
+        json!({
+            "cwd": cwd,
+            "approval_policy": "never",
+            "sandbox_policy": { "type": "danger-full-access" },
+            "model": model,
+            "summary": "auto",
+        }),
+    )
+}
+
+fn message(role: &str, text: &str) -> Value {
+    let content_type = if role == "user" {
+        "input_text"
+    } else {
+        "output_text"
+    };
+    json!({
+        "type": "message",
+        "role": role,
+        "content": [{ "type": content_type, "text": text }],
+    })
+}
+
+fn reasoning(encrypted_content: &str) -> Value {
+    json!({
+        "type": "reasoning",
+        "summary": [{ "type": "summary_text", "text": "thinking" }],
+        "encrypted_content": encrypted_content,
+    })
+}
+
+/// A rollout with two turns of a retired `o3` model, compacted in between,
+/// then a turn of the model the session resumes with.
+fn write_rollout(dir: &Path) -> Result<PathBuf> {
+    let lines = [
+        line(
+            "session_meta",
+            json!({
+                "id": ConversationId::new().to_string(),
+                "timestamp": "2030-01-01T00:00:00.000Z",
+                "cwd": dir,
+                "originator": "codex_exec",
+                "cli_version": "0.0.0",
+                "instructions": null,
+                "source": "exec",
+                "model_provider": "openai",
+            }),
+        ),
+        turn_context(dir, "o3"),
+        line("response_item", message("user", "first question")),
+        line("response_item", reasoning("o3-reasoning-1")),
+        line("response_item", message("assistant", "first answer")),
+        turn_context(dir, "o3"),
+        line("response_item", message("user", "second question")),
+        line("response_item", reasoning("o3-reasoning-2")),
+        line("response_item", message("assistant", "second answer")),
+        line(
+            "compacted",
+            json!({
+                "message": "",
+                "replacement_history": [
+                    message("user", "second question"),
+                    { "type": "compaction", "encrypted_content": "o3-summary" },
+                ],
+            }),
+        ),
+        turn_context(dir, "gpt-5.1-codex"),
+        line("response_item", message("user", "third question")),
+        line("response_item", reasoning("codex-reasoning")),
+        line("response_item", message("assistant", "third answer")),
+    ];
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_drops_encrypted_items_of_another_model_family() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(fixtures.path())?;
+    let original = std::fs::read_to_string(&path)?;
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "fourth answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex()
+        .with_model("gpt-5.1-codex")
+        .resume(&server, home, path.clone())
+        .await?;
+
+    let warnings = &test
+        .session_configured
+        .resumed
+        .as_ref()
+        .expect("resumed summary")
+        .warnings;
+    assert_eq!(
+        warnings,
+        &vec![
+            "Removed 2 reasoning items recorded with `o3`, whose encrypted_content `gpt-5.1-codex` cannot read."
+                .to_string(),
+            "Removed 1 compaction summary recorded with `o3`, whose encrypted_content `gpt-5.1-codex` cannot read."
+                .to_string(),
+        ]
+    );
+
+    test.submit_turn("fourth question").await?;
+
+    let input = mock.single_request().input();
+    let encrypted: Vec<&str> = input
+        .iter()
+        .filter_map(|item| item["encrypted_content"].as_str())
+        .collect();
+    assert_eq!(encrypted, vec!["codex-reasoning"]);
+    assert!(
+        input.iter().all(|item| item["type"] != "compaction"),
+        "{input:?}"
+    );
+
+    // The resumed rollout still holds everything it was written with.
+    let rollout = std::fs::read_to_string(&path)?;
+    assert!(rollout.starts_with(&original));
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_protocol::ConversationId;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+const TURNS: usize = 50;
+/// Far less than the 50 turns take, far more than one.
+const CONTEXT_WINDOW: i64 = 20_000;
+
+fn line(item_type: &str, payload: Value) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": item_type,
+        "payload": payload,
+    })
+    .to_string()
+}
+
+fn message(role: &str, text: &str) -> Value {
+    let content_type = if role == "user" {
+        "input_text"
+    } else {
+        "output_text"
+    };
+    json!({
+        "type": "message",
+        "role": role,
+        "content": [{ "type": content_type, "text": text }],
+    })
+}
+
+fn question(turn: usize) -> String {
+    format!("question {turn:02}.")
+}
+
+fn session_meta(dir: &Path) -> String {
+    line(
+        "session_meta",
+        json!({
+            "id": ConversationId::new().to_string(),
+            "timestamp": "2030-01-01T00:00:00.000Z",
+            "cwd": dir,
+            "originator": "codex_exec",
+            "cli_version": "0.0.0",
+            "instructions": null,
+            "source": "exec",
+            "model_provider": "openai",
+        }),
+    )
+}
+
+/// Turn `turn`, answered at a length of about 500 tokens.
+fn turn(turn: usize) -> Vec<String> {
+    let answer = format!("answer {turn:02}: {}", "lorem ipsum ".repeat(170));
+    vec![
+        line(
+            "event_msg",
+            json!({ "type": "user_message", "message": question(turn) }),
+        ),
+        line("response_item", message("user", &question(turn))),
+        line("response_item", message("assistant", &answer)),
+    ]
+}
+
+fn write_lines(dir: &Path, lines: &[String]) -> Result<PathBuf> {
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// A rollout of [`TURNS`] turns.
+fn write_rollout(dir: &Path) -> Result<PathBuf> {
+    let mut lines = vec![session_meta(dir)];
+    lines.extend((1..=TURNS).flat_map(turn));
+    write_lines(dir, &lines)
+}
+
+/// Questions of the user messages `request` sent to the model.
+fn questions(request: &ResponsesRequest) -> Vec<String> {
+    request
+        .input()
+        .iter()
+        .filter(|item| item["role"] == "user")
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .filter(|text| text.starts_with("question "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_onto_a_small_window_reports_the_turns_it_kept() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(fixtures.path())?;
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "last answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex()
+        .with_config(|config| config.model_context_window = Some(CONTEXT_WINDOW))
+        .resume(&server, home, path)
+        .await?;
+
+    let summary = test
+        .session_configured
+        .resumed
+        .clone()
+        .expect("resumed summary");
+    let report = summary.trim.expect("trim report");
+    assert_eq!(report.turns_restored + report.turns_dropped, TURNS);
+    assert!(report.turns_dropped > 0, "{report:?}");
+    assert!(report.turns_restored > 1, "{report:?}");
+    assert!(!report.compacted);
+    let window_tokens = report.window_tokens.expect("context window");
+    assert!(
+        report.estimated_prompt_tokens <= window_tokens,
+        "{report:?}"
+    );
+    // The client is still shown every turn.
+    assert_eq!(summary.turns, TURNS);
+
+    let event = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::HistoryTrimmedOnResume(_))
+    })
+    .await;
+    assert!(matches!(event, EventMsg::HistoryTrimmedOnResume(trimmed) if trimmed == report));
+
+    test.submit_turn("one more question").await?;
+
+    // The request holds the restored turns, the newest ones, and no others.
+    let expected: Vec<String> = (report.turns_dropped + 1..=TURNS).map(question).collect();
+    assert_eq!(questions(&mock.single_request()), expected);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_keeps_turns_rolled_back_before_the_cut_out() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // Turns 3 to 50 are rolled back to a checkpoint taken before them, which
+    // the oldest turns the resume drops include. Turns 51 to 100 follow.
+    let fixtures = TempDir::new()?;
+    let checkpoint = json!("checkpoint-before-turn-3");
+    let mut lines = vec![session_meta(fixtures.path())];
+    lines.extend((1..=2).flat_map(turn));
+    lines.push(line(
+        "checkpoint",
+        json!({ "id": checkpoint, "label": "before turn 3" }),
+    ));
+    lines.extend((3..=TURNS).flat_map(turn));
+    lines.push(line("checkpoint_rollback", json!({ "id": checkpoint })));
+    lines.extend((TURNS + 1..=2 * TURNS).flat_map(turn));
+    let path = write_lines(fixtures.path(), &lines)?;
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "last answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex()
+        .with_config(|config| config.model_context_window = Some(CONTEXT_WINDOW))
+        .resume(&server, home, path)
+        .await?;
+
+    let report = test
+        .session_configured
+        .resumed
+        .clone()
+        .and_then(|summary| summary.trim)
+        .expect("trim report");
+    // Only the turns left after the rollback count.
+    let remaining: Vec<usize> = (1..=2).chain(TURNS + 1..=2 * TURNS).collect();
+    assert_eq!(
+        report.turns_restored + report.turns_dropped,
+        remaining.len()
+    );
+    assert!(report.turns_dropped > 2, "{report:?}");
+
+    test.submit_turn("one more question").await?;
+
+    let expected: Vec<String> = remaining[report.turns_dropped..]
+        .iter()
+        .copied()
+        .map(question)
+        .collect();
+    assert_eq!(questions(&mock.single_request()), expected);
+
+    Ok(())
+}
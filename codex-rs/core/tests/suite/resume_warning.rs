@@ -33,12 +33,15 @@ fn resume_history(
         developer_instructions: None,
         final_output_json_schema: None,
         truncation_policy: None,
+        sampling: None,
+        turn_tmpdir: None,
     };
 
     InitialHistory::Resumed(ResumedHistory {
         conversation_id: ConversationId::default(),
         history: vec![RolloutItem::TurnContext(turn_ctx)],
         rollout_path: rollout_path.to_path_buf(),
+        parse_errors: Vec::new(),
     })
 }
 
@@ -70,7 +73,7 @@ async fn emits_warning_when_resumed_model_differs() {
 
     // Assert: a Warning event is emitted describing the model mismatch.
     let warning = wait_for_event(&conversation, |ev| matches!(ev, EventMsg::Warning(_))).await;
-    let EventMsg::Warning(WarningEvent { message }) = warning else {
+    let EventMsg::Warning(WarningEvent { message, .. }) = warning else {
         panic!("expected warning event");
     };
     assert!(message.contains("previous-model"));
@@ -0,0 +1,118 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::protocol::HistoryOrigin;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use uuid::Uuid;
+
+fn message(role: &str, text: &str) -> Value {
+    let content_type = if role == "user" {
+        "input_text"
+    } else {
+        "output_text"
+    };
+    json!({
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": role,
+            "content": [{ "type": content_type, "text": text }],
+        },
+    })
+}
+
+/// A rollout with two turns, the first compacted, a redacted input and an
+/// item written by a newer version.
+fn write_fixture(path: &Path) -> Result<()> {
+    let lines = vec![
+        json!({
+            "type": "session_meta",
+            "payload": {
+                "id": Uuid::new_v4(),
+                "timestamp": "2025-01-01T00:00:00Z",
+                "cwd": ".",
+                "originator": "test_originator",
+                "cli_version": "test_version",
+                "model_provider": "openai",
+            },
+        }),
+        message("user", "first question"),
+        message("assistant", "first answer"),
+        json!({ "type": "compacted", "payload": { "message": "summary of the first turn" } }),
+        message(
+            "user",
+            "second question\n  with the key [REDACTED:private_key]",
+        ),
+        message("assistant", "second answer"),
+        json!({ "type": "hologram", "payload": { "frames": 3 } }),
+    ];
+    let mut file = std::fs::File::create(path)?;
+    for (index, mut line) in lines.into_iter().enumerate() {
+        line["timestamp"] = json!(format!("2025-01-01T00:00:{index:02}.000Z"));
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_configured_summarizes_resumed_and_forked_histories() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex();
+    let initial = builder.build(&server).await?;
+    assert_eq!(initial.session_configured.resumed, None);
+
+    let fixture = initial.home.path().join("fixture-rollout.jsonl");
+    write_fixture(&fixture)?;
+
+    let resumed = builder
+        .resume(&server, initial.home.clone(), fixture.clone())
+        .await?;
+    let summary = resumed
+        .session_configured
+        .resumed
+        .clone()
+        .expect("resumed session has a summary");
+    assert_eq!(summary.origin, HistoryOrigin::Resumed);
+    assert_eq!(summary.turns, 2);
+    assert_eq!(
+        summary.last_user_message.as_deref(),
+        Some("second question with the key [REDACTED:private_key]")
+    );
+    assert!(summary.last_activity.is_some());
+    assert!(summary.truncated);
+    assert_eq!(
+        summary.warnings,
+        vec![
+            "1 item written by a newer version of Codex is not shown to the model.".to_string(),
+            "1 user message contains redacted text.".to_string(),
+        ]
+    );
+
+    let NewConversation {
+        session_configured: forked,
+        ..
+    } = resumed
+        .conversation_manager
+        .fork_conversation(1, resumed.config.clone(), fixture)
+        .await?;
+    let summary = forked.resumed.expect("forked session has a summary");
+    assert_eq!(summary.origin, HistoryOrigin::Forked);
+    assert_eq!(summary.turns, 1);
+    assert_eq!(summary.last_user_message.as_deref(), Some("first question"));
+    assert_eq!(summary.last_activity, None);
+    assert!(summary.truncated);
+    assert_eq!(summary.warnings, Vec::<String>::new());
+
+    Ok(())
+}
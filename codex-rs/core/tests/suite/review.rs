@@ -665,6 +665,7 @@ async fn review_history_surfaces_in_parent_session() {
             items: vec![UserInput::Text {
                 text: followup.clone(),
             }],
+            allow_sensitive: false,
         })
         .await
         .unwrap();
@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use wiremock::MockServer;
+
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// Room for the timestamp and chunk fields around each part's payload.
+const ENVELOPE_BYTES: usize = 256;
+
+/// About 5 MB of file contents, with characters that need escaping in JSON.
+fn huge_answer() -> String {
+    "fn main() { println!(\"hello, wörld\\n\"); }\n".repeat(120_000)
+}
+
+/// Mount the reply `answer` for the next turn.
+async fn mount_answer(server: &MockServer, answer: &str) {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created("resp"),
+            ev_assistant_message("msg", answer),
+            ev_completed("resp"),
+        ]),
+    )
+    .await;
+}
+
+async fn rollout_items(path: &Path) -> Result<Vec<RolloutItem>> {
+    Ok(RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items())
+}
+
+fn message_texts(items: &[RolloutItem], role: &str) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::ResponseItem(ResponseItem::Message {
+                role: item_role,
+                content,
+                ..
+            }) if item_role == role => Some(
+                content
+                    .iter()
+                    .filter_map(|content| match content {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            Some(text.as_str())
+                        }
+                        ContentItem::InputImage { .. } => None,
+                    })
+                    .collect::<String>(),
+            ),
+            _ => None,
+        })
+        .filter(|text| !text.starts_with('<'))
+        .collect()
+}
+
+fn line_type(line: &str) -> Result<Option<String>> {
+    let value: Value = serde_json::from_str(line)?;
+    Ok(value["type"].as_str().map(str::to_string))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn huge_messages_are_chunked_and_read_back_whole() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.rollout_max_line_bytes = Some(MAX_LINE_BYTES))
+        .build(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+    let huge = huge_answer();
+    mount_answer(&server, &huge).await;
+    test.submit_input("dump the file").await?;
+    mount_answer(&server, "second answer").await;
+    test.submit_input("second question").await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    // No line comes close to the size of the message.
+    let text = std::fs::read_to_string(&rollout_path)?;
+    let mut chunked_lines = 0;
+    for line in text.lines() {
+        assert!(
+            line.len() <= MAX_LINE_BYTES + ENVELOPE_BYTES,
+            "{}",
+            line.len()
+        );
+        if line_type(line)?.as_deref() == Some("chunked") {
+            chunked_lines += 1;
+        }
+    }
+    assert!(chunked_lines > huge.len() / MAX_LINE_BYTES);
+
+    // Readers see the message as it was produced.
+    let items = rollout_items(&rollout_path).await?;
+    assert!(
+        !items
+            .iter()
+            .any(|item| matches!(item, RolloutItem::Chunked { .. }))
+    );
+    assert_eq!(
+        message_texts(&items, "assistant"),
+        vec![huge.clone(), "second answer".to_string()]
+    );
+
+    // Forking before the second question keeps the huge message intact.
+    let NewConversation {
+        session_configured: forked,
+        ..
+    } = test
+        .conversation_manager
+        .fork_conversation(1, test.config.clone(), rollout_path)
+        .await?;
+    let forked_items = rollout_items(&forked.rollout_path).await?;
+    assert_eq!(message_texts(&forked_items, "user"), vec!["dump the file"]);
+    assert_eq!(message_texts(&forked_items, "assistant"), vec![huge]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn incomplete_trailing_chunk_sequence_is_dropped() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.rollout_max_line_bytes = Some(MAX_LINE_BYTES))
+        .build(&server)
+        .await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+    mount_answer(&server, &huge_answer()).await;
+    test.submit_input("dump the file").await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    // Simulate a crash while writing the first chunked item: keep everything
+    // before it, two whole parts, and half of the third.
+    let text = std::fs::read_to_string(&rollout_path)?;
+    let lines: Vec<&str> = text.lines().collect();
+    let first_chunk = lines
+        .iter()
+        .position(|line| line_type(line).ok().flatten().as_deref() == Some("chunked"))
+        .expect("rollout has chunked lines");
+    let before = lines[..first_chunk].join("\n") + "\n";
+    let third = lines[first_chunk + 2];
+    let cut = (0..=third.len() / 2)
+        .rev()
+        .find(|&index| third.is_char_boundary(index))
+        .unwrap_or_default();
+    let crashed = format!(
+        "{before}{}\n{}\n{}",
+        lines[first_chunk],
+        lines[first_chunk + 1],
+        &third[..cut]
+    );
+
+    let before_path = test.home.path().join("before.jsonl");
+    let crashed_path = test.home.path().join("crashed.jsonl");
+    std::fs::write(&before_path, before)?;
+    std::fs::write(&crashed_path, crashed)?;
+
+    let expected = rollout_items(&before_path).await?;
+    let recovered = rollout_items(&crashed_path).await?;
+    assert_eq!(
+        serde_json::to_value(&recovered)?,
+        serde_json::to_value(&expected)?
+    );
+    assert_eq!(message_texts(&recovered, "user"), vec!["dump the file"]);
+    assert!(message_texts(&recovered, "assistant").is_empty());
+
+    Ok(())
+}
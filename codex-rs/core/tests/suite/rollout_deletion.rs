@@ -0,0 +1,169 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::DeletionReport;
+use codex_core::blob_store::BlobStore;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::rollout::EventLogPolicy;
+use codex_core::rollout::event_log_path;
+use codex_core::rollout_parts;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+const CAP: usize = 1024;
+
+fn size(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+async fn plain_turn(test: &TestCodex, server: &wiremock::MockServer) -> Result<()> {
+    mount_sse_sequence(
+        server,
+        vec![sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "hello"),
+            ev_completed("resp-1"),
+        ])],
+    )
+    .await;
+    test.submit_turn("say hello").await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn deletion_removes_the_rollout_its_event_log_and_its_blobs() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let command = format!("head -c {} /dev/zero | tr '\\0' x", 4 * CAP);
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("big-call", &command),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.max_tool_output_bytes = Some(CAP);
+            config.event_log = Some(EventLogPolicy::default());
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("print a lot").await?;
+    let end = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ExecCommandEnd(end) => Some(end.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    let blob = BlobStore::new(test.codex_home_path()).path(&end.output_blob.expect("stored"));
+    let rollout = test.codex.rollout_path();
+    let mut files: Vec<PathBuf> = rollout_parts(&rollout).await?;
+    files.push(event_log_path(&rollout));
+    files.push(blob);
+    for file in &files {
+        assert!(file.exists(), "{}", file.display());
+    }
+    let size_before: u64 = files.iter().map(|file| size(file)).sum();
+
+    let conversation_id = test.session_configured.session_id;
+    let DeletionReport {
+        bytes_freed,
+        files_removed,
+    } = test
+        .conversation_manager
+        .remove_conversation_and_delete(conversation_id, false)
+        .await?;
+
+    assert_eq!(files_removed, files.len());
+    // Shutting down appends to the rollout and the event log first.
+    assert!(bytes_freed >= size_before, "{bytes_freed} < {size_before}");
+    for file in &files {
+        assert!(!file.exists(), "{}", file.display());
+    }
+    assert!(matches!(
+        test.conversation_manager
+            .get_conversation(conversation_id)
+            .await,
+        Err(CodexErr::ConversationNotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fork_bases_are_deleted_only_when_forced() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    plain_turn(&test, &server).await?;
+    let base_id = test.session_configured.session_id;
+    let fork = test
+        .conversation_manager
+        .fork_conversation_by_id(base_id, 0, test.config.clone(), false)
+        .await?;
+    let base_rollout = test.codex.rollout_path();
+
+    let refused = test
+        .conversation_manager
+        .remove_conversation_and_delete(base_id, false)
+        .await;
+    match refused {
+        Err(CodexErr::ForkBaseInUse {
+            conversation_id,
+            forks,
+        }) => {
+            assert_eq!(conversation_id, base_id);
+            assert_eq!(forks, vec![fork.conversation_id]);
+        }
+        other => panic!("expected the deletion to be refused, got {other:?}"),
+    }
+    // Nothing was shut down or deleted.
+    assert!(base_rollout.exists());
+    test.conversation_manager.get_conversation(base_id).await?;
+
+    let report = test
+        .conversation_manager
+        .remove_conversation_and_delete(base_id, true)
+        .await?;
+    assert!(report.files_removed >= 1, "{report:?}");
+    assert!(!base_rollout.exists());
+    // The fork keeps running on its own rollout.
+    assert!(fork.conversation.rollout_path().exists());
+    test.conversation_manager
+        .get_conversation(fork.conversation_id)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::NewConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::rollout::import::ImportFormat;
+use codex_core::rollout::import::from_jsonl;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/import")
+        .join(name)
+}
+
+/// Import `fixture_name`, resume it, and check the next request carries the
+/// imported transcript: both fixtures hold the same lookup conversation plus
+/// one entry that has no codex equivalent.
+async fn import_and_resume(fixture_name: &str, format: ImportFormat, call_id: &str) -> Result<()> {
+    let server = start_mock_server().await;
+    let response = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "still a cat"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+
+    let report = from_jsonl(&test.config, &fixture(fixture_name), format).await?;
+    assert_eq!(report.annotations, 1);
+    assert_eq!(report.items, 6);
+    assert!(report.rollout_path.exists());
+
+    let NewConversation {
+        conversation,
+        session_configured,
+        ..
+    } = test
+        .conversation_manager
+        .resume_conversation_from_rollout(
+            test.config.clone(),
+            report.rollout_path.clone(),
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy")),
+        )
+        .await?;
+    assert_eq!(session_configured.session_id, report.conversation_id);
+    let replayed: Vec<String> = session_configured
+        .initial_messages
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|event| match event {
+            EventMsg::UserMessage(event) => Some(event.message),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(replayed, vec!["what animal says meow?".to_string()]);
+
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "are you sure?".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&conversation, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let request = response.single_request();
+    let user_texts = request.message_input_texts("user");
+    assert!(user_texts.contains(&"what animal says meow?".to_string()));
+    assert!(
+        user_texts
+            .iter()
+            .any(|text| text.starts_with("<imported_entry>")),
+        "unmapped entry should be kept as an annotation: {user_texts:?}"
+    );
+    assert_eq!(user_texts.last().map(String::as_str), Some("are you sure?"));
+    assert!(request.has_function_call(call_id));
+    assert_eq!(
+        request.function_call_output_text(call_id),
+        Some("cat".to_string())
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_messages_import_resumes() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    import_and_resume("chat_messages.jsonl", ImportFormat::ChatMessages, "call-1").await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn claude_code_import_resumes() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    import_and_resume("claude_code.jsonl", ImportFormat::ClaudeCode, "toolu_01").await
+}
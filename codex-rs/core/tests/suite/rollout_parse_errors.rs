@@ -0,0 +1,200 @@
+#![allow(clippy::expect_used)]
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::InitialHistory;
+use codex_core::protocol::RolloutParseError;
+use codex_core::rollout::RolloutParseMode;
+use codex_protocol::ConversationId;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn meta_line(id: ConversationId, cwd: &Path) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:00.000Z",
+        "type": "session_meta",
+        "payload": {
+            "id": id.to_string(),
+            "timestamp": "2030-01-01T00:00:00.000Z",
+            "cwd": cwd,
+            "originator": "codex_exec",
+            "cli_version": "0.0.0",
+            "instructions": null,
+            "source": "exec",
+            "model_provider": "openai",
+        },
+    })
+    .to_string()
+}
+
+fn message_line(role: &str, content_type: &str, text: &str) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": role,
+            "content": [{ "type": content_type, "text": text }],
+        },
+    })
+    .to_string()
+}
+
+fn chunk_line(part: u32, of: u32, payload: &str) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:02.000Z",
+        "type": "chunked",
+        "payload": { "id": "run-1", "part": part, "of": of, "payload": payload },
+    })
+    .to_string()
+}
+
+fn write_rollout(dir: &Path, lines: &[String]) -> Result<PathBuf> {
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+fn reported_lines(history: &InitialHistory) -> Vec<usize> {
+    history
+        .parse_errors()
+        .iter()
+        .map(|error| error.line)
+        .collect()
+}
+
+async fn strict_error(path: &Path) -> RolloutParseError {
+    let err = RolloutRecorder::get_rollout_history_with_mode(path, RolloutParseMode::Strict)
+        .await
+        .expect_err("strict mode rejects the rollout");
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<RolloutParseError>())
+        .expect("the error carries the offending line")
+        .clone()
+}
+
+#[tokio::test]
+async fn malformed_line_in_the_middle_is_reported_by_number() -> Result<()> {
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(
+        fixtures.path(),
+        &[
+            meta_line(ConversationId::new(), fixtures.path()),
+            message_line("user", "input_text", "first question"),
+            "{\"timestamp\": \"2030-01-01T00:00:01.000Z\", edited by hand".to_string(),
+            String::new(),
+            message_line("assistant", "output_text", "first answer"),
+        ],
+    )?;
+
+    let history = RolloutRecorder::get_rollout_history(&path).await?;
+    assert_eq!(reported_lines(&history), vec![3]);
+    assert_eq!(history.get_rollout_items().len(), 3);
+    let error = &history.parse_errors()[0];
+    assert_eq!(
+        error.snippet,
+        "{\"timestamp\": \"2030-01-01T00:00:01.000Z\", edited by hand"
+    );
+    assert!(!error.error.is_empty());
+
+    assert_eq!(strict_error(&path).await, error.clone());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_last_line_is_reported_with_a_bounded_snippet() -> Result<()> {
+    let fixtures = TempDir::new()?;
+    let truncated = format!(
+        "{{\"timestamp\": \"2030-01-01T00:00:02.000Z\", \"type\": \"response_item\", \"payload\": \"{}",
+        "x".repeat(500)
+    );
+    let path = write_rollout(
+        fixtures.path(),
+        &[
+            meta_line(ConversationId::new(), fixtures.path()),
+            message_line("user", "input_text", "a question"),
+            truncated.clone(),
+        ],
+    )?;
+
+    let history = RolloutRecorder::get_rollout_history(&path).await?;
+    assert_eq!(reported_lines(&history), vec![3]);
+    assert_eq!(history.get_rollout_items().len(), 2);
+    let expected_snippet: String = truncated.chars().take(200).collect();
+    assert_eq!(history.parse_errors()[0].snippet, expected_snippet);
+
+    assert_eq!(strict_error(&path).await.line, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_chunked_item_is_reported_at_its_first_part() -> Result<()> {
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(
+        fixtures.path(),
+        &[
+            meta_line(ConversationId::new(), fixtures.path()),
+            message_line("user", "input_text", "a question"),
+            chunk_line(0, 2, "{\"timestamp\": \"2030-01-01T00:00:02.000Z\", "),
+            chunk_line(1, 2, "\"type\": broken}"),
+            message_line("assistant", "output_text", "an answer"),
+        ],
+    )?;
+
+    let history = RolloutRecorder::get_rollout_history(&path).await?;
+    assert_eq!(reported_lines(&history), vec![3]);
+    assert_eq!(history.get_rollout_items().len(), 3);
+
+    assert_eq!(strict_error(&path).await.line, 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn skipped_lines_are_listed_in_the_resume_warnings() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(
+        fixtures.path(),
+        &[
+            meta_line(ConversationId::new(), fixtures.path()),
+            message_line("user", "input_text", "earlier turn"),
+            "not json".to_string(),
+            message_line("assistant", "output_text", "earlier answer"),
+            "{\"timestamp\"".to_string(),
+        ],
+    )?;
+
+    let server = start_mock_server().await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex().resume(&server, home, path).await?;
+    let warnings = &test
+        .session_configured
+        .resumed
+        .as_ref()
+        .expect("resumed summary")
+        .warnings;
+    let skipped: Vec<&str> = warnings
+        .iter()
+        .filter_map(|warning| warning.strip_prefix("Skipped unreadable rollout line "))
+        .collect();
+    assert_eq!(skipped.len(), 2, "{warnings:?}");
+    assert!(skipped[0].starts_with("3: "), "{warnings:?}");
+    assert!(skipped[1].starts_with("5: "), "{warnings:?}");
+
+    Ok(())
+}
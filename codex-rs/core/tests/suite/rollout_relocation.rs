@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::child_conversations::ChildConversationOverrides;
+use codex_core::child_conversations::ConversationLineage;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::rollout::EventLogPolicy;
+use codex_core::rollout::RelocateReport;
+use codex_core::rollout::event_log_path;
+use codex_core::rollout::find_conversation_path_by_id_str;
+use codex_core::rollout::read_event_log;
+use codex_core::rollout::relocate;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+async fn shutdown(conversation: &CodexConversation) -> Result<()> {
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(conversation, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+    Ok(())
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sessions_resume_from_a_moved_codex_home() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-child"),
+            ev_assistant_message("msg-child", "done"),
+            ev_completed("resp-child"),
+        ]),
+    )
+    .await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.event_log = Some(EventLogPolicy::default());
+        })
+        .build(&server)
+        .await?;
+    let parent_id = test.codex.conversation_id();
+    let child_id = test
+        .codex
+        .spawn_child(ChildConversationOverrides::default(), "research X")
+        .await?;
+    let child = test.conversation_manager.get_conversation(child_id).await?;
+    wait_for_event(&child, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    shutdown(&child).await?;
+    shutdown(&test.codex).await?;
+
+    let new_home = TempDir::new()?;
+    copy_tree(&test.config.codex_home, new_home.path())?;
+    // Event logs already store relative locations, so there is nothing to fix.
+    assert_eq!(relocate(new_home.path()).await?, RelocateReport::default());
+
+    let parent_path = find_conversation_path_by_id_str(new_home.path(), &parent_id.to_string())
+        .await?
+        .expect("parent rollout should be found in the new home");
+    assert!(parent_path.starts_with(new_home.path()));
+    let events = read_event_log(new_home.path(), &event_log_path(&parent_path)).await?;
+    let Some(EventMsg::SessionConfigured(configured)) = events.first().map(|line| &line.event.msg)
+    else {
+        panic!("event log should start with SessionConfigured");
+    };
+    assert_eq!(configured.rollout_path, parent_path);
+
+    let mut config = test.config.clone();
+    config.codex_home = new_home.path().to_path_buf();
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("dummy"),
+        config.model_provider.clone(),
+        config.codex_home.clone(),
+    );
+    let resumed = manager
+        .resume_conversation_from_rollout(
+            config,
+            parent_path,
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy")),
+        )
+        .await?;
+    assert_eq!(resumed.conversation_id, parent_id);
+    assert_eq!(
+        manager.lineage(parent_id).await,
+        ConversationLineage {
+            parent: None,
+            children: vec![child_id],
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+#![allow(clippy::expect_used)]
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::find_conversation_path_by_id_str;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use wiremock::MockServer;
+
+const MAX_ROLLOUT_BYTES: u64 = 16 * 1024;
+
+/// An answer recorded twice per turn, as a response item and as an event, so
+/// every turn outgrows a part.
+fn long_answer(index: usize) -> String {
+    format!("answer {index}: {}", "lorem ipsum ".repeat(1_000))
+}
+
+/// Mount the reply `answer` for the next turn.
+async fn mount_answer(server: &MockServer, answer: &str) {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created("resp"),
+            ev_assistant_message("msg", answer),
+            ev_completed("resp"),
+        ]),
+    )
+    .await;
+}
+
+async fn shutdown(test: &TestCodex) -> Result<()> {
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(())
+}
+
+async fn user_messages(path: &Path) -> Result<Vec<String>> {
+    let items = RolloutRecorder::get_rollout_history(path)
+        .await?
+        .get_rollout_items();
+    assert!(
+        !items.iter().any(|item| matches!(
+            item,
+            RolloutItem::ContinuedIn(_) | RolloutItem::ContinuedFrom(_)
+        )),
+        "links between parts are not part of the history"
+    );
+    Ok(items
+        .into_iter()
+        .filter_map(|item| match item {
+            RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. })
+                if role == "user" =>
+            {
+                match content.as_slice() {
+                    [ContentItem::InputText { text }] if !text.starts_with('<') => {
+                        Some(text.clone())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+/// The files of the rollout whose first part is `head`, in order.
+fn parts(head: &Path) -> Result<Vec<PathBuf>> {
+    let mut parts = vec![head.to_path_buf()];
+    loop {
+        let text = std::fs::read_to_string(parts.last().expect("a part"))?;
+        let last: Value = serde_json::from_str(text.lines().last().unwrap_or("{}"))?;
+        if last["type"] != "continued_in" {
+            return Ok(parts);
+        }
+        let file = last["payload"]["file"].as_str().expect("file name");
+        parts.push(head.with_file_name(file));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rotated_rollouts_read_and_resume_as_one() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder =
+        test_codex().with_config(|config| config.max_rollout_bytes = Some(MAX_ROLLOUT_BYTES));
+    let test = builder.build(&server).await?;
+    let head = test.session_configured.rollout_path.clone();
+    let questions = ["first question", "second question", "third question"];
+    for (index, question) in questions.iter().enumerate() {
+        mount_answer(&server, &long_answer(index)).await;
+        test.submit_input(question).await?;
+    }
+    shutdown(&test).await?;
+
+    let written = parts(&head)?;
+    assert!(written.len() >= 3, "{written:?}");
+    for part in &written[1..] {
+        let name = part.file_name().expect("file name").to_string_lossy();
+        assert!(name.contains(".part"), "{name}");
+    }
+    assert_eq!(user_messages(&head).await?, questions);
+
+    // The conversation is still found by its first part.
+    let id = test.session_configured.session_id.to_string();
+    assert_eq!(
+        find_conversation_path_by_id_str(test.home.path(), &id).await?,
+        Some(head.clone())
+    );
+
+    // Resuming reads every part and appends to the last one.
+    let resumed = builder
+        .resume(&server, test.home.clone(), head.clone())
+        .await?;
+    let reply = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-4"),
+            ev_assistant_message("msg-4", "fourth answer"),
+            ev_completed("resp-4"),
+        ]),
+    )
+    .await;
+    resumed
+        .codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "fourth question".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&resumed.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    let input = reply.single_request().body_json()["input"].to_string();
+    assert!(input.contains("first question"), "{input}");
+    assert!(input.contains("third question"), "{input}");
+    shutdown(&resumed).await?;
+
+    assert!(parts(&head)?.len() >= written.len());
+    assert_eq!(
+        user_messages(&head).await?,
+        [
+            "first question",
+            "second question",
+            "third question",
+            "fourth question"
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn the_hard_cap_stops_recording_with_a_warning() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.rollout_hard_cap_bytes = Some(MAX_ROLLOUT_BYTES))
+        .build(&server)
+        .await?;
+    let head = test.session_configured.rollout_path.clone();
+
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp"),
+            ev_assistant_message("msg", &long_answer(0)),
+            ev_completed("resp"),
+        ]),
+    )
+    .await;
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "first question".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let warning = wait_for_event(
+        &test.codex,
+        |event| matches!(event, EventMsg::Warning(warning) if warning.message.contains("hard cap")),
+    )
+    .await;
+    let EventMsg::Warning(warning) = warning else {
+        unreachable!("matched a warning");
+    };
+    assert_eq!(
+        warning.message,
+        format!(
+            "The session is no longer being recorded: the rollout reached its hard cap of {MAX_ROLLOUT_BYTES} bytes. It cannot be resumed past this point."
+        )
+    );
+
+    assert!(std::fs::metadata(&head)?.len() <= MAX_ROLLOUT_BYTES);
+    assert_eq!(parts(&head)?, vec![head]);
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SafeModePolicies;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn safe_mode_holds_commands_until_leaving_it_is_approved() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let _responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("write-call", "echo hi > written.txt"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "not written"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    let original = SafeModePolicies {
+        approval_policy: test.config.approval_policy.value(),
+        sandbox_policy: test.config.sandbox_policy.get().clone(),
+        tool_policy: test.config.tool_policy.clone(),
+    };
+
+    test.codex.set_safe_mode(true).await?;
+    let entered = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::SafeModeChanged(change) => Some(change.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(entered.enabled);
+    assert_eq!(entered.before, original);
+    assert_eq!(entered.after.approval_policy, AskForApproval::UnlessTrusted);
+    assert!(
+        entered
+            .after
+            .tool_policy
+            .deny
+            .contains(&"web_search".to_string())
+    );
+
+    // The turn asks for the most permissive policies; safe mode ignores them.
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "write a file".to_string(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+    let approval = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ExecApprovalRequest(request) => Some(request.clone()),
+        EventMsg::TaskComplete(_) => panic!("expected an approval request"),
+        _ => None,
+    })
+    .await;
+    assert_eq!(approval.call_id, "write-call");
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(!test.cwd.path().join("written.txt").exists());
+
+    // Leaving safe mode waits for an approval.
+    let sub_id = test.codex.set_safe_mode(false).await?;
+    let exit = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ExecApprovalRequest(request) => Some(request.clone()),
+        EventMsg::SafeModeChanged(_) => panic!("left safe mode without approval"),
+        _ => None,
+    })
+    .await;
+    assert_eq!(exit.command, vec!["safe_mode", "off"]);
+    test.codex
+        .submit(Op::ExecApproval {
+            id: sub_id,
+            decision: ReviewDecision::Approved,
+        })
+        .await?;
+    let left = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::SafeModeChanged(change) => Some(change.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(!left.enabled);
+    assert_eq!(left.before, entered.after);
+    assert_eq!(left.after, original);
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let rollout = std::fs::read_to_string(test.codex.rollout_path())?;
+    let switches = rollout
+        .lines()
+        .filter(|line| line.contains("\"safe_mode_changed\""))
+        .count();
+    assert_eq!(switches, 2);
+
+    Ok(())
+}
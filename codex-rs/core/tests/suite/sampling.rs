@@ -0,0 +1,143 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::config_types::SamplingParams;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+fn message_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+/// The messages of the warnings among the events of a turn.
+fn warning_messages(events: Vec<EventMsg>) -> Vec<String> {
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            EventMsg::Warning(warning) => Some(warning.message),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sampling_parameters_are_sent_and_recorded_per_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let first = mount_sse_once(&server, message_response("resp-1")).await;
+    let second = mount_sse_once(&server, message_response("resp-2")).await;
+    let third = mount_sse_once(&server, message_response("resp-3")).await;
+    let test = test_codex()
+        .with_model("gpt-4.1")
+        .with_config(|config| {
+            config.sampling = SamplingParams {
+                temperature: Some(0.25),
+                top_p: Some(0.5),
+                seed: Some(7),
+            };
+        })
+        .build(&server)
+        .await?;
+
+    // The Responses API has no seed, which is reported once.
+    let warnings = warning_messages(test.submit_input("first").await?);
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("`seed`"), "{warnings:?}");
+    assert_eq!(
+        warning_messages(test.submit_input("second").await?),
+        Vec::<String>::new()
+    );
+
+    test.codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            execution_mode: None,
+            sampling: Some(SamplingParams {
+                temperature: Some(1.0),
+                ..Default::default()
+            }),
+            offline: None,
+        })
+        .await?;
+    assert_eq!(
+        warning_messages(test.submit_input("third").await?),
+        Vec::<String>::new()
+    );
+
+    for request in [first.single_request(), second.single_request()] {
+        let body = request.body_json();
+        assert_eq!(body["temperature"], json!(0.25));
+        assert_eq!(body["top_p"], json!(0.5));
+        assert_eq!(body.get("seed"), None);
+    }
+    let body = third.single_request().body_json();
+    assert_eq!(body["temperature"], json!(1.0));
+    assert_eq!(body.get("top_p"), None);
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+    let rollout = std::fs::read_to_string(test.codex.rollout_path())?;
+    let recorded: Vec<Value> = rollout
+        .lines()
+        .map(serde_json::from_str::<Value>)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|line| line["type"] == "turn_context")
+        .map(|line| line["payload"]["sampling"].clone())
+        .collect();
+    assert_eq!(
+        recorded,
+        vec![
+            json!({"temperature": 0.25, "top_p": 0.5}),
+            json!({"temperature": 0.25, "top_p": 0.5}),
+            json!({"temperature": 1.0}),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reasoning_models_reject_temperature_at_spawn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let result = test_codex()
+        .with_model("o3")
+        .with_config(|config| {
+            config.sampling.temperature = Some(0.25);
+        })
+        .build(&server)
+        .await;
+
+    let Err(err) = result else {
+        panic!("spawning o3 with a temperature should fail");
+    };
+    assert!(
+        err.to_string()
+            .contains("model `o3` does not support `temperature`"),
+        "{err}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SessionEndReason;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+async fn run_one_turn(test: &TestCodex) -> Result<()> {
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shutdown_ends_with_session_stats_in_events_and_rollout() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![sse(vec![
+            ev_assistant_message("msg-1", "hi"),
+            ev_completed_with_tokens("resp-1", 42),
+        ])],
+    )
+    .await;
+    let test = test_codex().build(&server).await?;
+    let rollout_path = test.session_configured.rollout_path.clone();
+
+    run_one_turn(&test).await?;
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let EventMsg::SessionEnded(ended) = test.codex.next_event().await?.msg else {
+        panic!("SessionEnded does not follow ShutdownComplete");
+    };
+    assert_eq!(ended.reason, SessionEndReason::Shutdown);
+    assert_eq!(ended.stats.turns, 1);
+    assert_eq!(ended.stats.token_usage.total_tokens, 42);
+    assert_eq!(ended.stats.rollout_path.as_ref(), Some(&rollout_path));
+
+    let items = RolloutRecorder::get_rollout_history(&rollout_path)
+        .await?
+        .get_rollout_items();
+    let Some(RolloutItem::EventMsg(EventMsg::SessionEnded(recorded))) = items.last() else {
+        panic!("rollout does not end with SessionEnded: {:?}", items.last());
+    };
+    assert_eq!(recorded.reason, ended.reason);
+    assert_eq!(recorded.stats.turns, ended.stats.turns);
+    assert_eq!(recorded.stats.duration_ms, ended.stats.duration_ms);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shutdown_all_ends_every_conversation() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let conversation_id = test.session_configured.session_id;
+
+    let ended = test.conversation_manager.shutdown_all().await;
+
+    assert_eq!(ended.len(), 1);
+    assert_eq!(
+        ended[&conversation_id].reason,
+        SessionEndReason::ManagerShutdown
+    );
+    assert_eq!(ended[&conversation_id].stats.turns, 0);
+    assert!(
+        test.conversation_manager
+            .get_conversation(conversation_id)
+            .await
+            .is_err()
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::SessionEnded(_))
+    })
+    .await;
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::NewConversation;
+use codex_core::built_in_model_providers;
+use codex_core::config::ConfigBuilder;
+use codex_core::config::ConfigOverrides;
+use codex_core::error::CodexErr;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::session_profiles::SESSION_PROFILES_SUBDIR;
+use codex_core::session_profiles::SessionProfileError;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const REVIEW_PROFILE: &str = r#"
+description = "Read-only review"
+model = "gpt-5.1"
+sandbox_mode = "read-only"
+developer_instructions = "Review the change; do not edit files."
+tool_policy = { deny = ["shell"] }
+"#;
+
+/// A codex home whose `config.toml` points at `server` and that defines the
+/// `review` session profile.
+fn codex_home(server: &MockServer) -> Result<TempDir> {
+    let home = TempDir::new()?;
+    std::fs::write(
+        home.path().join("config.toml"),
+        format!(
+            r#"
+model = "gpt-5.1-codex"
+model_provider = "mock"
+developer_instructions = "Base instructions."
+
+[model_providers.mock]
+name = "mock"
+base_url = "{}/v1"
+wire_api = "responses"
+"#,
+            server.uri()
+        ),
+    )?;
+    let profiles = home.path().join(SESSION_PROFILES_SUBDIR);
+    std::fs::create_dir_all(&profiles)?;
+    std::fs::write(profiles.join("review.toml"), REVIEW_PROFILE)?;
+    Ok(home)
+}
+
+fn manager(home: &Path) -> ConversationManager {
+    ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("dummy"),
+        built_in_model_providers()["openai"].clone(),
+        home.to_path_buf(),
+    )
+}
+
+fn overrides(home: &Path) -> ConfigOverrides {
+    ConfigOverrides {
+        cwd: Some(home.to_path_buf()),
+        ..ConfigOverrides::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn profile_values_sit_between_config_and_overrides() -> Result<()> {
+    let server = start_mock_server().await;
+    let home = codex_home(&server)?;
+    let manager = manager(home.path());
+
+    let listing = manager.list_profiles().await;
+    assert!(listing.errors.is_empty());
+    let names: Vec<&str> = listing
+        .profiles
+        .iter()
+        .map(|profile| profile.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["review"]);
+
+    let config = manager
+        .config_for_profile(
+            "review",
+            ConfigOverrides {
+                model: Some("o3".to_string()),
+                ..overrides(home.path())
+            },
+        )
+        .await?;
+    // The caller's override beats the profile, the profile beats config.toml,
+    // and config.toml still supplies what neither sets.
+    assert_eq!(config.model.as_deref(), Some("o3"));
+    assert_eq!(
+        config.developer_instructions.as_deref(),
+        Some("Review the change; do not edit files.")
+    );
+    assert_eq!(config.sandbox_policy.get(), &SandboxPolicy::ReadOnly);
+    assert_eq!(config.tool_policy.deny, vec!["shell".to_string()]);
+    assert_eq!(config.model_provider_id, "mock");
+    assert_eq!(config.session_profile.as_deref(), Some("review"));
+
+    let missing = manager
+        .new_conversation_with_profile("docs", overrides(home.path()))
+        .await;
+    assert!(matches!(
+        missing,
+        Err(CodexErr::SessionProfile(SessionProfileError::NotFound { name })) if name == "docs"
+    ));
+
+    std::fs::write(
+        home.path()
+            .join(SESSION_PROFILES_SUBDIR)
+            .join("broken.toml"),
+        "sandbox_mode = \"sometimes\"\n",
+    )?;
+    let broken = manager
+        .config_for_profile("broken", overrides(home.path()))
+        .await;
+    assert!(matches!(
+        broken,
+        Err(CodexErr::SessionProfile(SessionProfileError::InvalidField { field, .. }))
+            if field == "sandbox_mode"
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resumed_and_forked_conversations_report_their_profile() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let home = codex_home(&server)?;
+    let manager = manager(home.path());
+
+    let NewConversation {
+        conversation,
+        session_configured,
+        ..
+    } = manager
+        .new_conversation_with_profile("review", overrides(home.path()))
+        .await?;
+    assert_eq!(
+        session_configured.session_profile.as_deref(),
+        Some("review")
+    );
+
+    let turn = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "looks good"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "review my change".into(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&conversation, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    let request = turn.single_request();
+    assert_eq!(request.body_json()["model"], "gpt-5.1");
+    assert!(
+        request
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text.contains("Review the change; do not edit files."))
+    );
+    conversation.submit(Op::Shutdown).await?;
+    wait_for_event(&conversation, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    // Resuming with a plain config still reports the original profile.
+    let rollout_path = session_configured.rollout_path;
+    let plain_config = ConfigBuilder::default()
+        .codex_home(home.path().to_path_buf())
+        .harness_overrides(overrides(home.path()))
+        .build()
+        .await?;
+    assert_eq!(plain_config.session_profile, None);
+    let resumed = manager
+        .resume_conversation_from_rollout(
+            plain_config.clone(),
+            rollout_path.clone(),
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy")),
+        )
+        .await?;
+    assert_eq!(
+        resumed.session_configured.session_profile.as_deref(),
+        Some("review")
+    );
+
+    let forked = manager
+        .fork_conversation(0, plain_config, rollout_path)
+        .await?;
+    assert_eq!(
+        forked.session_configured.session_profile.as_deref(),
+        Some("review")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+use codex_core::skills::SkillPackError;
+use codex_core::skills::SkillPackSource;
+use codex_core::skills::SkillsManager;
+use pretty_assertions::assert_eq;
+use sha2::Digest;
+use sha2::Sha256;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const PACK: &str = "platform";
+
+fn write_skill(root: &Path, dir: &str, name: &str, description: &str) -> Result<()> {
+    let dir = root.join(dir);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join("SKILL.md"),
+        format!("---\nname: {name}\ndescription: {description}\n---\n\nSteps.\n"),
+    )?;
+    Ok(())
+}
+
+/// A `.tar.gz` holding `deploy` and `rollback` skills.
+fn pack_tarball() -> Result<Vec<u8>> {
+    let work = TempDir::new()?;
+    let contents = work.path().join("platform-skills");
+    write_skill(&contents, "deploy", "deploy", "Deploy a service")?;
+    write_skill(&contents, "rollback", "rollback", "Roll back a deploy")?;
+    let archive = work.path().join("pack.tar.gz");
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(work.path())
+        .arg("platform-skills")
+        .status()?;
+    assert!(status.success(), "tar failed");
+    Ok(std::fs::read(archive)?)
+}
+
+async fn serve(server: &MockServer, tarball: Vec<u8>, expected_requests: u64) {
+    Mock::given(method("GET"))
+        .and(path("/platform.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(tarball))
+        .expect(expected_requests)
+        .mount(server)
+        .await;
+}
+
+fn packs(source: SkillPackSource) -> BTreeMap<String, SkillPackSource> {
+    BTreeMap::from([(PACK.to_string(), source)])
+}
+
+fn source(server: &MockServer, version: &str, sha256: String) -> SkillPackSource {
+    SkillPackSource {
+        url: format!("{}/platform.tar.gz", server.uri()),
+        version: version.to_string(),
+        sha256: Some(sha256),
+    }
+}
+
+fn skill_names(manager: &SkillsManager, cwd: &Path) -> Vec<String> {
+    manager
+        .skills_for_cwd(cwd)
+        .skills
+        .into_iter()
+        .map(|skill| skill.name)
+        .filter(|name| name == "deploy" || name == "rollback")
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fetches_pinned_pack_once_and_then_uses_the_cache() -> Result<()> {
+    let server = MockServer::start().await;
+    let tarball = pack_tarball()?;
+    let sha256 = format!("{:x}", Sha256::digest(&tarball));
+    serve(&server, tarball, 1).await;
+
+    let codex_home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let manager = SkillsManager::new(codex_home.path().to_path_buf());
+    manager.set_skill_packs(packs(source(&server, "1.4.0", sha256.clone())));
+    assert_eq!(skill_names(&manager, cwd.path()), Vec::<String>::new());
+
+    let report = manager.sync().await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.fetched, vec![PACK.to_string()]);
+    assert_eq!(
+        skill_names(&manager, cwd.path()),
+        vec!["deploy", "rollback"]
+    );
+
+    // A second sync with the same pin does not hit the server again.
+    let report = manager.sync().await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.cached, vec![PACK.to_string()]);
+    assert!(report.fetched.is_empty());
+
+    // A new process starts from the cache as well.
+    let restarted = SkillsManager::new(codex_home.path().to_path_buf());
+    restarted.set_skill_packs(packs(source(&server, "1.4.0", sha256)));
+    assert_eq!(
+        skill_names(&restarted, cwd.path()),
+        vec!["deploy", "rollback"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unreachable_source_falls_back_to_the_cached_copy() -> Result<()> {
+    let server = MockServer::start().await;
+    let tarball = pack_tarball()?;
+    let sha256 = format!("{:x}", Sha256::digest(&tarball));
+    serve(&server, tarball, 1).await;
+
+    let codex_home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let manager = SkillsManager::new(codex_home.path().to_path_buf());
+    manager.set_skill_packs(packs(source(&server, "1.4.0", sha256)));
+    assert_eq!(manager.sync().await.fetched, vec![PACK.to_string()]);
+
+    // Bump the pin while the source is down.
+    let uri = server.uri();
+    drop(server);
+    manager.set_skill_packs(packs(SkillPackSource {
+        url: format!("{uri}/platform.tar.gz"),
+        version: "1.5.0".to_string(),
+        sha256: Some("0".repeat(64)),
+    }));
+
+    let report = manager.sync().await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.cached, vec![PACK.to_string()]);
+    assert_eq!(
+        skill_names(&manager, cwd.path()),
+        vec!["deploy", "rollback"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn checksum_mismatch_is_rejected() -> Result<()> {
+    let server = MockServer::start().await;
+    let tarball = pack_tarball()?;
+    let actual_sha256 = format!("{:x}", Sha256::digest(&tarball));
+    serve(&server, tarball, 1).await;
+
+    let codex_home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let manager = SkillsManager::new(codex_home.path().to_path_buf());
+    let expected_sha256 = "ab".repeat(32);
+    manager.set_skill_packs(packs(source(&server, "1.4.0", expected_sha256.clone())));
+
+    let report = manager.sync().await;
+
+    assert!(report.fetched.is_empty());
+    match report.errors.as_slice() {
+        [
+            SkillPackError::ChecksumMismatch {
+                name,
+                expected,
+                actual,
+            },
+        ] => {
+            assert_eq!(name, PACK);
+            assert_eq!(expected, &expected_sha256);
+            assert_eq!(actual, &actual_sha256);
+        }
+        errors => panic!("expected a checksum mismatch, got {errors:?}"),
+    }
+    assert_eq!(skill_names(&manager, cwd.path()), Vec::<String>::new());
+    assert!(!codex_home.path().join("skills/.packs").join(PACK).exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn local_skill_overrides_pack_skill_with_the_same_name() -> Result<()> {
+    let server = MockServer::start().await;
+    let tarball = pack_tarball()?;
+    let sha256 = format!("{:x}", Sha256::digest(&tarball));
+    serve(&server, tarball, 1).await;
+
+    let codex_home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    write_skill(
+        &codex_home.path().join("skills"),
+        "my-deploy",
+        "deploy",
+        "My own deploy steps",
+    )?;
+    let manager = SkillsManager::new(codex_home.path().to_path_buf());
+    manager.set_skill_packs(packs(source(&server, "1.4.0", sha256)));
+    assert!(manager.sync().await.errors.is_empty());
+
+    let outcome = manager.skills_for_cwd(cwd.path());
+
+    let deploy = outcome
+        .skills
+        .iter()
+        .find(|skill| skill.name == "deploy")
+        .expect("deploy skill");
+    assert_eq!(deploy.description, "My own deploy steps");
+    assert!(outcome.skills.iter().any(|skill| skill.name == "rollback"));
+    let diagnostics: Vec<&str> = outcome
+        .errors
+        .iter()
+        .map(|err| err.message.as_str())
+        .filter(|message| message.contains("skill pack"))
+        .collect();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(
+        diagnostics[0].starts_with("skill `deploy` from skill pack `platform` is overridden by "),
+        "{}",
+        diagnostics[0]
+    );
+
+    Ok(())
+}
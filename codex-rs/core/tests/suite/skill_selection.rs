@@ -0,0 +1,180 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExcludedSkill;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SkillSelection;
+use codex_protocol::config_types::SkillsMode;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+const SKILLS: &[&str] = &["always", "cargo-release", "python-lint"];
+
+fn write_skill(home: &Path, name: &str, applies_to: &str) {
+    let skill_dir = home.join("skills").join(name);
+    fs::create_dir_all(&skill_dir).unwrap();
+    let contents =
+        format!("---\nname: {name}\ndescription: {name} skill\n{applies_to}---\n\n{name} body\n");
+    fs::write(skill_dir.join("SKILL.md"), contents).unwrap();
+}
+
+fn write_skills(home: &Path) {
+    write_skill(home, "always", "");
+    write_skill(
+        home,
+        "cargo-release",
+        "metadata:\n  applies-to:\n    files: [\"Cargo.toml\"]\n",
+    );
+    write_skill(
+        home,
+        "python-lint",
+        "metadata:\n  applies-to:\n    languages: [\"python\"]\n",
+    );
+}
+
+/// Start a session in a workspace that has a `Cargo.toml` or not, and return
+/// the selection it reported, restricted to the skills written by the test.
+async fn start_session(
+    with_cargo_toml: bool,
+    include_all_skills: bool,
+) -> Result<(TestCodex, SkillSelection)> {
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::Skills);
+            config.include_all_skills = include_all_skills;
+            if with_cargo_toml {
+                fs::write(config.cwd.join("Cargo.toml"), "[package]\n").unwrap();
+            }
+        })
+        .with_pre_build_hook(write_skills)
+        .build(&server)
+        .await?;
+
+    let selection = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::SkillsSelected(selection) => Some(selection.clone()),
+        _ => None,
+    })
+    .await;
+    Ok((test, own_skills(selection)))
+}
+
+fn own_skills(selection: SkillSelection) -> SkillSelection {
+    SkillSelection {
+        included: selection
+            .included
+            .into_iter()
+            .filter(|name| SKILLS.contains(&name.as_str()))
+            .collect(),
+        excluded: selection
+            .excluded
+            .into_iter()
+            .filter(|skill| SKILLS.contains(&skill.name.as_str()))
+            .collect(),
+        mode: selection.mode,
+    }
+}
+
+fn python_lint_excluded() -> ExcludedSkill {
+    ExcludedSkill {
+        name: "python-lint".to_string(),
+        reason: "`python` not detected in the workspace".to_string(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn workspace_with_cargo_toml_selects_cargo_skill() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (test, selection) = start_session(true, false).await?;
+    let expected = SkillSelection {
+        included: vec!["always".to_string(), "cargo-release".to_string()],
+        excluded: vec![python_lint_excluded()],
+        mode: SkillsMode::Prompt,
+    };
+    let mut included = selection.included.clone();
+    included.sort();
+    assert_eq!(
+        SkillSelection {
+            included,
+            excluded: selection.excluded,
+            mode: selection.mode,
+        },
+        expected
+    );
+
+    // The selection is kept in the session meta of the rollout.
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let recorded = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => meta_line.meta.skills,
+            _ => None,
+        })
+        .expect("session meta records the skill selection");
+    let mut recorded = own_skills(recorded);
+    recorded.included.sort();
+    assert_eq!(recorded, expected);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn workspace_without_cargo_toml_excludes_cargo_skill() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (_test, selection) = start_session(false, false).await?;
+    assert_eq!(
+        selection,
+        SkillSelection {
+            included: vec!["always".to_string()],
+            excluded: vec![
+                ExcludedSkill {
+                    name: "cargo-release".to_string(),
+                    reason: "no workspace file matches `Cargo.toml`".to_string(),
+                },
+                python_lint_excluded(),
+            ],
+            mode: SkillsMode::Prompt,
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn include_all_skills_ignores_applicability_rules() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (_test, mut selection) = start_session(false, true).await?;
+    selection.included.sort();
+    assert_eq!(
+        selection,
+        SkillSelection {
+            included: SKILLS.iter().map(ToString::to_string).collect(),
+            excluded: Vec::new(),
+            mode: SkillsMode::Prompt,
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::config_types::SkillsMode;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+const SKILL_TOOL: &str = "skill_release-notes";
+
+fn write_skills(home: &Path) {
+    let write = |name: &str, contents: &str| {
+        let skill_dir = home.join("skills").join(name);
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(skill_dir.join("SKILL.md"), contents).unwrap();
+    };
+    write(
+        "release-notes",
+        "---\nname: release-notes\ndescription: Draft release notes.\nmetadata:\n  parameters:\n    - name: version\n      description: The version being released\n      required: true\n    - name: audience\n---\n\nWrite the notes for {{version}}, for {{audience}} readers.\n",
+    );
+    write(
+        "plain",
+        "---\nname: plain\ndescription: A skill without parameters.\n---\n\nplain body\n",
+    );
+}
+
+fn tool(request: &ResponsesRequest, name: &str) -> Option<Value> {
+    request.body_json()["tools"]
+        .as_array()?
+        .iter()
+        .find(|tool| tool.get("name").and_then(Value::as_str) == Some(name))
+        .cloned()
+}
+
+fn instructions(request: &ResponsesRequest) -> String {
+    request.message_input_texts("user").join("\n")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parameterized_skills_are_offered_and_run_as_tools() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::Skills);
+            config.skills_mode = SkillsMode::Tools;
+        })
+        .with_pre_build_hook(write_skills)
+        .build(&server)
+        .await?;
+
+    let arguments = json!({"version": "1.2.0", "audience": "internal"}).to_string();
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("call-1", SKILL_TOOL, &arguments),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "draft the release notes".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    let mut calls = Vec::new();
+    wait_for_event(&test.codex, |event| {
+        if let EventMsg::SkillToolCall(call) = event {
+            calls.push(call.clone());
+        }
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let offered = tool(&requests[0], SKILL_TOOL).expect("the skill is offered as a tool");
+    assert_eq!(offered["parameters"]["required"], json!(["version"]));
+    assert_eq!(
+        offered["parameters"]["properties"]["version"]["description"],
+        json!("The version being released")
+    );
+    assert_eq!(tool(&requests[0], "skill_plain"), None);
+    let instructions = instructions(&requests[0]);
+    assert!(instructions.contains("- plain:"), "{instructions}");
+    assert!(!instructions.contains("- release-notes:"), "{instructions}");
+
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].call_id, "call-1");
+    assert_eq!(calls[0].name, "release-notes");
+    assert_eq!(
+        calls[0].arguments,
+        [("audience", "internal"), ("version", "1.2.0")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    );
+
+    let output = "Write the notes for 1.2.0, for internal readers.\n";
+    assert_eq!(
+        requests[1].function_call_output_text("call-1").as_deref(),
+        Some(output)
+    );
+
+    // The mode is kept in the session meta, and the output in the rollout.
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let items = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items();
+    let mode = items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta_line) => meta_line
+            .meta
+            .skills
+            .as_ref()
+            .map(|selection| selection.mode),
+        _ => None,
+    });
+    assert_eq!(mode, Some(SkillsMode::Tools));
+    let recorded = items.iter().find_map(|item| match item {
+        RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { call_id, output })
+            if call_id == "call-1" =>
+        {
+            Some(output.content.clone())
+        }
+        _ => None,
+    });
+    assert_eq!(recorded.as_deref(), Some(output));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn skills_stay_in_the_prompt_by_default() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::Skills);
+        })
+        .with_pre_build_hook(write_skills)
+        .build(&server)
+        .await?;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let request = mock.single_request();
+    assert_eq!(tool(&request, SKILL_TOOL), None);
+    assert!(instructions(&request).contains("- release-notes:"));
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::RolloutRecorder;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SkillUsageEvent;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::skills::SkillUsageStats;
+use codex_protocol::config_types::SkillsMode;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn write_skills(home: &Path) {
+    let write = |name: &str, contents: &str| {
+        let skill_dir = home.join("skills").join(name);
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(skill_dir.join("SKILL.md"), contents).unwrap();
+    };
+    // Offered as a tool for its parameter; `plain` stays in the prompt.
+    write(
+        "release-notes",
+        "---\nname: release-notes\ndescription: Draft release notes.\nmetadata:\n  parameters:\n    - name: version\n      required: true\n---\n\nWrite the notes for {{version}}.\n",
+    );
+    write(
+        "plain",
+        "---\nname: plain\ndescription: A skill without parameters.\n---\n\nplain body\n",
+    );
+}
+
+const SKILLS: [&str; 2] = ["plain", "release-notes"];
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| (*name).to_string()).collect()
+}
+
+/// `names` without the system skills installed in every codex home, sorted.
+fn ours(names: &[String]) -> Vec<String> {
+    let mut ours: Vec<String> = names
+        .iter()
+        .filter(|name| SKILLS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    ours.sort();
+    ours
+}
+
+async fn usage_report(manager: &ConversationManager) -> Result<BTreeMap<String, SkillUsageStats>> {
+    let mut report = manager.skill_usage_report().await?;
+    report.retain(|name, _| SKILLS.contains(&name.as_str()));
+    Ok(report)
+}
+
+/// The skill usage a turn reported, with the turn's completion.
+fn reported_usage(events: Vec<EventMsg>) -> (SkillUsageEvent, TaskCompleteEvent) {
+    let mut usage = None;
+    for event in events {
+        match event {
+            EventMsg::SkillUsage(event) => usage = Some(event),
+            EventMsg::TaskComplete(complete) => {
+                return (usage.expect("skill usage before TaskComplete"), complete);
+            }
+            _ => {}
+        }
+    }
+    unreachable!("the events of a turn end with TaskComplete");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn invoked_and_available_skills_are_counted_apart() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::Skills);
+            config.skills_mode = SkillsMode::Tools;
+        })
+        .with_pre_build_hook(write_skills)
+        .build(&server)
+        .await?;
+    let arguments = json!({"version": "1.2.0"}).to_string();
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("call-1", "skill_release-notes", &arguments),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-1", "drafted"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_assistant_message("msg-2", "hello"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    let (first, complete) = reported_usage(test.submit_input("draft the release notes").await?);
+    assert_eq!(first.invoked, names(&["release-notes"]));
+    assert_eq!(complete.invoked_skills, names(&["release-notes"]));
+    let (second, complete) = reported_usage(test.submit_input("say hello").await?);
+    assert_eq!(second.invoked, Vec::<String>::new());
+    assert_eq!(complete.invoked_skills, Vec::<String>::new());
+    for usage in [&first, &second] {
+        assert_eq!(ours(&usage.available), names(&SKILLS));
+    }
+
+    let expected = BTreeMap::from([
+        (
+            "plain".to_string(),
+            SkillUsageStats {
+                available_turns: 2,
+                invoked_turns: 0,
+            },
+        ),
+        (
+            "release-notes".to_string(),
+            SkillUsageStats {
+                available_turns: 2,
+                invoked_turns: 1,
+            },
+        ),
+    ]);
+    assert_eq!(usage_report(&test.conversation_manager).await?, expected);
+
+    // Each turn's usage is kept in the rollout.
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let recorded: Vec<SkillUsageEvent> =
+        RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+            .await?
+            .get_rollout_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                RolloutItem::EventMsg(EventMsg::SkillUsage(usage)) => Some(usage),
+                _ => None,
+            })
+            .collect();
+    assert_eq!(recorded, vec![first, second]);
+
+    // The counts outlive the manager that kept them.
+    let restarted = ConversationManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("dummy"),
+        test.config.model_provider.clone(),
+        test.config.codex_home.clone(),
+    );
+    assert_eq!(usage_report(&restarted).await?, expected);
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used)]
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::config::Config;
+use codex_core::features::Feature;
+use codex_core::spawn_observer::SpawnMilestone;
+use codex_core::spawn_observer::SpawnObserver;
+use codex_protocol::ConversationId;
+use core_test_support::load_default_config_for_test;
+use core_test_support::skip_if_no_network;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockBuilder;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path_regex;
+
+#[derive(Default)]
+struct RecordingObserver {
+    milestones: Mutex<Vec<(ConversationId, SpawnMilestone)>>,
+}
+
+impl SpawnObserver for RecordingObserver {
+    fn on_milestone(&self, conversation_id: ConversationId, milestone: SpawnMilestone) {
+        self.milestones
+            .lock()
+            .expect("milestones lock")
+            .push((conversation_id, milestone));
+    }
+}
+
+impl RecordingObserver {
+    /// The milestones seen so far, checking they all carry the same id.
+    fn milestones(&self) -> (ConversationId, Vec<SpawnMilestone>) {
+        let milestones = self.milestones.lock().expect("milestones lock").clone();
+        let conversation_id = milestones.first().expect("a milestone was reported").0;
+        assert!(
+            milestones.iter().all(|(id, _)| *id == conversation_id),
+            "{milestones:?}"
+        );
+        (
+            conversation_id,
+            milestones
+                .into_iter()
+                .map(|(_, milestone)| milestone)
+                .collect(),
+        )
+    }
+}
+
+/// A manager whose model list is fetched from `server` on every spawn.
+async fn manager_and_config(
+    server: &MockServer,
+    home: &TempDir,
+    cwd: &TempDir,
+) -> (ConversationManager, Config) {
+    let mut config = load_default_config_for_test(home).await;
+    config.cwd = cwd.path().to_path_buf();
+    config.features.enable(Feature::RemoteModels);
+    let provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        request_max_retries: Some(0),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.model_provider = provider.clone();
+    let manager = ConversationManager::with_models_provider_and_home(
+        CodexAuth::create_dummy_chatgpt_auth_for_testing(),
+        provider,
+        home.path().to_path_buf(),
+    );
+    (manager, config)
+}
+
+fn models_endpoint() -> MockBuilder {
+    Mock::given(method("GET")).and(path_regex(".*/models$"))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_stalled_spawn_already_reported_its_id_and_rollout() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    models_endpoint()
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .mount(&server)
+        .await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let (manager, config) = manager_and_config(&server, &home, &cwd).await;
+    let observer = Arc::new(RecordingObserver::default());
+
+    let spawn = manager.new_conversation_with_observer(config, observer.clone());
+    let timed_out = tokio::time::timeout(Duration::from_millis(500), spawn).await;
+    assert!(timed_out.is_err(), "the spawn waits for the provider");
+
+    let (conversation_id, milestones) = observer.milestones();
+    let [
+        SpawnMilestone::IdAllocated,
+        SpawnMilestone::RolloutCreated { path },
+        SpawnMilestone::ProviderRequestIssued,
+    ] = milestones.as_slice()
+    else {
+        panic!("unexpected milestones: {milestones:?}");
+    };
+    assert!(path.exists(), "{}", path.display());
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    assert!(
+        file_name.is_some_and(|name| name.contains(&conversation_id.to_string())),
+        "{}",
+        path.display()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_failed_spawn_reports_the_failure_with_its_id() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    models_endpoint()
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let (manager, mut config) = manager_and_config(&server, &home, &cwd).await;
+    // Unknown to the built-in list the spawn falls back to.
+    config.model = Some("no-such-model".to_string());
+    let observer = Arc::new(RecordingObserver::default());
+
+    let err = manager
+        .new_conversation_with_observer(config, observer.clone())
+        .await
+        .err()
+        .expect("unknown model fails the spawn");
+
+    let (_, milestones) = observer.milestones();
+    let [
+        SpawnMilestone::IdAllocated,
+        SpawnMilestone::RolloutCreated { path },
+        SpawnMilestone::ProviderRequestIssued,
+        SpawnMilestone::Failed { error },
+    ] = milestones.as_slice()
+    else {
+        panic!("unexpected milestones: {milestones:?}");
+    };
+    assert_eq!(error, &err.to_string());
+    assert!(!path.exists(), "the rollout of a failed spawn is removed");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_spawn_reports_every_milestone_in_order() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    models_endpoint()
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let (manager, config) = manager_and_config(&server, &home, &cwd).await;
+    let observer = Arc::new(RecordingObserver::default());
+
+    let new = manager
+        .new_conversation_with_observer(config, observer.clone())
+        .await?;
+
+    let (conversation_id, milestones) = observer.milestones();
+    assert_eq!(conversation_id, new.conversation_id);
+    assert_eq!(
+        milestones,
+        vec![
+            SpawnMilestone::IdAllocated,
+            SpawnMilestone::RolloutCreated {
+                path: new.session_configured.rollout_path,
+            },
+            SpawnMilestone::ProviderRequestIssued,
+            SpawnMilestone::SessionConfigured,
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use codex_core::blob_store::BlobStore;
+use codex_core::protocol::EventMsg;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+const TEN_MIB: usize = 10 * 1024 * 1024;
+const CAP: usize = 4096;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn huge_outputs_are_cut_for_the_model_and_kept_in_a_blob() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let command = format!("printf START; head -c {TEN_MIB} /dev/zero | tr '\\0' x; printf END");
+    let requests = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("big-call", &command),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.max_tool_output_bytes = Some(CAP);
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("print a lot").await?;
+    let end = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ExecCommandEnd(end) => Some(end.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    // The event carries the head, the tail and a reference to the rest.
+    let blob = end.output_blob.expect("the cut output is stored");
+    let full = BlobStore::new(test.codex_home_path()).get(&blob).await?;
+    assert_eq!(full.len() as u64, blob.size_bytes);
+    assert!(full.len() > TEN_MIB, "{}", full.len());
+    assert!(full.starts_with(b"START") && full.ends_with(b"END"));
+    let marker = format!("[... {} bytes omitted ...]", full.len() - CAP);
+    assert!(end.aggregated_output.starts_with("START"));
+    assert!(end.aggregated_output.ends_with("END"));
+    assert!(
+        end.aggregated_output.contains(&format!("\n{marker}\n")),
+        "{}",
+        end.aggregated_output
+    );
+    assert!(end.aggregated_output.len() < CAP + 64);
+
+    // The model gets the same cut output.
+    let sent = requests
+        .function_call_output_text("big-call")
+        .expect("the output was sent back");
+    assert!(sent.contains(&marker), "{sent}");
+    assert!(sent.len() < 2 * CAP, "{}", sent.len());
+
+    Ok(())
+}
@@ -2,9 +2,11 @@
 #![allow(clippy::unwrap_used)]
 
 use std::fs;
+use std::num::NonZeroUsize;
 use std::time::Duration;
 use std::time::Instant;
 
+use codex_core::config::types::ToolCallIsolation;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
@@ -31,32 +33,9 @@ use serde_json::Value;
 use serde_json::json;
 use tokio::sync::oneshot;
 
-async fn run_turn(test: &TestCodex, prompt: &str) -> anyhow::Result<()> {
-    let session_model = test.session_configured.model.clone();
-
-    test.codex
-        .submit(Op::UserTurn {
-            items: vec![UserInput::Text {
-                text: prompt.into(),
-            }],
-            final_output_json_schema: None,
-            cwd: test.cwd.path().to_path_buf(),
-            approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::DangerFullAccess,
-            model: session_model,
-            effort: None,
-            summary: ReasoningSummary::Auto,
-        })
-        .await?;
-
-    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
-
-    Ok(())
-}
-
 async fn run_turn_and_measure(test: &TestCodex, prompt: &str) -> anyhow::Result<Duration> {
     let start = Instant::now();
-    run_turn(test, prompt).await?;
+    test.submit_turn(prompt).await?;
     Ok(start.elapsed())
 }
 
@@ -135,7 +114,7 @@ async fn read_file_tools_run_in_parallel() -> anyhow::Result<()> {
     )
     .await;
 
-    run_turn(&test, "warm up parallel tool").await?;
+    test.submit_turn("warm up parallel tool").await?;
 
     let duration = run_turn_and_measure(&test, "exercise sync tool").await?;
     assert_parallel_duration(duration);
@@ -242,7 +221,7 @@ async fn tool_results_grouped() -> anyhow::Result<()> {
     )
     .await;
 
-    run_turn(&test, "run shell three times").await?;
+    test.submit_turn("run shell three times").await?;
 
     let input = tool_output_request.single_request().input();
 
@@ -358,6 +337,9 @@ async fn shell_tools_start_before_response_completed_when_stream_delayed() -> an
             model: session_model,
             effort: None,
             summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
         })
         .await?;
 
@@ -407,3 +389,139 @@ async fn shell_tools_start_before_response_completed_when_stream_delayed() -> an
 
     Ok(())
 }
+
+/// The `call_id`s of the tool outputs in `input`, in order.
+fn output_call_ids(input: &[Value]) -> Vec<String> {
+    input
+        .iter()
+        .filter(|item| item.get("type").and_then(Value::as_str) == Some("function_call_output"))
+        .filter_map(|item| item.get("call_id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parallel_calls_respect_the_cap_and_keep_call_order() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .with_config(|config| {
+            config.parallel_tool_calls.max_concurrency = NonZeroUsize::new(2);
+        });
+    let test = builder.build(&server).await?;
+
+    // The calls finish second, first and third. The second fails after
+    // 100ms, which frees the slot the third waits for.
+    let slow = json!({"sleep_after_ms": 200}).to_string();
+    let failing = json!({
+        "sleep_before_ms": 100,
+        "barrier": {"id": "capped-parallel-calls", "participants": 0},
+    })
+    .to_string();
+    let slower = json!({"sleep_after_ms": 300}).to_string();
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                json!({"type": "response.created", "response": {"id": "resp-1"}}),
+                ev_function_call("call-1", "test_sync_tool", &slow),
+                ev_function_call("call-2", "test_sync_tool", &failing),
+                ev_function_call("call-3", "test_sync_tool", &slower),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let duration = run_turn_and_measure(&test, "run three calls").await?;
+    // Unlimited, the calls would take as long as the slowest: 300ms.
+    assert!(
+        duration >= Duration::from_millis(400),
+        "expected the third call to wait for a slot, got {duration:?}"
+    );
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    let follow_up = &requests[1];
+    assert_eq!(
+        output_call_ids(&follow_up.input()),
+        ["call-1", "call-2", "call-3"]
+    );
+    assert_eq!(
+        follow_up.function_call_output_text("call-1").as_deref(),
+        Some("ok")
+    );
+    let failure = follow_up
+        .function_call_output_text("call-2")
+        .unwrap_or_default();
+    assert!(failure.contains("participants"), "{failure}");
+    assert_eq!(
+        follow_up.function_call_output_text("call-3").as_deref(),
+        Some("ok")
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn per_call_tmp_gives_each_call_its_own_scratch_directory() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex().with_model("gpt-5.1").with_config(|config| {
+        config.parallel_tool_calls.isolation = ToolCallIsolation::PerCallTmp;
+    });
+    let test = builder.build(&server).await?;
+
+    let append = |call_id: &str| {
+        json!({
+            "command": format!(
+                "printf '{call_id};' >> \"$CODEX_TURN_TMPDIR/log\" && cat \"$CODEX_TURN_TMPDIR/log\""
+            ),
+            "timeout_ms": 1_000,
+        })
+        .to_string()
+    };
+    let call_ids = ["call-1", "call-2", "call-3"];
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                json!({"type": "response.created", "response": {"id": "resp-1"}}),
+                ev_function_call(call_ids[0], "shell_command", &append(call_ids[0])),
+                ev_function_call(call_ids[1], "shell_command", &append(call_ids[1])),
+                ev_function_call(call_ids[2], "shell_command", &append(call_ids[2])),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.submit_turn("append to the scratch log").await?;
+
+    // Each call only sees what it wrote itself.
+    let follow_up = &responses.requests()[1];
+    assert_eq!(output_call_ids(&follow_up.input()), call_ids);
+    for call_id in call_ids {
+        let output = follow_up
+            .function_call_output_text(call_id)
+            .unwrap_or_default();
+        let written: Vec<&str> = call_ids
+            .into_iter()
+            .filter(|other| output.contains(&format!("{other};")))
+            .collect();
+        assert_eq!(written, [call_id], "{output}");
+    }
+
+    Ok(())
+}
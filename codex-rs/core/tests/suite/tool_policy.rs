@@ -0,0 +1,125 @@
+use anyhow::Result;
+use codex_core::AuthManager;
+use codex_core::CodexAuth;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::config_types::ToolPolicy;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+fn tool_names(request: &ResponsesRequest) -> Vec<String> {
+    request.body_json()["tools"]
+        .as_array()
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|tool| {
+                    tool.get("name")
+                        .or_else(|| tool.get("type"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn message_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn denied_tools_are_hidden_until_the_policy_allows_them() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let denied_turn = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_shell_command_call("call-denied", "echo hi"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let refusal = mount_sse_once(&server, message_response("resp-2")).await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.tool_policy = ToolPolicy {
+                allow: None,
+                deny: vec!["shell_command".to_string()],
+            };
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("say hi").await?;
+
+    let tools = tool_names(&denied_turn.single_request());
+    assert!(!tools.contains(&"shell_command".to_string()), "{tools:?}");
+    assert_eq!(
+        refusal
+            .single_request()
+            .function_call_output_text("call-denied"),
+        Some("tool shell_command is not available in this conversation".to_string())
+    );
+
+    test.codex.update_tool_policy(ToolPolicy::default()).await?;
+    let updated = wait_for_event(&test.codex, |ev| {
+        matches!(ev, EventMsg::ToolPolicyUpdated(_))
+    })
+    .await;
+    let EventMsg::ToolPolicyUpdated(updated) = updated else {
+        unreachable!("filtered above");
+    };
+    assert_eq!(updated, ToolPolicy::default());
+
+    let allowed_turn = mount_sse_once(&server, message_response("resp-3")).await;
+    test.submit_turn("say hi again").await?;
+    let tools = tool_names(&allowed_turn.single_request());
+    assert!(tools.contains(&"shell_command".to_string()), "{tools:?}");
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+
+    // The recorded policy wins over the configured one when resuming.
+    let resumed = test
+        .conversation_manager
+        .resume_conversation_from_rollout(
+            test.config.clone(),
+            test.codex.rollout_path(),
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy")),
+        )
+        .await?
+        .conversation;
+    let resumed_turn = mount_sse_once(&server, message_response("resp-4")).await;
+    resumed
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "still there?".to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&resumed, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let tools = tool_names(&resumed_turn.single_request());
+    assert!(tools.contains(&"shell_command".to_string()), "{tools:?}");
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ToolTimedOutEvent;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::MockServer;
+
+const TOOL_TIMEOUT: Duration = Duration::from_secs(1);
+
+async fn mount_call_then_reply(
+    server: &MockServer,
+    call_id: &str,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> ResponseMock {
+    mount_sse_sequence(
+        server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call(call_id, tool_name, &arguments.to_string()),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "the command hung, moving on"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await
+}
+
+/// The tool timeout among the events of a turn.
+#[allow(clippy::expect_used)]
+fn tool_timeout(events: Vec<EventMsg>) -> ToolTimedOutEvent {
+    events
+        .into_iter()
+        .find_map(|event| match event {
+            EventMsg::ToolTimedOut(timed_out) => Some(timed_out),
+            _ => None,
+        })
+        .expect("the turn reports the timeout")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hung_shell_command_is_killed_with_its_output() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config
+                .tool_timeouts
+                .per_tool
+                .insert("shell_command".to_string(), TOOL_TIMEOUT);
+        })
+        .build(&server)
+        .await?;
+    let call_id = "hung-shell";
+    let mock = mount_call_then_reply(
+        &server,
+        call_id,
+        "shell_command",
+        json!({
+            "command": "echo started; sleep 1000",
+            "login": false,
+            "timeout_ms": 600_000,
+        }),
+    )
+    .await;
+
+    let started = Instant::now();
+    let timed_out = tool_timeout(test.submit_turn_events("run the hanging command").await?);
+    assert!(started.elapsed() < Duration::from_secs(30));
+
+    assert_eq!(timed_out.call_id, call_id);
+    assert_eq!(timed_out.after, TOOL_TIMEOUT);
+    let output = mock
+        .function_call_output_text(call_id)
+        .expect("timeout output");
+    assert_eq!(
+        output.replace("\r\n", "\n").trim_end(),
+        "exit: timeout\nThe tool was killed after 1s and did not complete.\ncaptured:\nstarted"
+    );
+    // The turn went on to ask the model again with the timeout output.
+    assert_eq!(mock.requests().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hung_tool_is_abandoned() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .with_config(|config| {
+            config.tool_timeouts.default = TOOL_TIMEOUT;
+        })
+        .build(&server)
+        .await?;
+    let call_id = "hung-tool";
+    let mock = mount_call_then_reply(
+        &server,
+        call_id,
+        "test_sync_tool",
+        json!({ "sleep_before_ms": 600_000 }),
+    )
+    .await;
+
+    let timed_out = tool_timeout(test.submit_turn_events("call the slow tool").await?);
+
+    assert_eq!(timed_out.call_id, call_id);
+    assert_eq!(timed_out.after, TOOL_TIMEOUT);
+    let output = mock
+        .function_call_output_text(call_id)
+        .expect("timeout output");
+    assert_eq!(
+        output,
+        "exit: timeout\nThe tool was killed after 1s and did not complete.\ncaptured:\n"
+    );
+    assert_eq!(mock.requests().len(), 2);
+
+    Ok(())
+}
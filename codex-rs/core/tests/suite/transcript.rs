@@ -0,0 +1,129 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+const SYNTHETIC_ITEMS: usize = 3_000;
+
+fn message(role: &str, text: &str) -> Value {
+    let content_type = if role == "user" {
+        "input_text"
+    } else {
+        "output_text"
+    };
+    json!({
+        "type": "message",
+        "role": role,
+        "content": [{ "type": content_type, "text": text }],
+    })
+}
+
+fn line(item_type: &str, payload: Value) -> String {
+    json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": item_type,
+        "payload": payload,
+    })
+    .to_string()
+}
+
+/// A rollout of `SYNTHETIC_ITEMS` messages, all compacted into one summary.
+fn write_long_rollout(dir: &Path) -> Result<std::path::PathBuf> {
+    let mut lines = vec![line(
+        "session_meta",
+        json!({
+            "id": ConversationId::new().to_string(),
+            "timestamp": "2030-01-01T00:00:00.000Z",
+            "cwd": dir,
+            "originator": "codex_exec",
+            "cli_version": "0.0.0",
+            "instructions": null,
+            "source": "exec",
+            "model_provider": "openai",
+        }),
+    )];
+    for index in 0..SYNTHETIC_ITEMS {
+        let role = if index % 2 == 0 { "user" } else { "assistant" };
+        lines.push(line(
+            "response_item",
+            message(role, &format!("item {index}")),
+        ));
+    }
+    lines.push(line(
+        "compacted",
+        json!({
+            "message": "",
+            "replacement_history": [message("user", "summary of everything so far")],
+        }),
+    ));
+
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+fn text_of(item: &ResponseItem) -> Option<&str> {
+    match item {
+        ResponseItem::Message { content, .. } => content.iter().find_map(|content| match content {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                Some(text.as_str())
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn transcript_reads_back_items_compaction_removed() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_long_rollout(fixtures.path())?;
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("msg-1", "latest answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex().resume(&server, home, path).await?;
+    test.submit_turn("latest question").await?;
+
+    // Only the compacted window is held and sent to the model.
+    let input = mock.single_request().input();
+    assert!(input.len() < 10, "{input:?}");
+
+    let transcript = test.codex.transcript().await?;
+    let texts: Vec<&str> = transcript.iter().filter_map(text_of).collect();
+    let expected: Vec<String> = (0..SYNTHETIC_ITEMS)
+        .map(|index| format!("item {index}"))
+        .collect();
+    assert_eq!(
+        texts[..SYNTHETIC_ITEMS].to_vec(),
+        expected.iter().map(String::as_str).collect::<Vec<_>>()
+    );
+    assert!(texts.contains(&"latest question"), "{texts:?}");
+    assert_eq!(texts.last(), Some(&"latest answer"));
+
+    Ok(())
+}
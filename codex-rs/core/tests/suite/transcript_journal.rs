@@ -0,0 +1,225 @@
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::config_types::ContextRecoveryStrategy;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TranscriptMutationKind;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_failed;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), text),
+        ev_completed(id),
+    ])
+}
+
+/// Sequence number of the journal entry whose transcript is the input of
+/// `request`, if any.
+async fn seq_sent_in(codex: &CodexConversation, request: &ResponsesRequest) -> Option<u64> {
+    let input = Value::Array(request.input());
+    for mutation in codex.mutation_log() {
+        let Some(transcript) = codex.transcript_as_of(mutation.seq).await.ok().flatten() else {
+            continue;
+        };
+        if serde_json::to_value(transcript).ok()? == input {
+            return Some(mutation.seq);
+        }
+    }
+    None
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn transcript_as_of_reproduces_each_prompt() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            answer("resp-1", "first"),
+            answer("resp-2", "second"),
+            answer("resp-3", "third"),
+            answer("resp-4", "summary of the conversation"),
+            sse_failed(
+                "resp-5",
+                "context_length_exceeded",
+                "Your input exceeds the context window of this model.",
+            ),
+            answer("resp-6", "fourth"),
+        ],
+    )
+    .await;
+    let mut builder = test_codex().with_config(|config| {
+        config.context_recovery = ContextRecoveryStrategy::AutoTruncate;
+        // Compact locally, through the mocked responses endpoint.
+        config.model_provider.name = "OpenAI (test)".into();
+    });
+    let test = builder.build(&server).await?;
+
+    test.submit_turn("turn 1").await?;
+    let checkpoint = test.codex.checkpoint("after turn 1").await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::CheckpointCreated(_))
+    })
+    .await;
+    test.submit_turn("turn 2").await?;
+    test.codex
+        .rollback_to_checkpoint(checkpoint.clone())
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::CheckpointRolledBack(_))
+    })
+    .await;
+    test.submit_turn("turn 3").await?;
+    test.codex.submit(Op::Compact).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    test.submit_turn("turn 4").await?;
+
+    let log = test.codex.mutation_log();
+    let kinds: Vec<TranscriptMutationKind> = log
+        .iter()
+        .map(|mutation| mutation.kind.clone())
+        .filter(|kind| *kind != TranscriptMutationKind::Append)
+        .collect();
+    assert_eq!(
+        kinds[..2],
+        [
+            TranscriptMutationKind::CheckpointRollback { checkpoint },
+            TranscriptMutationKind::Compaction,
+        ]
+    );
+    assert!(
+        matches!(
+            kinds[2..],
+            [TranscriptMutationKind::ContextTruncation { dropped_turns }] if dropped_turns > 0
+        ),
+        "{kinds:?}"
+    );
+    assert_eq!(
+        log.iter().map(|mutation| mutation.seq).collect::<Vec<_>>(),
+        (1..=log.len() as u64).collect::<Vec<_>>()
+    );
+
+    // Every prompt but the compaction request, which adds the summarization
+    // prompt, is the transcript right after some change.
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 6);
+    let mut sent: Vec<Option<u64>> = Vec::new();
+    for index in [0, 1, 2, 4, 5] {
+        sent.push(seq_sent_in(&test.codex, &requests[index]).await);
+    }
+    assert!(sent.iter().all(Option::is_some), "{sent:?}");
+    assert!(sent.windows(2).all(|pair| pair[0] < pair[1]), "{sent:?}");
+
+    // The journal survives a resume, and replays the same transcripts.
+    let home = test.home.clone();
+    let rollout_path = test.codex.rollout_path();
+    let resumed = builder.resume(&server, home, rollout_path).await?;
+    let resumed_log = resumed.codex.mutation_log();
+    assert_eq!(resumed_log.get(..log.len()), Some(&log[..]));
+    for mutation in &log {
+        assert_eq!(
+            resumed.codex.transcript_as_of(mutation.seq).await?,
+            test.codex.transcript_as_of(mutation.seq).await?,
+            "transcript after change {}",
+            mutation.seq
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compaction_leaves_older_items_in_the_rollout() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    const ITEMS: usize = 3_000;
+
+    let server = start_mock_server().await;
+    let mut events = vec![ev_response_created("resp-1")];
+    events.extend(
+        (0..ITEMS).map(|index| {
+            ev_assistant_message(&format!("msg-{index}"), &format!("message {index}"))
+        }),
+    );
+    events.push(ev_completed("resp-1"));
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(events),
+            answer("resp-2", "summary of the conversation"),
+            answer("resp-3", "after the summary"),
+        ],
+    )
+    .await;
+    let mut builder = test_codex().with_config(|config| {
+        // Compact locally, through the mocked responses endpoint.
+        config.model_provider.name = "OpenAI (test)".into();
+    });
+    let test = builder.build(&server).await?;
+
+    test.submit_turn("turn 1").await?;
+    assert!(test.codex.transcript_footprint().resident_items > ITEMS);
+    test.codex.submit(Op::Compact).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+    test.submit_turn("turn 2").await?;
+
+    let footprint = test.codex.transcript_footprint();
+    assert!(footprint.resident_items < 50, "{footprint:?}");
+    assert!(footprint.spilled_items > ITEMS, "{footprint:?}");
+
+    // Everything is still there, read back from the rollout.
+    let answers: Vec<String> = test
+        .codex
+        .transcript()
+        .await?
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::Message { role, content, .. } if role == "assistant" => {
+                match content.as_slice() {
+                    [ContentItem::OutputText { text }] if text.starts_with("message ") => {
+                        Some(text.clone())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect();
+    let expected: Vec<String> = (0..ITEMS).map(|index| format!("message {index}")).collect();
+    assert_eq!(answers, expected);
+    // So is the history as it was right before the compaction.
+    let log = test.codex.mutation_log();
+    let compaction = log
+        .iter()
+        .position(|mutation| mutation.kind == TranscriptMutationKind::Compaction)
+        .expect("compaction");
+    let before = test
+        .codex
+        .transcript_as_of(log[compaction - 1].seq)
+        .await?
+        .expect("transcript");
+    assert!(before.len() > ITEMS, "{} items", before.len());
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TurnDeadlineExceededEvent;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tokio::sync::oneshot;
+
+const DEADLINE: Duration = Duration::from_secs(1);
+/// Headroom for stopping the turn once the deadline passes.
+const SLACK: Duration = Duration::from_secs(5);
+
+/// Wait for the deadline to pass and the turn to complete, returning the
+/// deadline event and the final message of the turn.
+async fn wait_for_partial_completion(
+    test: &TestCodex,
+) -> (TurnDeadlineExceededEvent, Option<String>) {
+    let EventMsg::TurnDeadlineExceeded(exceeded) = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnDeadlineExceeded(_))
+    })
+    .await
+    else {
+        unreachable!("wait_for_event returns the matched event");
+    };
+    let EventMsg::TaskComplete(complete) = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await
+    else {
+        unreachable!("wait_for_event returns the matched event");
+    };
+    (exceeded, complete.last_agent_message)
+}
+
+async fn recorded_deadline_events(test: &TestCodex) -> Result<usize> {
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    Ok(
+        RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+            .await?
+            .get_rollout_items()
+            .into_iter()
+            .filter(|item| {
+                matches!(
+                    item,
+                    RolloutItem::EventMsg(EventMsg::TurnDeadlineExceeded(_))
+                )
+            })
+            .count(),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_sleeping_past_the_deadline_ends_the_turn_partially() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .build(&server)
+        .await?;
+    let call_id = "slow-call";
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_function_call(
+                call_id,
+                "test_sync_tool",
+                &json!({ "sleep_after_ms": 600_000 }).to_string(),
+            ),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    let started = Instant::now();
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "run the slow tool".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: Some(DEADLINE),
+        })
+        .await?;
+
+    let (exceeded, last_agent_message) = wait_for_partial_completion(&test).await;
+    assert!(started.elapsed() < DEADLINE + SLACK);
+    assert_eq!(exceeded.budget, DEADLINE);
+    assert!(exceeded.elapsed >= DEADLINE, "{exceeded:?}");
+    assert_eq!(last_agent_message, None);
+    // The model was not asked again with the aborted output.
+    assert_eq!(mock.requests().len(), 1);
+    assert_eq!(recorded_deadline_events(&test).await?, 1);
+
+    // The next turn sends the model an output for the stopped call.
+    let follow_up = mount_sse_once(
+        &server,
+        sse(vec![ev_response_created("resp-2"), ev_completed("resp-2")]),
+    )
+    .await;
+    let resumed = test_codex()
+        .with_model("test-gpt-5.1-codex")
+        .resume(
+            &server,
+            test.home.clone(),
+            test.session_configured.rollout_path.clone(),
+        )
+        .await?;
+    resumed.submit_turn("what happened?").await?;
+    let output = follow_up
+        .single_request()
+        .function_call_output_text(call_id)
+        .expect("output for the stopped call");
+    assert!(
+        output.starts_with("aborted, the turn ran out of time"),
+        "{output}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn configured_deadline_stops_a_stalled_model_stream() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The rest of the answer never arrives.
+    let (_rest_tx, rest_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![vec![
+        StreamingSseChunk {
+            gate: None,
+            body: sse(vec![
+                ev_response_created("resp-1"),
+                ev_message_item_added("msg-1", ""),
+                ev_output_text_delta("thinking about it"),
+            ]),
+        },
+        StreamingSseChunk {
+            gate: Some(rest_rx),
+            body: sse(vec![ev_completed("resp-1")]),
+        },
+    ]])
+    .await;
+    let test = test_codex()
+        .with_config(|config| config.turn_deadline = Some(DEADLINE))
+        .build_with_streaming_server(&server)
+        .await?;
+
+    let started = Instant::now();
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "take your time".into(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+
+    let (exceeded, last_agent_message) = wait_for_partial_completion(&test).await;
+    assert!(started.elapsed() < DEADLINE + SLACK);
+    assert_eq!(exceeded.budget, DEADLINE);
+    assert_eq!(last_agent_message, None);
+    assert_eq!(recorded_deadline_events(&test).await?, 1);
+
+    server.shutdown().await;
+    Ok(())
+}
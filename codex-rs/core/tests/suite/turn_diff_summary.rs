@@ -0,0 +1,203 @@
+use std::fs;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TurnDiffSummaryEvent;
+use codex_core::protocol::TurnFileChange;
+use codex_core::protocol::TurnFileChangeKind;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_apply_patch_function_call;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn summary_covers_patched_and_shell_edited_files() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.include_apply_patch_tool = true)
+        .build(&server)
+        .await?;
+    let cwd = test.cwd.path();
+    fs::write(cwd.join("existing.txt"), "one\ntwo\nthree\n")?;
+    fs::write(cwd.join("obsolete.txt"), "remove me\n")?;
+    fs::write(cwd.join("untouched.txt"), "same\n")?;
+    let outside = test.home.path().join("notes.md");
+
+    let patch = format!(
+        "*** Begin Patch\n*** Update File: existing.txt\n@@\n one\n-two\n+TWO\n three\n+four\n*** Add File: {}\n+# Notes\n+outside the workspace\n*** End Patch",
+        outside.display()
+    );
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_apply_patch_function_call("patch-call", &patch),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_shell_command_call(
+                    "shell-call",
+                    "printf 'a\\nb\\nc\\n' > created.txt && echo five >> existing.txt && rm obsolete.txt",
+                ),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "edit the files".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+    let summary = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::TurnDiffSummary(summary) => Some(summary.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let change = |path, kind, additions, deletions, outside_cwd| TurnFileChange {
+        path,
+        kind,
+        additions,
+        deletions,
+        outside_cwd,
+    };
+    let mut files = vec![
+        change(
+            cwd.join("created.txt"),
+            TurnFileChangeKind::Added,
+            3,
+            0,
+            false,
+        ),
+        change(
+            cwd.join("existing.txt"),
+            TurnFileChangeKind::Modified,
+            3,
+            1,
+            false,
+        ),
+        change(
+            cwd.join("obsolete.txt"),
+            TurnFileChangeKind::Deleted,
+            0,
+            1,
+            false,
+        ),
+        change(outside, TurnFileChangeKind::Added, 2, 0, true),
+    ];
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let expected = TurnDiffSummaryEvent {
+        files,
+        additions: 8,
+        deletions: 2,
+    };
+    assert_eq!(summary, expected);
+
+    // The summary is kept in the rollout for later review.
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let recorded: Vec<TurnDiffSummaryEvent> =
+        RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+            .await?
+            .get_rollout_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                RolloutItem::EventMsg(EventMsg::TurnDiffSummary(summary)) => Some(summary),
+                _ => None,
+            })
+            .collect();
+    assert_eq!(recorded, vec![expected]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_without_file_changes_has_no_summary() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().with_model("gpt-5.1").build(&server).await?;
+    fs::write(test.cwd.path().join("existing.txt"), "one\n")?;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("shell-call", "cat existing.txt"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.submit_turn("read the file").await?;
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    let items = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items();
+    assert!(
+        !items
+            .iter()
+            .any(|item| matches!(item, RolloutItem::EventMsg(EventMsg::TurnDiffSummary(_))))
+    );
+
+    Ok(())
+}
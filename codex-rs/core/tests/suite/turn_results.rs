@@ -0,0 +1,125 @@
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::turn_results::TurnError;
+use codex_core::turn_results::TurnTicket;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tokio::sync::oneshot;
+
+async fn submit(conversation: &CodexConversation, text: &str) -> Result<TurnTicket> {
+    Ok(conversation
+        .submit_with_ticket(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queued_submissions_each_get_their_own_answer() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (answer_tx, answer_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![
+        vec![
+            StreamingSseChunk {
+                gate: None,
+                body: sse(vec![
+                    ev_response_created("resp-1"),
+                    ev_message_item_added("msg-1", ""),
+                    ev_output_text_delta("first"),
+                ]),
+            },
+            StreamingSseChunk {
+                gate: Some(answer_rx),
+                body: sse(vec![
+                    ev_assistant_message("msg-1", "first answer"),
+                    ev_completed("resp-1"),
+                ]),
+            },
+        ],
+        vec![StreamingSseChunk {
+            gate: None,
+            body: sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-2", "second answer"),
+                ev_completed("resp-2"),
+            ]),
+        }],
+    ])
+    .await;
+    let test = test_codex().build_with_streaming_server(&server).await?;
+
+    let first = submit(&test.codex, "first question").await?;
+    // The first answer is streaming, so the second question is queued behind
+    // it rather than sent along with the first.
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::AgentMessageContentDelta(_))
+    })
+    .await;
+    let second = submit(&test.codex, "second question").await?;
+    test.codex.submit(Op::ListCustomPrompts).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ListCustomPromptsResponse(_))
+    })
+    .await;
+    let _ = answer_tx.send(());
+
+    // Nobody reads the events from here on.
+    let second = test.codex.wait_for_turn(second).await?;
+    let first = test.codex.wait_for_turn(first).await?;
+    assert_eq!(first.final_message.as_deref(), Some("first answer"));
+    assert_eq!(first.error, None);
+    assert_eq!(second.final_message.as_deref(), Some("second answer"));
+    assert_eq!(second.error, None);
+
+    server.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interrupted_turn_resolves_as_aborted() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The answer never arrives.
+    let (_answer_tx, answer_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![vec![StreamingSseChunk {
+        gate: Some(answer_rx),
+        body: sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "too late"),
+            ev_completed("resp-1"),
+        ]),
+    }]])
+    .await;
+    let test = test_codex().build_with_streaming_server(&server).await?;
+
+    let ticket = submit(&test.codex, "take your time").await?;
+    test.codex.submit(Op::Interrupt).await?;
+
+    let result = test.codex.wait_for_turn(ticket).await?;
+    assert_eq!(
+        result.error,
+        Some(TurnError::Aborted(TurnAbortReason::Interrupted))
+    );
+    assert_eq!(result.final_message, None);
+
+    server.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::classify::ItemClass;
+use codex_core::classify::classify_item;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn shell_call(call_id: &str, command: &str) -> serde_json::Value {
+    let args = json!({"command": command, "timeout_ms": 60_000}).to_string();
+    ev_function_call(call_id, "shell_command", &args)
+}
+
+async fn submit(codex: &CodexConversation, text: &str) -> Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    Ok(())
+}
+
+async fn task_complete(codex: &CodexConversation) -> TaskCompleteEvent {
+    let EventMsg::TaskComplete(complete) =
+        wait_for_event(codex, |event| matches!(event, EventMsg::TaskComplete(_))).await
+    else {
+        unreachable!();
+    };
+    complete
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn plain_turns_report_their_only_message_as_final() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("1"),
+            ev_assistant_message("msg-1", "hello there"),
+            ev_completed("1"),
+        ]),
+    )
+    .await;
+
+    submit(&test.codex, "hi").await?;
+    let complete = task_complete(&test.codex).await;
+
+    assert_eq!(complete.final_message.as_deref(), Some("hello there"));
+    assert_eq!(complete.message_count, 1);
+    assert_eq!(complete.tool_call_count, 0);
+    assert_eq!(complete.error, None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_chatter_is_counted_but_not_final() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("1"),
+                ev_assistant_message("msg-1", "let me look"),
+                shell_call("call-1", "echo one"),
+                shell_call("call-2", "echo two"),
+                ev_completed("1"),
+            ]),
+            sse(vec![
+                ev_response_created("2"),
+                ev_assistant_message("msg-2", "both commands ran"),
+                ev_completed("2"),
+            ]),
+        ],
+    )
+    .await;
+
+    submit(&test.codex, "run both").await?;
+    let complete = task_complete(&test.codex).await;
+
+    assert_eq!(complete.final_message.as_deref(), Some("both commands ran"));
+    assert_eq!(complete.message_count, 2);
+    assert_eq!(complete.tool_call_count, 2);
+
+    // The counts agree with the structured transcript of the turn.
+    let turns = test.codex.transcript_turns().await?;
+    let turn = turns.last().expect("the turn is in the transcript");
+    let calls = turn
+        .items
+        .iter()
+        .filter(|item| classify_item(item) == ItemClass::ToolCall)
+        .count();
+    assert_eq!(calls, 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interrupted_turns_report_the_reason_and_counts() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let codex = &test.codex;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("1"),
+            shell_call("call-sleep", "sleep 60"),
+            ev_completed("1"),
+        ]),
+    )
+    .await;
+
+    submit(codex, "start sleep").await?;
+    wait_for_event(codex, |event| {
+        matches!(event, EventMsg::ExecCommandBegin(_))
+    })
+    .await;
+    codex.submit(Op::Interrupt).await?;
+
+    let mut completed = false;
+    let EventMsg::TurnAborted(aborted) = wait_for_event(codex, |event| {
+        completed |= matches!(event, EventMsg::TaskComplete(_));
+        matches!(event, EventMsg::TurnAborted(_))
+    })
+    .await
+    else {
+        unreachable!();
+    };
+    assert!(!completed, "an interrupted turn does not complete");
+    assert_eq!(aborted.reason, TurnAbortReason::Interrupted);
+    assert_eq!(aborted.message_count, 0);
+    assert_eq!(aborted.tool_call_count, 1);
+
+    Ok(())
+}
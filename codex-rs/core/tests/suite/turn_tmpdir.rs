@@ -0,0 +1,150 @@
+#![allow(clippy::expect_used)]
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use codex_core::RolloutRecorder;
+use codex_core::config::types::TurnTmpdirLocation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RolloutItem;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::ev_shell_command_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+/// Writes into the turn directory, reads the file back and prints the
+/// directory on the last line.
+const SCRIPT: &str = "printf scratch > \"$CODEX_TURN_TMPDIR/out.txt\" && cat \"$CODEX_TURN_TMPDIR/out.txt\" && printf '\\n%s' \"$CODEX_TURN_TMPDIR\"";
+
+/// Run a turn whose command uses the turn directory, and return the
+/// directory it printed.
+async fn run_scripted_turn(test: &TestCodex) -> Result<PathBuf> {
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "use scratch space".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            allow_sensitive: false,
+            bypass_read_cache: false,
+            deadline: None,
+        })
+        .await?;
+    let end = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ExecCommandEnd(end) => Some(end.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(end.exit_code, 0, "{}", end.stderr);
+    // The file was written and read back while the turn was running.
+    let (written, dir) = end
+        .stdout
+        .split_once('\n')
+        .expect("the script prints the directory last");
+    assert_eq!(written, "scratch");
+    Ok(PathBuf::from(dir))
+}
+
+async fn build(server: &wiremock::MockServer, keep: bool) -> Result<TestCodex> {
+    mount_sse_sequence(
+        server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_shell_command_call("scratch-call", SCRIPT),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    test_codex()
+        .with_model("gpt-5.1")
+        .with_config(move |config| {
+            config.keep_turn_tmpdirs = keep;
+            if keep {
+                config.turn_tmpdir_location = TurnTmpdirLocation::CodexHome;
+            }
+        })
+        .build(server)
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_tmpdir_is_removed_when_the_turn_ends() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = build(&server, false).await?;
+
+    let dir = run_scripted_turn(&test).await?;
+    assert!(dir.is_absolute(), "{}", dir.display());
+    assert!(!dir.exists(), "{} was not removed", dir.display());
+
+    // The rollout records where the directory was.
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let recorded = RolloutRecorder::get_rollout_history(&test.session_configured.rollout_path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::TurnContext(turn_context) => turn_context.turn_tmpdir,
+            _ => None,
+        });
+    assert_eq!(recorded, Some(dir));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_tmpdir_is_kept_when_configured() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = build(&server, true).await?;
+
+    let dir = run_scripted_turn(&test).await?;
+    assert!(
+        dir.starts_with(test.home.path()),
+        "{} is not under codex home",
+        dir.display()
+    );
+    assert_eq!(std::fs::read_to_string(dir.join("out.txt"))?, "scratch");
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+/// Items a newer version could write: an item type this build does not know
+/// and an unknown event inside a known item type.
+fn invented_items() -> Vec<Value> {
+    vec![
+        json!({"type": "hologram", "payload": {"frames": 3, "codec": "h3d"}}),
+        json!({"type": "event_msg", "payload": {"type": "teleport_started", "destination": "mars"}}),
+    ]
+}
+
+fn append_items(path: &Path, items: &[Value]) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+    for item in items {
+        let mut line = item.clone();
+        line["timestamp"] = json!("2030-01-01T00:00:00.000Z");
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+/// Lines of the rollout at `path` that are one of `items`, ignoring their
+/// timestamps.
+fn preserved_items(path: &Path, items: &[Value]) -> Result<Vec<Value>> {
+    let mut preserved = Vec::new();
+    for line in std::fs::read_to_string(path)?.lines() {
+        let mut value: Value = serde_json::from_str(line)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("timestamp");
+        }
+        if items.contains(&value) {
+            preserved.push(value);
+        }
+    }
+    Ok(preserved)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_and_fork_keep_items_from_newer_versions() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex();
+    let initial = builder.build(&server).await?;
+    let codex = Arc::clone(&initial.codex);
+    let home = initial.home.clone();
+    let rollout_path = initial.session_configured.rollout_path.clone();
+    assert_eq!(initial.session_configured.unknown_rollout_items, 0);
+
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "first answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "first question".into(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+
+    let invented = invented_items();
+    append_items(&rollout_path, &invented)?;
+
+    // Resume: the session opens and reports what it could not read.
+    let resumed = builder.resume(&server, home, rollout_path.clone()).await?;
+    assert_eq!(resumed.session_configured.unknown_rollout_items, 2);
+
+    let second_turn = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-2"),
+            ev_assistant_message("msg-2", "second answer"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+    resumed
+        .codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "second question".into(),
+            }],
+            allow_sensitive: false,
+        })
+        .await?;
+    wait_for_event(&resumed.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    // The model sees the earlier turn but none of the unknown items.
+    let input = second_turn.single_request().body_json()["input"].to_string();
+    assert!(input.contains("first question"));
+    assert!(input.contains("second question"));
+    assert!(!input.contains("hologram"));
+    assert!(!input.contains("teleport_started"));
+
+    // Appending to the resumed rollout left the unknown items untouched.
+    assert_eq!(preserved_items(&rollout_path, &invented)?, invented);
+
+    // Fork before the second question: the unknown items precede it and are
+    // copied verbatim into the new rollout.
+    let NewConversation {
+        session_configured: forked,
+        ..
+    } = resumed
+        .conversation_manager
+        .fork_conversation(1, resumed.config.clone(), rollout_path)
+        .await?;
+    assert_eq!(forked.unknown_rollout_items, 2);
+    assert_eq!(preserved_items(&forked.rollout_path, &invented)?, invented);
+
+    Ok(())
+}
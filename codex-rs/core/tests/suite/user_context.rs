@@ -0,0 +1,124 @@
+#![allow(clippy::expect_used)]
+
+use anyhow::Result;
+use codex_core::config::types::UserContext;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use core_test_support::responses::ResponseMock;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+const EXPECTED_PREFIX_ITEM: &str = "<user_context>
+  <timezone>America/New_York</timezone>
+  <locale>en-US</locale>
+  <current_time>2001-02-03 04:05 UTC</current_time>
+</user_context>";
+
+fn user_context() -> UserContext {
+    UserContext {
+        timezone: Some("America/New_York".to_string()),
+        locale: Some("en-US".to_string()),
+        now_override: Some("2001-02-03T04:05:06Z".parse().expect("valid timestamp")),
+    }
+}
+
+async fn mount_reply(server: &MockServer, id: &str) -> ResponseMock {
+    mount_sse_once(
+        server,
+        sse(vec![
+            ev_response_created(&format!("resp-{id}")),
+            ev_assistant_message(&format!("msg-{id}"), "ok"),
+            ev_completed(&format!("resp-{id}")),
+        ]),
+    )
+    .await
+}
+
+fn user_context_items(request: &ResponseMock) -> Vec<String> {
+    request
+        .single_request()
+        .message_input_texts("user")
+        .into_iter()
+        .filter(|text| text.starts_with("<user_context>"))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn user_context_is_part_of_the_session_prefix() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.user_context = Some(user_context()))
+        .build(&server)
+        .await?;
+
+    let first = mount_reply(&server, "1").await;
+    test.submit_input("what day is it?").await?;
+    let second = mount_reply(&server, "2").await;
+    test.submit_input("and now?").await?;
+
+    // Both turns start within the reported minute, so nothing is added.
+    assert_eq!(user_context_items(&first), vec![EXPECTED_PREFIX_ITEM]);
+    assert_eq!(user_context_items(&second), vec![EXPECTED_PREFIX_ITEM]);
+
+    test.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let rollout = std::fs::read_to_string(&test.session_configured.rollout_path)?;
+    assert!(rollout.contains("<timezone>America/New_York</timezone>"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn forked_conversations_inherit_the_user_context() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| config.user_context = Some(user_context()))
+        .build(&server)
+        .await?;
+    mount_reply(&server, "1").await;
+    test.submit_input("schedule a call").await?;
+    mount_reply(&server, "2").await;
+    test.submit_input("move it an hour later").await?;
+
+    // The fork is configured without a user context of its own.
+    let mut config = test.config.clone();
+    config.user_context = None;
+    let fork = test
+        .conversation_manager
+        .fork_conversation(1, config, test.session_configured.rollout_path.clone())
+        .await?;
+
+    let forked_turn = mount_reply(&server, "3").await;
+    TestCodex::submit_input_to(&fork.conversation, "what time is it there?").await?;
+
+    let items = user_context_items(&forked_turn);
+    assert_eq!(
+        items.first().map(String::as_str),
+        Some(EXPECTED_PREFIX_ITEM)
+    );
+    // The fork keeps the time zone and locale and reads the time afresh.
+    let refreshed = items.last().expect("user context items");
+    assert_eq!(items.len(), 2);
+    assert!(refreshed.contains("<timezone>America/New_York</timezone>"));
+    assert!(refreshed.contains("<locale>en-US</locale>"));
+    assert!(!refreshed.contains("2001-02-03"));
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::CodexConversation;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::WarningCode;
+use codex_core::protocol::WarningEvent;
+use codex_core::protocol::WarningSeverity;
+use codex_core::sensitive_input::SensitiveInputPolicy;
+use codex_core::sensitive_input::SensitivePattern;
+use codex_protocol::ConversationId;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodexBuilder;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+
+const SECRET: &str = "itk_abcdefghijkl";
+
+fn write_rollout(dir: &Path) -> Result<PathBuf> {
+    let meta = json!({
+        "timestamp": "2030-01-01T00:00:00.000Z",
+        "type": "session_meta",
+        "payload": {
+            "id": ConversationId::new().to_string(),
+            "timestamp": "2030-01-01T00:00:00.000Z",
+            "cwd": dir,
+            "originator": "codex_exec",
+            "cli_version": "0.0.0",
+            "instructions": null,
+            "source": "exec",
+            "model_provider": "openai",
+        },
+    });
+    let message = json!({
+        "timestamp": "2030-01-01T00:00:01.000Z",
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": "earlier turn" }],
+        },
+    });
+    let path = dir.join("rollout-2030-01-01T00-00-00-fixture.jsonl");
+    std::fs::write(&path, format!("{meta}\nnot json\n{message}\n"))?;
+    Ok(path)
+}
+
+fn with_sensitive_input(builder: TestCodexBuilder) -> TestCodexBuilder {
+    builder.with_config(|config| {
+        config.sensitive_input = Some(SensitiveInputPolicy {
+            patterns: vec![SensitivePattern {
+                name: "internal-token".to_string(),
+                regex: "itk_[a-z]{12}".to_string(),
+            }],
+            ..SensitiveInputPolicy::default()
+        });
+    })
+}
+
+/// Every event up to and including the first one matching `until`.
+async fn events_until(
+    codex: &CodexConversation,
+    until: impl Fn(&EventMsg) -> bool,
+) -> Result<Vec<Event>> {
+    let mut events = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), codex.next_event()).await??;
+        let done = until(&event.msg);
+        events.push(event);
+        if done {
+            return Ok(events);
+        }
+    }
+}
+
+fn warnings(events: &[Event]) -> Vec<(String, WarningEvent)> {
+    events
+        .iter()
+        .filter_map(|event| match &event.msg {
+            EventMsg::Warning(warning) => Some((event.id.clone(), warning.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn submit_secret(codex: &CodexConversation) -> Result<String> {
+    let sub_id = codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: format!("use {SECRET} now"),
+            }],
+            allow_sensitive: true,
+        })
+        .await?;
+    Ok(sub_id)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn skipped_rollout_lines_are_a_conversation_warning_on_resume() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(fixtures.path())?;
+    let server = start_mock_server().await;
+    let home = Arc::new(TempDir::new()?);
+    let test = test_codex().resume(&server, home, path).await?;
+
+    // Sent right after SessionConfigured, before any turn runs.
+    test.codex.submit(Op::ListMcpTools).await?;
+    let events = events_until(&test.codex, |msg| {
+        matches!(msg, EventMsg::McpListToolsResponse(_))
+    })
+    .await?;
+    let warnings = warnings(&events);
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    let (_, warning) = &warnings[0];
+    assert_eq!(warning.code, WarningCode::RolloutLinesSkipped);
+    assert_eq!(warning.severity, WarningSeverity::Warning);
+    assert!(warning.conversation_scope);
+    assert_eq!(warning.details, Some(json!({ "lines": [2] })));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn redaction_warning_precedes_the_turn_it_belongs_to() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let test = with_sensitive_input(test_codex()).build(&server).await?;
+
+    let sub_id = submit_secret(&test.codex).await?;
+    let events = events_until(&test.codex, |msg| matches!(msg, EventMsg::TaskComplete(_))).await?;
+    let warning_at = events
+        .iter()
+        .position(|event| matches!(event.msg, EventMsg::Warning(_)))
+        .expect("redaction warning");
+    let started_at = events
+        .iter()
+        .position(|event| matches!(event.msg, EventMsg::TaskStarted(_)))
+        .expect("task started");
+    assert!(warning_at < started_at, "{events:?}");
+
+    let warnings = warnings(&events);
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    let (id, warning) = &warnings[0];
+    assert_eq!(id, &sub_id);
+    assert_eq!(warning.code, WarningCode::SensitiveInputRedacted);
+    assert_eq!(warning.severity, WarningSeverity::Info);
+    assert!(!warning.conversation_scope);
+    assert_eq!(
+        warning.details,
+        Some(json!({ "rules": ["internal-token"] }))
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn warnings_below_the_configured_severity_are_not_sent() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let fixtures = TempDir::new()?;
+    let path = write_rollout(fixtures.path())?;
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let home = Arc::new(TempDir::new()?);
+    let test = with_sensitive_input(test_codex())
+        .with_config(|config| config.min_warning_severity = WarningSeverity::Warning)
+        .resume(&server, home, path)
+        .await?;
+
+    submit_secret(&test.codex).await?;
+    let events = events_until(&test.codex, |msg| matches!(msg, EventMsg::TaskComplete(_))).await?;
+    let codes: Vec<WarningCode> = warnings(&events)
+        .into_iter()
+        .map(|(_, warning)| warning.code)
+        .collect();
+    assert_eq!(codes, vec![WarningCode::RolloutLinesSkipped]);
+
+    Ok(())
+}
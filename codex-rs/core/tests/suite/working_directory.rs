@@ -0,0 +1,128 @@
+#![allow(clippy::expect_used)]
+
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::NewConversation;
+use codex_core::RolloutRecorder;
+use codex_core::error::CodexErr;
+use codex_core::protocol::RolloutItem;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+/// Spawn a conversation like `test`'s, in `cwd`.
+async fn spawn_in(
+    test: &TestCodex,
+    cwd: &Path,
+    create_if_missing: bool,
+) -> Result<NewConversation, CodexErr> {
+    let mut config = test.config.clone();
+    config.cwd = cwd.to_path_buf();
+    config.create_cwd_if_missing = create_if_missing;
+    test.conversation_manager.new_conversation(config).await
+}
+
+fn expect_invalid(result: Result<NewConversation, CodexErr>, cwd: &Path, reason: &str) {
+    match result {
+        Err(CodexErr::InvalidWorkingDirectory {
+            path,
+            reason: actual,
+        }) => {
+            assert_eq!(path, cwd);
+            assert!(actual.contains(reason), "{actual}");
+        }
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("spawned in invalid working directory {}", cwd.display()),
+    }
+}
+
+#[tokio::test]
+async fn spawn_rejects_missing_working_directory() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let missing = test.cwd.path().join("missing");
+
+    expect_invalid(
+        spawn_in(&test, &missing, false).await,
+        &missing,
+        "does not exist",
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn spawn_rejects_file_as_working_directory() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let file = test.cwd.path().join("notes.txt");
+    std::fs::write(&file, "not a directory")?;
+
+    expect_invalid(
+        spawn_in(&test, &file, false).await,
+        &file,
+        "is not a directory",
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn spawn_rejects_unreadable_working_directory() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let locked = test.cwd.path().join("locked");
+    std::fs::create_dir(&locked)?;
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000))?;
+    if std::fs::read_dir(&locked).is_ok() {
+        eprintln!("Skipping test: permissions are not enforced for this user.");
+        return Ok(());
+    }
+
+    let result = spawn_in(&test, &locked, false).await;
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755))?;
+    expect_invalid(result, &locked, "cannot be read");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn spawn_creates_missing_working_directory_when_asked() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+    let missing = test.cwd.path().join("scripted").join("workspace");
+
+    let NewConversation {
+        session_configured, ..
+    } = spawn_in(&test, &missing, true).await?;
+
+    assert!(missing.is_dir());
+    let canonical = missing.canonicalize()?;
+    assert_eq!(session_configured.cwd, canonical);
+    let meta_cwd = RolloutRecorder::get_rollout_history(&session_configured.rollout_path)
+        .await?
+        .get_rollout_items()
+        .into_iter()
+        .find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.cwd),
+            _ => None,
+        })
+        .expect("rollout starts with session meta");
+    assert_eq!(meta_cwd, canonical);
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_core::features::Feature;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+fn git(path: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .status()
+        .with_context(|| format!("failed to run git {args:?}"))?;
+    if status.success() {
+        return Ok(());
+    }
+    bail!("git {args:?} exited with {status}");
+}
+
+fn init_git_repo(path: &Path) -> Result<()> {
+    git(path, &["init", "--initial-branch=main"])?;
+    git(path, &["config", "user.name", "Codex Tests"])?;
+    git(path, &["config", "user.email", "codex-tests@example.com"])?;
+    fs::write(path.join("lib.rs"), "fn main() {}\n")?;
+    git(path, &["add", "lib.rs"])?;
+    git(path, &["commit", "-m", "initial"])?;
+    Ok(())
+}
+
+fn notices(texts: &[String]) -> Vec<&String> {
+    texts
+        .iter()
+        .filter(|text| text.starts_with("<workspace_changed>"))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn branch_switch_between_turns_is_reported_to_the_model() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_assistant_message("msg-1", "read lib.rs"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-2", "noted"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::WorkspaceWatch);
+        })
+        .build(&server)
+        .await?;
+    init_git_repo(test.cwd_path())?;
+
+    test.submit_turn("look at lib.rs").await?;
+
+    git(test.cwd_path(), &["checkout", "-b", "feature"])?;
+    fs::write(test.cwd_path().join("lib.rs"), "fn main() { run() }\n")?;
+    git(test.cwd_path(), &["commit", "-am", "change lib.rs"])?;
+
+    test.submit_turn("what now?").await?;
+
+    let requests = responses.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(notices(&requests[0].message_input_texts("user")).is_empty());
+
+    let second_texts = requests[1].message_input_texts("user");
+    let notices = notices(&second_texts);
+    assert_eq!(notices.len(), 1, "{second_texts:?}");
+    let notice = notices[0];
+    assert!(
+        notice.contains("workspace changed: branch main → feature, 1 file modified"),
+        "{notice}"
+    );
+    assert!(notice.contains("- lib.rs"), "{notice}");
+
+    let rollout = fs::read_to_string(&test.session_configured.rollout_path)?;
+    assert!(rollout.contains("<workspace_changed>"));
+    assert!(rollout.contains("\"type\":\"workspace_changed\""));
+
+    Ok(())
+}
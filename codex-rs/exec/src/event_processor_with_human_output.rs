@@ -11,6 +11,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::InputRejectedEvent;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
@@ -21,8 +22,10 @@ use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnRejectedEvent;
 use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
+use codex_core::protocol::WorkspaceChangedEvent;
 use codex_protocol::num_format::format_with_separators;
 use owo_colors::OwoColorize;
 use owo_colors::Style;
@@ -160,19 +163,31 @@ impl EventProcessor for EventProcessorWithHumanOutput {
     }
 
     fn process_event(&mut self, event: Event) -> CodexStatus {
-        let Event { id: _, msg } = event;
+        let Event { id: _, msg, .. } = event;
         match msg {
             EventMsg::Error(ErrorEvent { message, .. }) => {
                 let prefix = "ERROR:".style(self.red);
                 ts_msg!(self, "{prefix} {message}");
             }
-            EventMsg::Warning(WarningEvent { message }) => {
+            EventMsg::TurnRejected(TurnRejectedEvent { reason }) => {
+                let prefix = "turn rejected:".style(self.red);
+                ts_msg!(self, "{prefix} {reason}");
+            }
+            EventMsg::InputRejected(InputRejectedEvent { reason, .. }) => {
+                let prefix = "input rejected:".style(self.red);
+                ts_msg!(self, "{prefix} {reason}");
+                return CodexStatus::InitiateShutdown;
+            }
+            EventMsg::Warning(WarningEvent { message, .. }) => {
                 ts_msg!(
                     self,
                     "{} {message}",
                     "warning:".style(self.yellow).style(self.bold)
                 );
             }
+            EventMsg::WorkspaceChanged(WorkspaceChangedEvent { message, .. }) => {
+                ts_msg!(self, "{}", message.style(self.yellow));
+            }
             EventMsg::DeprecationNotice(DeprecationNoticeEvent { summary, details }) => {
                 ts_msg!(
                     self,
@@ -249,7 +264,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     "auto-cancelling (not supported in exec mode)".style(self.dimmed)
                 );
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => {
                 let last_message = last_agent_message.as_deref();
                 if let Some(output_file) = self.last_message_path.as_deref() {
                     handle_last_message(last_message, output_file);
@@ -593,6 +610,44 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::Retrying(_)
+            | EventMsg::InterruptedTurnRecovered(_)
+            | EventMsg::EffortEscalated(_)
+            | EventMsg::EffortDeescalated(_)
+            | EventMsg::SkillUsage(_)
+            | EventMsg::PersistenceDegraded(_)
+            | EventMsg::PersistenceRecovered(_)
+            | EventMsg::ForkedContextStale(_)
+            | EventMsg::ModelDeprecated(_)
+            | EventMsg::SafeModeChanged(_)
+            | EventMsg::ContextRecovered(_)
+            | EventMsg::QueuedOffline(_)
+            | EventMsg::ConversationPaused(_)
+            | EventMsg::ConversationResumed
+            | EventMsg::SkillToolCall(_)
+            | EventMsg::Forked(_)
+            | EventMsg::HistoryTrimmedOnResume(_)
+            | EventMsg::TurnDeadlineExceeded(_)
+            | EventMsg::ItemPinned(_)
+            | EventMsg::ItemUnpinned(_)
+            | EventMsg::ToolTimedOut(_)
+            | EventMsg::AuthRefreshFailed(_)
+            | EventMsg::ToolsDowngraded(_)
+            | EventMsg::SessionEnded(_)
+            | EventMsg::InstructionsReloaded(_)
+            | EventMsg::ModelListRefreshed(_)
+            | EventMsg::PendingInputsRestored(_)
+            | EventMsg::SkillsSelected(_)
+            | EventMsg::TurnDiffSummary(_)
+            | EventMsg::ContextUsageUpdated(_)
+            | EventMsg::ToolPolicyUpdated(_)
+            | EventMsg::PlannedToolCall(_)
+            | EventMsg::StructuredOutput(_)
+            | EventMsg::ConversationLinked(_)
+            | EventMsg::Heartbeat(_)
+            | EventMsg::ProviderEndpointSwitched(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::CheckpointRolledBack(_)
             | EventMsg::SkillsUpdateAvailable
             | EventMsg::UndoCompleted(_)
             | EventMsg::UndoStarted(_) => {}
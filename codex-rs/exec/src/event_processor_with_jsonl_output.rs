@@ -136,6 +136,13 @@ impl EventProcessorWithJsonOutput {
                 self.last_critical_error = Some(error.clone());
                 vec![ThreadEvent::Error(error)]
             }
+            EventMsg::InputRejected(ev) => {
+                let error = ThreadErrorEvent {
+                    message: format!("input rejected: {}", ev.reason),
+                };
+                self.last_critical_error = Some(error.clone());
+                vec![ThreadEvent::Error(error)]
+            }
             EventMsg::Warning(ev) => {
                 let item = ThreadItem {
                     id: self.get_next_item_id(),
@@ -517,6 +524,7 @@ impl EventProcessor for EventProcessorWithJsonOutput {
         self.process_event(Event {
             id: "".to_string(),
             msg: EventMsg::SessionConfigured(ev.clone()),
+            correlation_id: None,
         });
     }
 
@@ -536,13 +544,17 @@ impl EventProcessor for EventProcessorWithJsonOutput {
 
         let Event { msg, .. } = event;
 
-        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = msg {
-            if let Some(output_file) = self.last_message_path.as_deref() {
-                handle_last_message(last_agent_message.as_deref(), output_file);
+        match msg {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => {
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                CodexStatus::InitiateShutdown
             }
-            CodexStatus::InitiateShutdown
-        } else {
-            CodexStatus::Running
+            EventMsg::InputRejected(_) => CodexStatus::InitiateShutdown,
+            _ => CodexStatus::Running,
         }
     }
 }
@@ -297,6 +297,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         conversation_id: _,
         conversation,
         session_configured,
+        ..
     } = if let Some(ExecCommand::Resume(args)) = command.as_ref() {
         let resume_path = resolve_resume_path(&config, args).await?;
 
@@ -429,6 +430,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                     effort: default_effort,
                     summary: default_summary,
                     final_output_json_schema: output_schema,
+                    allow_sensitive: false,
+                    bypass_read_cache: false,
+                    deadline: None,
                 })
                 .await?;
             info!("Sent prompt with event ID: {task_id}");
@@ -456,7 +460,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 })
                 .await?;
         }
-        if matches!(event.msg, EventMsg::Error(_)) {
+        if matches!(event.msg, EventMsg::Error(_) | EventMsg::InputRejected(_)) {
             error_seen = true;
         }
         let shutdown: CodexStatus = event_processor.process_event(event);
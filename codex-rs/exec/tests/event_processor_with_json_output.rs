@@ -11,10 +11,12 @@ use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::PROTOCOL_VERSION;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::WarningCode;
 use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
@@ -62,6 +64,7 @@ fn event(id: &str, msg: EventMsg) -> Event {
     Event {
         id: id.to_string(),
         msg,
+        correlation_id: None,
     }
 }
 
@@ -76,6 +79,7 @@ fn session_configured_produces_thread_started_event() {
         "e1",
         EventMsg::SessionConfigured(SessionConfiguredEvent {
             session_id,
+            protocol_version: PROTOCOL_VERSION,
             model: "codex-mini-latest".to_string(),
             model_provider_id: "test-provider".to_string(),
             approval_policy: AskForApproval::Never,
@@ -86,6 +90,9 @@ fn session_configured_produces_thread_started_event() {
             history_entry_count: 0,
             initial_messages: None,
             rollout_path,
+            unknown_rollout_items: 0,
+            session_profile: None,
+            resumed: None,
         }),
     );
     let out = ep.collect_thread_events(&ev);
@@ -104,6 +111,7 @@ fn task_started_produces_turn_started_event() {
         "t1",
         EventMsg::TaskStarted(codex_core::protocol::TaskStartedEvent {
             model_context_window: Some(32_000),
+            turn_id: None,
         }),
     ));
 
@@ -220,6 +228,12 @@ fn plan_update_emits_todo_list_started_updated_and_completed() {
         "p3",
         EventMsg::TaskComplete(codex_core::protocol::TaskCompleteEvent {
             last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
     );
     let out_complete = ep.collect_thread_events(&complete);
@@ -464,6 +478,12 @@ fn plan_update_after_complete_starts_new_todo_list_with_new_id() {
         "t2",
         EventMsg::TaskComplete(codex_core::protocol::TaskCompleteEvent {
             last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
     );
     let _ = ep.collect_thread_events(&complete);
@@ -558,9 +578,10 @@ fn warning_event_produces_error_item() {
     let mut ep = EventProcessorWithJsonOutput::new(None);
     let out = ep.collect_thread_events(&event(
         "e1",
-        EventMsg::Warning(WarningEvent {
-            message: "Heads up: Long conversations and multiple compactions can cause the model to be less accurate. Start a new conversation when possible to keep conversations small and targeted.".to_string(),
-        }),
+        EventMsg::Warning(WarningEvent::new(
+            WarningCode::LongConversation,
+            "Heads up: Long conversations and multiple compactions can cause the model to be less accurate. Start a new conversation when possible to keep conversations small and targeted.",
+        )),
     ));
     assert_eq!(
         out,
@@ -616,6 +637,12 @@ fn error_followed_by_task_complete_produces_turn_failed() {
         "e2",
         EventMsg::TaskComplete(codex_core::protocol::TaskCompleteEvent {
             last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
     );
     assert_eq!(
@@ -683,6 +710,7 @@ fn exec_command_end_success_produces_completed_command_item() {
             exit_code: 0,
             duration: Duration::from_millis(5),
             formatted_output: String::new(),
+            output_blob: None,
         }),
     );
     let out_ok = ep.collect_thread_events(&end_ok);
@@ -770,6 +798,7 @@ fn command_execution_output_delta_updates_item_progress() {
             exit_code: 0,
             duration: Duration::from_millis(3),
             formatted_output: String::new(),
+            output_blob: None,
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -843,6 +872,7 @@ fn exec_command_end_failure_produces_failed_command_item() {
             exit_code: 1,
             duration: Duration::from_millis(2),
             formatted_output: String::new(),
+            output_blob: None,
         }),
     );
     let out_fail = ep.collect_thread_events(&end_fail);
@@ -884,6 +914,7 @@ fn exec_command_end_without_begin_is_ignored() {
             exit_code: 0,
             duration: Duration::from_millis(1),
             formatted_output: String::new(),
+            output_blob: None,
         }),
     );
     let out = ep.collect_thread_events(&end_only);
@@ -1063,6 +1094,12 @@ fn task_complete_produces_turn_completed_with_usage() {
         "e2",
         EventMsg::TaskComplete(codex_core::protocol::TaskCompleteEvent {
             last_agent_message: Some("done".to_string()),
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
     );
     let out = ep.collect_thread_events(&complete_event);
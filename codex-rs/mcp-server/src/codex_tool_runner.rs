@@ -48,6 +48,7 @@ pub async fn run_codex_tool_session(
         conversation_id,
         conversation,
         session_configured,
+        ..
     } = match conversation_manager.new_conversation(config).await {
         Ok(res) => res,
         Err(e) => {
@@ -69,6 +70,7 @@ pub async fn run_codex_tool_session(
         // Use a fake id value for now.
         id: "".to_string(),
         msg: EventMsg::SessionConfigured(session_configured.clone()),
+        correlation_id: None,
     };
     outgoing
         .send_event_as_notification(
@@ -94,7 +96,9 @@ pub async fn run_codex_tool_session(
             items: vec![UserInput::Text {
                 text: initial_prompt.clone(),
             }],
+            allow_sensitive: false,
         },
+        correlation_id: None,
     };
 
     if let Err(e) = conversation.submit_with_id(submission).await {
@@ -128,6 +132,7 @@ pub async fn run_codex_tool_session_reply(
     if let Err(e) = conversation
         .submit(Op::UserInput {
             items: vec![UserInput::Text { text: prompt }],
+            allow_sensitive: false,
         })
         .await
     {
@@ -204,6 +209,13 @@ async fn run_codex_tool_session_inner(
                         outgoing.send_response(request_id.clone(), result).await;
                         break;
                     }
+                    EventMsg::InputRejected(rejected) => {
+                        let result = json!({
+                            "error": rejected.reason,
+                        });
+                        outgoing.send_response(request_id.clone(), result).await;
+                        break;
+                    }
                     EventMsg::Warning(_) => {
                         continue;
                     }
@@ -232,7 +244,9 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
-                    EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                    EventMsg::TaskComplete(TaskCompleteEvent {
+                        last_agent_message, ..
+                    }) => {
                         let text = match last_agent_message {
                             Some(msg) => msg,
                             None => "".to_string(),
@@ -304,6 +318,46 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentMessageContentDelta(_)
                     | EventMsg::ReasoningContentDelta(_)
                     | EventMsg::ReasoningRawContentDelta(_)
+                    | EventMsg::Retrying(_)
+                    | EventMsg::InterruptedTurnRecovered(_)
+                    | EventMsg::EffortEscalated(_)
+                    | EventMsg::EffortDeescalated(_)
+                    | EventMsg::SkillUsage(_)
+                    | EventMsg::PersistenceDegraded(_)
+                    | EventMsg::PersistenceRecovered(_)
+                    | EventMsg::ForkedContextStale(_)
+                    | EventMsg::ModelDeprecated(_)
+                    | EventMsg::SafeModeChanged(_)
+                    | EventMsg::ContextRecovered(_)
+                    | EventMsg::QueuedOffline(_)
+                    | EventMsg::ConversationPaused(_)
+                    | EventMsg::ConversationResumed
+                    | EventMsg::SkillToolCall(_)
+                    | EventMsg::Forked(_)
+                    | EventMsg::HistoryTrimmedOnResume(_)
+                    | EventMsg::TurnDeadlineExceeded(_)
+                    | EventMsg::ItemPinned(_)
+                    | EventMsg::ItemUnpinned(_)
+                    | EventMsg::ToolTimedOut(_)
+                    | EventMsg::AuthRefreshFailed(_)
+                    | EventMsg::ToolsDowngraded(_)
+                    | EventMsg::SessionEnded(_)
+                    | EventMsg::InstructionsReloaded(_)
+                    | EventMsg::ModelListRefreshed(_)
+                    | EventMsg::PendingInputsRestored(_)
+                    | EventMsg::SkillsSelected(_)
+                    | EventMsg::TurnDiffSummary(_)
+                    | EventMsg::ContextUsageUpdated(_)
+                    | EventMsg::ToolPolicyUpdated(_)
+                    | EventMsg::PlannedToolCall(_)
+                    | EventMsg::StructuredOutput(_)
+                    | EventMsg::ConversationLinked(_)
+                    | EventMsg::TurnRejected(_)
+                    | EventMsg::Heartbeat(_)
+                    | EventMsg::WorkspaceChanged(_)
+                    | EventMsg::ProviderEndpointSwitched(_)
+                    | EventMsg::CheckpointCreated(_)
+                    | EventMsg::CheckpointRolledBack(_)
                     | EventMsg::SkillsUpdateAvailable
                     | EventMsg::UndoStarted(_)
                     | EventMsg::UndoCompleted(_)
@@ -595,6 +595,7 @@ impl MessageProcessor {
             .submit_with_id(Submission {
                 id: request_id_string,
                 op: codex_core::protocol::Op::Interrupt,
+                correlation_id: None,
             })
             .await;
         if let Err(e) = err {
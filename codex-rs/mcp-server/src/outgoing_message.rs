@@ -236,6 +236,7 @@ mod tests {
     use anyhow::Result;
     use codex_core::protocol::AskForApproval;
     use codex_core::protocol::EventMsg;
+    use codex_core::protocol::PROTOCOL_VERSION;
     use codex_core::protocol::SandboxPolicy;
     use codex_core::protocol::SessionConfiguredEvent;
     use codex_protocol::ConversationId;
@@ -257,6 +258,7 @@ mod tests {
             id: "1".to_string(),
             msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
                 session_id: conversation_id,
+                protocol_version: PROTOCOL_VERSION,
                 model: "gpt-4o".to_string(),
                 model_provider_id: "test-provider".to_string(),
                 approval_policy: AskForApproval::Never,
@@ -267,7 +269,11 @@ mod tests {
                 history_entry_count: 1000,
                 initial_messages: None,
                 rollout_path: rollout_file.path().to_path_buf(),
+                unknown_rollout_items: 0,
+                session_profile: None,
+                resumed: None,
             }),
+            correlation_id: None,
         };
 
         outgoing_message_sender
@@ -296,6 +302,7 @@ mod tests {
         let rollout_file = NamedTempFile::new()?;
         let session_configured_event = SessionConfiguredEvent {
             session_id: conversation_id,
+            protocol_version: PROTOCOL_VERSION,
             model: "gpt-4o".to_string(),
             model_provider_id: "test-provider".to_string(),
             approval_policy: AskForApproval::Never,
@@ -306,10 +313,14 @@ mod tests {
             history_entry_count: 1000,
             initial_messages: None,
             rollout_path: rollout_file.path().to_path_buf(),
+            unknown_rollout_items: 0,
+            session_profile: None,
+            resumed: None,
         };
         let event = Event {
             id: "1".to_string(),
             msg: EventMsg::SessionConfigured(session_configured_event.clone()),
+            correlation_id: None,
         };
         let meta = OutgoingNotificationMeta {
             request_id: Some(RequestId::String("123".to_string())),
@@ -332,6 +343,7 @@ mod tests {
             "msg": {
                 "type": "session_configured",
                 "session_id": session_configured_event.session_id,
+                "protocol_version": PROTOCOL_VERSION,
                 "model": "gpt-4o",
                 "model_provider_id": "test-provider",
                 "approval_policy": "never",
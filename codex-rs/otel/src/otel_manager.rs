@@ -46,6 +46,7 @@ pub struct OtelEventMetadata {
     log_user_prompts: bool,
     app_version: &'static str,
     terminal_type: String,
+    correlation_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -84,6 +85,7 @@ impl OtelManager {
                 log_user_prompts,
                 app_version: env!("CARGO_PKG_VERSION"),
                 terminal_type,
+                correlation_id: None,
             },
             session_span,
         }
@@ -96,6 +98,13 @@ impl OtelManager {
         manager
     }
 
+    /// Tag the events of a turn with the `correlation_id` of its submission.
+    pub fn with_correlation_id(&self, correlation_id: Option<String>) -> Self {
+        let mut manager = self.clone();
+        manager.metadata.correlation_id = correlation_id;
+        manager
+    }
+
     pub fn current_span(&self) -> &Span {
         &self.session_span
     }
@@ -138,6 +147,7 @@ impl OtelManager {
             event.name = "codex.conversation_starts",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -187,6 +197,7 @@ impl OtelManager {
             event.name = "codex.api_request",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -255,6 +266,7 @@ impl OtelManager {
             event.timestamp = %timestamp(),
             event.kind = %kind,
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -277,6 +289,7 @@ impl OtelManager {
                 event.timestamp = %timestamp(),
                 event.kind = %kind,
                 conversation.id = %self.metadata.conversation_id,
+                correlation.id = self.metadata.correlation_id,
                 app.version = %self.metadata.app_version,
                 auth_mode = self.metadata.auth_mode,
                 user.account_id = self.metadata.account_id,
@@ -292,6 +305,7 @@ impl OtelManager {
                 event.name = "codex.sse_event",
                 event.timestamp = %timestamp(),
                 conversation.id = %self.metadata.conversation_id,
+                correlation.id = self.metadata.correlation_id,
                 app.version = %self.metadata.app_version,
                 auth_mode = self.metadata.auth_mode,
                 user.account_id = self.metadata.account_id,
@@ -315,6 +329,7 @@ impl OtelManager {
             event.kind = %"response.completed",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -340,6 +355,7 @@ impl OtelManager {
             event.timestamp = %timestamp(),
             event.kind = %"response.completed",
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -375,6 +391,7 @@ impl OtelManager {
             event.name = "codex.user_prompt",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -399,6 +416,7 @@ impl OtelManager {
             event.name = "codex.tool_decision",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -452,6 +470,7 @@ impl OtelManager {
             event.name = "codex.tool_result",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -482,6 +501,7 @@ impl OtelManager {
             event.name = "codex.tool_result",
             event.timestamp = %timestamp(),
             conversation.id = %self.metadata.conversation_id,
+            correlation.id = self.metadata.correlation_id,
             app.version = %self.metadata.app_version,
             auth_mode = self.metadata.auth_mode,
             user.account_id = self.metadata.account_id,
@@ -37,6 +37,22 @@ impl From<Vec<String>> for ExecPolicyAmendment {
     }
 }
 
+/// Command pattern that stays approved for a project across sessions.
+///
+/// Rules are matched against the tokens of a single plain command; shell
+/// scripts that chain several commands never match.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum ProjectApprovalRule {
+    /// The command must equal these tokens.
+    Exact { command: Vec<String> },
+    /// The command must start with these tokens.
+    Prefix { prefix: Vec<String> },
+    /// The shell-escaped command must match this glob (`*` and `?`).
+    Glob { pattern: String },
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecApprovalRequestEvent {
     /// Identifier for the associated exec call, if available.
@@ -71,6 +71,145 @@ pub enum ForcedLoginMethod {
     Api,
 }
 
+/// Whether tool calls run or are only planned.
+#[derive(
+    Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ExecutionMode {
+    #[default]
+    Normal,
+    /// Every tool call is reported as a planned call and answered with a
+    /// synthetic "skipped" output instead of running.
+    DryRun,
+}
+
+/// How skills are offered to the model.
+#[derive(
+    Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum SkillsMode {
+    /// Skills are listed in the instructions and their `SKILL.md` is
+    /// injected when the user mentions one.
+    #[default]
+    Prompt,
+    /// Skills that declare parameters are offered as tools the model calls
+    /// with arguments; the others are still listed in the instructions.
+    Tools,
+}
+
+/// What a turn does when the provider rejects its prompt as longer than the
+/// model context window.
+#[derive(
+    Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ContextRecoveryStrategy {
+    /// The turn ends with an error.
+    #[default]
+    FailFast,
+    /// The history is compacted and the request retried once.
+    AutoCompact,
+    /// The oldest turns are dropped from the history and the request
+    /// retried once.
+    AutoTruncate,
+}
+
+/// Which tools a conversation may use. Denied tools are not advertised to
+/// the model, and calls to them are refused.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(default)]
+pub struct ToolPolicy {
+    /// Tool names to advertise; `None` allows every tool.
+    pub allow: Option<Vec<String>>,
+    /// Tool names never to advertise. Takes precedence over `allow`.
+    pub deny: Vec<String>,
+}
+
+impl ToolPolicy {
+    pub fn allows(&self, tool_name: &str) -> bool {
+        !self.deny.iter().any(|name| name == tool_name)
+            && self
+                .allow
+                .as_ref()
+                .is_none_or(|allow| allow.iter().any(|name| name == tool_name))
+    }
+}
+
+/// What a provider accepts in the tools of a request. The tools of a turn are
+/// adjusted to these limits before they are sent.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(default)]
+pub struct ToolCapabilities {
+    /// Whether the model may call several tools in one response. When not,
+    /// tool calls are run one at a time.
+    pub supports_parallel_tool_calls: bool,
+    /// Keywords accepted in tool parameter schemas; `None` accepts all of
+    /// them. Other keywords are removed from the schemas.
+    pub schema_keywords: Option<Vec<SchemaKeyword>>,
+    /// Most tools a request may carry; `None` for no limit. Tools past the
+    /// limit are left out, last in the roster first.
+    pub max_tools: Option<usize>,
+}
+
+impl Default for ToolCapabilities {
+    fn default() -> Self {
+        Self {
+            supports_parallel_tool_calls: true,
+            schema_keywords: None,
+            max_tools: None,
+        }
+    }
+}
+
+/// Optional JSON Schema keywords of the tool parameter schemas Codex sends.
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Display,
+    JsonSchema,
+    TS,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum SchemaKeyword {
+    Description,
+    Required,
+    AdditionalProperties,
+}
+
+/// Sampling parameters pinned for a conversation. Unset parameters keep the
+/// provider's default.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, JsonSchema, TS)]
+#[serde(default)]
+pub struct SamplingParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Only honored by providers using the Chat Completions API.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(type = "number | null")]
+    pub seed: Option<u64>,
+}
+
+impl SamplingParams {
+    pub fn is_unset(&self) -> bool {
+        self.temperature.is_none() && self.top_p.is_none() && self.seed.is_none()
+    }
+}
+
 /// Represents the trust level for a project directory.
 /// This determines the approval policy and sandbox mode applied.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS)]
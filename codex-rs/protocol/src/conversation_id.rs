@@ -28,6 +28,12 @@ impl ConversationId {
     }
 }
 
+impl From<Uuid> for ConversationId {
+    fn from(uuid: Uuid) -> Self {
+        Self { uuid }
+    }
+}
+
 impl Default for ConversationId {
     fn default() -> Self {
         Self::new()
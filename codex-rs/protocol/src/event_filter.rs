@@ -0,0 +1,247 @@
+//! Selecting the events a subscriber receives.
+//!
+//! Every [`EventMsg`] belongs to one [`EventCategory`]. An [`EventFilter`]
+//! names the categories to deliver and can drop streaming deltas, leaving
+//! the final message of each. Events that end a turn or the session, and
+//! requests waiting for an answer, are delivered whatever the filter.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::protocol::EventMsg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum EventCategory {
+    /// What the user reads: messages, errors, plans and the summary of each
+    /// tool call.
+    Content,
+    /// Reasoning summaries and raw reasoning.
+    Reasoning,
+    /// Tool calls as they run: begin events, output deltas and raw items.
+    ToolDetail,
+    /// Token counts, context usage, skill usage and heartbeats.
+    Metrics,
+    /// Session and turn state, approvals, checkpoints and settings changes.
+    Lifecycle,
+}
+
+impl EventCategory {
+    pub const ALL: [EventCategory; 5] = [
+        EventCategory::Content,
+        EventCategory::Reasoning,
+        EventCategory::ToolDetail,
+        EventCategory::Metrics,
+        EventCategory::Lifecycle,
+    ];
+
+    pub fn of(msg: &EventMsg) -> Self {
+        match msg {
+            EventMsg::Error(_)
+            | EventMsg::Warning(_)
+            | EventMsg::StreamError(_)
+            | EventMsg::AgentMessage(_)
+            | EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentMessageContentDelta(_)
+            | EventMsg::InterruptedTurnRecovered(_)
+            | EventMsg::UserMessage(_)
+            | EventMsg::ItemStarted(_)
+            | EventMsg::ItemCompleted(_)
+            | EventMsg::PlanUpdate(_)
+            | EventMsg::McpToolCallEnd(_)
+            | EventMsg::WebSearchEnd(_)
+            | EventMsg::ExecCommandEnd(_)
+            | EventMsg::ViewImageToolCall(_)
+            | EventMsg::PatchApplyEnd(_)
+            | EventMsg::SkillToolCall(_)
+            | EventMsg::TurnDiffSummary(_)
+            | EventMsg::StructuredOutput(_)
+            | EventMsg::DeprecationNotice(_)
+            | EventMsg::ModelDeprecated(_) => EventCategory::Content,
+            EventMsg::AgentReasoning(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContent(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::AgentReasoningSectionBreak(_)
+            | EventMsg::ReasoningContentDelta(_)
+            | EventMsg::ReasoningRawContentDelta(_) => EventCategory::Reasoning,
+            EventMsg::McpToolCallBegin(_)
+            | EventMsg::WebSearchBegin(_)
+            | EventMsg::PlannedToolCall(_)
+            | EventMsg::ExecCommandBegin(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+            | EventMsg::TerminalInteraction(_)
+            | EventMsg::PatchApplyBegin(_)
+            | EventMsg::TurnDiff(_)
+            | EventMsg::RawResponseItem(_)
+            | EventMsg::ToolTimedOut(_)
+            | EventMsg::ToolsDowngraded(_)
+            | EventMsg::McpStartupUpdate(_)
+            | EventMsg::McpStartupComplete(_) => EventCategory::ToolDetail,
+            EventMsg::TokenCount(_)
+            | EventMsg::ContextUsageUpdated(_)
+            | EventMsg::Heartbeat(_)
+            | EventMsg::SkillUsage(_) => EventCategory::Metrics,
+            EventMsg::AuthRefreshFailed(_)
+            | EventMsg::ContextCompacted(_)
+            | EventMsg::ContextRecovered(_)
+            | EventMsg::TaskStarted(_)
+            | EventMsg::TaskComplete(_)
+            | EventMsg::SessionConfigured(_)
+            | EventMsg::PendingInputsRestored(_)
+            | EventMsg::HistoryTrimmedOnResume(_)
+            | EventMsg::ModelListRefreshed(_)
+            | EventMsg::InstructionsReloaded(_)
+            | EventMsg::ExecApprovalRequest(_)
+            | EventMsg::ElicitationRequest(_)
+            | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::BackgroundEvent(_)
+            | EventMsg::UndoStarted(_)
+            | EventMsg::UndoCompleted(_)
+            | EventMsg::GetHistoryEntryResponse(_)
+            | EventMsg::McpListToolsResponse(_)
+            | EventMsg::ListCustomPromptsResponse(_)
+            | EventMsg::ListSkillsResponse(_)
+            | EventMsg::SkillsUpdateAvailable
+            | EventMsg::SkillsSelected(_)
+            | EventMsg::TurnAborted(_)
+            | EventMsg::ShutdownComplete
+            | EventMsg::SessionEnded(_)
+            | EventMsg::EnteredReviewMode(_)
+            | EventMsg::ExitedReviewMode(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::CheckpointRolledBack(_)
+            | EventMsg::ItemPinned(_)
+            | EventMsg::ItemUnpinned(_)
+            | EventMsg::ProviderEndpointSwitched(_)
+            | EventMsg::WorkspaceChanged(_)
+            | EventMsg::TurnRejected(_)
+            | EventMsg::InputRejected(_)
+            | EventMsg::ConversationLinked(_)
+            | EventMsg::Forked(_)
+            | EventMsg::ConversationPaused(_)
+            | EventMsg::ConversationResumed
+            | EventMsg::QueuedOffline(_)
+            | EventMsg::ToolPolicyUpdated(_)
+            | EventMsg::SafeModeChanged(_)
+            | EventMsg::Retrying(_)
+            | EventMsg::EffortEscalated(_)
+            | EventMsg::EffortDeescalated(_)
+            | EventMsg::PersistenceDegraded(_)
+            | EventMsg::PersistenceRecovered(_)
+            | EventMsg::ForkedContextStale(_)
+            | EventMsg::TurnDeadlineExceeded(_) => EventCategory::Lifecycle,
+        }
+    }
+}
+
+/// Which events a subscription delivers, see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+pub struct EventFilter {
+    /// Categories to deliver.
+    pub categories: Vec<EventCategory>,
+    /// Leave out streaming deltas; their content arrives in the final
+    /// message, reasoning item or command end event.
+    #[serde(default)]
+    pub collapse_deltas: bool,
+}
+
+impl Default for EventFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl EventFilter {
+    /// Every event.
+    pub fn all() -> Self {
+        Self::only(EventCategory::ALL)
+    }
+
+    /// The events of `categories`, and those no filter drops.
+    pub fn only(categories: impl IntoIterator<Item = EventCategory>) -> Self {
+        Self {
+            categories: categories.into_iter().collect(),
+            collapse_deltas: false,
+        }
+    }
+
+    /// `self`, leaving out streaming deltas.
+    pub fn with_collapsed_deltas(self) -> Self {
+        Self {
+            collapse_deltas: true,
+            ..self
+        }
+    }
+
+    pub fn accepts(&self, msg: &EventMsg) -> bool {
+        if is_undroppable(msg) {
+            return true;
+        }
+        if self.collapse_deltas && is_delta(msg) {
+            return false;
+        }
+        self.categories.contains(&EventCategory::of(msg))
+    }
+}
+
+/// Events that end a turn or the session, and requests the session waits on.
+fn is_undroppable(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::Error(_)
+            | EventMsg::TaskComplete(_)
+            | EventMsg::TurnAborted(_)
+            | EventMsg::TurnRejected(_)
+            | EventMsg::ShutdownComplete
+            | EventMsg::SessionEnded(_)
+            | EventMsg::ExecApprovalRequest(_)
+            | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::ElicitationRequest(_)
+    )
+}
+
+fn is_delta(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentMessageContentDelta(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::ReasoningContentDelta(_)
+            | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::AgentMessageDeltaEvent;
+    use crate::protocol::AgentMessageEvent;
+    use crate::protocol::TokenCountEvent;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn content_only_keeps_final_messages_and_terminal_events() {
+        let filter = EventFilter::only([EventCategory::Content]).with_collapsed_deltas();
+        let delta = EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+            delta: "par".to_string(),
+        });
+        let message = EventMsg::AgentMessage(AgentMessageEvent {
+            message: "partial".to_string(),
+        });
+        let tokens = EventMsg::TokenCount(TokenCountEvent {
+            info: None,
+            rate_limits: None,
+        });
+
+        assert_eq!(
+            [&delta, &message, &tokens, &EventMsg::ShutdownComplete].map(|msg| filter.accepts(msg)),
+            [false, true, false, true]
+        );
+        assert!(EventFilter::all().accepts(&delta));
+    }
+}
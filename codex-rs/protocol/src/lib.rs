@@ -4,6 +4,7 @@ pub use conversation_id::ConversationId;
 pub mod approvals;
 pub mod config_types;
 pub mod custom_prompts;
+pub mod event_filter;
 pub mod items;
 pub mod message_history;
 pub mod models;
@@ -12,4 +13,5 @@ pub mod openai_models;
 pub mod parse_command;
 pub mod plan_tool;
 pub mod protocol;
+pub mod schema;
 pub mod user_input;
@@ -3,7 +3,9 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
@@ -12,7 +14,14 @@ use std::time::Duration;
 
 use crate::ConversationId;
 use crate::approvals::ElicitationRequestEvent;
+use crate::config_types::ContextRecoveryStrategy;
+use crate::config_types::ExecutionMode;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::config_types::SamplingParams;
+use crate::config_types::SchemaKeyword;
+use crate::config_types::SkillsMode;
+use crate::config_types::ToolCapabilities;
+use crate::config_types::ToolPolicy;
 use crate::custom_prompts::CustomPrompt;
 use crate::items::TurnItem;
 use crate::message_history::HistoryEntry;
@@ -42,6 +51,8 @@ pub use crate::approvals::ApplyPatchApprovalRequestEvent;
 pub use crate::approvals::ElicitationAction;
 pub use crate::approvals::ExecApprovalRequestEvent;
 pub use crate::approvals::ExecPolicyAmendment;
+pub use crate::approvals::ProjectApprovalRule;
+pub use crate::schema::PROTOCOL_VERSION;
 
 /// Open/close tags for special user-input blocks. Used across crates to avoid
 /// duplicated hardcoded strings.
@@ -58,6 +69,11 @@ pub struct Submission {
     pub id: String,
     /// Payload
     pub op: Op,
+    /// Client-chosen id echoed on every event of the submission, including
+    /// those of the turn it starts, and stored with the turn's rollout lines.
+    /// Never sent to the model provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
 }
 
 /// Submission operation
@@ -74,6 +90,12 @@ pub enum Op {
     UserInput {
         /// User input items, see `InputItem`
         items: Vec<UserInput>,
+
+        /// Submit even if the input matches a configured sensitive input
+        /// rule. The matched text is still sent to the model but is redacted
+        /// wherever the conversation is persisted.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        allow_sensitive: bool,
     },
 
     /// Similar to [`Op::UserInput`], but contains additional context required
@@ -102,8 +124,27 @@ pub enum Op {
 
         /// Will only be honored if the model is configured to use reasoning.
         summary: ReasoningSummaryConfig,
-        // The JSON schema to use for the final assistant message
+        // The JSON schema to use for the final assistant message. Answers
+        // that do not match are sent back to the model for repair; a matching
+        // answer is reported in `TaskCompleteEvent::structured_output`.
         final_output_json_schema: Option<Value>,
+
+        /// Submit even if the input matches a configured sensitive input
+        /// rule. The matched text is still sent to the model but is redacted
+        /// wherever the conversation is persisted.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        allow_sensitive: bool,
+
+        /// Return every file read of this turn in full, even when the
+        /// `read_cache` would replace it with a reference to an identical
+        /// earlier read.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        bypass_read_cache: bool,
+
+        /// Wall-clock budget of the turn, covering the model's time and every
+        /// tool call. Overrides the configured `turn_deadline_sec`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deadline: Option<Duration>,
     },
 
     /// Override parts of the persistent turn context for subsequent turns.
@@ -139,6 +180,18 @@ pub enum Op {
         /// Updated reasoning summary preference (honored only for reasoning-capable models).
         #[serde(skip_serializing_if = "Option::is_none")]
         summary: Option<ReasoningSummaryConfig>,
+
+        /// Switch between running tool calls and only planning them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        execution_mode: Option<ExecutionMode>,
+
+        /// Replace the sampling parameters sent with each request.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sampling: Option<SamplingParams>,
+
+        /// Switch offline mode on or off, see [`Op::FlushQueued`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        offline: Option<bool>,
     },
 
     /// Approve a command execution
@@ -213,6 +266,10 @@ pub enum Op {
     /// Request to shut down codex instance.
     Shutdown,
 
+    /// Like [`Op::Shutdown`], with the `reason` reported in the final
+    /// [`EventMsg::SessionEnded`].
+    EndSession { reason: SessionEndReason },
+
     /// Execute a user-initiated one-off shell command (triggered by "!cmd").
     ///
     /// The command string is executed using the user's default shell and may
@@ -225,6 +282,136 @@ pub enum Op {
 
     /// Request the list of available models.
     ListModels,
+
+    /// Record a named checkpoint at the current end of the conversation
+    /// history. Reply is delivered via `EventMsg::CheckpointCreated`.
+    Checkpoint {
+        /// Caller-generated identifier for the checkpoint.
+        id: CheckpointId,
+        /// Human-readable label for the checkpoint.
+        label: String,
+    },
+
+    /// Drop every history item recorded after the given checkpoint. Reply is
+    /// delivered via `EventMsg::CheckpointRolledBack`, or `EventMsg::Error`
+    /// when the checkpoint is unknown or a task is running.
+    RollbackToCheckpoint { id: CheckpointId },
+
+    /// Protect a history item from compaction and token-budget truncation.
+    /// Reply is delivered via `EventMsg::ItemPinned`, or `EventMsg::Error`
+    /// when no item matches.
+    PinItem { item: ItemRef },
+
+    /// Remove the protection added by [`Op::PinItem`]. Reply is delivered via
+    /// `EventMsg::ItemUnpinned`, or `EventMsg::Error` when the item is not
+    /// pinned.
+    UnpinItem { item: ItemRef },
+
+    /// Record that this conversation is the parent or the child in `link`.
+    /// Reply is delivered via `EventMsg::ConversationLinked`.
+    LinkConversation { link: ConversationLink },
+
+    /// Add the final answer of a child conversation to the history, so the
+    /// model sees it on its next request.
+    IngestChildResult {
+        child_id: ConversationId,
+        message: String,
+    },
+
+    /// Replace the conversation's tool policy, starting with the next turn.
+    /// Reply is delivered via `EventMsg::ToolPolicyUpdated`.
+    UpdateToolPolicy { tool_policy: ToolPolicy },
+
+    /// Turn safe mode on or off, starting with the next turn. Turning it off
+    /// is first sent for approval as an `EventMsg::ExecApprovalRequest`.
+    /// Reply is delivered via `EventMsg::SafeModeChanged`.
+    SetSafeMode { enabled: bool },
+
+    /// Stop accepting turns, after the running turn finishes or is aborted
+    /// as `mode` says. Reply is delivered via `EventMsg::ConversationPaused`
+    /// once the pause is complete.
+    Pause { mode: PauseMode },
+
+    /// Accept turns again after [`Op::Pause`]. Reply is delivered via
+    /// `EventMsg::ConversationResumed`.
+    Resume,
+
+    /// Run the turns submitted while the conversation was offline, in the
+    /// order they were submitted. Reply is delivered via `EventMsg::Error`
+    /// when the conversation is still offline.
+    FlushQueued,
+}
+
+/// Identifier for a checkpoint recorded via [`Op::Checkpoint`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(transparent)]
+#[ts(type = "string")]
+pub struct CheckpointId(String);
+
+impl CheckpointId {
+    pub fn new() -> Self {
+        Self(uuid::Uuid::now_v7().to_string())
+    }
+}
+
+impl Default for CheckpointId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for CheckpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Identifier of a user turn. Turns are numbered in the order they start,
+/// and keep their id when the conversation is resumed or forked, whatever
+/// compaction or rollback did to the history since.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, TS,
+)]
+#[serde(transparent)]
+#[ts(type = "number")]
+pub struct TurnId(u64);
+
+impl TurnId {
+    pub fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// The id of the turn started after this one.
+    pub fn next(self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl fmt::Display for TurnId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A history item, as named by [`Op::PinItem`] and [`Op::UnpinItem`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemRef {
+    /// Item `offset` of turn `turn`, where offset 0 is the user message that
+    /// starts the turn. Turns are counted from 0 by user message in the
+    /// history the model sees, as fork ordinals are.
+    Turn { turn: usize, offset: usize },
+    /// The item with this id, as assigned by the model provider.
+    Id { id: String },
+}
+
+impl fmt::Display for ItemRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemRef::Turn { turn, offset } => write!(f, "item {offset} of turn {turn}"),
+            ItemRef::Id { id } => write!(f, "item {id}"),
+        }
+    }
 }
 
 /// Determines the conditions under which the user is consulted to approve
@@ -513,12 +700,15 @@ impl SandboxPolicy {
 }
 
 /// Event Queue Entry - events from agent
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Event {
     /// Submission `id` that this event is correlated with.
     pub id: String,
     /// Payload
     pub msg: EventMsg,
+    /// `correlation_id` of the submission this event belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
 }
 
 /// Response event from the agent
@@ -533,11 +723,20 @@ pub enum EventMsg {
 
     /// Warning issued while processing a submission. Unlike `Error`, this
     /// indicates the task continued but the user should still be notified.
+    /// Never recorded in the rollout.
     Warning(WarningEvent),
 
+    /// Refreshing the ChatGPT tokens ahead of their expiry failed. Requests
+    /// keep working until the access token expires.
+    AuthRefreshFailed(AuthRefreshFailedEvent),
+
     /// Conversation history was compacted (either automatically or manually).
     ContextCompacted(ContextCompactedEvent),
 
+    /// The provider rejected the prompt as longer than the context window,
+    /// and the history was shortened so the request can be retried.
+    ContextRecovered(ContextRecoveredEvent),
+
     /// Agent has started a task
     TaskStarted(TaskStartedEvent),
 
@@ -548,6 +747,10 @@ pub enum EventMsg {
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
 
+    /// Estimated context usage crossed one of the configured
+    /// `context_usage_thresholds`.
+    ContextUsageUpdated(ContextUsageUpdatedEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -574,6 +777,29 @@ pub enum EventMsg {
     /// Ack the client's configure message.
     SessionConfigured(SessionConfiguredEvent),
 
+    /// Inputs that were queued behind a turn when a resumed session last
+    /// stopped, and whether they are being replayed.
+    PendingInputsRestored(PendingInputsRestoredEvent),
+
+    /// A resumed session last stopped while an answer was streaming; `text`
+    /// is what had been recorded of it.
+    InterruptedTurnRecovered(InterruptedTurnRecoveredEvent),
+
+    /// The oldest turns of a resumed history did not fit the model's context
+    /// window and were left out of the prompt.
+    HistoryTrimmedOnResume(ResumeTrimReport),
+
+    /// The model list was fetched after the session started from a cached or
+    /// built-in list.
+    ModelListRefreshed(ModelListRefreshedEvent),
+
+    /// The model this conversation uses was removed from the model list.
+    ModelDeprecated(ModelDeprecatedEvent),
+
+    /// The instruction files changed and the instructions in the prompt were
+    /// replaced before the turn started.
+    InstructionsReloaded(InstructionsReloadedEvent),
+
     /// Incremental MCP startup progress updates.
     McpStartupUpdate(McpStartupUpdateEvent),
 
@@ -588,6 +814,10 @@ pub enum EventMsg {
 
     WebSearchEnd(WebSearchEndEvent),
 
+    /// A tool call the model made while the conversation is in dry-run
+    /// mode. The call was not executed.
+    PlannedToolCall(PlannedToolCallEvent),
+
     /// Notification that the server is about to execute a command.
     ExecCommandBegin(ExecCommandBeginEvent),
 
@@ -631,6 +861,9 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// Files changed by tools during a turn, emitted when the turn ends.
+    TurnDiffSummary(TurnDiffSummaryEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
@@ -646,6 +879,17 @@ pub enum EventMsg {
     /// Notification that skill data may have been updated and clients may want to reload.
     SkillsUpdateAvailable,
 
+    /// Skills offered to the model at the start of the session, and the ones
+    /// left out because they do not apply to the workspace.
+    SkillsSelected(SkillSelection),
+
+    /// The model called a skill offered as a tool.
+    SkillToolCall(SkillToolCallEvent),
+
+    /// Skills in context for a user turn and those it invoked. Sent and
+    /// recorded before the turn's `TaskComplete` or `TurnAborted`.
+    SkillUsage(SkillUsageEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -653,6 +897,11 @@ pub enum EventMsg {
     /// Notification that the agent is shutting down.
     ShutdownComplete,
 
+    /// The last event of a session, sent after [`EventMsg::ShutdownComplete`]
+    /// and also written to the rollout, which lacks it when the session did
+    /// not end cleanly.
+    SessionEnded(SessionEndedEvent),
+
     /// Entered review mode.
     EnteredReviewMode(ReviewRequest),
 
@@ -667,6 +916,105 @@ pub enum EventMsg {
     AgentMessageContentDelta(AgentMessageContentDeltaEvent),
     ReasoningContentDelta(ReasoningContentDeltaEvent),
     ReasoningRawContentDelta(ReasoningRawContentDeltaEvent),
+
+    /// A named checkpoint was recorded in response to `Op::Checkpoint`.
+    CheckpointCreated(CheckpointCreatedEvent),
+
+    /// The conversation history was rolled back to a checkpoint.
+    CheckpointRolledBack(CheckpointRolledBackEvent),
+
+    /// A history item was pinned in response to `Op::PinItem`.
+    ItemPinned(ItemPinnedEvent),
+
+    /// A history item was unpinned in response to `Op::UnpinItem`.
+    ItemUnpinned(ItemUnpinnedEvent),
+
+    /// Requests to the model provider are now served by a different base URL
+    /// (failover away from an unhealthy endpoint, or recovery back to it).
+    ProviderEndpointSwitched(ProviderEndpointSwitchedEvent),
+
+    /// A failed request is about to be retried after a backoff delay.
+    Retrying(RetryingEvent),
+
+    /// The workspace changed outside of codex since the previous turn (cwd,
+    /// git branch, HEAD, or uncommitted files). A matching notice was added
+    /// to the transcript.
+    WorkspaceChanged(WorkspaceChangedEvent),
+
+    /// Keepalive emitted periodically while a turn is working but has not
+    /// produced output for a while. Never recorded in the rollout.
+    Heartbeat(HeartbeatEvent),
+
+    /// A turn post-processor rejected the model's output. None of the turn's
+    /// items were recorded and its tool calls did not run.
+    TurnRejected(TurnRejectedEvent),
+
+    /// A submitted user input matched a sensitive input rule or was rejected
+    /// by a turn pre-processor, and was discarded. Nothing from it was sent to
+    /// the model or recorded.
+    InputRejected(InputRejectedEvent),
+
+    /// This conversation was linked to a parent or child conversation.
+    ConversationLinked(ConversationLink),
+
+    /// This conversation was forked from another one.
+    Forked(ForkedEvent),
+
+    /// Files the history of this fork read have changed or disappeared in
+    /// its workspace since. Sent right after `Forked`.
+    ForkedContextStale(ForkConsistencyReport),
+
+    /// The rollout cannot be written for lack of disk space or quota. What
+    /// the session records is held in memory, within a bound, until the
+    /// disk takes it again.
+    PersistenceDegraded(PersistenceDegradedEvent),
+
+    /// The rollout is written again after [`EventMsg::PersistenceDegraded`],
+    /// starting with what was held in memory.
+    PersistenceRecovered(PersistenceRecoveredEvent),
+
+    /// The conversation stopped accepting turns, see [`Op::Pause`].
+    ConversationPaused(ConversationPausedEvent),
+
+    /// The conversation accepts turns again after a pause.
+    ConversationResumed,
+
+    /// A turn was submitted while the conversation is offline. Its input is
+    /// queued until [`Op::FlushQueued`].
+    QueuedOffline(QueuedOfflineEvent),
+
+    /// The conversation's tool policy changed. Recorded so a resumed
+    /// conversation keeps it.
+    ToolPolicyUpdated(ToolPolicy),
+
+    /// Safe mode was turned on or off. Recorded so a resumed conversation
+    /// stays in safe mode.
+    SafeModeChanged(SafeModeChangedEvent),
+
+    /// The tools of a turn were adjusted to what the provider accepts. Sent
+    /// when the adjustments differ from those last reported.
+    ToolsDowngraded(ToolDowngradeReport),
+
+    /// The final answer of a turn matched its `final_output_json_schema`.
+    /// Sent right before `TaskComplete`, which carries the same value.
+    StructuredOutput(StructuredOutputEvent),
+
+    /// A tool call ran past its configured timeout and was stopped. The model
+    /// was sent a timeout output with whatever the tool had produced.
+    ToolTimedOut(ToolTimedOutEvent),
+
+    /// The turn ran out of its wall-clock budget. Whatever was running was
+    /// stopped, pending tool calls got an aborted output, and the turn
+    /// completes without asking the model again.
+    TurnDeadlineExceeded(TurnDeadlineExceededEvent),
+
+    /// The adaptive effort policy raised the reasoning effort of the turns
+    /// that follow, after the previous turns showed signs of floundering.
+    EffortEscalated(EffortEscalatedEvent),
+
+    /// The adaptive effort policy lowered the reasoning effort again after
+    /// enough clean turns.
+    EffortDeescalated(EffortDeescalatedEvent),
 }
 
 /// Codex errors that we expose to clients.
@@ -695,6 +1043,9 @@ pub enum CodexErrorInfo {
     ResponseTooManyFailedAttempts {
         http_status_code: Option<u16>,
     },
+    /// The final answer still did not match the turn's output schema after
+    /// every repair attempt.
+    StructuredOutputInvalid,
     Other,
 }
 
@@ -828,20 +1179,187 @@ pub struct ErrorEvent {
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct WarningEvent {
+    /// What the warning is about. Codes are stable, so clients can filter or
+    /// suppress them.
+    #[serde(default)]
+    pub code: WarningCode,
+    #[serde(default)]
+    pub severity: WarningSeverity,
+    pub message: String,
+    /// Facts behind `message`, such as sizes or line numbers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub details: Option<Value>,
+    /// Whether the warning concerns the whole conversation rather than the
+    /// submission or turn that raised it.
+    #[serde(default)]
+    pub conversation_scope: bool,
+}
+
+impl WarningEvent {
+    /// A warning of `code` at its severity, about the operation raising it.
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: code.severity(),
+            message: message.into(),
+            details: None,
+            conversation_scope: false,
+        }
+    }
+
+    pub fn with_details(self, details: Value) -> Self {
+        Self {
+            details: Some(details),
+            ..self
+        }
+    }
+
+    /// `self`, about the whole conversation.
+    pub fn for_conversation(self) -> Self {
+        Self {
+            conversation_scope: true,
+            ..self
+        }
+    }
+}
+
+/// Stable identifier of a [`WarningEvent`].
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema, TS,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// The resumed rollout was recorded with another model.
+    ModelChangedOnResume,
+    /// The resumed rollout was anonymized.
+    AnonymizedSession,
+    /// Lines of the resumed rollout could not be read and were skipped.
+    RolloutLinesSkipped,
+    /// The rollout writer failed; nothing after it is recorded.
+    RecordingStopped,
+    /// The project docs exceed `project_doc_max_bytes` and were truncated.
+    InstructionsTruncated,
+    /// The model list could not be fetched; a cached or built-in one is used.
+    ModelListFallback,
+    /// A submission matched sensitive input rules and is redacted on disk.
+    SensitiveInputRedacted,
+    ProjectApprovalNotSaved,
+    ExecpolicyAmendmentNotSaved,
+    /// The provider accepts fewer tools than are available.
+    ToolsLeftOut,
+    /// A configured sampling parameter is ignored by the model.
+    SamplingParameterIgnored,
+    SafeModeUnchanged,
+    /// A skill mentioned by the user could not be injected.
+    SkillNotInjected,
+    SnapshotSlow,
+    /// The snapshot left out large untracked files or directories.
+    SnapshotFilesSkipped,
+    /// The conversation was compacted; accuracy may degrade.
+    LongConversation,
+    /// Warnings recorded before codes existed.
+    #[default]
+    Other,
+}
+
+impl WarningCode {
+    /// Severity of warnings of this code.
+    pub fn severity(self) -> WarningSeverity {
+        match self {
+            WarningCode::SensitiveInputRedacted
+            | WarningCode::SafeModeUnchanged
+            | WarningCode::LongConversation => WarningSeverity::Info,
+            _ => WarningSeverity::Warning,
+        }
+    }
+}
+
+/// How much a [`WarningEvent`] matters, lowest first. See
+/// `Config::min_warning_severity`.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+    TS,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningSeverity {
+    /// Worth knowing, nothing to act on.
+    Info,
+    #[default]
+    Warning,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct AuthRefreshFailedEvent {
     pub message: String,
+    /// Consecutive failed attempts, this one included.
+    pub attempt: u32,
+    /// Delay before the next attempt. `None` when the refresh token was
+    /// rejected and the user has to sign in again.
+    pub retry_in_ms: Option<u64>,
+    /// Unix timestamp, in seconds, at which the access token expires.
+    pub expires_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ContextCompactedEvent;
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextRecoveredEvent {
+    pub strategy: ContextRecoveryStrategy,
+    /// Turns dropped from the history; zero once it was compacted instead.
+    pub dropped_turns: usize,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskCompleteEvent {
     pub last_agent_message: Option<String>,
+    /// `last_agent_message` parsed as JSON, when the turn was submitted with
+    /// `final_output_json_schema` and the answer matched it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub structured_output: Option<Value>,
+    /// The last assistant message the turn recorded, after post-processing.
+    /// `None` when the turn ended with `error`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub final_message: Option<String>,
+    /// Assistant messages the turn recorded, counted as its transcript
+    /// turn counts them.
+    #[serde(default)]
+    pub message_count: u32,
+    /// Tool calls the turn recorded, counted as its transcript turn counts
+    /// them.
+    #[serde(default)]
+    pub tool_call_count: u32,
+    /// Why the turn ended without a final message: the error it reported,
+    /// its rejection, or its deadline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+    /// Skills the turn explicitly invoked, see [`SkillUsageEvent::invoked`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invoked_skills: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskStartedEvent {
     pub model_context_window: Option<i64>,
+    /// Id of the user turn the task runs, see [`TurnId`]. `None` for tasks
+    /// that are not user turns, such as compaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_id: Option<TurnId>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default, JsonSchema, TS)]
@@ -858,6 +1376,40 @@ pub struct TokenUsage {
     pub total_tokens: i64,
 }
 
+/// Estimated share of the model's context window a conversation uses,
+/// available before the provider reports actual usage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextUsage {
+    /// Estimated tokens of the next request, instructions included.
+    pub used_tokens_estimate: i64,
+    /// Usable context window of the current model, if known.
+    #[ts(type = "number | null")]
+    pub window: Option<i64>,
+    /// `used_tokens_estimate` as a percentage of `window`, capped at 100.
+    pub percent: Option<u8>,
+}
+
+impl ContextUsage {
+    pub fn new(used_tokens_estimate: i64, window: Option<i64>) -> Self {
+        let percent = window.filter(|window| *window > 0).map(|window| {
+            let percent = used_tokens_estimate.max(0).saturating_mul(100) / window;
+            percent.min(100) as u8
+        });
+        Self {
+            used_tokens_estimate,
+            window,
+            percent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextUsageUpdatedEvent {
+    pub usage: ContextUsage,
+    /// The threshold (in percent) that was crossed.
+    pub threshold: u8,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TokenUsageInfo {
     pub total_token_usage: TokenUsage,
@@ -1160,12 +1712,43 @@ pub struct ResumedHistory {
     pub conversation_id: ConversationId,
     pub history: Vec<RolloutItem>,
     pub rollout_path: PathBuf,
+    /// Lines of the rollout that could not be parsed and were skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_errors: Vec<RolloutParseError>,
+}
+
+/// A line of a rollout file that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct RolloutParseError {
+    /// 1-based line in the file. For an item written in parts this is the
+    /// line of its first part.
+    pub line: usize,
+    /// Start of the offending line.
+    pub snippet: String,
+    /// Why the line could not be parsed.
+    pub error: String,
 }
 
+impl fmt::Display for RolloutParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} (in {:?})",
+            self.line, self.error, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for RolloutParseError {}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub enum InitialHistory {
     New,
     Resumed(ResumedHistory),
+    /// A rollout holding nothing but its session meta, left by a session that
+    /// ended before its first turn. It resumes as a new session that keeps
+    /// the recorded conversation id and session meta.
+    ResumedEmpty(ResumedHistory),
     Forked(Vec<RolloutItem>),
 }
 
@@ -1173,14 +1756,104 @@ impl InitialHistory {
     pub fn get_rollout_items(&self) -> Vec<RolloutItem> {
         match self {
             InitialHistory::New => Vec::new(),
-            InitialHistory::Resumed(resumed) => resumed.history.clone(),
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                resumed.history.clone()
+            }
             InitialHistory::Forked(items) => items.clone(),
         }
     }
 
+    /// Session profile recorded in the first session meta of the history.
+    pub fn session_profile(&self) -> Option<String> {
+        let items = match self {
+            InitialHistory::New => return None,
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                &resumed.history
+            }
+            InitialHistory::Forked(items) => items,
+        };
+        items.iter().find_map(|item| match item {
+            RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.session_profile.clone()),
+            _ => None,
+        })?
+    }
+
+    /// Inputs queued in a resumed history that were never consumed, in the
+    /// order they were submitted.
+    pub fn pending_inputs(&self) -> Vec<PendingInput> {
+        let InitialHistory::Resumed(resumed) = self else {
+            return Vec::new();
+        };
+        let consumed: HashSet<&str> = resumed
+            .history
+            .iter()
+            .filter_map(|item| match item {
+                RolloutItem::PendingInput(PendingInputItem::Consumed { id }) => Some(id.as_str()),
+                _ => None,
+            })
+            .collect();
+        resumed
+            .history
+            .iter()
+            .filter_map(|item| match item {
+                RolloutItem::PendingInput(PendingInputItem::Queued(input))
+                    if !consumed.contains(input.id.as_str()) =>
+                {
+                    Some(input.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The answer a resumed session was streaming when it last stopped, from
+    /// the [`RolloutItem::PartialMessage`] nothing superseded.
+    pub fn interrupted_turn(&self) -> Option<InterruptedTurnRecoveredEvent> {
+        let InitialHistory::Resumed(resumed) = self else {
+            return None;
+        };
+        let last = resumed
+            .history
+            .iter()
+            .rposition(RolloutItem::supersedes_partial_message)?;
+        match &resumed.history[last] {
+            RolloutItem::PartialMessage { turn_id, text, .. } => {
+                Some(InterruptedTurnRecoveredEvent {
+                    turn_id: *turn_id,
+                    text: text.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of items written by a newer version, see [`RolloutItem::Unknown`].
+    pub fn unknown_item_count(&self) -> usize {
+        let items = match self {
+            InitialHistory::New | InitialHistory::ResumedEmpty(_) => return 0,
+            InitialHistory::Resumed(resumed) => &resumed.history,
+            InitialHistory::Forked(items) => items,
+        };
+        items
+            .iter()
+            .filter(|item| matches!(item, RolloutItem::Unknown { .. }))
+            .count()
+    }
+
+    /// Rollout lines skipped because they could not be parsed.
+    pub fn parse_errors(&self) -> &[RolloutParseError] {
+        match self {
+            InitialHistory::Resumed(resumed) | InitialHistory::ResumedEmpty(resumed) => {
+                &resumed.parse_errors
+            }
+            InitialHistory::New | InitialHistory::Forked(_) => &[],
+        }
+    }
+
     pub fn get_event_msgs(&self) -> Option<Vec<EventMsg>> {
         match self {
             InitialHistory::New => None,
+            InitialHistory::ResumedEmpty(_) => Some(Vec::new()),
             InitialHistory::Resumed(resumed) => Some(
                 resumed
                     .history
@@ -1261,6 +1934,40 @@ pub struct SessionMeta {
     #[serde(default)]
     pub source: SessionSource,
     pub model_provider: Option<String>,
+    /// Session profile the conversation was started with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_profile: Option<String>,
+    /// Skills selected for the conversation's workspace when it started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skills: Option<SkillSelection>,
+    /// Where the model list the session started with came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_list_source: Option<ModelListSource>,
+    /// Tool limits of the provider the session started with, which the
+    /// tools of its turns were adjusted to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_capabilities: Option<ToolCapabilities>,
+    /// Ids of the project notes the session prefix carried when it started,
+    /// `None` when project notes were disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_notes: Option<Vec<String>>,
+    /// The conversation this one was forked from, `None` when it was not
+    /// forked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<ParentRef>,
+}
+
+/// Source of the model list a session was started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ModelListSource {
+    /// Fetched from the provider when the session started.
+    Network,
+    /// Read from the list cached by an earlier fetch.
+    Cache,
+    /// The list built into Codex.
+    Builtin,
 }
 
 impl Default for SessionMeta {
@@ -1274,6 +1981,12 @@ impl Default for SessionMeta {
             instructions: None,
             source: SessionSource::default(),
             model_provider: None,
+            session_profile: None,
+            skills: None,
+            model_list_source: None,
+            tool_capabilities: None,
+            project_notes: None,
+            parent: None,
         }
     }
 }
@@ -1294,6 +2007,274 @@ pub enum RolloutItem {
     Compacted(CompactedItem),
     TurnContext(TurnContextItem),
     EventMsg(EventMsg),
+    Checkpoint(CheckpointItem),
+    CheckpointRollback(CheckpointRollbackItem),
+    PendingInput(PendingInputItem),
+    InstructionsReplaced(InstructionsReplacedItem),
+    ReadReference(ReadReferenceItem),
+    InputRejected(InputRejectedItem),
+    ItemPinned(PinnedItem),
+    ItemUnpinned(PinnedItem),
+    /// Journal entry of a change to the history, recorded after the items the
+    /// change added.
+    TranscriptMutation(TranscriptMutation),
+    /// Start of a user turn: the items up to the next one belong to it.
+    TurnStarted(TurnStartedItem),
+    /// Git context of the workspace, recorded at the start of a turn when
+    /// HEAD moved since the session meta or the previous one.
+    GitContext(GitInfo),
+    /// Fingerprint of a request sent to the model, recorded after the items
+    /// it was built from.
+    RequestFingerprint(RequestFingerprint),
+    /// Note attached to the conversation by a client; it never reaches the
+    /// model.
+    Annotation(RolloutAnnotation),
+    /// Assistant text of turn `turn_id` recorded while it was streaming, the
+    /// whole text of its item up to a sentence boundary. Provisional: a later
+    /// partial message or any item that [supersedes] it replaces it, and
+    /// readers drop it then.
+    ///
+    /// [supersedes]: RolloutItem::supersedes_partial_message
+    PartialMessage {
+        turn_id: TurnId,
+        seq: u64,
+        text: String,
+    },
+    /// Last line of a rollout part: the rollout continues in `file`.
+    ContinuedIn(RolloutContinuation),
+    /// First line of a rollout part: the rollout started in `file`.
+    ContinuedFrom(RolloutContinuation),
+    /// Part `part` (zero-based) of `of` of an item whose line was too large to
+    /// write in one piece. `payload` is a slice of the original line; readers
+    /// join the parts sharing `id` and parse the result in its place.
+    Chunked {
+        id: String,
+        part: u32,
+        of: u32,
+        payload: String,
+    },
+    /// Item written by a newer version that this build cannot parse. Kept
+    /// verbatim (`type`, `payload` and any other fields) so that appending to
+    /// the rollout does not lose it; it never reaches the model.
+    #[serde(skip)]
+    #[ts(skip)]
+    Unknown {
+        raw: Value,
+    },
+}
+
+impl RolloutItem {
+    /// Whether this item, recorded after a [`RolloutItem::PartialMessage`],
+    /// replaces it: a newer partial message, the completed item, a later
+    /// turn, or the end of its own.
+    pub fn supersedes_partial_message(&self) -> bool {
+        matches!(
+            self,
+            RolloutItem::ResponseItem(_)
+                | RolloutItem::Compacted(_)
+                | RolloutItem::TurnStarted(_)
+                | RolloutItem::PartialMessage { .. }
+                | RolloutItem::EventMsg(
+                    EventMsg::TaskComplete(_)
+                        | EventMsg::TurnAborted(_)
+                        | EventMsg::InterruptedTurnRecovered(_)
+                )
+        )
+    }
+}
+
+/// Link between consecutive parts of a rollout too large for one file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct RolloutContinuation {
+    /// File name of the other part, in the same directory.
+    pub file: String,
+}
+
+/// Marker recorded when a named checkpoint is taken. The checkpoint refers to
+/// the end of the history reconstructed from the items that precede it.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct CheckpointItem {
+    pub id: CheckpointId,
+    pub label: String,
+}
+
+/// Marker recorded when the history is rolled back to a checkpoint.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct CheckpointRollbackItem {
+    pub id: CheckpointId,
+}
+
+/// Marker recorded before the first item of a user turn.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct TurnStartedItem {
+    pub turn_id: TurnId,
+}
+
+/// What one request to the model carried, recorded after it was sent so
+/// that the request can be rebuilt from the rollout.
+///
+/// Segments of the request are named by the first 16 hex digits of the
+/// sha256 of their JSON encoding.
+/// The instructions and the tool roster are kept in `segments` the first
+/// time the rollout sends them; the input is rebuilt from the history
+/// recorded before the fingerprint and checked item by item against `input`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+pub struct RequestFingerprint {
+    pub model: String,
+    /// Hash of the instructions.
+    pub instructions: String,
+    /// Hash of the tool roster.
+    pub tools: String,
+    /// Hash of each input item, in order.
+    pub input: Vec<String>,
+    /// The request body with `instructions`, `tools` and `input` set to
+    /// `null`: parameters such as reasoning, sampling and output schema,
+    /// verbatim and in the order they were sent.
+    pub parameters: Value,
+    /// Contents of the segments not kept by an earlier fingerprint of the
+    /// rollout, by hash.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub segments: BTreeMap<String, Value>,
+}
+
+/// Note recorded in a rollout on behalf of a client rather than by the
+/// session's turns.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RolloutAnnotation {
+    /// Summary of the conversation written for a human taking it over.
+    Handoff(HandoffDocument),
+}
+
+/// Summary of a conversation for whoever it is escalated to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+pub struct HandoffDocument {
+    /// What the user set out to do.
+    pub goal: String,
+    pub actions_taken: Vec<String>,
+    /// Files the turns changed, from their diff summaries, with the line
+    /// counts of every turn added up.
+    pub files_changed: Vec<TurnFileChange>,
+    /// What went wrong or was left unresolved.
+    pub failures: Vec<String>,
+    pub suggested_next_steps: Vec<String>,
+    /// The sections above rendered as Markdown.
+    pub markdown: String,
+    /// Model that wrote the summary.
+    pub model: String,
+}
+
+/// Marker recorded when the instructions in the prompt are replaced by ones
+/// re-read from their files.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct InstructionsReplacedItem {
+    /// Instruction files the new instructions were read from.
+    pub sources: Vec<PathBuf>,
+    /// The item that replaces the instructions in the history, or `None` when
+    /// there are no instructions anymore.
+    pub instructions: Option<ResponseItem>,
+}
+
+/// Marker recorded when the output of a file read was replaced by a reference
+/// to an identical earlier read still in the history.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct ReadReferenceItem {
+    /// Call whose output is the reference.
+    pub call_id: String,
+    /// Call whose output holds the content.
+    pub original_call_id: String,
+    /// SHA-256 of the content, hex encoded.
+    pub hash: String,
+}
+
+/// Audit marker recorded when a turn pre-processor rejected a user input.
+/// The input itself is not recorded.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct InputRejectedItem {
+    pub reason: String,
+}
+
+/// Marker recorded when a history item is pinned or unpinned. The item is
+/// recorded as it was, so the pin survives changes in item positions.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+pub struct PinnedItem {
+    pub item: ResponseItem,
+}
+
+/// One change to the conversation history, as journaled for audit. The
+/// change kept the first `added.start` items of the history and replaced the
+/// rest, `removed`, with `added`; an append removes nothing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+pub struct TranscriptMutation {
+    /// Position in the conversation's journal, counted from 1.
+    pub seq: u64,
+    pub kind: TranscriptMutationKind,
+    /// Items of the history before the change that it dropped.
+    pub removed: ItemRange,
+    /// Items of the history after the change that it added.
+    pub added: ItemRange,
+    /// SHA-256 of the history after the change, hex encoded. Each item is
+    /// hashed as its JSON serialization followed by a newline.
+    pub transcript_hash: String,
+}
+
+/// Half-open range of history positions.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema, TS)]
+pub struct ItemRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ItemRange {
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// What changed the history in a [`TranscriptMutation`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum TranscriptMutationKind {
+    /// Items recorded at the end of the history.
+    Append,
+    /// A compaction replaced the history with a summary.
+    Compaction,
+    /// The oldest turns were dropped to retry a prompt the provider found
+    /// too long.
+    ContextTruncation { dropped_turns: usize },
+    /// The history was cut back to a checkpoint.
+    CheckpointRollback { checkpoint: CheckpointId },
+    /// The instructions were re-read from their files.
+    InstructionsReplaced,
+    /// An undo removed the ghost snapshot of `commit_id`.
+    Undo { commit_id: String },
+    /// Resuming normalized the history rebuilt from the rollout.
+    Resumed,
+}
+
+/// User input submitted while a turn was running, waiting for the turn to
+/// pick it up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
+pub struct PendingInput {
+    /// Unique within the rollout.
+    pub id: String,
+    pub items: Vec<UserInput>,
+}
+
+/// Lifecycle of a [`PendingInput`] in the rollout. Every queued input is
+/// followed by a `consumed` marker once it reaches the history or its turn
+/// ends, so a rollout that stops in between still holds the input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[ts(tag = "status")]
+pub enum PendingInputItem {
+    Queued(PendingInput),
+    Consumed { id: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
@@ -1334,6 +2315,14 @@ pub struct TurnContextItem {
     pub final_output_json_schema: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub truncation_policy: Option<TruncationPolicy>,
+    /// Sampling parameters sent with the turn's requests, when any are set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingParams>,
+    /// Scratch directory exported to the turn's commands as
+    /// `CODEX_TURN_TMPDIR`. Removed when the turn ends unless
+    /// `keep_turn_tmpdirs` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_tmpdir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
@@ -1343,14 +2332,100 @@ pub enum TruncationPolicy {
     Tokens(usize),
 }
 
-#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+/// One line of a rollout file. Lines whose item this build does not
+/// understand deserialize to [`RolloutItem::Unknown`] instead of failing.
+#[derive(Clone, JsonSchema)]
 pub struct RolloutLine {
     pub timestamp: String,
+    /// `correlation_id` of the submission whose turn wrote the line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
     #[serde(flatten)]
     pub item: RolloutItem,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[derive(Serialize)]
+struct RolloutLineRef<'a> {
+    timestamp: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<&'a str>,
+    #[serde(flatten)]
+    item: &'a RolloutItem,
+}
+
+impl Serialize for RolloutLine {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        let RolloutItem::Unknown { raw } = &self.item else {
+            return RolloutLineRef {
+                timestamp: &self.timestamp,
+                correlation_id: self.correlation_id.as_deref(),
+                item: &self.item,
+            }
+            .serialize(serializer);
+        };
+        let fields = raw.as_object();
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("timestamp", &self.timestamp)?;
+        if let Some(correlation_id) = &self.correlation_id {
+            map.serialize_entry("correlation_id", correlation_id)?;
+        }
+        for (key, value) in fields.into_iter().flatten() {
+            if key != "timestamp" && key != "correlation_id" {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RolloutLine {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let mut fields = serde_json::Map::<String, Value>::deserialize(deserializer)?;
+        let timestamp = match fields.remove("timestamp") {
+            Some(Value::String(timestamp)) => timestamp,
+            Some(other) => {
+                return Err(D::Error::custom(format!(
+                    "invalid rollout timestamp: {other}"
+                )));
+            }
+            None => return Err(D::Error::missing_field("timestamp")),
+        };
+        let correlation_id = match fields.remove("correlation_id") {
+            Some(Value::String(correlation_id)) => Some(correlation_id),
+            Some(Value::Null) | None => None,
+            Some(other) => {
+                return Err(D::Error::custom(format!(
+                    "invalid rollout correlation id: {other}"
+                )));
+            }
+        };
+        let raw = Value::Object(fields);
+        let item = match RolloutItem::deserialize(&raw) {
+            Ok(item) => item,
+            // A tagged item we cannot parse comes from a newer version: an
+            // unknown item type or an unknown variant inside a known one.
+            Err(_) if raw.get("type").is_some_and(Value::is_string) => RolloutItem::Unknown { raw },
+            Err(err) => return Err(D::Error::custom(err)),
+        };
+        Ok(Self {
+            timestamp,
+            correlation_id,
+            item,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema, TS)]
 pub struct GitInfo {
     /// Current commit hash (SHA)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1358,9 +2433,15 @@ pub struct GitInfo {
     /// Current branch name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch: Option<String>,
-    /// Repository URL (if available from remote)
+    /// Repository URL (if available from remote), without credentials
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repository_url: Option<String>,
+    /// Root of the working tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+    /// Whether tracked files had uncommitted changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<bool>,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
@@ -1461,6 +2542,16 @@ pub enum ExecCommandSource {
     UnifiedExecInteraction,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PlannedToolCallEvent {
+    pub call_id: String,
+    /// Tool name as the model called it, e.g. `shell_command` or an MCP
+    /// tool's qualified name.
+    pub name: String,
+    /// Raw arguments or input the model passed to the tool.
+    pub arguments: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandBeginEvent {
     /// Identifier so this can be paired with the ExecCommandEnd event.
@@ -1522,6 +2613,21 @@ pub struct ExecCommandEndEvent {
     pub duration: Duration,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// The full aggregated output when it was larger than
+    /// `max_tool_output_bytes`. The output fields above then only hold its
+    /// head and tail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub output_blob: Option<BlobRef>,
+}
+
+/// Content kept in the blob store of the Codex home, addressed by its
+/// SHA-256.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct BlobRef {
+    /// Lowercase hex SHA-256 of the content.
+    pub sha256: String,
+    pub size_bytes: u64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1584,6 +2690,273 @@ pub struct UndoStartedEvent {
     pub message: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProviderEndpointSwitchedEvent {
+    /// Name of the model provider.
+    pub provider: String,
+    /// Base URL that previously served requests.
+    pub from: String,
+    /// Base URL now serving requests.
+    pub to: String,
+}
+
+/// The requests a retry policy applies to.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display, JsonSchema, TS,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RetrySubsystem {
+    /// Streaming a turn from the model provider.
+    ModelRequests,
+    /// Fetching the model list.
+    PresetFetch,
+    /// Refreshing ChatGPT tokens after a request was rejected as
+    /// unauthorized.
+    AuthRefresh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct RetryingEvent {
+    pub subsystem: RetrySubsystem,
+    /// Number of this retry, from 1.
+    pub attempt: u64,
+    /// Time waited before the retry is made.
+    pub delay_ms: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceChangedEvent {
+    /// Human-readable summary, e.g. `workspace changed: branch main → dev, 12
+    /// files modified`.
+    pub message: String,
+    pub previous_cwd: PathBuf,
+    pub cwd: PathBuf,
+    pub previous_branch: Option<String>,
+    pub branch: Option<String>,
+    pub previous_head: Option<String>,
+    pub head: Option<String>,
+    /// Files changed since the previous turn, relative to the repository root.
+    pub changed_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct HeartbeatEvent {
+    pub phase: HeartbeatPhase,
+    /// Time since the turn last produced output, or since the phase began.
+    pub elapsed_ms: u64,
+}
+
+/// What a turn is waiting on when it emits a [`HeartbeatEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum HeartbeatPhase {
+    WaitingForModel,
+    ExecutingTool { call_id: String },
+}
+
+/// Parent/child relationship between two conversations, recorded in the
+/// rollouts of both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ConversationLink {
+    pub parent_id: ConversationId,
+    pub child_id: ConversationId,
+}
+
+/// Where a forked conversation was cut from its parent.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ParentRef {
+    pub conversation_id: ConversationId,
+    /// The parent's rollout when the fork was taken.
+    pub rollout_path: PathBuf,
+    /// The user message the fork was cut before, as passed to
+    /// `fork_conversation`: forking the parent at it again keeps the same
+    /// history.
+    pub cut_ordinal: usize,
+    /// What the fork's history read that no longer matched the workspace
+    /// when the fork was taken, if anything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub stale_context: Option<ForkConsistencyReport>,
+}
+
+/// A conversation was forked from another one. Sent by the fork right after
+/// `SessionConfigured`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ForkedEvent {
+    pub parent_id: ConversationId,
+    pub child_id: ConversationId,
+    /// See [`ParentRef::cut_ordinal`].
+    pub cut_ordinal: usize,
+}
+
+/// Files read by a forked history, checked again in the fork's workspace.
+/// Paths are where the fork finds them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ForkConsistencyReport {
+    /// Files that read differently than the history recorded.
+    pub stale_files: Vec<PathBuf>,
+    /// Files that no longer exist.
+    pub missing_files: Vec<PathBuf>,
+}
+
+impl ForkConsistencyReport {
+    pub fn has_diverged(&self) -> bool {
+        !self.stale_files.is_empty() || !self.missing_files.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PersistenceDegradedEvent {
+    /// The error the rollout write failed with.
+    pub reason: String,
+    /// How many items of this session had reached the rollout, or `None`
+    /// when none had; the items after it are held in memory.
+    pub last_persisted_item: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PersistenceRecoveredEvent {
+    /// Items held in memory and now written to the rollout.
+    pub flushed_items: u64,
+    /// Events dropped when the buffer filled up: token counts, reasoning and
+    /// tool details, which the rollout's items hold anyway.
+    pub dropped_events: u64,
+    /// Other items dropped when the buffer filled up, messages among them,
+    /// once there were no events left to drop.
+    pub dropped_items: u64,
+}
+
+/// What [`Op::Pause`] does with a turn that is running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMode {
+    /// Let the turn run to completion, then pause.
+    #[default]
+    FinishTurn,
+    /// Abort the turn as an interrupt would, then pause.
+    AbortTurn,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ConversationPausedEvent {
+    pub mode: PauseMode,
+    /// Whether a turn was running when the pause was requested.
+    pub had_running_turn: bool,
+}
+
+/// The policies safe mode replaces.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SafeModePolicies {
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub tool_policy: ToolPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SafeModeChangedEvent {
+    /// Whether safe mode is now on.
+    pub enabled: bool,
+    /// The policies in effect until the change.
+    pub before: SafeModePolicies,
+    /// The policies in effect from the next turn on.
+    pub after: SafeModePolicies,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EffortEscalatedEvent {
+    /// The effort of the turn that crossed the threshold.
+    pub from: ReasoningEffortConfig,
+    /// The effort of the turns that follow.
+    pub to: ReasoningEffortConfig,
+    /// The signals that crossed the threshold, such as `turn failed`.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EffortDeescalatedEvent {
+    pub from: ReasoningEffortConfig,
+    pub to: ReasoningEffortConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct QueuedOfflineEvent {
+    /// The id the input is queued under in the rollout.
+    pub id: String,
+    /// How many inputs are queued, this one included.
+    pub queued: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct StructuredOutputEvent {
+    pub output: Value,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolTimedOutEvent {
+    pub call_id: String,
+    /// The timeout that expired.
+    #[ts(type = "string")]
+    pub after: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnDeadlineExceededEvent {
+    /// The wall-clock budget of the turn.
+    #[ts(type = "string")]
+    pub budget: Duration,
+    /// How long the turn ran, including stopping what was running.
+    #[ts(type = "string")]
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnRejectedEvent {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct InputRejectedEvent {
+    /// Why the input was rejected, for display.
+    pub reason: String,
+    /// Name of the sensitive input rule that matched, if one did.
+    pub rule: Option<String>,
+    pub span: Option<InputSpan>,
+}
+
+/// A byte range within the text of one submitted input item.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct InputSpan {
+    /// Index of the item in the submitted `items`.
+    pub item_index: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointCreatedEvent {
+    pub id: CheckpointId,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CheckpointRolledBackEvent {
+    pub id: CheckpointId,
+    /// Checkpoints recorded after `id` that are no longer reachable.
+    pub invalidated: Vec<CheckpointId>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ItemPinnedEvent {
+    pub item: ItemRef,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ItemUnpinnedEvent {
+    pub item: ItemRef,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct UndoCompletedEvent {
     pub success: bool,
@@ -1646,6 +3019,38 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct TurnDiffSummaryEvent {
+    /// Changed files, sorted by path.
+    pub files: Vec<TurnFileChange>,
+    /// Lines added across all files.
+    pub additions: u64,
+    /// Lines deleted across all files.
+    pub deletions: u64,
+}
+
+/// A file whose contents differ between the start and the end of a turn.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct TurnFileChange {
+    /// Path of the file at the end of the turn.
+    pub path: PathBuf,
+    pub kind: TurnFileChangeKind,
+    /// Lines added; zero for binary files.
+    pub additions: u64,
+    /// Lines deleted; zero for binary files.
+    pub deletions: u64,
+    /// True when the file is not under the turn's working directory.
+    pub outside_cwd: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnFileChangeKind {
+    Added,
+    Deleted,
+    Modified,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -1766,11 +3171,94 @@ pub struct SkillsListEntry {
     pub errors: Vec<SkillErrorInfo>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SkillSelection {
+    /// Names of the skills offered to the model, in its instructions or as
+    /// tools.
+    pub included: Vec<String>,
+    pub excluded: Vec<ExcludedSkill>,
+    /// How the included skills were offered.
+    #[serde(default)]
+    pub mode: SkillsMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SkillToolCallEvent {
+    /// Identifier for the originating tool call.
+    pub call_id: String,
+    /// Name of the skill, as declared in its `SKILL.md`.
+    pub name: String,
+    /// Arguments the model passed, by parameter name.
+    pub arguments: BTreeMap<String, String>,
+}
+
+/// Which skills a user turn could have drawn on, and which it used.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SkillUsageEvent {
+    pub turn_id: TurnId,
+    /// Skills in the model's context for the turn: listed in its
+    /// instructions, offered as tools, or injected for the user. Being
+    /// available says nothing about whether a skill shaped the answer.
+    pub available: Vec<String>,
+    /// Skills the turn explicitly invoked, through their tool or by the user
+    /// mentioning them, in order of first use.
+    pub invoked: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ExcludedSkill {
+    pub name: String,
+    /// Why the skill was left out, for display.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PendingInputsRestoredEvent {
+    pub inputs: Vec<PendingInput>,
+    /// Whether the inputs are submitted again, see
+    /// `Config::replay_pending_inputs`.
+    pub replayed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct InterruptedTurnRecoveredEvent {
+    pub turn_id: TurnId,
+    /// The answer up to its last recorded sentence.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ModelListRefreshedEvent {
+    /// Slugs of the models now available.
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ModelDeprecatedEvent {
+    /// Slug of the removed model.
+    pub model: String,
+    /// The default model of the new list, to switch to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub suggested: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct InstructionsReloadedEvent {
+    /// Instruction files the new instructions were read from.
+    pub sources: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct SessionConfiguredEvent {
     /// Name left as session_id instead of conversation_id for backwards compatibility.
     pub session_id: ConversationId,
 
+    /// Version of the event wire format, see [`crate::schema::PROTOCOL_VERSION`].
+    /// Absent (`0`) when produced by builds that predate versioning.
+    #[serde(default)]
+    pub protocol_version: u32,
+
     /// Tell the client what model is being queried.
     pub model: String,
 
@@ -1802,6 +3290,84 @@ pub struct SessionConfiguredEvent {
     pub initial_messages: Option<Vec<EventMsg>>,
 
     pub rollout_path: PathBuf,
+
+    /// Number of items in the resumed or forked rollout that were written by
+    /// a newer version and are not shown to the model.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unknown_rollout_items: usize,
+
+    /// Session profile the conversation was started with, also for resumed
+    /// and forked conversations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_profile: Option<String>,
+
+    /// Summary of the history a resumed or forked session starts from, for
+    /// UIs that show it instead of rendering `initial_messages`. `None` for
+    /// new sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed: Option<ResumedSummary>,
+}
+
+/// Where the history of a session that did not start empty came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOrigin {
+    /// The conversation's own rollout, continued.
+    Resumed,
+    /// A copy of another conversation's history, possibly cut at a user
+    /// message, in a new rollout.
+    Forked,
+}
+
+/// Compact account of a resumed or forked history, see
+/// [`SessionConfiguredEvent::resumed`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ResumedSummary {
+    pub origin: HistoryOrigin,
+    /// Turns in the history, counted by user message.
+    pub turns: usize,
+    /// Start of the last user message, on a single line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_user_message: Option<String>,
+    /// When the resumed rollout was last written, in RFC 3339. Not known for
+    /// forked histories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<String>,
+    /// Whether earlier parts of the conversation were compacted or rolled
+    /// back, so the model no longer sees everything the history records.
+    pub truncated: bool,
+    /// Problems the UI may want to mention, such as items written by a newer
+    /// version or redacted inputs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// How much of a resumed history made it into the prompt. Not reported
+    /// for forked histories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim: Option<ResumeTrimReport>,
+}
+
+/// How much of a resumed history fits the model's context window. Turns are
+/// counted by user message and dropped oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ResumeTrimReport {
+    /// Turns in the prompt.
+    pub turns_restored: usize,
+    /// Turns left out of the prompt; they stay in the rollout.
+    pub turns_dropped: usize,
+    /// Whether the prompt holds a compacted summary of earlier turns.
+    pub compacted: bool,
+    /// Context window of the model, when known. Nothing is dropped without
+    /// one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_tokens: Option<i64>,
+    /// Estimated size of the prompt built from the restored turns.
+    pub estimated_prompt_tokens: i64,
+}
+
+// `skip_serializing_if` passes the field by reference.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(count: &usize) -> bool {
+    *count == 0
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -1822,6 +3388,10 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants future commands matching
+    /// `rule` to run without approval in this project, across sessions.
+    ApprovedForProject { rule: ProjectApprovalRule },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -1859,6 +3429,12 @@ pub struct Chunk {
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnAbortedEvent {
     pub reason: TurnAbortReason,
+    /// Assistant messages the turn recorded before it was aborted.
+    #[serde(default)]
+    pub message_count: u32,
+    /// Tool calls the turn recorded before it was aborted.
+    #[serde(default)]
+    pub tool_call_count: u32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
@@ -1869,6 +3445,60 @@ pub enum TurnAbortReason {
     ReviewEnded,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEndReason {
+    /// A client submitted [`Op::Shutdown`].
+    Shutdown,
+    /// The conversation manager shut all of its sessions down.
+    ManagerShutdown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SessionEndedEvent {
+    pub reason: SessionEndReason,
+    pub stats: SessionStats,
+}
+
+/// How the tools of a turn were adjusted to the provider's
+/// [`ToolCapabilities`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolDowngradeReport {
+    /// Tools whose parameter schemas lost keywords the provider rejects.
+    pub simplified: Vec<ToolSchemaDowngrade>,
+    /// Whether tools that may run in parallel were made to run one at a time.
+    pub parallel_calls_serialized: bool,
+    /// Tools left out to stay within the provider's tool limit.
+    pub pruned: Vec<String>,
+}
+
+impl ToolDowngradeReport {
+    pub fn is_empty(&self) -> bool {
+        self.simplified.is_empty() && !self.parallel_calls_serialized && self.pruned.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolSchemaDowngrade {
+    pub tool: String,
+    /// Keywords removed from the schema, sorted.
+    pub removed: Vec<SchemaKeyword>,
+}
+
+/// Totals of a session, taken from the events it emitted.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SessionStats {
+    /// Turns that ran to completion.
+    pub turns: u64,
+    /// Tokens used by the session, as last reported by `token_count`.
+    pub token_usage: TokenUsage,
+    /// Time from the start of the session to its end.
+    pub duration_ms: u64,
+    /// Files changed by the session's turns, sorted.
+    pub files_changed: Vec<PathBuf>,
+    pub rollout_path: Option<PathBuf>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1934,6 +3564,7 @@ mod tests {
             id: "1234".to_string(),
             msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
                 session_id: conversation_id,
+                protocol_version: PROTOCOL_VERSION,
                 model: "codex-mini-latest".to_string(),
                 model_provider_id: "openai".to_string(),
                 approval_policy: AskForApproval::Never,
@@ -1944,7 +3575,11 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: rollout_file.path().to_path_buf(),
+                unknown_rollout_items: 0,
+                session_profile: None,
+                resumed: None,
             }),
+            correlation_id: None,
         };
 
         let expected = json!({
@@ -1952,6 +3587,7 @@ mod tests {
             "msg": {
                 "type": "session_configured",
                 "session_id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "protocol_version": PROTOCOL_VERSION,
                 "model": "codex-mini-latest",
                 "model_provider_id": "openai",
                 "approval_policy": "never",
@@ -1997,6 +3633,7 @@ mod tests {
                     error: "boom".to_string(),
                 },
             }),
+            correlation_id: None,
         };
 
         let value = serde_json::to_value(&event)?;
@@ -2019,6 +3656,7 @@ mod tests {
                 }],
                 cancelled: vec!["c".to_string()],
             }),
+            correlation_id: None,
         };
 
         let value = serde_json::to_value(&event)?;
@@ -2029,4 +3667,82 @@ mod tests {
         assert_eq!(value["msg"]["cancelled"][0], "c");
         Ok(())
     }
+
+    #[test]
+    fn unknown_rollout_items_round_trip_verbatim() -> Result<()> {
+        let lines = [
+            json!({
+                "timestamp": "2030-01-01T00:00:00.000Z",
+                "type": "hologram",
+                "payload": {"frames": 3},
+                "checksum": "abc",
+            }),
+            json!({
+                "timestamp": "2030-01-01T00:00:01.000Z",
+                "type": "event_msg",
+                "payload": {"type": "teleport_started", "destination": "mars"},
+            }),
+        ];
+        for line in lines {
+            let parsed: RolloutLine = serde_json::from_value(line.clone())?;
+            assert!(matches!(parsed.item, RolloutItem::Unknown { .. }));
+            assert_eq!(serde_json::to_value(&parsed)?, line);
+        }
+
+        let known: RolloutLine = serde_json::from_value(json!({
+            "timestamp": "2030-01-01T00:00:02.000Z",
+            "type": "event_msg",
+            "payload": {"type": "shutdown_complete"},
+        }))?;
+        assert!(matches!(
+            known.item,
+            RolloutItem::EventMsg(EventMsg::ShutdownComplete)
+        ));
+
+        // Without a `type` tag the line is not a rollout item at all.
+        assert!(
+            serde_json::from_value::<RolloutLine>(json!({
+                "timestamp": "2030-01-01T00:00:03.000Z",
+                "payload": {},
+            }))
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pending_inputs_skip_consumed_ones() -> Result<()> {
+        let queued = |id: &str| {
+            RolloutItem::PendingInput(PendingInputItem::Queued(PendingInput {
+                id: id.to_string(),
+                items: vec![UserInput::Text {
+                    text: format!("input {id}"),
+                }],
+            }))
+        };
+        let consumed =
+            |id: &str| RolloutItem::PendingInput(PendingInputItem::Consumed { id: id.to_string() });
+        // A marker can land before the input it consumes.
+        let history = vec![queued("a"), consumed("c"), queued("b"), queued("c")];
+        let resumed = InitialHistory::Resumed(ResumedHistory {
+            conversation_id: ConversationId::new(),
+            history: history.clone(),
+            rollout_path: PathBuf::from("rollout.jsonl"),
+            parse_errors: Vec::new(),
+        });
+        let ids: Vec<String> = resumed
+            .pending_inputs()
+            .into_iter()
+            .map(|input| input.id)
+            .collect();
+        assert_eq!(ids, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(InitialHistory::Forked(history).pending_inputs(), Vec::new());
+
+        let line = serde_json::to_value(&consumed("a"))?;
+        assert_eq!(
+            line,
+            json!({"type": "pending_input", "payload": {"status": "consumed", "id": "a"}})
+        );
+        Ok(())
+    }
 }
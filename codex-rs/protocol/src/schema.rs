@@ -0,0 +1,29 @@
+//! Versioned wire contract for the events core emits.
+//!
+//! Clients that deserialize [`Event`] JSON can compare
+//! [`SessionConfiguredEvent::protocol_version`] against the version they were
+//! built for. Adding an [`EventMsg`] variant is backwards compatible; changing
+//! the serialized shape of an existing one requires bumping
+//! [`PROTOCOL_VERSION`].
+//!
+//! [`EventMsg`]: crate::protocol::EventMsg
+//! [`SessionConfiguredEvent::protocol_version`]: crate::protocol::SessionConfiguredEvent::protocol_version
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::protocol::Event;
+
+/// Version of the [`Event`] wire format.
+pub const PROTOCOL_VERSION: u32 = 12;
+
+/// JSON Schema of [`Event`], with [`PROTOCOL_VERSION`] recorded under the
+/// `x-protocol-version` extension.
+pub fn events_schema() -> RootSchema {
+    let mut schema = schema_for!(Event);
+    schema
+        .schema
+        .extensions
+        .insert("x-protocol-version".to_string(), PROTOCOL_VERSION.into());
+    schema
+}
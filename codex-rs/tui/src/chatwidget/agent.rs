@@ -28,6 +28,7 @@ pub(crate) fn spawn_agent(
             conversation_id: _,
             conversation,
             session_configured,
+            ..
         } = match server.new_conversation(config).await {
             Ok(v) => v,
             #[allow(clippy::print_stderr)]
@@ -37,6 +38,7 @@ pub(crate) fn spawn_agent(
                 app_event_tx_clone.send(AppEvent::CodexEvent(Event {
                     id: "".to_string(),
                     msg: EventMsg::Error(err.to_error_event(None)),
+                    correlation_id: None,
                 }));
                 app_event_tx_clone.send(AppEvent::ExitRequest);
                 tracing::error!("failed to initialize codex: {err}");
@@ -49,6 +51,7 @@ pub(crate) fn spawn_agent(
             // The `id` does not matter for rendering, so we can use a fake value.
             id: "".to_string(),
             msg: codex_core::protocol::EventMsg::SessionConfigured(session_configured),
+            correlation_id: None,
         };
         app_event_tx_clone.send(AppEvent::CodexEvent(ev));
 
@@ -86,6 +89,7 @@ pub(crate) fn spawn_agent_from_existing(
         let ev = codex_core::protocol::Event {
             id: "".to_string(),
             msg: codex_core::protocol::EventMsg::SessionConfigured(session_configured),
+            correlation_id: None,
         };
         app_event_tx_clone.send(AppEvent::CodexEvent(ev));
 
@@ -1610,6 +1610,9 @@ impl App {
                                         model: None,
                                         effort: None,
                                         summary: None,
+                                        execution_mode: None,
+                                        sampling: None,
+                                        offline: None,
                                     },
                                 ));
                                 self.app_event_tx
@@ -2075,6 +2078,7 @@ mod tests {
     use codex_core::protocol::AskForApproval;
     use codex_core::protocol::Event;
     use codex_core::protocol::EventMsg;
+    use codex_core::protocol::PROTOCOL_VERSION;
     use codex_core::protocol::SandboxPolicy;
     use codex_core::protocol::SessionConfiguredEvent;
     use codex_protocol::ConversationId;
@@ -2309,6 +2313,7 @@ mod tests {
         let make_header = |is_first| {
             let event = SessionConfiguredEvent {
                 session_id: ConversationId::new(),
+                protocol_version: PROTOCOL_VERSION,
                 model: "gpt-test".to_string(),
                 model_provider_id: "test-provider".to_string(),
                 approval_policy: AskForApproval::Never,
@@ -2319,6 +2324,9 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: PathBuf::new(),
+                unknown_rollout_items: 0,
+                session_profile: None,
+                resumed: None,
             };
             Arc::new(new_session_info(
                 app.chat_widget.config_ref(),
@@ -2604,6 +2612,7 @@ mod tests {
         let conversation_id = ConversationId::new();
         let event = SessionConfiguredEvent {
             session_id: conversation_id,
+            protocol_version: PROTOCOL_VERSION,
             model: "gpt-test".to_string(),
             model_provider_id: "test-provider".to_string(),
             approval_policy: AskForApproval::Never,
@@ -2614,11 +2623,15 @@ mod tests {
             history_entry_count: 0,
             initial_messages: None,
             rollout_path: PathBuf::new(),
+            unknown_rollout_items: 0,
+            session_profile: None,
+            resumed: None,
         };
 
         app.chat_widget.handle_codex_event(Event {
             id: String::new(),
             msg: EventMsg::SessionConfigured(event),
+            correlation_id: None,
         });
 
         while app_event_rx.try_recv().is_ok() {}
@@ -1663,6 +1663,7 @@ impl ChatWidget {
                         reason: None,
                         grant_root: Some(PathBuf::from("/tmp")),
                     }),
+                    correlation_id: None,
                 }));
             }
         }
@@ -1764,7 +1765,10 @@ impl ChatWidget {
         }
 
         self.codex_op_tx
-            .send(Op::UserInput { items })
+            .send(Op::UserInput {
+                items,
+                allow_sensitive: false,
+            })
             .unwrap_or_else(|e| {
                 tracing::error!("failed to send message: {e}");
             });
@@ -1801,7 +1805,7 @@ impl ChatWidget {
     }
 
     pub(crate) fn handle_codex_event(&mut self, event: Event) {
-        let Event { id, msg } = event;
+        let Event { id, msg, .. } = event;
         self.dispatch_event_msg(Some(id), msg, false);
     }
 
@@ -1843,15 +1847,26 @@ impl ChatWidget {
             }
             EventMsg::AgentReasoningSectionBreak(_) => self.on_reasoning_section_break(),
             EventMsg::TaskStarted(_) => self.on_task_started(),
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
-                self.on_task_complete(last_agent_message)
-            }
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => self.on_task_complete(last_agent_message),
             EventMsg::TokenCount(ev) => {
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
             }
-            EventMsg::Warning(WarningEvent { message }) => self.on_warning(message),
+            EventMsg::Warning(WarningEvent { message, .. }) => self.on_warning(message),
+            EventMsg::InterruptedTurnRecovered(ev) => {
+                self.on_agent_message(ev.text);
+                self.on_warning(
+                    "Turn interrupted: the session stopped before this answer was complete.",
+                );
+            }
+            EventMsg::AuthRefreshFailed(ev) => self.on_warning(ev.message),
             EventMsg::Error(ErrorEvent { message, .. }) => self.on_error(message),
+            EventMsg::TurnRejected(ev) => self.on_error(format!("Turn rejected: {}", ev.reason)),
+            EventMsg::InputRejected(ev) => {
+                self.on_error(format!("Message not sent: {}.", ev.reason))
+            }
             EventMsg::McpStartupUpdate(ev) => self.on_mcp_startup_update(ev),
             EventMsg::McpStartupComplete(ev) => self.on_mcp_startup_complete(ev),
             EventMsg::TurnAborted(ev) => match ev.reason {
@@ -1900,6 +1915,7 @@ impl ChatWidget {
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
+            EventMsg::WorkspaceChanged(ev) => self.add_info_message(ev.message, None),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
@@ -1925,7 +1941,43 @@ impl ChatWidget {
             | EventMsg::ItemCompleted(_)
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
-            | EventMsg::ReasoningRawContentDelta(_) => {}
+            | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::Retrying(_)
+            | EventMsg::EffortEscalated(_)
+            | EventMsg::EffortDeescalated(_)
+            | EventMsg::SkillUsage(_)
+            | EventMsg::PersistenceDegraded(_)
+            | EventMsg::PersistenceRecovered(_)
+            | EventMsg::ForkedContextStale(_)
+            | EventMsg::ModelDeprecated(_)
+            | EventMsg::SafeModeChanged(_)
+            | EventMsg::ContextRecovered(_)
+            | EventMsg::QueuedOffline(_)
+            | EventMsg::ConversationPaused(_)
+            | EventMsg::ConversationResumed
+            | EventMsg::SkillToolCall(_)
+            | EventMsg::Forked(_)
+            | EventMsg::HistoryTrimmedOnResume(_)
+            | EventMsg::TurnDeadlineExceeded(_)
+            | EventMsg::ItemPinned(_)
+            | EventMsg::ItemUnpinned(_)
+            | EventMsg::ToolTimedOut(_)
+            | EventMsg::ToolsDowngraded(_)
+            | EventMsg::SessionEnded(_)
+            | EventMsg::InstructionsReloaded(_)
+            | EventMsg::ModelListRefreshed(_)
+            | EventMsg::PendingInputsRestored(_)
+            | EventMsg::SkillsSelected(_)
+            | EventMsg::TurnDiffSummary(_)
+            | EventMsg::ContextUsageUpdated(_)
+            | EventMsg::ToolPolicyUpdated(_)
+            | EventMsg::PlannedToolCall(_)
+            | EventMsg::StructuredOutput(_)
+            | EventMsg::ConversationLinked(_)
+            | EventMsg::Heartbeat(_)
+            | EventMsg::ProviderEndpointSwitched(_)
+            | EventMsg::CheckpointCreated(_)
+            | EventMsg::CheckpointRolledBack(_) => {}
         }
     }
 
@@ -2150,6 +2202,9 @@ impl ChatWidget {
                 model: Some(switch_model.clone()),
                 effort: Some(Some(default_effort)),
                 summary: None,
+                execution_mode: None,
+                sampling: None,
+                offline: None,
             }));
             tx.send(AppEvent::UpdateModel(switch_model.clone()));
             tx.send(AppEvent::UpdateReasoningEffort(Some(default_effort)));
@@ -2370,6 +2425,9 @@ impl ChatWidget {
                 model: Some(model_for_action.clone()),
                 effort: Some(effort_for_action),
                 summary: None,
+                execution_mode: None,
+                sampling: None,
+                offline: None,
             }));
             tx.send(AppEvent::UpdateModel(model_for_action.clone()));
             tx.send(AppEvent::UpdateReasoningEffort(effort_for_action));
@@ -2541,6 +2599,9 @@ impl ChatWidget {
                 model: Some(model.clone()),
                 effort: Some(effort),
                 summary: None,
+                execution_mode: None,
+                sampling: None,
+                offline: None,
             }));
         self.app_event_tx.send(AppEvent::UpdateModel(model.clone()));
         self.app_event_tx
@@ -2648,6 +2709,9 @@ impl ChatWidget {
                 model: None,
                 effort: None,
                 summary: None,
+                execution_mode: None,
+                sampling: None,
+                offline: None,
             }));
             tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
             tx.send(AppEvent::UpdateSandboxPolicy(sandbox_clone));
@@ -3556,6 +3620,8 @@ fn skills_for_cwd(cwd: &Path, skills_entries: &[SkillsListEntry]) -> Vec<SkillMe
                     short_description: skill.short_description.clone(),
                     path: skill.path.clone(),
                     scope: skill.scope,
+                    applies_to: Default::default(),
+                    parameters: Vec::new(),
                 })
                 .collect()
         })
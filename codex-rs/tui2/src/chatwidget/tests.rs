@@ -44,6 +44,7 @@ use codex_core::protocol::TokenUsageInfo;
 use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UndoStartedEvent;
 use codex_core::protocol::ViewImageToolCallEvent;
+use codex_core::protocol::WarningCode;
 use codex_core::protocol::WarningEvent;
 use codex_protocol::ConversationId;
 use codex_protocol::account::PlanType;
@@ -103,6 +104,7 @@ async fn resumed_initial_messages_render_history() {
     let rollout_file = NamedTempFile::new().unwrap();
     let configured = codex_core::protocol::SessionConfiguredEvent {
         session_id: conversation_id,
+        protocol_version: codex_core::protocol::PROTOCOL_VERSION,
         model: "test-model".to_string(),
         model_provider_id: "test-provider".to_string(),
         approval_policy: AskForApproval::Never,
@@ -121,11 +123,15 @@ async fn resumed_initial_messages_render_history() {
             }),
         ]),
         rollout_path: rollout_file.path().to_path_buf(),
+        unknown_rollout_items: 0,
+        session_profile: None,
+        resumed: None,
     };
 
     chat.handle_codex_event(Event {
         id: "initial".into(),
         msg: EventMsg::SessionConfigured(configured),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -163,6 +169,7 @@ async fn entered_review_mode_uses_request_hint() {
             },
             user_facing_hint: Some("feature branch".to_string()),
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -182,6 +189,7 @@ async fn entered_review_mode_defaults_to_current_changes_banner() {
             target: ReviewTarget::UncommittedChanges,
             user_facing_hint: None,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -205,6 +213,7 @@ async fn review_restores_context_window_indicator() {
             info: Some(make_token_info(pre_review_tokens, context_window)),
             rate_limits: None,
         }),
+        correlation_id: None,
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
 
@@ -216,6 +225,7 @@ async fn review_restores_context_window_indicator() {
             },
             user_facing_hint: Some("feature branch".to_string()),
         }),
+        correlation_id: None,
     });
 
     chat.handle_codex_event(Event {
@@ -224,6 +234,7 @@ async fn review_restores_context_window_indicator() {
             info: Some(make_token_info(review_tokens, context_window)),
             rate_limits: None,
         }),
+        correlation_id: None,
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(97));
 
@@ -232,6 +243,7 @@ async fn review_restores_context_window_indicator() {
         msg: EventMsg::ExitedReviewMode(ExitedReviewModeEvent {
             review_output: None,
         }),
+        correlation_id: None,
     });
     let _ = drain_insert_history(&mut rx);
 
@@ -253,6 +265,7 @@ async fn token_count_none_resets_context_indicator() {
             info: Some(make_token_info(pre_compact_tokens, context_window)),
             rate_limits: None,
         }),
+        correlation_id: None,
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
 
@@ -262,6 +275,7 @@ async fn token_count_none_resets_context_indicator() {
             info: None,
             rate_limits: None,
         }),
+        correlation_id: None,
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), None);
 }
@@ -292,6 +306,7 @@ async fn context_indicator_shows_used_tokens_when_window_unknown() {
             info: Some(token_info),
             rate_limits: None,
         }),
+        correlation_id: None,
     });
 
     assert_eq!(chat.bottom_pane.context_window_percent(), None);
@@ -724,6 +739,7 @@ async fn exec_approval_emits_proposed_command_and_decision_history() {
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
         msg: EventMsg::ExecApprovalRequest(ev),
+        correlation_id: None,
     });
 
     let proposed_cells = drain_insert_history(&mut rx);
@@ -768,6 +784,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
         msg: EventMsg::ExecApprovalRequest(ev_multi),
+        correlation_id: None,
     });
     let proposed_multi = drain_insert_history(&mut rx);
     assert!(
@@ -818,6 +835,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
         msg: EventMsg::ExecApprovalRequest(ev_long),
+        correlation_id: None,
     });
     let proposed_long = drain_insert_history(&mut rx);
     assert!(
@@ -860,6 +878,7 @@ fn begin_exec_with_source(
     chat.handle_codex_event(Event {
         id: call_id.to_string(),
         msg: EventMsg::ExecCommandBegin(event.clone()),
+        correlation_id: None,
     });
     event
 }
@@ -907,7 +926,9 @@ fn end_exec(
             exit_code,
             duration: std::time::Duration::from_millis(5),
             formatted_output: aggregated,
+            output_blob: None,
         }),
+        correlation_id: None,
     });
 }
 
@@ -1162,7 +1183,9 @@ async fn exec_end_without_begin_uses_event_command() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
             formatted_output: "done".to_string(),
+            output_blob: None,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -1335,6 +1358,7 @@ async fn undo_success_events_render_info_messages() {
         msg: EventMsg::UndoStarted(UndoStartedEvent {
             message: Some("Undo requested for the last turn...".to_string()),
         }),
+        correlation_id: None,
     });
     assert!(
         chat.bottom_pane.status_indicator_visible(),
@@ -1347,6 +1371,7 @@ async fn undo_success_events_render_info_messages() {
             success: true,
             message: None,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -1370,6 +1395,7 @@ async fn undo_failure_events_render_error_message() {
     chat.handle_codex_event(Event {
         id: "turn-2".to_string(),
         msg: EventMsg::UndoStarted(UndoStartedEvent { message: None }),
+        correlation_id: None,
     });
     assert!(
         chat.bottom_pane.status_indicator_visible(),
@@ -1382,6 +1408,7 @@ async fn undo_failure_events_render_error_message() {
             success: false,
             message: Some("Failed to restore workspace state.".to_string()),
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -1405,6 +1432,7 @@ async fn undo_started_hides_interrupt_hint() {
     chat.handle_codex_event(Event {
         id: "turn-hint".to_string(),
         msg: EventMsg::UndoStarted(UndoStartedEvent { message: None }),
+        correlation_id: None,
     });
 
     let status = chat
@@ -1531,6 +1559,7 @@ async fn view_image_tool_call_adds_history_cell() {
             call_id: "call-image".into(),
             path: image_path,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -1554,7 +1583,10 @@ async fn interrupt_exec_marks_failed_snapshot() {
         id: "call-int".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            message_count: 0,
+            tool_call_count: 0,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -1579,7 +1611,9 @@ async fn interrupted_turn_error_message_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
 
     // Abort the turn (like pressing Esc) and drain inserted history.
@@ -1587,7 +1621,10 @@ async fn interrupted_turn_error_message_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            message_count: 0,
+            tool_call_count: 0,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -2057,6 +2094,7 @@ async fn approval_modal_exec_snapshot() {
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
         msg: EventMsg::ExecApprovalRequest(ev),
+        correlation_id: None,
     });
     // Render to a fixed-size test terminal and snapshot.
     // Call desired_height first and use that exact height for rendering.
@@ -2108,6 +2146,7 @@ async fn approval_modal_exec_without_reason_snapshot() {
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
         msg: EventMsg::ExecApprovalRequest(ev),
+        correlation_id: None,
     });
 
     let width = 100;
@@ -2148,6 +2187,7 @@ async fn approval_modal_patch_snapshot() {
     chat.handle_codex_event(Event {
         id: "sub-approve-patch".into(),
         msg: EventMsg::ApplyPatchApprovalRequest(ev),
+        correlation_id: None,
     });
 
     // Render at the widget's desired height and snapshot.
@@ -2183,7 +2223,10 @@ async fn interrupt_restores_queued_messages_into_composer() {
         id: "turn-1".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            message_count: 0,
+            tool_call_count: 0,
         }),
+        correlation_id: None,
     });
 
     // Composer should now contain the queued messages joined by newlines, in order.
@@ -2221,7 +2264,10 @@ async fn interrupt_prepends_queued_messages_before_existing_composer_text() {
         id: "turn-1".into(),
         msg: EventMsg::TurnAborted(codex_core::protocol::TurnAbortedEvent {
             reason: TurnAbortReason::Interrupted,
+            message_count: 0,
+            tool_call_count: 0,
         }),
+        correlation_id: None,
     });
 
     assert_eq!(
@@ -2266,13 +2312,16 @@ async fn ui_snapshots_small_heights_task_running() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "task-1".into(),
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "**Thinking**".into(),
         }),
+        correlation_id: None,
     });
     for h in [1u16, 2, 3] {
         let name = format!("chat_small_running_h{h}");
@@ -2297,7 +2346,9 @@ async fn status_widget_and_approval_modal_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     // Provide a deterministic header for the status line.
     chat.handle_codex_event(Event {
@@ -2305,6 +2356,7 @@ async fn status_widget_and_approval_modal_snapshot() {
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "**Analyzing**".into(),
         }),
+        correlation_id: None,
     });
 
     // Now show an approval modal (e.g. exec approval).
@@ -2325,6 +2377,7 @@ async fn status_widget_and_approval_modal_snapshot() {
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
         msg: EventMsg::ExecApprovalRequest(ev),
+        correlation_id: None,
     });
 
     // Render at the widget's desired height and snapshot.
@@ -2349,7 +2402,9 @@ async fn status_widget_active_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     // Provide a deterministic header via a bold reasoning chunk.
     chat.handle_codex_event(Event {
@@ -2357,6 +2412,7 @@ async fn status_widget_active_snapshot() {
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "**Analyzing**".into(),
         }),
+        correlation_id: None,
     });
     // Render and snapshot.
     let height = chat.desired_height(80);
@@ -2379,6 +2435,7 @@ async fn mcp_startup_header_booting_snapshot() {
             server: "alpha".into(),
             status: McpStartupStatus::Starting,
         }),
+        correlation_id: None,
     });
 
     let height = chat.desired_height(80);
@@ -2399,6 +2456,7 @@ async fn background_event_updates_status_header() {
         msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
             message: "Waiting for `vim`".to_string(),
         }),
+        correlation_id: None,
     });
 
     assert!(chat.bottom_pane.status_indicator_visible());
@@ -2428,6 +2486,7 @@ async fn apply_patch_events_emit_history_cells() {
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::ApplyPatchApprovalRequest(ev),
+        correlation_id: None,
     });
     let cells = drain_insert_history(&mut rx);
     assert!(
@@ -2468,6 +2527,7 @@ async fn apply_patch_events_emit_history_cells() {
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::PatchApplyBegin(begin),
+        correlation_id: None,
     });
     let cells = drain_insert_history(&mut rx);
     assert!(!cells.is_empty(), "expected apply block cell to be sent");
@@ -2496,6 +2556,7 @@ async fn apply_patch_events_emit_history_cells() {
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::PatchApplyEnd(end),
+        correlation_id: None,
     });
     let cells = drain_insert_history(&mut rx);
     assert!(
@@ -2524,6 +2585,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
             reason: None,
             grant_root: None,
         }),
+        correlation_id: None,
     });
     drain_insert_history(&mut rx);
 
@@ -2542,6 +2604,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
             auto_approved: false,
             changes: apply_changes,
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -2573,6 +2636,7 @@ async fn apply_patch_manual_flow_snapshot() {
             reason: Some("Manual review required".into()),
             grant_root: None,
         }),
+        correlation_id: None,
     });
     let history_before_apply = drain_insert_history(&mut rx);
     assert!(
@@ -2595,6 +2659,7 @@ async fn apply_patch_manual_flow_snapshot() {
             auto_approved: false,
             changes: apply_changes,
         }),
+        correlation_id: None,
     });
     let approved_lines = drain_insert_history(&mut rx)
         .pop()
@@ -2627,6 +2692,7 @@ async fn apply_patch_approval_sends_op_with_submission_id() {
     chat.handle_codex_event(Event {
         id: "sub-123".into(),
         msg: EventMsg::ApplyPatchApprovalRequest(ev),
+        correlation_id: None,
     });
 
     // Approve via key press 'y'
@@ -2664,6 +2730,7 @@ async fn apply_patch_full_flow_integration_like() {
             reason: None,
             grant_root: None,
         }),
+        correlation_id: None,
     });
 
     // 2) User approves via 'y' and App receives a CodexOp
@@ -2704,6 +2771,7 @@ async fn apply_patch_full_flow_integration_like() {
             auto_approved: false,
             changes: changes2,
         }),
+        correlation_id: None,
     });
     let mut end_changes = HashMap::new();
     end_changes.insert(
@@ -2720,6 +2788,7 @@ async fn apply_patch_full_flow_integration_like() {
             success: true,
             changes: end_changes,
         }),
+        correlation_id: None,
     });
 }
 
@@ -2744,6 +2813,7 @@ async fn apply_patch_untrusted_shows_approval_modal() {
             reason: None,
             grant_root: None,
         }),
+        correlation_id: None,
     });
 
     // Render and ensure the approval modal title is present
@@ -2793,6 +2863,7 @@ async fn apply_patch_request_shows_diff_summary() {
             reason: None,
             grant_root: None,
         }),
+        correlation_id: None,
     });
 
     // No history entries yet; the modal should contain the diff summary
@@ -2857,6 +2928,7 @@ async fn plan_update_renders_history_cell() {
     chat.handle_codex_event(Event {
         id: "sub-1".into(),
         msg: EventMsg::PlanUpdate(update),
+        correlation_id: None,
     });
     let cells = drain_insert_history(&mut rx);
     assert!(!cells.is_empty(), "expected plan update cell to be sent");
@@ -2883,6 +2955,7 @@ async fn stream_error_updates_status_indicator() {
             codex_error_info: Some(CodexErrorInfo::Other),
             additional_details: Some(details.to_string()),
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -2903,9 +2976,11 @@ async fn warning_event_adds_warning_history_cell() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
     chat.handle_codex_event(Event {
         id: "sub-1".into(),
-        msg: EventMsg::Warning(WarningEvent {
-            message: "test warning message".to_string(),
-        }),
+        msg: EventMsg::Warning(WarningEvent::new(
+            WarningCode::Other,
+            "test warning message",
+        )),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -2924,7 +2999,9 @@ async fn stream_recovery_restores_previous_status_header() {
         id: "task".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     drain_insert_history(&mut rx);
     chat.handle_codex_event(Event {
@@ -2934,6 +3011,7 @@ async fn stream_recovery_restores_previous_status_header() {
             codex_error_info: Some(CodexErrorInfo::Other),
             additional_details: None,
         }),
+        correlation_id: None,
     });
     drain_insert_history(&mut rx);
     chat.handle_codex_event(Event {
@@ -2941,6 +3019,7 @@ async fn stream_recovery_restores_previous_status_header() {
         msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
             delta: "hello".to_string(),
         }),
+        correlation_id: None,
     });
 
     let status = chat
@@ -2961,7 +3040,9 @@ async fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
 
     // First finalized assistant message
@@ -2970,6 +3051,7 @@ async fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         msg: EventMsg::AgentMessage(AgentMessageEvent {
             message: "First message".into(),
         }),
+        correlation_id: None,
     });
 
     // Second finalized assistant message in the same turn
@@ -2978,6 +3060,7 @@ async fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         msg: EventMsg::AgentMessage(AgentMessageEvent {
             message: "Second message".into(),
         }),
+        correlation_id: None,
     });
 
     // End turn
@@ -2985,7 +3068,14 @@ async fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
+        correlation_id: None,
     });
 
     let cells = drain_insert_history(&mut rx);
@@ -3016,12 +3106,14 @@ async fn final_reasoning_then_message_without_deltas_are_rendered() {
         msg: EventMsg::AgentReasoning(AgentReasoningEvent {
             text: "I will first analyze the request.".into(),
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::AgentMessage(AgentMessageEvent {
             message: "Here is the result.".into(),
         }),
+        correlation_id: None,
     });
 
     // Drain history and snapshot the combined visible content.
@@ -3043,24 +3135,28 @@ async fn deltas_then_same_final_message_are_rendered_snapshot() {
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "I will ".into(),
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "first analyze the ".into(),
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "request.".into(),
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::AgentReasoning(AgentReasoningEvent {
             text: "request.".into(),
         }),
+        correlation_id: None,
     });
 
     // Then stream answer deltas, followed by the exact same final message.
@@ -3069,12 +3165,14 @@ async fn deltas_then_same_final_message_are_rendered_snapshot() {
         msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
             delta: "Here is the ".into(),
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "s1".into(),
         msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
             delta: "result.".into(),
         }),
+        correlation_id: None,
     });
 
     chat.handle_codex_event(Event {
@@ -3082,6 +3180,7 @@ async fn deltas_then_same_final_message_are_rendered_snapshot() {
         msg: EventMsg::AgentMessage(AgentMessageEvent {
             message: "Here is the result.".into(),
         }),
+        correlation_id: None,
     });
 
     // Snapshot the combined visible content to ensure we render as expected
@@ -3103,6 +3202,7 @@ async fn chatwidget_exec_and_status_layout_vt100_snapshot() {
     chat.handle_codex_event(Event {
         id: "t1".into(),
         msg: EventMsg::AgentMessage(AgentMessageEvent { message: "I’m going to search the repo for where “Change Approved” is rendered to update that view.".into() }),
+        correlation_id: None,
     });
 
     let command = vec!["bash".into(), "-lc".into(), "rg \"Change Approved\"".into()];
@@ -3131,6 +3231,7 @@ async fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             source: ExecCommandSource::Agent,
             interaction_input: None,
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "c1".into(),
@@ -3149,19 +3250,24 @@ async fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(16000),
             formatted_output: String::new(),
+            output_blob: None,
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     chat.handle_codex_event(Event {
         id: "t1".into(),
         msg: EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
             delta: "**Investigating rendering code**".into(),
         }),
+        correlation_id: None,
     });
     chat.bottom_pane
         .set_composer_text("Summarize recent commits".to_string());
@@ -3199,7 +3305,9 @@ async fn chatwidget_markdown_code_blocks_vt100_snapshot() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     // Build a vt100 visual from the history insertions only (no UI overlay)
     let width: u16 = 80;
@@ -3246,6 +3354,7 @@ printf 'fenced within fenced\n'
         chat.handle_codex_event(Event {
             id: "t1".into(),
             msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }),
+            correlation_id: None,
         });
         // Drive commit ticks and drain emitted history lines into the vt100 buffer.
         loop {
@@ -3270,7 +3379,14 @@ printf 'fenced within fenced\n'
         id: "t1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            structured_output: None,
+            final_message: None,
+            message_count: 0,
+            tool_call_count: 0,
+            error: None,
+            invoked_skills: Vec::new(),
         }),
+        correlation_id: None,
     });
     for lines in drain_insert_history(&mut rx) {
         crate::insert_history::insert_history_lines(&mut term, lines)
@@ -3287,7 +3403,9 @@ async fn chatwidget_tall() {
         id: "t1".into(),
         msg: EventMsg::TaskStarted(TaskStartedEvent {
             model_context_window: None,
+            turn_id: None,
         }),
+        correlation_id: None,
     });
     for i in 0..30 {
         chat.queue_user_message(format!("Hello, world! {i}").into());